@@ -0,0 +1,25 @@
+//! Main entrypoint for the `kona-check-config` binary.
+
+use anyhow::Result;
+use clap::Parser;
+use kona_host::{
+    check_config::{check_config, CheckConfigCli},
+    init_tracing_subscriber,
+};
+
+#[tokio::main(flavor = "multi_thread")]
+async fn main() -> Result<()> {
+    let cfg = CheckConfigCli::parse();
+    init_tracing_subscriber(cfg.v, None)?;
+
+    let checks = check_config(&cfg).await?;
+    checks.iter().for_each(|check| println!("{check}"));
+
+    // Exit with a non-zero status if the rollup config does not match the chains.
+    let mismatches = checks.iter().filter(|check| !check.matches).count();
+    if mismatches > 0 {
+        eprintln!("{mismatches} mismatches found in {}", cfg.rollup_config_path.display());
+        std::process::exit(1);
+    }
+    Ok(())
+}
@@ -0,0 +1,23 @@
+//! Main entrypoint for the `kona-diff` binary.
+
+use anyhow::Result;
+use clap::Parser;
+use kona_host::{
+    diff::{diff, DiffCli},
+    init_tracing_subscriber,
+};
+
+#[tokio::main(flavor = "multi_thread")]
+async fn main() -> Result<()> {
+    let cfg = DiffCli::parse();
+    init_tracing_subscriber(cfg.v, None)?;
+
+    let report = diff(&cfg).await?;
+    print!("{report}");
+
+    // Exit with a non-zero status if kona diverged from the rollup node.
+    if report.divergence.is_some() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
@@ -0,0 +1,19 @@
+//! Main entrypoint for the `kona-extract` binary.
+
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use kona_host::{
+    extract::{extract, ExtractCli},
+    init_tracing_subscriber,
+};
+
+#[tokio::main(flavor = "multi_thread")]
+async fn main() -> Result<()> {
+    let cfg = ExtractCli::parse();
+    init_tracing_subscriber(cfg.v, None)?;
+
+    let report = extract(&cfg).await?;
+    let json = serde_json::to_string_pretty(&report).map_err(|e| anyhow!(e))?;
+    println!("{json}");
+    Ok(())
+}
@@ -0,0 +1,18 @@
+//! Main entrypoint for the `kona-trace` binary.
+
+use anyhow::Result;
+use clap::Parser;
+use kona_host::{
+    init_tracing_subscriber,
+    trace::{trace, TraceCli},
+};
+
+#[tokio::main(flavor = "multi_thread")]
+async fn main() -> Result<()> {
+    let cfg = TraceCli::parse();
+    init_tracing_subscriber(cfg.v, None)?;
+
+    let report = trace(&cfg).await?;
+    print!("{report}");
+    Ok(())
+}
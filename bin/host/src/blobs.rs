@@ -1,10 +1,26 @@
 //! Contains an online implementation of the `BlobProvider` trait.
 
-use alloy_eips::eip4844::{BlobTransactionSidecarItem, IndexedBlobHash};
-use alloy_rpc_types_beacon::sidecar::{BeaconBlobBundle, BlobData};
-use kona_derive::errors::BlobProviderError;
+use crate::slots::{SimpleSlotDerivation, SlotDerivation};
+use alloy_eips::eip4844::{
+    deserialize_blob, kzg_to_versioned_hash, Blob, BlobTransactionSidecarItem, Bytes48,
+    IndexedBlobHash,
+};
+use alloy_primitives::B256;
+use alloy_rpc_types_beacon::{header::Header, sidecar::BlobData};
+use async_trait::async_trait;
+use kona_derive::{errors::BlobProviderError, traits::BlobProvider};
+use lru::LruCache;
 use maili_protocol::BlockInfo;
 use reqwest::Client;
+use std::{
+    fmt::Debug,
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+use tokio::sync::OnceCell;
 
 /// The config spec engine api method.
 const SPEC_METHOD: &str = "eth/v1/config/spec";
@@ -15,11 +31,14 @@ const GENESIS_METHOD: &str = "eth/v1/beacon/genesis";
 /// The blob sidecars engine api method prefix.
 const SIDECARS_METHOD_PREFIX: &str = "eth/v1/beacon/blob_sidecars";
 
+/// The default number of slots whose blob sidecars are cached by the [OnlineBlobProvider].
+pub const DEFAULT_SIDECAR_CACHE_SIZE: usize = 16;
+
 /// A reduced genesis data.
 #[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct ReducedGenesisData {
     /// The genesis time.
-    #[serde(rename = "genesis_time")]
+    #[serde(rename = "genesis_time", alias = "genesisTime")]
     #[serde(with = "alloy_serde::quantity")]
     pub genesis_time: u64,
 }
@@ -35,7 +54,7 @@ pub struct APIGenesisResponse {
 #[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct ReducedConfigData {
     /// The seconds per slot.
-    #[serde(rename = "SECONDS_PER_SLOT")]
+    #[serde(rename = "SECONDS_PER_SLOT", alias = "secondsPerSlot")]
     #[serde(with = "alloy_serde::quantity")]
     pub seconds_per_slot: u64,
 }
@@ -61,50 +80,248 @@ impl APIGenesisResponse {
     }
 }
 
-/// An online provider to fetch blob sidecars.
+/// A blob sidecar, as returned by the beacon node.
+///
+/// Unlike [BlobData], the field casing used by Nimbus and Lodestar is accepted and the block
+/// header and inclusion proof, which are not used by the host, may be omitted.
+#[derive(Debug, Clone, serde::Deserialize)]
+struct BeaconSidecar {
+    #[serde(with = "alloy_serde::quantity")]
+    index: u64,
+    #[serde(deserialize_with = "deserialize_blob")]
+    blob: Box<Blob>,
+    #[serde(alias = "kzgCommitment")]
+    kzg_commitment: Bytes48,
+    #[serde(alias = "kzgProof")]
+    kzg_proof: Bytes48,
+    #[serde(default, alias = "signedBlockHeader")]
+    signed_block_header: Header,
+    #[serde(default, alias = "kzgCommitmentInclusionProof")]
+    kzg_commitment_inclusion_proof: Vec<B256>,
+}
+
+impl From<BeaconSidecar> for BlobData {
+    fn from(sidecar: BeaconSidecar) -> Self {
+        Self {
+            index: sidecar.index,
+            blob: sidecar.blob,
+            kzg_commitment: sidecar.kzg_commitment,
+            kzg_proof: sidecar.kzg_proof,
+            signed_block_header: sidecar.signed_block_header,
+            kzg_commitment_inclusion_proof: sidecar.kzg_commitment_inclusion_proof,
+        }
+    }
+}
+
+/// A bundle of blob sidecars, as returned by the beacon node.
+#[derive(Debug, Clone, serde::Deserialize)]
+struct BeaconSidecarBundle {
+    data: Vec<BeaconSidecar>,
+}
+
+/// The beacon node API used by the [OnlineBlobProvider].
+///
+/// Custom backends can be used through [OnlineBlobProvider::new_with_client].
+#[async_trait]
+pub trait BeaconClient: Debug + Send + Sync {
+    /// Returns the beacon chain config spec.
+    async fn config_spec(&self) -> Result<APIConfigResponse, BlobProviderError>;
+
+    /// Returns the beacon chain genesis.
+    async fn beacon_genesis(&self) -> Result<APIGenesisResponse, BlobProviderError>;
+
+    /// Returns all blob sidecars of the given slot.
+    async fn beacon_blob_side_cars(&self, slot: u64) -> Result<Vec<BlobData>, BlobProviderError>;
+}
+
+/// A [BeaconClient] for the beacon node HTTP API.
+///
+/// The genesis and config spec responses never change, so they are fetched once and cached for
+/// the lifetime of the client. Clones of the client share the cached responses.
 #[derive(Debug, Clone)]
-pub struct OnlineBlobProvider {
+pub struct OnlineBeaconClient {
     /// The base url.
     base: String,
     /// The inner reqwest client.
     inner: Client,
-    /// The genesis time.
-    genesis_time: u64,
-    /// The slot interval.
-    slot_interval: u64,
+    /// The cached genesis response.
+    genesis: Arc<OnceCell<APIGenesisResponse>>,
+    /// The cached config spec response.
+    spec: Arc<OnceCell<APIConfigResponse>>,
 }
 
-impl OnlineBlobProvider {
-    /// Creates a new instance of the [OnlineBlobProvider].
-    ///
-    /// The `genesis_time` and `slot_interval` arguments are _optional_ and the
-    /// [OnlineBlobProvider] will attempt to load them dynamically at runtime if they are not
-    /// provided.
-    pub async fn new_http(base: String) -> Result<Self, BlobProviderError> {
-        let inner = Client::new();
-        let genesis = inner
-            .get(format!("{}/{}", base, GENESIS_METHOD))
-            .send()
-            .await
-            .map_err(|_| BlobProviderError::Backend("Failed to fetch genesis".to_string()))?;
-        let genesis_time = genesis
-            .json::<APIGenesisResponse>()
-            .await
-            .map_err(|e| BlobProviderError::Backend(e.to_string()))?
-            .data
-            .genesis_time;
-        let spec = inner
-            .get(format!("{}/{}", base, SPEC_METHOD))
+impl OnlineBeaconClient {
+    /// Creates a new [OnlineBeaconClient] for the beacon node at the given base url.
+    pub fn new_http(base: String) -> Self {
+        Self::new_http_with_client(base, Client::new())
+    }
+
+    /// Creates a new [OnlineBeaconClient] for the beacon node at the given base url, sending
+    /// requests with the given [Client], e.g. one that attaches authentication headers.
+    pub fn new_http_with_client(base: String, inner: Client) -> Self {
+        Self { base, inner, genesis: Default::default(), spec: Default::default() }
+    }
+
+    /// Fetches and deserializes the response of the given beacon API method.
+    async fn get<T: serde::de::DeserializeOwned>(
+        &self,
+        method: &str,
+    ) -> Result<T, BlobProviderError> {
+        self.inner
+            .get(format!("{}/{}", self.base, method))
             .send()
             .await
-            .map_err(|_| BlobProviderError::Backend("Failed to fetch config".to_string()))?;
-        let slot_interval = spec
-            .json::<APIConfigResponse>()
+            .map_err(|e| BlobProviderError::Backend(format!("Failed to fetch {method}: {e}")))?
+            .json::<T>()
             .await
-            .map_err(|e| BlobProviderError::Backend(e.to_string()))?
-            .data
-            .seconds_per_slot;
-        Ok(Self { base, inner, genesis_time, slot_interval })
+            .map_err(|e| BlobProviderError::Backend(e.to_string()))
+    }
+}
+
+#[async_trait]
+impl BeaconClient for OnlineBeaconClient {
+    async fn config_spec(&self) -> Result<APIConfigResponse, BlobProviderError> {
+        self.spec.get_or_try_init(|| self.get(SPEC_METHOD)).await.cloned()
+    }
+
+    async fn beacon_genesis(&self) -> Result<APIGenesisResponse, BlobProviderError> {
+        self.genesis.get_or_try_init(|| self.get(GENESIS_METHOD)).await.cloned()
+    }
+
+    async fn beacon_blob_side_cars(&self, slot: u64) -> Result<Vec<BlobData>, BlobProviderError> {
+        let bundle = self
+            .get::<BeaconSidecarBundle>(&format!("{}/{}", SIDECARS_METHOD_PREFIX, slot))
+            .await?;
+        Ok(bundle.data.into_iter().map(Into::into).collect())
+    }
+}
+
+/// The blob sidecars of a slot, initialized by the first request for the slot.
+type SlotSidecars = Arc<OnceCell<Arc<Vec<BlobData>>>>;
+
+/// Statistics of the [OnlineBlobProvider]'s sidecar cache.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SidecarCacheStats {
+    /// The number of requests served from a cached slot.
+    pub hits: u64,
+    /// The number of requests that fetched the sidecars of a slot from the beacon node.
+    pub misses: u64,
+    /// The number of requests that waited on an in-flight fetch for the same slot.
+    pub coalesced: u64,
+}
+
+/// The sidecar cache counters, shared between clones of the [OnlineBlobProvider].
+#[derive(Debug, Default)]
+struct SidecarCacheCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    coalesced: AtomicU64,
+}
+
+/// An online provider to fetch blob sidecars.
+///
+/// The sidecars of recently requested slots are cached, and concurrent requests for the same slot
+/// are coalesced into a single beacon node request. Clones of the provider share the cache.
+#[derive(Debug, Clone)]
+pub struct OnlineBlobProvider {
+    /// The beacon node client.
+    client: Arc<dyn BeaconClient>,
+    /// The slot derivation strategy.
+    slot_derivation: Arc<dyn SlotDerivation>,
+    /// The number of slots after the derived slot that are searched for the sidecars, if they
+    /// are not found in the derived slot.
+    missed_slot_tolerance: u64,
+    /// The blob sidecars of recently requested slots, keyed by slot.
+    sidecars: Arc<Mutex<LruCache<u64, SlotSidecars>>>,
+    /// The sidecar cache counters.
+    counters: Arc<SidecarCacheCounters>,
+}
+
+impl OnlineBlobProvider {
+    /// Creates a new instance of the [OnlineBlobProvider] for the beacon node at the given base
+    /// url.
+    ///
+    /// The genesis time and slot interval are loaded from the beacon node.
+    pub async fn new_http(base: String) -> Result<Self, BlobProviderError> {
+        Self::new_with_client(Arc::new(OnlineBeaconClient::new_http(base))).await
+    }
+
+    /// Creates a new instance of the [OnlineBlobProvider] backed by the given [BeaconClient].
+    ///
+    /// The genesis time and slot interval are loaded from the client, and used for a
+    /// [SimpleSlotDerivation].
+    pub async fn new_with_client(client: Arc<dyn BeaconClient>) -> Result<Self, BlobProviderError> {
+        let genesis_time = client.beacon_genesis().await?.data.genesis_time;
+        let slot_interval = client.config_spec().await?.data.seconds_per_slot;
+        Ok(Self {
+            client,
+            slot_derivation: Arc::new(SimpleSlotDerivation::new(genesis_time, slot_interval)),
+            missed_slot_tolerance: 0,
+            sidecars: Arc::new(Mutex::new(LruCache::new(
+                NonZeroUsize::new(DEFAULT_SIDECAR_CACHE_SIZE).expect("non-zero cache size"),
+            ))),
+            counters: Default::default(),
+        })
+    }
+
+    /// Sets the [SlotDerivation] strategy, e.g. for devnets or L1s with non-standard slots.
+    pub fn with_slot_derivation(mut self, slot_derivation: Arc<dyn SlotDerivation>) -> Self {
+        self.slot_derivation = slot_derivation;
+        self
+    }
+
+    /// Sets the number of slots after the derived slot that are searched for the requested
+    /// sidecars, if they are not found in the derived slot.
+    ///
+    /// Sidecars found in a later slot are only accepted if their KZG commitments match the
+    /// requested blob hashes.
+    pub const fn with_missed_slot_tolerance(mut self, missed_slot_tolerance: u64) -> Self {
+        self.missed_slot_tolerance = missed_slot_tolerance;
+        self
+    }
+
+    /// Sets the number of slots whose blob sidecars are cached, clearing the cache.
+    pub fn with_sidecar_cache_size(mut self, size: NonZeroUsize) -> Self {
+        self.sidecars = Arc::new(Mutex::new(LruCache::new(size)));
+        self
+    }
+
+    /// Returns the [SidecarCacheStats] of the sidecar cache.
+    pub fn sidecar_cache_stats(&self) -> SidecarCacheStats {
+        SidecarCacheStats {
+            hits: self.counters.hits.load(Ordering::Relaxed),
+            misses: self.counters.misses.load(Ordering::Relaxed),
+            coalesced: self.counters.coalesced.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Returns all blob sidecars of the given slot, fetching them from the beacon node if they
+    /// are not cached or being fetched already.
+    async fn slot_sidecars(&self, slot: u64) -> Result<Arc<Vec<BlobData>>, BlobProviderError> {
+        let cell = self
+            .sidecars
+            .lock()
+            .expect("sidecar cache lock poisoned")
+            .get_or_insert(slot, SlotSidecars::default)
+            .clone();
+        if let Some(sidecars) = cell.get() {
+            self.counters.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(sidecars.clone());
+        }
+
+        // Only the first request for the slot fetches the sidecars, concurrent requests wait for
+        // it to complete. If the fetch fails, the next waiting request retries it.
+        let mut fetched = false;
+        let sidecars = cell
+            .get_or_try_init(|| async {
+                fetched = true;
+                self.client.beacon_blob_side_cars(slot).await.map(Arc::new)
+            })
+            .await?
+            .clone();
+        let counter = if fetched { &self.counters.misses } else { &self.counters.coalesced };
+        counter.fetch_add(1, Ordering::Relaxed);
+        Ok(sidecars)
     }
 
     /// Fetches blob sidecars that were confirmed in the specified L1 block with the given indexed
@@ -114,36 +331,43 @@ impl OnlineBlobProvider {
         &self,
         slot: u64,
         hashes: &[IndexedBlobHash],
-    ) -> Result<Vec<BlobData>, reqwest::Error> {
-        let raw_response = self
-            .inner
-            .get(format!("{}/{}/{}", self.base, SIDECARS_METHOD_PREFIX, slot))
-            .send()
-            .await?;
-        let raw_response = raw_response.json::<BeaconBlobBundle>().await?;
+    ) -> Result<Vec<BlobData>, BlobProviderError> {
+        let slot_sidecars = self.slot_sidecars(slot).await?;
 
         // Filter the sidecars by the hashes, in-order.
         let mut sidecars = Vec::with_capacity(hashes.len());
         hashes.iter().for_each(|hash| {
-            if let Some(sidecar) =
-                raw_response.data.iter().find(|sidecar| sidecar.index == hash.index)
+            if let Some(sidecar) = slot_sidecars.iter().find(|sidecar| sidecar.index == hash.index)
             {
                 sidecars.push(sidecar.clone());
             }
         });
 
+        // A beacon node that has not fully synced the slot may return an empty or partial list
+        // of sidecars. It is evicted, so that the next request for the slot refetches it.
+        if sidecars.len() != hashes.len() {
+            self.evict_slot_sidecars(slot, &slot_sidecars);
+        }
+
         Ok(sidecars)
     }
 
+    /// Evicts the cached sidecars of the given slot, unless they were refetched already.
+    fn evict_slot_sidecars(&self, slot: u64, sidecars: &Arc<Vec<BlobData>>) {
+        let mut cache = self.sidecars.lock().expect("sidecar cache lock poisoned");
+        let cached = cache.peek(&slot).and_then(|cell| cell.get());
+        if cached.is_some_and(|cached| Arc::ptr_eq(cached, sidecars)) {
+            cache.pop(&slot);
+        }
+    }
+
     /// Fetches blob sidecars for the given slot and blob hashes.
     pub async fn fetch_sidecars(
         &self,
         slot: u64,
         hashes: &[IndexedBlobHash],
     ) -> Result<Vec<BlobData>, BlobProviderError> {
-        self.beacon_blob_side_cars(slot, hashes)
-            .await
-            .map_err(|e| BlobProviderError::Backend(e.to_string()))
+        self.beacon_blob_side_cars(slot, hashes).await
     }
 
     /// Computes the slot for the given timestamp.
@@ -158,20 +382,12 @@ impl OnlineBlobProvider {
         Ok((timestamp - genesis) / slot_time)
     }
 
-    /// Fetches blob sidecars for the given block reference and blob hashes.
-    pub async fn fetch_filtered_sidecars(
+    /// Fetches the blob sidecars of the given slot with the indices of the given blob hashes.
+    async fn fetch_slot_filtered_sidecars(
         &self,
-        block_ref: &BlockInfo,
+        slot: u64,
         blob_hashes: &[IndexedBlobHash],
-    ) -> Result<Vec<BlobTransactionSidecarItem>, BlobProviderError> {
-        if blob_hashes.is_empty() {
-            return Ok(Vec::new());
-        }
-
-        // Calculate the slot for the given timestamp.
-        let slot = Self::slot(self.genesis_time, self.slot_interval, block_ref.timestamp)?;
-
-        // Fetch blob sidecars for the slot using the given blob hashes.
+    ) -> Result<Vec<BlobData>, BlobProviderError> {
         let sidecars = self.fetch_sidecars(slot, blob_hashes).await?;
 
         // Filter blob sidecars that match the indicies in the specified list.
@@ -185,6 +401,45 @@ impl OnlineBlobProvider {
         if blob_hashes.len() != filtered.len() {
             return Err(BlobProviderError::SidecarLengthMismatch(blob_hashes.len(), filtered.len()));
         }
+        Ok(filtered)
+    }
+
+    /// Fetches blob sidecars for the given block reference and blob hashes.
+    pub async fn fetch_filtered_sidecars(
+        &self,
+        block_ref: &BlockInfo,
+        blob_hashes: &[IndexedBlobHash],
+    ) -> Result<Vec<BlobTransactionSidecarItem>, BlobProviderError> {
+        if blob_hashes.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Calculate the slot for the given timestamp.
+        let slot = self.slot_derivation.slot(block_ref.timestamp)?;
+
+        // Fetch blob sidecars for the slot using the given blob hashes. If a missed slot
+        // tolerance is configured, the following slots are searched as well, and the sidecars are
+        // only accepted if their commitments match the blob hashes.
+        let mut result = Err(BlobProviderError::SlotDerivation);
+        for candidate in slot..=slot.saturating_add(self.missed_slot_tolerance) {
+            result = self.fetch_slot_filtered_sidecars(candidate, blob_hashes).await;
+            match result {
+                Ok(_) if self.missed_slot_tolerance == 0 => break,
+                Ok(ref filtered) => {
+                    let matches = blob_hashes.iter().zip(filtered.iter()).all(|(hash, sidecar)| {
+                        kzg_to_versioned_hash(sidecar.kzg_commitment.as_slice()) == hash.hash
+                    });
+                    if matches {
+                        break;
+                    }
+                    result = Err(BlobProviderError::Backend(format!(
+                        "Blob sidecars in slot {candidate} do not match the blob hashes"
+                    )));
+                }
+                Err(_) => {}
+            }
+        }
+        let filtered = result?;
 
         Ok(filtered
             .into_iter()
@@ -197,3 +452,158 @@ impl OnlineBlobProvider {
             .collect::<Vec<BlobTransactionSidecarItem>>())
     }
 }
+
+#[async_trait]
+impl BlobProvider for OnlineBlobProvider {
+    type Error = BlobProviderError;
+
+    async fn get_blobs(
+        &mut self,
+        block_ref: &BlockInfo,
+        blob_hashes: &[IndexedBlobHash],
+    ) -> Result<Vec<Box<Blob>>, Self::Error> {
+        let sidecars = self.fetch_filtered_sidecars(block_ref, blob_hashes).await?;
+        Ok(sidecars.into_iter().map(|sidecar| sidecar.blob).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sidecar_json(index: &str, casing: fn(&str) -> String) -> serde_json::Value {
+        let blob = format!("0x{}", "00".repeat(alloy_eips::eip4844::BYTES_PER_BLOB));
+        let commitment = format!("0x{}", "11".repeat(48));
+        serde_json::json!({
+            "data": [{
+                "index": serde_json::from_str::<serde_json::Value>(index).unwrap(),
+                "blob": blob,
+                casing("kzg_commitment"): commitment,
+                casing("kzg_proof"): commitment,
+            }]
+        })
+    }
+
+    fn camel_case(field: &str) -> String {
+        let mut parts = field.split('_');
+        let first = parts.next().unwrap().to_string();
+        parts.fold(first, |acc, p| acc + &p[..1].to_uppercase() + &p[1..])
+    }
+
+    #[test]
+    fn test_deserialize_sidecars_snake_case() {
+        let json = sidecar_json("\"3\"", str::to_string);
+        let bundle = serde_json::from_value::<BeaconSidecarBundle>(json).unwrap();
+        let sidecar = BlobData::from(bundle.data[0].clone());
+        assert_eq!(sidecar.index, 3);
+        assert_eq!(sidecar.kzg_commitment, Bytes48::repeat_byte(0x11));
+        assert_eq!(sidecar.signed_block_header, Header::default());
+    }
+
+    #[test]
+    fn test_deserialize_sidecars_camel_case() {
+        let json = sidecar_json("5", camel_case);
+        let bundle = serde_json::from_value::<BeaconSidecarBundle>(json).unwrap();
+        assert_eq!(bundle.data[0].index, 5);
+        assert_eq!(bundle.data[0].kzg_proof, Bytes48::repeat_byte(0x11));
+    }
+
+    /// A [BeaconClient] that serves the queued sidecar responses in order.
+    #[derive(Debug, Default)]
+    struct MockBeaconClient {
+        responses: Mutex<Vec<Vec<BlobData>>>,
+        fetches: AtomicU64,
+    }
+
+    #[async_trait]
+    impl BeaconClient for MockBeaconClient {
+        async fn config_spec(&self) -> Result<APIConfigResponse, BlobProviderError> {
+            Ok(APIConfigResponse::new(12))
+        }
+
+        async fn beacon_genesis(&self) -> Result<APIGenesisResponse, BlobProviderError> {
+            Ok(APIGenesisResponse::new(0))
+        }
+
+        async fn beacon_blob_side_cars(&self, _: u64) -> Result<Vec<BlobData>, BlobProviderError> {
+            self.fetches.fetch_add(1, Ordering::Relaxed);
+            Ok(self.responses.lock().unwrap().remove(0))
+        }
+    }
+
+    fn blob_data(index: u64) -> BlobData {
+        BlobData {
+            index,
+            blob: Box::new(Blob::ZERO),
+            kzg_commitment: Bytes48::ZERO,
+            kzg_proof: Bytes48::ZERO,
+            signed_block_header: Header::default(),
+            kzg_commitment_inclusion_proof: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_partial_sidecars_are_refetched() {
+        let client = Arc::new(MockBeaconClient {
+            responses: Mutex::new(vec![
+                vec![],
+                vec![blob_data(0)],
+                vec![blob_data(0), blob_data(1)],
+            ]),
+            ..Default::default()
+        });
+        let provider = OnlineBlobProvider::new_with_client(client.clone()).await.unwrap();
+        let hashes = [
+            IndexedBlobHash { index: 0, ..Default::default() },
+            IndexedBlobHash { index: 1, ..Default::default() },
+        ];
+
+        let err = provider.fetch_slot_filtered_sidecars(1, &hashes).await.unwrap_err();
+        assert_eq!(err, BlobProviderError::SidecarLengthMismatch(2, 0));
+        let err = provider.fetch_slot_filtered_sidecars(1, &hashes).await.unwrap_err();
+        assert_eq!(err, BlobProviderError::SidecarLengthMismatch(2, 1));
+        let sidecars = provider.fetch_slot_filtered_sidecars(1, &hashes).await.unwrap();
+        assert_eq!(sidecars.len(), 2);
+
+        // The complete response is served from the cache.
+        provider.fetch_slot_filtered_sidecars(1, &hashes).await.unwrap();
+        assert_eq!(client.fetches.load(Ordering::Relaxed), 3);
+        assert_eq!(
+            provider.sidecar_cache_stats(),
+            SidecarCacheStats { hits: 1, misses: 3, coalesced: 0 }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_blobs() {
+        let mut blob = Blob::ZERO;
+        blob[0] = 0x01;
+        let client = Arc::new(MockBeaconClient {
+            responses: Mutex::new(vec![vec![
+                blob_data(0),
+                BlobData { blob: Box::new(blob), ..blob_data(1) },
+            ]]),
+            ..Default::default()
+        });
+        let mut provider = OnlineBlobProvider::new_with_client(client).await.unwrap();
+        let block = BlockInfo { timestamp: 24, ..Default::default() };
+        let blobs =
+            provider.get_blobs(&block, &[IndexedBlobHash { index: 1, ..Default::default() }]).await;
+        assert_eq!(blobs.unwrap(), [Box::new(blob)]);
+        assert!(provider.get_blobs(&block, &[]).await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_deserialize_genesis_and_spec_casing() {
+        let genesis = r#"{"data":{"genesisTime":"1606824023"}}"#;
+        assert_eq!(
+            serde_json::from_str::<APIGenesisResponse>(genesis).unwrap(),
+            APIGenesisResponse::new(1606824023)
+        );
+        let spec = r#"{"data":{"SECONDS_PER_SLOT":"12"}}"#;
+        assert_eq!(
+            serde_json::from_str::<APIConfigResponse>(spec).unwrap(),
+            APIConfigResponse::new(12)
+        );
+    }
+}
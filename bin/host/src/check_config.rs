@@ -0,0 +1,232 @@
+//! The `kona-check-config` tool, which cross-verifies a rollup config against the L1 and L2
+//! chains it configures.
+
+use crate::{
+    cli::{parse_b256, read_rollup_config, EndpointAuth, L1Args},
+    HostCli,
+};
+use alloy_primitives::{keccak256, Address, TxKind, B256, U256};
+use alloy_provider::{Provider, RootProvider};
+use alloy_rpc_types::{
+    BlockId, BlockNumberOrTag, BlockTransactionsKind, TransactionInput, TransactionRequest,
+};
+use alloy_transport::BoxTransport;
+use anyhow::{anyhow, Result};
+use clap::{ArgAction, Parser};
+use std::{fmt, path::PathBuf};
+
+const ABOUT: &str = "
+kona-check-config cross-verifies a rollup config against the L1 and L2 chains it configures: the
+chain IDs, the genesis blocks, and the addresses and start block read from the SystemConfig
+contract. Mismatches are reported, and make the tool exit with a non-zero status.
+";
+
+/// The `kona-check-config` CLI application arguments.
+#[derive(Parser, Clone, Debug)]
+#[command(about = ABOUT, version)]
+pub struct CheckConfigCli {
+    /// Verbosity level (0-2)
+    #[arg(long, short, action = ArgAction::Count)]
+    pub v: u8,
+    /// Path to the rollup config to check.
+    #[clap(long, alias = "rollup-cfg", env)]
+    pub rollup_config_path: PathBuf,
+    /// The L1 node and L1 Beacon API arguments.
+    #[command(flatten)]
+    pub l1: L1Args,
+    /// Address of L2 JSON-RPC endpoint to use. Either an HTTP(S) URL, or the path of an IPC
+    /// socket.
+    #[clap(long, visible_alias = "l2", env)]
+    pub l2_node_address: String,
+    /// Authentication for the L2 node, see `--l1-node-auth`.
+    #[clap(long, value_delimiter = ',', env)]
+    pub l2_node_auth: Vec<EndpointAuth>,
+    /// Hash of the L1 block to read the SystemConfig contract at. Defaults to the latest block.
+    #[clap(long, value_parser = parse_b256)]
+    pub l1_block: Option<B256>,
+}
+
+/// A value of the rollup config, compared against the value read from the chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigCheck {
+    /// The name of the checked value.
+    pub name: &'static str,
+    /// The configured value.
+    pub configured: String,
+    /// The value read from the chain.
+    pub on_chain: String,
+    /// Whether the values match.
+    pub matches: bool,
+}
+
+impl ConfigCheck {
+    /// Compares a configured value against the value read from the chain.
+    pub fn new<T: PartialEq + fmt::Display>(
+        name: &'static str,
+        configured: T,
+        on_chain: T,
+    ) -> Self {
+        Self {
+            name,
+            matches: configured == on_chain,
+            configured: configured.to_string(),
+            on_chain: on_chain.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.matches {
+            write!(f, "ok       {}: {}", self.name, self.configured)
+        } else {
+            write!(
+                f,
+                "MISMATCH {}: configured {}, on-chain {}",
+                self.name, self.configured, self.on_chain
+            )
+        }
+    }
+}
+
+/// Returns the ABI selector of the given function signature.
+fn selector(signature: &str) -> [u8; 4] {
+    let hash = keccak256(signature);
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// Calls a getter of the SystemConfig contract without arguments, returning its first word.
+async fn call_getter(
+    provider: &RootProvider<BoxTransport>,
+    system_config: Address,
+    signature: &str,
+    block: BlockId,
+) -> Result<B256> {
+    let request = TransactionRequest {
+        to: Some(TxKind::Call(system_config)),
+        input: TransactionInput::new(selector(signature).into()),
+        ..Default::default()
+    };
+    let output = provider
+        .call(&request)
+        .block(block)
+        .await
+        .map_err(|e| anyhow!("Failed to call SystemConfig.{signature}: {e}"))?;
+    output
+        .get(..32)
+        .map(B256::from_slice)
+        .ok_or_else(|| anyhow!("Invalid SystemConfig.{signature} return data: {output}"))
+}
+
+/// Returns the hash and timestamp of the block with the given number.
+async fn block_hash_and_time(
+    provider: &RootProvider<BoxTransport>,
+    number: u64,
+) -> Result<(B256, u64)> {
+    let block = provider
+        .get_block_by_number(BlockNumberOrTag::Number(number), BlockTransactionsKind::Hashes)
+        .await
+        .map_err(|e| anyhow!("Failed to fetch block {number}: {e}"))?
+        .ok_or_else(|| anyhow!("Block {number} not found"))?;
+    Ok((block.header.hash, block.header.timestamp))
+}
+
+/// Cross-verifies the rollup config of the [CheckConfigCli] against the L1 and L2 chains.
+///
+/// The SystemConfig contract is read for its batch inbox, OptimismPortal and start block, which
+/// must match the rollup config for the whole lifetime of the chain. Values that are updated by
+/// config update events, such as the batcher address, are not checked, since the genesis system
+/// config of the rollup config only holds their initial values.
+pub async fn check_config(cfg: &CheckConfigCli) -> Result<Vec<ConfigCheck>> {
+    let rollup_config = read_rollup_config(&cfg.rollup_config_path)?;
+    let l1_provider = cfg.l1.l1_provider().await?;
+    let l2_provider = HostCli::rpc_provider(&cfg.l2_node_address, &cfg.l2_node_auth).await?;
+
+    let l1_chain_id = l1_provider
+        .get_chain_id()
+        .await
+        .map_err(|e| anyhow!("Failed to fetch the L1 chain ID: {e}"))?;
+    let l2_chain_id = l2_provider
+        .get_chain_id()
+        .await
+        .map_err(|e| anyhow!("Failed to fetch the L2 chain ID: {e}"))?;
+    let (l1_genesis_hash, _) =
+        block_hash_and_time(&l1_provider, rollup_config.genesis.l1.number).await?;
+    let (l2_genesis_hash, l2_genesis_time) =
+        block_hash_and_time(&l2_provider, rollup_config.genesis.l2.number).await?;
+
+    let block = cfg.l1_block.map_or_else(BlockId::latest, BlockId::hash);
+    let system_config = rollup_config.l1_system_config_address;
+    let batch_inbox = call_getter(&l1_provider, system_config, "batchInbox()", block).await?;
+    let portal = call_getter(&l1_provider, system_config, "optimismPortal()", block).await?;
+    let start_block = call_getter(&l1_provider, system_config, "startBlock()", block).await?;
+
+    Ok(vec![
+        ConfigCheck::new("l1ChainId", rollup_config.l1_chain_id, l1_chain_id),
+        ConfigCheck::new("l2ChainId", rollup_config.l2_chain_id, l2_chain_id),
+        ConfigCheck::new("genesis.l1.hash", rollup_config.genesis.l1.hash, l1_genesis_hash),
+        ConfigCheck::new("genesis.l2.hash", rollup_config.genesis.l2.hash, l2_genesis_hash),
+        ConfigCheck::new("genesis.l2Time", rollup_config.genesis.l2_time, l2_genesis_time),
+        ConfigCheck::new(
+            "batchInboxAddress",
+            rollup_config.batch_inbox_address,
+            Address::from_word(batch_inbox),
+        ),
+        ConfigCheck::new(
+            "depositContractAddress",
+            rollup_config.deposit_contract_address,
+            Address::from_word(portal),
+        ),
+        ConfigCheck::new(
+            "genesis.l1.number",
+            U256::from(rollup_config.genesis.l1.number),
+            U256::from_be_bytes(start_block.0),
+        ),
+    ])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_selector() {
+        assert_eq!(selector("transfer(address,uint256)"), [0xa9, 0x05, 0x9c, 0xbb]);
+    }
+
+    #[test]
+    fn test_config_check() {
+        let check = ConfigCheck::new("l2ChainId", 10, 10);
+        assert!(check.matches);
+        assert_eq!(check.to_string(), "ok       l2ChainId: 10");
+
+        let check = ConfigCheck::new("batchInboxAddress", Address::ZERO, Address::repeat_byte(1));
+        assert!(!check.matches);
+        assert_eq!(
+            check.to_string(),
+            format!(
+                "MISMATCH batchInboxAddress: configured {}, on-chain {}",
+                Address::ZERO,
+                Address::repeat_byte(1)
+            )
+        );
+    }
+
+    #[test]
+    fn test_flags() {
+        let base = ["kona-check-config", "--rollup-config-path", "dummy", "--l1", "dummy"];
+        let l1_block = B256::ZERO.to_string();
+        let l1_block_args = ["--l2", "dummy", "--l1-block", &l1_block];
+        let cases = [
+            (["--l2", "dummy"].as_slice(), true),
+            (l1_block_args.as_slice(), true),
+            (["--l2", "dummy", "--l2-node-auth", "bearer:abc"].as_slice(), true),
+            ([].as_slice(), false),
+            (["--l2", "dummy", "--l1-block", "1"].as_slice(), false),
+        ];
+        for (args_ext, valid) in cases {
+            let args = base.iter().chain(args_ext.iter()).cloned().collect::<Vec<_>>();
+            assert_eq!(CheckConfigCli::try_parse_from(args).is_ok(), valid);
+        }
+    }
+}
@@ -0,0 +1,141 @@
+//! Authentication of the host's RPC and beacon API endpoints.
+
+use anyhow::{anyhow, bail, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION},
+    Client,
+};
+use std::{fmt, str::FromStr};
+
+/// Authentication attached to every request sent to an endpoint.
+///
+/// Parsed from `bearer:<token>`, `basic:<username>:<password>` or `header:<name>:<value>`.
+#[derive(Clone, PartialEq, Eq)]
+pub enum EndpointAuth {
+    /// A bearer token, sent in the `Authorization` header.
+    Bearer(String),
+    /// HTTP basic authentication, sent in the `Authorization` header.
+    Basic {
+        /// The username.
+        username: String,
+        /// The password.
+        password: String,
+    },
+    /// An arbitrary header, e.g. an API key.
+    Header(String, String),
+}
+
+impl EndpointAuth {
+    /// Returns the header carrying the authentication.
+    fn header(&self) -> Result<(HeaderName, HeaderValue)> {
+        let (name, value) = match self {
+            Self::Bearer(token) => (AUTHORIZATION, format!("Bearer {token}")),
+            Self::Basic { username, password } => (
+                AUTHORIZATION,
+                format!("Basic {}", STANDARD.encode(format!("{username}:{password}"))),
+            ),
+            Self::Header(name, value) => (name.parse()?, value.clone()),
+        };
+        let mut value = HeaderValue::from_str(&value)?;
+        value.set_sensitive(true);
+        Ok((name, value))
+    }
+}
+
+impl FromStr for EndpointAuth {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (kind, rest) = s
+            .split_once(':')
+            .ok_or_else(|| anyhow!("Expected `bearer:`, `basic:` or `header:` authentication"))?;
+        match kind {
+            "bearer" if !rest.is_empty() => Ok(Self::Bearer(rest.to_string())),
+            "basic" => {
+                let (username, password) = rest
+                    .split_once(':')
+                    .ok_or_else(|| anyhow!("Expected `basic:<username>:<password>`"))?;
+                Ok(Self::Basic { username: username.to_string(), password: password.to_string() })
+            }
+            "header" => {
+                let (name, value) = rest
+                    .split_once(':')
+                    .filter(|(name, _)| !name.is_empty())
+                    .ok_or_else(|| anyhow!("Expected `header:<name>:<value>`"))?;
+                Ok(Self::Header(name.to_string(), value.trim_start().to_string()))
+            }
+            _ => bail!("Invalid endpoint authentication: `{kind}`"),
+        }
+    }
+}
+
+/// Secrets are never printed, e.g. when the CLI arguments are logged.
+impl fmt::Debug for EndpointAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bearer(_) => f.write_str("Bearer(<redacted>)"),
+            Self::Basic { username, .. } => write!(f, "Basic({username}:<redacted>)"),
+            Self::Header(name, _) => write!(f, "Header({name}: <redacted>)"),
+        }
+    }
+}
+
+/// Returns an HTTP client that attaches the given authentication to every request.
+pub(crate) fn http_client(auth: &[EndpointAuth]) -> Result<Client> {
+    let headers = auth.iter().map(EndpointAuth::header).collect::<Result<HeaderMap>>()?;
+    Ok(Client::builder().default_headers(headers).build()?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_endpoint_auth() {
+        assert_eq!(
+            "bearer:abc".parse::<EndpointAuth>().unwrap(),
+            EndpointAuth::Bearer("abc".to_string())
+        );
+        assert_eq!(
+            "basic:user:p:ss".parse::<EndpointAuth>().unwrap(),
+            EndpointAuth::Basic { username: "user".to_string(), password: "p:ss".to_string() }
+        );
+        assert_eq!(
+            "header:X-Api-Key: 123".parse::<EndpointAuth>().unwrap(),
+            EndpointAuth::Header("X-Api-Key".to_string(), "123".to_string())
+        );
+
+        for invalid in ["abc", "bearer:", "basic:user", "header::123", "jwt:abc"] {
+            assert!(invalid.parse::<EndpointAuth>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_endpoint_auth_headers() {
+        let (name, value) = EndpointAuth::Bearer("abc".to_string()).header().unwrap();
+        assert_eq!((name, value.to_str().unwrap()), (AUTHORIZATION, "Bearer abc"));
+        assert!(value.is_sensitive());
+
+        let basic =
+            EndpointAuth::Basic { username: "user".to_string(), password: "pass".to_string() };
+        let (_, value) = basic.header().unwrap();
+        assert_eq!(value.to_str().unwrap(), "Basic dXNlcjpwYXNz");
+
+        let (name, value) =
+            EndpointAuth::Header("X-Api-Key".to_string(), "123".to_string()).header().unwrap();
+        assert_eq!((name.as_str(), value.to_str().unwrap()), ("x-api-key", "123"));
+
+        let invalid = EndpointAuth::Header("X Api Key".to_string(), "123".to_string());
+        assert!(invalid.header().is_err());
+        assert!(http_client(&[invalid]).is_err());
+    }
+
+    #[test]
+    fn test_endpoint_auth_debug_redacted() {
+        let auth = "basic:user:secret".parse::<EndpointAuth>().unwrap();
+        assert_eq!(format!("{auth:?}"), "Basic(user:<redacted>)");
+        let auth = "header:X-Api-Key:secret".parse::<EndpointAuth>().unwrap();
+        assert!(!format!("{auth:?}").contains("secret"));
+    }
+}
@@ -0,0 +1,55 @@
+//! The L1 endpoint arguments shared by the host tools.
+
+use super::{http_client, EndpointAuth};
+use crate::{
+    blobs::{OnlineBeaconClient, OnlineBlobProvider},
+    HostCli,
+};
+use alloy_provider::RootProvider;
+use alloy_transport::BoxTransport;
+use anyhow::{anyhow, Result};
+use clap::Args;
+use std::sync::Arc;
+
+/// The L1 node and L1 Beacon API arguments of the `kona-check-config`, `kona-extract` and
+/// `kona-trace` tools.
+#[derive(Args, Clone, Debug)]
+pub struct L1Args {
+    /// Address of L1 JSON-RPC endpoint to use. Either an HTTP(S) URL, or the path of an IPC
+    /// socket.
+    #[clap(long, visible_alias = "l1", env)]
+    pub l1_node_address: String,
+    /// Address of the L1 Beacon API endpoint to use. Required to read the frames of blob
+    /// transactions.
+    #[clap(long, visible_alias = "beacon", env)]
+    pub l1_beacon_address: Option<String>,
+    /// Authentication for the L1 node, as `bearer:<token>`, `basic:<username>:<password>` or
+    /// `header:<name>:<value>`. May be repeated, or comma separated.
+    #[clap(long, value_delimiter = ',', env)]
+    pub l1_node_auth: Vec<EndpointAuth>,
+    /// Authentication for the L1 Beacon API, see `--l1-node-auth`.
+    #[clap(long, value_delimiter = ',', env)]
+    pub l1_beacon_auth: Vec<EndpointAuth>,
+}
+
+impl L1Args {
+    /// Returns a provider for the L1 node.
+    pub async fn l1_provider(&self) -> Result<RootProvider<BoxTransport>> {
+        HostCli::rpc_provider(&self.l1_node_address, &self.l1_node_auth).await
+    }
+
+    /// Returns an [OnlineBlobProvider] for the L1 Beacon API, if its address is set.
+    pub async fn blob_provider(&self) -> Result<Option<OnlineBlobProvider>> {
+        let Some(address) = &self.l1_beacon_address else {
+            return Ok(None);
+        };
+        let client = OnlineBeaconClient::new_http_with_client(
+            address.clone(),
+            http_client(&self.l1_beacon_auth)?,
+        );
+        OnlineBlobProvider::new_with_client(Arc::new(client))
+            .await
+            .map(Some)
+            .map_err(|e| anyhow!("Failed to load blob provider configuration: {e}"))
+    }
+}
@@ -1,16 +1,19 @@
 //! This module contains all CLI-specific code for the host binary.
 
 use crate::{
-    blobs::OnlineBlobProvider,
+    blobs::{OnlineBeaconClient, OnlineBlobProvider},
+    fetcher::{CircuitBreakerConfig, RequestTimeouts},
     kv::{
         DiskKeyValueStore, LocalKeyValueStore, MemoryKeyValueStore, SharedKeyValueStore,
         SplitKeyValueStore,
     },
 };
 use alloy_primitives::B256;
-use alloy_provider::ReqwestProvider;
-use alloy_rpc_client::RpcClient;
+use alloy_provider::RootProvider;
+use alloy_rpc_client::{ClientBuilder, RpcClient};
+use alloy_transport::BoxTransport;
 use alloy_transport_http::Http;
+use alloy_transport_ipc::IpcConnect;
 use anyhow::{anyhow, Result};
 use clap::{
     builder::styling::{AnsiColor, Color, Style},
@@ -19,14 +22,28 @@ use clap::{
 use op_alloy_genesis::RollupConfig;
 use reqwest::Client;
 use serde::Serialize;
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 use tokio::sync::RwLock;
 
 mod parser;
 pub(crate) use parser::parse_b256;
 
+mod auth;
+pub(crate) use auth::http_client;
+pub use auth::EndpointAuth;
+
+mod l1;
+pub use l1::L1Args;
+
+mod overrides;
+pub use overrides::{validate_rollup_config, Hardfork, RollupConfigOverride};
+
 mod tracing_util;
-pub use tracing_util::init_tracing_subscriber;
+pub use tracing_util::{init_tracing_subscriber, shutdown_tracing_subscriber};
 
 const ABOUT: &str = "
 kona-host is a CLI application that runs the Kona pre-image server and client program. The host
@@ -58,7 +75,8 @@ pub struct HostCli {
     /// Number of the L2 block that the claimed output root commits to.
     #[clap(long, visible_alias = "l2-block-number", env)]
     pub claimed_l2_block_number: u64,
-    /// Address of L2 JSON-RPC endpoint to use (eth and debug namespace required).
+    /// Address of L2 JSON-RPC endpoint to use (eth and debug namespace required). Either an
+    /// HTTP(S) URL, or the path of an IPC socket.
     #[clap(
         long,
         visible_alias = "l2",
@@ -67,7 +85,8 @@ pub struct HostCli {
         env
     )]
     pub l2_node_address: Option<String>,
-    /// Address of L1 JSON-RPC endpoint to use (eth and debug namespace required)
+    /// Address of L1 JSON-RPC endpoint to use (eth and debug namespace required). Either an
+    /// HTTP(S) URL, or the path of an IPC socket.
     #[clap(
         long,
         visible_alias = "l1",
@@ -85,6 +104,43 @@ pub struct HostCli {
         env
     )]
     pub l1_beacon_address: Option<String>,
+    /// Authentication for the L2 node, as `bearer:<token>`, `basic:<username>:<password>` or
+    /// `header:<name>:<value>`. May be repeated, or comma separated.
+    #[clap(long, value_delimiter = ',', env)]
+    #[serde(skip)]
+    pub l2_node_auth: Vec<EndpointAuth>,
+    /// Authentication for the L1 node, see `--l2-node-auth`.
+    #[clap(long, value_delimiter = ',', env)]
+    #[serde(skip)]
+    pub l1_node_auth: Vec<EndpointAuth>,
+    /// Authentication for the L1 Beacon API, see `--l2-node-auth`.
+    #[clap(long, value_delimiter = ',', env)]
+    #[serde(skip)]
+    pub l1_beacon_auth: Vec<EndpointAuth>,
+    /// Timeout of block header requests, in seconds. Defaults to 30 seconds.
+    #[clap(long, env)]
+    pub header_timeout: Option<u64>,
+    /// Timeout of block transaction requests, in seconds. Defaults to 60 seconds.
+    #[clap(long, env)]
+    pub transactions_timeout: Option<u64>,
+    /// Timeout of block receipt requests, in seconds. Defaults to 60 seconds.
+    #[clap(long, env)]
+    pub receipts_timeout: Option<u64>,
+    /// Timeout of blob sidecar requests to the L1 Beacon API, in seconds. Defaults to 60 seconds.
+    #[clap(long, env)]
+    pub blob_timeout: Option<u64>,
+    /// Timeout of L2 state requests, i.e. proofs, state nodes, code and execution witnesses, in
+    /// seconds. Defaults to 120 seconds.
+    #[clap(long, env)]
+    pub state_timeout: Option<u64>,
+    /// The number of consecutive failed requests to an endpoint after which requests to it are
+    /// suspended for `--circuit-cooldown`. Defaults to 5.
+    #[clap(long, env)]
+    pub circuit_failure_threshold: Option<u32>,
+    /// The duration for which requests to a failing endpoint are suspended, in seconds. Defaults
+    /// to 30 seconds.
+    #[clap(long, env)]
+    pub circuit_cooldown: Option<u64>,
     /// The Data Directory for preimage data storage. Optional if running in online mode,
     /// required if running in offline mode.
     #[clap(
@@ -120,6 +176,24 @@ pub struct HostCli {
         env
     )]
     pub rollup_config_path: Option<PathBuf>,
+    /// Overrides of the fields of the rollup config at `--rollup-config-path`, as `<key>=<value>`,
+    /// for devnets and fork rehearsals. The keys are `batch_inbox_address`, `block_time`,
+    /// `max_sequencer_drift`, `seq_window_size`, `channel_timeout`, `granite_channel_timeout` and
+    /// the `<hardfork>_time` activation timestamps, e.g. `holocene_time`, which are unset with
+    /// `none`. May be repeated, or comma separated.
+    #[clap(
+        long = "cfg-override",
+        value_delimiter = ',',
+        requires = "rollup_config_path",
+        conflicts_with = "l2_chain_id",
+        env
+    )]
+    pub cfg_overrides: Vec<RollupConfigOverride>,
+    /// OTLP/HTTP endpoint to export the derivation pipeline's trace spans to, e.g.
+    /// `http://localhost:4318/v1/traces`. The spans are emitted at the debug level, so `-v` is
+    /// needed to export them. Requires the host to be built with the `otlp` feature.
+    #[clap(long, env)]
+    pub otlp_endpoint: Option<String>,
 }
 
 impl HostCli {
@@ -130,37 +204,85 @@ impl HostCli {
             self.l1_beacon_address.is_none()
     }
 
-    /// Returns an HTTP provider for the given URL.
-    fn http_provider(url: &str) -> ReqwestProvider {
-        let url = url.parse().unwrap();
-        let http = Http::<Client>::new(url);
-        ReqwestProvider::new(RpcClient::new(http, true))
+    /// Returns a provider for the given RPC address.
+    ///
+    /// `http://` and `https://` URLs are connected to over HTTP, authenticated with the given
+    /// [EndpointAuth]. Any other address is the path of an IPC socket, or of a named pipe on
+    /// Windows, which needs no authentication.
+    pub(crate) async fn rpc_provider(
+        address: &str,
+        auth: &[EndpointAuth],
+    ) -> Result<RootProvider<BoxTransport>> {
+        if address.starts_with("http://") || address.starts_with("https://") {
+            let url = address.parse().map_err(|e| anyhow!("Invalid RPC URL {address}: {e}"))?;
+            let http = Http::<Client>::with_client(http_client(auth)?, url);
+            return Ok(RootProvider::new(RpcClient::new(http, true).boxed()));
+        }
+
+        if !auth.is_empty() {
+            return Err(anyhow!("Authentication is not supported for IPC endpoint {address}"));
+        }
+        let client = ClientBuilder::default()
+            .ipc(IpcConnect::new(PathBuf::from(address)))
+            .await
+            .map_err(|e| anyhow!("Failed to connect to IPC endpoint {address}: {e}"))?;
+        Ok(RootProvider::new(client.boxed()))
     }
 
     /// Creates the providers associated with the [HostCli] configuration.
     ///
     /// ## Returns
-    /// - A [ReqwestProvider] for the L1 node.
+    /// - A [RootProvider] for the L1 node.
     /// - An [OnlineBlobProvider] for the L1 beacon node.
-    /// - A [ReqwestProvider] for the L2 node.
+    /// - A [RootProvider] for the L2 node.
     pub async fn create_providers(
         &self,
-    ) -> Result<(ReqwestProvider, OnlineBlobProvider, ReqwestProvider)> {
-        let blob_provider = OnlineBlobProvider::new_http(
+    ) -> Result<(RootProvider<BoxTransport>, OnlineBlobProvider, RootProvider<BoxTransport>)> {
+        let beacon_client = OnlineBeaconClient::new_http_with_client(
             self.l1_beacon_address.clone().ok_or(anyhow!("Beacon API URL must be set"))?,
-        )
-        .await
-        .map_err(|e| anyhow!("Failed to load blob provider configuration: {e}"))?;
-        let l1_provider = Self::http_provider(
-            self.l1_node_address.as_ref().ok_or(anyhow!("Provider must be set"))?,
+            http_client(&self.l1_beacon_auth)?,
         );
-        let l2_provider = Self::http_provider(
+        let blob_provider = OnlineBlobProvider::new_with_client(Arc::new(beacon_client))
+            .await
+            .map_err(|e| anyhow!("Failed to load blob provider configuration: {e}"))?;
+        let l1_provider = Self::rpc_provider(
+            self.l1_node_address.as_ref().ok_or(anyhow!("Provider must be set"))?,
+            &self.l1_node_auth,
+        )
+        .await?;
+        let l2_provider = Self::rpc_provider(
             self.l2_node_address.as_ref().ok_or(anyhow!("L2 node address must be set"))?,
-        );
+            &self.l2_node_auth,
+        )
+        .await?;
 
         Ok((l1_provider, blob_provider, l2_provider))
     }
 
+    /// Returns the [RequestTimeouts] of the fetcher, with the defaults of
+    /// [RequestTimeouts::DEFAULT] for timeouts that are not set.
+    pub fn request_timeouts(&self) -> RequestTimeouts {
+        let secs = |timeout: Option<u64>, default| timeout.map_or(default, Duration::from_secs);
+        let defaults = RequestTimeouts::DEFAULT;
+        RequestTimeouts {
+            header: secs(self.header_timeout, defaults.header),
+            transactions: secs(self.transactions_timeout, defaults.transactions),
+            receipts: secs(self.receipts_timeout, defaults.receipts),
+            blob: secs(self.blob_timeout, defaults.blob),
+            state: secs(self.state_timeout, defaults.state),
+        }
+    }
+
+    /// Returns the [CircuitBreakerConfig] of the fetcher, with the defaults of
+    /// [CircuitBreakerConfig::DEFAULT] for options that are not set.
+    pub fn circuit_breaker_config(&self) -> CircuitBreakerConfig {
+        let defaults = CircuitBreakerConfig::DEFAULT;
+        CircuitBreakerConfig {
+            failure_threshold: self.circuit_failure_threshold.unwrap_or(defaults.failure_threshold),
+            cooldown: self.circuit_cooldown.map_or(defaults.cooldown, Duration::from_secs),
+        }
+    }
+
     /// Parses the CLI arguments and returns a new instance of a [SharedKeyValueStore], as it is
     /// configured to be created.
     pub fn construct_kv_store(&self) -> SharedKeyValueStore {
@@ -179,24 +301,32 @@ impl HostCli {
         kv_store
     }
 
-    /// Reads the [RollupConfig] from the file system and returns it as a string.
+    /// Reads the [RollupConfig] from the file system, and applies the `--cfg-override` overrides
+    /// to it.
     pub fn read_rollup_config(&self) -> Result<RollupConfig> {
         let path = self.rollup_config_path.as_ref().ok_or_else(|| {
             anyhow::anyhow!(
                 "No rollup config path provided. Please provide a path to the rollup config."
             )
         })?;
+        let mut config = read_rollup_config(path)?;
 
-        // Read the serialized config from the file system.
-        let ser_config = std::fs::read_to_string(path)
-            .map_err(|e| anyhow!("Error reading RollupConfig file: {e}"))?;
-
-        // Deserialize the config and return it.
-        serde_json::from_str(&ser_config)
-            .map_err(|e| anyhow!("Error deserializing RollupConfig: {e}"))
+        // Apply the overrides, if any, and validate the overridden config.
+        if !self.cfg_overrides.is_empty() {
+            RollupConfigOverride::apply_all(&self.cfg_overrides, &mut config)
+                .map_err(|e| anyhow!("Invalid rollup config overrides: {e}"))?;
+        }
+        Ok(config)
     }
 }
 
+/// Reads and deserializes the [RollupConfig] at the given path.
+pub fn read_rollup_config(path: &Path) -> Result<RollupConfig> {
+    let ser_config = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Error reading RollupConfig file: {e}"))?;
+    serde_json::from_str(&ser_config).map_err(|e| anyhow!("Error deserializing RollupConfig: {e}"))
+}
+
 /// Styles for the CLI application.
 const fn cli_styles() -> clap::builder::Styles {
     clap::builder::Styles::styled()
@@ -211,9 +341,13 @@ const fn cli_styles() -> clap::builder::Styles {
 
 #[cfg(test)]
 mod test {
-    use crate::HostCli;
+    use crate::{
+        fetcher::{CircuitBreakerConfig, RequestTimeouts},
+        HostCli,
+    };
     use alloy_primitives::B256;
     use clap::Parser;
+    use std::time::Duration;
 
     #[test]
     fn test_flags() {
@@ -253,8 +387,67 @@ mod test {
                 .as_slice(),
                 true,
             ),
+            (
+                [
+                    "--server",
+                    "--l2-chain-id",
+                    "0",
+                    "--data-dir",
+                    "dummy",
+                    "--l1-node-auth",
+                    "bearer:abc,header:X-Api-Key:123",
+                    "--l1-beacon-auth",
+                    "basic:user:pass",
+                ]
+                .as_slice(),
+                true,
+            ),
+            (
+                [
+                    "--server",
+                    "--rollup-config-path",
+                    "dummy",
+                    "--data-dir",
+                    "dummy",
+                    "--cfg-override",
+                    "seq_window_size=10,holocene_time=none",
+                ]
+                .as_slice(),
+                true,
+            ),
             // invalid
             (["--server", "--native", "--l2-chain-id", "0"].as_slice(), false),
+            (
+                [
+                    "--server",
+                    "--l2-chain-id",
+                    "0",
+                    "--data-dir",
+                    "dummy",
+                    "--cfg-override",
+                    "seq_window_size=10",
+                ]
+                .as_slice(),
+                false,
+            ),
+            (
+                [
+                    "--server",
+                    "--rollup-config-path",
+                    "dummy",
+                    "--data-dir",
+                    "dummy",
+                    "--cfg-override",
+                    "l2_chain_id=10",
+                ]
+                .as_slice(),
+                false,
+            ),
+            (
+                ["--server", "--l2-chain-id", "0", "--data-dir", "dummy", "--l2-node-auth", "abc"]
+                    .as_slice(),
+                false,
+            ),
             (["--l2-chain-id", "0", "--rollup-config-path", "dummy", "--server"].as_slice(), false),
             (["--server"].as_slice(), false),
             (["--native"].as_slice(), false),
@@ -273,4 +466,65 @@ mod test {
             assert_eq!(parsed.is_ok(), valid);
         }
     }
+
+    #[test]
+    fn test_request_timeouts() {
+        let zero_hash_str = &B256::ZERO.to_string();
+        let args = [
+            "host",
+            "--l1-head",
+            zero_hash_str,
+            "--l2-head",
+            zero_hash_str,
+            "--l2-output-root",
+            zero_hash_str,
+            "--l2-claim",
+            zero_hash_str,
+            "--l2-block-number",
+            "0",
+            "--server",
+            "--l2-chain-id",
+            "0",
+            "--data-dir",
+            "dummy",
+            "--blob-timeout",
+            "5",
+        ];
+        let cfg = HostCli::try_parse_from(args).unwrap();
+        assert_eq!(
+            cfg.request_timeouts(),
+            RequestTimeouts { blob: Duration::from_secs(5), ..RequestTimeouts::DEFAULT }
+        );
+        assert_eq!(cfg.circuit_breaker_config(), CircuitBreakerConfig::DEFAULT);
+    }
+
+    #[test]
+    fn test_circuit_breaker_config() {
+        let zero_hash_str = &B256::ZERO.to_string();
+        let args = [
+            "host",
+            "--l1-head",
+            zero_hash_str,
+            "--l2-head",
+            zero_hash_str,
+            "--l2-output-root",
+            zero_hash_str,
+            "--l2-claim",
+            zero_hash_str,
+            "--l2-block-number",
+            "0",
+            "--server",
+            "--l2-chain-id",
+            "0",
+            "--data-dir",
+            "dummy",
+            "--circuit-failure-threshold",
+            "10",
+        ];
+        let cfg = HostCli::try_parse_from(args).unwrap();
+        assert_eq!(
+            cfg.circuit_breaker_config(),
+            CircuitBreakerConfig { failure_threshold: 10, ..CircuitBreakerConfig::DEFAULT }
+        );
+    }
 }
@@ -0,0 +1,304 @@
+//! Overrides of the fields of the host's [RollupConfig], for devnets and fork rehearsals.
+
+use alloy_primitives::Address;
+use anyhow::{anyhow, bail, ensure, Result};
+use op_alloy_genesis::RollupConfig;
+use serde::Serialize;
+use std::{fmt, str::FromStr};
+
+/// A hardfork with an activation timestamp in the [RollupConfig].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Hardfork {
+    /// The Regolith hardfork.
+    Regolith,
+    /// The Canyon hardfork.
+    Canyon,
+    /// The Delta hardfork.
+    Delta,
+    /// The Ecotone hardfork.
+    Ecotone,
+    /// The Fjord hardfork.
+    Fjord,
+    /// The Granite hardfork.
+    Granite,
+    /// The Holocene hardfork.
+    Holocene,
+    /// The Isthmus hardfork.
+    Isthmus,
+}
+
+impl Hardfork {
+    /// All hardforks, in activation order.
+    pub const ALL: [Self; 8] = [
+        Self::Regolith,
+        Self::Canyon,
+        Self::Delta,
+        Self::Ecotone,
+        Self::Fjord,
+        Self::Granite,
+        Self::Holocene,
+        Self::Isthmus,
+    ];
+
+    /// Returns the name of the hardfork, as used in the `<name>_time` override keys.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Regolith => "regolith",
+            Self::Canyon => "canyon",
+            Self::Delta => "delta",
+            Self::Ecotone => "ecotone",
+            Self::Fjord => "fjord",
+            Self::Granite => "granite",
+            Self::Holocene => "holocene",
+            Self::Isthmus => "isthmus",
+        }
+    }
+
+    /// Returns the activation timestamp of the hardfork in the given [RollupConfig].
+    pub const fn time(&self, cfg: &RollupConfig) -> Option<u64> {
+        match self {
+            Self::Regolith => cfg.regolith_time,
+            Self::Canyon => cfg.canyon_time,
+            Self::Delta => cfg.delta_time,
+            Self::Ecotone => cfg.ecotone_time,
+            Self::Fjord => cfg.fjord_time,
+            Self::Granite => cfg.granite_time,
+            Self::Holocene => cfg.holocene_time,
+            Self::Isthmus => cfg.isthmus_time,
+        }
+    }
+
+    /// Returns a mutable reference to the activation timestamp of the hardfork in the given
+    /// [RollupConfig].
+    const fn time_mut(self, cfg: &mut RollupConfig) -> &mut Option<u64> {
+        match self {
+            Self::Regolith => &mut cfg.regolith_time,
+            Self::Canyon => &mut cfg.canyon_time,
+            Self::Delta => &mut cfg.delta_time,
+            Self::Ecotone => &mut cfg.ecotone_time,
+            Self::Fjord => &mut cfg.fjord_time,
+            Self::Granite => &mut cfg.granite_time,
+            Self::Holocene => &mut cfg.holocene_time,
+            Self::Isthmus => &mut cfg.isthmus_time,
+        }
+    }
+}
+
+/// An override of a field of the [RollupConfig].
+///
+/// Parsed from `<key>=<value>`, where the key is one of `batch_inbox_address`, `block_time`,
+/// `max_sequencer_drift`, `seq_window_size`, `channel_timeout`, `granite_channel_timeout`, or the
+/// `<hardfork>_time` activation timestamp of a [Hardfork], e.g. `holocene_time`. The activation
+/// timestamp of a hardfork is unset with `none`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RollupConfigOverride {
+    /// The address of the batch inbox.
+    BatchInboxAddress(Address),
+    /// The L2 block time, in seconds.
+    BlockTime(u64),
+    /// The maximum drift of the L2 timestamps from their L1 origin, in seconds.
+    MaxSequencerDrift(u64),
+    /// The sequencing window size, in L1 blocks.
+    SeqWindowSize(u64),
+    /// The channel timeout, in L1 blocks.
+    ChannelTimeout(u64),
+    /// The channel timeout after the Granite hardfork, in L1 blocks.
+    GraniteChannelTimeout(u64),
+    /// The activation timestamp of a hardfork.
+    ForkTime(Hardfork, Option<u64>),
+}
+
+impl RollupConfigOverride {
+    /// Applies the override to the given [RollupConfig].
+    pub const fn apply(&self, cfg: &mut RollupConfig) {
+        match *self {
+            Self::BatchInboxAddress(address) => cfg.batch_inbox_address = address,
+            Self::BlockTime(time) => cfg.block_time = time,
+            Self::MaxSequencerDrift(drift) => cfg.max_sequencer_drift = drift,
+            Self::SeqWindowSize(size) => cfg.seq_window_size = size,
+            Self::ChannelTimeout(timeout) => cfg.channel_timeout = timeout,
+            Self::GraniteChannelTimeout(timeout) => cfg.granite_channel_timeout = timeout,
+            Self::ForkTime(fork, time) => *fork.time_mut(cfg) = time,
+        }
+    }
+
+    /// Applies the given overrides to the [RollupConfig] in order, and validates the resulting
+    /// config.
+    pub fn apply_all(overrides: &[Self], cfg: &mut RollupConfig) -> Result<()> {
+        overrides.iter().for_each(|o| o.apply(cfg));
+        validate_rollup_config(cfg)
+    }
+}
+
+impl FromStr for RollupConfigOverride {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (key, value) =
+            s.split_once('=').ok_or_else(|| anyhow!("Expected `<key>=<value>` override"))?;
+        let (key, value) = (key.trim(), value.trim());
+        let u64_value = || {
+            value.parse::<u64>().map_err(|e| anyhow!("Invalid value `{value}` for `{key}`: {e}"))
+        };
+
+        Ok(match key {
+            "batch_inbox_address" => Self::BatchInboxAddress(
+                value.parse().map_err(|e| anyhow!("Invalid batch inbox address `{value}`: {e}"))?,
+            ),
+            "block_time" => Self::BlockTime(u64_value()?),
+            "max_sequencer_drift" => Self::MaxSequencerDrift(u64_value()?),
+            "seq_window_size" => Self::SeqWindowSize(u64_value()?),
+            "channel_timeout" => Self::ChannelTimeout(u64_value()?),
+            "granite_channel_timeout" => Self::GraniteChannelTimeout(u64_value()?),
+            _ => {
+                let fork = key
+                    .strip_suffix("_time")
+                    .and_then(|name| Hardfork::ALL.into_iter().find(|f| f.name() == name))
+                    .ok_or_else(|| anyhow!("Unknown rollup config override key `{key}`"))?;
+                let time = if value == "none" { None } else { Some(u64_value()?) };
+                Self::ForkTime(fork, time)
+            }
+        })
+    }
+}
+
+impl fmt::Display for RollupConfigOverride {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BatchInboxAddress(address) => write!(f, "batch_inbox_address={address}"),
+            Self::BlockTime(time) => write!(f, "block_time={time}"),
+            Self::MaxSequencerDrift(drift) => write!(f, "max_sequencer_drift={drift}"),
+            Self::SeqWindowSize(size) => write!(f, "seq_window_size={size}"),
+            Self::ChannelTimeout(timeout) => write!(f, "channel_timeout={timeout}"),
+            Self::GraniteChannelTimeout(timeout) => write!(f, "granite_channel_timeout={timeout}"),
+            Self::ForkTime(fork, Some(time)) => write!(f, "{}_time={time}", fork.name()),
+            Self::ForkTime(fork, None) => write!(f, "{}_time=none", fork.name()),
+        }
+    }
+}
+
+/// Validates the fields of the [RollupConfig] that can be overridden.
+///
+/// The batch inbox must be set, the block time, sequencing window and channel timeouts must be
+/// non-zero, and every hardfork must activate no earlier than the hardforks before it. A hardfork
+/// that is unset may not be followed by one that is set.
+pub fn validate_rollup_config(cfg: &RollupConfig) -> Result<()> {
+    ensure!(cfg.batch_inbox_address != Address::ZERO, "The batch inbox address must be set");
+    ensure!(cfg.block_time > 0, "The block time must be non-zero");
+    ensure!(cfg.seq_window_size > 0, "The sequencing window size must be non-zero");
+    ensure!(cfg.channel_timeout > 0, "The channel timeout must be non-zero");
+    ensure!(cfg.granite_channel_timeout > 0, "The Granite channel timeout must be non-zero");
+
+    let mut previous: Option<(Hardfork, Option<u64>)> = None;
+    for fork in Hardfork::ALL {
+        let time = fork.time(cfg);
+        if let Some((prev, prev_time)) = previous {
+            match (prev_time, time) {
+                (None, Some(_)) => {
+                    bail!("{} is scheduled, but {} is not", fork.name(), prev.name())
+                }
+                (Some(prev_time), Some(time)) if time < prev_time => bail!(
+                    "{} activates at {time}, before {} at {prev_time}",
+                    fork.name(),
+                    prev.name()
+                ),
+                _ => {}
+            }
+        }
+        previous = Some((fork, time));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloy_primitives::address;
+
+    const INBOX: Address = address!("ff00000000000000000000000000000000000010");
+
+    fn base_config() -> RollupConfig {
+        RollupConfig {
+            batch_inbox_address: INBOX,
+            block_time: 2,
+            seq_window_size: 3600,
+            channel_timeout: 300,
+            granite_channel_timeout: 50,
+            regolith_time: Some(0),
+            canyon_time: Some(0),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_rollup_config_override() {
+        assert_eq!(
+            "batch_inbox_address=0xff00000000000000000000000000000000000010"
+                .parse::<RollupConfigOverride>()
+                .unwrap(),
+            RollupConfigOverride::BatchInboxAddress(INBOX)
+        );
+        assert_eq!(
+            "seq_window_size=100".parse::<RollupConfigOverride>().unwrap(),
+            RollupConfigOverride::SeqWindowSize(100)
+        );
+        assert_eq!(
+            "holocene_time=1700000000".parse::<RollupConfigOverride>().unwrap(),
+            RollupConfigOverride::ForkTime(Hardfork::Holocene, Some(1700000000))
+        );
+        assert_eq!(
+            "isthmus_time=none".parse::<RollupConfigOverride>().unwrap(),
+            RollupConfigOverride::ForkTime(Hardfork::Isthmus, None)
+        );
+
+        for invalid in
+            ["seq_window_size", "seq_window_size=-1", "bedrock_time=0", "l2_chain_id=1", "time=0"]
+        {
+            assert!(invalid.parse::<RollupConfigOverride>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_rollup_config_override_display_roundtrip() {
+        for o in [
+            RollupConfigOverride::BatchInboxAddress(INBOX),
+            RollupConfigOverride::BlockTime(1),
+            RollupConfigOverride::ForkTime(Hardfork::Fjord, None),
+        ] {
+            assert_eq!(o.to_string().parse::<RollupConfigOverride>().unwrap(), o);
+        }
+    }
+
+    #[test]
+    fn test_apply_rollup_config_overrides() {
+        let mut cfg = base_config();
+        let overrides =
+            ["channel_timeout=10", "delta_time=5", "ecotone_time=5", "canyon_time=none"]
+                .map(|s| s.parse::<RollupConfigOverride>().unwrap());
+
+        // Canyon is unset, but the later hardforks are scheduled.
+        assert!(RollupConfigOverride::apply_all(&overrides, &mut cfg).is_err());
+
+        let mut cfg = base_config();
+        RollupConfigOverride::apply_all(&overrides[..3], &mut cfg).unwrap();
+        assert_eq!(cfg.channel_timeout, 10);
+        assert_eq!((cfg.delta_time, cfg.ecotone_time, cfg.fjord_time), (Some(5), Some(5), None));
+    }
+
+    #[test]
+    fn test_validate_rollup_config() {
+        assert!(validate_rollup_config(&base_config()).is_ok());
+        for invalid in [
+            RollupConfigOverride::BatchInboxAddress(Address::ZERO),
+            RollupConfigOverride::SeqWindowSize(0),
+            RollupConfigOverride::ChannelTimeout(0),
+            RollupConfigOverride::ForkTime(Hardfork::Canyon, Some(10)),
+        ] {
+            let mut cfg = base_config();
+            cfg.delta_time = Some(5);
+            assert!(RollupConfigOverride::apply_all(&[invalid], &mut cfg).is_err(), "{invalid}");
+        }
+    }
+}
@@ -2,21 +2,82 @@
 
 use anyhow::{anyhow, Result};
 use tracing::Level;
+use tracing_subscriber::{filter::LevelFilter, layer::SubscriberExt, Layer};
 
 /// Initializes the tracing subscriber
 ///
 /// # Arguments
 /// * `verbosity_level` - The verbosity level (0-2)
+/// * `otlp_endpoint` - The OTLP/HTTP endpoint to export spans to, if any. Requires the `otlp`
+///   feature.
 ///
 /// # Returns
 /// * `Result<()>` - Ok if successful, Err otherwise.
-pub fn init_tracing_subscriber(verbosity_level: u8) -> Result<()> {
-    let subscriber = tracing_subscriber::fmt()
-        .with_max_level(match verbosity_level {
-            0 => Level::INFO,
-            1 => Level::DEBUG,
-            _ => Level::TRACE,
-        })
-        .finish();
+pub fn init_tracing_subscriber(verbosity_level: u8, otlp_endpoint: Option<&str>) -> Result<()> {
+    let filter = LevelFilter::from_level(match verbosity_level {
+        0 => Level::INFO,
+        1 => Level::DEBUG,
+        _ => Level::TRACE,
+    });
+    let subscriber = tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_filter(filter))
+        .with(otlp_endpoint.map(|endpoint| otlp_layer(endpoint, filter)).transpose()?);
     tracing::subscriber::set_global_default(subscriber).map_err(|e| anyhow!(e))
 }
+
+/// Flushes and shuts down the OTLP exporter, if one was installed by [init_tracing_subscriber].
+#[cfg(feature = "otlp")]
+pub fn shutdown_tracing_subscriber() {
+    opentelemetry::global::shutdown_tracer_provider();
+}
+
+/// Flushes and shuts down the OTLP exporter, which is a no-op without the `otlp` feature.
+#[cfg(not(feature = "otlp"))]
+pub const fn shutdown_tracing_subscriber() {}
+
+/// Returns a layer exporting spans to the given OTLP/HTTP endpoint.
+#[cfg(feature = "otlp")]
+fn otlp_layer<S>(endpoint: &str, filter: LevelFilter) -> Result<impl Layer<S>>
+where
+    S: tracing::Subscriber + for<'span> tracing_subscriber::registry::LookupSpan<'span>,
+{
+    use opentelemetry::{trace::TracerProvider as _, KeyValue};
+    use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+    use opentelemetry_sdk::{runtime, trace::TracerProvider, Resource};
+
+    let exporter = SpanExporter::builder().with_http().with_endpoint(endpoint).build()?;
+    let provider = TracerProvider::builder()
+        .with_batch_exporter(exporter, runtime::Tokio)
+        .with_resource(Resource::new([KeyValue::new("service.name", "kona-host")]))
+        .build();
+    let tracer = provider.tracer("kona-host");
+    opentelemetry::global::set_tracer_provider(provider);
+    Ok(tracing_opentelemetry::layer().with_tracer(tracer).with_filter(filter))
+}
+
+/// Fails, as the host was built without the `otlp` feature.
+#[cfg(not(feature = "otlp"))]
+fn otlp_layer(_: &str, _: LevelFilter) -> Result<tracing_subscriber::layer::Identity> {
+    Err(anyhow!("exporting spans requires building the host with the `otlp` feature"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "otlp")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_otlp_layer() {
+        otlp_layer::<tracing_subscriber::Registry>(
+            "http://localhost:4318/v1/traces",
+            LevelFilter::INFO,
+        )
+        .unwrap();
+    }
+
+    #[cfg(not(feature = "otlp"))]
+    #[test]
+    fn test_otlp_layer_requires_feature() {
+        assert!(otlp_layer("http://localhost:4318/v1/traces", LevelFilter::INFO).is_err());
+    }
+}
@@ -0,0 +1,480 @@
+//! The `kona-diff` tool, which derives L2 blocks with kona and compares them, block by block,
+//! against the blocks derived by a rollup node.
+
+use crate::{
+    cli::{read_rollup_config, EndpointAuth, L1Args},
+    pipeline::{check_attributes, derive_next_attributes, new_online_pipeline, AttributeCheck},
+    providers::{OnlineL1ChainProvider, OnlineL2ChainProvider},
+    HostCli,
+};
+use alloy_primitives::{B256, U64};
+use alloy_provider::{Provider, RootProvider};
+use alloy_transport::BoxTransport;
+use anyhow::{anyhow, bail, Result};
+use clap::{ArgAction, Parser};
+use kona_derive::{
+    attributes::{attributes_from_block, l1_block_info_from_encoded_tx},
+    traits::OriginProvider,
+};
+use maili_protocol::{BatchValidationProvider, BlockInfo, L2BlockInfo};
+use op_alloy_rpc_types_engine::OpPayloadAttributes;
+use serde::Deserialize;
+use std::{fmt, path::PathBuf, sync::Arc};
+use tracing::info;
+
+const ABOUT: &str = "
+kona-diff derives L2 blocks with the kona derivation pipeline, starting from an L2 safe head, and
+compares them block by block against a rollup node: the derived attributes against the attributes
+of the blocks, and the L1 origins against the block references of the rollup node. Derivation
+stops at the first divergence, which is reported with its context, or at the safe head of the
+rollup node.
+";
+
+/// The `kona-diff` CLI application arguments.
+#[derive(Parser, Clone, Debug)]
+#[command(about = ABOUT, version)]
+pub struct DiffCli {
+    /// Verbosity level (0-2)
+    #[arg(long, short, action = ArgAction::Count)]
+    pub v: u8,
+    /// Number of the L2 block to start from. Its children are derived and compared.
+    #[clap(long)]
+    pub start_block: u64,
+    /// Number of L2 blocks to derive. Defaults to all blocks up to the safe head of the rollup
+    /// node.
+    #[clap(long)]
+    pub blocks: Option<u64>,
+    /// The L1 node and L1 Beacon API arguments.
+    #[command(flatten)]
+    pub l1: L1Args,
+    /// Address of L2 JSON-RPC endpoint to use. Either an HTTP(S) URL, or the path of an IPC
+    /// socket. The endpoint must serve `debug_getRawBlock`.
+    #[clap(long, visible_alias = "l2", env)]
+    pub l2_node_address: String,
+    /// Authentication for the L2 node, see `--l1-node-auth`.
+    #[clap(long, value_delimiter = ',', env)]
+    pub l2_node_auth: Vec<EndpointAuth>,
+    /// Address of the rollup node RPC endpoint to compare against, which must serve
+    /// `optimism_syncStatus` and `optimism_outputAtBlock`.
+    #[clap(long, visible_alias = "rollup", env)]
+    pub rollup_node_address: String,
+    /// Authentication for the rollup node, see `--l1-node-auth`.
+    #[clap(long, value_delimiter = ',', env)]
+    pub rollup_node_auth: Vec<EndpointAuth>,
+    /// Path to the rollup config of the L2 chain.
+    #[clap(long, alias = "rollup-cfg", env)]
+    pub rollup_config_path: PathBuf,
+}
+
+/// The ID of a block, as returned by the rollup node.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub struct RollupBlockId {
+    /// The block hash.
+    pub hash: B256,
+    /// The block number.
+    pub number: u64,
+}
+
+impl fmt::Display for RollupBlockId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.number, self.hash)
+    }
+}
+
+/// An L2 block reference, as returned by the rollup node.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RollupBlockRef {
+    /// The block hash.
+    pub hash: B256,
+    /// The block number.
+    pub number: u64,
+    /// The L1 origin of the block.
+    #[serde(rename = "l1origin")]
+    pub l1_origin: RollupBlockId,
+    /// The sequence number of the block within its epoch.
+    pub sequence_number: u64,
+}
+
+/// The part of the `optimism_syncStatus` response that is compared against.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub struct RollupSyncStatus {
+    /// The L2 safe head of the rollup node.
+    pub safe_l2: RollupBlockRef,
+}
+
+/// The part of the `optimism_outputAtBlock` response that is compared against.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RollupOutput {
+    /// The output root of the block.
+    pub output_root: B256,
+    /// The block the output root is for.
+    pub block_ref: RollupBlockRef,
+}
+
+/// An L2 block derived by kona and compared against the rollup node.
+#[derive(Debug, Clone)]
+pub struct BlockDiff {
+    /// The number of the block.
+    pub number: u64,
+    /// The L2 safe head the block was derived from.
+    pub l2_safe_head: L2BlockInfo,
+    /// The L1 origin of the pipeline when the block was derived.
+    pub derived_at: Option<BlockInfo>,
+    /// The output of the block, as returned by the rollup node.
+    pub output: RollupOutput,
+    /// The checks of the derived attributes and L1 origin.
+    pub checks: Vec<AttributeCheck>,
+    /// Why the block could not be derived, if it could not.
+    pub error: Option<String>,
+}
+
+impl BlockDiff {
+    /// Returns whether kona diverged from the rollup node on the block.
+    pub fn diverged(&self) -> bool {
+        self.error.is_some() || self.checks.iter().any(|check| !check.passed)
+    }
+}
+
+impl fmt::Display for BlockDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (safe_head, block_ref) = (&self.l2_safe_head, &self.output.block_ref);
+        writeln!(f, "L2 block {} ({})", self.number, block_ref.hash)?;
+        writeln!(
+            f,
+            "Derived from L2 safe head {} ({}), L1 origin {}",
+            safe_head.block_info.number, safe_head.block_info.hash, safe_head.l1_origin.number
+        )?;
+        if let Some(derived_at) = &self.derived_at {
+            writeln!(f, "Derived at L1 block {} ({})", derived_at.number, derived_at.hash)?;
+        }
+        writeln!(
+            f,
+            "Rollup node: L1 origin {}, sequence number {}, output root {}",
+            block_ref.l1_origin, block_ref.sequence_number, self.output.output_root
+        )?;
+        if let Some(error) = &self.error {
+            writeln!(f, "Derivation failed: {error}")?;
+        }
+        for check in &self.checks {
+            writeln!(f, "  {check}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The output of `kona-diff`.
+#[derive(Debug, Clone)]
+pub struct DiffReport {
+    /// The number of the L2 block derivation started from.
+    pub start_block: u64,
+    /// The number of blocks that matched the rollup node.
+    pub matched: u64,
+    /// The safe head of the rollup node when derivation started.
+    pub rollup_safe_head: RollupBlockRef,
+    /// The first block on which kona diverged from the rollup node, if any.
+    pub divergence: Option<BlockDiff>,
+}
+
+impl fmt::Display for DiffReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (first, last) = (self.start_block + 1, self.start_block + self.matched);
+        if self.matched > 0 {
+            writeln!(f, "L2 blocks {first}-{last} match the rollup node")?;
+        }
+        match &self.divergence {
+            Some(diff) => write!(f, "\nFirst divergence:\n{diff}"),
+            None => writeln!(
+                f,
+                "No divergence up to L2 block {last}, rollup node safe head {}",
+                self.rollup_safe_head.number
+            ),
+        }
+    }
+}
+
+/// Checks the L1 origin of the derived attributes against the L1 origin reported by the rollup
+/// node.
+pub fn check_l1_origin(
+    derived: &OpPayloadAttributes,
+    block_ref: &RollupBlockRef,
+) -> AttributeCheck {
+    let derived_origin = derived
+        .transactions
+        .as_ref()
+        .and_then(|txs| txs.first())
+        .and_then(|tx| l1_block_info_from_encoded_tx(tx).ok())
+        .map(|l1_info| RollupBlockId { hash: l1_info.id().hash, number: l1_info.id().number });
+    AttributeCheck {
+        name: "l1Origin",
+        passed: derived_origin == Some(block_ref.l1_origin),
+        derived: derived_origin.map_or_else(|| "none".to_string(), |origin| origin.to_string()),
+        block: block_ref.l1_origin.to_string(),
+    }
+}
+
+/// Sends a request to the rollup node.
+async fn rollup_request<P, R>(
+    provider: &RootProvider<BoxTransport>,
+    method: &'static str,
+    params: P,
+) -> Result<R>
+where
+    P: serde::Serialize + Clone + Send + Sync + Unpin + fmt::Debug + 'static,
+    R: serde::de::DeserializeOwned + fmt::Debug + Send + Sync + Unpin + 'static,
+{
+    provider
+        .client()
+        .request(method, params)
+        .await
+        .map_err(|e| anyhow!("Failed to call {method} on the rollup node: {e}"))
+}
+
+/// Derives the L2 blocks of the [DiffCli] and compares them against the rollup node.
+///
+/// Derivation starts one channel timeout before the L1 origin of the start block, as when syncing
+/// from it. After each block, the derived attributes are compared with the attributes of the
+/// block read from the L2 node, and the L1 origin with the block reference of the rollup node.
+/// When they match, the block becomes the L2 safe head the next block is derived from.
+pub async fn diff(cfg: &DiffCli) -> Result<DiffReport> {
+    let rollup_config = Arc::new(read_rollup_config(&cfg.rollup_config_path)?);
+    let l1_provider = OnlineL1ChainProvider::new(cfg.l1.l1_provider().await?);
+    let mut l2_provider = OnlineL2ChainProvider::new(
+        HostCli::rpc_provider(&cfg.l2_node_address, &cfg.l2_node_auth).await?,
+        rollup_config.clone(),
+    );
+    let rollup_provider =
+        HostCli::rpc_provider(&cfg.rollup_node_address, &cfg.rollup_node_auth).await?;
+    let blob_provider = cfg
+        .l1
+        .blob_provider()
+        .await?
+        .ok_or_else(|| anyhow!("Deriving blocks requires --l1-beacon-address"))?;
+
+    let status: RollupSyncStatus =
+        rollup_request(&rollup_provider, "optimism_syncStatus", ()).await?;
+    let rollup_safe_head = status.safe_l2;
+    if cfg.start_block >= rollup_safe_head.number {
+        bail!(
+            "Start block {} is not behind the rollup node safe head {}",
+            cfg.start_block,
+            rollup_safe_head.number
+        );
+    }
+    let end = cfg
+        .blocks
+        .map_or(rollup_safe_head.number, |blocks| cfg.start_block.saturating_add(blocks))
+        .min(rollup_safe_head.number);
+
+    let mut l2_safe_head = l2_provider.l2_block_info_by_number(cfg.start_block).await?;
+    let mut pipeline = new_online_pipeline(
+        rollup_config.clone(),
+        l1_provider,
+        l2_provider.clone(),
+        blob_provider,
+        l2_safe_head,
+    )
+    .await?;
+
+    let mut report =
+        DiffReport { start_block: cfg.start_block, matched: 0, rollup_safe_head, divergence: None };
+    for number in cfg.start_block + 1..=end {
+        let output: RollupOutput =
+            rollup_request(&rollup_provider, "optimism_outputAtBlock", (U64::from(number),))
+                .await?;
+        let block = l2_provider.block_by_number(number).await?;
+        let block_info = L2BlockInfo::from_block_and_genesis(&block, &rollup_config.genesis)
+            .map_err(|e| anyhow!("Failed to read the L1 origin of L2 block {number}: {e}"))?;
+        if output.block_ref.hash != block_info.block_info.hash {
+            bail!(
+                "The rollup node and the L2 node disagree on L2 block {number}: {} and {}",
+                output.block_ref.hash,
+                block_info.block_info.hash
+            );
+        }
+
+        // The child's batch is included at the latest one sequencing window after its epoch,
+        // which is at most the epoch after the safe head's.
+        let max_origin =
+            l2_safe_head.l1_origin.number.saturating_add(rollup_config.seq_window_size + 1);
+        let mut diff = BlockDiff {
+            number,
+            l2_safe_head,
+            derived_at: None,
+            output,
+            checks: Vec::new(),
+            error: None,
+        };
+        match derive_next_attributes(&mut pipeline, l2_safe_head, max_origin).await {
+            Ok(derived) => {
+                let expected = attributes_from_block(&rollup_config, &block)
+                    .map_err(|e| anyhow!("Failed to read the attributes of block {number}: {e}"))?;
+                diff.derived_at = pipeline.origin();
+                diff.checks = check_attributes(&derived.attributes, &expected);
+                diff.checks.push(check_l1_origin(&derived.attributes, &output.block_ref));
+            }
+            Err(e) => diff.error = Some(e.to_string()),
+        }
+        if diff.diverged() {
+            report.divergence = Some(diff);
+            break;
+        }
+
+        info!(
+            target: "kona_diff",
+            "L2 block {number} matches, derived at L1 block {}",
+            diff.derived_at.map(|origin| origin.number).unwrap_or_default()
+        );
+        report.matched += 1;
+        l2_safe_head = block_info;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloy_primitives::Bytes;
+    use alloy_rpc_types_engine::PayloadAttributes;
+    use maili_protocol::L1BlockInfoTx;
+
+    #[test]
+    fn test_flags() {
+        let base = ["kona-diff", "--l1", "dummy", "--l2", "dummy", "--rollup", "dummy"];
+        let cases = [
+            (["--start-block", "10", "--rollup-config-path", "dummy"].as_slice(), true),
+            (
+                [
+                    "--start-block",
+                    "10",
+                    "--blocks",
+                    "5",
+                    "--rollup-cfg",
+                    "dummy",
+                    "--beacon",
+                    "dummy",
+                ]
+                .as_slice(),
+                true,
+            ),
+            (
+                [
+                    "--start-block",
+                    "10",
+                    "--rollup-config-path",
+                    "dummy",
+                    "--rollup-node-auth",
+                    "bearer:abc",
+                ]
+                .as_slice(),
+                true,
+            ),
+            (["--rollup-config-path", "dummy"].as_slice(), false),
+            (["--start-block", "latest", "--rollup-config-path", "dummy"].as_slice(), false),
+        ];
+        for (args_ext, valid) in cases {
+            let args = base.iter().chain(args_ext.iter()).cloned().collect::<Vec<_>>();
+            assert_eq!(DiffCli::try_parse_from(args).is_ok(), valid);
+        }
+    }
+
+    #[test]
+    fn test_deserialize_rollup_responses() {
+        let block_ref = r#"{
+            "hash": "0x0101010101010101010101010101010101010101010101010101010101010101",
+            "number": 10,
+            "parentHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+            "timestamp": 20,
+            "l1origin": {
+                "hash": "0x0202020202020202020202020202020202020202020202020202020202020202",
+                "number": 5
+            },
+            "sequenceNumber": 1
+        }"#;
+        let expected = RollupBlockRef {
+            hash: B256::repeat_byte(0x01),
+            number: 10,
+            l1_origin: RollupBlockId { hash: B256::repeat_byte(0x02), number: 5 },
+            sequence_number: 1,
+        };
+
+        let status = format!(r#"{{"current_l1": {{}}, "safe_l2": {block_ref}}}"#);
+        let status: RollupSyncStatus = serde_json::from_str(&status).unwrap();
+        assert_eq!(status.safe_l2, expected);
+
+        let output = format!(
+            r#"{{
+                "version": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                "outputRoot": "0x0303030303030303030303030303030303030303030303030303030303030303",
+                "blockRef": {block_ref}
+            }}"#
+        );
+        let output: RollupOutput = serde_json::from_str(&output).unwrap();
+        assert_eq!(
+            output,
+            RollupOutput { output_root: B256::repeat_byte(0x03), block_ref: expected }
+        );
+    }
+
+    #[test]
+    fn test_report() {
+        let diff = BlockDiff {
+            number: 11,
+            l2_safe_head: Default::default(),
+            derived_at: None,
+            output: Default::default(),
+            checks: vec![AttributeCheck::equal("gasLimit", 1, 1)],
+            error: None,
+        };
+        assert!(!diff.diverged());
+        let failed =
+            BlockDiff { checks: vec![AttributeCheck::equal("gasLimit", 1, 2)], ..diff.clone() };
+        assert!(failed.diverged());
+        assert!(BlockDiff { error: Some("Derivation failed".to_string()), ..diff }.diverged());
+
+        let mut report = DiffReport {
+            start_block: 10,
+            matched: 3,
+            rollup_safe_head: RollupBlockRef { number: 13, ..Default::default() },
+            divergence: None,
+        };
+        assert_eq!(
+            report.to_string(),
+            "L2 blocks 11-13 match the rollup node\nNo divergence up to L2 block 13, rollup node safe \
+             head 13\n"
+        );
+        report.matched = 0;
+        report.divergence = Some(failed);
+        let report = report.to_string();
+        assert!(report.starts_with("\nFirst divergence:\nL2 block 11"));
+        assert!(report.ends_with("  MISMATCH gasLimit: derived 1, block 2\n"));
+    }
+
+    #[test]
+    fn test_check_l1_origin() {
+        let l1_info = L1BlockInfoTx::Bedrock(Default::default());
+        let deposit = op_alloy_consensus::TxDeposit {
+            input: l1_info.encode_calldata(),
+            ..Default::default()
+        };
+        let encoded = alloy_eips::eip2718::Encodable2718::encoded_2718(
+            &op_alloy_consensus::OpTxEnvelope::Deposit(alloy_primitives::Sealed::new(deposit)),
+        );
+        let derived = OpPayloadAttributes {
+            payload_attributes: PayloadAttributes::default(),
+            transactions: Some(vec![Bytes::from(encoded)]),
+            ..Default::default()
+        };
+        let mut block_ref = RollupBlockRef::default();
+        assert!(check_l1_origin(&derived, &block_ref).passed);
+
+        block_ref.l1_origin.number = 1;
+        let check = check_l1_origin(&derived, &block_ref);
+        assert!(!check.passed);
+        assert_eq!(check.name, "l1Origin");
+
+        let check = check_l1_origin(&OpPayloadAttributes::default(), &block_ref);
+        assert_eq!(check.derived, "none");
+    }
+}
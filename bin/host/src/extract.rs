@@ -0,0 +1,416 @@
+//! The `kona-extract` tool, which extracts the frames, channels and batches carried by an L1
+//! batcher transaction.
+
+use crate::{
+    blobs::OnlineBlobProvider,
+    cli::{parse_b256, read_rollup_config, L1Args},
+};
+use alloy_consensus::{Transaction as _, TxEnvelope};
+use alloy_eips::eip4844::IndexedBlobHash;
+use alloy_primitives::{Address, Bytes, FixedBytes, B256};
+use alloy_provider::{Provider, RootProvider};
+use alloy_rpc_types::{BlockNumberOrTag, BlockTransactions, BlockTransactionsKind};
+use alloy_transport::BoxTransport;
+use anyhow::{anyhow, Result};
+use clap::{ArgAction, Parser};
+use kona_derive::{
+    provenance::{FrameRef, L1TxRef},
+    replay::ChannelReplay,
+    sources::BlobChannelData,
+    types::{ChannelId, SingleBatchDef, SpanBatchElementDef},
+};
+use maili_protocol::{Batch, BlockInfo, Frame, SingleBatch, SpanBatchElement, CHANNEL_ID_LENGTH};
+use op_alloy_genesis::RollupConfig;
+use serde::Serialize;
+use std::{collections::BTreeMap, path::PathBuf};
+use tracing::{info, warn};
+
+const ABOUT: &str = "
+kona-extract fetches an L1 batcher transaction, decodes the frames it carries from its calldata or
+blobs, reassembles their channels, fetching the other batcher transactions of the channels from
+the surrounding L1 blocks, and dumps the decoded batches as JSON.
+";
+
+/// The `kona-extract` CLI application arguments.
+#[derive(Parser, Clone, Debug)]
+#[command(about = ABOUT, version)]
+pub struct ExtractCli {
+    /// Verbosity level (0-2)
+    #[arg(long, short, action = ArgAction::Count)]
+    pub v: u8,
+    /// Hash of the L1 batcher transaction to extract.
+    #[clap(long, value_parser = parse_b256)]
+    pub tx: B256,
+    /// The L1 node and L1 Beacon API arguments.
+    #[command(flatten)]
+    pub l1: L1Args,
+    /// Path to the rollup config of the L2 chain.
+    #[clap(long, alias = "rollup-cfg", env)]
+    pub rollup_config_path: PathBuf,
+    /// Number of L1 blocks before and after the transaction's block that are searched for the
+    /// other frames of its channels. Defaults to the channel timeout.
+    #[clap(long)]
+    pub scan_blocks: Option<u64>,
+}
+
+/// A decoded batch, as reported by `kona-extract`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum BatchReport {
+    /// A single batch.
+    Single(#[serde(with = "SingleBatchDef")] SingleBatch),
+    /// A span batch, with its L2 blocks.
+    #[serde(rename_all = "camelCase")]
+    Span {
+        /// The first 20 bytes of the parent hash of the first L2 block.
+        parent_check: FixedBytes<20>,
+        /// The first 20 bytes of the L1 origin hash of the last L2 block.
+        l1_origin_check: FixedBytes<20>,
+        /// The L2 blocks of the span batch.
+        blocks: Vec<SpanBlockReport>,
+    },
+}
+
+/// An L2 block of a span batch.
+#[derive(Debug, Clone, Serialize)]
+pub struct SpanBlockReport(#[serde(with = "SpanBatchElementDef")] pub SpanBatchElement);
+
+impl From<Batch> for BatchReport {
+    fn from(batch: Batch) -> Self {
+        match batch {
+            Batch::Single(batch) => Self::Single(batch),
+            Batch::Span(batch) => Self::Span {
+                parent_check: batch.parent_check,
+                l1_origin_check: batch.l1_origin_check,
+                blocks: batch.batches.into_iter().map(SpanBlockReport).collect(),
+            },
+        }
+    }
+}
+
+/// A channel with frames carried by the extracted transaction.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelReport {
+    /// The channel ID.
+    pub id: ChannelId,
+    /// The frames of the channel that were found, ordered by frame number.
+    pub frames: Vec<FrameRef>,
+    /// The batches decoded from the channel.
+    pub batches: Vec<BatchReport>,
+    /// Why the channel could not be decoded, if it could not.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The output of `kona-extract`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtractReport {
+    /// The extracted transaction.
+    pub tx: L1TxRef,
+    /// The sender of the extracted transaction.
+    pub sender: Address,
+    /// The channels with frames carried by the transaction.
+    pub channels: Vec<ChannelReport>,
+}
+
+/// A batcher transaction and the payloads it carries, its calldata or its decoded blobs.
+#[derive(Debug, Clone)]
+pub(crate) struct BatcherTx {
+    pub(crate) tx: L1TxRef,
+    pub(crate) payloads: Vec<Bytes>,
+}
+
+/// The frames of the extracted channels, and the transactions that carried them.
+#[derive(Debug, Default)]
+struct ChannelFrames {
+    channels: BTreeMap<[u8; CHANNEL_ID_LENGTH], Vec<(Frame, L1TxRef)>>,
+}
+
+impl ChannelFrames {
+    /// Tracks the channels of the given frames.
+    fn new(frames: &[Frame]) -> Self {
+        let channels = frames.iter().map(|f| (f.id, Vec::new())).collect();
+        Self { channels }
+    }
+
+    /// Adds the frames of tracked channels carried by a batcher transaction. Frames that were
+    /// already added, e.g. by a resubmission of the transaction, are ignored.
+    fn add(&mut self, tx: L1TxRef, frames: Vec<Frame>) {
+        for frame in frames {
+            let Some(channel) = self.channels.get_mut(&frame.id) else {
+                continue;
+            };
+            if channel.iter().all(|(f, _)| f.number != frame.number) {
+                channel.push((frame, tx));
+            }
+        }
+    }
+
+    /// Adds the frames of tracked channels carried by the given batcher transactions, skipping
+    /// transactions whose frames fail to parse.
+    fn add_batcher_txs(&mut self, txs: Vec<BatcherTx>) {
+        for tx in txs {
+            match parse_frames(&tx) {
+                Ok(frames) => self.add(tx.tx, frames),
+                Err(e) => warn!(target: "kona_extract", "{e}"),
+            }
+        }
+    }
+
+    /// Returns whether all frames of every tracked channel were added.
+    fn is_complete(&self) -> bool {
+        self.channels.values().all(|frames| {
+            frames
+                .iter()
+                .find(|(f, _)| f.is_last)
+                .is_some_and(|(last, _)| frames.len() == last.number as usize + 1)
+        })
+    }
+
+    /// Decodes the batches of the tracked channels.
+    fn report(self, cfg: &RollupConfig) -> Vec<ChannelReport> {
+        self.channels
+            .into_iter()
+            .map(|(id, mut frames)| {
+                frames.sort_by_key(|(f, _)| f.number);
+                let refs = frames
+                    .iter()
+                    .map(|(f, tx)| FrameRef { number: f.number, size: f.data.len(), tx: *tx })
+                    .collect();
+                let frames = frames.into_iter().map(|(f, _)| f).collect();
+                let (batches, error) = match ChannelReplay::from_decoded_frames(frames)
+                    .and_then(|replay| replay.decode_batches(cfg))
+                {
+                    Ok(batches) => (batches.into_iter().map(Into::into).collect(), None),
+                    Err(e) => (Vec::new(), Some(e.to_string())),
+                };
+                ChannelReport { id: id.into(), frames: refs, batches, error }
+            })
+            .collect()
+    }
+}
+
+/// Fetches the batcher transactions of an L1 block.
+#[derive(Debug)]
+pub(crate) struct BatcherTxFetcher {
+    pub(crate) l1_provider: RootProvider<BoxTransport>,
+    pub(crate) blob_provider: Option<OnlineBlobProvider>,
+    pub(crate) batch_inbox_address: Address,
+    pub(crate) sender: Address,
+}
+
+impl BatcherTxFetcher {
+    /// Returns the transactions of the L1 block with the given number that were sent to the
+    /// batch inbox by the sender, or none if the block does not exist yet.
+    pub(crate) async fn block_txs(&self, number: u64) -> Result<Vec<BatcherTx>> {
+        let Some(block) = self
+            .l1_provider
+            .get_block_by_number(BlockNumberOrTag::Number(number), BlockTransactionsKind::Full)
+            .await
+            .map_err(|e| anyhow!("Failed to fetch block {number}: {e}"))?
+        else {
+            return Ok(Vec::new());
+        };
+        let BlockTransactions::Full(txs) = block.transactions else {
+            anyhow::bail!("Only BlockTransactions::Full are supported.");
+        };
+        let block_info = BlockInfo {
+            hash: block.header.hash,
+            number: block.header.number,
+            parent_hash: block.header.parent_hash,
+            timestamp: block.header.timestamp,
+        };
+
+        let mut batcher_txs = Vec::new();
+        let mut blob_index = 0;
+        for (index, tx) in txs.into_iter().enumerate() {
+            let from = tx.from;
+            let envelope: TxEnvelope = tx.into();
+            let blob_hashes = envelope.blob_versioned_hashes().unwrap_or_default().to_vec();
+            let first_blob = blob_index;
+            blob_index += blob_hashes.len() as u64;
+            if envelope.to() != Some(self.batch_inbox_address) || from != self.sender {
+                continue;
+            }
+
+            let tx = L1TxRef { block: block_info, hash: *envelope.tx_hash(), index: index as u64 };
+            let payloads = if blob_hashes.is_empty() {
+                vec![envelope.input().clone()]
+            } else {
+                self.blobs(&tx, first_blob, blob_hashes).await?
+            };
+            batcher_txs.push(BatcherTx { tx, payloads });
+        }
+        Ok(batcher_txs)
+    }
+
+    /// Fetches and decodes the blobs of a batcher transaction, the first of which has the given
+    /// index in its block.
+    async fn blobs(&self, tx: &L1TxRef, first: u64, hashes: Vec<B256>) -> Result<Vec<Bytes>> {
+        let blob_provider = self.blob_provider.as_ref().ok_or_else(|| {
+            anyhow!("Transaction {} carries blobs, which requires --l1-beacon-address", tx.hash)
+        })?;
+        let hashes = hashes
+            .into_iter()
+            .zip(first..)
+            .map(|(hash, index)| IndexedBlobHash { index, hash })
+            .collect::<Vec<_>>();
+        let sidecars = blob_provider
+            .fetch_filtered_sidecars(&tx.block, &hashes)
+            .await
+            .map_err(|e| anyhow!("Failed to fetch blob sidecars of {}: {e}", tx.hash))?;
+        sidecars
+            .iter()
+            .map(|sidecar| {
+                sidecar
+                    .blob
+                    .decode_channel_data()
+                    .map_err(|e| anyhow!("Failed to decode blob {}: {e}", sidecar.index))
+            })
+            .collect()
+    }
+}
+
+/// Parses the frames of the payloads of a batcher transaction.
+pub(crate) fn parse_frames(tx: &BatcherTx) -> Result<Vec<Frame>> {
+    let mut frames = Vec::new();
+    for payload in &tx.payloads {
+        frames.extend(
+            Frame::parse_frames(payload)
+                .map_err(|e| anyhow!("Failed to parse the frames of {}: {e}", tx.tx.hash))?,
+        );
+    }
+    Ok(frames)
+}
+
+/// Extracts the channels and batches carried by the batcher transaction of the [ExtractCli].
+///
+/// The other frames of the transaction's channels are searched in the transactions sent to the
+/// batch inbox by the same sender, in the L1 blocks around the transaction's block. The sender is
+/// not checked against the batcher address of the rollup config, which may have been rotated.
+pub async fn extract(cfg: &ExtractCli) -> Result<ExtractReport> {
+    let rollup_config = read_rollup_config(&cfg.rollup_config_path)?;
+    let l1_provider = cfg.l1.l1_provider().await?;
+    let blob_provider = cfg.l1.blob_provider().await?;
+
+    let tx = l1_provider
+        .get_transaction_by_hash(cfg.tx)
+        .await
+        .map_err(|e| anyhow!("Failed to fetch transaction {}: {e}", cfg.tx))?
+        .ok_or_else(|| anyhow!("Transaction {} not found", cfg.tx))?;
+    let block_number =
+        tx.block_number.ok_or_else(|| anyhow!("Transaction {} is pending", cfg.tx))?;
+    let fetcher = BatcherTxFetcher {
+        l1_provider,
+        blob_provider,
+        batch_inbox_address: rollup_config.batch_inbox_address,
+        sender: tx.from,
+    };
+
+    let block_txs = fetcher.block_txs(block_number).await?;
+    let target =
+        block_txs.iter().find(|batcher_tx| batcher_tx.tx.hash == cfg.tx).ok_or_else(|| {
+            anyhow!(
+                "Transaction {} is not sent to the batch inbox {}",
+                cfg.tx,
+                rollup_config.batch_inbox_address
+            )
+        })?;
+    let target_tx = target.tx;
+    let frames = parse_frames(target)?;
+    info!(target: "kona_extract", "Transaction {} carries {} frames", cfg.tx, frames.len());
+    let mut channels = ChannelFrames::new(&frames);
+    channels.add_batcher_txs(block_txs);
+
+    // Search the surrounding blocks for the other frames, closest blocks first.
+    let scan_blocks =
+        cfg.scan_blocks.unwrap_or_else(|| rollup_config.channel_timeout(target_tx.block.timestamp));
+    for distance in 1..=scan_blocks {
+        if channels.is_complete() {
+            break;
+        }
+        let numbers = [block_number.checked_sub(distance), block_number.checked_add(distance)];
+        for number in numbers.into_iter().flatten() {
+            channels.add_batcher_txs(fetcher.block_txs(number).await?);
+        }
+    }
+
+    Ok(ExtractReport {
+        tx: target_tx,
+        sender: fetcher.sender,
+        channels: channels.report(&rollup_config),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use kona_derive::test_utils::{new_compressed_batch_data, tx_ref};
+
+    #[test]
+    fn test_flags() {
+        let base = ["kona-extract", "--tx", &B256::ZERO.to_string(), "--l1", "dummy"];
+        let cases = [
+            (["--rollup-config-path", "dummy"].as_slice(), true),
+            (["--rollup-cfg", "dummy", "--beacon", "dummy", "--scan-blocks", "5"].as_slice(), true),
+            ([].as_slice(), false),
+            (["--rollup-config-path", "dummy", "--l1-node-auth", "abc"].as_slice(), false),
+        ];
+        for (args_ext, valid) in cases {
+            let args = base.iter().chain(args_ext.iter()).cloned().collect::<Vec<_>>();
+            assert_eq!(ExtractCli::try_parse_from(args).is_ok(), valid);
+        }
+    }
+
+    #[test]
+    fn test_channel_frames_multi_tx() {
+        let data = new_compressed_batch_data();
+        let (first, second) = data.split_at(data.len() / 2);
+        let id = [0xAA; 16];
+
+        let target = vec![Frame::new(id, 1, second.to_vec(), true)];
+        let mut channels = ChannelFrames::new(&target);
+        channels.add(tx_ref(2, 0), target);
+        assert!(!channels.is_complete());
+
+        // Frames of other channels are ignored, and resubmitted frames are deduplicated.
+        channels.add(tx_ref(1, 0), vec![Frame::new([0xBB; 16], 0, vec![0x01], true)]);
+        channels.add(tx_ref(1, 0), vec![Frame::new(id, 0, first.to_vec(), false)]);
+        channels.add(tx_ref(3, 0), vec![Frame::new(id, 0, first.to_vec(), false)]);
+        assert!(channels.is_complete());
+
+        let report = channels.report(&RollupConfig::default());
+        assert_eq!(report.len(), 1);
+        assert_eq!(report[0].id, ChannelId(id));
+        assert_eq!(
+            report[0].frames,
+            [
+                FrameRef { number: 0, size: first.len(), tx: tx_ref(1, 0) },
+                FrameRef { number: 1, size: second.len(), tx: tx_ref(2, 0) },
+            ]
+        );
+        assert_eq!(report[0].batches.len(), 1);
+        assert!(report[0].error.is_none());
+
+        let json = serde_json::to_value(&report[0]).unwrap();
+        assert_eq!(json["batches"][0]["type"], "span");
+        assert!(json.get("error").is_none());
+    }
+
+    #[test]
+    fn test_channel_frames_incomplete() {
+        let target = vec![Frame::new([0xAA; 16], 1, vec![0x01], true)];
+        let mut channels = ChannelFrames::new(&target);
+        channels.add(tx_ref(1, 0), target);
+        assert!(!channels.is_complete());
+
+        let report = channels.report(&RollupConfig::default());
+        assert!(report[0].batches.is_empty());
+        assert_eq!(
+            report[0].error.as_deref(),
+            Some("Channel 0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa is incomplete")
+        );
+    }
+}
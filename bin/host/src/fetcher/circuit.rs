@@ -0,0 +1,287 @@
+//! A circuit breaker for the remote endpoints of the [Fetcher].
+//!
+//! When an endpoint fails consistently, e.g. because the node is down or rate limiting the host,
+//! retrying every hint immediately only produces a tight loop of failing requests. After a number
+//! of consecutive failures, the circuit of the endpoint is opened, and requests to it are rejected
+//! with [ProviderUnhealthy] until a cooldown elapses. The next request after the cooldown probes
+//! the endpoint: a success closes the circuit, and a failure opens it for another cooldown.
+//!
+//! [Fetcher]: super::Fetcher
+
+use kona_proof::HintType;
+use std::{
+    fmt,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use tracing::{info, warn};
+
+/// The remote endpoints of the [Fetcher], which each have their own circuit.
+///
+/// [Fetcher]: super::Fetcher
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endpoint {
+    /// The L1 execution client.
+    L1,
+    /// The L1 beacon node.
+    Beacon,
+    /// The L2 execution client.
+    L2,
+}
+
+impl Endpoint {
+    /// Returns the [Endpoint] that serves the given [HintType], or [None] if the hint is served
+    /// locally.
+    pub const fn for_hint(hint_type: HintType) -> Option<Self> {
+        match hint_type {
+            HintType::L1BlockHeader | HintType::L1Transactions | HintType::L1Receipts => {
+                Some(Self::L1)
+            }
+            HintType::L1Blob => Some(Self::Beacon),
+            HintType::L1Precompile => None,
+            HintType::L2BlockHeader |
+            HintType::L2Transactions |
+            HintType::L2Code |
+            HintType::StartingL2Output |
+            HintType::L2StateNode |
+            HintType::L2AccountProof |
+            HintType::L2AccountStorageProof |
+            HintType::L2PayloadWitness => Some(Self::L2),
+        }
+    }
+
+    /// Returns the index of the circuit of the endpoint.
+    const fn index(self) -> usize {
+        match self {
+            Self::L1 => 0,
+            Self::Beacon => 1,
+            Self::L2 => 2,
+        }
+    }
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::L1 => write!(f, "L1"),
+            Self::Beacon => write!(f, "beacon"),
+            Self::L2 => write!(f, "L2"),
+        }
+    }
+}
+
+/// The configuration of the [CircuitBreaker].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitBreakerConfig {
+    /// The number of consecutive failures after which the circuit of an endpoint is opened.
+    pub failure_threshold: u32,
+    /// The duration for which an opened circuit rejects requests.
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl CircuitBreakerConfig {
+    /// The default configuration.
+    pub const DEFAULT: Self = Self { failure_threshold: 5, cooldown: Duration::from_secs(30) };
+}
+
+/// A request that was rejected because the circuit of its endpoint is open.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProviderUnhealthy {
+    /// The unhealthy endpoint.
+    pub endpoint: Endpoint,
+    /// The remaining cooldown of the circuit.
+    pub retry_in: Duration,
+}
+
+impl fmt::Display for ProviderUnhealthy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "The {} provider is unhealthy, retrying in {:?}", self.endpoint, self.retry_in)
+    }
+}
+
+impl std::error::Error for ProviderUnhealthy {}
+
+/// The statistics of the circuit of an [Endpoint].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitStats {
+    /// The number of consecutive failed requests.
+    pub consecutive_failures: u32,
+    /// The number of times the circuit was opened.
+    pub opened: u64,
+    /// The number of requests rejected while the circuit was open.
+    pub rejected: u64,
+}
+
+/// The state of the circuit of an [Endpoint].
+#[derive(Debug, Clone, Copy)]
+struct Circuit {
+    /// The statistics of the circuit.
+    stats: CircuitStats,
+    /// The instant until which the circuit is open, if it was opened.
+    open_until: Option<Instant>,
+}
+
+impl Circuit {
+    /// A closed circuit without any recorded requests.
+    const CLOSED: Self = Self {
+        stats: CircuitStats { consecutive_failures: 0, opened: 0, rejected: 0 },
+        open_until: None,
+    };
+}
+
+/// Tracks the consecutive failures of each [Endpoint], and rejects requests to endpoints that
+/// failed [CircuitBreakerConfig::failure_threshold] times in a row.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    /// The configuration of the breaker.
+    config: CircuitBreakerConfig,
+    /// The circuits, indexed by [Endpoint].
+    circuits: Mutex<[Circuit; 3]>,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new(CircuitBreakerConfig::DEFAULT)
+    }
+}
+
+impl CircuitBreaker {
+    /// Creates a new [CircuitBreaker] with the given configuration.
+    pub const fn new(config: CircuitBreakerConfig) -> Self {
+        Self { config, circuits: Mutex::new([Circuit::CLOSED; 3]) }
+    }
+
+    /// Returns the configuration of the breaker.
+    pub const fn config(&self) -> &CircuitBreakerConfig {
+        &self.config
+    }
+
+    /// Checks whether a request to the endpoint may be made at `now`, returning
+    /// [ProviderUnhealthy] if its circuit is open.
+    pub fn check(&self, endpoint: Endpoint, now: Instant) -> Result<(), ProviderUnhealthy> {
+        let mut circuits = self.circuits.lock().expect("Circuit breaker lock poisoned");
+        let circuit = &mut circuits[endpoint.index()];
+        match circuit.open_until {
+            Some(until) if now < until => {
+                circuit.stats.rejected += 1;
+                Err(ProviderUnhealthy { endpoint, retry_in: until - now })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Records the outcome of a request to the endpoint made at `now`.
+    pub fn record(&self, endpoint: Endpoint, success: bool, now: Instant) {
+        let mut circuits = self.circuits.lock().expect("Circuit breaker lock poisoned");
+        let circuit = &mut circuits[endpoint.index()];
+
+        if success {
+            if circuit.open_until.take().is_some() {
+                info!(target: "fetcher", "The {endpoint} provider recovered, closing its circuit");
+            }
+            circuit.stats.consecutive_failures = 0;
+            return;
+        }
+
+        circuit.stats.consecutive_failures = circuit.stats.consecutive_failures.saturating_add(1);
+        if circuit.stats.consecutive_failures >= self.config.failure_threshold {
+            circuit.open_until = Some(now + self.config.cooldown);
+            circuit.stats.opened += 1;
+            warn!(
+                target: "fetcher",
+                "The {endpoint} provider failed {} consecutive requests, opening its circuit for {:?}",
+                circuit.stats.consecutive_failures,
+                self.config.cooldown
+            );
+        }
+    }
+
+    /// Returns the [CircuitStats] of the endpoint.
+    pub fn stats(&self, endpoint: Endpoint) -> CircuitStats {
+        self.circuits.lock().expect("Circuit breaker lock poisoned")[endpoint.index()].stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: CircuitBreakerConfig =
+        CircuitBreakerConfig { failure_threshold: 3, cooldown: Duration::from_secs(10) };
+
+    #[test]
+    fn test_endpoint_for_hint() {
+        assert_eq!(Endpoint::for_hint(HintType::L1Receipts), Some(Endpoint::L1));
+        assert_eq!(Endpoint::for_hint(HintType::L1Blob), Some(Endpoint::Beacon));
+        assert_eq!(Endpoint::for_hint(HintType::L1Precompile), None);
+        assert_eq!(Endpoint::for_hint(HintType::L2PayloadWitness), Some(Endpoint::L2));
+    }
+
+    #[test]
+    fn test_circuit_opens_after_threshold() {
+        let breaker = CircuitBreaker::new(CONFIG);
+        let now = Instant::now();
+
+        for _ in 0..2 {
+            breaker.record(Endpoint::L1, false, now);
+            assert!(breaker.check(Endpoint::L1, now).is_ok());
+        }
+        breaker.record(Endpoint::L1, false, now);
+
+        let err = breaker.check(Endpoint::L1, now + Duration::from_secs(4)).unwrap_err();
+        assert_eq!(
+            err,
+            ProviderUnhealthy { endpoint: Endpoint::L1, retry_in: Duration::from_secs(6) }
+        );
+        assert_eq!(err.to_string(), "The L1 provider is unhealthy, retrying in 6s");
+        assert_eq!(
+            breaker.stats(Endpoint::L1),
+            CircuitStats { consecutive_failures: 3, opened: 1, rejected: 1 }
+        );
+
+        // The circuits of the other endpoints are unaffected.
+        assert!(breaker.check(Endpoint::L2, now).is_ok());
+        assert_eq!(breaker.stats(Endpoint::L2), CircuitStats::default());
+    }
+
+    #[test]
+    fn test_circuit_reopens_on_failed_probe() {
+        let breaker = CircuitBreaker::new(CONFIG);
+        let now = Instant::now();
+        for _ in 0..3 {
+            breaker.record(Endpoint::Beacon, false, now);
+        }
+
+        // After the cooldown, a single failed probe opens the circuit again.
+        let probe = now + CONFIG.cooldown;
+        assert!(breaker.check(Endpoint::Beacon, probe).is_ok());
+        breaker.record(Endpoint::Beacon, false, probe);
+        assert!(breaker.check(Endpoint::Beacon, probe + Duration::from_secs(1)).is_err());
+        assert_eq!(breaker.stats(Endpoint::Beacon).opened, 2);
+    }
+
+    #[test]
+    fn test_circuit_closes_on_success() {
+        let breaker = CircuitBreaker::new(CONFIG);
+        let now = Instant::now();
+        for _ in 0..3 {
+            breaker.record(Endpoint::L2, false, now);
+        }
+
+        let probe = now + CONFIG.cooldown;
+        assert!(breaker.check(Endpoint::L2, probe).is_ok());
+        breaker.record(Endpoint::L2, true, probe);
+        assert!(breaker.check(Endpoint::L2, probe).is_ok());
+        assert_eq!(breaker.stats(Endpoint::L2).consecutive_failures, 0);
+
+        // A single failure after recovering does not reopen the circuit.
+        breaker.record(Endpoint::L2, false, probe);
+        assert!(breaker.check(Endpoint::L2, probe).is_ok());
+    }
+}
@@ -9,23 +9,32 @@ use alloy_eips::{
     BlockId,
 };
 use alloy_primitives::{address, keccak256, map::HashMap, Address, Bytes, B256};
-use alloy_provider::{Provider, ReqwestProvider};
+use alloy_provider::{Provider, RootProvider};
 use alloy_rlp::{Decodable, EMPTY_STRING_CODE};
 use alloy_rpc_types::{
     debug::ExecutionWitness, Block, BlockNumberOrTag, BlockTransactions, BlockTransactionsKind,
     Transaction,
 };
+use alloy_transport::BoxTransport;
 use anyhow::{anyhow, Result};
 use kona_preimage::{PreimageKey, PreimageKeyType};
 use kona_proof::{Hint, HintType};
 use maili_protocol::BlockInfo;
 use op_alloy_rpc_types_engine::OpPayloadAttributes;
-use std::sync::Arc;
+use std::{sync::Arc, time::Instant};
 use tokio::sync::RwLock;
 use tracing::{error, trace, warn};
 
 mod precompiles;
 
+mod timeouts;
+pub use timeouts::{RequestKind, RequestTimeout, RequestTimeouts};
+
+mod circuit;
+pub use circuit::{
+    CircuitBreaker, CircuitBreakerConfig, CircuitStats, Endpoint, ProviderUnhealthy,
+};
+
 /// The [Fetcher] struct is responsible for fetching preimages from a remote source.
 #[derive(Debug)]
 pub struct Fetcher<KV>
@@ -35,15 +44,19 @@ where
     /// Key-value store for preimages.
     kv_store: Arc<RwLock<KV>>,
     /// L1 chain provider.
-    l1_provider: ReqwestProvider,
+    l1_provider: RootProvider<BoxTransport>,
     /// The blob provider
     blob_provider: OnlineBlobProvider,
     /// L2 chain provider.
-    l2_provider: ReqwestProvider,
+    l2_provider: RootProvider<BoxTransport>,
     /// L2 head
     l2_head: B256,
     /// The last hint that was received. [None] if no hint has been received yet.
     last_hint: Option<String>,
+    /// The timeouts of the remote requests.
+    timeouts: RequestTimeouts,
+    /// The circuit breaker of the remote endpoints.
+    circuit_breaker: CircuitBreaker,
 }
 
 impl<KV> Fetcher<KV>
@@ -53,12 +66,38 @@ where
     /// Create a new [Fetcher] with the given [KeyValueStore].
     pub const fn new(
         kv_store: Arc<RwLock<KV>>,
-        l1_provider: ReqwestProvider,
+        l1_provider: RootProvider<BoxTransport>,
         blob_provider: OnlineBlobProvider,
-        l2_provider: ReqwestProvider,
+        l2_provider: RootProvider<BoxTransport>,
         l2_head: B256,
     ) -> Self {
-        Self { kv_store, l1_provider, blob_provider, l2_provider, l2_head, last_hint: None }
+        Self {
+            kv_store,
+            l1_provider,
+            blob_provider,
+            l2_provider,
+            l2_head,
+            last_hint: None,
+            timeouts: RequestTimeouts::DEFAULT,
+            circuit_breaker: CircuitBreaker::new(CircuitBreakerConfig::DEFAULT),
+        }
+    }
+
+    /// Sets the [RequestTimeouts] of the remote requests.
+    pub const fn with_request_timeouts(mut self, timeouts: RequestTimeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// Sets the [CircuitBreakerConfig] of the remote endpoints.
+    pub const fn with_circuit_breaker(mut self, config: CircuitBreakerConfig) -> Self {
+        self.circuit_breaker = CircuitBreaker::new(config);
+        self
+    }
+
+    /// Returns the [CircuitStats] of the given [Endpoint].
+    pub fn circuit_stats(&self, endpoint: Endpoint) -> CircuitStats {
+        self.circuit_breaker.stats(endpoint)
     }
 
     /// Set the last hint to be received.
@@ -83,7 +122,16 @@ where
             let hint = self.last_hint.as_ref().expect("Cannot be None");
 
             if let Err(e) = self.prefetch(hint).await {
-                error!(target: "fetcher", "Failed to prefetch hint: {e}");
+                if let Some(unhealthy) = e.downcast_ref::<ProviderUnhealthy>() {
+                    // Back off until the circuit of the endpoint can be probed again.
+                    warn!(target: "fetcher", "{unhealthy}");
+                    tokio::time::sleep(unhealthy.retry_in).await;
+                    continue;
+                } else if e.is::<RequestTimeout>() {
+                    warn!(target: "fetcher", "{e}");
+                } else {
+                    error!(target: "fetcher", "Failed to prefetch hint: {e}");
+                }
                 warn!(target: "fetcher", "Retrying hint fetch: {hint}");
                 continue;
             }
@@ -96,11 +144,26 @@ where
     }
 
     /// Fetch the preimage for the given hint and insert it into the key-value store.
+    ///
+    /// Fails with [ProviderUnhealthy] without making any requests if the circuit of the endpoint
+    /// serving the hint is open.
     async fn prefetch(&self, hint: &str) -> Result<()> {
         let hint = Hint::parse(hint)?;
         let (hint_type, hint_data) = hint.split();
         trace!(target: "fetcher", "Fetching hint: {hint_type} {hint_data}");
 
+        let Some(endpoint) = Endpoint::for_hint(hint_type) else {
+            return self.fetch_hint(hint_type, hint_data).await;
+        };
+        self.circuit_breaker.check(endpoint, Instant::now())?;
+        let result = self.fetch_hint(hint_type, hint_data).await;
+        self.circuit_breaker.record(endpoint, result.is_ok(), Instant::now());
+        result
+    }
+
+    /// Fetch the preimage for the given hint type and data from the remote endpoints, and insert
+    /// it into the key-value store.
+    async fn fetch_hint(&self, hint_type: HintType, hint_data: Bytes) -> Result<()> {
         match hint_type {
             HintType::L1BlockHeader => {
                 // Validate the hint data length.
@@ -114,11 +177,15 @@ where
                     .try_into()
                     .map_err(|e| anyhow!("Failed to convert bytes to B256: {e}"))?;
                 let raw_header: Bytes = self
-                    .l1_provider
-                    .client()
-                    .request("debug_getRawHeader", [hash])
-                    .await
-                    .map_err(|e| anyhow!(e))?;
+                    .timeouts
+                    .run(RequestKind::Header, async {
+                        self.l1_provider
+                            .client()
+                            .request("debug_getRawHeader", [hash])
+                            .await
+                            .map_err(|e| anyhow!(e))
+                    })
+                    .await?;
 
                 // Acquire a lock on the key-value store and set the preimage.
                 let mut kv_lock = self.kv_store.write().await;
@@ -140,10 +207,14 @@ where
                     .try_into()
                     .map_err(|e| anyhow!("Failed to convert bytes to B256: {e}"))?;
                 let Block { transactions, .. } = self
-                    .l1_provider
-                    .get_block_by_hash(hash, BlockTransactionsKind::Full)
-                    .await
-                    .map_err(|e| anyhow!("Failed to fetch block: {e}"))?
+                    .timeouts
+                    .run(RequestKind::Transactions, async {
+                        self.l1_provider
+                            .get_block_by_hash(hash, BlockTransactionsKind::Full)
+                            .await
+                            .map_err(|e| anyhow!("Failed to fetch block: {e}"))
+                    })
+                    .await?
                     .ok_or(anyhow!("Block not found."))?;
                 self.store_transactions(transactions).await?;
             }
@@ -160,11 +231,15 @@ where
                     .try_into()
                     .map_err(|e| anyhow!("Failed to convert bytes to B256: {e}"))?;
                 let raw_receipts: Vec<Bytes> = self
-                    .l1_provider
-                    .client()
-                    .request("debug_getRawReceipts", [hash])
-                    .await
-                    .map_err(|e| anyhow!(e))?;
+                    .timeouts
+                    .run(RequestKind::Receipts, async {
+                        self.l1_provider
+                            .client()
+                            .request("debug_getRawReceipts", [hash])
+                            .await
+                            .map_err(|e| anyhow!(e))
+                    })
+                    .await?;
                 self.store_trie_nodes(raw_receipts.as_slice()).await?;
             }
             HintType::L1Blob => {
@@ -191,10 +266,14 @@ where
 
                 // Fetch the blob sidecar from the blob provider.
                 let mut sidecars = self
-                    .blob_provider
-                    .fetch_filtered_sidecars(&partial_block_ref, &[indexed_hash])
-                    .await
-                    .map_err(|e| anyhow!("Failed to fetch blob sidecars: {e}"))?;
+                    .timeouts
+                    .run(RequestKind::Blob, async {
+                        self.blob_provider
+                            .fetch_filtered_sidecars(&partial_block_ref, &[indexed_hash])
+                            .await
+                            .map_err(|e| anyhow!("Failed to fetch blob sidecars: {e}"))
+                    })
+                    .await?;
                 if sidecars.len() != 1 {
                     anyhow::bail!("Expected 1 sidecar, got {}", sidecars.len());
                 }
@@ -286,11 +365,15 @@ where
                     .try_into()
                     .map_err(|e| anyhow!("Failed to convert bytes to B256: {e}"))?;
                 let raw_header: Bytes = self
-                    .l2_provider
-                    .client()
-                    .request("debug_getRawHeader", [hash])
-                    .await
-                    .map_err(|e| anyhow!(e))?;
+                    .timeouts
+                    .run(RequestKind::Header, async {
+                        self.l2_provider
+                            .client()
+                            .request("debug_getRawHeader", [hash])
+                            .await
+                            .map_err(|e| anyhow!(e))
+                    })
+                    .await?;
 
                 // Acquire a lock on the key-value store and set the preimage.
                 let mut kv_lock = self.kv_store.write().await;
@@ -311,15 +394,19 @@ where
                     .as_ref()
                     .try_into()
                     .map_err(|e| anyhow!("Failed to convert bytes to B256: {e}"))?;
-                let Block { transactions, .. } = self
-                    .l2_provider
-                    .get_block_by_hash(hash, BlockTransactionsKind::Hashes)
-                    .await
-                    .map_err(|e| anyhow!("Failed to fetch block: {e}"))?
-                    .ok_or(anyhow!("Block not found."))?;
-
-                match transactions {
-                    BlockTransactions::Hashes(transactions) => {
+                let encoded_transactions = self
+                    .timeouts
+                    .run(RequestKind::Transactions, async {
+                        let Block { transactions, .. } = self
+                            .l2_provider
+                            .get_block_by_hash(hash, BlockTransactionsKind::Hashes)
+                            .await
+                            .map_err(|e| anyhow!("Failed to fetch block: {e}"))?
+                            .ok_or(anyhow!("Block not found."))?;
+
+                        let BlockTransactions::Hashes(transactions) = transactions else {
+                            anyhow::bail!("Only BlockTransactions::Hashes are supported.");
+                        };
                         let mut encoded_transactions = Vec::with_capacity(transactions.len());
                         for tx_hash in transactions {
                             let tx = self
@@ -330,11 +417,11 @@ where
                                 .map_err(|e| anyhow!("Error fetching transaction: {e}"))?;
                             encoded_transactions.push(tx);
                         }
+                        Ok(encoded_transactions)
+                    })
+                    .await?;
 
-                        self.store_trie_nodes(encoded_transactions.as_slice()).await?;
-                    }
-                    _ => anyhow::bail!("Only BlockTransactions::Hashes are supported."),
-                };
+                self.store_trie_nodes(encoded_transactions.as_slice()).await?;
             }
             HintType::L2Code => {
                 // geth hashdb scheme code hash key prefix
@@ -352,22 +439,27 @@ where
                 // Attempt to fetch the code from the L2 chain provider.
                 let code_hash = [&[CODE_PREFIX], hash.as_slice()].concat();
                 let code = self
-                    .l2_provider
-                    .client()
-                    .request::<&[Bytes; 1], Bytes>("debug_dbGet", &[code_hash.into()])
-                    .await;
-
-                // Check if the first attempt to fetch the code failed. If it did, try fetching the
-                // code hash preimage without the geth hashdb scheme prefix.
-                let code = match code {
-                    Ok(code) => code,
-                    Err(_) => self
-                        .l2_provider
-                        .client()
-                        .request::<&[B256; 1], Bytes>("debug_dbGet", &[hash])
-                        .await
-                        .map_err(|e| anyhow!("Error fetching code hash preimage: {e}"))?,
-                };
+                    .timeouts
+                    .run(RequestKind::State, async {
+                        let code = self
+                            .l2_provider
+                            .client()
+                            .request::<&[Bytes; 1], Bytes>("debug_dbGet", &[code_hash.into()])
+                            .await;
+
+                        // Check if the first attempt to fetch the code failed. If it did, try
+                        // fetching the code hash preimage without the geth hashdb scheme prefix.
+                        match code {
+                            Ok(code) => Ok(code),
+                            Err(_) => self
+                                .l2_provider
+                                .client()
+                                .request::<&[B256; 1], Bytes>("debug_dbGet", &[hash])
+                                .await
+                                .map_err(|e| anyhow!("Error fetching code hash preimage: {e}")),
+                        }
+                    })
+                    .await?;
 
                 let mut kv_write_lock = self.kv_store.write().await;
                 kv_write_lock
@@ -384,21 +476,29 @@ where
 
                 // Fetch the header for the L2 head block.
                 let raw_header: Bytes = self
-                    .l2_provider
-                    .client()
-                    .request("debug_getRawHeader", &[self.l2_head])
-                    .await
-                    .map_err(|e| anyhow!("Failed to fetch header RLP: {e}"))?;
+                    .timeouts
+                    .run(RequestKind::Header, async {
+                        self.l2_provider
+                            .client()
+                            .request("debug_getRawHeader", &[self.l2_head])
+                            .await
+                            .map_err(|e| anyhow!("Failed to fetch header RLP: {e}"))
+                    })
+                    .await?;
                 let header = Header::decode(&mut raw_header.as_ref())
                     .map_err(|e| anyhow!("Failed to decode header: {e}"))?;
 
                 // Fetch the storage root for the L2 head block.
                 let l2_to_l1_message_passer = self
-                    .l2_provider
-                    .get_proof(L2_TO_L1_MESSAGE_PASSER_ADDRESS, Default::default())
-                    .block_id(BlockId::Hash(self.l2_head.into()))
-                    .await
-                    .map_err(|e| anyhow!("Failed to fetch account proof: {e}"))?;
+                    .timeouts
+                    .run(RequestKind::State, async {
+                        self.l2_provider
+                            .get_proof(L2_TO_L1_MESSAGE_PASSER_ADDRESS, Default::default())
+                            .block_id(BlockId::Hash(self.l2_head.into()))
+                            .await
+                            .map_err(|e| anyhow!("Failed to fetch account proof: {e}"))
+                    })
+                    .await?;
 
                 let mut raw_output = [0u8; 128];
                 raw_output[31] = OUTPUT_ROOT_VERSION;
@@ -429,11 +529,15 @@ where
 
                 // Fetch the preimage from the L2 chain provider.
                 let preimage: Bytes = self
-                    .l2_provider
-                    .client()
-                    .request("debug_dbGet", &[hash])
-                    .await
-                    .map_err(|e| anyhow!("Failed to fetch preimage: {e}"))?;
+                    .timeouts
+                    .run(RequestKind::State, async {
+                        self.l2_provider
+                            .client()
+                            .request("debug_dbGet", &[hash])
+                            .await
+                            .map_err(|e| anyhow!("Failed to fetch preimage: {e}"))
+                    })
+                    .await?;
 
                 let mut kv_write_lock = self.kv_store.write().await;
                 kv_write_lock.set(
@@ -454,11 +558,15 @@ where
                 let address = Address::from_slice(&hint_data.as_ref()[8..28]);
 
                 let proof_response = self
-                    .l2_provider
-                    .get_proof(address, Default::default())
-                    .block_id(BlockId::Number(BlockNumberOrTag::Number(block_number)))
-                    .await
-                    .map_err(|e| anyhow!("Failed to fetch account proof: {e}"))?;
+                    .timeouts
+                    .run(RequestKind::State, async {
+                        self.l2_provider
+                            .get_proof(address, Default::default())
+                            .block_id(BlockId::Number(BlockNumberOrTag::Number(block_number)))
+                            .await
+                            .map_err(|e| anyhow!("Failed to fetch account proof: {e}"))
+                    })
+                    .await?;
 
                 let mut kv_write_lock = self.kv_store.write().await;
 
@@ -484,11 +592,15 @@ where
                 let slot = B256::from_slice(&hint_data.as_ref()[28..]);
 
                 let mut proof_response = self
-                    .l2_provider
-                    .get_proof(address, vec![slot])
-                    .block_id(BlockId::Number(BlockNumberOrTag::Number(block_number)))
-                    .await
-                    .map_err(|e| anyhow!("Failed to fetch account proof: {e}"))?;
+                    .timeouts
+                    .run(RequestKind::State, async {
+                        self.l2_provider
+                            .get_proof(address, vec![slot])
+                            .block_id(BlockId::Number(BlockNumberOrTag::Number(block_number)))
+                            .await
+                            .map_err(|e| anyhow!("Failed to fetch account proof: {e}"))
+                    })
+                    .await?;
 
                 let mut kv_write_lock = self.kv_store.write().await;
 
@@ -518,14 +630,18 @@ where
                     serde_json::from_slice(&hint_data[32..])?;
 
                 let execute_payload_response: ExecutionWitness = self
-                    .l2_provider
-                    .client()
-                    .request::<(B256, OpPayloadAttributes), ExecutionWitness>(
-                        "debug_executePayload",
-                        (parent_block_hash, payload_attributes),
-                    )
-                    .await
-                    .map_err(|e| anyhow!("Failed to fetch preimage: {e}"))?;
+                    .timeouts
+                    .run(RequestKind::State, async {
+                        self.l2_provider
+                            .client()
+                            .request::<(B256, OpPayloadAttributes), ExecutionWitness>(
+                                "debug_executePayload",
+                                (parent_block_hash, payload_attributes),
+                            )
+                            .await
+                            .map_err(|e| anyhow!("Failed to fetch preimage: {e}"))
+                    })
+                    .await?;
 
                 let mut merged = HashMap::<B256, Bytes>::default();
                 merged.extend(execute_payload_response.state);
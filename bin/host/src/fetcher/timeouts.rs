@@ -0,0 +1,144 @@
+//! Per-operation request timeouts of the [Fetcher].
+//!
+//! [Fetcher]: super::Fetcher
+
+use std::{fmt, future::Future, time::Duration};
+
+/// The kinds of remote requests made by the [Fetcher], which have separate timeouts.
+///
+/// [Fetcher]: super::Fetcher
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestKind {
+    /// A raw block header request.
+    Header,
+    /// A request for the transactions of a block.
+    Transactions,
+    /// A raw receipts request.
+    Receipts,
+    /// A blob sidecar request to the beacon node.
+    Blob,
+    /// A state request, i.e. an account or storage proof, a state node, contract code, or an
+    /// execution witness.
+    State,
+}
+
+impl fmt::Display for RequestKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Header => write!(f, "header"),
+            Self::Transactions => write!(f, "transactions"),
+            Self::Receipts => write!(f, "receipts"),
+            Self::Blob => write!(f, "blob"),
+            Self::State => write!(f, "state"),
+        }
+    }
+}
+
+/// The timeouts of each [RequestKind].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestTimeouts {
+    /// The timeout of [RequestKind::Header] requests.
+    pub header: Duration,
+    /// The timeout of [RequestKind::Transactions] requests.
+    pub transactions: Duration,
+    /// The timeout of [RequestKind::Receipts] requests.
+    pub receipts: Duration,
+    /// The timeout of [RequestKind::Blob] requests.
+    pub blob: Duration,
+    /// The timeout of [RequestKind::State] requests.
+    pub state: Duration,
+}
+
+impl Default for RequestTimeouts {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl RequestTimeouts {
+    /// The default timeouts, which leave room for slow archive nodes and large execution
+    /// witnesses.
+    pub const DEFAULT: Self = Self {
+        header: Duration::from_secs(30),
+        transactions: Duration::from_secs(60),
+        receipts: Duration::from_secs(60),
+        blob: Duration::from_secs(60),
+        state: Duration::from_secs(120),
+    };
+
+    /// Returns the timeout of the given [RequestKind].
+    pub const fn get(&self, kind: RequestKind) -> Duration {
+        match kind {
+            RequestKind::Header => self.header,
+            RequestKind::Transactions => self.transactions,
+            RequestKind::Receipts => self.receipts,
+            RequestKind::Blob => self.blob,
+            RequestKind::State => self.state,
+        }
+    }
+
+    /// Runs the request future, failing with a [RequestTimeout] if it does not complete within
+    /// the timeout of the given [RequestKind].
+    pub async fn run<T>(
+        &self,
+        kind: RequestKind,
+        request: impl Future<Output = anyhow::Result<T>>,
+    ) -> anyhow::Result<T> {
+        let timeout = self.get(kind);
+        tokio::time::timeout(timeout, request)
+            .await
+            .map_err(|_| RequestTimeout { kind, timeout })?
+    }
+}
+
+/// A remote request that did not complete within its timeout.
+///
+/// Timeouts are temporary errors: the [Fetcher] retries the hint that issued the request.
+///
+/// [Fetcher]: super::Fetcher
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestTimeout {
+    /// The kind of the request.
+    pub kind: RequestKind,
+    /// The timeout that elapsed.
+    pub timeout: Duration,
+}
+
+impl fmt::Display for RequestTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "The {} request timed out after {:?}", self.kind, self.timeout)
+    }
+}
+
+impl std::error::Error for RequestTimeout {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_request_timeout() {
+        let timeouts = RequestTimeouts { blob: Duration::from_millis(10), ..Default::default() };
+        let err = timeouts
+            .run(RequestKind::Blob, std::future::pending::<anyhow::Result<()>>())
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<RequestTimeout>(),
+            Some(&RequestTimeout { kind: RequestKind::Blob, timeout: Duration::from_millis(10) })
+        );
+        assert_eq!(err.to_string(), "The blob request timed out after 10ms");
+    }
+
+    #[tokio::test]
+    async fn test_request_within_timeout() {
+        let timeouts = RequestTimeouts::default();
+        let result = timeouts
+            .run(RequestKind::Header, async {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                Ok(1)
+            })
+            .await;
+        assert_eq!(result.unwrap(), 1);
+    }
+}
@@ -3,13 +3,20 @@
 #![cfg_attr(not(test), warn(unused_crate_dependencies))]
 
 pub mod blobs;
+pub mod check_config;
 pub mod cli;
-pub use cli::{init_tracing_subscriber, HostCli};
+pub use cli::{init_tracing_subscriber, shutdown_tracing_subscriber, HostCli};
 
+pub mod diff;
+pub mod extract;
 pub mod fetcher;
 pub mod kv;
+pub mod pipeline;
 pub mod preimage;
+pub mod providers;
 pub mod server;
+pub mod slots;
+pub mod trace;
 
 use anyhow::Result;
 use fetcher::Fetcher;
@@ -35,13 +42,16 @@ pub async fn start_server(cfg: HostCli) -> Result<()> {
     let kv_store = cfg.construct_kv_store();
     let fetcher = if !cfg.is_offline() {
         let (l1_provider, blob_provider, l2_provider) = cfg.create_providers().await?;
-        Some(Arc::new(RwLock::new(Fetcher::new(
+        let fetcher = Fetcher::new(
             kv_store.clone(),
             l1_provider,
             blob_provider,
             l2_provider,
             cfg.agreed_l2_head_hash,
-        ))))
+        )
+        .with_request_timeouts(cfg.request_timeouts())
+        .with_circuit_breaker(cfg.circuit_breaker_config());
+        Some(Arc::new(RwLock::new(fetcher)))
     } else {
         None
     };
@@ -70,13 +80,16 @@ pub async fn start_server_and_native_client(cfg: HostCli) -> Result<i32> {
     let kv_store = cfg.construct_kv_store();
     let fetcher = if !cfg.is_offline() {
         let (l1_provider, blob_provider, l2_provider) = cfg.create_providers().await?;
-        Some(Arc::new(RwLock::new(Fetcher::new(
+        let fetcher = Fetcher::new(
             kv_store.clone(),
             l1_provider,
             blob_provider,
             l2_provider,
             cfg.agreed_l2_head_hash,
-        ))))
+        )
+        .with_request_timeouts(cfg.request_timeouts())
+        .with_circuit_breaker(cfg.circuit_breaker_config());
+        Some(Arc::new(RwLock::new(fetcher)))
     } else {
         None
     };
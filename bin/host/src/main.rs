@@ -1,14 +1,29 @@
 //! Main entrypoint for the host binary.
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use clap::Parser;
-use kona_host::{init_tracing_subscriber, start_server, start_server_and_native_client, HostCli};
+use kona_host::{
+    init_tracing_subscriber, shutdown_tracing_subscriber, start_server,
+    start_server_and_native_client, HostCli,
+};
 use tracing::{error, info};
 
 #[tokio::main(flavor = "multi_thread")]
 async fn main() -> Result<()> {
     let cfg = HostCli::parse();
-    init_tracing_subscriber(cfg.v)?;
+    init_tracing_subscriber(cfg.v, cfg.otlp_endpoint.as_deref())?;
+
+    // Validate the rollup config overrides up front, and print the effective config.
+    if !cfg.cfg_overrides.is_empty() {
+        let rollup_config = cfg.read_rollup_config()?;
+        let overrides = cfg.cfg_overrides.iter().map(ToString::to_string).collect::<Vec<_>>();
+        info!(target: "kona_host", "Applied rollup config overrides: {}", overrides.join(", "));
+        info!(
+            target: "kona_host",
+            "Effective rollup config: {}",
+            serde_json::to_string_pretty(&rollup_config).map_err(|e| anyhow!(e))?
+        );
+    }
 
     if cfg.server {
         start_server(cfg).await?;
@@ -22,9 +37,11 @@ async fn main() -> Result<()> {
         };
 
         // Bubble up the exit status of the client program.
+        shutdown_tracing_subscriber();
         std::process::exit(status as i32);
     }
 
     info!("Exiting host program.");
+    shutdown_tracing_subscriber();
     Ok(())
 }
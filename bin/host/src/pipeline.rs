@@ -0,0 +1,309 @@
+//! The online derivation pipeline of the host tools, which derives L2 payload attributes from
+//! the L1 node and L1 Beacon API, and compares them to the attributes of L2 blocks.
+
+use crate::{
+    blobs::OnlineBlobProvider,
+    providers::{OnlineL1ChainProvider, OnlineL2ChainProvider},
+};
+use alloy_primitives::Bytes;
+use anyhow::{anyhow, bail, Result};
+use kona_derive::{
+    attributes::StatefulAttributesBuilder,
+    errors::{PipelineError, PipelineErrorKind, ResetError},
+    pipeline::{DerivationPipeline, PipelineBuilder},
+    sources::EthereumDataSource,
+    stages::{
+        AttributesQueue, BatchProvider, BatchStream, ChannelProvider, ChannelReader, FrameQueue,
+        L1Retrieval, L1Traversal,
+    },
+    traits::{ChainProvider, OriginProvider, Pipeline, SignalReceiver},
+    types::{ActivationSignal, ResetSignal, StepResult},
+};
+use maili_protocol::L2BlockInfo;
+use op_alloy_genesis::RollupConfig;
+use op_alloy_rpc_types_engine::{OpAttributesWithParent, OpPayloadAttributes};
+use std::{fmt, sync::Arc};
+use tracing::{debug, warn};
+
+/// An online derivation pipeline.
+pub type OnlineDerivationPipeline =
+    DerivationPipeline<OnlineAttributesQueue, OnlineL2ChainProvider>;
+
+/// An online Ethereum data source.
+pub type OnlineDataProvider = EthereumDataSource<OnlineL1ChainProvider, OnlineBlobProvider>;
+
+/// An online payload attributes builder for the `AttributesQueue` stage of the derivation
+/// pipeline.
+pub type OnlineAttributesBuilder =
+    StatefulAttributesBuilder<OnlineL1ChainProvider, OnlineL2ChainProvider>;
+
+/// An online attributes queue for the derivation pipeline.
+pub type OnlineAttributesQueue = AttributesQueue<
+    BatchProvider<
+        BatchStream<
+            ChannelReader<
+                ChannelProvider<
+                    FrameQueue<L1Retrieval<OnlineDataProvider, L1Traversal<OnlineL1ChainProvider>>>,
+                >,
+            >,
+            OnlineL2ChainProvider,
+        >,
+        OnlineL2ChainProvider,
+    >,
+    OnlineAttributesBuilder,
+>;
+
+/// Returns the number of the L1 block that derivation of the children of the L2 safe head starts
+/// from: one channel timeout before the L1 origin of the safe head, so that the frames of
+/// channels opened before the origin are read, clamped to the L1 genesis.
+pub fn sync_start(cfg: &RollupConfig, l2_safe_head: &L2BlockInfo) -> u64 {
+    let channel_timeout = cfg.channel_timeout(l2_safe_head.block_info.timestamp);
+    l2_safe_head.l1_origin.number.saturating_sub(channel_timeout).max(cfg.genesis.l1.number)
+}
+
+/// Creates an [OnlineDerivationPipeline] that derives the children of the L2 safe head, reset to
+/// the safe head and its system config at the [sync_start] L1 block.
+pub async fn new_online_pipeline(
+    cfg: Arc<RollupConfig>,
+    mut l1_provider: OnlineL1ChainProvider,
+    l2_provider: OnlineL2ChainProvider,
+    blob_provider: OnlineBlobProvider,
+    l2_safe_head: L2BlockInfo,
+) -> Result<OnlineDerivationPipeline> {
+    let l1_origin = l1_provider.block_info_by_number(sync_start(&cfg, &l2_safe_head)).await?;
+    let attributes =
+        StatefulAttributesBuilder::new(cfg.clone(), l2_provider.clone(), l1_provider.clone());
+    let dap = EthereumDataSource::new_from_parts(l1_provider.clone(), blob_provider, &cfg);
+    let mut pipeline = PipelineBuilder::new()
+        .rollup_config(cfg)
+        .dap_source(dap)
+        .l2_chain_provider(l2_provider)
+        .chain_provider(l1_provider)
+        .builder(attributes)
+        .origin(l1_origin)
+        .build();
+
+    let system_config = pipeline
+        .system_config_by_number(l2_safe_head.block_info.number)
+        .await
+        .map_err(|e| anyhow!("Failed to fetch the system config of the L2 safe head: {e}"))?;
+    pipeline
+        .signal(
+            ResetSignal { l2_safe_head, l1_origin, system_config: Some(system_config) }.signal(),
+        )
+        .await
+        .map_err(|e| anyhow!("Failed to reset the pipeline to the L2 safe head: {e}"))?;
+    Ok(pipeline)
+}
+
+/// Steps the pipeline until it prepares the attributes of the child of the L2 safe head, giving
+/// up once its L1 origin passes `max_origin`.
+///
+/// Like the driver, temporary errors are retried and the Holocene activation is signaled.
+/// Failed provider requests are returned instead of being retried, as are all other resets,
+/// which an L1 node serving a consistent chain does not cause.
+pub async fn derive_next_attributes(
+    pipeline: &mut OnlineDerivationPipeline,
+    l2_safe_head: L2BlockInfo,
+    max_origin: u64,
+) -> Result<OpAttributesWithParent> {
+    loop {
+        match pipeline.step(l2_safe_head).await {
+            StepResult::PreparedAttributes | StepResult::AdvancedOrigin => {}
+            StepResult::OriginAdvanceErr(e) => {
+                bail!("Failed to advance the L1 origin: {e}");
+            }
+            StepResult::StepFailed(e) => match e {
+                PipelineErrorKind::Temporary(PipelineError::Provider(e)) => {
+                    bail!("Provider request failed: {e}");
+                }
+                PipelineErrorKind::Temporary(_) => {
+                    debug!(target: "online_pipeline", "Retrying step: {e}");
+                }
+                PipelineErrorKind::Reset(ResetError::HoloceneActivation) => {
+                    let system_config =
+                        pipeline.system_config_by_number(l2_safe_head.block_info.number).await?;
+                    let l1_origin = pipeline.origin().ok_or(PipelineError::MissingOrigin.crit())?;
+                    let signal = ActivationSignal {
+                        l2_safe_head,
+                        l1_origin,
+                        system_config: Some(system_config),
+                    };
+                    pipeline.signal(signal.signal()).await?;
+                }
+                PipelineErrorKind::Reset(_) | PipelineErrorKind::Critical(_) => {
+                    warn!(target: "online_pipeline", "Derivation failed: {e}");
+                    bail!("Derivation failed: {e}");
+                }
+            },
+        }
+
+        if let Some(attributes) = pipeline.next() {
+            return Ok(attributes);
+        }
+        let origin = pipeline.origin().ok_or(PipelineError::MissingOrigin.crit())?;
+        if origin.number > max_origin {
+            bail!(
+                "No attributes derived for the child of L2 block {} up to L1 block {max_origin}",
+                l2_safe_head.block_info.number
+            );
+        }
+    }
+}
+
+/// A comparison of a derived payload attribute with the attribute of the L2 block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributeCheck {
+    /// The name of the attribute.
+    pub name: &'static str,
+    /// The derived value.
+    pub derived: String,
+    /// The value of the L2 block.
+    pub block: String,
+    /// Whether the derived value matches the value of the block.
+    pub passed: bool,
+}
+
+impl AttributeCheck {
+    /// Compares a derived attribute with the attribute of the L2 block.
+    pub fn equal<T: PartialEq + fmt::Debug>(name: &'static str, derived: T, block: T) -> Self {
+        Self {
+            name,
+            passed: derived == block,
+            derived: format!("{derived:?}"),
+            block: format!("{block:?}"),
+        }
+    }
+
+    /// Compares the derived transactions with the transactions of the L2 block, describing the
+    /// first transaction that differs.
+    pub fn transactions(derived: &[Bytes], block: &[Bytes]) -> Self {
+        let mut desc = format!("{} transactions", derived.len());
+        if let Some(index) = derived.iter().zip(block).position(|(derived, block)| derived != block)
+        {
+            desc.push_str(&format!(", first difference at {index}"));
+        }
+        Self {
+            name: "transactions",
+            passed: derived == block,
+            derived: desc,
+            block: format!("{} transactions", block.len()),
+        }
+    }
+}
+
+impl fmt::Display for AttributeCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.passed {
+            write!(f, "ok       {}: {}", self.name, self.derived)
+        } else {
+            write!(f, "MISMATCH {}: derived {}, block {}", self.name, self.derived, self.block)
+        }
+    }
+}
+
+/// Compares derived payload attributes with the attributes of the L2 block, as returned by
+/// [kona_derive::attributes::attributes_from_block].
+pub fn check_attributes(
+    derived: &OpPayloadAttributes,
+    block: &OpPayloadAttributes,
+) -> Vec<AttributeCheck> {
+    let (derived_payload, block_payload) = (&derived.payload_attributes, &block.payload_attributes);
+    let withdrawals = |attributes: &OpPayloadAttributes| {
+        attributes.payload_attributes.withdrawals.as_ref().map(Vec::len)
+    };
+    vec![
+        AttributeCheck::equal("timestamp", derived_payload.timestamp, block_payload.timestamp),
+        AttributeCheck::equal("prevRandao", derived_payload.prev_randao, block_payload.prev_randao),
+        AttributeCheck::equal(
+            "suggestedFeeRecipient",
+            derived_payload.suggested_fee_recipient,
+            block_payload.suggested_fee_recipient,
+        ),
+        AttributeCheck::equal("withdrawals", withdrawals(derived), withdrawals(block)),
+        AttributeCheck::equal(
+            "parentBeaconBlockRoot",
+            derived_payload.parent_beacon_block_root,
+            block_payload.parent_beacon_block_root,
+        ),
+        AttributeCheck::equal("gasLimit", derived.gas_limit, block.gas_limit),
+        AttributeCheck::equal("eip1559Params", derived.eip_1559_params, block.eip_1559_params),
+        AttributeCheck::transactions(
+            derived.transactions.as_deref().unwrap_or_default(),
+            block.transactions.as_deref().unwrap_or_default(),
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloy_primitives::{Address, B256};
+    use alloy_rpc_types_engine::PayloadAttributes;
+    use maili_protocol::BlockInfo;
+    use op_alloy_genesis::ChainGenesis;
+
+    fn failed(checks: &[AttributeCheck]) -> Vec<&'static str> {
+        checks.iter().filter(|check| !check.passed).map(|check| check.name).collect()
+    }
+
+    #[test]
+    fn test_sync_start() {
+        let mut cfg = RollupConfig { channel_timeout: 50, ..Default::default() };
+        cfg.genesis = ChainGenesis { l1: BlockInfo::default().id(), ..Default::default() };
+        let safe_head = |origin: u64| {
+            L2BlockInfo::new(
+                BlockInfo::default(),
+                BlockInfo { number: origin, ..Default::default() }.id(),
+                0,
+            )
+        };
+        assert_eq!(sync_start(&cfg, &safe_head(100)), 50);
+        assert_eq!(sync_start(&cfg, &safe_head(20)), 0);
+
+        cfg.genesis.l1.number = 30;
+        assert_eq!(sync_start(&cfg, &safe_head(60)), 30);
+    }
+
+    #[test]
+    fn test_check_attributes() {
+        let block = OpPayloadAttributes {
+            payload_attributes: PayloadAttributes {
+                timestamp: 20,
+                prev_randao: B256::repeat_byte(0x01),
+                suggested_fee_recipient: Address::repeat_byte(0x02),
+                withdrawals: Some(Vec::new()),
+                parent_beacon_block_root: Some(B256::repeat_byte(0x03)),
+            },
+            transactions: Some(vec![Bytes::from_static(&[0x7e]), Bytes::from_static(&[0x02])]),
+            no_tx_pool: Some(true),
+            gas_limit: Some(30_000_000),
+            eip_1559_params: None,
+        };
+        let checks = check_attributes(&block, &block);
+        assert_eq!(checks.len(), 8);
+        assert!(failed(&checks).is_empty());
+        assert_eq!(checks[0].to_string(), "ok       timestamp: 20");
+        assert_eq!(checks[7].to_string(), "ok       transactions: 2 transactions");
+
+        let mut derived = block.clone();
+        derived.gas_limit = Some(25_000_000);
+        derived.transactions = Some(vec![Bytes::from_static(&[0x7e]), Bytes::from_static(&[0x01])]);
+        let checks = check_attributes(&derived, &block);
+        assert_eq!(failed(&checks), ["gasLimit", "transactions"]);
+        assert_eq!(
+            checks[5].to_string(),
+            "MISMATCH gasLimit: derived Some(25000000), block Some(30000000)"
+        );
+        assert_eq!(
+            checks[7].to_string(),
+            "MISMATCH transactions: derived 2 transactions, first difference at 1, block 2 \
+             transactions"
+        );
+
+        derived.transactions = Some(vec![Bytes::from_static(&[0x7e])]);
+        let checks = check_attributes(&derived, &block);
+        assert_eq!(checks[7].derived, "1 transactions");
+        assert!(!checks[7].passed);
+    }
+}
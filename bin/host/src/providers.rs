@@ -0,0 +1,201 @@
+//! Online implementations of the chain providers of the derivation pipeline, backed by the
+//! JSON-RPC endpoints of an L1 and an L2 node.
+
+use alloy_consensus::{Header, Receipt, ReceiptEnvelope, TxEnvelope};
+use alloy_eips::eip2718::Decodable2718;
+use alloy_primitives::{Bytes, B256};
+use alloy_provider::{Provider, RootProvider};
+use alloy_rlp::Decodable;
+use alloy_rpc_types::{BlockNumberOrTag, BlockTransactions, BlockTransactionsKind};
+use alloy_transport::BoxTransport;
+use async_trait::async_trait;
+use kona_derive::{
+    errors::{PipelineError, PipelineErrorKind},
+    traits::{ChainProvider, L2ChainProvider},
+};
+use maili_protocol::{to_system_config, BatchValidationProvider, BlockInfo, L2BlockInfo};
+use op_alloy_consensus::OpBlock;
+use op_alloy_genesis::{RollupConfig, SystemConfig};
+use std::{fmt, sync::Arc};
+
+/// An error of the online chain providers, describing the request that failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OnlineProviderError(pub String);
+
+impl fmt::Display for OnlineProviderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for OnlineProviderError {}
+
+impl From<OnlineProviderError> for PipelineErrorKind {
+    fn from(e: OnlineProviderError) -> Self {
+        PipelineError::Provider(e.0).temp()
+    }
+}
+
+/// A [ChainProvider] backed by the JSON-RPC endpoint of an L1 node, which must serve
+/// `debug_getRawHeader` and `debug_getRawReceipts`.
+#[derive(Debug, Clone)]
+pub struct OnlineL1ChainProvider {
+    /// The L1 node provider.
+    pub provider: RootProvider<BoxTransport>,
+}
+
+impl OnlineL1ChainProvider {
+    /// Creates a new [OnlineL1ChainProvider].
+    pub const fn new(provider: RootProvider<BoxTransport>) -> Self {
+        Self { provider }
+    }
+}
+
+#[async_trait]
+impl ChainProvider for OnlineL1ChainProvider {
+    type Error = OnlineProviderError;
+
+    async fn header_by_hash(&mut self, hash: B256) -> Result<Header, Self::Error> {
+        let raw_header: Bytes =
+            self.provider.client().request("debug_getRawHeader", [hash]).await.map_err(|e| {
+                OnlineProviderError(format!("Failed to fetch L1 header {hash}: {e}"))
+            })?;
+        Header::decode(&mut raw_header.as_ref())
+            .map_err(|e| OnlineProviderError(format!("Failed to decode L1 header {hash}: {e}")))
+    }
+
+    async fn block_info_by_number(&mut self, number: u64) -> Result<BlockInfo, Self::Error> {
+        let block = self
+            .provider
+            .get_block_by_number(BlockNumberOrTag::Number(number), BlockTransactionsKind::Hashes)
+            .await
+            .map_err(|e| OnlineProviderError(format!("Failed to fetch L1 block {number}: {e}")))?
+            .ok_or_else(|| OnlineProviderError(format!("L1 block {number} not found")))?;
+        Ok(BlockInfo {
+            hash: block.header.hash,
+            number: block.header.number,
+            parent_hash: block.header.parent_hash,
+            timestamp: block.header.timestamp,
+        })
+    }
+
+    async fn receipts_by_hash(&mut self, hash: B256) -> Result<Vec<Receipt>, Self::Error> {
+        let raw_receipts: Vec<Bytes> =
+            self.provider.client().request("debug_getRawReceipts", [hash]).await.map_err(|e| {
+                OnlineProviderError(format!("Failed to fetch the receipts of L1 block {hash}: {e}"))
+            })?;
+        raw_receipts
+            .iter()
+            .map(|raw| {
+                ReceiptEnvelope::decode_2718(&mut raw.as_ref())
+                    .ok()
+                    .and_then(|envelope| envelope.as_receipt().cloned())
+                    .ok_or_else(|| {
+                        OnlineProviderError(format!(
+                            "Failed to decode the receipts of L1 block {hash}"
+                        ))
+                    })
+            })
+            .collect()
+    }
+
+    async fn block_info_and_transactions_by_hash(
+        &mut self,
+        hash: B256,
+    ) -> Result<(BlockInfo, Vec<TxEnvelope>), Self::Error> {
+        let block = self
+            .provider
+            .get_block_by_hash(hash, BlockTransactionsKind::Full)
+            .await
+            .map_err(|e| OnlineProviderError(format!("Failed to fetch L1 block {hash}: {e}")))?
+            .ok_or_else(|| OnlineProviderError(format!("L1 block {hash} not found")))?;
+        let BlockTransactions::Full(txs) = block.transactions else {
+            return Err(OnlineProviderError(format!(
+                "L1 block {hash} was not returned with full transactions"
+            )));
+        };
+        let block_info = BlockInfo {
+            hash: block.header.hash,
+            number: block.header.number,
+            parent_hash: block.header.parent_hash,
+            timestamp: block.header.timestamp,
+        };
+        Ok((block_info, txs.into_iter().map(Into::into).collect()))
+    }
+}
+
+/// An [L2ChainProvider] backed by the JSON-RPC endpoint of an L2 node, which must serve
+/// `debug_getRawBlock`.
+#[derive(Debug, Clone)]
+pub struct OnlineL2ChainProvider {
+    /// The L2 node provider.
+    pub provider: RootProvider<BoxTransport>,
+    /// The rollup config of the L2 chain.
+    pub rollup_config: Arc<RollupConfig>,
+}
+
+impl OnlineL2ChainProvider {
+    /// Creates a new [OnlineL2ChainProvider].
+    pub const fn new(
+        provider: RootProvider<BoxTransport>,
+        rollup_config: Arc<RollupConfig>,
+    ) -> Self {
+        Self { provider, rollup_config }
+    }
+}
+
+#[async_trait]
+impl BatchValidationProvider for OnlineL2ChainProvider {
+    type Error = OnlineProviderError;
+
+    async fn l2_block_info_by_number(&mut self, number: u64) -> Result<L2BlockInfo, Self::Error> {
+        let block = self.block_by_number(number).await?;
+        L2BlockInfo::from_block_and_genesis(&block, &self.rollup_config.genesis).map_err(|e| {
+            OnlineProviderError(format!("Failed to read the L1 origin of L2 block {number}: {e}"))
+        })
+    }
+
+    async fn block_by_number(&mut self, number: u64) -> Result<OpBlock, Self::Error> {
+        let raw_block: Bytes = self
+            .provider
+            .client()
+            .request("debug_getRawBlock", [BlockNumberOrTag::Number(number)])
+            .await
+            .map_err(|e| OnlineProviderError(format!("Failed to fetch L2 block {number}: {e}")))?;
+        OpBlock::decode(&mut raw_block.as_ref())
+            .map_err(|e| OnlineProviderError(format!("Failed to decode L2 block {number}: {e}")))
+    }
+}
+
+#[async_trait]
+impl L2ChainProvider for OnlineL2ChainProvider {
+    type Error = OnlineProviderError;
+
+    async fn system_config_by_number(
+        &mut self,
+        number: u64,
+        rollup_config: Arc<RollupConfig>,
+    ) -> Result<SystemConfig, <Self as L2ChainProvider>::Error> {
+        let block = self.block_by_number(number).await?;
+        to_system_config(&block, &rollup_config).map_err(|e| {
+            OnlineProviderError(format!(
+                "Failed to read the system config of L2 block {number}: {e}"
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_provider_error_is_temporary() {
+        let err = OnlineProviderError("L1 block 1 not found".to_string());
+        assert_eq!(err.to_string(), "L1 block 1 not found");
+        assert_eq!(
+            PipelineErrorKind::from(err),
+            PipelineError::Provider("L1 block 1 not found".to_string()).temp()
+        );
+    }
+}
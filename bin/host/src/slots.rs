@@ -0,0 +1,116 @@
+//! Contains the [SlotDerivation] strategies used to map L1 block timestamps to beacon chain slots.
+
+use kona_derive::errors::BlobProviderError;
+use std::fmt::Debug;
+
+/// Maps an L1 block timestamp to the beacon chain slot the block was proposed in.
+pub trait SlotDerivation: Debug + Send + Sync {
+    /// Returns the slot for the given L1 block timestamp.
+    fn slot(&self, timestamp: u64) -> Result<u64, BlobProviderError>;
+}
+
+/// A [SlotDerivation] assuming fixed-length slots, counted from the beacon chain genesis.
+///
+/// This is the slot derivation used by default, with the genesis time and slot time loaded from
+/// the beacon node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SimpleSlotDerivation {
+    /// The beacon chain genesis time.
+    pub genesis_time: u64,
+    /// The slot time, in seconds.
+    pub slot_time: u64,
+}
+
+impl SimpleSlotDerivation {
+    /// Creates a new [SimpleSlotDerivation].
+    pub const fn new(genesis_time: u64, slot_time: u64) -> Self {
+        Self { genesis_time, slot_time }
+    }
+}
+
+impl SlotDerivation for SimpleSlotDerivation {
+    fn slot(&self, timestamp: u64) -> Result<u64, BlobProviderError> {
+        if timestamp < self.genesis_time || self.slot_time == 0 {
+            return Err(BlobProviderError::SlotDerivation);
+        }
+        Ok((timestamp - self.genesis_time) / self.slot_time)
+    }
+}
+
+/// A [SlotDerivation] for devnets and alternative L1s, whose slots do not start at the beacon
+/// chain genesis or whose blocks are not aligned to slot boundaries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfigurableSlotDerivation {
+    /// The time of the first slot.
+    pub genesis_time: u64,
+    /// The slot time, in seconds.
+    pub slot_time: u64,
+    /// The slot number at `genesis_time`, e.g. on chains where the beacon chain was started
+    /// before the merge.
+    pub genesis_slot: u64,
+    /// The maximum offset, in seconds, of a block timestamp from the start of its slot. Blocks
+    /// beyond it are rejected.
+    pub max_slot_offset: u64,
+}
+
+impl ConfigurableSlotDerivation {
+    /// Creates a new [ConfigurableSlotDerivation] that requires block timestamps to be aligned to
+    /// slot boundaries.
+    pub const fn new(genesis_time: u64, slot_time: u64) -> Self {
+        Self { genesis_time, slot_time, genesis_slot: 0, max_slot_offset: 0 }
+    }
+
+    /// Sets the slot number at the genesis time.
+    pub const fn with_genesis_slot(mut self, genesis_slot: u64) -> Self {
+        self.genesis_slot = genesis_slot;
+        self
+    }
+
+    /// Sets the maximum offset of a block timestamp from the start of its slot.
+    pub const fn with_max_slot_offset(mut self, max_slot_offset: u64) -> Self {
+        self.max_slot_offset = max_slot_offset;
+        self
+    }
+}
+
+impl SlotDerivation for ConfigurableSlotDerivation {
+    fn slot(&self, timestamp: u64) -> Result<u64, BlobProviderError> {
+        if timestamp < self.genesis_time || self.slot_time == 0 {
+            return Err(BlobProviderError::SlotDerivation);
+        }
+        let elapsed = timestamp - self.genesis_time;
+        if elapsed % self.slot_time > self.max_slot_offset {
+            return Err(BlobProviderError::SlotDerivation);
+        }
+        self.genesis_slot
+            .checked_add(elapsed / self.slot_time)
+            .ok_or(BlobProviderError::SlotDerivation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simple_slot_derivation() {
+        let derivation = SimpleSlotDerivation::new(10, 12);
+        assert_eq!(derivation.slot(10).unwrap(), 0);
+        assert_eq!(derivation.slot(34).unwrap(), 2);
+        assert_eq!(derivation.slot(35).unwrap(), 2);
+        assert_eq!(derivation.slot(9).unwrap_err(), BlobProviderError::SlotDerivation);
+        assert!(SimpleSlotDerivation::new(0, 0).slot(1).is_err());
+    }
+
+    #[test]
+    fn test_configurable_slot_derivation() {
+        let derivation = ConfigurableSlotDerivation::new(100, 2).with_genesis_slot(1000);
+        assert_eq!(derivation.slot(100).unwrap(), 1000);
+        assert_eq!(derivation.slot(110).unwrap(), 1005);
+        assert_eq!(derivation.slot(111).unwrap_err(), BlobProviderError::SlotDerivation);
+        assert_eq!(derivation.slot(99).unwrap_err(), BlobProviderError::SlotDerivation);
+
+        let derivation = derivation.with_max_slot_offset(1);
+        assert_eq!(derivation.slot(111).unwrap(), 1005);
+    }
+}
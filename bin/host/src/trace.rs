@@ -0,0 +1,469 @@
+//! The `kona-trace` tool, which derives an L2 block and traces it back to the L1 batcher
+//! transactions it was derived from.
+
+use crate::{
+    cli::{read_rollup_config, EndpointAuth, L1Args},
+    extract::{BatcherTx, BatcherTxFetcher},
+    pipeline::{
+        check_attributes, derive_next_attributes, new_online_pipeline, sync_start, AttributeCheck,
+    },
+    providers::{OnlineL1ChainProvider, OnlineL2ChainProvider},
+    HostCli,
+};
+use alloy_primitives::Address;
+use anyhow::{anyhow, bail, Result};
+use clap::{ArgAction, Parser};
+use kona_derive::{
+    attributes::attributes_from_block,
+    provenance::{BatchTracer, L1TxRef, TracedBatch},
+    traits::{ChainProvider, OriginProvider},
+    types::ChannelId,
+};
+use maili_protocol::{
+    Batch, BatchValidationProvider, BatchValidity, BatchWithInclusionBlock, BlockInfo, Frame,
+    L1BlockInfoTx, L2BlockInfo,
+};
+use op_alloy_consensus::{OpBlock, OpTxEnvelope};
+use op_alloy_genesis::RollupConfig;
+use std::{fmt, path::PathBuf, sync::Arc};
+use tracing::{info, warn};
+
+const ABOUT: &str = "
+kona-trace derives an L2 block from its parent with the derivation pipeline, and traces it: its
+L1 origin, the batcher transactions and frames sent from one channel timeout before the origin,
+the channel its batch was decoded from, the validity of the batch, and the derived attributes
+compared to the attributes of the block.
+";
+
+/// The `kona-trace` CLI application arguments.
+#[derive(Parser, Clone, Debug)]
+#[command(about = ABOUT, version)]
+pub struct TraceCli {
+    /// Verbosity level (0-2)
+    #[arg(long, short, action = ArgAction::Count)]
+    pub v: u8,
+    /// Number of the L2 block to trace.
+    #[clap(long)]
+    pub block: u64,
+    /// The L1 node and L1 Beacon API arguments.
+    #[command(flatten)]
+    pub l1: L1Args,
+    /// Address of L2 JSON-RPC endpoint to use. Either an HTTP(S) URL, or the path of an IPC
+    /// socket. The endpoint must serve `debug_getRawBlock`.
+    #[clap(long, visible_alias = "l2", env)]
+    pub l2_node_address: String,
+    /// Authentication for the L2 node, see `--l1-node-auth`.
+    #[clap(long, value_delimiter = ',', env)]
+    pub l2_node_auth: Vec<EndpointAuth>,
+    /// Path to the rollup config of the L2 chain.
+    #[clap(long, alias = "rollup-cfg", env)]
+    pub rollup_config_path: PathBuf,
+    /// Number of L1 blocks after the L1 origin of the block that are derived from before giving
+    /// up on the batch of the block. Defaults to the sequencing window size.
+    #[clap(long)]
+    pub scan_blocks: Option<u64>,
+}
+
+/// The traced L2 block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TracedBlock {
+    /// The L2 block, with its L1 origin and sequence number.
+    pub info: L2BlockInfo,
+    /// The batcher address of the system config the block was derived with.
+    pub batcher: Address,
+}
+
+impl TracedBlock {
+    /// Creates a [TracedBlock] from an L2 block, decoding its L1 info deposit.
+    pub fn from_block(block: &OpBlock) -> Result<Self> {
+        let number = block.header.number;
+        let Some(OpTxEnvelope::Deposit(deposit)) = block.body.transactions.first() else {
+            bail!("Block {number} does not start with an L1 info deposit");
+        };
+        let l1_info = L1BlockInfoTx::decode_calldata(deposit.input.as_ref())
+            .map_err(|e| anyhow!("Failed to decode the L1 info deposit of block {number}: {e}"))?;
+        Ok(Self {
+            info: L2BlockInfo::new(block.into(), l1_info.id(), l1_info.sequence_number()),
+            batcher: l1_info.batcher_address(),
+        })
+    }
+}
+
+/// A frame carried by a batcher transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TracedFrame {
+    /// The ID of the frame's channel.
+    pub channel_id: ChannelId,
+    /// The frame number.
+    pub number: u16,
+    /// The size of the frame data, in bytes.
+    pub size: usize,
+    /// Whether the frame is the last frame of its channel.
+    pub is_last: bool,
+}
+
+/// A batcher transaction read by the derivation of the traced block, and the frames it carries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TracedTx {
+    /// The batcher transaction.
+    pub tx: L1TxRef,
+    /// The frames carried by the transaction.
+    pub frames: Vec<TracedFrame>,
+    /// The number of batches decoded from the channels completed by the transaction.
+    pub batches: usize,
+    /// Why the frames of the transaction could not be parsed, if they could not.
+    pub error: Option<String>,
+}
+
+impl TracedTx {
+    /// Adds the frames of a batcher transaction to the [BatchTracer], recording them.
+    fn trace(tracer: &mut BatchTracer, batcher_tx: &BatcherTx) -> Self {
+        let mut traced = Self { tx: batcher_tx.tx, frames: Vec::new(), batches: 0, error: None };
+        for payload in &batcher_tx.payloads {
+            let frames = match Frame::parse_frames(payload) {
+                Ok(frames) => frames,
+                Err(e) => {
+                    traced.error = Some(format!("Failed to parse frames: {e}"));
+                    continue;
+                }
+            };
+            traced.frames.extend(frames.iter().map(|frame| TracedFrame {
+                channel_id: frame.id.into(),
+                number: frame.number,
+                size: frame.data.len(),
+                is_last: frame.is_last,
+            }));
+            traced.batches += tracer.add_transaction(batcher_tx.tx, payload).unwrap_or_default();
+        }
+        traced
+    }
+}
+
+impl fmt::Display for TracedTx {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "L1 block {}, tx {} (index {})",
+            self.tx.block.number, self.tx.hash, self.tx.index
+        )?;
+        if self.batches > 0 {
+            write!(f, ", {} batches decoded", self.batches)?;
+        }
+        for frame in &self.frames {
+            write!(
+                f,
+                "\n  channel {} frame {}: {} bytes",
+                frame.channel_id, frame.number, frame.size
+            )?;
+            if frame.is_last {
+                write!(f, ", last")?;
+            }
+        }
+        if let Some(error) = &self.error {
+            write!(f, "\n  {error}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the number of the L2 safe head that a batch is validated against when the traced
+/// block is derived from it: the parent of the block for a single batch, and the parent of the
+/// first block of a span batch, which is validated as a whole.
+pub fn batch_parent_number(batch: &Batch, block: &L2BlockInfo, block_time: u64) -> u64 {
+    let blocks_before =
+        block.block_info.timestamp.saturating_sub(batch.timestamp()) / block_time.max(1);
+    block.block_info.number.saturating_sub(blocks_before + 1)
+}
+
+/// The validity of the batch of the traced block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TracedValidity {
+    /// The number of the L2 safe head the batch was validated against.
+    pub l2_safe_head: u64,
+    /// The number of the L1 block that completed the channel of the batch.
+    pub inclusion_block: u64,
+    /// The validity of the batch.
+    pub validity: BatchValidity,
+}
+
+impl fmt::Display for TracedValidity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} on L2 safe head {}, included in L1 block {}",
+            self.validity, self.l2_safe_head, self.inclusion_block
+        )
+    }
+}
+
+/// The output of `kona-trace`.
+#[derive(Debug, Clone)]
+pub struct TraceReport {
+    /// The traced L2 block.
+    pub block: TracedBlock,
+    /// The L1 origin of the block.
+    pub l1_origin: BlockInfo,
+    /// The first and last L1 block that were read by the derivation of the block.
+    pub scanned: (u64, u64),
+    /// The batcher transactions in the read L1 blocks, in inclusion order.
+    pub txs: Vec<TracedTx>,
+    /// The batch of the block, if it was found.
+    pub batch: Option<TracedBatch>,
+    /// The validity of the batch, if it was found.
+    pub validity: Option<TracedValidity>,
+    /// The derived attributes compared to the attributes of the block, or why derivation failed.
+    pub derivation: Result<Vec<AttributeCheck>, String>,
+}
+
+impl fmt::Display for TraceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (block, origin) = (&self.block.info, &self.l1_origin);
+        writeln!(
+            f,
+            "L2 block {} ({}), timestamp {}",
+            block.block_info.number, block.block_info.hash, block.block_info.timestamp
+        )?;
+        writeln!(
+            f,
+            "L1 origin {} ({}), timestamp {}, sequence number {}",
+            origin.number, origin.hash, origin.timestamp, block.seq_num
+        )?;
+        writeln!(f, "Batcher {}", self.block.batcher)?;
+
+        let (first, last) = self.scanned;
+        writeln!(f, "\nBatcher transactions in L1 blocks {first}-{last}:")?;
+        for tx in &self.txs {
+            writeln!(f, "{tx}")?;
+        }
+
+        match &self.batch {
+            Some(traced) => {
+                match &traced.batch {
+                    Batch::Single(_) => writeln!(f, "\nBatch: single batch")?,
+                    Batch::Span(batch) => {
+                        writeln!(f, "\nBatch: span batch of {} blocks", batch.batches.len())?
+                    }
+                }
+                let provenance = &traced.provenance;
+                writeln!(f, "Channel {}:", provenance.channel_id)?;
+                for frame in &provenance.frames {
+                    writeln!(
+                        f,
+                        "  frame {}: {} bytes, L1 block {}, tx {}",
+                        frame.number, frame.size, frame.tx.block.number, frame.tx.hash
+                    )?;
+                }
+                if let Some(validity) = &self.validity {
+                    writeln!(f, "Validity: {validity}")?;
+                }
+            }
+            None => writeln!(f, "\nNo batch found for the block")?,
+        }
+
+        match &self.derivation {
+            Ok(checks) => {
+                writeln!(f, "\nDerived attributes:")?;
+                for check in checks {
+                    writeln!(f, "  {check}")?;
+                }
+                Ok(())
+            }
+            Err(e) => writeln!(f, "\nDerivation failed: {e}"),
+        }
+    }
+}
+
+/// Derives and traces the L2 block of the [TraceCli].
+///
+/// The block is derived from its parent by a derivation pipeline that starts one channel timeout
+/// before the L1 origin of the parent, as when syncing from the parent, and the derived
+/// attributes are compared to the attributes of the block. The batcher transactions of the L1
+/// blocks read by the pipeline are then fed to a [BatchTracer] to find the channel of the
+/// block's batch, whose validity is checked with the batch validation rules of the pipeline.
+pub async fn trace(cfg: &TraceCli) -> Result<TraceReport> {
+    let rollup_config = Arc::new(read_rollup_config(&cfg.rollup_config_path)?);
+    if cfg.block <= rollup_config.genesis.l2.number {
+        bail!("Block {} is not derived from batches", cfg.block);
+    }
+    let mut l1_provider = OnlineL1ChainProvider::new(cfg.l1.l1_provider().await?);
+    let mut l2_provider = OnlineL2ChainProvider::new(
+        HostCli::rpc_provider(&cfg.l2_node_address, &cfg.l2_node_auth).await?,
+        rollup_config.clone(),
+    );
+    let blob_provider = cfg
+        .l1
+        .blob_provider()
+        .await?
+        .ok_or_else(|| anyhow!("Deriving the block requires --l1-beacon-address"))?;
+
+    let op_block = l2_provider.block_by_number(cfg.block).await?;
+    let block = TracedBlock::from_block(&op_block)?;
+    let parent = l2_provider.l2_block_info_by_number(cfg.block - 1).await?;
+    let origin = block.info.l1_origin;
+    let l1_origin = l1_provider.block_info_by_number(origin.number).await?;
+    if l1_origin.hash != origin.hash {
+        warn!(target: "kona_trace", "L1 origin {} was reorged out", origin.hash);
+    }
+
+    let start = sync_start(&rollup_config, &parent);
+    let max_origin =
+        origin.number.saturating_add(cfg.scan_blocks.unwrap_or(rollup_config.seq_window_size));
+    let mut pipeline = new_online_pipeline(
+        rollup_config.clone(),
+        l1_provider.clone(),
+        l2_provider.clone(),
+        blob_provider.clone(),
+        parent,
+    )
+    .await?;
+    let derivation = match derive_next_attributes(&mut pipeline, parent, max_origin).await {
+        Ok(derived) => {
+            let expected = attributes_from_block(&rollup_config, &op_block).map_err(|e| {
+                anyhow!("Failed to read the attributes of block {}: {e}", cfg.block)
+            })?;
+            Ok(check_attributes(&derived.attributes, &expected))
+        }
+        Err(e) => Err(e.to_string()),
+    };
+    let end = pipeline.origin().map_or(max_origin, |origin| origin.number.min(max_origin));
+
+    let fetcher = BatcherTxFetcher {
+        l1_provider: l1_provider.provider.clone(),
+        blob_provider: Some(blob_provider),
+        batch_inbox_address: rollup_config.batch_inbox_address,
+        sender: block.batcher,
+    };
+    let mut tracer = BatchTracer::new(rollup_config.clone());
+    let mut txs = Vec::new();
+    for number in start..=end {
+        for batcher_tx in fetcher.block_txs(number).await? {
+            txs.push(TracedTx::trace(&mut tracer, &batcher_tx));
+        }
+    }
+    info!(
+        target: "kona_trace",
+        "Scanned {} batcher transactions in L1 blocks {start}-{end}",
+        txs.len()
+    );
+
+    let batch = tracer.find_by_timestamp(block.info.block_info.timestamp).cloned();
+    let validity = match &batch {
+        Some(traced) => Some(
+            check_validity(traced, &block.info, &rollup_config, &mut l1_provider, &mut l2_provider)
+                .await?,
+        ),
+        None => None,
+    };
+
+    Ok(TraceReport { block, l1_origin, scanned: (start, end), txs, batch, validity, derivation })
+}
+
+/// Checks the validity of the batch of the traced block against the L2 safe head it is
+/// validated against, see [batch_parent_number].
+async fn check_validity(
+    traced: &TracedBatch,
+    block: &L2BlockInfo,
+    cfg: &RollupConfig,
+    l1_provider: &mut OnlineL1ChainProvider,
+    l2_provider: &mut OnlineL2ChainProvider,
+) -> Result<TracedValidity> {
+    let inclusion_block = traced
+        .provenance
+        .l1_txs()
+        .last()
+        .map(|tx| tx.block)
+        .ok_or_else(|| anyhow!("The batch of the block has no frames"))?;
+    let l2_safe_head = l2_provider
+        .l2_block_info_by_number(batch_parent_number(&traced.batch, block, cfg.block_time))
+        .await?;
+
+    // The L1 blocks start at the origin of the safe head, and end at the inclusion block.
+    let mut l1_blocks = Vec::new();
+    for number in l2_safe_head.l1_origin.number..=inclusion_block.number {
+        l1_blocks.push(l1_provider.block_info_by_number(number).await?);
+    }
+    let validity = BatchWithInclusionBlock::new(inclusion_block, traced.batch.clone())
+        .check_batch(cfg, &l1_blocks, l2_safe_head, l2_provider)
+        .await;
+    Ok(TracedValidity {
+        l2_safe_head: l2_safe_head.block_info.number,
+        inclusion_block: inclusion_block.number,
+        validity,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloy_primitives::{Bytes, B256};
+    use maili_protocol::{SingleBatch, SpanBatch, SpanBatchElement, DERIVATION_VERSION_0};
+
+    #[test]
+    fn test_flags() {
+        let base = ["kona-trace", "--block", "10", "--l1", "dummy", "--l2", "dummy"];
+        let cases = [
+            (["--rollup-config-path", "dummy"].as_slice(), true),
+            (["--rollup-cfg", "dummy", "--beacon", "dummy", "--scan-blocks", "5"].as_slice(), true),
+            (["--rollup-config-path", "dummy", "--l2-node-auth", "bearer:abc"].as_slice(), true),
+            ([].as_slice(), false),
+            (["--rollup-config-path", "dummy", "--block", "latest"].as_slice(), false),
+        ];
+        for (args_ext, valid) in cases {
+            let args = base.iter().chain(args_ext.iter()).cloned().collect::<Vec<_>>();
+            assert_eq!(TraceCli::try_parse_from(args).is_ok(), valid);
+        }
+    }
+
+    #[test]
+    fn test_batch_parent_number() {
+        let block = L2BlockInfo::new(
+            BlockInfo { number: 10, timestamp: 20, ..Default::default() },
+            Default::default(),
+            0,
+        );
+        let single = Batch::Single(SingleBatch { timestamp: 20, ..Default::default() });
+        assert_eq!(batch_parent_number(&single, &block, 2), 9);
+
+        // A span batch is validated against the parent of its first block.
+        let element = |timestamp| SpanBatchElement { timestamp, ..Default::default() };
+        let span = SpanBatch {
+            batches: vec![element(16), element(18), element(20)],
+            ..Default::default()
+        };
+        assert_eq!(batch_parent_number(&Batch::Span(span), &block, 2), 7);
+    }
+
+    #[test]
+    fn test_traced_validity() {
+        let validity =
+            TracedValidity { l2_safe_head: 9, inclusion_block: 7, validity: BatchValidity::Accept };
+        assert_eq!(validity.to_string(), "Accept on L2 safe head 9, included in L1 block 7");
+    }
+
+    #[test]
+    fn test_traced_tx() {
+        let id = [0xAA; 16];
+        let frame = |number, data: &[u8], is_last| {
+            let frame = Frame::new(id, number, data.to_vec(), is_last);
+            Bytes::from([&[DERIVATION_VERSION_0], frame.encode().as_slice()].concat())
+        };
+        let tx = L1TxRef { hash: B256::repeat_byte(0x01), ..Default::default() };
+        let batcher_tx = BatcherTx {
+            tx,
+            payloads: vec![frame(0, &[0x01; 4], false), Bytes::from_static(&[0x01])],
+        };
+
+        let mut tracer = BatchTracer::new(Arc::new(RollupConfig::default()));
+        let traced = TracedTx::trace(&mut tracer, &batcher_tx);
+        assert_eq!(
+            traced.frames,
+            [TracedFrame { channel_id: id.into(), number: 0, size: 4, is_last: false }]
+        );
+        assert_eq!(traced.batches, 0);
+        assert!(traced.error.is_some());
+        assert_eq!(
+            traced.to_string().lines().nth(1),
+            Some("  channel 0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa frame 0: 4 bytes")
+        );
+    }
+}
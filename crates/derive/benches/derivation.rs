@@ -0,0 +1,156 @@
+#![allow(missing_docs)]
+//! Contains benchmarks for the derivation pipeline.
+//!
+//! Run with `cargo bench -p kona-derive --features test-utils,serde`.
+
+use alloy_consensus::TxEnvelope;
+use alloy_eips::eip2718::Decodable2718;
+use alloy_primitives::{address, hex, Bytes};
+use criterion::{criterion_group, criterion_main, Criterion};
+use kona_derive::{
+    constants::MAX_RLP_BYTES_PER_CHANNEL_FJORD,
+    errors::{PipelineError, PipelineErrorKind},
+    sources::EthereumDataSource,
+    stages::{
+        BatchStreamProvider, ChannelProvider, ChannelReader, FrameQueue, L1Retrieval, L1Traversal,
+        NextFrameProvider,
+    },
+    test_utils::{DerivationFixture, TestBlobProvider, TestChainProvider, TestFrameQueueProvider},
+    traits::SignalReceiver,
+    types::ResetSignal,
+};
+use maili_protocol::{Batch, BatchReader, BlockInfo, Frame, DERIVATION_VERSION_0};
+use miniz_oxide::inflate::decompress_to_vec_zlib;
+use op_alloy_genesis::{RollupConfig, SystemConfig};
+use pprof::criterion::{Output, PProfProfiler};
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+
+/// Returns the compressed channel data of a recorded span batch.
+fn channel_data() -> Vec<u8> {
+    let raw = include_str!("../testdata/batch.hex");
+    hex::decode(raw.trim()).unwrap()
+}
+
+fn decode(c: &mut Criterion) {
+    let mut g = c.benchmark_group("decode");
+    g.sample_size(10);
+
+    let cfg = RollupConfig::default();
+    let data = channel_data();
+
+    g.bench_function("Channel decompression", |b| {
+        b.iter(|| decompress_to_vec_zlib(&data).unwrap());
+    });
+
+    g.bench_function("Span batch decode", |b| {
+        b.iter(|| {
+            let mut reader =
+                BatchReader::new(data.as_slice(), MAX_RLP_BYTES_PER_CHANNEL_FJORD as usize);
+            reader.next_batch(&cfg).unwrap()
+        });
+    });
+
+    let mut reader = BatchReader::new(data.as_slice(), MAX_RLP_BYTES_PER_CHANNEL_FJORD as usize);
+    let Some(Batch::Span(batch)) = reader.next_batch(&cfg) else {
+        panic!("expected a span batch");
+    };
+    let txs = batch.batches.iter().flat_map(|b| b.transactions.iter().cloned()).collect::<Vec<_>>();
+
+    g.bench_function("Span batch sender recovery", |b| {
+        b.iter(|| {
+            for tx in &txs {
+                let tx = TxEnvelope::decode_2718(&mut tx.as_ref()).unwrap();
+                tx.recover_signer().unwrap();
+            }
+        });
+    });
+}
+
+fn pipeline(c: &mut Criterion) {
+    let mut g = c.benchmark_group("pipeline");
+    g.sample_size(10);
+    let rt = Runtime::new().unwrap();
+
+    // An L1 block carrying a recorded calldata batcher transaction.
+    let batcher_address = address!("6887246668a3b87F54DeB3b94Ba47a6f63F32985");
+    let system_config = SystemConfig { batcher_address, ..Default::default() };
+    let cfg = Arc::new(RollupConfig {
+        batch_inbox_address: address!("FF00000000000000000000000000000000000010"),
+        ..Default::default()
+    });
+    let block = BlockInfo { number: 10, ..Default::default() };
+    let raw_tx = include_bytes!("../testdata/raw_batcher_tx.hex");
+    let tx = TxEnvelope::decode_2718(&mut raw_tx.as_ref()).unwrap();
+    let mut chain = TestChainProvider::default();
+    chain.insert_block_with_transactions(10, block, vec![tx]);
+
+    g.bench_function("Batcher transaction to frames", |b| {
+        b.iter(|| {
+            rt.block_on(async {
+                let dap = EthereumDataSource::new_from_parts(
+                    chain.clone(),
+                    TestBlobProvider::default(),
+                    &cfg,
+                );
+                let traversal = L1Traversal::new(chain.clone(), cfg.clone());
+                let mut frame_queue =
+                    FrameQueue::new(L1Retrieval::new(traversal, dap), cfg.clone());
+                let reset = ResetSignal {
+                    l1_origin: block,
+                    system_config: Some(system_config),
+                    ..Default::default()
+                };
+                frame_queue.signal(reset.signal()).await.unwrap();
+
+                let mut frames = 0;
+                while frame_queue.next_frame().await.is_ok() {
+                    frames += 1;
+                }
+                assert!(frames > 0);
+            })
+        });
+    });
+
+    // The recorded span batch channel, in a single frame.
+    let frame = Frame::new([0xFF; 16], 0, channel_data(), true);
+    let mut frame_data = vec![DERIVATION_VERSION_0];
+    frame_data.extend_from_slice(&frame.encode());
+    let frame_data = Bytes::from(frame_data);
+
+    g.bench_function("Frames to span batch", |b| {
+        b.iter(|| {
+            rt.block_on(async {
+                let mut provider = TestFrameQueueProvider::new(vec![Ok(frame_data.clone())]);
+                provider.set_origin(block);
+                let frame_queue = FrameQueue::new(provider, cfg.clone());
+                let channel_provider = ChannelProvider::new(cfg.clone(), frame_queue);
+                let mut reader = ChannelReader::new(channel_provider, cfg.clone());
+
+                let mut batches = 0;
+                loop {
+                    match reader.next_batch().await {
+                        Ok(_) => batches += 1,
+                        Err(PipelineErrorKind::Temporary(PipelineError::NotEnoughData)) => {}
+                        Err(PipelineErrorKind::Temporary(PipelineError::Eof)) => break,
+                        Err(e) => panic!("unexpected error: {e}"),
+                    }
+                }
+                assert_eq!(batches, 1);
+            })
+        });
+    });
+
+    let fixture: DerivationFixture =
+        serde_json::from_str(include_str!("../testdata/deposit_only_fixture.json")).unwrap();
+    g.bench_function("Deposit-only attributes - 12 blocks", |b| {
+        b.iter(|| rt.block_on(fixture.derive()).unwrap());
+    });
+}
+
+criterion_group! {
+    name = derivation_benches;
+    config = Criterion::default().with_profiler(PProfProfiler::new(100, Output::Flamegraph(None)));
+    targets = decode, pipeline
+}
+criterion_main!(derivation_benches);
@@ -0,0 +1,173 @@
+#![allow(missing_docs)]
+//! A steady-state derivation fuzzer.
+//!
+//! Random batcher data is pushed through the [FrameQueue] and [ChannelReader] stages, and random
+//! batch payloads through [decode_batch]. Every input must be accepted as frames or batches, or
+//! dropped with a temporary error, without panicking.
+//!
+//! Run with `cargo run -p kona-derive --example derivation_fuzzer --features test-utils --
+//! [iterations] [seed]`.
+
+use alloy_primitives::Bytes;
+use alloy_rlp::Encodable;
+use kona_derive::{
+    errors::PipelineErrorKind,
+    stages::{decode_batch, BatchStreamProvider, ChannelReader, FrameQueue, NextFrameProvider},
+    test_utils::{TestChannelReaderProvider, TestFrameQueueProvider},
+};
+use maili_protocol::{
+    Batch, BlockInfo, Frame, SingleBatch, DERIVATION_VERSION_0, SINGLE_BATCH_TYPE, SPAN_BATCH_TYPE,
+};
+use miniz_oxide::deflate::compress_to_vec_zlib;
+use op_alloy_genesis::RollupConfig;
+use std::sync::Arc;
+
+/// The maximum length of the generated inputs.
+const MAX_INPUT_LEN: usize = 2048;
+
+/// A xorshift pseudo-random number generator, so that runs are reproducible from their seed.
+struct Rng(u64);
+
+impl Rng {
+    const fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    const fn bool(&mut self) -> bool {
+        self.next_u64() & 1 == 1
+    }
+
+    fn bytes(&mut self) -> Vec<u8> {
+        let len = self.next_u64() as usize % MAX_INPUT_LEN;
+        (0..len).map(|_| self.next_u64() as u8).collect()
+    }
+
+    /// Flips a random byte of the data half of the time.
+    fn mutate(&mut self, mut data: Vec<u8>) -> Vec<u8> {
+        if !data.is_empty() && self.bool() {
+            let index = self.next_u64() as usize % data.len();
+            data[index] ^= self.next_u64() as u8 | 1;
+        }
+        data
+    }
+
+    /// Returns batcher transaction data, either random or a possibly mutated, encoded frame.
+    fn batcher_data(&mut self) -> Vec<u8> {
+        if self.bool() {
+            return self.bytes();
+        }
+        let frame = Frame {
+            id: self.next_u64().to_be_bytes().repeat(2).try_into().unwrap(),
+            number: self.next_u64() as u16,
+            data: self.bytes(),
+            is_last: self.bool(),
+        };
+        let data = [&[DERIVATION_VERSION_0], frame.encode().as_slice()].concat();
+        self.mutate(data)
+    }
+
+    /// Returns a batch, either a random payload with a random batch type or a possibly mutated,
+    /// encoded single batch.
+    fn batch(&mut self) -> Vec<u8> {
+        if self.bool() {
+            let batch_type = match self.next_u64() % 3 {
+                0 => SINGLE_BATCH_TYPE,
+                1 => SPAN_BATCH_TYPE,
+                _ => self.next_u64() as u8,
+            };
+            return [&[batch_type], self.bytes().as_slice()].concat();
+        }
+        let batch = SingleBatch {
+            epoch_num: self.next_u64(),
+            timestamp: self.next_u64(),
+            transactions: (0..self.next_u64() % 4).map(|_| self.bytes().into()).collect(),
+            ..Default::default()
+        };
+        let mut data = Vec::new();
+        Batch::Single(batch).encode(&mut data).unwrap();
+        self.mutate(data)
+    }
+}
+
+/// The number of inputs accepted and dropped by a stage.
+#[derive(Debug, Default)]
+struct Outcomes {
+    accepted: usize,
+    dropped: usize,
+}
+
+impl Outcomes {
+    fn record(&mut self, accepted: usize, err: PipelineErrorKind) {
+        assert!(matches!(err, PipelineErrorKind::Temporary(_)), "unexpected error: {err:?}");
+        if accepted > 0 {
+            self.accepted += 1;
+        } else {
+            self.dropped += 1;
+        }
+    }
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let mut args = std::env::args().skip(1);
+    let iterations = args.next().map_or(10_000, |a| a.parse().expect("invalid iterations"));
+    let seed = args.next().map_or(0x6b6f6e61, |a| a.parse().expect("invalid seed"));
+    let mut rng = Rng(seed | 1);
+
+    let (mut frames, mut channels, mut batches) =
+        (Outcomes::default(), Outcomes::default(), Outcomes::default());
+    for _ in 0..iterations {
+        let cfg = Arc::new(RollupConfig {
+            holocene_time: rng.bool().then_some(0),
+            fjord_time: rng.bool().then_some(0),
+            ..Default::default()
+        });
+
+        let data = rng.batcher_data();
+        let mut provider = TestFrameQueueProvider::new(vec![Ok(Bytes::from(data))]);
+        provider.set_origin(BlockInfo::default());
+        let mut frame_queue = FrameQueue::new(provider, cfg.clone());
+        let mut count = 0;
+        let err = loop {
+            match frame_queue.next_frame().await {
+                Ok(_) => count += 1,
+                Err(e) => break e,
+            }
+        };
+        frames.record(count, err);
+
+        // Channel data, either random or a zlib compressed random batch.
+        let data = if rng.bool() {
+            rng.bytes()
+        } else {
+            let mut rlp = Vec::new();
+            Bytes::from(rng.batch()).encode(&mut rlp);
+            compress_to_vec_zlib(&rlp, 1)
+        };
+        let provider = TestChannelReaderProvider::new(vec![Ok(Some(Bytes::from(data)))]);
+        let mut reader = ChannelReader::new(provider, cfg.clone());
+        let mut count = 0;
+        let err = loop {
+            match reader.next_batch().await {
+                Ok(_) => count += 1,
+                Err(e) => break e,
+            }
+        };
+        channels.record(count, err);
+
+        // Batch payloads.
+        if decode_batch(&rng.batch(), &cfg).is_ok() {
+            batches.accepted += 1;
+        } else {
+            batches.dropped += 1;
+        }
+    }
+
+    println!("Ran {iterations} iterations with seed {seed}");
+    println!("Frames:   {frames:?}");
+    println!("Channels: {channels:?}");
+    println!("Batches:  {batches:?}");
+}
@@ -0,0 +1,12 @@
+//! Decodes arbitrary batch data, including span batch bit lists and transaction data.
+
+#![no_main]
+
+use kona_derive::stages::decode_batch;
+use libfuzzer_sys::fuzz_target;
+use op_alloy_genesis::RollupConfig;
+
+fuzz_target!(|data: &[u8]| {
+    let cfg = RollupConfig { delta_time: Some(0), ..Default::default() };
+    let _ = decode_batch(data, &cfg);
+});
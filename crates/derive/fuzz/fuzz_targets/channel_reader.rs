@@ -0,0 +1,21 @@
+//! Decodes arbitrary channel data into batches, as the `ChannelReader` stage does.
+
+#![no_main]
+
+use kona_derive::{
+    constants::{MAX_RLP_BYTES_PER_CHANNEL_BEDROCK, MAX_RLP_BYTES_PER_CHANNEL_FJORD},
+    stages::BatchReader,
+};
+use libfuzzer_sys::fuzz_target;
+use op_alloy_genesis::RollupConfig;
+
+fuzz_target!(|input: (bool, &[u8])| {
+    let (fjord, data) = input;
+    let (cfg, max) = if fjord {
+        (RollupConfig { fjord_time: Some(0), ..Default::default() }, MAX_RLP_BYTES_PER_CHANNEL_FJORD)
+    } else {
+        (RollupConfig::default(), MAX_RLP_BYTES_PER_CHANNEL_BEDROCK)
+    };
+    let mut reader = BatchReader::new(data, max as usize);
+    while reader.next_batch(&cfg).is_some() {}
+});
@@ -0,0 +1,12 @@
+//! Parses arbitrary batcher transaction data into frames.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use maili_protocol::Frame;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(frames) = Frame::parse_frames(data) {
+        assert!(!frames.is_empty());
+    }
+});
@@ -0,0 +1,27 @@
+//! Roundtrips well-formed span batches through their wire format.
+
+#![no_main]
+
+use kona_derive::{fuzzing::ArbitrarySpanBatch, stages::decode_batch};
+use libfuzzer_sys::fuzz_target;
+use maili_protocol::Batch;
+use op_alloy_genesis::{ChainGenesis, RollupConfig};
+
+fuzz_target!(|input: ArbitrarySpanBatch| {
+    let ArbitrarySpanBatch { batch, block_time } = input;
+    let cfg = RollupConfig {
+        block_time,
+        l2_chain_id: batch.chain_id,
+        genesis: ChainGenesis { l2_time: batch.genesis_timestamp, ..Default::default() },
+        ..Default::default()
+    };
+
+    let mut encoded = Vec::new();
+    Batch::Span(batch.clone()).encode(&mut encoded).unwrap();
+    let Ok(Batch::Span(decoded)) = decode_batch(&encoded, &cfg) else {
+        panic!("span batch failed to roundtrip");
+    };
+    assert_eq!(decoded.batches, batch.batches);
+    assert_eq!(decoded.parent_check, batch.parent_check);
+    assert_eq!(decoded.l1_origin_check, batch.l1_origin_check);
+});
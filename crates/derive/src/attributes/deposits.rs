@@ -0,0 +1,203 @@
+//! Derivation of user deposit transactions from L1 receipts.
+
+use crate::errors::PipelineEncodingError;
+use alloc::vec::Vec;
+use alloy_consensus::{Eip658Value, Receipt};
+use alloy_primitives::{Address, Bytes, Log, B256};
+use maili_protocol::{decode_deposit, DEPOSIT_EVENT_ABI_HASH};
+use op_alloy_consensus::TxDeposit;
+
+/// Derive deposits as `Vec<Bytes>` for transaction receipts.
+///
+/// Successful deposits must be emitted by the deposit contract and have the correct event
+/// signature. So the receipt address must equal the specified deposit contract and the first topic
+/// must be the [DEPOSIT_EVENT_ABI_HASH].
+///
+/// The returned transactions are EIP-2718 encoded, ready to be included in the payload attributes.
+pub fn derive_deposits(
+    block_hash: B256,
+    receipts: &[Receipt],
+    deposit_contract: Address,
+) -> Result<Vec<Bytes>, PipelineEncodingError> {
+    deposit_logs(receipts, deposit_contract)
+        .map(|(index, log)| decode_deposit(block_hash, index, log).map_err(Into::into))
+        .collect()
+}
+
+/// Derive deposits as [TxDeposit]s for transaction receipts.
+///
+/// This follows the same rules as [derive_deposits], but returns the decoded transactions so that
+/// the deposits of an L1 block can be inspected without re-decoding the EIP-2718 envelopes.
+///
+/// User deposits are never system transactions, and their source hash is derived from the L1
+/// block hash and the index of the log within the block.
+pub fn decode_deposits(
+    block_hash: B256,
+    receipts: &[Receipt],
+    deposit_contract: Address,
+) -> Result<Vec<TxDeposit>, PipelineEncodingError> {
+    derive_deposits(block_hash, receipts, deposit_contract)?
+        .iter()
+        .map(|encoded| {
+            // Strip the EIP-2718 type byte before decoding the RLP payload.
+            let mut buf = encoded.get(1..).ok_or(PipelineEncodingError::EmptyBuffer)?;
+            TxDeposit::rlp_decode(&mut buf).map_err(PipelineEncodingError::AlloyRlpError)
+        })
+        .collect()
+}
+
+/// Returns an iterator over the deposit event logs in the receipts, paired with their index
+/// within the block.
+///
+/// Logs within failed receipts are skipped, and do not count towards the log index.
+fn deposit_logs(
+    receipts: &[Receipt],
+    deposit_contract: Address,
+) -> impl Iterator<Item = (usize, &Log)> {
+    receipts
+        .iter()
+        .filter(|r| r.status != Eip658Value::Eip658(false))
+        .flat_map(|r| r.logs.iter())
+        .enumerate()
+        .filter(move |(_, l)| {
+            l.data.topics().first() == Some(&DEPOSIT_EVENT_ABI_HASH) &&
+                l.address == deposit_contract
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use alloy_primitives::{address, LogData, TxKind, U256, U64};
+    use maili_protocol::DepositError;
+
+    fn generate_valid_log() -> Log {
+        let deposit_contract = address!("1111111111111111111111111111111111111111");
+        let mut data = vec![0u8; 192];
+        let offset: [u8; 8] = U64::from(32).to_be_bytes();
+        data[24..32].copy_from_slice(&offset);
+        let len: [u8; 8] = U64::from(128).to_be_bytes();
+        data[56..64].copy_from_slice(&len);
+        // Copy the u128 mint value
+        let mint: [u8; 16] = 10_u128.to_be_bytes();
+        data[80..96].copy_from_slice(&mint);
+        // Copy the tx value
+        let value: [u8; 32] = U256::from(100).to_be_bytes();
+        data[96..128].copy_from_slice(&value);
+        // Copy the gas limit
+        let gas: [u8; 8] = 1000_u64.to_be_bytes();
+        data[128..136].copy_from_slice(&gas);
+        // Copy the isCreation flag
+        data[136] = 1;
+        let from = address!("2222222222222222222222222222222222222222");
+        let mut from_bytes = vec![0u8; 32];
+        from_bytes[12..32].copy_from_slice(from.as_slice());
+        let to = address!("3333333333333333333333333333333333333333");
+        let mut to_bytes = vec![0u8; 32];
+        to_bytes[12..32].copy_from_slice(to.as_slice());
+        Log {
+            address: deposit_contract,
+            data: LogData::new_unchecked(
+                vec![
+                    DEPOSIT_EVENT_ABI_HASH,
+                    B256::from_slice(&from_bytes),
+                    B256::from_slice(&to_bytes),
+                    B256::default(),
+                ],
+                Bytes::from(data),
+            ),
+        }
+    }
+
+    fn generate_valid_receipt() -> Receipt {
+        let mut bad_dest_log = generate_valid_log();
+        bad_dest_log.data.topics_mut()[1] = B256::default();
+        let mut invalid_topic_log = generate_valid_log();
+        invalid_topic_log.data.topics_mut()[0] = B256::default();
+        Receipt {
+            status: Eip658Value::Eip658(true),
+            logs: vec![generate_valid_log(), bad_dest_log, invalid_topic_log],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_derive_deposits_empty() {
+        let receipts = vec![];
+        let deposit_contract = Address::default();
+        let result = derive_deposits(B256::default(), &receipts, deposit_contract);
+        assert!(result.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_derive_deposits_non_deposit_events_filtered_out() {
+        let deposit_contract = address!("1111111111111111111111111111111111111111");
+        let mut invalid = generate_valid_receipt();
+        invalid.logs[0].data = LogData::new_unchecked(vec![], Bytes::default());
+        let receipts = vec![generate_valid_receipt(), generate_valid_receipt(), invalid];
+        let result = derive_deposits(B256::default(), &receipts, deposit_contract);
+        assert_eq!(result.unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_derive_deposits_non_deposit_contract_addr() {
+        let deposit_contract = address!("1111111111111111111111111111111111111111");
+        let mut invalid = generate_valid_receipt();
+        invalid.logs[0].address = Address::default();
+        let receipts = vec![generate_valid_receipt(), generate_valid_receipt(), invalid];
+        let result = derive_deposits(B256::default(), &receipts, deposit_contract);
+        assert_eq!(result.unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_derive_deposits_decoding_errors() {
+        let deposit_contract = address!("1111111111111111111111111111111111111111");
+        let mut invalid = generate_valid_receipt();
+        invalid.logs[0].data =
+            LogData::new_unchecked(vec![DEPOSIT_EVENT_ABI_HASH], Bytes::default());
+        let receipts = vec![generate_valid_receipt(), generate_valid_receipt(), invalid];
+        let result = derive_deposits(B256::default(), &receipts, deposit_contract);
+        let downcasted = result.unwrap_err();
+        assert_eq!(downcasted, DepositError::UnexpectedTopicsLen(1).into());
+    }
+
+    #[test]
+    fn test_derive_deposits_succeeds() {
+        let deposit_contract = address!("1111111111111111111111111111111111111111");
+        let receipts = vec![generate_valid_receipt(), generate_valid_receipt()];
+        let result = derive_deposits(B256::default(), &receipts, deposit_contract);
+        assert_eq!(result.unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_decode_deposits_succeeds() {
+        let deposit_contract = address!("1111111111111111111111111111111111111111");
+        let receipts = vec![generate_valid_receipt()];
+        let deposits = decode_deposits(B256::default(), &receipts, deposit_contract).unwrap();
+        assert_eq!(deposits.len(), 2);
+
+        let deposit = &deposits[0];
+        assert_eq!(deposit.from, address!("2222222222222222222222222222222222222222"));
+        assert_eq!(deposit.to, TxKind::Create);
+        assert_eq!(deposit.mint, Some(10));
+        assert_eq!(deposit.value, U256::from(100));
+        assert_eq!(deposit.gas_limit, 1000);
+        assert!(!deposit.is_system_transaction);
+        // Deposits at different log indices have distinct source hashes.
+        assert_ne!(deposit.source_hash, deposits[1].source_hash);
+    }
+
+    #[test]
+    fn test_decode_deposits_skips_failed_receipts() {
+        let deposit_contract = address!("1111111111111111111111111111111111111111");
+        let mut failed = generate_valid_receipt();
+        failed.status = Eip658Value::Eip658(false);
+        let receipts = vec![failed, generate_valid_receipt()];
+        let deposits = decode_deposits(B256::default(), &receipts, deposit_contract).unwrap();
+        let expected =
+            decode_deposits(B256::default(), &[generate_valid_receipt()], deposit_contract)
+                .unwrap();
+        assert_eq!(deposits, expected);
+    }
+}
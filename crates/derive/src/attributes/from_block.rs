@@ -0,0 +1,162 @@
+//! Conversion of executed L2 blocks back into the payload attributes they were built from.
+
+use crate::errors::BuilderError;
+use alloc::vec::Vec;
+use alloy_eips::eip2718::Encodable2718;
+use alloy_primitives::B64;
+use alloy_rpc_types_engine::PayloadAttributes;
+use op_alloy_consensus::OpBlock;
+use op_alloy_genesis::RollupConfig;
+use op_alloy_rpc_types_engine::OpPayloadAttributes;
+
+/// The version byte of the Holocene header extra data.
+const HOLOCENE_EXTRA_DATA_VERSION: u8 = 0x00;
+
+/// Returns the [OpPayloadAttributes] that the given L2 block was built from, the inverse of
+/// executing the attributes.
+///
+/// All transactions of the block are included, with `no_tx_pool` set, so that the attributes
+/// can be compared against derived attributes, or replayed to re-execute the block.
+///
+/// After Holocene, the EIP-1559 parameters are read from the header extra data. The Holocene
+/// activation block signals the Canyon base fee parameters with zeroed parameters, which is what
+/// is returned for it.
+pub fn attributes_from_block(
+    cfg: &RollupConfig,
+    block: &OpBlock,
+) -> Result<OpPayloadAttributes, BuilderError> {
+    let header = &block.header;
+    let timestamp = header.timestamp;
+
+    let mut eip_1559_params = None;
+    if cfg.is_holocene_active(timestamp) {
+        let extra_data = &header.extra_data;
+        if extra_data.len() != 1 + B64::len_bytes() || extra_data[0] != HOLOCENE_EXTRA_DATA_VERSION
+        {
+            return Err(BuilderError::InvalidHoloceneExtraData(extra_data.clone()));
+        }
+        let is_activation_block = !cfg.is_holocene_active(timestamp.saturating_sub(cfg.block_time));
+        eip_1559_params =
+            Some(if is_activation_block { B64::ZERO } else { B64::from_slice(&extra_data[1..]) });
+    }
+
+    let transactions = block.body.transactions.iter().map(|tx| tx.encoded_2718().into()).collect();
+
+    Ok(OpPayloadAttributes {
+        payload_attributes: PayloadAttributes {
+            timestamp,
+            prev_randao: header.mix_hash,
+            suggested_fee_recipient: header.beneficiary,
+            parent_beacon_block_root: header.parent_beacon_block_root,
+            withdrawals: cfg.is_canyon_active(timestamp).then(Vec::new),
+        },
+        transactions: Some(transactions),
+        no_tx_pool: Some(true),
+        gas_limit: Some(header.gas_limit),
+        eip_1559_params,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attributes::stateful::payload_attributes_from_parts;
+    use alloy_consensus::{BlockBody, Header};
+    use alloy_eips::eip2718::Decodable2718;
+    use alloy_primitives::{Bytes, B256};
+    use maili_protocol::{BlockInfo, L2BlockInfo};
+    use op_alloy_consensus::OpTxEnvelope;
+    use op_alloy_genesis::SystemConfig;
+
+    fn holocene_config() -> RollupConfig {
+        RollupConfig {
+            block_time: 2,
+            canyon_time: Some(0),
+            ecotone_time: Some(0),
+            holocene_time: Some(10),
+            ..Default::default()
+        }
+    }
+
+    /// Builds the attributes for the child of an L2 block at the given timestamp.
+    fn attributes(cfg: &RollupConfig, parent_timestamp: u64) -> OpPayloadAttributes {
+        let parent = L2BlockInfo {
+            block_info: BlockInfo { timestamp: parent_timestamp, ..Default::default() },
+            ..Default::default()
+        };
+        let l1_header = Header {
+            mix_hash: B256::with_last_byte(1),
+            parent_beacon_block_root: Some(B256::with_last_byte(2)),
+            ..Default::default()
+        };
+        let sys_config = SystemConfig {
+            gas_limit: 30_000_000,
+            eip1559_denominator: Some(250),
+            eip1559_elasticity: Some(6),
+            ..Default::default()
+        };
+        payload_attributes_from_parts(cfg, parent, &l1_header, 0, Vec::new(), &sys_config).unwrap()
+    }
+
+    /// Builds the block the execution of the attributes results in.
+    fn execute(attrs: &OpPayloadAttributes, extra_data: Bytes) -> OpBlock {
+        let transactions = attrs
+            .transactions
+            .iter()
+            .flatten()
+            .map(|tx| OpTxEnvelope::decode_2718(&mut tx.as_ref()).unwrap())
+            .collect();
+        OpBlock {
+            header: Header {
+                timestamp: attrs.payload_attributes.timestamp,
+                mix_hash: attrs.payload_attributes.prev_randao,
+                beneficiary: attrs.payload_attributes.suggested_fee_recipient,
+                parent_beacon_block_root: attrs.payload_attributes.parent_beacon_block_root,
+                gas_limit: attrs.gas_limit.unwrap(),
+                extra_data,
+                ..Default::default()
+            },
+            body: BlockBody { transactions, ommers: Vec::new(), withdrawals: None },
+        }
+    }
+
+    #[test]
+    fn test_attributes_from_block_roundtrip() {
+        let cfg = holocene_config();
+        let attrs = attributes(&cfg, 6);
+        assert_eq!(attrs.eip_1559_params, None);
+        let block = execute(&attrs, Bytes::new());
+        assert_eq!(attributes_from_block(&cfg, &block).unwrap(), attrs);
+    }
+
+    #[test]
+    fn test_attributes_from_block_holocene() {
+        let cfg = holocene_config();
+
+        // The activation block carries the Canyon parameters, signalled with zeroes.
+        let attrs = attributes(&cfg, 8);
+        assert_eq!(attrs.eip_1559_params, Some(B64::ZERO));
+        let canyon = [0x00, 0x00, 0x00, 0x00, 0x32, 0x00, 0x00, 0x00, 0x06];
+        let block = execute(&attrs, Bytes::copy_from_slice(&canyon));
+        assert_eq!(attributes_from_block(&cfg, &block).unwrap(), attrs);
+
+        let attrs = attributes(&cfg, 10);
+        let params = attrs.eip_1559_params.unwrap();
+        assert_eq!(params, B64::from_slice(&[0x00, 0x00, 0x00, 0xFA, 0x00, 0x00, 0x00, 0x06]));
+        let block =
+            execute(&attrs, [&[HOLOCENE_EXTRA_DATA_VERSION], params.as_slice()].concat().into());
+        assert_eq!(attributes_from_block(&cfg, &block).unwrap(), attrs);
+    }
+
+    #[test]
+    fn test_attributes_from_block_invalid_extra_data() {
+        let cfg = holocene_config();
+        let attrs = attributes(&cfg, 10);
+        let extra_data = Bytes::from_static(&[0x01; 9]);
+        let block = execute(&attrs, extra_data.clone());
+        assert_eq!(
+            attributes_from_block(&cfg, &block),
+            Err(BuilderError::InvalidHoloceneExtraData(extra_data))
+        );
+    }
+}
@@ -0,0 +1,102 @@
+//! Decoding of the L1 info deposit transaction at the top of every L2 block.
+
+use crate::errors::PipelineEncodingError;
+use alloc::string::ToString;
+use alloy_eips::eip2718::Decodable2718;
+use op_alloy_consensus::OpTxEnvelope;
+
+pub use maili_protocol::{L1BlockInfoBedrock, L1BlockInfoEcotone, L1BlockInfoTx};
+
+/// Decodes the [L1BlockInfoTx] from the L1 info deposit transaction, which is always the first
+/// transaction in an L2 block.
+///
+/// Both the Bedrock `setL1BlockValues` and the Ecotone `setL1BlockValuesEcotone` calldata formats
+/// are supported.
+pub fn l1_block_info_from_tx(tx: &OpTxEnvelope) -> Result<L1BlockInfoTx, PipelineEncodingError> {
+    let OpTxEnvelope::Deposit(deposit) = tx else {
+        return Err(PipelineEncodingError::InvalidL1InfoTxType(tx.tx_type() as u8));
+    };
+    L1BlockInfoTx::decode_calldata(deposit.input.as_ref())
+        .map_err(|e| PipelineEncodingError::L1InfoDecodingError(e.to_string()))
+}
+
+/// Decodes the [L1BlockInfoTx] from an EIP-2718 encoded L1 info deposit transaction.
+///
+/// See [l1_block_info_from_tx].
+pub fn l1_block_info_from_encoded_tx(
+    mut encoded: &[u8],
+) -> Result<L1BlockInfoTx, PipelineEncodingError> {
+    if encoded.is_empty() {
+        return Err(PipelineEncodingError::EmptyBuffer);
+    }
+    let tx = OpTxEnvelope::decode_2718(&mut encoded)
+        .map_err(|e| PipelineEncodingError::L1InfoDecodingError(e.to_string()))?;
+    l1_block_info_from_tx(&tx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use alloy_consensus::{Header, TxLegacy};
+    use alloy_eips::eip2718::Encodable2718;
+    use alloy_primitives::{PrimitiveSignature, B256};
+    use op_alloy_genesis::{RollupConfig, SystemConfig};
+
+    fn build_l1_info(cfg: &RollupConfig, l2_block_time: u64) -> (L1BlockInfoTx, OpTxEnvelope) {
+        let header = Header { number: 10, timestamp: 100, ..Default::default() };
+        L1BlockInfoTx::try_new_with_deposit_tx(
+            cfg,
+            &SystemConfig::default(),
+            3,
+            &header,
+            l2_block_time,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_l1_block_info_from_tx_bedrock() {
+        let cfg = RollupConfig::default();
+        let (expected, tx) = build_l1_info(&cfg, 102);
+        assert!(matches!(expected, L1BlockInfoTx::Bedrock(_)));
+
+        let decoded = l1_block_info_from_tx(&tx).unwrap();
+        assert_eq!(decoded.encode_calldata(), expected.encode_calldata());
+        assert_eq!(decoded.sequence_number(), 3);
+        assert_eq!(decoded.id().number, 10);
+    }
+
+    #[test]
+    fn test_l1_block_info_from_encoded_tx_ecotone() {
+        let cfg = RollupConfig { ecotone_time: Some(0), ..Default::default() };
+        let (expected, tx) = build_l1_info(&cfg, 102);
+        assert!(matches!(expected, L1BlockInfoTx::Ecotone(_)));
+
+        let mut encoded = Vec::new();
+        tx.encode_2718(&mut encoded);
+        let decoded = l1_block_info_from_encoded_tx(&encoded).unwrap();
+        assert_eq!(decoded.encode_calldata(), expected.encode_calldata());
+    }
+
+    #[test]
+    fn test_l1_block_info_from_tx_not_deposit() {
+        let tx = OpTxEnvelope::Legacy(alloy_consensus::Signed::new_unchecked(
+            TxLegacy::default(),
+            PrimitiveSignature::test_signature(),
+            B256::ZERO,
+        ));
+        assert_eq!(
+            l1_block_info_from_tx(&tx).unwrap_err(),
+            PipelineEncodingError::InvalidL1InfoTxType(0)
+        );
+    }
+
+    #[test]
+    fn test_l1_block_info_from_encoded_tx_empty() {
+        assert_eq!(
+            l1_block_info_from_encoded_tx(&[]).unwrap_err(),
+            PipelineEncodingError::EmptyBuffer
+        );
+    }
+}
@@ -2,5 +2,26 @@
 //!
 //! [AttributesBuilder]: crate::traits::AttributesBuilder
 
+mod deposits;
+pub use deposits::{decode_deposits, derive_deposits};
+
+mod l1_info;
+pub use l1_info::{
+    l1_block_info_from_encoded_tx, l1_block_info_from_tx, L1BlockInfoBedrock, L1BlockInfoEcotone,
+    L1BlockInfoTx,
+};
+
+mod upgrades;
+pub use upgrades::upgrade_transactions;
+
 mod stateful;
 pub use stateful::StatefulAttributesBuilder;
+
+mod from_block;
+pub use from_block::attributes_from_block;
+
+mod validation;
+pub use validation::validate_attributes;
+
+mod stateless;
+pub use stateless::StatelessAttributesBuilder;
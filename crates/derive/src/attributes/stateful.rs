@@ -1,21 +1,20 @@
-#![allow(clippy::unnecessary_map_or)]
 //! The [`AttributesBuilder`] and it's default implementation.
 
 use crate::{
-    errors::{BuilderError, PipelineEncodingError, PipelineError, PipelineErrorKind},
-    traits::{AttributesBuilder, ChainProvider, L2ChainProvider},
-    types::PipelineResult,
+    attributes::{derive_deposits, upgrade_transactions, L1BlockInfoTx},
+    errors::{BuilderError, PipelineError, PipelineErrorKind},
+    traits::{AttributesBuilder, BeaconRootProvider, ChainProvider, L2ChainProvider},
+    types::{update_system_config, PipelineResult},
 };
 use alloc::{boxed::Box, fmt::Debug, string::ToString, sync::Arc, vec, vec::Vec};
-use alloy_consensus::{Eip658Value, Receipt};
+use alloy_consensus::Header;
 use alloy_eips::{eip2718::Encodable2718, BlockNumHash};
-use alloy_primitives::{address, Address, Bytes, B256};
+use alloy_primitives::{address, Address, Bytes};
 use alloy_rlp::Encodable;
 use alloy_rpc_types_engine::PayloadAttributes;
 use async_trait::async_trait;
-use maili_protocol::{decode_deposit, L1BlockInfoTx, L2BlockInfo, DEPOSIT_EVENT_ABI_HASH};
-use op_alloy_consensus::{Hardfork, Hardforks};
-use op_alloy_genesis::RollupConfig;
+use maili_protocol::L2BlockInfo;
+use op_alloy_genesis::{RollupConfig, SystemConfig};
 use op_alloy_rpc_types_engine::OpPayloadAttributes;
 
 /// The sequencer fee vault address.
@@ -34,6 +33,8 @@ where
     config_fetcher: L2P,
     /// The L1 receipts fetcher.
     receipts_fetcher: L1P,
+    /// The fallback for parent beacon block roots missing from L1 headers.
+    beacon_roots: Option<Arc<dyn BeaconRootProvider>>,
 }
 
 impl<L1P, L2P> StatefulAttributesBuilder<L1P, L2P>
@@ -43,7 +44,19 @@ where
 {
     /// Create a new [StatefulAttributesBuilder] with the given epoch.
     pub const fn new(rcfg: Arc<RollupConfig>, sys_cfg_fetcher: L2P, receipts: L1P) -> Self {
-        Self { rollup_cfg: rcfg, config_fetcher: sys_cfg_fetcher, receipts_fetcher: receipts }
+        Self {
+            rollup_cfg: rcfg,
+            config_fetcher: sys_cfg_fetcher,
+            receipts_fetcher: receipts,
+            beacon_roots: None,
+        }
+    }
+
+    /// Sets the [BeaconRootProvider] used to fetch the parent beacon block root of post-Cancun L1
+    /// origins whose header does not carry it.
+    pub fn with_beacon_root_fallback(mut self, beacon_roots: Arc<dyn BeaconRootProvider>) -> Self {
+        self.beacon_roots = Some(beacon_roots);
+        self
     }
 }
 
@@ -58,7 +71,7 @@ where
         l2_parent: L2BlockInfo,
         epoch: BlockNumHash,
     ) -> PipelineResult<OpPayloadAttributes> {
-        let l1_header;
+        let mut l1_header;
         let deposit_transactions: Vec<Bytes>;
 
         let mut sys_config = self
@@ -87,15 +100,14 @@ where
                 self.receipts_fetcher.receipts_by_hash(epoch.hash).await.map_err(Into::into)?;
             let deposits =
                 derive_deposits(epoch.hash, &receipts, self.rollup_cfg.deposit_contract_address)
-                    .await
                     .map_err(|e| PipelineError::BadEncoding(e).crit())?;
-            sys_config
-                .update_with_receipts(
-                    &receipts,
-                    self.rollup_cfg.l1_system_config_address,
-                    self.rollup_cfg.is_ecotone_active(header.timestamp),
-                )
-                .map_err(|e| PipelineError::SystemConfigUpdate(e).crit())?;
+            update_system_config(
+                &mut sys_config,
+                &receipts,
+                self.rollup_cfg.l1_system_config_address,
+                self.rollup_cfg.is_ecotone_active(header.timestamp),
+            )
+            .map_err(|e| PipelineError::SystemConfigUpdate(e).crit())?;
             l1_header = header;
             deposit_transactions = deposits;
             0
@@ -114,116 +126,122 @@ where
             l2_parent.seq_num + 1
         };
 
-        // Sanity check the L1 origin was correctly selected to maintain the time invariant
-        // between L1 and L2.
+        // Post-Ecotone, the attributes commit to the parent beacon block root of the L1 origin.
+        // Fall back to the beacon chain if the L1 provider did not return it.
         let next_l2_time = l2_parent.block_info.timestamp + self.rollup_cfg.block_time;
-        if next_l2_time < l1_header.timestamp {
-            return Err(PipelineErrorKind::Reset(
-                BuilderError::BrokenTimeInvariant(
-                    l2_parent.l1_origin,
-                    next_l2_time,
-                    BlockNumHash { hash: l1_header.hash_slow(), number: l1_header.number },
-                    l1_header.timestamp,
-                )
-                .into(),
-            ));
-        }
-
-        let mut upgrade_transactions: Vec<Bytes> = vec![];
         if self.rollup_cfg.is_ecotone_active(next_l2_time) &&
-            !self.rollup_cfg.is_ecotone_active(l2_parent.block_info.timestamp)
-        {
-            upgrade_transactions = Hardforks::ECOTONE.txs().collect();
-        }
-        if self.rollup_cfg.is_fjord_active(next_l2_time) &&
-            !self.rollup_cfg.is_fjord_active(l2_parent.block_info.timestamp)
+            is_missing_parent_beacon_root(&l1_header)
         {
-            upgrade_transactions.append(&mut Hardforks::FJORD.txs().collect());
+            if let Some(beacon_roots) = &self.beacon_roots {
+                let root = beacon_roots
+                    .parent_beacon_block_root(&l1_header)
+                    .await
+                    .map_err(PipelineErrorKind::from)?;
+                l1_header.parent_beacon_block_root = Some(root);
+            }
         }
 
-        // Build and encode the L1 info transaction for the current payload.
-        let (_, l1_info_tx_envelope) = L1BlockInfoTx::try_new_with_deposit_tx(
+        payload_attributes_from_parts(
             &self.rollup_cfg,
-            &sys_config,
-            sequence_number,
+            l2_parent,
             &l1_header,
-            next_l2_time,
+            sequence_number,
+            deposit_transactions,
+            &sys_config,
         )
-        .map_err(|e| {
-            PipelineError::AttributesBuilder(BuilderError::Custom(e.to_string())).crit()
-        })?;
-        let mut encoded_l1_info_tx = Vec::with_capacity(l1_info_tx_envelope.length());
-        l1_info_tx_envelope.encode_2718(&mut encoded_l1_info_tx);
-
-        let mut txs =
-            Vec::with_capacity(1 + deposit_transactions.len() + upgrade_transactions.len());
-        txs.push(encoded_l1_info_tx.into());
-        txs.extend(deposit_transactions);
-        txs.extend(upgrade_transactions);
-
-        let mut withdrawals = None;
-        if self.rollup_cfg.is_canyon_active(next_l2_time) {
-            withdrawals = Some(Vec::default());
-        }
-
-        let mut parent_beacon_root = None;
-        if self.rollup_cfg.is_ecotone_active(next_l2_time) {
-            // if the parent beacon root is not available, default to zero hash
-            parent_beacon_root = Some(l1_header.parent_beacon_block_root.unwrap_or_default());
-        }
+    }
+}
 
-        Ok(OpPayloadAttributes {
-            payload_attributes: PayloadAttributes {
-                timestamp: next_l2_time,
-                prev_randao: l1_header.mix_hash,
-                suggested_fee_recipient: SEQUENCER_FEE_VAULT_ADDRESS,
-                parent_beacon_block_root: parent_beacon_root,
-                withdrawals,
-            },
-            transactions: Some(txs),
-            no_tx_pool: Some(true),
-            gas_limit: Some(u64::from_be_bytes(
-                alloy_primitives::U64::from(sys_config.gas_limit).to_be_bytes(),
-            )),
-            eip_1559_params: sys_config.eip_1559_params(
-                &self.rollup_cfg,
-                l2_parent.block_info.timestamp,
+/// Builds the [OpPayloadAttributes] for the child of `l2_parent`, given the L1 origin header,
+/// the sequence number in the epoch, the user deposits of the epoch, and the updated
+/// [SystemConfig].
+pub(crate) fn payload_attributes_from_parts(
+    rollup_cfg: &RollupConfig,
+    l2_parent: L2BlockInfo,
+    l1_header: &Header,
+    sequence_number: u64,
+    deposit_transactions: Vec<Bytes>,
+    sys_config: &SystemConfig,
+) -> PipelineResult<OpPayloadAttributes> {
+    // Sanity check the L1 origin was correctly selected to maintain the time invariant
+    // between L1 and L2.
+    let next_l2_time = l2_parent.block_info.timestamp + rollup_cfg.block_time;
+    if next_l2_time < l1_header.timestamp {
+        return Err(PipelineErrorKind::Reset(
+            BuilderError::BrokenTimeInvariant(
+                l2_parent.l1_origin,
                 next_l2_time,
-            ),
-        })
+                BlockNumHash { hash: l1_header.hash_slow(), number: l1_header.number },
+                l1_header.timestamp,
+            )
+            .into(),
+        ));
     }
-}
 
-/// Derive deposits as `Vec<Bytes>` for transaction receipts.
-///
-/// Successful deposits must be emitted by the deposit contract and have the correct event
-/// signature. So the receipt address must equal the specified deposit contract and the first topic
-/// must be the [DEPOSIT_EVENT_ABI_HASH].
-async fn derive_deposits(
-    block_hash: B256,
-    receipts: &[Receipt],
-    deposit_contract: Address,
-) -> Result<Vec<Bytes>, PipelineEncodingError> {
-    let mut global_index = 0;
-    let mut res = Vec::new();
-    for r in receipts.iter() {
-        if Eip658Value::Eip658(false) == r.status {
-            continue;
-        }
-        for l in r.logs.iter() {
-            let curr_index = global_index;
-            global_index += 1;
-            if l.data.topics().first().map_or(true, |i| *i != DEPOSIT_EVENT_ABI_HASH) {
-                continue;
-            }
-            if l.address != deposit_contract {
-                continue;
-            }
-            let decoded = decode_deposit(block_hash, curr_index, l)?;
-            res.push(decoded);
+    let upgrade_transactions =
+        upgrade_transactions(rollup_cfg, l2_parent.block_info.timestamp, next_l2_time);
+
+    // Build and encode the L1 info transaction for the current payload.
+    let (_, l1_info_tx_envelope) = L1BlockInfoTx::try_new_with_deposit_tx(
+        rollup_cfg,
+        sys_config,
+        sequence_number,
+        l1_header,
+        next_l2_time,
+    )
+    .map_err(|e| PipelineError::AttributesBuilder(BuilderError::Custom(e.to_string())).crit())?;
+    let mut encoded_l1_info_tx = Vec::with_capacity(l1_info_tx_envelope.length());
+    l1_info_tx_envelope.encode_2718(&mut encoded_l1_info_tx);
+
+    let mut txs = Vec::with_capacity(1 + deposit_transactions.len() + upgrade_transactions.len());
+    txs.push(encoded_l1_info_tx.into());
+    txs.extend(deposit_transactions);
+    txs.extend(upgrade_transactions);
+
+    let mut withdrawals = None;
+    if rollup_cfg.is_canyon_active(next_l2_time) {
+        withdrawals = Some(Vec::default());
+    }
+
+    let mut parent_beacon_root = None;
+    if rollup_cfg.is_ecotone_active(next_l2_time) {
+        if is_missing_parent_beacon_root(l1_header) {
+            return Err(PipelineError::AttributesBuilder(BuilderError::MissingParentBeaconRoot(
+                l1_header.hash_slow(),
+            ))
+            .crit());
         }
+        // Pre-Cancun L1 origins have no parent beacon root, in which case it is the zero hash.
+        parent_beacon_root = Some(l1_header.parent_beacon_block_root.unwrap_or_default());
     }
-    Ok(res)
+
+    Ok(OpPayloadAttributes {
+        payload_attributes: PayloadAttributes {
+            timestamp: next_l2_time,
+            prev_randao: l1_header.mix_hash,
+            suggested_fee_recipient: SEQUENCER_FEE_VAULT_ADDRESS,
+            parent_beacon_block_root: parent_beacon_root,
+            withdrawals,
+        },
+        transactions: Some(txs),
+        no_tx_pool: Some(true),
+        gas_limit: Some(u64::from_be_bytes(
+            alloy_primitives::U64::from(sys_config.gas_limit).to_be_bytes(),
+        )),
+        eip_1559_params: sys_config.eip_1559_params(
+            rollup_cfg,
+            l2_parent.block_info.timestamp,
+            next_l2_time,
+        ),
+    })
+}
+
+/// Returns whether the L1 header is post-Cancun, but does not carry the parent beacon block root.
+///
+/// The blob gas fields and the parent beacon block root were all introduced by Cancun, so a header
+/// with the former but not the latter was returned incomplete by the L1 provider.
+const fn is_missing_parent_beacon_root(l1_header: &Header) -> bool {
+    l1_header.excess_blob_gas.is_some() && l1_header.parent_beacon_block_root.is_none()
 }
 
 #[cfg(test)]
@@ -234,108 +252,10 @@ mod tests {
         test_utils::{TestChainProvider, TestSystemConfigL2Fetcher},
     };
     use alloc::vec;
-    use alloy_consensus::Header;
-    use alloy_primitives::{Log, LogData, B256, U256, U64};
-    use maili_protocol::{BlockInfo, DepositError};
-    use op_alloy_genesis::SystemConfig;
-
-    fn generate_valid_log() -> Log {
-        let deposit_contract = address!("1111111111111111111111111111111111111111");
-        let mut data = vec![0u8; 192];
-        let offset: [u8; 8] = U64::from(32).to_be_bytes();
-        data[24..32].copy_from_slice(&offset);
-        let len: [u8; 8] = U64::from(128).to_be_bytes();
-        data[56..64].copy_from_slice(&len);
-        // Copy the u128 mint value
-        let mint: [u8; 16] = 10_u128.to_be_bytes();
-        data[80..96].copy_from_slice(&mint);
-        // Copy the tx value
-        let value: [u8; 32] = U256::from(100).to_be_bytes();
-        data[96..128].copy_from_slice(&value);
-        // Copy the gas limit
-        let gas: [u8; 8] = 1000_u64.to_be_bytes();
-        data[128..136].copy_from_slice(&gas);
-        // Copy the isCreation flag
-        data[136] = 1;
-        let from = address!("2222222222222222222222222222222222222222");
-        let mut from_bytes = vec![0u8; 32];
-        from_bytes[12..32].copy_from_slice(from.as_slice());
-        let to = address!("3333333333333333333333333333333333333333");
-        let mut to_bytes = vec![0u8; 32];
-        to_bytes[12..32].copy_from_slice(to.as_slice());
-        Log {
-            address: deposit_contract,
-            data: LogData::new_unchecked(
-                vec![
-                    DEPOSIT_EVENT_ABI_HASH,
-                    B256::from_slice(&from_bytes),
-                    B256::from_slice(&to_bytes),
-                    B256::default(),
-                ],
-                Bytes::from(data),
-            ),
-        }
-    }
-
-    fn generate_valid_receipt() -> Receipt {
-        let mut bad_dest_log = generate_valid_log();
-        bad_dest_log.data.topics_mut()[1] = B256::default();
-        let mut invalid_topic_log = generate_valid_log();
-        invalid_topic_log.data.topics_mut()[0] = B256::default();
-        Receipt {
-            status: Eip658Value::Eip658(true),
-            logs: vec![generate_valid_log(), bad_dest_log, invalid_topic_log],
-            ..Default::default()
-        }
-    }
-
-    #[tokio::test]
-    async fn test_derive_deposits_empty() {
-        let receipts = vec![];
-        let deposit_contract = Address::default();
-        let result = derive_deposits(B256::default(), &receipts, deposit_contract).await;
-        assert!(result.unwrap().is_empty());
-    }
-
-    #[tokio::test]
-    async fn test_derive_deposits_non_deposit_events_filtered_out() {
-        let deposit_contract = address!("1111111111111111111111111111111111111111");
-        let mut invalid = generate_valid_receipt();
-        invalid.logs[0].data = LogData::new_unchecked(vec![], Bytes::default());
-        let receipts = vec![generate_valid_receipt(), generate_valid_receipt(), invalid];
-        let result = derive_deposits(B256::default(), &receipts, deposit_contract).await;
-        assert_eq!(result.unwrap().len(), 5);
-    }
-
-    #[tokio::test]
-    async fn test_derive_deposits_non_deposit_contract_addr() {
-        let deposit_contract = address!("1111111111111111111111111111111111111111");
-        let mut invalid = generate_valid_receipt();
-        invalid.logs[0].address = Address::default();
-        let receipts = vec![generate_valid_receipt(), generate_valid_receipt(), invalid];
-        let result = derive_deposits(B256::default(), &receipts, deposit_contract).await;
-        assert_eq!(result.unwrap().len(), 5);
-    }
-
-    #[tokio::test]
-    async fn test_derive_deposits_decoding_errors() {
-        let deposit_contract = address!("1111111111111111111111111111111111111111");
-        let mut invalid = generate_valid_receipt();
-        invalid.logs[0].data =
-            LogData::new_unchecked(vec![DEPOSIT_EVENT_ABI_HASH], Bytes::default());
-        let receipts = vec![generate_valid_receipt(), generate_valid_receipt(), invalid];
-        let result = derive_deposits(B256::default(), &receipts, deposit_contract).await;
-        let downcasted = result.unwrap_err();
-        assert_eq!(downcasted, DepositError::UnexpectedTopicsLen(1).into());
-    }
-
-    #[tokio::test]
-    async fn test_derive_deposits_succeeds() {
-        let deposit_contract = address!("1111111111111111111111111111111111111111");
-        let receipts = vec![generate_valid_receipt(), generate_valid_receipt()];
-        let result = derive_deposits(B256::default(), &receipts, deposit_contract).await;
-        assert_eq!(result.unwrap().len(), 4);
-    }
+    use alloy_consensus::Receipt;
+    use alloy_primitives::{hex, Log, LogData, B256, B64};
+    use maili_protocol::BlockInfo;
+    use op_alloy_genesis::{CONFIG_UPDATE_EVENT_VERSION_0, CONFIG_UPDATE_TOPIC};
 
     #[tokio::test]
     async fn test_prepare_payload_block_mismatch_epoch_reset() {
@@ -509,6 +429,40 @@ mod tests {
         assert_eq!(payload.transactions.unwrap().len(), 1);
     }
 
+    #[tokio::test]
+    async fn test_prepare_payload_canyon_boundary() {
+        let block_time = 2;
+        let timestamp = 100;
+        let header = Header { timestamp, ..Default::default() };
+        let hash = header.hash_slow();
+        let epoch = BlockNumHash { hash, number: 1 };
+        let l2_parent = L2BlockInfo {
+            block_info: BlockInfo { hash: B256::ZERO, number: 1, timestamp, parent_hash: hash },
+            l1_origin: epoch,
+            seq_num: 0,
+        };
+
+        // The first Canyon block carries empty withdrawals, even if its parent is pre-Canyon.
+        for (canyon_time, withdrawals) in [
+            (timestamp + block_time + 1, None),
+            (timestamp + block_time, Some(vec![])),
+            (timestamp, Some(vec![])),
+        ] {
+            let cfg = Arc::new(RollupConfig {
+                block_time,
+                canyon_time: Some(canyon_time),
+                ..Default::default()
+            });
+            let mut fetcher = TestSystemConfigL2Fetcher::default();
+            fetcher.insert(1, SystemConfig::default());
+            let mut provider = TestChainProvider::default();
+            provider.insert_header(hash, header.clone());
+            let mut builder = StatefulAttributesBuilder::new(cfg, fetcher, provider);
+            let payload = builder.prepare_payload_attributes(l2_parent, epoch).await.unwrap();
+            assert_eq!(payload.payload_attributes.withdrawals, withdrawals);
+        }
+    }
+
     #[tokio::test]
     async fn test_prepare_payload_with_ecotone() {
         let block_time = 2;
@@ -557,6 +511,74 @@ mod tests {
         assert_eq!(payload.transactions.unwrap().len(), 7);
     }
 
+    /// A [BeaconRootProvider] returning a fixed root.
+    #[derive(Debug)]
+    struct TestBeaconRoots(B256);
+
+    #[async_trait]
+    impl BeaconRootProvider for TestBeaconRoots {
+        async fn parent_beacon_block_root(
+            &self,
+            _: &Header,
+        ) -> Result<B256, crate::errors::BlobProviderError> {
+            Ok(self.0)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_prepare_payload_parent_beacon_root() {
+        let block_time = 2;
+        let timestamp = 100;
+        let cfg =
+            Arc::new(RollupConfig { block_time, ecotone_time: Some(0), ..Default::default() });
+        let root = B256::with_last_byte(0xBE);
+        let prepare = |header: Header, beacon_roots: Option<B256>| {
+            let mut fetcher = TestSystemConfigL2Fetcher::default();
+            fetcher.insert(1, SystemConfig::default());
+            let hash = header.hash_slow();
+            let mut provider = TestChainProvider::default();
+            provider.insert_header(hash, header);
+            let mut builder = StatefulAttributesBuilder::new(cfg.clone(), fetcher, provider);
+            if let Some(root) = beacon_roots {
+                builder = builder.with_beacon_root_fallback(Arc::new(TestBeaconRoots(root)));
+            }
+            let epoch = BlockNumHash { hash, number: 1 };
+            let l2_parent = L2BlockInfo {
+                block_info: BlockInfo { number: 1, timestamp, ..Default::default() },
+                l1_origin: epoch,
+                seq_num: 0,
+            };
+            async move { builder.prepare_payload_attributes(l2_parent, epoch).await }
+        };
+
+        // The root is taken from the header if present.
+        let header = Header {
+            timestamp,
+            excess_blob_gas: Some(0),
+            parent_beacon_block_root: Some(root),
+            ..Default::default()
+        };
+        let payload = prepare(header, None).await.unwrap();
+        assert_eq!(payload.payload_attributes.parent_beacon_block_root, Some(root));
+
+        // A post-Cancun header without the root is an error, unless there is a fallback.
+        let header = Header { timestamp, excess_blob_gas: Some(0), ..Default::default() };
+        assert_eq!(
+            prepare(header.clone(), None).await.unwrap_err(),
+            PipelineError::AttributesBuilder(BuilderError::MissingParentBeaconRoot(
+                header.hash_slow()
+            ))
+            .crit()
+        );
+        let payload = prepare(header, Some(root)).await.unwrap();
+        assert_eq!(payload.payload_attributes.parent_beacon_block_root, Some(root));
+
+        // Pre-Cancun L1 origins have a zero parent beacon root.
+        let header = Header { timestamp, ..Default::default() };
+        let payload = prepare(header, Some(root)).await.unwrap();
+        assert_eq!(payload.payload_attributes.parent_beacon_block_root, Some(B256::ZERO));
+    }
+
     #[tokio::test]
     async fn test_prepare_payload_with_fjord() {
         let block_time = 2;
@@ -603,4 +625,87 @@ mod tests {
         assert_eq!(payload.transactions.as_ref().unwrap().len(), 10);
         assert_eq!(payload, expected);
     }
+
+    #[tokio::test]
+    async fn test_prepare_payload_holocene_activation_block() {
+        let block_time = 2;
+        let timestamp = 100;
+        let cfg =
+            Arc::new(RollupConfig { block_time, holocene_time: Some(102), ..Default::default() });
+        let l2_number = 1;
+        let mut fetcher = TestSystemConfigL2Fetcher::default();
+        fetcher.insert(l2_number, SystemConfig::default());
+        let mut provider = TestChainProvider::default();
+        let header = Header { timestamp, ..Default::default() };
+        let hash = header.hash_slow();
+        provider.insert_header(hash, header);
+        let mut builder = StatefulAttributesBuilder::new(cfg, fetcher, provider);
+        let epoch = BlockNumHash { hash, number: l2_number };
+        let l2_parent = L2BlockInfo {
+            block_info: BlockInfo { number: l2_number, timestamp, ..Default::default() },
+            l1_origin: BlockNumHash { hash, number: l2_number },
+            seq_num: 0,
+        };
+        let payload = builder.prepare_payload_attributes(l2_parent, epoch).await.unwrap();
+
+        // The first Holocene block signals the execution layer to use the Canyon parameters.
+        assert_eq!(payload.eip_1559_params, Some(B64::ZERO));
+    }
+
+    #[tokio::test]
+    async fn test_prepare_payload_holocene_eip1559_update() {
+        let block_time = 2;
+        let timestamp = 100;
+        let l1_system_config_address = address!("1337000000000000000000000000000000000000");
+        let cfg = Arc::new(RollupConfig {
+            block_time,
+            holocene_time: Some(0),
+            l1_system_config_address,
+            ..Default::default()
+        });
+        let l2_number = 1;
+        let mut fetcher = TestSystemConfigL2Fetcher::default();
+        let sys_config = SystemConfig {
+            eip1559_denominator: Some(250),
+            eip1559_elasticity: Some(6),
+            ..Default::default()
+        };
+        fetcher.insert(l2_number, sys_config);
+
+        // The epoch's L1 block updates the EIP-1559 parameters.
+        let parent_hash = B256::with_last_byte(1);
+        let header = Header { parent_hash, timestamp, ..Default::default() };
+        let hash = header.hash_slow();
+        let log = Log {
+            address: l1_system_config_address,
+            data: LogData::new_unchecked(
+                vec![
+                    CONFIG_UPDATE_TOPIC,
+                    CONFIG_UPDATE_EVENT_VERSION_0,
+                    B256::left_padding_from(&4u64.to_be_bytes()),
+                ],
+                hex!("000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000babe0000beef").into(),
+            ),
+        };
+        let receipt = Receipt { status: true.into(), logs: vec![log], ..Default::default() };
+        let mut provider = TestChainProvider::default();
+        provider.insert_header(hash, header);
+        provider.insert_receipts(hash, vec![receipt]);
+
+        let mut builder = StatefulAttributesBuilder::new(cfg.clone(), fetcher, provider);
+        let epoch = BlockNumHash { hash, number: l2_number + 1 };
+        let l2_parent = L2BlockInfo {
+            block_info: BlockInfo { number: l2_number, timestamp, ..Default::default() },
+            l1_origin: BlockNumHash { hash: parent_hash, number: l2_number },
+            seq_num: 3,
+        };
+        let payload = builder.prepare_payload_attributes(l2_parent, epoch).await.unwrap();
+        assert_eq!(payload.eip_1559_params, Some(B64::from(0x0000babe_0000beef_u64)));
+
+        // Within the epoch, the parent's parameters are used.
+        let epoch = BlockNumHash { hash, number: l2_number };
+        let l2_parent = L2BlockInfo { l1_origin: epoch, ..l2_parent };
+        let payload = builder.prepare_payload_attributes(l2_parent, epoch).await.unwrap();
+        assert_eq!(payload.eip_1559_params, Some(B64::from(0x000000fa_00000006_u64)));
+    }
 }
@@ -0,0 +1,298 @@
+//! Contains the [StatelessAttributesBuilder], an [AttributesBuilder] fed with explicit inputs.
+
+use crate::{
+    attributes::{derive_deposits, stateful::payload_attributes_from_parts},
+    errors::{BuilderError, PipelineError, PipelineErrorKind},
+    traits::AttributesBuilder,
+    types::{update_system_config, PipelineResult},
+};
+use alloc::{boxed::Box, collections::BTreeMap, sync::Arc, vec::Vec};
+use alloy_consensus::{proofs::calculate_receipt_root, Header, Receipt, ReceiptEnvelope};
+use alloy_eips::BlockNumHash;
+use alloy_primitives::B256;
+use async_trait::async_trait;
+use maili_protocol::L2BlockInfo;
+use op_alloy_genesis::{RollupConfig, SystemConfig};
+use op_alloy_rpc_types_engine::OpPayloadAttributes;
+
+/// An [AttributesBuilder] that builds payload attributes from explicitly provided inputs,
+/// without calling into any provider.
+///
+/// The L1 origin headers, the receipts of the L1 origins that start a new epoch, and the system
+/// configs of the L2 parent blocks are inserted up front, e.g. from a witness in FPVM or zkVM
+/// environments. A missing input is a critical error, since it cannot be fetched later.
+///
+/// The L1 inputs are authenticated against the epoch hashes: headers are keyed by their hash, and
+/// receipts are only accepted if they match the receipts root of their header, so that a witness
+/// cannot inject deposits or system config updates. The system configs are trusted, and must be
+/// derived from the L2 parent blocks by the caller.
+#[derive(Debug, Default, Clone)]
+pub struct StatelessAttributesBuilder {
+    /// The rollup config.
+    rollup_cfg: Arc<RollupConfig>,
+    /// The L1 headers, keyed by block hash.
+    headers: BTreeMap<B256, Header>,
+    /// The L1 receipts, keyed by block hash.
+    receipts: BTreeMap<B256, Vec<Receipt>>,
+    /// The system configs, keyed by L2 block number.
+    system_configs: BTreeMap<u64, SystemConfig>,
+}
+
+impl StatelessAttributesBuilder {
+    /// Creates a new [StatelessAttributesBuilder] without any inputs.
+    pub const fn new(rollup_cfg: Arc<RollupConfig>) -> Self {
+        Self {
+            rollup_cfg,
+            headers: BTreeMap::new(),
+            receipts: BTreeMap::new(),
+            system_configs: BTreeMap::new(),
+        }
+    }
+
+    /// Inserts an L1 header, keyed by its hash.
+    pub fn insert_header(&mut self, header: Header) {
+        self.headers.insert(header.hash_slow(), header);
+    }
+
+    /// Inserts the receipts of the L1 block with the given hash, in block order.
+    ///
+    /// The header of the block must be inserted first, and the receipts are rejected unless their
+    /// ordered trie root matches its receipts root.
+    pub fn insert_receipts(
+        &mut self,
+        hash: B256,
+        receipts: Vec<ReceiptEnvelope>,
+    ) -> Result<(), BuilderError> {
+        let header = self.headers.get(&hash).ok_or(BuilderError::MissingHeader(hash))?;
+        let receipts_root = calculate_receipt_root(&receipts);
+        if receipts_root != header.receipts_root {
+            return Err(BuilderError::ReceiptsRootMismatch(header.receipts_root, receipts_root));
+        }
+        let receipts = receipts.iter().filter_map(|r| r.as_receipt().cloned()).collect();
+        self.receipts.insert(hash, receipts);
+        Ok(())
+    }
+
+    /// Inserts the system config of the L2 block with the given number.
+    pub fn insert_system_config(&mut self, number: u64, system_config: SystemConfig) {
+        self.system_configs.insert(number, system_config);
+    }
+
+    /// Builds the [OpPayloadAttributes] for the child of `l2_parent` in the given epoch from the
+    /// provided inputs.
+    pub fn build(
+        &self,
+        l2_parent: L2BlockInfo,
+        epoch: BlockNumHash,
+    ) -> PipelineResult<OpPayloadAttributes> {
+        let missing = |e: BuilderError| PipelineError::AttributesBuilder(e).crit();
+
+        let mut sys_config =
+            self.system_configs.get(&l2_parent.block_info.number).cloned().ok_or_else(|| {
+                missing(BuilderError::MissingSystemConfig(l2_parent.block_info.number))
+            })?;
+
+        // If the L1 origin changed in this block, then we are in the first block of the epoch,
+        // and the user deposits and system config updates of the L1 origin are applied.
+        let (l1_header, sequence_number, deposit_transactions) =
+            if l2_parent.l1_origin.number != epoch.number {
+                let header = self
+                    .headers
+                    .get(&epoch.hash)
+                    .ok_or_else(|| missing(BuilderError::MissingHeader(epoch.hash)))?;
+                if l2_parent.l1_origin.hash != header.parent_hash {
+                    return Err(PipelineErrorKind::Reset(
+                        BuilderError::BlockMismatchEpochReset(
+                            epoch,
+                            l2_parent.l1_origin,
+                            header.parent_hash,
+                        )
+                        .into(),
+                    ));
+                }
+                let receipts = self
+                    .receipts
+                    .get(&epoch.hash)
+                    .ok_or_else(|| missing(BuilderError::MissingReceipts(epoch.hash)))?;
+                let deposits =
+                    derive_deposits(epoch.hash, receipts, self.rollup_cfg.deposit_contract_address)
+                        .map_err(|e| PipelineError::BadEncoding(e).crit())?;
+                update_system_config(
+                    &mut sys_config,
+                    receipts,
+                    self.rollup_cfg.l1_system_config_address,
+                    self.rollup_cfg.is_ecotone_active(header.timestamp),
+                )
+                .map_err(|e| PipelineError::SystemConfigUpdate(e).crit())?;
+                (header, 0, deposits)
+            } else {
+                if l2_parent.l1_origin.hash != epoch.hash {
+                    return Err(PipelineErrorKind::Reset(
+                        BuilderError::BlockMismatch(epoch, l2_parent.l1_origin).into(),
+                    ));
+                }
+                let header = self
+                    .headers
+                    .get(&epoch.hash)
+                    .ok_or_else(|| missing(BuilderError::MissingHeader(epoch.hash)))?;
+                (header, l2_parent.seq_num + 1, Vec::new())
+            };
+
+        payload_attributes_from_parts(
+            &self.rollup_cfg,
+            l2_parent,
+            l1_header,
+            sequence_number,
+            deposit_transactions,
+            &sys_config,
+        )
+    }
+}
+
+#[async_trait]
+impl AttributesBuilder for StatelessAttributesBuilder {
+    async fn prepare_payload_attributes(
+        &mut self,
+        l2_parent: L2BlockInfo,
+        epoch: BlockNumHash,
+    ) -> PipelineResult<OpPayloadAttributes> {
+        self.build(l2_parent, epoch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        attributes::StatefulAttributesBuilder,
+        errors::ResetError,
+        test_utils::{TestChainProvider, TestSystemConfigL2Fetcher},
+    };
+    use alloc::vec;
+    use alloy_consensus::{Eip658Value, ReceiptWithBloom};
+    use alloy_primitives::{Bytes, Log};
+    use maili_protocol::BlockInfo;
+
+    fn test_inputs() -> (Arc<RollupConfig>, Header, L2BlockInfo, BlockNumHash) {
+        let cfg = Arc::new(RollupConfig {
+            block_time: 2,
+            canyon_time: Some(0),
+            ecotone_time: Some(0),
+            ..Default::default()
+        });
+        let parent_hash = B256::with_last_byte(1);
+        let header = Header { parent_hash, number: 2, timestamp: 100, ..Default::default() };
+        let epoch = BlockNumHash { hash: header.hash_slow(), number: 2 };
+        let l2_parent = L2BlockInfo {
+            block_info: BlockInfo { number: 1, timestamp: 100, ..Default::default() },
+            l1_origin: BlockNumHash { hash: parent_hash, number: 1 },
+            seq_num: 4,
+        };
+        (cfg, header, l2_parent, epoch)
+    }
+
+    #[tokio::test]
+    async fn test_stateless_matches_stateful() {
+        let (cfg, header, l2_parent, epoch) = test_inputs();
+
+        let mut fetcher = TestSystemConfigL2Fetcher::default();
+        fetcher.insert(1, SystemConfig::default());
+        let mut provider = TestChainProvider::default();
+        provider.insert_header(epoch.hash, header.clone());
+        provider.insert_receipts(epoch.hash, vec![]);
+        let mut stateful = StatefulAttributesBuilder::new(cfg.clone(), fetcher, provider);
+
+        let mut stateless = StatelessAttributesBuilder::new(cfg);
+        stateless.insert_header(header);
+        stateless.insert_receipts(epoch.hash, vec![]).unwrap();
+        stateless.insert_system_config(1, SystemConfig::default());
+
+        assert_eq!(
+            stateless.prepare_payload_attributes(l2_parent, epoch).await.unwrap(),
+            stateful.prepare_payload_attributes(l2_parent, epoch).await.unwrap()
+        );
+
+        // The next block in the epoch only requires the L1 origin header.
+        let l2_parent = L2BlockInfo { l1_origin: epoch, seq_num: 0, ..l2_parent };
+        let attributes = stateless.build(l2_parent, epoch).unwrap();
+        assert_eq!(
+            attributes,
+            stateful.prepare_payload_attributes(l2_parent, epoch).await.unwrap()
+        );
+    }
+
+    #[test]
+    fn test_stateless_missing_inputs() {
+        let (cfg, header, l2_parent, epoch) = test_inputs();
+        let mut builder = StatelessAttributesBuilder::new(cfg);
+        let err = |e| PipelineError::AttributesBuilder(e).crit();
+
+        assert_eq!(
+            builder.build(l2_parent, epoch).unwrap_err(),
+            err(BuilderError::MissingSystemConfig(1))
+        );
+        builder.insert_system_config(1, SystemConfig::default());
+        assert_eq!(
+            builder.build(l2_parent, epoch).unwrap_err(),
+            err(BuilderError::MissingHeader(epoch.hash))
+        );
+        builder.insert_header(header);
+        assert_eq!(
+            builder.build(l2_parent, epoch).unwrap_err(),
+            err(BuilderError::MissingReceipts(epoch.hash))
+        );
+        builder.insert_receipts(epoch.hash, vec![]).unwrap();
+        assert!(builder.build(l2_parent, epoch).is_ok());
+    }
+
+    #[test]
+    fn test_stateless_receipts_root() {
+        let (cfg, mut header, _, epoch) = test_inputs();
+        let receipt = Receipt {
+            status: Eip658Value::Eip658(true),
+            logs: vec![Log::new_unchecked(cfg.deposit_contract_address, vec![], Bytes::new())],
+            ..Default::default()
+        };
+        let receipts = vec![ReceiptEnvelope::Eip1559(ReceiptWithBloom::from(receipt))];
+        let mut builder = StatelessAttributesBuilder::new(cfg);
+
+        // The receipts are checked against their header, which must be inserted first.
+        assert_eq!(
+            builder.insert_receipts(epoch.hash, receipts.clone()),
+            Err(BuilderError::MissingHeader(epoch.hash))
+        );
+        builder.insert_header(header.clone());
+        assert_eq!(
+            builder.insert_receipts(epoch.hash, receipts.clone()),
+            Err(BuilderError::ReceiptsRootMismatch(
+                header.receipts_root,
+                calculate_receipt_root(&receipts)
+            ))
+        );
+
+        header.receipts_root = calculate_receipt_root(&receipts);
+        let hash = header.hash_slow();
+        builder.insert_header(header);
+        builder.insert_receipts(hash, receipts).unwrap();
+        assert_eq!(builder.receipts[&hash].len(), 1);
+    }
+
+    #[test]
+    fn test_stateless_epoch_mismatch() {
+        let (cfg, header, l2_parent, epoch) = test_inputs();
+        let mut builder = StatelessAttributesBuilder::new(cfg);
+        builder.insert_system_config(1, SystemConfig::default());
+        builder.insert_header(header);
+
+        // The L2 parent is in the same epoch, but on a different L1 origin.
+        let l2_parent =
+            L2BlockInfo { l1_origin: BlockNumHash { hash: B256::ZERO, number: 2 }, ..l2_parent };
+        assert_eq!(
+            builder.build(l2_parent, epoch).unwrap_err(),
+            PipelineErrorKind::Reset(ResetError::AttributesBuilder(BuilderError::BlockMismatch(
+                epoch,
+                l2_parent.l1_origin
+            )))
+        );
+    }
+}
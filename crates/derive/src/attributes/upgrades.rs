@@ -0,0 +1,78 @@
+//! Network upgrade transactions injected into hardfork activation blocks.
+
+use alloc::vec::Vec;
+use alloy_primitives::Bytes;
+use op_alloy_consensus::{Hardfork, Hardforks};
+use op_alloy_genesis::RollupConfig;
+
+/// Returns the network upgrade deposit transactions that must be included in the L2 block at
+/// `next_l2_time`, given the timestamp of its parent.
+///
+/// Upgrade transactions are only included in the first block after a hardfork activates, after
+/// the L1 info transaction and the user deposits. When multiple hardforks activate in the same
+/// block, their upgrade transactions are included in activation order.
+pub fn upgrade_transactions(
+    cfg: &RollupConfig,
+    l2_parent_time: u64,
+    next_l2_time: u64,
+) -> Vec<Bytes> {
+    let mut txs = Vec::new();
+    if cfg.is_ecotone_active(next_l2_time) && !cfg.is_ecotone_active(l2_parent_time) {
+        txs.extend(Hardforks::ECOTONE.txs());
+    }
+    if cfg.is_fjord_active(next_l2_time) && !cfg.is_fjord_active(l2_parent_time) {
+        txs.extend(Hardforks::FJORD.txs());
+    }
+    txs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_eips::eip2718::Decodable2718;
+    use op_alloy_consensus::OpTxEnvelope;
+
+    #[test]
+    fn test_upgrade_transactions_no_activation() {
+        let cfg = RollupConfig { ecotone_time: Some(0), fjord_time: Some(0), ..Default::default() };
+        assert!(upgrade_transactions(&cfg, 100, 102).is_empty());
+
+        let cfg = RollupConfig::default();
+        assert!(upgrade_transactions(&cfg, 100, 102).is_empty());
+    }
+
+    #[test]
+    fn test_upgrade_transactions_ecotone_activation() {
+        let cfg = RollupConfig { ecotone_time: Some(102), ..Default::default() };
+        let txs = upgrade_transactions(&cfg, 100, 102);
+        assert_eq!(txs, Hardforks::ECOTONE.txs().collect::<Vec<_>>());
+        assert_eq!(txs.len(), 6);
+
+        // The block after the activation block does not include the upgrade transactions.
+        assert!(upgrade_transactions(&cfg, 102, 104).is_empty());
+    }
+
+    #[test]
+    fn test_upgrade_transactions_fjord_activation() {
+        let cfg =
+            RollupConfig { ecotone_time: Some(0), fjord_time: Some(102), ..Default::default() };
+        let txs = upgrade_transactions(&cfg, 100, 102);
+        assert_eq!(txs, Hardforks::FJORD.txs().collect::<Vec<_>>());
+        assert_eq!(txs.len(), 3);
+    }
+
+    #[test]
+    fn test_upgrade_transactions_same_block_activation() {
+        let cfg =
+            RollupConfig { ecotone_time: Some(102), fjord_time: Some(102), ..Default::default() };
+        let txs = upgrade_transactions(&cfg, 100, 102);
+        let expected = Hardforks::ECOTONE.txs().chain(Hardforks::FJORD.txs()).collect::<Vec<_>>();
+        assert_eq!(txs, expected);
+
+        // All upgrade transactions are deposits.
+        for tx in txs {
+            let envelope = OpTxEnvelope::decode_2718(&mut tx.as_ref()).unwrap();
+            assert!(matches!(envelope, OpTxEnvelope::Deposit(_)));
+        }
+    }
+}
@@ -0,0 +1,341 @@
+//! Validation of payload attributes against the protocol invariants.
+
+use crate::{attributes::l1_block_info_from_encoded_tx, errors::AttributesValidationError};
+use maili_protocol::{L1BlockInfoTx, L2BlockInfo};
+use op_alloy_consensus::DEPOSIT_TX_TYPE_ID;
+use op_alloy_genesis::{RollupConfig, SystemConfig};
+use op_alloy_rpc_types_engine::OpPayloadAttributes;
+
+/// Checks that the [OpPayloadAttributes] for the child of `parent` uphold the protocol invariants,
+/// regardless of where the attributes come from.
+///
+/// The following is checked:
+/// - The timestamp is exactly one block time after the parent.
+/// - The first transaction is the L1 info deposit, for the parent's L1 origin or its successor,
+///   with the sequence number following the parent's, and not newer than the L2 block.
+/// - No deposit transaction follows a user transaction.
+/// - The gas limit is the one of the [SystemConfig].
+/// - The withdrawals, parent beacon block root and EIP-1559 parameters are set if and only if
+///   Canyon, Ecotone and Holocene are active, respectively.
+///
+/// The L1 origin hash is only checked within the parent's epoch, since the hash of the next L1
+/// block is not known from the parent alone.
+pub fn validate_attributes(
+    cfg: &RollupConfig,
+    parent: &L2BlockInfo,
+    system_config: &SystemConfig,
+    attributes: &OpPayloadAttributes,
+) -> Result<(), AttributesValidationError> {
+    let timestamp = attributes.payload_attributes.timestamp;
+    let expected_timestamp = parent.block_info.timestamp + cfg.block_time;
+    if timestamp != expected_timestamp {
+        return Err(AttributesValidationError::InvalidTimestamp(expected_timestamp, timestamp));
+    }
+
+    let txs = attributes.transactions.as_deref().unwrap_or_default();
+    let l1_info_tx = txs.first().ok_or(AttributesValidationError::MissingTransactions)?;
+    let l1_info = l1_block_info_from_encoded_tx(l1_info_tx)
+        .map_err(AttributesValidationError::InvalidL1InfoTx)?;
+    validate_l1_origin(parent, &l1_info, timestamp)?;
+
+    if let Some(user_tx) = txs.iter().position(|tx| tx.first() != Some(&DEPOSIT_TX_TYPE_ID)) {
+        let deposit = txs[user_tx..].iter().position(|tx| tx.first() == Some(&DEPOSIT_TX_TYPE_ID));
+        if let Some(index) = deposit {
+            return Err(AttributesValidationError::DepositAfterUserTx(user_tx + index));
+        }
+    }
+
+    if attributes.gas_limit != Some(system_config.gas_limit) {
+        return Err(AttributesValidationError::GasLimitMismatch(
+            system_config.gas_limit,
+            attributes.gas_limit,
+        ));
+    }
+
+    let payload = &attributes.payload_attributes;
+    if payload.withdrawals.as_ref().is_some_and(|w| !w.is_empty()) ||
+        payload.withdrawals.is_some() != cfg.is_canyon_active(timestamp)
+    {
+        return Err(AttributesValidationError::HardforkField("withdrawals"));
+    }
+    if payload.parent_beacon_block_root.is_some() != cfg.is_ecotone_active(timestamp) {
+        return Err(AttributesValidationError::HardforkField("parentBeaconBlockRoot"));
+    }
+    if attributes.eip_1559_params.is_some() != cfg.is_holocene_active(timestamp) {
+        return Err(AttributesValidationError::HardforkField("eip1559Params"));
+    }
+
+    Ok(())
+}
+
+/// Checks the L1 origin and sequence number of the L1 info transaction against the parent.
+fn validate_l1_origin(
+    parent: &L2BlockInfo,
+    l1_info: &L1BlockInfoTx,
+    timestamp: u64,
+) -> Result<(), AttributesValidationError> {
+    let origin = l1_info.id();
+    let expected_sequence_number = if origin.number == parent.l1_origin.number {
+        if origin.hash != parent.l1_origin.hash {
+            return Err(AttributesValidationError::L1OriginMismatch(parent.l1_origin, origin));
+        }
+        parent.seq_num + 1
+    } else if origin.number == parent.l1_origin.number + 1 {
+        0
+    } else {
+        return Err(AttributesValidationError::L1OriginMismatch(parent.l1_origin, origin));
+    };
+
+    let sequence_number = l1_info.sequence_number();
+    if sequence_number != expected_sequence_number {
+        return Err(AttributesValidationError::InvalidSequenceNumber(
+            expected_sequence_number,
+            sequence_number,
+        ));
+    }
+
+    let l1_time = match l1_info {
+        L1BlockInfoTx::Bedrock(info) => info.time,
+        L1BlockInfoTx::Ecotone(info) => info.time,
+    };
+    if l1_time > timestamp {
+        return Err(AttributesValidationError::L1OriginInFuture(l1_time, timestamp));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        attributes::stateful::payload_attributes_from_parts, errors::PipelineEncodingError,
+    };
+    use alloc::{vec, vec::Vec};
+    use alloy_consensus::Header;
+    use alloy_eips::BlockNumHash;
+    use alloy_primitives::{Bytes, B256};
+    use maili_protocol::BlockInfo;
+
+    fn l1_header() -> Header {
+        Header { number: 10, timestamp: 20, ..Default::default() }
+    }
+
+    fn parent(cfg: &RollupConfig, seq_num: u64) -> L2BlockInfo {
+        let header = l1_header();
+        let l1_origin = if seq_num == 0 {
+            BlockNumHash { number: 9, hash: header.parent_hash }
+        } else {
+            BlockNumHash { number: 10, hash: header.hash_slow() }
+        };
+        L2BlockInfo {
+            block_info: BlockInfo {
+                number: 5,
+                timestamp: 30 - cfg.block_time,
+                ..Default::default()
+            },
+            l1_origin,
+            seq_num: seq_num.saturating_sub(1),
+        }
+    }
+
+    fn attributes(
+        cfg: &RollupConfig,
+        parent: L2BlockInfo,
+        seq_num: u64,
+        deposits: Vec<Bytes>,
+    ) -> OpPayloadAttributes {
+        payload_attributes_from_parts(
+            cfg,
+            parent,
+            &l1_header(),
+            seq_num,
+            deposits,
+            &SystemConfig::default(),
+        )
+        .unwrap()
+    }
+
+    fn validate(
+        cfg: &RollupConfig,
+        parent: &L2BlockInfo,
+        attrs: &OpPayloadAttributes,
+    ) -> Result<(), AttributesValidationError> {
+        validate_attributes(cfg, parent, &SystemConfig::default(), attrs)
+    }
+
+    #[test]
+    fn test_validate_attributes_valid() {
+        let cfg = RollupConfig { block_time: 2, ..Default::default() };
+        let epoch_start = parent(&cfg, 0);
+        let deposit = Bytes::from_static(&[DEPOSIT_TX_TYPE_ID, 0xC0]);
+        let attrs = attributes(&cfg, epoch_start, 0, vec![deposit]);
+        assert_eq!(validate(&cfg, &epoch_start, &attrs), Ok(()));
+
+        let within_epoch = parent(&cfg, 3);
+        let attrs = attributes(&cfg, within_epoch, 3, vec![]);
+        assert_eq!(validate(&cfg, &within_epoch, &attrs), Ok(()));
+
+        let cfg = RollupConfig {
+            block_time: 2,
+            canyon_time: Some(0),
+            ecotone_time: Some(0),
+            holocene_time: Some(0),
+            ..Default::default()
+        };
+        let attrs = attributes(&cfg, epoch_start, 0, vec![]);
+        assert_eq!(validate(&cfg, &epoch_start, &attrs), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_attributes_timestamp() {
+        let cfg = RollupConfig { block_time: 2, ..Default::default() };
+        let parent = parent(&cfg, 0);
+        let mut attrs = attributes(&cfg, parent, 0, vec![]);
+        attrs.payload_attributes.timestamp += 1;
+        assert_eq!(
+            validate(&cfg, &parent, &attrs),
+            Err(AttributesValidationError::InvalidTimestamp(30, 31))
+        );
+    }
+
+    #[test]
+    fn test_validate_attributes_l1_info_tx() {
+        let cfg = RollupConfig { block_time: 2, ..Default::default() };
+        let parent = parent(&cfg, 0);
+        let mut attrs = attributes(&cfg, parent, 0, vec![]);
+        attrs.transactions = Some(vec![]);
+        assert_eq!(
+            validate(&cfg, &parent, &attrs),
+            Err(AttributesValidationError::MissingTransactions)
+        );
+
+        attrs.transactions = Some(vec![Bytes::from_static(&[0x02, 0xC0])]);
+        assert!(matches!(
+            validate(&cfg, &parent, &attrs),
+            Err(AttributesValidationError::InvalidL1InfoTx(
+                PipelineEncodingError::L1InfoDecodingError(_)
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_validate_attributes_l1_origin() {
+        let cfg = RollupConfig { block_time: 2, ..Default::default() };
+        let header = l1_header();
+        let origin = BlockNumHash { number: 10, hash: header.hash_slow() };
+
+        // The parent is two epochs behind.
+        let mut stale = parent(&cfg, 0);
+        stale.l1_origin.number = 8;
+        let attrs = attributes(&cfg, stale, 0, vec![]);
+        assert_eq!(
+            validate(&cfg, &stale, &attrs),
+            Err(AttributesValidationError::L1OriginMismatch(stale.l1_origin, origin))
+        );
+
+        // The parent is in the same epoch, but on a different L1 block.
+        let mut forked = parent(&cfg, 1);
+        forked.l1_origin.hash = B256::with_last_byte(1);
+        let attrs = attributes(&cfg, forked, 1, vec![]);
+        assert_eq!(
+            validate(&cfg, &forked, &attrs),
+            Err(AttributesValidationError::L1OriginMismatch(forked.l1_origin, origin))
+        );
+    }
+
+    #[test]
+    fn test_validate_attributes_sequence_number() {
+        let cfg = RollupConfig { block_time: 2, ..Default::default() };
+        let within_epoch = parent(&cfg, 2);
+        let attrs = attributes(&cfg, within_epoch, 3, vec![]);
+        assert_eq!(
+            validate(&cfg, &within_epoch, &attrs),
+            Err(AttributesValidationError::InvalidSequenceNumber(2, 3))
+        );
+
+        // A new epoch restarts the sequence number.
+        let epoch_start = parent(&cfg, 0);
+        let attrs = attributes(&cfg, epoch_start, 1, vec![]);
+        assert_eq!(
+            validate(&cfg, &epoch_start, &attrs),
+            Err(AttributesValidationError::InvalidSequenceNumber(0, 1))
+        );
+    }
+
+    #[test]
+    fn test_validate_attributes_l1_origin_in_future() {
+        let cfg = RollupConfig { block_time: 2, ..Default::default() };
+        let mut parent = parent(&cfg, 0);
+        let mut attrs = attributes(&cfg, parent, 0, vec![]);
+        // Move the parent back in time, so that the L1 origin is after the attributes.
+        parent.block_info.timestamp = 10;
+        attrs.payload_attributes.timestamp = 12;
+        assert_eq!(
+            validate(&cfg, &parent, &attrs),
+            Err(AttributesValidationError::L1OriginInFuture(20, 12))
+        );
+    }
+
+    #[test]
+    fn test_validate_attributes_deposit_after_user_tx() {
+        let cfg = RollupConfig { block_time: 2, ..Default::default() };
+        let parent = parent(&cfg, 0);
+        let deposit = Bytes::from_static(&[DEPOSIT_TX_TYPE_ID, 0xC0]);
+        let mut attrs = attributes(&cfg, parent, 0, vec![deposit.clone()]);
+        let txs = attrs.transactions.as_mut().unwrap();
+        txs.push(Bytes::from_static(&[0x02, 0xC0]));
+        txs.push(deposit);
+        assert_eq!(
+            validate(&cfg, &parent, &attrs),
+            Err(AttributesValidationError::DepositAfterUserTx(3))
+        );
+    }
+
+    #[test]
+    fn test_validate_attributes_gas_limit() {
+        let cfg = RollupConfig { block_time: 2, ..Default::default() };
+        let parent = parent(&cfg, 0);
+        let mut attrs = attributes(&cfg, parent, 0, vec![]);
+        attrs.gas_limit = None;
+        assert_eq!(
+            validate(&cfg, &parent, &attrs),
+            Err(AttributesValidationError::GasLimitMismatch(
+                SystemConfig::default().gas_limit,
+                None
+            ))
+        );
+    }
+
+    #[test]
+    fn test_validate_attributes_hardfork_fields() {
+        let cfg = RollupConfig { block_time: 2, ..Default::default() };
+        let parent = parent(&cfg, 0);
+        let mut attrs = attributes(&cfg, parent, 0, vec![]);
+        attrs.payload_attributes.withdrawals = Some(vec![]);
+        assert_eq!(
+            validate(&cfg, &parent, &attrs),
+            Err(AttributesValidationError::HardforkField("withdrawals"))
+        );
+
+        let cfg = RollupConfig {
+            block_time: 2,
+            canyon_time: Some(0),
+            ecotone_time: Some(0),
+            holocene_time: Some(0),
+            ..Default::default()
+        };
+        let mut attrs = attributes(&cfg, parent, 0, vec![]);
+        attrs.payload_attributes.parent_beacon_block_root = None;
+        assert_eq!(
+            validate(&cfg, &parent, &attrs),
+            Err(AttributesValidationError::HardforkField("parentBeaconBlockRoot"))
+        );
+
+        let mut attrs = attributes(&cfg, parent, 0, vec![]);
+        attrs.eip_1559_params = None;
+        assert_eq!(
+            validate(&cfg, &parent, &attrs),
+            Err(AttributesValidationError::HardforkField("eip1559Params"))
+        );
+    }
+}
@@ -0,0 +1,94 @@
+//! Protocol constants used by the derivation pipeline.
+//!
+//! Values that change across hardforks are resolved for an L1 origin timestamp with
+//! [ProtocolConstants::for_timestamp], so that every stage applies the same limits at the same
+//! origin.
+
+use op_alloy_genesis::RollupConfig;
+
+pub use maili_protocol::{MAX_FRAME_LEN, MAX_SPAN_BATCH_ELEMENTS};
+pub use op_alloy_genesis::{
+    FJORD_MAX_SEQUENCER_DRIFT, GRANITE_CHANNEL_TIMEOUT, MAX_RLP_BYTES_PER_CHANNEL_BEDROCK,
+    MAX_RLP_BYTES_PER_CHANNEL_FJORD,
+};
+
+/// The maximum size of a channel bank.
+pub const MAX_CHANNEL_BANK_SIZE: usize = 100_000_000;
+
+/// The maximum size of a channel bank after the Fjord Hardfork.
+pub const FJORD_MAX_CHANNEL_BANK_SIZE: usize = 1_000_000_000;
+
+/// The protocol constants in effect at an L1 origin timestamp.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProtocolConstants {
+    /// The maximum size of a frame, in bytes.
+    pub max_frame_size: usize,
+    /// The number of L1 blocks after which an incomplete channel times out.
+    pub channel_timeout: u64,
+    /// The maximum compressed and decompressed size of a channel, in bytes.
+    pub max_channel_size: usize,
+    /// The maximum size of the channel bank, in bytes.
+    pub max_channel_bank_size: usize,
+    /// The maximum encoded size of a span batch, in bytes.
+    pub max_span_batch_size: usize,
+    /// The maximum number of seconds the L2 time of a batch may run ahead of its L1 origin.
+    pub max_sequencer_drift: u64,
+}
+
+impl ProtocolConstants {
+    /// Returns the [ProtocolConstants] in effect at the given L1 origin timestamp.
+    pub fn for_timestamp(cfg: &RollupConfig, timestamp: u64) -> Self {
+        let max_channel_size = cfg.max_rlp_bytes_per_channel(timestamp) as usize;
+        Self {
+            max_frame_size: MAX_FRAME_LEN,
+            channel_timeout: cfg.channel_timeout(timestamp),
+            max_channel_size,
+            max_channel_bank_size: if cfg.is_fjord_active(timestamp) {
+                FJORD_MAX_CHANNEL_BANK_SIZE
+            } else {
+                MAX_CHANNEL_BANK_SIZE
+            },
+            // Span batches are bounded by the size of the channel they are decoded from.
+            max_span_batch_size: max_channel_size,
+            max_sequencer_drift: cfg.max_sequencer_drift(timestamp),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_protocol_constants_for_timestamp() {
+        let cfg = RollupConfig {
+            channel_timeout: 300,
+            max_sequencer_drift: 600,
+            fjord_time: Some(10),
+            granite_time: Some(20),
+            ..Default::default()
+        };
+
+        let bedrock = ProtocolConstants::for_timestamp(&cfg, 9);
+        assert_eq!(
+            bedrock,
+            ProtocolConstants {
+                max_frame_size: MAX_FRAME_LEN,
+                channel_timeout: 300,
+                max_channel_size: MAX_RLP_BYTES_PER_CHANNEL_BEDROCK as usize,
+                max_channel_bank_size: MAX_CHANNEL_BANK_SIZE,
+                max_span_batch_size: MAX_RLP_BYTES_PER_CHANNEL_BEDROCK as usize,
+                max_sequencer_drift: 600,
+            }
+        );
+
+        let fjord = ProtocolConstants::for_timestamp(&cfg, 10);
+        assert_eq!(fjord.channel_timeout, 300);
+        assert_eq!(fjord.max_channel_size, MAX_RLP_BYTES_PER_CHANNEL_FJORD as usize);
+        assert_eq!(fjord.max_channel_bank_size, FJORD_MAX_CHANNEL_BANK_SIZE);
+        assert_eq!(fjord.max_sequencer_drift, FJORD_MAX_SEQUENCER_DRIFT);
+
+        let granite = ProtocolConstants::for_timestamp(&cfg, 20);
+        assert_eq!(granite.channel_timeout, GRANITE_CHANNEL_TIMEOUT);
+    }
+}
@@ -0,0 +1,252 @@
+//! Contains the [BatchEconomics] analyzer, which aggregates the data availability cost of each
+//! channel against the L2 blocks it carries.
+
+use crate::{
+    provenance::{BatchTracer, L1TxRef},
+    types::ChannelId,
+};
+use alloc::{collections::BTreeMap, sync::Arc, vec::Vec};
+use alloy_consensus::Transaction;
+use alloy_eips::eip2718::Decodable2718;
+use alloy_primitives::{Bytes, B256};
+use maili_protocol::{Batch, FrameParseError};
+use op_alloy_consensus::OpTxEnvelope;
+use op_alloy_genesis::RollupConfig;
+
+/// The data availability type of a batcher transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub enum DaType {
+    /// The frames are posted in the transaction calldata.
+    Calldata,
+    /// The frames are posted in blobs.
+    Blob,
+}
+
+/// The L1 fee paid by the batcher for a transaction, as reported by its receipt.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct BatcherTxFee {
+    /// The execution gas used by the transaction.
+    pub gas_used: u64,
+    /// The effective execution gas price, in wei.
+    pub effective_gas_price: u128,
+    /// The blob gas used by the transaction.
+    pub blob_gas_used: u64,
+    /// The blob gas price, in wei.
+    pub blob_gas_price: u128,
+}
+
+impl BatcherTxFee {
+    /// Returns the total fee paid, in wei.
+    pub const fn total(&self) -> u128 {
+        self.gas_used as u128 * self.effective_gas_price +
+            self.blob_gas_used as u128 * self.blob_gas_price
+    }
+}
+
+/// The data availability statistics of a single channel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct ChannelEconomics {
+    /// The channel ID.
+    pub channel_id: ChannelId,
+    /// The compressed size of the channel, the sum of its frame data sizes, in bytes.
+    pub compressed_size: usize,
+    /// The number of calldata transactions that carried the channel's frames.
+    pub calldata_txs: usize,
+    /// The number of blob transactions that carried the channel's frames.
+    pub blob_txs: usize,
+    /// The L1 fee paid by the batcher for the transactions, in wei.
+    ///
+    /// A transaction carrying frames of several channels is counted in full for each of them.
+    pub l1_fee: u128,
+    /// The number of L2 blocks in the channel.
+    pub l2_blocks: u64,
+    /// The number of L2 transactions in the channel.
+    pub l2_txs: u64,
+    /// The sum of the gas limits of the L2 transactions in the channel.
+    pub l2_gas: u64,
+}
+
+impl ChannelEconomics {
+    /// Returns the L1 fee paid per compressed byte, in wei.
+    pub const fn fee_per_byte(&self) -> Option<u128> {
+        self.l1_fee.checked_div(self.compressed_size as u128)
+    }
+
+    /// Returns the L1 fee paid per unit of L2 gas covered, in wei.
+    pub const fn fee_per_l2_gas(&self) -> Option<u128> {
+        self.l1_fee.checked_div(self.l2_gas as u128)
+    }
+}
+
+/// Aggregates per-channel data availability statistics from the batcher transactions fed to it.
+///
+/// Channels are assembled and decoded by a [BatchTracer], so the same caveats apply: the analyzer
+/// does not apply channel timeouts or batch validity checks. The L1 fee of each transaction is
+/// taken from its receipt. The L2 gas covered is the sum of the gas limits of the batched
+/// transactions, as the gas used is only known after execution.
+#[derive(Debug)]
+pub struct BatchEconomics {
+    /// The tracer assembling the channels.
+    tracer: BatchTracer,
+    /// The DA type and fee of each fed transaction, by transaction hash.
+    txs: BTreeMap<B256, (DaType, BatcherTxFee)>,
+}
+
+impl BatchEconomics {
+    /// Creates a new [BatchEconomics] analyzer.
+    pub const fn new(cfg: Arc<RollupConfig>) -> Self {
+        Self { tracer: BatchTracer::new(cfg), txs: BTreeMap::new() }
+    }
+
+    /// Returns the underlying [BatchTracer].
+    pub const fn tracer(&self) -> &BatchTracer {
+        &self.tracer
+    }
+
+    /// Adds a calldata batcher transaction and the fee it paid.
+    pub fn add_calldata_tx(
+        &mut self,
+        tx: L1TxRef,
+        data: &[u8],
+        fee: BatcherTxFee,
+    ) -> Result<usize, FrameParseError> {
+        self.txs.insert(tx.hash, (DaType::Calldata, fee));
+        self.tracer.add_transaction(tx, data)
+    }
+
+    /// Adds a blob batcher transaction, with the decoded data of its blobs, and the fee it paid.
+    pub fn add_blob_tx(
+        &mut self,
+        tx: L1TxRef,
+        blobs: &[Bytes],
+        fee: BatcherTxFee,
+    ) -> Result<usize, FrameParseError> {
+        self.txs.insert(tx.hash, (DaType::Blob, fee));
+        let mut decoded = 0;
+        for blob in blobs {
+            decoded += self.tracer.add_transaction(tx, blob)?;
+        }
+        Ok(decoded)
+    }
+
+    /// Returns the statistics of each decoded channel, in decoding order.
+    pub fn report(&self) -> Vec<ChannelEconomics> {
+        let mut report = Vec::<ChannelEconomics>::new();
+        let mut last = None;
+        for traced in self.tracer.batches() {
+            let provenance = &traced.provenance;
+            if !last.is_some_and(|last| Arc::ptr_eq(last, provenance)) {
+                last = Some(provenance);
+                let mut channel = ChannelEconomics {
+                    channel_id: provenance.channel_id,
+                    compressed_size: provenance.frames.iter().map(|f| f.size).sum(),
+                    calldata_txs: 0,
+                    blob_txs: 0,
+                    l1_fee: 0,
+                    l2_blocks: 0,
+                    l2_txs: 0,
+                    l2_gas: 0,
+                };
+                for tx in provenance.l1_txs() {
+                    let (da_type, fee) = self
+                        .txs
+                        .get(&tx.hash)
+                        .copied()
+                        .unwrap_or((DaType::Calldata, BatcherTxFee::default()));
+                    match da_type {
+                        DaType::Calldata => channel.calldata_txs += 1,
+                        DaType::Blob => channel.blob_txs += 1,
+                    }
+                    channel.l1_fee += fee.total();
+                }
+                report.push(channel);
+            }
+
+            let Some(channel) = report.last_mut() else { continue };
+            let blocks: Vec<&[Bytes]> = match &traced.batch {
+                Batch::Single(batch) => Vec::from([batch.transactions.as_slice()]),
+                Batch::Span(batch) => {
+                    batch.batches.iter().map(|b| b.transactions.as_slice()).collect()
+                }
+            };
+            for transactions in blocks {
+                channel.l2_blocks += 1;
+                channel.l2_txs += transactions.len() as u64;
+                channel.l2_gas += transactions
+                    .iter()
+                    .filter_map(|tx| OpTxEnvelope::decode_2718(&mut tx.as_ref()).ok())
+                    .map(|tx| tx.gas_limit())
+                    .sum::<u64>();
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{encode_frames, new_compressed_batch_data, tx_ref};
+    use maili_protocol::Frame;
+
+    #[test]
+    fn test_batcher_tx_fee_total() {
+        let fee = BatcherTxFee {
+            gas_used: 21_000,
+            effective_gas_price: 10,
+            blob_gas_used: 131_072,
+            blob_gas_price: 2,
+        };
+        assert_eq!(fee.total(), 21_000 * 10 + 131_072 * 2);
+    }
+
+    #[test]
+    fn test_batch_economics_report() {
+        let data = new_compressed_batch_data();
+        let (first, second) = data.split_at(data.len() / 2);
+        let mut economics = BatchEconomics::new(Arc::new(RollupConfig::default()));
+
+        // A channel split over a calldata and a blob transaction.
+        let calldata_fee =
+            BatcherTxFee { gas_used: 100, effective_gas_price: 2, ..Default::default() };
+        let frames = [Frame::new([0xAA; 16], 0, first.to_vec(), false)];
+        economics.add_calldata_tx(tx_ref(1, 0), &encode_frames(&frames), calldata_fee).unwrap();
+        let blob_fee = BatcherTxFee { blob_gas_used: 10, blob_gas_price: 3, ..Default::default() };
+        let frames = [Frame::new([0xAA; 16], 1, second.to_vec(), true)];
+        economics.add_blob_tx(tx_ref(2, 1), &[encode_frames(&frames)], blob_fee).unwrap();
+
+        // A second channel in a single calldata transaction.
+        let frames = [Frame::new([0xBB; 16], 0, data.clone(), true)];
+        economics.add_calldata_tx(tx_ref(3, 2), &encode_frames(&frames), calldata_fee).unwrap();
+
+        let report = economics.report();
+        assert_eq!(report.len(), 2);
+
+        let channel = &report[0];
+        assert_eq!(channel.channel_id, [0xAA; 16]);
+        assert_eq!(channel.compressed_size, data.len());
+        assert_eq!((channel.calldata_txs, channel.blob_txs), (1, 1));
+        assert_eq!(channel.l1_fee, 200 + 30);
+        assert_eq!(channel.fee_per_byte(), Some(230 / data.len() as u128));
+
+        let Batch::Span(batch) = &economics.tracer().batches()[0].batch else {
+            panic!("expected a span batch");
+        };
+        assert_eq!(channel.l2_blocks, batch.batches.len() as u64);
+        let l2_txs = batch.batches.iter().map(|b| b.transactions.len()).sum::<usize>();
+        assert_eq!(channel.l2_txs, l2_txs as u64);
+        assert!(channel.l2_gas > 0);
+
+        let channel = &report[1];
+        assert_eq!(channel.channel_id, [0xBB; 16]);
+        assert_eq!((channel.calldata_txs, channel.blob_txs), (1, 0));
+        assert_eq!(channel.l1_fee, 200);
+        assert_eq!(channel.l2_gas, report[0].l2_gas);
+    }
+}
@@ -1,8 +1,9 @@
 //! Error types for kona's attributes builder.
 
+use crate::errors::PipelineEncodingError;
 use alloc::string::String;
 use alloy_eips::BlockNumHash;
-use alloy_primitives::B256;
+use alloy_primitives::{Bytes, B256};
 use thiserror::Error;
 
 /// An [AttributesBuilder] Error.
@@ -27,7 +28,61 @@ pub enum BuilderError {
     /// Attributes unavailable.
     #[error("Attributes unavailable")]
     AttributesUnavailable,
+    /// The L1 header with the given hash was not provided.
+    #[error("Missing L1 header input: {0}")]
+    MissingHeader(B256),
+    /// The receipts of the L1 block with the given hash were not provided.
+    #[error("Missing L1 receipts input: {0}")]
+    MissingReceipts(B256),
+    /// The receipts root of the provided L1 receipts does not match the receipts root of their
+    /// header.
+    #[error("L1 receipts root mismatch. Expected {0}, got {1}")]
+    ReceiptsRootMismatch(B256, B256),
+    /// The system config of the L2 block with the given number was not provided.
+    #[error("Missing system config input for L2 block #{0}")]
+    MissingSystemConfig(u64),
+    /// The post-Cancun L1 header with the given hash has no parent beacon block root.
+    #[error("Missing parent beacon block root in L1 header: {0}")]
+    MissingParentBeaconRoot(B256),
+    /// The Holocene header extra data does not hold the EIP-1559 parameters.
+    #[error("Invalid Holocene extra data: {0}")]
+    InvalidHoloceneExtraData(Bytes),
     /// A custom error.
     #[error("Error in attributes builder: {0}")]
     Custom(String),
 }
+
+/// An invariant violated by [OpPayloadAttributes], returned by [validate_attributes].
+///
+/// [OpPayloadAttributes]: op_alloy_rpc_types_engine::OpPayloadAttributes
+/// [validate_attributes]: crate::attributes::validate_attributes
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum AttributesValidationError {
+    /// The timestamp is not one block time after the parent.
+    #[error("Invalid timestamp. Expected {0}, got {1}")]
+    InvalidTimestamp(u64, u64),
+    /// The attributes carry no transactions.
+    #[error("Attributes carry no transactions")]
+    MissingTransactions,
+    /// The first transaction is not a valid L1 info deposit.
+    #[error("Invalid L1 info transaction: {0}")]
+    InvalidL1InfoTx(PipelineEncodingError),
+    /// The L1 origin is neither the parent's L1 origin nor its successor.
+    #[error("L1 origin mismatch. Parent L1 origin: {0:?}, got {1:?}")]
+    L1OriginMismatch(BlockNumHash, BlockNumHash),
+    /// The sequence number does not follow the parent's.
+    #[error("Invalid sequence number. Expected {0}, got {1}")]
+    InvalidSequenceNumber(u64, u64),
+    /// The L1 origin is newer than the L2 block.
+    #[error("L1 origin timestamp {0} is after the L2 block timestamp {1}")]
+    L1OriginInFuture(u64, u64),
+    /// A deposit transaction follows a user transaction.
+    #[error("Deposit transaction at index {0} follows a user transaction")]
+    DepositAfterUserTx(usize),
+    /// The gas limit does not match the system config.
+    #[error("Gas limit mismatch. Expected {0}, got {1:?}")]
+    GasLimitMismatch(u64, Option<u64>),
+    /// A field is set although its hardfork is not active, or missing although it is.
+    #[error("Field `{0}` does not match the active hardforks")]
+    HardforkField(&'static str),
+}
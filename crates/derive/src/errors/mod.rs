@@ -1,13 +1,18 @@
 //! Error types for the kona derivation pipeline.
 
 mod attributes;
-pub use attributes::BuilderError;
+pub use attributes::{AttributesValidationError, BuilderError};
 
 mod stages;
-pub use stages::BatchDecompressionError;
+pub use stages::{
+    BatchDecompressionError, ChannelReplayError, SpanBatchLimitError, TxEquivalenceError,
+};
 
 mod pipeline;
 pub use pipeline::{PipelineEncodingError, PipelineError, PipelineErrorKind, ResetError};
 
 mod sources;
-pub use sources::{BlobDecodingError, BlobProviderError};
+pub use sources::{
+    AltDAProviderError, ArchiveError, BlobDecodingError, BlobEncodingError, BlobProviderError,
+    CommitmentError, EigenDAProviderError, SystemConfigReplayError,
+};
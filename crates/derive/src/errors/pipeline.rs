@@ -1,6 +1,6 @@
 //! This module contains derivation errors thrown within the pipeline.
 
-use crate::errors::BuilderError;
+use crate::errors::{BuilderError, SpanBatchLimitError, TxEquivalenceError};
 use alloc::string::String;
 use alloy_primitives::B256;
 use maili_protocol::{DepositError, SpanBatchError};
@@ -42,6 +42,17 @@ pub enum PipelineError {
     /// [PipelineError::Eof] will be encountered.
     #[error("Not enough data")]
     NotEnoughData,
+    /// The prepared attributes queue of the [DerivationPipeline] is at capacity, and must be
+    /// drained by the consumer before the pipeline can make progress.
+    ///
+    /// [DerivationPipeline]: crate::pipeline::DerivationPipeline
+    #[error("Prepared attributes queue is full")]
+    PreparedQueueFull,
+    /// The [DerivationPipeline] is paused, and does not step its stages until it is resumed.
+    ///
+    /// [DerivationPipeline]: crate::pipeline::DerivationPipeline
+    #[error("The pipeline is paused")]
+    Paused,
     /// No channels are available in the [ChannelProvider].
     ///
     /// [ChannelProvider]: crate::stages::ChannelProvider
@@ -62,6 +73,12 @@ pub enum PipelineError {
     /// [ChannelReader]: crate::stages::ChannelReader
     #[error("The channel reader has no channel available")]
     ChannelReaderEmpty,
+    /// The channel decompresses to more than the maximum RLP bytes per channel, given in the
+    /// variant, and was dropped by the [ChannelReader] stage.
+    ///
+    /// [ChannelReader]: crate::stages::ChannelReader
+    #[error("Channel exceeds the maximum decompressed size of {0} bytes")]
+    ChannelTooLarge(usize),
     /// The [BatchQueue] is empty.
     ///
     /// [BatchQueue]: crate::stages::BatchQueue
@@ -95,6 +112,10 @@ pub enum PipelineError {
     /// The data source can no longer provide any more data.
     #[error("Data source exhausted")]
     EndOfSource,
+    /// The input of an alt-DA commitment is unavailable, and its challenge window passed without
+    /// a challenge that could make the input available on L1.
+    #[error("Alt-DA input missing past its challenge window")]
+    MissingAltDAInput,
     /// Provider error variant.
     #[error("Blob provider error: {0}")]
     Provider(String),
@@ -139,6 +160,10 @@ pub enum ResetError {
     /// A Holocene activation temporary error.
     #[error("Holocene activation reset")]
     HoloceneActivation,
+    /// The challenge of an alt-DA commitment that was derived from expired. The argument is the
+    /// L1 block number the commitment was included in.
+    #[error("Challenge of the alt-DA commitment included in L1 block {0} expired")]
+    AltDAChallengeExpired(u64),
 }
 
 impl ResetError {
@@ -163,6 +188,18 @@ pub enum PipelineEncodingError {
     /// Span Batch Error.
     #[error("{0}")]
     SpanBatchError(#[from] SpanBatchError),
+    /// Span Batch Limit Error.
+    #[error("{0}")]
+    SpanBatchLimitError(#[from] SpanBatchLimitError),
+    /// Transaction equivalence error.
+    #[error("{0}")]
+    TxEquivalenceError(#[from] TxEquivalenceError),
+    /// The L1 info transaction is not a deposit transaction.
+    #[error("Invalid L1 info transaction type: {0}")]
+    InvalidL1InfoTxType(u8),
+    /// L1 info transaction calldata decoding error.
+    #[error("Error decoding L1 info transaction: {0}")]
+    L1InfoDecodingError(String),
 }
 
 #[cfg(test)]
@@ -2,10 +2,12 @@
 
 use super::{PipelineError, PipelineErrorKind};
 use alloc::string::{String, ToString};
+use alloy_primitives::B256;
+use op_alloy_genesis::SystemConfigUpdateError;
 use thiserror::Error;
 
 /// Blob Decoding Error
-#[derive(Error, Debug, PartialEq, Eq)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
 pub enum BlobDecodingError {
     /// Invalid field element
     #[error("Invalid field element")]
@@ -21,8 +23,19 @@ pub enum BlobDecodingError {
     MissingData,
 }
 
+/// Blob Encoding Error
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum BlobEncodingError {
+    /// The data is larger than the maximum blob data size.
+    #[error("Blob data too large: {0} bytes")]
+    DataTooLarge(usize),
+    /// The KZG commitment or proof of a blob could not be computed.
+    #[error("KZG error: {0}")]
+    Kzg(String),
+}
+
 /// An error returned by the [BlobProviderError].
-#[derive(Error, Debug, PartialEq, Eq)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
 pub enum BlobProviderError {
     /// The number of specified blob hashes did not match the number of returned sidecars.
     #[error("Blob sidecar length mismatch: expected {0}, got {1}")]
@@ -51,6 +64,147 @@ impl From<BlobProviderError> for PipelineErrorKind {
     }
 }
 
+/// An error decoding an alt-DA [Commitment].
+///
+/// [Commitment]: crate::sources::Commitment
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum CommitmentError {
+    /// The batcher data does not start with the alt-DA derivation version byte.
+    #[error("Not an alt-DA commitment")]
+    NotAltDA,
+    /// The commitment is empty.
+    #[error("Empty commitment")]
+    Empty,
+    /// The commitment type is unknown.
+    #[error("Unknown commitment type: {0}")]
+    UnknownType(u8),
+    /// The keccak256 commitment is not 32 bytes long.
+    #[error("Invalid keccak256 commitment length: {0}")]
+    InvalidLength(usize),
+    /// The generic commitment is missing its DA layer byte.
+    #[error("Generic commitment is missing the DA layer byte")]
+    MissingDALayer,
+}
+
+/// An error returned by an [AltDAProvider].
+///
+/// [AltDAProvider]: crate::traits::AltDAProvider
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum AltDAProviderError {
+    /// The commitment is invalid, e.g. its certificate does not verify, so it is dropped.
+    #[error("Invalid commitment: {0}")]
+    InvalidCommitment(String),
+    /// The input is not available (yet), and the fetch is retried.
+    #[error("Input not available: {0}")]
+    Unavailable(String),
+    /// Error pertaining to the backend transport.
+    #[error("{0}")]
+    Backend(String),
+}
+
+impl From<AltDAProviderError> for PipelineErrorKind {
+    fn from(val: AltDAProviderError) -> Self {
+        PipelineError::Provider(val.to_string()).temp()
+    }
+}
+
+impl From<EigenDAProviderError> for AltDAProviderError {
+    fn from(val: EigenDAProviderError) -> Self {
+        match val {
+            EigenDAProviderError::InvalidCert(e) => Self::InvalidCommitment(e),
+            EigenDAProviderError::Unavailable(e) => Self::Unavailable(e),
+            EigenDAProviderError::Backend(e) => Self::Backend(e),
+        }
+    }
+}
+
+/// An error returned by an [EigenDAProvider].
+///
+/// [EigenDAProvider]: crate::traits::EigenDAProvider
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum EigenDAProviderError {
+    /// The certificate of the commitment is invalid, so the commitment is dropped.
+    #[error("Invalid EigenDA certificate: {0}")]
+    InvalidCert(String),
+    /// The blob is not available (yet), and the fetch is retried.
+    #[error("EigenDA blob not available: {0}")]
+    Unavailable(String),
+    /// Error pertaining to the backend transport.
+    #[error("{0}")]
+    Backend(String),
+}
+
+impl From<EigenDAProviderError> for PipelineErrorKind {
+    fn from(val: EigenDAProviderError) -> Self {
+        PipelineError::Provider(val.to_string()).temp()
+    }
+}
+
+/// An error returned by the [SystemConfigReplayer].
+///
+/// [SystemConfigReplayer]: crate::sources::SystemConfigReplayer
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SystemConfigReplayError {
+    /// There is no checkpoint at or below the requested L1 block number.
+    #[error("No system config checkpoint at or below L1 block {0}")]
+    MissingCheckpoint(u64),
+    /// A system config update log failed to decode.
+    #[error("System config update error: {0}")]
+    SystemConfigUpdate(#[from] SystemConfigUpdateError),
+    /// Error pertaining to the chain provider.
+    #[error("{0}")]
+    Provider(String),
+}
+
+/// An error returned by the [ArchiveChainProvider] and [ArchiveIndex].
+///
+/// [ArchiveChainProvider]: crate::sources::ArchiveChainProvider
+/// [ArchiveIndex]: crate::sources::ArchiveIndex
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ArchiveError {
+    /// The archive is not a valid RLP stream.
+    #[error("Malformed archive: {0}")]
+    Rlp(alloy_rlp::Error),
+    /// The blocks of the archive are not consecutive.
+    #[error("Expected block {expected} in archive, found block {found}")]
+    NonConsecutive {
+        /// The expected block number.
+        expected: u64,
+        /// The block number found in the archive.
+        found: u64,
+    },
+    /// The number of receipt lists does not match the number of blocks.
+    #[error("Archive has {blocks} blocks, but {receipts} receipt lists")]
+    ReceiptsMismatch {
+        /// The number of blocks.
+        blocks: usize,
+        /// The number of receipt lists.
+        receipts: usize,
+    },
+    /// The block number is not in the archive.
+    #[error("Block {0} not in archive")]
+    BlockNotFound(u64),
+    /// The block hash is not in the archive.
+    #[error("Block {0} not in archive")]
+    HashNotFound(B256),
+}
+
+impl From<alloy_rlp::Error> for ArchiveError {
+    fn from(err: alloy_rlp::Error) -> Self {
+        Self::Rlp(err)
+    }
+}
+
+impl From<ArchiveError> for PipelineErrorKind {
+    fn from(val: ArchiveError) -> Self {
+        match val {
+            // Blocks past the end of the archive are treated like blocks not yet produced by L1.
+            ArchiveError::BlockNotFound(_) => PipelineError::Provider(val.to_string()).temp(),
+            _ => PipelineError::Provider(val.to_string()).crit(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,6 +1,11 @@
 //! Error types for derivation pipeline stages.
 
-use maili_protocol::MAX_SPAN_BATCH_ELEMENTS;
+use crate::types::ChannelId;
+use alloc::string::String;
+use alloy_primitives::B256;
+use maili_protocol::{
+    BatchDecodingError, ChannelError, FrameDecodingError, FrameParseError, MAX_SPAN_BATCH_ELEMENTS,
+};
 use thiserror::Error;
 
 /// A frame decompression error.
@@ -10,3 +15,116 @@ pub enum BatchDecompressionError {
     #[error("The batch exceeds the maximum number of elements: {max_size}", max_size = MAX_SPAN_BATCH_ELEMENTS)]
     BatchTooLarge,
 }
+
+/// An error returned when a span batch exceeds the configured [SpanBatchLimits].
+///
+/// [SpanBatchLimits]: crate::stages::SpanBatchLimits
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum SpanBatchLimitError {
+    /// The span batch contains more transactions than allowed.
+    #[error("Span batch contains too many transactions: {0} > {1}")]
+    TooManyTransactions(usize, usize),
+    /// The span batch transaction payloads are larger than allowed.
+    #[error("Span batch payload is too large: {0} > {1} bytes")]
+    PayloadTooLarge(usize, usize),
+}
+
+/// An error returned by the [ChannelReplay] while replaying a channel from raw frames.
+///
+/// [ChannelReplay]: crate::replay::ChannelReplay
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ChannelReplayError {
+    /// No frames were given.
+    #[error("No frames to replay")]
+    NoFrames,
+    /// A raw frame failed to decode.
+    #[error("Failed to decode frame at index {index}: {error}")]
+    FrameDecoding {
+        /// The index of the raw frame.
+        index: usize,
+        /// The decoding error.
+        error: FrameDecodingError,
+    },
+    /// The frames of a batcher transaction payload failed to parse.
+    #[error("Failed to parse the frames of payload at index {index}: {error}")]
+    FrameParse {
+        /// The index of the payload.
+        index: usize,
+        /// The parsing error.
+        error: FrameParseError,
+    },
+    /// A frame could not be added to the channel.
+    #[error("Failed to add frame {number} of channel {id}: {error}")]
+    AddFrame {
+        /// The channel ID of the frame.
+        id: ChannelId,
+        /// The frame number.
+        number: u16,
+        /// The channel error.
+        error: ChannelError,
+    },
+    /// The channel is missing frames, or its last frame.
+    #[error("Channel {0} is incomplete")]
+    Incomplete(ChannelId),
+    /// The channel data failed to decompress within the maximum channel size.
+    #[error("Failed to decompress the channel data within {0} bytes")]
+    Decompression(usize),
+    /// The RLP encoding of a batch is invalid.
+    #[error("Failed to decode the RLP encoding of batch {index}: {error}")]
+    BatchRlp {
+        /// The index of the batch in the channel.
+        index: usize,
+        /// The RLP error.
+        error: alloy_rlp::Error,
+    },
+    /// A batch failed to decode.
+    #[error("Failed to decode batch {index}: {error}")]
+    BatchDecoding {
+        /// The index of the batch in the channel.
+        index: usize,
+        /// The decoding error.
+        error: BatchDecodingError,
+    },
+    /// The channel is brotli compressed, but a batch predates the Fjord hardfork.
+    #[error("Brotli compressed channel contains batch {0} from before Fjord")]
+    BrotliBeforeFjord(usize),
+}
+
+/// An error returned when a transaction reconstructed from a span batch does not match its
+/// canonical encoding, or the transaction included in the L2 chain.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum TxEquivalenceError {
+    /// A reconstructed transaction failed to decode.
+    #[error("Failed to decode reconstructed transaction {index}: {error}")]
+    Decoding {
+        /// The index of the transaction in the batch.
+        index: usize,
+        /// The decoding error.
+        error: String,
+    },
+    /// A reconstructed transaction re-encodes to different bytes.
+    #[error("Reconstructed transaction {0} does not re-encode to the same bytes")]
+    Reencoding(usize),
+    /// The L2 block contains a different number of non-deposit transactions.
+    #[error("L2 block {number} has {expected} non-deposit transactions, batch has {actual}")]
+    TransactionCount {
+        /// The number of the L2 block.
+        number: u64,
+        /// The number of non-deposit transactions in the L2 block.
+        expected: usize,
+        /// The number of transactions in the batch.
+        actual: usize,
+    },
+    /// A reconstructed transaction hash does not match the transaction in the L2 block.
+    #[error("Transaction {index} of L2 block {number} has hash {expected}, batch has {actual}")]
+    HashMismatch {
+        /// The number of the L2 block.
+        number: u64,
+        /// The index of the transaction in the batch.
+        index: usize,
+        /// The hash of the transaction in the L2 block.
+        expected: B256,
+        /// The hash of the reconstructed transaction.
+        actual: B256,
+    },
+}
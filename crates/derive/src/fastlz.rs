@@ -0,0 +1,186 @@
+//! Contains the FastLZ compressed size estimation used by the Fjord L1 fee formula.
+//!
+//! The estimation follows the [LibZip] `flzCompress` implementation that the Fjord `GasPriceOracle`
+//! uses on L2, and only computes the length of the compressed output.
+//!
+//! [LibZip]: https://github.com/Vectorized/solady/blob/main/src/utils/LibZip.sol
+
+use alloy_primitives::U256;
+
+/// The size of the FastLZ hash table.
+const HASH_TABLE_SIZE: usize = 8192;
+
+/// The maximum distance of a FastLZ back reference.
+const MAX_DISTANCE: u32 = 8192;
+
+/// The minimum estimated transaction size of the Fjord L1 fee formula, scaled by 1e6.
+const MIN_TRANSACTION_SIZE_SCALED: u64 = 100_000_000;
+
+/// The intercept of the Fjord linear regression, scaled by 1e6.
+const INTERCEPT_SCALED: u64 = 42_585_600;
+
+/// The FastLZ size coefficient of the Fjord linear regression, scaled by 1e6.
+const FASTLZ_COEF_SCALED: u64 = 836_500;
+
+/// Returns the length of the input after FastLZ compression.
+pub const fn flz_compress_len(input: &[u8]) -> u32 {
+    let len = input.len() as u32;
+    let idx_limit = len.saturating_sub(13);
+    let mut htab = [0u32; HASH_TABLE_SIZE];
+    let (mut idx, mut anchor, mut size) = (2, 0, 0);
+
+    while idx < idx_limit {
+        // Find the next match, hashing every position along the way.
+        let (mut reference, mut distance);
+        loop {
+            let seq = u24(input, idx);
+            let slot = hash(seq);
+            reference = htab[slot];
+            htab[slot] = idx;
+            distance = idx - reference;
+            if idx >= idx_limit {
+                break;
+            }
+            idx += 1;
+            if distance < MAX_DISTANCE && seq == u24(input, reference) {
+                break;
+            }
+        }
+        if idx >= idx_limit {
+            break;
+        }
+        idx -= 1;
+
+        if idx > anchor {
+            size = literals_len(idx - anchor, size);
+        }
+        let match_len = match_len(input, reference + 3, idx + 3, idx_limit + 9);
+        size = back_reference_len(match_len, size);
+
+        idx = insert_hash(&mut htab, input, idx + match_len);
+        idx = insert_hash(&mut htab, input, idx);
+        anchor = idx;
+    }
+
+    literals_len(len - anchor, size)
+}
+
+/// Returns the estimated compressed size of the transaction used by the Fjord L1 fee formula,
+/// scaled by 1e6:
+///
+/// `max(minTransactionSize, intercept + fastlzCoef * fastlzSize)`
+pub fn fjord_estimated_size(input: &[u8]) -> U256 {
+    U256::from(flz_compress_len(input))
+        .saturating_mul(U256::from(FASTLZ_COEF_SCALED))
+        .saturating_sub(U256::from(INTERCEPT_SCALED))
+        .max(U256::from(MIN_TRANSACTION_SIZE_SCALED))
+}
+
+/// Returns the size after emitting `count` literals, in runs of at most 32 bytes.
+const fn literals_len(count: u32, size: u32) -> u32 {
+    let size = size + 0x21 * (count / 0x20);
+    let rest = count % 0x20;
+    if rest != 0 {
+        size + rest + 1
+    } else {
+        size
+    }
+}
+
+/// Returns the size after emitting a back reference of the given match length.
+const fn back_reference_len(match_len: u32, size: u32) -> u32 {
+    let len = match_len - 1;
+    let size = size + 3 * (len / 262);
+    if len % 262 >= 6 {
+        size + 3
+    } else {
+        size + 2
+    }
+}
+
+/// Returns the length of the match between the positions `p` and `q`, bounded by `limit`.
+///
+/// Like the reference implementation, the length is counted up to the first mismatch, inclusive.
+const fn match_len(input: &[u8], p: u32, q: u32, limit: u32) -> u32 {
+    let mut len = 0;
+    let mut end = limit - q;
+    while len < end {
+        if input[(p + len) as usize] != input[(q + len) as usize] {
+            end = 0;
+        }
+        len += 1;
+    }
+    len
+}
+
+/// Inserts the position into the hash table, returning the next position.
+const fn insert_hash(htab: &mut [u32; HASH_TABLE_SIZE], input: &[u8], idx: u32) -> u32 {
+    htab[hash(u24(input, idx))] = idx;
+    idx + 1
+}
+
+/// Returns the hash table slot of a 3 byte sequence.
+const fn hash(seq: u32) -> usize {
+    (((seq as u64 * 2654435769) >> 19) & 0x1fff) as usize
+}
+
+/// Reads the little-endian 3 byte sequence at the given position.
+const fn u24(input: &[u8], idx: u32) -> u32 {
+    let idx = idx as usize;
+    input[idx] as u32 | (input[idx + 1] as u32) << 8 | (input[idx + 2] as u32) << 16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use alloy_primitives::bytes;
+    use maili_protocol::data_gas_fjord;
+
+    #[test]
+    fn test_flz_compress_len_reference_vectors() {
+        assert_eq!(flz_compress_len(&[]), 0);
+        assert_eq!(flz_compress_len(&[0; 1000]), 21);
+        assert_eq!(flz_compress_len(&[42; 1000]), 21);
+        assert_eq!(flz_compress_len(&bytes!("FACADE")), 4);
+        let contract_call = bytes!("02f901550a758302df1483be21b88304743f94f80e51afb613d764fa61751affd3313c190a86bb870151bd62fd12adb8e41ef24f3f000000000000000000000000000000000000000000000000000000000000006e000000000000000000000000af88d065e77c8cc2239327c5edb3a432268e5831000000000000000000000000000000000000000000000000000000000003c1e5000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000a000000000000000000000000000000000000000000000000000000000000000148c89ed219d02f1a5be012c689b4f5b731827bebe000000000000000000000000c001a033fd89cb37c31b2cba46b6466e040c61fc9b2a3675a7f5f493ebd5ad77c497f8a07cdf65680e238392693019b4092f610222e71b7cec06449cb922b93b6a12744e");
+        assert_eq!(flz_compress_len(&contract_call), 202);
+        assert_eq!(fjord_estimated_size(&contract_call), U256::from(126_387_400));
+    }
+
+    #[test]
+    fn test_flz_compress_len_no_repeats() {
+        let mut input = Vec::new();
+        let mut len = 0;
+        for i in 0..=255u8 {
+            input.push(i);
+            let prev_len = len;
+            len = flz_compress_len(&input);
+            assert!(len > prev_len);
+        }
+    }
+
+    proptest::proptest! {
+        /// The estimation must match the Fjord data gas of the reference implementation.
+        #[test]
+        fn fuzz_fjord_estimated_size_parity(
+            input in proptest::collection::vec(proptest::prelude::any::<u8>(), 0..4096),
+        ) {
+            let data_gas = fjord_estimated_size(&input)
+                .saturating_mul(U256::from(16))
+                .wrapping_div(U256::from(1_000_000));
+            proptest::prop_assert_eq!(data_gas, data_gas_fjord(&input));
+        }
+
+        /// Repetitive input must not compress to more than its literal encoding.
+        #[test]
+        fn fuzz_flz_compress_len_bound(
+            chunk in proptest::collection::vec(proptest::prelude::any::<u8>(), 1..64),
+            repeats in 1usize..64,
+        ) {
+            let input = chunk.repeat(repeats);
+            let len = input.len() as u32;
+            proptest::prop_assert!(flz_compress_len(&input) <= literals_len(len, 0));
+        }
+    }
+}
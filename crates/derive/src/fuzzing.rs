@@ -0,0 +1,304 @@
+//! [Arbitrary] generators for the wire types of the derivation pipeline.
+//!
+//! The frame, channel, and batch types are defined in [maili_protocol], so they are wrapped in
+//! newtypes implementing [Arbitrary]. The generated values are well-formed, so that they
+//! roundtrip through their encodings, e.g. a span batch only contains signed transactions of
+//! the types supported by span batches, and its L2 blocks are `block_time` seconds apart.
+
+use crate::constants::MAX_FRAME_LEN;
+use alloc::vec::Vec;
+use alloy_consensus::{SignableTransaction, TxEip1559, TxEip2930, TxEnvelope, TxLegacy};
+use alloy_eips::{
+    eip2718::Encodable2718,
+    eip2930::{AccessList, AccessListItem},
+};
+use alloy_primitives::{Address, Bytes, PrimitiveSignature, TxKind, B256, U256};
+use arbitrary::{Arbitrary, Result, Unstructured};
+use maili_protocol::{BlockInfo, Channel, Frame, SingleBatch, SpanBatch, SpanBatchTransactionData};
+
+/// The maximum number of transactions in a generated L2 block.
+const MAX_BLOCK_TXS: usize = 4;
+
+/// The maximum number of frames in a generated channel.
+const MAX_CHANNEL_FRAMES: u16 = 8;
+
+/// The maximum number of L2 blocks in a generated span batch.
+const MAX_SPAN_BLOCKS: usize = 8;
+
+/// An arbitrary [Frame], with at most [MAX_FRAME_LEN] bytes of data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArbitraryFrame(pub Frame);
+
+impl<'a> Arbitrary<'a> for ArbitraryFrame {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let (id, number, is_last) = (u.arbitrary()?, u.arbitrary()?, u.arbitrary()?);
+        let len = u.arbitrary_len::<u8>()?.min(MAX_FRAME_LEN);
+        Ok(Self(Frame { id, number, data: u.bytes(len)?.to_vec(), is_last }))
+    }
+}
+
+/// An arbitrary ready [Channel], along with the frames it was assembled from, in order.
+#[derive(Debug)]
+pub struct ArbitraryChannel {
+    /// The assembled channel, opened at the default [BlockInfo].
+    pub channel: Channel,
+    /// The frames of the channel.
+    pub frames: Vec<Frame>,
+}
+
+impl<'a> Arbitrary<'a> for ArbitraryChannel {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let id = u.arbitrary()?;
+        let count = u.int_in_range(1..=MAX_CHANNEL_FRAMES)?;
+        let mut channel = Channel::new(id, BlockInfo::default());
+        let mut frames = Vec::with_capacity(count as usize);
+        for number in 0..count {
+            let ArbitraryFrame(frame) = u.arbitrary()?;
+            let frame = Frame { id, number, is_last: number == count - 1, ..frame };
+            channel
+                .add_frame(frame.clone(), BlockInfo::default())
+                .map_err(|_| arbitrary::Error::IncorrectFormat)?;
+            frames.push(frame);
+        }
+        Ok(Self { channel, frames })
+    }
+}
+
+/// An arbitrary signed [TxEnvelope] of a type supported by span batches, replay protected with
+/// the given chain ID if it is protected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArbitraryTransaction(pub TxEnvelope);
+
+impl ArbitraryTransaction {
+    /// Generates an [ArbitraryTransaction] for the given chain ID.
+    pub fn arbitrary_for_chain(u: &mut Unstructured<'_>, chain_id: u64) -> Result<Self> {
+        let to = if u.arbitrary()? { TxKind::Call(u.arbitrary()?) } else { TxKind::Create };
+        let nonce = u.arbitrary()?;
+        let gas_limit = u.arbitrary()?;
+        let value = U256::from(u.arbitrary::<u128>()?);
+        let input = Bytes::from(u.arbitrary::<Vec<u8>>()?);
+        let signature = PrimitiveSignature::new(u.arbitrary()?, u.arbitrary()?, u.arbitrary()?);
+
+        let tx = match u.int_in_range(0..=2u8)? {
+            0 => {
+                let chain_id = u.arbitrary::<bool>()?.then_some(chain_id);
+                let gas_price = u.arbitrary()?;
+                let tx = TxLegacy { chain_id, nonce, gas_price, gas_limit, to, value, input };
+                TxEnvelope::from(tx.into_signed(signature))
+            }
+            1 => {
+                let gas_price = u.arbitrary()?;
+                let access_list = arbitrary_access_list(u)?;
+                let tx = TxEip2930 {
+                    chain_id,
+                    nonce,
+                    gas_price,
+                    gas_limit,
+                    to,
+                    value,
+                    access_list,
+                    input,
+                };
+                TxEnvelope::from(tx.into_signed(signature))
+            }
+            _ => {
+                let tx = TxEip1559 {
+                    chain_id,
+                    nonce,
+                    gas_limit,
+                    max_fee_per_gas: u.arbitrary()?,
+                    max_priority_fee_per_gas: u.arbitrary()?,
+                    to,
+                    value,
+                    access_list: arbitrary_access_list(u)?,
+                    input,
+                };
+                TxEnvelope::from(tx.into_signed(signature))
+            }
+        };
+        Ok(Self(tx))
+    }
+}
+
+impl<'a> Arbitrary<'a> for ArbitraryTransaction {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let chain_id = u.arbitrary::<u32>()?.into();
+        Self::arbitrary_for_chain(u, chain_id)
+    }
+}
+
+/// Arbitrary [SpanBatchTransactionData] of any variant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArbitraryTransactionData(pub SpanBatchTransactionData);
+
+impl<'a> Arbitrary<'a> for ArbitraryTransactionData {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let ArbitraryTransaction(tx) = u.arbitrary()?;
+        SpanBatchTransactionData::try_from(&tx)
+            .map(Self)
+            .map_err(|_| arbitrary::Error::IncorrectFormat)
+    }
+}
+
+/// An arbitrary [SingleBatch], containing signed transactions for the given chain ID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArbitrarySingleBatch(pub SingleBatch);
+
+impl ArbitrarySingleBatch {
+    /// Generates an [ArbitrarySingleBatch] for the given chain ID.
+    pub fn arbitrary_for_chain(u: &mut Unstructured<'_>, chain_id: u64) -> Result<Self> {
+        Ok(Self(SingleBatch {
+            parent_hash: u.arbitrary()?,
+            epoch_num: u.arbitrary()?,
+            epoch_hash: u.arbitrary()?,
+            timestamp: u.arbitrary()?,
+            transactions: arbitrary_block_txs(u, chain_id)?,
+        }))
+    }
+}
+
+impl<'a> Arbitrary<'a> for ArbitrarySingleBatch {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let chain_id = u.arbitrary::<u32>()?.into();
+        Self::arbitrary_for_chain(u, chain_id)
+    }
+}
+
+/// An arbitrary non-empty [SpanBatch], along with the block time its L2 blocks are spaced by.
+#[derive(Debug, Clone)]
+pub struct ArbitrarySpanBatch {
+    /// The span batch.
+    pub batch: SpanBatch,
+    /// The L2 block time, in seconds.
+    pub block_time: u64,
+}
+
+impl<'a> Arbitrary<'a> for ArbitrarySpanBatch {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let block_time = u.int_in_range(1..=12)?;
+        let chain_id = u.arbitrary::<u32>()?.into();
+        let genesis_timestamp = u.arbitrary::<u32>()?.into();
+        let mut batch = SpanBatch { genesis_timestamp, chain_id, ..Default::default() };
+
+        let mut timestamp = genesis_timestamp + u.arbitrary::<u16>()? as u64 * block_time;
+        let mut epoch_num = u.arbitrary::<u32>()? as u64;
+        let mut epoch_hash: B256 = u.arbitrary()?;
+        let parent_hash = u.arbitrary()?;
+        for i in 0..u.int_in_range(1..=MAX_SPAN_BLOCKS)? {
+            if i > 0 && u.arbitrary()? {
+                epoch_num += 1;
+                epoch_hash = u.arbitrary()?;
+            }
+            let block = SingleBatch {
+                parent_hash,
+                epoch_num,
+                epoch_hash,
+                timestamp,
+                transactions: arbitrary_block_txs(u, chain_id)?,
+            };
+            let seq_num = u.arbitrary()?;
+            batch
+                .append_singular_batch(block, seq_num)
+                .map_err(|_| arbitrary::Error::IncorrectFormat)?;
+            timestamp += block_time;
+        }
+        Ok(Self { batch, block_time })
+    }
+}
+
+/// Generates the EIP-2718 encoded transactions of an L2 block.
+fn arbitrary_block_txs(u: &mut Unstructured<'_>, chain_id: u64) -> Result<Vec<Bytes>> {
+    (0..u.int_in_range(0..=MAX_BLOCK_TXS)?)
+        .map(|_| {
+            let ArbitraryTransaction(tx) = ArbitraryTransaction::arbitrary_for_chain(u, chain_id)?;
+            Ok(tx.encoded_2718().into())
+        })
+        .collect()
+}
+
+/// Generates a small [AccessList].
+fn arbitrary_access_list(u: &mut Unstructured<'_>) -> Result<AccessList> {
+    let items = (0..u.int_in_range(0..=2)?)
+        .map(|_| {
+            Ok(AccessListItem {
+                address: u.arbitrary::<Address>()?,
+                storage_keys: (0..u.int_in_range(0..=2)?)
+                    .map(|_| u.arbitrary())
+                    .collect::<Result<_>>()?,
+            })
+        })
+        .collect::<Result<_>>()?;
+    Ok(AccessList(items))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_rlp::{Decodable, Encodable};
+    use maili_protocol::{Batch, RawSpanBatch, DERIVATION_VERSION_0};
+    use op_alloy_genesis::RollupConfig;
+    use proptest::{collection::vec, prelude::any, prop_assert_eq, prop_assume, proptest};
+
+    /// Generates a value of type `T` from the given bytes, if they suffice.
+    fn generate<'a, T: Arbitrary<'a>>(bytes: &'a [u8]) -> Option<T> {
+        T::arbitrary(&mut Unstructured::new(bytes)).ok()
+    }
+
+    proptest! {
+        #[test]
+        fn test_frame_roundtrip(bytes in vec(any::<u8>(), 0..4096)) {
+            let ArbitraryFrame(frame) = generate(&bytes).unwrap();
+            let encoded = frame.encode();
+            prop_assert_eq!(Frame::decode(&encoded).unwrap(), (encoded.len(), frame));
+        }
+
+        #[test]
+        fn test_channel_roundtrip(bytes in vec(any::<u8>(), 0..4096)) {
+            let Some(ArbitraryChannel { channel, frames }) = generate(&bytes) else {
+                return Ok(());
+            };
+            let mut data = alloc::vec![DERIVATION_VERSION_0];
+            frames.iter().for_each(|f| data.extend(f.encode()));
+            let expected = frames.iter().flat_map(|f| f.data.clone()).collect::<Vec<_>>();
+            prop_assert_eq!(channel.frame_data().unwrap().to_vec(), expected);
+            prop_assert_eq!(Frame::parse_frames(&data).unwrap(), frames);
+        }
+
+        #[test]
+        fn test_transaction_data_roundtrip(bytes in vec(any::<u8>(), 0..4096)) {
+            let Some(ArbitraryTransactionData(data)) = generate(&bytes) else {
+                return Ok(());
+            };
+            let mut encoded = Vec::new();
+            data.encode(&mut encoded);
+            prop_assert_eq!(SpanBatchTransactionData::decode(&mut encoded.as_slice()).unwrap(), data);
+        }
+
+        #[test]
+        fn test_single_batch_roundtrip(bytes in vec(any::<u8>(), 0..4096)) {
+            let Some(ArbitrarySingleBatch(batch)) = generate(&bytes) else {
+                return Ok(());
+            };
+            let mut encoded = Vec::new();
+            Batch::Single(batch.clone()).encode(&mut encoded).unwrap();
+            let decoded = Batch::decode(&mut encoded.as_slice(), &RollupConfig::default());
+            prop_assert_eq!(decoded.unwrap(), Batch::Single(batch.clone()));
+            prop_assert_eq!(SingleBatch::decode(&mut &encoded[1..]).unwrap(), batch);
+        }
+
+        #[test]
+        fn test_span_batch_roundtrip(bytes in vec(any::<u8>(), 0..8192)) {
+            let generated: Option<ArbitrarySpanBatch> = generate(&bytes);
+            prop_assume!(generated.is_some());
+            let ArbitrarySpanBatch { batch, block_time } = generated.unwrap();
+
+            let mut encoded = Vec::new();
+            Batch::Span(batch.clone()).encode(&mut encoded).unwrap();
+            let mut raw = RawSpanBatch::decode(&mut &encoded[1..]).unwrap();
+            let decoded = raw.derive(block_time, batch.genesis_timestamp, batch.chain_id).unwrap();
+            prop_assert_eq!(decoded.batches, batch.batches);
+            prop_assert_eq!(decoded.parent_check, batch.parent_check);
+            prop_assert_eq!(decoded.l1_origin_check, batch.l1_origin_check);
+        }
+    }
+}
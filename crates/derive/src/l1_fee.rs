@@ -0,0 +1,208 @@
+//! Contains the L1 data fee calculation of L2 transactions, under the fee formula of each
+//! hardfork.
+//!
+//! Validators and indexers can use [l1_fee] to cross-check the L1 fee reported in the receipts
+//! of L2 transactions, given the [SystemConfig] and the L1 fee parameters of the L1 origin.
+//!
+//! On custom gas token chains, the L1 data fee is charged in the [GasPayingToken] of the chain,
+//! without any conversion of the L1 base fees, so the fee computed here is denominated in the
+//! smallest unit of the token.
+//!
+//! [GasPayingToken]: crate::types::GasPayingToken
+
+use crate::types::unpack_ecotone_scalar;
+use alloy_primitives::U256;
+use maili_protocol::{
+    calculate_tx_l1_cost_bedrock, calculate_tx_l1_cost_ecotone, calculate_tx_l1_cost_fjord,
+    calculate_tx_l1_cost_regolith,
+};
+use op_alloy_consensus::DEPOSIT_TX_TYPE_ID;
+use op_alloy_genesis::{RollupConfig, SystemConfig};
+
+/// The formula used to compute the L1 data fee of L2 transactions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum L1FeeFormula {
+    /// The Bedrock formula, charging the calldata gas of the transaction, plus 68 non-zero bytes
+    /// for the signature, scaled by the overhead and scalar.
+    Bedrock,
+    /// The Regolith formula, charging the calldata gas of the transaction, scaled by the
+    /// overhead and scalar.
+    Regolith,
+    /// The Ecotone formula, charging the calldata gas of the transaction at the L1 base fee and
+    /// blob base fee, weighted by the base fee and blob base fee scalars.
+    Ecotone,
+    /// The Fjord formula, replacing the calldata gas of the Ecotone formula with a linear
+    /// estimation of the compressed transaction size, derived from its FastLZ compressed size.
+    Fjord,
+}
+
+impl L1FeeFormula {
+    /// Returns the formula in effect for the L2 block with the given timestamp.
+    ///
+    /// The first Ecotone block still uses the pre-Ecotone formula, since the Ecotone scalars are
+    /// only set by the L1 info transaction of the block.
+    pub fn from_timestamp(cfg: &RollupConfig, timestamp: u64) -> Self {
+        let parent_timestamp = timestamp.saturating_sub(cfg.block_time);
+        if !cfg.is_ecotone_active(timestamp) || !cfg.is_ecotone_active(parent_timestamp) {
+            if cfg.is_regolith_active(timestamp) {
+                Self::Regolith
+            } else {
+                Self::Bedrock
+            }
+        } else if cfg.is_fjord_active(timestamp) {
+            Self::Fjord
+        } else {
+            Self::Ecotone
+        }
+    }
+
+    /// Returns the L1 data fee of the EIP-2718 encoded L2 transaction under this formula.
+    ///
+    /// Deposit transactions do not pay an L1 data fee.
+    pub fn l1_fee(
+        &self,
+        sys_config: &SystemConfig,
+        l1_base_fee: U256,
+        l1_blob_base_fee: U256,
+        tx: &[u8],
+    ) -> U256 {
+        if tx.first() == Some(&DEPOSIT_TX_TYPE_ID) {
+            return U256::ZERO;
+        }
+        match self {
+            Self::Bedrock => calculate_tx_l1_cost_bedrock(
+                tx,
+                sys_config.overhead,
+                l1_base_fee,
+                sys_config.scalar,
+            ),
+            Self::Regolith => calculate_tx_l1_cost_regolith(
+                tx,
+                sys_config.overhead,
+                l1_base_fee,
+                sys_config.scalar,
+            ),
+            Self::Ecotone => {
+                let (base_fee_scalar, blob_base_fee_scalar) = ecotone_scalars(sys_config);
+                calculate_tx_l1_cost_ecotone(
+                    tx,
+                    l1_base_fee,
+                    base_fee_scalar,
+                    l1_blob_base_fee,
+                    blob_base_fee_scalar,
+                )
+            }
+            Self::Fjord => {
+                let (base_fee_scalar, blob_base_fee_scalar) = ecotone_scalars(sys_config);
+                calculate_tx_l1_cost_fjord(
+                    tx,
+                    l1_base_fee,
+                    base_fee_scalar,
+                    l1_blob_base_fee,
+                    blob_base_fee_scalar,
+                )
+            }
+        }
+    }
+}
+
+/// Returns the L1 data fee of the EIP-2718 encoded L2 transaction included in the L2 block with
+/// the given timestamp, given the [SystemConfig] and the base fee and blob base fee of the
+/// block's L1 origin.
+pub fn l1_fee(
+    cfg: &RollupConfig,
+    timestamp: u64,
+    sys_config: &SystemConfig,
+    l1_base_fee: U256,
+    l1_blob_base_fee: U256,
+    tx: &[u8],
+) -> U256 {
+    L1FeeFormula::from_timestamp(cfg, timestamp).l1_fee(
+        sys_config,
+        l1_base_fee,
+        l1_blob_base_fee,
+        tx,
+    )
+}
+
+/// Returns the `(base_fee_scalar, blob_base_fee_scalar)` of the [SystemConfig], unpacking them
+/// from the Ecotone scalar if they are not set.
+fn ecotone_scalars(sys_config: &SystemConfig) -> (U256, U256) {
+    match (sys_config.base_fee_scalar, sys_config.blob_base_fee_scalar) {
+        (Some(base_fee_scalar), Some(blob_base_fee_scalar)) => {
+            (U256::from(base_fee_scalar), U256::from(blob_base_fee_scalar))
+        }
+        _ => {
+            let (blob_base_fee_scalar, base_fee_scalar) =
+                unpack_ecotone_scalar(sys_config.scalar.to_be_bytes());
+            (U256::from(base_fee_scalar), U256::from(blob_base_fee_scalar))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::bytes;
+
+    fn test_cfg() -> RollupConfig {
+        RollupConfig {
+            block_time: 2,
+            regolith_time: Some(10),
+            ecotone_time: Some(20),
+            fjord_time: Some(30),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_l1_fee_formula_from_timestamp() {
+        let cfg = test_cfg();
+        assert_eq!(L1FeeFormula::from_timestamp(&cfg, 8), L1FeeFormula::Bedrock);
+        assert_eq!(L1FeeFormula::from_timestamp(&cfg, 10), L1FeeFormula::Regolith);
+        // The first Ecotone block uses the pre-Ecotone formula.
+        assert_eq!(L1FeeFormula::from_timestamp(&cfg, 20), L1FeeFormula::Regolith);
+        assert_eq!(L1FeeFormula::from_timestamp(&cfg, 22), L1FeeFormula::Ecotone);
+        assert_eq!(L1FeeFormula::from_timestamp(&cfg, 30), L1FeeFormula::Fjord);
+
+        let genesis = RollupConfig { ecotone_time: Some(0), ..test_cfg() };
+        assert_eq!(L1FeeFormula::from_timestamp(&genesis, 0), L1FeeFormula::Ecotone);
+    }
+
+    #[test]
+    fn test_l1_fee() {
+        let cfg = test_cfg();
+        let tx = bytes!("FACADE");
+        let base_fee = U256::from(1_000);
+        let sys_config = SystemConfig {
+            overhead: U256::from(1_000),
+            scalar: U256::from(1_000),
+            base_fee_scalar: Some(1_000),
+            blob_base_fee_scalar: Some(1_000),
+            ..Default::default()
+        };
+
+        assert_eq!(l1_fee(&cfg, 8, &sys_config, base_fee, base_fee, &tx), U256::from(2136));
+        assert_eq!(l1_fee(&cfg, 10, &sys_config, base_fee, base_fee, &tx), U256::from(1048));
+        assert_eq!(l1_fee(&cfg, 22, &sys_config, base_fee, base_fee, &tx), U256::from(51));
+        assert_eq!(l1_fee(&cfg, 30, &sys_config, base_fee, base_fee, &tx), U256::from(1700));
+
+        // Deposit transactions do not pay an L1 data fee.
+        let deposit = bytes!("7EFACADE");
+        assert_eq!(l1_fee(&cfg, 30, &sys_config, base_fee, base_fee, &deposit), U256::ZERO);
+    }
+
+    #[test]
+    fn test_l1_fee_packed_ecotone_scalar() {
+        let tx = bytes!("FACADE");
+        let mut scalar = [0u8; 32];
+        scalar[0] = 1;
+        scalar[24..28].copy_from_slice(&1_000u32.to_be_bytes());
+        scalar[28..].copy_from_slice(&1_000u32.to_be_bytes());
+        let sys_config = SystemConfig { scalar: U256::from_be_bytes(scalar), ..Default::default() };
+
+        let fee = U256::from(1_000);
+        assert_eq!(L1FeeFormula::Ecotone.l1_fee(&sys_config, fee, fee, &tx), U256::from(51));
+        assert_eq!(L1FeeFormula::Fjord.l1_fee(&sys_config, fee, fee, &tx), U256::from(1700));
+    }
+}
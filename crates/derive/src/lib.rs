@@ -5,7 +5,7 @@
 )]
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 #![cfg_attr(not(test), warn(unused_crate_dependencies))]
-#![cfg_attr(not(test), no_std)]
+#![cfg_attr(not(any(test, feature = "arbitrary")), no_std)]
 
 extern crate alloc;
 
@@ -15,17 +15,27 @@ extern crate tracing;
 /// Required types and traits for kona's derivation pipeline.
 pub mod prelude {
     pub use crate::{
-        attributes::*, errors::*, pipeline::*, sources::*, stages::*, traits::*, types::*,
+        attributes::*, constants::*, errors::*, fastlz::*, l1_fee::*, pipeline::*, provenance::*,
+        sources::*, stages::*, traits::*, types::*,
     };
 }
 
 pub mod attributes;
+pub mod constants;
+pub mod economics;
 pub mod errors;
+pub mod fastlz;
+pub mod l1_fee;
 pub mod pipeline;
+pub mod provenance;
+pub mod replay;
 pub mod sources;
 pub mod stages;
 pub mod traits;
 pub mod types;
 
+#[cfg(any(test, feature = "arbitrary"))]
+pub mod fuzzing;
+
 #[cfg(any(test, feature = "test-utils"))]
 pub mod test_utils;
@@ -1,12 +1,15 @@
 //! Contains the `PipelineBuilder` object that is used to build a `DerivationPipeline`.
 
 use crate::{
-    pipeline::DerivationPipeline,
+    pipeline::{DerivationPipeline, StageStack},
     stages::{
         AttributesQueue, BatchProvider, BatchStream, ChannelProvider, ChannelReader, FrameQueue,
         L1Retrieval, L1Traversal,
     },
-    traits::{AttributesBuilder, ChainProvider, DataAvailabilityProvider, L2ChainProvider},
+    traits::{
+        AttributesBuilder, ChainProvider, DataAvailabilityProvider, L1BlockTag, L2ChainProvider,
+        NextAttributes, OriginAdvancer, OriginProvider, SignalReceiver,
+    },
 };
 use alloc::sync::Arc;
 use core::fmt::Debug;
@@ -37,6 +40,8 @@ where
     builder: Option<B>,
     origin: Option<BlockInfo>,
     rollup_config: Option<Arc<RollupConfig>>,
+    l1_confirmations: u64,
+    l1_follow_tag: Option<L1BlockTag>,
 }
 
 impl<B, P, T, D> Default for PipelineBuilder<B, P, T, D>
@@ -54,6 +59,8 @@ where
             builder: None,
             origin: None,
             rollup_config: None,
+            l1_confirmations: 0,
+            l1_follow_tag: None,
         }
     }
 }
@@ -82,6 +89,20 @@ where
         self
     }
 
+    /// Sets the number of confirmations an L1 block needs before the pipeline advances its
+    /// origin to it. Defaults to zero.
+    pub const fn l1_confirmations(mut self, l1_confirmations: u64) -> Self {
+        self.l1_confirmations = l1_confirmations;
+        self
+    }
+
+    /// Sets the L1 head tag the pipeline follows, so that it only derives from L1 blocks that
+    /// are labelled safe or finalized. By default, the pipeline advances to any available block.
+    pub const fn l1_follow_tag(mut self, tag: L1BlockTag) -> Self {
+        self.l1_follow_tag = Some(tag);
+        self
+    }
+
     /// Sets the data availability provider for the pipeline.
     pub fn dap_source(mut self, dap_source: D) -> Self {
         self.dap_source = Some(dap_source);
@@ -110,6 +131,40 @@ where
     pub fn build(self) -> DerivationPipeline<AttributesQueueStage<D, P, T, B>, T> {
         self.into()
     }
+
+    /// Builds a pipeline with a custom stage stack.
+    ///
+    /// The closure is given a [StageStack] containing the [L1Traversal] stage, positioned at the
+    /// origin, along with the data availability provider and attributes builder. It may compose
+    /// the remaining stages in any order, wrapping or replacing individual stages as needed.
+    pub fn build_with<S, F>(self, f: F) -> DerivationPipeline<S, T>
+    where
+        S: NextAttributes + SignalReceiver + OriginProvider + OriginAdvancer + Debug + Send,
+        F: FnOnce(StageStack<L1TraversalStage<P>, T>, D, B) -> StageStack<S, T>,
+    {
+        let (stack, dap_source, attributes_builder) = self.into_parts();
+        f(stack, dap_source, attributes_builder).build()
+    }
+
+    /// Splits the builder into a [StageStack] containing the [L1Traversal] stage, the data
+    /// availability provider, and the attributes builder.
+    fn into_parts(self) -> (StageStack<L1TraversalStage<P>, T>, D, B) {
+        let rollup_config = self.rollup_config.expect("rollup_config must be set");
+        let chain_provider = self.chain_provider.expect("chain_provider must be set");
+        let l2_chain_provider = self.l2_chain_provider.expect("chain_provider must be set");
+        let dap_source = self.dap_source.expect("dap_source must be set");
+        let attributes_builder = self.builder.expect("builder must be set");
+
+        let mut l1_traversal = L1Traversal::new(chain_provider, Arc::clone(&rollup_config))
+            .with_confirmations(self.l1_confirmations);
+        l1_traversal.follow_tag = self.l1_follow_tag;
+        l1_traversal.block = Some(self.origin.expect("origin must be set"));
+        (
+            StageStack::new(l1_traversal, rollup_config, l2_chain_provider),
+            dap_source,
+            attributes_builder,
+        )
+    }
 }
 
 impl<B, P, T, D> From<PipelineBuilder<B, P, T, D>>
@@ -121,28 +176,16 @@ where
     D: DataAvailabilityProvider + Send + Sync + Debug,
 {
     fn from(builder: PipelineBuilder<B, P, T, D>) -> Self {
-        // Extract the builder fields.
-        let rollup_config = builder.rollup_config.expect("rollup_config must be set");
-        let chain_provider = builder.chain_provider.expect("chain_provider must be set");
-        let l2_chain_provider = builder.l2_chain_provider.expect("chain_provider must be set");
-        let dap_source = builder.dap_source.expect("dap_source must be set");
-        let attributes_builder = builder.builder.expect("builder must be set");
-
-        // Compose the stage stack.
-        let mut l1_traversal = L1Traversal::new(chain_provider, Arc::clone(&rollup_config));
-        l1_traversal.block = Some(builder.origin.expect("origin must be set"));
-        let l1_retrieval = L1Retrieval::new(l1_traversal, dap_source);
-        let frame_queue = FrameQueue::new(l1_retrieval, Arc::clone(&rollup_config));
-        let channel_provider = ChannelProvider::new(Arc::clone(&rollup_config), frame_queue);
-        let channel_reader = ChannelReader::new(channel_provider, Arc::clone(&rollup_config));
-        let batch_stream =
-            BatchStream::new(channel_reader, rollup_config.clone(), l2_chain_provider.clone());
-        let batch_provider =
-            BatchProvider::new(rollup_config.clone(), batch_stream, l2_chain_provider.clone());
-        let attributes =
-            AttributesQueue::new(rollup_config.clone(), batch_provider, attributes_builder);
-
-        // Create the pipeline.
-        Self::new(attributes, rollup_config, l2_chain_provider)
+        // Compose the default stage stack.
+        builder.build_with(|stack, dap_source, attributes_builder| {
+            stack
+                .l1_retrieval(dap_source)
+                .frame_queue()
+                .channel_provider()
+                .channel_reader()
+                .batch_stream()
+                .batch_provider()
+                .attributes_queue(attributes_builder)
+        })
     }
 }
@@ -4,15 +4,18 @@ use crate::{
     errors::{PipelineError, PipelineErrorKind},
     traits::{
         L2ChainProvider, NextAttributes, OriginAdvancer, OriginProvider, Pipeline, SignalReceiver,
+        StageSnapshot,
     },
-    types::{ActivationSignal, PipelineResult, ResetSignal, Signal, StepResult},
+    types::{ActivationSignal, PipelineResult, PipelineSnapshot, ResetSignal, Signal, StepResult},
 };
-use alloc::{boxed::Box, collections::VecDeque, sync::Arc};
+use alloc::{boxed::Box, collections::VecDeque, sync::Arc, vec::Vec};
+use alloy_primitives::{keccak256, B256};
 use async_trait::async_trait;
 use core::fmt::Debug;
 use maili_protocol::{BlockInfo, L2BlockInfo};
 use op_alloy_genesis::{RollupConfig, SystemConfig};
 use op_alloy_rpc_types_engine::OpAttributesWithParent;
+use tracing::Instrument;
 
 /// The derivation pipeline is responsible for deriving L2 inputs from L1 data.
 #[derive(Debug)]
@@ -31,6 +34,12 @@ where
     pub rollup_config: Arc<RollupConfig>,
     /// The L2 Chain Provider used to fetch the system config on reset.
     pub l2_chain_provider: P,
+    /// The maximum number of prepared [OpAttributesWithParent], if bounded.
+    pub prepared_capacity: Option<usize>,
+    /// The hash of the last prepared [OpAttributesWithParent].
+    pub last_attributes_hash: Option<B256>,
+    /// Whether the pipeline is paused, see [Pipeline::pause].
+    pub paused: bool,
 }
 
 impl<S, P> DerivationPipeline<S, P>
@@ -44,8 +53,55 @@ where
         rollup_config: Arc<RollupConfig>,
         l2_chain_provider: P,
     ) -> Self {
-        Self { attributes, prepared: VecDeque::new(), rollup_config, l2_chain_provider }
+        Self {
+            attributes,
+            prepared: VecDeque::new(),
+            rollup_config,
+            l2_chain_provider,
+            prepared_capacity: None,
+            last_attributes_hash: None,
+            paused: false,
+        }
+    }
+
+    /// Bounds the number of prepared [OpAttributesWithParent] to `capacity`.
+    ///
+    /// Once the bound is reached, [Pipeline::step] stops pulling from the stages and fails with a
+    /// temporary [PipelineError::PreparedQueueFull] until the consumer drains the queue, e.g. with
+    /// [Pipeline::produce_next]. This keeps a slow consumer from causing unbounded memory growth.
+    pub const fn with_prepared_capacity(mut self, capacity: usize) -> Self {
+        self.prepared_capacity = Some(capacity);
+        self
+    }
+
+    /// Returns a [PipelineSnapshot] of the pipeline's current state.
+    ///
+    /// The hash of the last derived attributes is the keccak256 hash of their parent block hash,
+    /// timestamp, and transactions, so that derivation can be compared across nodes.
+    pub fn snapshot(&self) -> PipelineSnapshot
+    where
+        S: StageSnapshot,
+    {
+        let mut snapshot = PipelineSnapshot {
+            origin: self.origin(),
+            prepared: self.prepared.len(),
+            last_attributes_hash: self.last_attributes_hash,
+            ..Default::default()
+        };
+        self.attributes.snapshot(&mut snapshot);
+        snapshot
+    }
+}
+
+/// Returns the hash of the given attributes, see [DerivationPipeline::snapshot].
+fn attributes_hash(attributes: &OpAttributesWithParent) -> B256 {
+    let mut data = Vec::new();
+    data.extend_from_slice(attributes.parent.block_info.hash.as_slice());
+    data.extend_from_slice(&attributes.attributes.payload_attributes.timestamp.to_be_bytes());
+    for tx in attributes.attributes.transactions.iter().flatten() {
+        data.extend_from_slice(tx);
     }
+    keccak256(data)
 }
 
 impl<S, P> OriginProvider for DerivationPipeline<S, P>
@@ -133,6 +189,38 @@ where
         self.prepared.front()
     }
 
+    /// Resets the pipeline to the given L2 safe head and L1 origin, dropping the prepared
+    /// [OpAttributesWithParent].
+    async fn reset(
+        &mut self,
+        l2_safe_head: L2BlockInfo,
+        l1_origin: BlockInfo,
+    ) -> PipelineResult<()> {
+        self.prepared.clear();
+        self.signal(ResetSignal { l2_safe_head, l1_origin, system_config: None }.signal()).await
+    }
+
+    /// Pauses the pipeline, see [Pipeline::pause].
+    fn pause(&mut self) {
+        if !self.paused {
+            info!(target: "pipeline", "Pausing derivation");
+            self.paused = true;
+        }
+    }
+
+    /// Resumes the pipeline.
+    fn resume(&mut self) {
+        if self.paused {
+            info!(target: "pipeline", "Resuming derivation");
+            self.paused = false;
+        }
+    }
+
+    /// Returns `true` if the pipeline is paused.
+    fn is_paused(&self) -> bool {
+        self.paused
+    }
+
     /// Returns the rollup config.
     fn rollup_config(&self) -> &RollupConfig {
         &self.rollup_config
@@ -162,16 +250,32 @@ where
     ///
     /// [PipelineError]: crate::errors::PipelineError
     async fn step(&mut self, cursor: L2BlockInfo) -> StepResult {
-        match self.attributes.next_attributes(cursor).await {
+        if self.paused {
+            trace!(target: "pipeline", "Pipeline is paused");
+            return StepResult::StepFailed(PipelineError::Paused.temp());
+        }
+        if self.prepared_capacity.is_some_and(|capacity| self.prepared.len() >= capacity) {
+            trace!(target: "pipeline", "Prepared attributes queue is full");
+            return StepResult::StepFailed(PipelineError::PreparedQueueFull.temp());
+        }
+
+        let span = debug_span!(
+            target: "pipeline",
+            "step",
+            l2_block = cursor.block_info.number,
+            origin = self.origin().map(|o| o.number),
+        );
+        match self.attributes.next_attributes(cursor).instrument(span.clone()).await {
             Ok(a) => {
                 trace!(target: "pipeline", "Prepared L2 attributes: {:?}", a);
+                self.last_attributes_hash = Some(attributes_hash(&a));
                 self.prepared.push_back(a);
                 StepResult::PreparedAttributes
             }
             Err(err) => match err {
                 PipelineErrorKind::Temporary(PipelineError::Eof) => {
                     trace!(target: "pipeline", "Pipeline advancing origin");
-                    if let Err(e) = self.attributes.advance_origin().await {
+                    if let Err(e) = self.attributes.advance_origin().instrument(span).await {
                         return StepResult::OriginAdvanceErr(e);
                     }
                     StepResult::AdvancedOrigin
@@ -192,12 +296,17 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{pipeline::DerivationPipeline, test_utils::*};
-    use alloc::{string::ToString, sync::Arc};
+    use crate::{
+        pipeline::DerivationPipeline, test_utils::*, traits::PRODUCE_YIELD_BUDGET,
+        types::ProduceResult,
+    };
+    use alloc::{string::ToString, sync::Arc, vec};
+    use alloy_primitives::Bytes;
     use alloy_rpc_types_engine::PayloadAttributes;
-    use maili_protocol::L2BlockInfo;
+    use maili_protocol::{BlockInfo, L2BlockInfo};
     use op_alloy_genesis::{RollupConfig, SystemConfig};
     use op_alloy_rpc_types_engine::{OpAttributesWithParent, OpPayloadAttributes};
+    use tracing_subscriber::layer::SubscriberExt;
 
     fn default_test_payload_attributes() -> OpAttributesWithParent {
         OpAttributesWithParent {
@@ -219,6 +328,26 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_pipeline_step_spans() {
+        let spans = TraceStorage::default();
+        let layer = CollectingLayer::new(TraceStorage::default()).with_spans(spans.clone());
+        let subscriber = tracing_subscriber::Registry::default().with(layer);
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let mut pipeline = new_test_pipeline();
+        let cursor = L2BlockInfo {
+            block_info: BlockInfo { number: 10, ..Default::default() },
+            ..Default::default()
+        };
+        pipeline.step(cursor).await;
+
+        let spans = spans.get_by_level(tracing::Level::DEBUG);
+        assert!(spans[0].starts_with("step"));
+        assert!(spans[0].contains("l2_block: 10"));
+        assert!(spans.iter().any(|s| s.starts_with("next_batch")));
+    }
+
     #[test]
     fn test_pipeline_next_attributes_empty() {
         let mut pipeline = new_test_pipeline();
@@ -239,6 +368,32 @@ mod tests {
         assert_eq!(result, Some(expected));
     }
 
+    #[tokio::test]
+    async fn test_derivation_pipeline_snapshot() {
+        let pipeline = new_test_pipeline();
+        assert_eq!(
+            pipeline.snapshot(),
+            PipelineSnapshot { origin: Some(BlockInfo::default()), ..Default::default() }
+        );
+
+        let expected = default_test_payload_attributes();
+        let attributes = TestNextAttributes { next_attributes: Some(expected.clone()) };
+        let mut pipeline = DerivationPipeline::new(
+            attributes,
+            Arc::new(RollupConfig::default()),
+            TestL2ChainProvider::default(),
+        );
+        assert_eq!(pipeline.step(L2BlockInfo::default()).await, StepResult::PreparedAttributes);
+        let snapshot = pipeline.snapshot();
+        assert_eq!(snapshot.prepared, 1);
+        assert_eq!(snapshot.last_attributes_hash, Some(attributes_hash(&expected)));
+
+        // The hash covers the transactions of the attributes.
+        let mut other = expected.clone();
+        other.attributes.transactions = Some(vec![Bytes::from_static(&[0x7E])]);
+        assert_ne!(attributes_hash(&other), attributes_hash(&expected));
+    }
+
     #[tokio::test]
     async fn test_derivation_pipeline_missing_block() {
         let mut pipeline = new_test_pipeline();
@@ -328,4 +483,219 @@ mod tests {
         let result = pipeline.signal(ResetSignal::default().signal()).await;
         assert!(result.is_ok());
     }
+
+    /// A stage that records the last signal it received.
+    #[derive(Debug, Default)]
+    struct SignalRecorder {
+        last_signal: Option<Signal>,
+    }
+
+    #[async_trait]
+    impl SignalReceiver for SignalRecorder {
+        async fn signal(&mut self, signal: Signal) -> PipelineResult<()> {
+            self.last_signal = Some(signal);
+            Ok(())
+        }
+    }
+
+    impl OriginProvider for SignalRecorder {
+        fn origin(&self) -> Option<BlockInfo> {
+            None
+        }
+    }
+
+    #[async_trait]
+    impl OriginAdvancer for SignalRecorder {
+        async fn advance_origin(&mut self) -> PipelineResult<()> {
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl NextAttributes for SignalRecorder {
+        async fn next_attributes(
+            &mut self,
+            _: L2BlockInfo,
+        ) -> PipelineResult<OpAttributesWithParent> {
+            Err(PipelineError::Eof.temp())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_derivation_pipeline_reset() {
+        let rollup_config = Arc::new(RollupConfig::default());
+        let system_config = SystemConfig { gas_limit: 30_000_000, ..Default::default() };
+        let mut l2_chain_provider = TestL2ChainProvider::default();
+        l2_chain_provider.system_configs.insert(5, system_config);
+        let mut pipeline =
+            DerivationPipeline::new(SignalRecorder::default(), rollup_config, l2_chain_provider);
+        pipeline.prepared.push_back(default_test_payload_attributes());
+
+        let l2_safe_head = L2BlockInfo {
+            block_info: BlockInfo { number: 5, ..Default::default() },
+            ..Default::default()
+        };
+        let l1_origin = BlockInfo { number: 10, ..Default::default() };
+        pipeline.reset(l2_safe_head, l1_origin).await.unwrap();
+        assert!(pipeline.prepared.is_empty());
+        assert_eq!(
+            pipeline.attributes.last_signal,
+            Some(
+                ResetSignal { l2_safe_head, l1_origin, system_config: Some(system_config) }
+                    .signal()
+            )
+        );
+
+        // The reset fails if the system config of the L2 safe head is unknown.
+        let l2_safe_head = L2BlockInfo::default();
+        assert!(pipeline.reset(l2_safe_head, l1_origin).await.is_err());
+    }
+
+    /// A stage that fails with [PipelineError::NotEnoughData] a number of times before producing
+    /// attributes.
+    #[derive(Debug)]
+    struct SlowNextAttributes {
+        remaining: usize,
+        fail_advance: bool,
+    }
+
+    #[async_trait]
+    impl SignalReceiver for SlowNextAttributes {
+        async fn signal(&mut self, _: Signal) -> PipelineResult<()> {
+            Ok(())
+        }
+    }
+
+    impl OriginProvider for SlowNextAttributes {
+        fn origin(&self) -> Option<BlockInfo> {
+            Some(BlockInfo::default())
+        }
+    }
+
+    #[async_trait]
+    impl OriginAdvancer for SlowNextAttributes {
+        async fn advance_origin(&mut self) -> PipelineResult<()> {
+            if self.fail_advance {
+                return Err(PipelineError::MissingOrigin.crit());
+            }
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl NextAttributes for SlowNextAttributes {
+        async fn next_attributes(
+            &mut self,
+            _: L2BlockInfo,
+        ) -> PipelineResult<OpAttributesWithParent> {
+            if self.remaining == usize::MAX {
+                return Err(PipelineError::Eof.temp());
+            }
+            if self.remaining > 0 {
+                self.remaining -= 1;
+                return Err(PipelineError::NotEnoughData.temp());
+            }
+            Ok(default_test_payload_attributes())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_derivation_pipeline_produce_next() {
+        let rollup_config = Arc::new(RollupConfig::default());
+        let attributes =
+            SlowNextAttributes { remaining: 3 * PRODUCE_YIELD_BUDGET, fail_advance: false };
+        let mut pipeline =
+            DerivationPipeline::new(attributes, rollup_config, TestL2ChainProvider::default());
+        let result = pipeline.produce_next(L2BlockInfo::default()).await;
+        assert_eq!(result, ProduceResult::Attributes(default_test_payload_attributes()));
+        assert!(pipeline.next().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_derivation_pipeline_produce_next_prepared() {
+        let mut pipeline = new_test_pipeline();
+        pipeline.prepared.push_back(default_test_payload_attributes());
+        let result = pipeline.produce_next(L2BlockInfo::default()).await;
+        assert_eq!(result, ProduceResult::Attributes(default_test_payload_attributes()));
+    }
+
+    #[tokio::test]
+    async fn test_derivation_pipeline_produce_next_exhausted() {
+        let mut pipeline = new_test_pipeline();
+        let result = pipeline.produce_next(L2BlockInfo::default()).await;
+        assert_eq!(
+            result,
+            ProduceResult::Exhausted(PipelineError::Provider("Block not found".to_string()).temp())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_derivation_pipeline_produce_next_failed() {
+        let rollup_config = Arc::new(RollupConfig::default());
+        let attributes = SlowNextAttributes { remaining: usize::MAX, fail_advance: true };
+        let mut pipeline =
+            DerivationPipeline::new(attributes, rollup_config, TestL2ChainProvider::default());
+        let result = pipeline.produce_next(L2BlockInfo::default()).await;
+        assert_eq!(result, ProduceResult::Failed(PipelineError::MissingOrigin.crit()));
+    }
+
+    #[tokio::test]
+    async fn test_derivation_pipeline_prepared_capacity() {
+        let rollup_config = Arc::new(RollupConfig::default());
+        let attributes =
+            TestNextAttributes { next_attributes: Some(default_test_payload_attributes()) };
+        let mut pipeline =
+            DerivationPipeline::new(attributes, rollup_config, TestL2ChainProvider::default())
+                .with_prepared_capacity(1);
+        pipeline.prepared.push_back(default_test_payload_attributes());
+
+        // The full queue is not extended until the consumer drains it.
+        let cursor = L2BlockInfo::default();
+        assert_eq!(
+            pipeline.step(cursor).await,
+            StepResult::StepFailed(PipelineError::PreparedQueueFull.temp())
+        );
+        assert!(pipeline.attributes.next_attributes.is_some());
+        assert!(pipeline.next().is_some());
+        assert_eq!(pipeline.step(cursor).await, StepResult::PreparedAttributes);
+        assert_eq!(pipeline.prepared.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_derivation_pipeline_pause_resume() {
+        let rollup_config = Arc::new(RollupConfig::default());
+        let attributes =
+            TestNextAttributes { next_attributes: Some(default_test_payload_attributes()) };
+        let mut pipeline =
+            DerivationPipeline::new(attributes, rollup_config, TestL2ChainProvider::default());
+
+        pipeline.pause();
+        assert!(pipeline.is_paused());
+        let cursor = L2BlockInfo::default();
+        assert_eq!(
+            pipeline.step(cursor).await,
+            StepResult::StepFailed(PipelineError::Paused.temp())
+        );
+        assert_eq!(pipeline.produce_next(cursor).await, ProduceResult::Paused);
+        assert!(pipeline.attributes.next_attributes.is_some());
+
+        pipeline.resume();
+        assert!(!pipeline.is_paused());
+        assert_eq!(pipeline.step(cursor).await, StepResult::PreparedAttributes);
+    }
+
+    #[tokio::test]
+    async fn test_derivation_pipeline_paused_reset() {
+        let rollup_config = Arc::new(RollupConfig::default());
+        let mut l2_chain_provider = TestL2ChainProvider::default();
+        l2_chain_provider.system_configs.insert(0, SystemConfig::default());
+        let mut pipeline =
+            DerivationPipeline::new(SignalRecorder::default(), rollup_config, l2_chain_provider);
+
+        // A paused pipeline can be reset, and stays paused.
+        pipeline.pause();
+        pipeline.reset(L2BlockInfo::default(), BlockInfo::default()).await.unwrap();
+        assert!(pipeline.attributes.last_signal.is_some());
+        assert!(pipeline.is_paused());
+    }
 }
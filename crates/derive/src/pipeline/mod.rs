@@ -3,5 +3,11 @@
 mod builder;
 pub use builder::PipelineBuilder;
 
+mod set;
+pub use set::PipelineSet;
+
+mod stack;
+pub use stack::StageStack;
+
 mod core;
 pub use core::DerivationPipeline;
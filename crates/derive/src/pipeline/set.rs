@@ -0,0 +1,186 @@
+//! Contains the [PipelineSet], which drives derivation pipelines for multiple L2 chains.
+
+use crate::{traits::Pipeline, types::StepResult};
+use alloc::{collections::BTreeMap, vec::Vec};
+use maili_protocol::L2BlockInfo;
+use op_alloy_rpc_types_engine::OpAttributesWithParent;
+
+/// A [PipelineSet] manages independent derivation pipelines for multiple L2 chains, keyed by the
+/// L2 chain ID of each pipeline's rollup config.
+///
+/// Pipelines in a set typically share their L1 chain and blob providers, wrapped in a
+/// [SharedChainProvider] and [SharedBlobProvider] so that L1 data requested by more than one
+/// chain is only fetched once.
+///
+/// [SharedChainProvider]: crate::sources::SharedChainProvider
+/// [SharedBlobProvider]: crate::sources::SharedBlobProvider
+#[derive(Debug)]
+pub struct PipelineSet<P>
+where
+    P: Pipeline + Send,
+{
+    /// The pipelines, keyed by L2 chain ID.
+    pipelines: BTreeMap<u64, P>,
+}
+
+impl<P> Default for PipelineSet<P>
+where
+    P: Pipeline + Send,
+{
+    fn default() -> Self {
+        Self { pipelines: BTreeMap::new() }
+    }
+}
+
+impl<P> PipelineSet<P>
+where
+    P: Pipeline + Send,
+{
+    /// Creates a new, empty [PipelineSet].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts a pipeline into the set, returning the pipeline previously registered for the same
+    /// L2 chain ID, if any.
+    pub fn insert(&mut self, pipeline: P) -> Option<P> {
+        self.pipelines.insert(pipeline.rollup_config().l2_chain_id, pipeline)
+    }
+
+    /// Removes the pipeline for the given L2 chain ID from the set.
+    pub fn remove(&mut self, chain_id: u64) -> Option<P> {
+        self.pipelines.remove(&chain_id)
+    }
+
+    /// Returns a reference to the pipeline for the given L2 chain ID.
+    pub fn get(&self, chain_id: u64) -> Option<&P> {
+        self.pipelines.get(&chain_id)
+    }
+
+    /// Returns a mutable reference to the pipeline for the given L2 chain ID.
+    pub fn get_mut(&mut self, chain_id: u64) -> Option<&mut P> {
+        self.pipelines.get_mut(&chain_id)
+    }
+
+    /// Returns the L2 chain IDs of the pipelines in the set, in ascending order.
+    pub fn chain_ids(&self) -> impl Iterator<Item = u64> + '_ {
+        self.pipelines.keys().copied()
+    }
+
+    /// Returns the number of pipelines in the set.
+    pub fn len(&self) -> usize {
+        self.pipelines.len()
+    }
+
+    /// Returns whether the set contains no pipelines.
+    pub fn is_empty(&self) -> bool {
+        self.pipelines.is_empty()
+    }
+
+    /// Steps every pipeline that has a cursor in `cursors` once, returning the [StepResult] of
+    /// each stepped pipeline keyed by L2 chain ID.
+    ///
+    /// Pipelines are stepped in ascending chain ID order. A failing pipeline does not prevent the
+    /// remaining pipelines from being stepped.
+    pub async fn step(
+        &mut self,
+        cursors: &BTreeMap<u64, L2BlockInfo>,
+    ) -> BTreeMap<u64, StepResult> {
+        let mut results = BTreeMap::new();
+        for (chain_id, pipeline) in self.pipelines.iter_mut() {
+            let Some(cursor) = cursors.get(chain_id) else {
+                continue;
+            };
+            results.insert(*chain_id, pipeline.step(*cursor).await);
+        }
+        results
+    }
+
+    /// Drains the prepared [OpAttributesWithParent] from every pipeline in the set, paired with
+    /// the L2 chain ID of the pipeline that derived them.
+    pub fn drain_attributes(&mut self) -> Vec<(u64, OpAttributesWithParent)> {
+        self.pipelines
+            .iter_mut()
+            .flat_map(|(chain_id, pipeline)| pipeline.map(|attributes| (*chain_id, attributes)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        pipeline::DerivationPipeline,
+        test_utils::{TestL2ChainProvider, TestNextAttributes},
+    };
+    use alloc::sync::Arc;
+    use alloy_rpc_types_engine::PayloadAttributes;
+    use op_alloy_genesis::RollupConfig;
+    use op_alloy_rpc_types_engine::OpPayloadAttributes;
+
+    fn test_attributes(timestamp: u64) -> OpAttributesWithParent {
+        OpAttributesWithParent {
+            attributes: OpPayloadAttributes {
+                payload_attributes: PayloadAttributes {
+                    timestamp,
+                    prev_randao: Default::default(),
+                    suggested_fee_recipient: Default::default(),
+                    withdrawals: None,
+                    parent_beacon_block_root: None,
+                },
+                transactions: None,
+                no_tx_pool: None,
+                gas_limit: None,
+                eip_1559_params: None,
+            },
+            parent: Default::default(),
+            is_last_in_span: false,
+        }
+    }
+
+    fn new_pipeline(
+        chain_id: u64,
+        attributes: Option<OpAttributesWithParent>,
+    ) -> DerivationPipeline<TestNextAttributes, TestL2ChainProvider> {
+        let cfg = Arc::new(RollupConfig { l2_chain_id: chain_id, ..Default::default() });
+        DerivationPipeline::new(
+            TestNextAttributes { next_attributes: attributes },
+            cfg,
+            TestL2ChainProvider::default(),
+        )
+    }
+
+    #[test]
+    fn test_pipeline_set_insert_remove() {
+        let mut set = PipelineSet::new();
+        assert!(set.is_empty());
+        assert!(set.insert(new_pipeline(10, None)).is_none());
+        assert!(set.insert(new_pipeline(8453, None)).is_none());
+        assert!(set.insert(new_pipeline(10, None)).is_some());
+        assert_eq!(set.len(), 2);
+        assert_eq!(set.chain_ids().collect::<Vec<_>>(), [10, 8453]);
+        assert_eq!(set.get(8453).unwrap().rollup_config().l2_chain_id, 8453);
+        assert!(set.remove(10).is_some());
+        assert!(set.get(10).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_set_step() {
+        let mut set = PipelineSet::new();
+        set.insert(new_pipeline(10, Some(test_attributes(1))));
+        set.insert(new_pipeline(8453, Some(test_attributes(2))));
+        set.insert(new_pipeline(7777777, None));
+
+        // Only pipelines with a cursor are stepped.
+        let cursors =
+            BTreeMap::from([(10, L2BlockInfo::default()), (7777777, L2BlockInfo::default())]);
+        let results = set.step(&cursors).await;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[&10], StepResult::PreparedAttributes);
+        assert_ne!(results[&7777777], StepResult::PreparedAttributes);
+
+        let attributes = set.drain_attributes();
+        assert_eq!(attributes, [(10, test_attributes(1))]);
+        assert!(set.drain_attributes().is_empty());
+    }
+}
@@ -0,0 +1,290 @@
+//! Contains the [StageStack], a type-checked builder for custom stage stacks.
+
+use crate::{
+    pipeline::DerivationPipeline,
+    stages::{
+        AttributesQueue, BatchProvider, BatchStream, BatchStreamProvider, ChannelProvider,
+        ChannelReader, ChannelReaderProvider, FrameQueue, FrameQueueProvider, L1Retrieval,
+        L1RetrievalProvider, NextBatchProvider, NextFrameProvider,
+    },
+    traits::{
+        AttributesBuilder, AttributesProvider, DataAvailabilityProvider, L2ChainProvider,
+        NextAttributes, OriginAdvancer, OriginProvider, SignalReceiver,
+    },
+};
+use alloc::sync::Arc;
+use core::fmt::Debug;
+use op_alloy_genesis::RollupConfig;
+
+/// A [StageStack] composes the stages of a [DerivationPipeline] one at a time, from the bottom
+/// of the stack up.
+///
+/// Each step is only available when the current top of the stack implements the provider trait
+/// required by the next stage, so custom stages can be swapped in or wrapped around the default
+/// ones without giving up type-checked chaining. For example, a recording stage can be injected
+/// between the [ChannelReader] and [BatchStream] stages:
+///
+/// ```ignore
+/// let pipeline = StageStack::new(l1_traversal, rollup_config, l2_chain_provider)
+///     .l1_retrieval(dap_source)
+///     .frame_queue()
+///     .channel_provider()
+///     .channel_reader()
+///     .map(RecordingStage::new)
+///     .batch_stream()
+///     .batch_provider()
+///     .attributes_queue(attributes_builder)
+///     .build();
+/// ```
+///
+/// The [PipelineBuilder] uses a [StageStack] to compose the default stage stack.
+///
+/// [PipelineBuilder]: crate::pipeline::PipelineBuilder
+#[derive(Debug)]
+pub struct StageStack<S, T>
+where
+    T: L2ChainProvider + Clone + Send + Sync + Debug,
+{
+    /// The top of the stage stack.
+    stage: S,
+    /// The rollup config.
+    rollup_config: Arc<RollupConfig>,
+    /// The L2 chain provider.
+    l2_chain_provider: T,
+}
+
+impl<S, T> StageStack<S, T>
+where
+    T: L2ChainProvider + Clone + Send + Sync + Debug,
+{
+    /// Creates a new [StageStack] with the given bottom stage, typically an [L1Traversal].
+    ///
+    /// [L1Traversal]: crate::stages::L1Traversal
+    pub const fn new(stage: S, rollup_config: Arc<RollupConfig>, l2_chain_provider: T) -> Self {
+        Self { stage, rollup_config, l2_chain_provider }
+    }
+
+    /// Returns a reference to the top of the stage stack.
+    pub const fn stage(&self) -> &S {
+        &self.stage
+    }
+
+    /// Replaces the top of the stage stack with the result of `f`, which may wrap, configure, or
+    /// replace the current top stage.
+    pub fn map<N>(self, f: impl FnOnce(S) -> N) -> StageStack<N, T> {
+        StageStack {
+            stage: f(self.stage),
+            rollup_config: self.rollup_config,
+            l2_chain_provider: self.l2_chain_provider,
+        }
+    }
+
+    /// Replaces the top of the stage stack with the result of `f`, which is also given the
+    /// rollup config and L2 chain provider.
+    pub fn map_with<N>(self, f: impl FnOnce(S, Arc<RollupConfig>, T) -> N) -> StageStack<N, T> {
+        let stage = f(self.stage, self.rollup_config.clone(), self.l2_chain_provider.clone());
+        StageStack {
+            stage,
+            rollup_config: self.rollup_config,
+            l2_chain_provider: self.l2_chain_provider,
+        }
+    }
+}
+
+impl<S, T> StageStack<S, T>
+where
+    S: L1RetrievalProvider + OriginAdvancer + OriginProvider + SignalReceiver,
+    T: L2ChainProvider + Clone + Send + Sync + Debug,
+{
+    /// Pushes an [L1Retrieval] stage onto the stack.
+    pub fn l1_retrieval<D>(self, dap_source: D) -> StageStack<L1Retrieval<D, S>, T>
+    where
+        D: DataAvailabilityProvider,
+    {
+        self.map(|stage| L1Retrieval::new(stage, dap_source))
+    }
+}
+
+impl<S, T> StageStack<S, T>
+where
+    S: FrameQueueProvider + OriginAdvancer + OriginProvider + SignalReceiver + Debug,
+    T: L2ChainProvider + Clone + Send + Sync + Debug,
+{
+    /// Pushes a [FrameQueue] stage onto the stack.
+    pub fn frame_queue(self) -> StageStack<FrameQueue<S>, T> {
+        self.map_with(|stage, cfg, _| FrameQueue::new(stage, cfg))
+    }
+}
+
+impl<S, T> StageStack<S, T>
+where
+    S: NextFrameProvider + OriginAdvancer + OriginProvider + SignalReceiver + Debug,
+    T: L2ChainProvider + Clone + Send + Sync + Debug,
+{
+    /// Pushes a [ChannelProvider] stage onto the stack.
+    pub fn channel_provider(self) -> StageStack<ChannelProvider<S>, T> {
+        self.map_with(|stage, cfg, _| ChannelProvider::new(cfg, stage))
+    }
+}
+
+impl<S, T> StageStack<S, T>
+where
+    S: ChannelReaderProvider + OriginAdvancer + OriginProvider + SignalReceiver + Debug,
+    T: L2ChainProvider + Clone + Send + Sync + Debug,
+{
+    /// Pushes a [ChannelReader] stage onto the stack.
+    pub fn channel_reader(self) -> StageStack<ChannelReader<S>, T> {
+        self.map_with(|stage, cfg, _| ChannelReader::new(stage, cfg))
+    }
+}
+
+impl<S, T> StageStack<S, T>
+where
+    S: BatchStreamProvider + OriginAdvancer + OriginProvider + SignalReceiver + Debug,
+    T: L2ChainProvider + Clone + Send + Sync + Debug,
+{
+    /// Pushes a [BatchStream] stage onto the stack.
+    pub fn batch_stream(self) -> StageStack<BatchStream<S, T>, T> {
+        self.map_with(|stage, cfg, l2| BatchStream::new(stage, cfg, l2))
+    }
+}
+
+impl<S, T> StageStack<S, T>
+where
+    S: NextBatchProvider + OriginAdvancer + OriginProvider + SignalReceiver + Debug,
+    T: L2ChainProvider + Clone + Send + Sync + Debug,
+{
+    /// Pushes a [BatchProvider] stage onto the stack.
+    pub fn batch_provider(self) -> StageStack<BatchProvider<S, T>, T> {
+        self.map_with(|stage, cfg, l2| BatchProvider::new(cfg, stage, l2))
+    }
+}
+
+impl<S, T> StageStack<S, T>
+where
+    S: AttributesProvider + OriginAdvancer + OriginProvider + SignalReceiver + Debug,
+    T: L2ChainProvider + Clone + Send + Sync + Debug,
+{
+    /// Pushes an [AttributesQueue] stage onto the stack.
+    pub fn attributes_queue<B>(self, builder: B) -> StageStack<AttributesQueue<S, B>, T>
+    where
+        B: AttributesBuilder + Debug,
+    {
+        self.map_with(|stage, cfg, _| AttributesQueue::new(cfg, stage, builder))
+    }
+}
+
+impl<S, T> StageStack<S, T>
+where
+    S: NextAttributes + SignalReceiver + OriginProvider + OriginAdvancer + Debug + Send,
+    T: L2ChainProvider + Clone + Send + Sync + Debug,
+{
+    /// Builds the [DerivationPipeline] from the stage stack.
+    pub fn build(self) -> DerivationPipeline<S, T> {
+        DerivationPipeline::new(self.stage, self.rollup_config, self.l2_chain_provider)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        stages::{ChannelBankConfig, L1Traversal},
+        test_utils::{
+            TestAttributesBuilder, TestChainProvider, TestChannelReader, TestDAP,
+            TestL2ChainProvider,
+        },
+        traits::Pipeline,
+        types::{PipelineResult, Signal, StepResult},
+    };
+    use alloc::{boxed::Box, vec, vec::Vec};
+    use async_trait::async_trait;
+    use maili_protocol::{Batch, BlockInfo, L2BlockInfo};
+    use spin::Mutex;
+
+    /// A stage that records the calls passing through it.
+    #[derive(Debug)]
+    struct RecordingStage<P> {
+        prev: P,
+        calls: Arc<Mutex<Vec<&'static str>>>,
+    }
+
+    #[async_trait]
+    impl<P: BatchStreamProvider + Send> BatchStreamProvider for RecordingStage<P> {
+        async fn next_batch(&mut self) -> PipelineResult<Batch> {
+            self.calls.lock().push("next_batch");
+            self.prev.next_batch().await
+        }
+
+        fn flush(&mut self) {
+            self.calls.lock().push("flush");
+            self.prev.flush()
+        }
+    }
+
+    #[async_trait]
+    impl<P: OriginAdvancer + Send> OriginAdvancer for RecordingStage<P> {
+        async fn advance_origin(&mut self) -> PipelineResult<()> {
+            self.calls.lock().push("advance_origin");
+            self.prev.advance_origin().await
+        }
+    }
+
+    impl<P: OriginProvider> OriginProvider for RecordingStage<P> {
+        fn origin(&self) -> Option<BlockInfo> {
+            self.prev.origin()
+        }
+    }
+
+    #[async_trait]
+    impl<P: SignalReceiver + Send> SignalReceiver for RecordingStage<P> {
+        async fn signal(&mut self, signal: Signal) -> PipelineResult<()> {
+            self.calls.lock().push("signal");
+            self.prev.signal(signal).await
+        }
+    }
+
+    fn test_stack() -> StageStack<TestChannelReader, TestL2ChainProvider> {
+        let rollup_config = Arc::new(RollupConfig::default());
+        let mut l1_traversal =
+            L1Traversal::new(TestChainProvider::default(), rollup_config.clone());
+        l1_traversal.block = Some(BlockInfo::default());
+        StageStack::new(l1_traversal, rollup_config, TestL2ChainProvider::default())
+            .l1_retrieval(TestDAP::default())
+            .frame_queue()
+            .channel_provider()
+            .map(|stage| stage.with_channel_bank_config(ChannelBankConfig::default()))
+            .channel_reader()
+    }
+
+    #[test]
+    fn test_stage_stack_default_stages() {
+        let pipeline = test_stack()
+            .batch_stream()
+            .batch_provider()
+            .attributes_queue(TestAttributesBuilder::default())
+            .build();
+        assert_eq!(pipeline.origin(), Some(BlockInfo::default()));
+        assert_eq!(pipeline.rollup_config(), &RollupConfig::default());
+    }
+
+    #[tokio::test]
+    async fn test_stage_stack_injected_stage() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let stack =
+            test_stack().map(|prev| RecordingStage { prev, calls: calls.clone() }).batch_stream();
+        let mut pipeline =
+            stack.batch_provider().attributes_queue(TestAttributesBuilder::default()).build();
+        assert_eq!(pipeline.origin(), Some(BlockInfo::default()));
+
+        // Stepping the pipeline pulls a batch through the injected stage and, once the stages
+        // are exhausted, advances the origin through it.
+        let result = pipeline.step(L2BlockInfo::default()).await;
+        assert!(matches!(result, StepResult::AdvancedOrigin | StepResult::OriginAdvanceErr(_)));
+        assert_eq!(*calls.lock(), vec!["next_batch", "advance_origin"]);
+
+        // Signals are forwarded through the injected stage.
+        pipeline.signal(Signal::FlushChannel).await.unwrap();
+        assert_eq!(*calls.lock(), vec!["next_batch", "advance_origin", "signal"]);
+    }
+}
@@ -0,0 +1,262 @@
+//! Contains the [BatchTracer], an inspection API that traces decoded batches back to the channel,
+//! frames, and L1 transactions they were derived from.
+
+use crate::{constants::ProtocolConstants, stages::BatchReader, types::ChannelId};
+use alloc::{collections::BTreeMap, sync::Arc, vec::Vec};
+use alloy_consensus::{Transaction, TxEnvelope};
+use alloy_primitives::{Address, B256};
+use maili_protocol::{Batch, BlockInfo, Channel, Frame, FrameParseError, CHANNEL_ID_LENGTH};
+use op_alloy_genesis::RollupConfig;
+
+/// A reference to an L1 batcher transaction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct L1TxRef {
+    /// The L1 block the transaction was included in.
+    pub block: BlockInfo,
+    /// The transaction hash.
+    pub hash: B256,
+    /// The index of the transaction in the block.
+    pub index: u64,
+}
+
+/// A frame of a traced channel, and the L1 transaction that carried it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct FrameRef {
+    /// The frame number.
+    pub number: u16,
+    /// The size of the frame data, in bytes.
+    pub size: usize,
+    /// The L1 transaction that carried the frame.
+    pub tx: L1TxRef,
+}
+
+/// The provenance of the batches decoded from a single channel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct BatchProvenance {
+    /// The channel ID.
+    pub channel_id: ChannelId,
+    /// The frames of the channel, ordered by frame number.
+    pub frames: Vec<FrameRef>,
+}
+
+impl BatchProvenance {
+    /// Returns the distinct L1 transactions that carried the channel's frames, in inclusion order.
+    pub fn l1_txs(&self) -> Vec<L1TxRef> {
+        let mut txs = self.frames.iter().map(|f| f.tx).collect::<Vec<_>>();
+        txs.sort_by_key(|tx| (tx.block.number, tx.index));
+        txs.dedup();
+        txs
+    }
+}
+
+/// A [Batch] decoded by the [BatchTracer], paired with its [BatchProvenance].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TracedBatch {
+    /// The decoded batch.
+    pub batch: Batch,
+    /// The provenance of the channel the batch was decoded from.
+    pub provenance: Arc<BatchProvenance>,
+}
+
+impl TracedBatch {
+    /// Returns whether the batch contains the L2 block with the given timestamp.
+    pub fn contains_timestamp(&self, timestamp: u64) -> bool {
+        match &self.batch {
+            Batch::Single(batch) => batch.timestamp == timestamp,
+            Batch::Span(batch) => batch.batches.iter().any(|b| b.timestamp == timestamp),
+        }
+    }
+}
+
+/// A channel that is being assembled by the [BatchTracer].
+#[derive(Debug)]
+struct TracedChannel {
+    channel: Channel,
+    frames: Vec<FrameRef>,
+}
+
+/// The [BatchTracer] assembles frames from batcher transactions into channels and decodes their
+/// batches, recording the channel ID, frame numbers, and L1 transactions each batch originated
+/// from.
+///
+/// The tracer is meant for provenance queries such as "which batcher transaction produced L2
+/// block N". It does not apply channel timeouts, channel bank size limits, or batch validity
+/// checks, so it may report batches that the derivation pipeline would drop.
+#[derive(Debug)]
+pub struct BatchTracer {
+    /// The rollup config.
+    cfg: Arc<RollupConfig>,
+    /// The channels being assembled, keyed by channel ID.
+    channels: BTreeMap<[u8; CHANNEL_ID_LENGTH], TracedChannel>,
+    /// The decoded batches, in decoding order.
+    batches: Vec<TracedBatch>,
+}
+
+impl BatchTracer {
+    /// Creates a new [BatchTracer].
+    pub const fn new(cfg: Arc<RollupConfig>) -> Self {
+        Self { cfg, channels: BTreeMap::new(), batches: Vec::new() }
+    }
+
+    /// Adds the frames carried by a batcher transaction, returning the number of batches decoded
+    /// from channels completed by the transaction.
+    ///
+    /// For blob transactions, this is called once per blob with the decoded blob data.
+    pub fn add_transaction(&mut self, tx: L1TxRef, data: &[u8]) -> Result<usize, FrameParseError> {
+        let frames = Frame::parse_frames(data)?;
+        let decoded = self.batches.len();
+        for frame in frames {
+            let (id, number, size) = (frame.id, frame.number, frame.data.len());
+            let traced = self.channels.entry(id).or_insert_with(|| TracedChannel {
+                channel: Channel::new(id, tx.block),
+                frames: Vec::new(),
+            });
+            if traced.channel.add_frame(frame, tx.block).is_err() {
+                continue;
+            }
+            traced.frames.push(FrameRef { number, size, tx });
+
+            if traced.channel.is_ready() {
+                if let Some(traced) = self.channels.remove(&id) {
+                    self.decode_channel(traced, tx.block.timestamp);
+                }
+            }
+        }
+        Ok(self.batches.len() - decoded)
+    }
+
+    /// Adds the calldata batcher transactions in the given L1 block, filtering them the same way
+    /// as the [CalldataSource], and returns the number of batches decoded.
+    ///
+    /// [CalldataSource]: crate::sources::CalldataSource
+    pub fn add_calldata_block(
+        &mut self,
+        block: BlockInfo,
+        txs: &[TxEnvelope],
+        batch_inbox_address: Address,
+        signer: Address,
+    ) -> usize {
+        let mut decoded = 0;
+        for (index, tx) in txs.iter().enumerate() {
+            if !matches!(
+                tx,
+                TxEnvelope::Legacy(_) | TxEnvelope::Eip2930(_) | TxEnvelope::Eip1559(_)
+            ) {
+                continue;
+            }
+            if tx.to() != Some(batch_inbox_address) || tx.recover_signer().ok() != Some(signer) {
+                continue;
+            }
+            let tx_ref = L1TxRef { block, hash: *tx.tx_hash(), index: index as u64 };
+            decoded += self.add_transaction(tx_ref, tx.input()).unwrap_or_default();
+        }
+        decoded
+    }
+
+    /// Returns the batches decoded so far, in decoding order.
+    pub fn batches(&self) -> &[TracedBatch] {
+        &self.batches
+    }
+
+    /// Returns the first decoded batch containing the L2 block with the given timestamp.
+    pub fn find_by_timestamp(&self, timestamp: u64) -> Option<&TracedBatch> {
+        self.batches.iter().find(|b| b.contains_timestamp(timestamp))
+    }
+
+    /// Returns the first decoded batch containing the L2 block with the given number.
+    pub fn find_by_l2_block(&self, number: u64) -> Option<&TracedBatch> {
+        let blocks = number.checked_sub(self.cfg.genesis.l2.number)?;
+        let timestamp = self.cfg.genesis.l2_time.checked_add(blocks * self.cfg.block_time)?;
+        self.find_by_timestamp(timestamp)
+    }
+
+    /// Decodes the batches of a ready channel.
+    fn decode_channel(&mut self, mut traced: TracedChannel, timestamp: u64) {
+        let Some(data) = traced.channel.frame_data() else {
+            return;
+        };
+        let max_rlp_bytes_per_channel =
+            ProtocolConstants::for_timestamp(&self.cfg, timestamp).max_channel_size;
+
+        traced.frames.sort_by_key(|f| f.number);
+        let provenance = Arc::new(BatchProvenance {
+            channel_id: traced.channel.id().into(),
+            frames: traced.frames,
+        });
+        let mut reader = BatchReader::new(&data[..], max_rlp_bytes_per_channel);
+        while let Some(batch) = reader.next_batch(&self.cfg) {
+            self.batches.push(TracedBatch { batch, provenance: provenance.clone() });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{encode_frames, new_compressed_batch_data, tx_ref};
+
+    #[test]
+    fn test_batch_tracer_multi_tx_channel() {
+        let data = new_compressed_batch_data();
+        let (first, second) = data.split_at(data.len() / 2);
+        let id = [0xAA; 16];
+
+        let mut tracer = BatchTracer::new(Arc::new(RollupConfig::default()));
+        let frames = [Frame::new(id, 1, second.to_vec(), true)];
+        assert_eq!(tracer.add_transaction(tx_ref(2, 0), &encode_frames(&frames)).unwrap(), 0);
+        let frames = [Frame::new(id, 0, first.to_vec(), false)];
+        assert_eq!(tracer.add_transaction(tx_ref(3, 5), &encode_frames(&frames)).unwrap(), 1);
+
+        let traced = &tracer.batches()[0];
+        assert_eq!(traced.provenance.channel_id, id);
+        assert_eq!(
+            traced.provenance.frames,
+            [
+                FrameRef { number: 0, size: first.len(), tx: tx_ref(3, 5) },
+                FrameRef { number: 1, size: second.len(), tx: tx_ref(2, 0) }
+            ]
+        );
+        assert_eq!(traced.provenance.l1_txs(), [tx_ref(2, 0), tx_ref(3, 5)]);
+        assert_eq!(tracer.find_by_timestamp(traced.batch.timestamp()), Some(traced));
+        assert!(tracer.find_by_timestamp(traced.batch.timestamp() + 1).is_none());
+    }
+
+    #[test]
+    fn test_batch_tracer_find_by_l2_block() {
+        let data = new_compressed_batch_data();
+        let frames = [Frame::new([0xBB; 16], 0, data, true)];
+        let mut tracer = BatchTracer::new(Arc::new(RollupConfig::default()));
+        assert_eq!(tracer.add_transaction(tx_ref(1, 0), &encode_frames(&frames)).unwrap(), 1);
+
+        let timestamp = tracer.batches()[0].batch.timestamp();
+        let cfg = RollupConfig { block_time: 2, ..Default::default() };
+        tracer.cfg = Arc::new(RollupConfig {
+            genesis: op_alloy_genesis::ChainGenesis {
+                l2_time: timestamp - 2 * cfg.block_time,
+                ..Default::default()
+            },
+            ..cfg
+        });
+        assert!(tracer.find_by_l2_block(2).is_some());
+        assert!(tracer.find_by_l2_block(3).is_none());
+    }
+
+    #[test]
+    fn test_batch_tracer_invalid_data() {
+        let mut tracer = BatchTracer::new(Arc::new(RollupConfig::default()));
+        assert_eq!(
+            tracer.add_transaction(tx_ref(1, 0), &[0x01]).unwrap_err(),
+            FrameParseError::UnsupportedVersion
+        );
+        assert_eq!(
+            tracer.add_calldata_block(BlockInfo::default(), &[], Address::ZERO, Address::ZERO),
+            0
+        );
+    }
+}
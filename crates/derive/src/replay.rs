@@ -0,0 +1,184 @@
+//! Contains the [ChannelReplay], which deterministically replays a single channel from raw frames
+//! for forensic analysis of batcher transactions.
+
+use crate::{
+    constants::ProtocolConstants,
+    errors::ChannelReplayError,
+    stages::{decode_batch, decompress_channel},
+};
+use alloc::vec::Vec;
+use alloy_primitives::Bytes;
+use alloy_rlp::Decodable;
+use maili_protocol::{Batch, BlockInfo, Channel, Frame};
+use op_alloy_genesis::RollupConfig;
+
+/// A channel assembled from raw frames, outside of the derivation pipeline.
+///
+/// Unlike the channel stages, the replay does not apply channel timeouts or channel bank limits,
+/// and reports why a frame or batch was rejected instead of dropping it, so that the channel of a
+/// specific batcher transaction can be inspected.
+#[derive(Debug, Clone)]
+pub struct ChannelReplay {
+    /// The assembled channel.
+    channel: Channel,
+}
+
+impl ChannelReplay {
+    /// Assembles a channel from raw encoded frames, e.g. extracted from L1 transactions by an
+    /// external tool.
+    pub fn from_frames<I, T>(frames: I) -> Result<Self, ChannelReplayError>
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<[u8]>,
+    {
+        let frames = frames
+            .into_iter()
+            .enumerate()
+            .map(|(index, raw)| {
+                Frame::decode(raw.as_ref())
+                    .map(|(_, frame)| frame)
+                    .map_err(|error| ChannelReplayError::FrameDecoding { index, error })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::from_decoded_frames(frames)
+    }
+
+    /// Assembles a channel from batcher transaction payloads, the derivation version byte
+    /// followed by the frames, as carried by calldata or decoded from blobs.
+    pub fn from_tx_data<I, T>(payloads: I) -> Result<Self, ChannelReplayError>
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<[u8]>,
+    {
+        let mut frames = Vec::new();
+        for (index, payload) in payloads.into_iter().enumerate() {
+            let parsed = Frame::parse_frames(payload.as_ref())
+                .map_err(|error| ChannelReplayError::FrameParse { index, error })?;
+            frames.extend(parsed);
+        }
+        Self::from_decoded_frames(frames)
+    }
+
+    /// Assembles a channel from decoded frames, which must all belong to the same channel.
+    pub fn from_decoded_frames(frames: Vec<Frame>) -> Result<Self, ChannelReplayError> {
+        let id = frames.first().ok_or(ChannelReplayError::NoFrames)?.id;
+        let mut channel = Channel::new(id, BlockInfo::default());
+        for frame in frames {
+            let (id, number) = (frame.id.into(), frame.number);
+            channel
+                .add_frame(frame, BlockInfo::default())
+                .map_err(|error| ChannelReplayError::AddFrame { id, number, error })?;
+        }
+        if !channel.is_ready() {
+            return Err(ChannelReplayError::Incomplete(id.into()));
+        }
+        Ok(Self { channel })
+    }
+
+    /// Returns the assembled channel.
+    pub const fn channel(&self) -> &Channel {
+        &self.channel
+    }
+
+    /// Decompresses the channel and decodes all of its batches.
+    ///
+    /// As the L1 inclusion block of the channel is not known, the channel is decompressed within
+    /// the maximum channel size of the latest hardfork scheduled in the rollup config.
+    pub fn decode_batches(&self, cfg: &RollupConfig) -> Result<Vec<Batch>, ChannelReplayError> {
+        let max = ProtocolConstants::for_timestamp(cfg, u64::MAX).max_channel_size;
+        let data = self.channel.frame_data().unwrap_or_default();
+        let (decompressed, brotli) =
+            decompress_channel(&data, max).ok_or(ChannelReplayError::Decompression(max))?;
+
+        let mut batches = Vec::new();
+        let mut buf = decompressed.as_slice();
+        while !buf.is_empty() {
+            let index = batches.len();
+            let encoded = Bytes::decode(&mut buf)
+                .map_err(|error| ChannelReplayError::BatchRlp { index, error })?;
+            let batch = decode_batch(&encoded, cfg)
+                .map_err(|error| ChannelReplayError::BatchDecoding { index, error })?;
+            if brotli && !cfg.is_fjord_active(batch.timestamp()) {
+                return Err(ChannelReplayError::BrotliBeforeFjord(index));
+            }
+            batches.push(batch);
+        }
+        Ok(batches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_utils::new_compressed_batch_data, types::ChannelId};
+    use alloc::vec;
+    use maili_protocol::{ChannelError, DERIVATION_VERSION_0};
+
+    #[test]
+    fn test_channel_replay_decode_batches() {
+        let data = new_compressed_batch_data();
+        let (first, second) = data.split_at(data.len() / 2);
+        let frames = [
+            Frame::new([0xAA; 16], 1, second.to_vec(), true).encode(),
+            Frame::new([0xAA; 16], 0, first.to_vec(), false).encode(),
+        ];
+        let replay = ChannelReplay::from_frames(&frames).unwrap();
+        assert_eq!(replay.channel().len(), 2);
+
+        let batches = replay.decode_batches(&RollupConfig::default()).unwrap();
+        assert_eq!(batches.len(), 1);
+        assert!(matches!(batches[0], Batch::Span(_)));
+    }
+
+    #[test]
+    fn test_channel_replay_from_tx_data() {
+        let frame = Frame::new([0xBB; 16], 0, new_compressed_batch_data(), true);
+        let mut payload = vec![DERIVATION_VERSION_0];
+        payload.extend(frame.encode());
+        let replay = ChannelReplay::from_tx_data([payload]).unwrap();
+        assert_eq!(replay.decode_batches(&RollupConfig::default()).unwrap().len(), 1);
+
+        assert!(matches!(
+            ChannelReplay::from_tx_data([[0x01, 0x02]]),
+            Err(ChannelReplayError::FrameParse { index: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_channel_replay_errors() {
+        assert_eq!(
+            ChannelReplay::from_frames(Vec::<Vec<u8>>::new()).unwrap_err(),
+            ChannelReplayError::NoFrames
+        );
+        assert!(matches!(
+            ChannelReplay::from_frames([[0x00; 4]]),
+            Err(ChannelReplayError::FrameDecoding { index: 0, .. })
+        ));
+
+        let incomplete = [Frame::new([0xAA; 16], 0, vec![0x01], false).encode()];
+        assert_eq!(
+            ChannelReplay::from_frames(&incomplete).unwrap_err(),
+            ChannelReplayError::Incomplete(ChannelId([0xAA; 16]))
+        );
+
+        let mismatched = [
+            Frame::new([0xAA; 16], 0, vec![0x01], false).encode(),
+            Frame::new([0xBB; 16], 1, vec![0x02], true).encode(),
+        ];
+        assert_eq!(
+            ChannelReplay::from_frames(&mismatched).unwrap_err(),
+            ChannelReplayError::AddFrame {
+                id: ChannelId([0xBB; 16]),
+                number: 1,
+                error: ChannelError::FrameIdMismatch
+            }
+        );
+
+        let garbage = [Frame::new([0xAA; 16], 0, vec![0x78, 0x01, 0x02], true).encode()];
+        let replay = ChannelReplay::from_frames(&garbage).unwrap();
+        assert!(matches!(
+            replay.decode_batches(&RollupConfig::default()),
+            Err(ChannelReplayError::Decompression(_))
+        ));
+    }
+}
@@ -0,0 +1,413 @@
+//! Contains the alt-DA [Commitment] types, and the [AltDADataSource], which dispatches the
+//! commitments posted by the batcher to the [AltDAProvider] registered for their type.
+
+use crate::{
+    errors::{AltDAProviderError, CommitmentError, PipelineError, PipelineErrorKind},
+    sources::{CalldataSource, ChallengeStatus, DAChallengeConfig, DAChallengeWatcher},
+    traits::{AltDAProvider, ChainProvider, DataAvailabilityProvider},
+    types::PipelineResult,
+};
+use alloc::{boxed::Box, collections::BTreeMap, vec::Vec};
+use alloy_primitives::{Address, Bytes, B256};
+use async_trait::async_trait;
+use core::fmt::Debug;
+use maili_protocol::BlockInfo;
+use op_alloy_genesis::RollupConfig;
+
+/// The version byte of batcher data carrying an alt-DA commitment, in place of the
+/// [DERIVATION_VERSION_0] frame data.
+///
+/// [DERIVATION_VERSION_0]: maili_protocol::DERIVATION_VERSION_0
+pub const ALT_DA_DERIVATION_VERSION: u8 = 0x01;
+
+/// The type byte of a [Commitment::Keccak256].
+pub const KECCAK256_COMMITMENT_TYPE: u8 = 0x00;
+
+/// The type byte of a [Commitment::Generic], whose format is defined by its DA layer.
+pub const GENERIC_COMMITMENT_TYPE: u8 = 0x01;
+
+/// An alt-DA commitment, as posted to the batch inbox after the [ALT_DA_DERIVATION_VERSION] byte.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Commitment {
+    /// The keccak256 hash of the input, whose preimage is served by a DA server.
+    Keccak256(B256),
+    /// A commitment to the DA layer with the given DA layer byte, with a payload in a format
+    /// defined by the DA layer.
+    Generic(u8, Bytes),
+}
+
+impl Commitment {
+    /// Returns the type byte of the commitment.
+    pub const fn commitment_type(&self) -> u8 {
+        match self {
+            Self::Keccak256(_) => KECCAK256_COMMITMENT_TYPE,
+            Self::Generic(..) => GENERIC_COMMITMENT_TYPE,
+        }
+    }
+
+    /// Decodes a commitment, starting with its type byte.
+    pub fn decode(data: &[u8]) -> Result<Self, CommitmentError> {
+        let (&commitment_type, data) = data.split_first().ok_or(CommitmentError::Empty)?;
+        match commitment_type {
+            KECCAK256_COMMITMENT_TYPE => B256::try_from(data)
+                .map(Self::Keccak256)
+                .map_err(|_| CommitmentError::InvalidLength(data.len())),
+            GENERIC_COMMITMENT_TYPE => {
+                let (&da_layer, payload) =
+                    data.split_first().ok_or(CommitmentError::MissingDALayer)?;
+                Ok(Self::Generic(da_layer, Bytes::copy_from_slice(payload)))
+            }
+            _ => Err(CommitmentError::UnknownType(commitment_type)),
+        }
+    }
+
+    /// Encodes the commitment, starting with its type byte.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut data = Vec::from([self.commitment_type()]);
+        match self {
+            Self::Keccak256(hash) => data.extend_from_slice(hash.as_slice()),
+            Self::Generic(da_layer, payload) => {
+                data.push(*da_layer);
+                data.extend_from_slice(payload);
+            }
+        }
+        data
+    }
+
+    /// Decodes a commitment from batcher data, starting with the [ALT_DA_DERIVATION_VERSION].
+    pub fn decode_batcher_data(data: &[u8]) -> Result<Self, CommitmentError> {
+        match data.split_first() {
+            Some((&ALT_DA_DERIVATION_VERSION, commitment)) => Self::decode(commitment),
+            _ => Err(CommitmentError::NotAltDA),
+        }
+    }
+
+    /// Encodes the commitment as batcher data, starting with the [ALT_DA_DERIVATION_VERSION].
+    pub fn to_batcher_data(&self) -> Vec<u8> {
+        [&[ALT_DA_DERIVATION_VERSION][..], &self.encode()].concat()
+    }
+}
+
+/// The [AltDAProvider]s of an [AltDADataSource], keyed by the commitments they serve.
+#[derive(Debug, Default)]
+pub struct AltDARegistry {
+    /// The provider of [Commitment::Keccak256] inputs.
+    keccak256: Option<Box<dyn AltDAProvider>>,
+    /// The providers of [Commitment::Generic] inputs, keyed by DA layer byte.
+    generic: BTreeMap<u8, Box<dyn AltDAProvider>>,
+}
+
+impl AltDARegistry {
+    /// Creates a new, empty [AltDARegistry].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the provider of [Commitment::Keccak256] inputs.
+    pub fn with_keccak256(mut self, provider: impl AltDAProvider + 'static) -> Self {
+        self.keccak256 = Some(Box::new(provider));
+        self
+    }
+
+    /// Registers the provider of [Commitment::Generic] inputs to the given DA layer.
+    pub fn with_generic(mut self, da_layer: u8, provider: impl AltDAProvider + 'static) -> Self {
+        self.generic.insert(da_layer, Box::new(provider));
+        self
+    }
+
+    /// Returns the provider registered for the given commitment, if any.
+    pub fn provider(&mut self, commitment: &Commitment) -> Option<&mut dyn AltDAProvider> {
+        let provider = match commitment {
+            Commitment::Keccak256(_) => self.keccak256.as_mut(),
+            Commitment::Generic(da_layer, _) => self.generic.get_mut(da_layer),
+        }?;
+        Some(provider.as_mut())
+    }
+}
+
+/// A [DataAvailabilityProvider] for chains using one or more alt-DA layers.
+///
+/// Batcher transactions are read from calldata. Data with the [ALT_DA_DERIVATION_VERSION] is
+/// decoded as a [Commitment], and the input is fetched from the [AltDAProvider] registered for it
+/// in the [AltDARegistry]. Plain [DERIVATION_VERSION_0] data is passed through, as the batcher
+/// falls back to posting frames to L1 when its DA layer is unavailable.
+///
+/// Following the alt-DA derivation rules, malformed commitments, commitments without a
+/// registered provider, and commitments rejected as invalid by their provider are skipped. If the
+/// input is not available, the commitment is kept and derivation waits for it, retrying the fetch
+/// on the next step.
+///
+/// With a [DAChallengeWatcher], [Commitment::Keccak256]s are subject to the challenges on the L1
+/// challenge contract. Commitments whose challenge expired are skipped, and the input of resolved
+/// challenges is read from L1. While an input is unavailable, the watcher looks ahead of the L1
+/// origin for a challenge, and derivation fails critically if the challenge window passes without
+/// one.
+///
+/// [DERIVATION_VERSION_0]: maili_protocol::DERIVATION_VERSION_0
+#[derive(Debug)]
+pub struct AltDADataSource<C>
+where
+    C: ChainProvider + Send,
+{
+    /// The calldata source the commitments are read from.
+    pub calldata_source: CalldataSource<C>,
+    /// The registered alt-DA providers.
+    pub registry: AltDARegistry,
+    /// The watcher of the challenge contract, if commitments can be challenged.
+    pub challenge_watcher: Option<DAChallengeWatcher>,
+}
+
+impl<C> AltDADataSource<C>
+where
+    C: ChainProvider + Send,
+{
+    /// Creates a new [AltDADataSource] for the batch inbox of the given [RollupConfig].
+    pub fn new(chain_provider: C, registry: AltDARegistry, cfg: &RollupConfig) -> Self {
+        Self {
+            calldata_source: CalldataSource::new_from_config(chain_provider, cfg)
+                .with_version(ALT_DA_DERIVATION_VERSION),
+            registry,
+            challenge_watcher: None,
+        }
+    }
+
+    /// Follows the challenges on the given challenge contract.
+    pub fn with_challenge_watcher(mut self, config: DAChallengeConfig) -> Self {
+        self.challenge_watcher = Some(DAChallengeWatcher::new(config));
+        self
+    }
+}
+
+#[async_trait]
+impl<C> DataAvailabilityProvider for AltDADataSource<C>
+where
+    C: ChainProvider + Send + Sync + Debug,
+{
+    type Item = Bytes;
+
+    async fn next(
+        &mut self,
+        block_ref: &BlockInfo,
+        batcher_address: Address,
+    ) -> PipelineResult<Self::Item> {
+        if let Some(watcher) = self.challenge_watcher.as_mut() {
+            watcher.advance_to(&mut self.calldata_source.chain_provider, block_ref.number).await?;
+        }
+
+        loop {
+            let data = self.calldata_source.next(block_ref, batcher_address).await?;
+            if data.first() != Some(&ALT_DA_DERIVATION_VERSION) {
+                return Ok(data);
+            }
+
+            let commitment = match Commitment::decode_batcher_data(&data) {
+                Ok(commitment) => commitment,
+                Err(e) => {
+                    warn!(target: "alt-da-source", "Skipping malformed alt-DA commitment: {e}");
+                    continue;
+                }
+            };
+
+            // Only keccak256 commitments can be challenged.
+            let mut watcher = self
+                .challenge_watcher
+                .as_mut()
+                .filter(|_| matches!(commitment, Commitment::Keccak256(_)));
+            if let Some(watcher) = watcher.as_deref_mut() {
+                match watcher.status(block_ref.number, &commitment) {
+                    ChallengeStatus::Expired => {
+                        warn!(
+                            target: "alt-da-source",
+                            "Skipping alt-DA commitment with an expired challenge"
+                        );
+                        continue;
+                    }
+                    ChallengeStatus::Resolved => {
+                        if let Some(input) = watcher.resolved_input(block_ref.number, &commitment) {
+                            let input = input.clone();
+                            watcher.mark_used(block_ref.number, commitment);
+                            return Ok(input);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            let Some(provider) = self.registry.provider(&commitment) else {
+                warn!(
+                    target: "alt-da-source",
+                    "Skipping alt-DA commitment of type {} without a provider",
+                    commitment.commitment_type()
+                );
+                continue;
+            };
+            match provider.get_input(&commitment).await {
+                Ok(input) => {
+                    if let Some(watcher) = watcher {
+                        watcher.mark_used(block_ref.number, commitment);
+                    }
+                    return Ok(input);
+                }
+                Err(e @ AltDAProviderError::InvalidCommitment(_)) => {
+                    warn!(target: "alt-da-source", "Skipping alt-DA commitment: {e}");
+                }
+                Err(e) => {
+                    warn!(target: "alt-da-source", "Failed to fetch alt-DA input: {e}");
+                    self.calldata_source.calldata.push_front(data);
+                    if let Some(watcher) = watcher {
+                        if watcher.status(block_ref.number, &commitment) ==
+                            ChallengeStatus::Uninitialized &&
+                            watcher.challenge_window_closed(block_ref.number)
+                        {
+                            return Err(PipelineError::MissingAltDAInput.crit());
+                        }
+
+                        // Look ahead for a challenge that makes the input available on L1, or
+                        // expires the commitment.
+                        let next = watcher.origin().map_or(block_ref.number, |origin| origin + 1);
+                        match watcher
+                            .advance_to(&mut self.calldata_source.chain_provider, next)
+                            .await
+                        {
+                            Err(e @ PipelineErrorKind::Reset(_)) => return Err(e),
+                            Err(e) => {
+                                debug!(
+                                    target: "alt-da-source",
+                                    "Failed to look ahead for challenges: {e}"
+                                );
+                            }
+                            Ok(()) => {}
+                        }
+                    }
+                    return Err(e.into());
+                }
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.calldata_source.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        errors::PipelineErrorKind,
+        test_utils::{batcher_tx, TestChainProvider, SIMULATED_BATCHER},
+    };
+    use alloc::{string::ToString, vec};
+    use alloy_consensus::TxEnvelope;
+    use alloy_primitives::keccak256;
+    use maili_protocol::DERIVATION_VERSION_0;
+
+    /// Serves the inputs of known commitments, failing with the scripted error first if there is
+    /// one.
+    #[derive(Debug, Clone, Default)]
+    struct TestAltDAProvider {
+        inputs: Vec<(Commitment, Bytes)>,
+        error: Option<AltDAProviderError>,
+    }
+
+    #[async_trait]
+    impl AltDAProvider for TestAltDAProvider {
+        async fn get_input(
+            &mut self,
+            commitment: &Commitment,
+        ) -> Result<Bytes, AltDAProviderError> {
+            if let Some(error) = self.error.take() {
+                return Err(error);
+            }
+            self.inputs
+                .iter()
+                .find(|(c, _)| c == commitment)
+                .map(|(_, input)| input.clone())
+                .ok_or_else(|| AltDAProviderError::InvalidCommitment("unknown".to_string()))
+        }
+    }
+
+    fn source(txs: Vec<TxEnvelope>, registry: AltDARegistry) -> AltDADataSource<TestChainProvider> {
+        let mut chain_provider = TestChainProvider::default();
+        chain_provider.insert_block_with_transactions(0, BlockInfo::default(), txs);
+        AltDADataSource::new(chain_provider, registry, &RollupConfig::default())
+    }
+
+    #[test]
+    fn test_commitment_roundtrip() {
+        let keccak = Commitment::Keccak256(keccak256([0xDD]));
+        let encoded = keccak.to_batcher_data();
+        assert_eq!(encoded[..2], [ALT_DA_DERIVATION_VERSION, KECCAK256_COMMITMENT_TYPE]);
+        assert_eq!(encoded.len(), 34);
+        assert_eq!(Commitment::decode_batcher_data(&encoded), Ok(keccak));
+
+        let generic = Commitment::Generic(0x0c, Bytes::from(vec![0xAA; 8]));
+        assert_eq!(generic.encode()[..2], [GENERIC_COMMITMENT_TYPE, 0x0c]);
+        assert_eq!(Commitment::decode(&generic.encode()), Ok(generic));
+    }
+
+    #[test]
+    fn test_commitment_decode_errors() {
+        assert_eq!(Commitment::decode_batcher_data(&[0x00, 0x00]), Err(CommitmentError::NotAltDA));
+        assert_eq!(Commitment::decode(&[]), Err(CommitmentError::Empty));
+        assert_eq!(Commitment::decode(&[0x02]), Err(CommitmentError::UnknownType(0x02)));
+        assert_eq!(Commitment::decode(&[0x00, 0xAA]), Err(CommitmentError::InvalidLength(1)));
+        assert_eq!(Commitment::decode(&[0x01]), Err(CommitmentError::MissingDALayer));
+    }
+
+    #[tokio::test]
+    async fn test_alt_da_data_source_dispatch() {
+        let keccak = Commitment::Keccak256(keccak256([0xAA]));
+        let generic = Commitment::Generic(0x0c, Bytes::from(vec![0xBB; 8]));
+        let unregistered = Commitment::Generic(0x0d, Bytes::from(vec![0xCC; 8]));
+        let frames = |byte| Bytes::from(vec![DERIVATION_VERSION_0, byte]);
+        let txs = vec![
+            batcher_tx(vec![ALT_DA_DERIVATION_VERSION, 0x02]),
+            batcher_tx(unregistered.to_batcher_data()),
+            batcher_tx(keccak.to_batcher_data()),
+            batcher_tx(generic.to_batcher_data()),
+            batcher_tx(frames(0xDD).to_vec()),
+        ];
+        let signer = SIMULATED_BATCHER;
+        let registry = AltDARegistry::new()
+            .with_keccak256(TestAltDAProvider {
+                inputs: vec![(keccak, frames(0xAA))],
+                ..Default::default()
+            })
+            .with_generic(
+                0x0c,
+                TestAltDAProvider { inputs: vec![(generic, frames(0xBB))], ..Default::default() },
+            );
+        let mut source = source(txs, registry);
+
+        // The malformed and unregistered commitments are skipped, and each input is served by the
+        // provider registered for its commitment.
+        let block_ref = BlockInfo::default();
+        assert_eq!(source.next(&block_ref, signer).await.unwrap(), frames(0xAA));
+        assert_eq!(source.next(&block_ref, signer).await.unwrap(), frames(0xBB));
+        assert_eq!(source.next(&block_ref, signer).await.unwrap(), frames(0xDD));
+        assert!(source.next(&block_ref, signer).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_alt_da_data_source_unavailable_input() {
+        let keccak = Commitment::Keccak256(keccak256([0xAA]));
+        let tx = batcher_tx(keccak.to_batcher_data());
+        let signer = SIMULATED_BATCHER;
+        let input = Bytes::from(vec![DERIVATION_VERSION_0, 0xAA]);
+        let registry = AltDARegistry::new().with_keccak256(TestAltDAProvider {
+            inputs: vec![(keccak, input.clone())],
+            error: Some(AltDAProviderError::Unavailable("not found".to_string())),
+        });
+        let mut source = source(vec![tx], registry);
+
+        let block_ref = BlockInfo::default();
+        assert!(matches!(
+            source.next(&block_ref, signer).await,
+            Err(PipelineErrorKind::Temporary(_))
+        ));
+
+        // The commitment is retried once the input is available.
+        assert_eq!(source.next(&block_ref, signer).await.unwrap(), input);
+    }
+}
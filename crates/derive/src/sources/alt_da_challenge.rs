@@ -0,0 +1,557 @@
+//! Contains the [DAChallengeWatcher], which follows the challenges of alt-DA commitments on the L1
+//! `DataAvailabilityChallenge` contract.
+//!
+//! Within the challenge window after the L1 block a [Commitment::Keccak256] was included in,
+//! anyone can challenge the availability of its input. The challenge is resolved by posting the
+//! input to L1 within the resolve window, and expires otherwise. The contract emits a
+//! `ChallengeStatusChanged` event on every transition of a challenge:
+//!
+//! ```text
+//! event ChallengeStatusChanged(
+//!    uint256 indexed challengedBlockNumber,
+//!    bytes challengedCommitment,
+//!    ChallengeStatus status
+//! );
+//! ```
+//!
+//! Derivation skips commitments whose challenge expired, and reads the input of resolved
+//! challenges from the `resolve` transaction. If a commitment that was already derived from
+//! expires later on, the L2 chain derived from it is invalid, and the pipeline is reset.
+
+use crate::{
+    errors::ResetError, sources::Commitment, traits::ChainProvider, types::PipelineResult,
+};
+use alloc::{collections::BTreeMap, vec::Vec};
+use alloy_consensus::{Receipt, Transaction, TxEnvelope};
+use alloy_primitives::{b256, keccak256, Address, Bytes, Log, B256};
+
+/// The topic of the `ChallengeStatusChanged(uint256,bytes,uint8)` event.
+pub const CHALLENGE_STATUS_CHANGED_TOPIC: B256 =
+    b256!("c5d8c630ba2fdacb1db24c4599df78c7fb8cf97b5aecde34939597f6697bb1ad");
+
+/// The selector of the `resolve(uint256,bytes,bytes)` function of the challenge contract.
+const RESOLVE_SELECTOR: [u8; 4] = [0x7a, 0xe9, 0x29, 0xd9];
+
+/// The status of a challenge, as defined by the `ChallengeStatus` enum of the challenge contract.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ChallengeStatus {
+    /// The commitment was not challenged.
+    #[default]
+    Uninitialized,
+    /// The commitment was challenged, and the challenge awaits resolution.
+    Active,
+    /// The input of the commitment was posted to L1.
+    Resolved,
+    /// The challenge was not resolved within the resolve window.
+    Expired,
+}
+
+impl ChallengeStatus {
+    /// Returns the [ChallengeStatus] with the given ABI value, if it is known.
+    pub const fn from_u8(status: u8) -> Option<Self> {
+        match status {
+            0 => Some(Self::Uninitialized),
+            1 => Some(Self::Active),
+            2 => Some(Self::Resolved),
+            3 => Some(Self::Expired),
+            _ => None,
+        }
+    }
+}
+
+/// A decoded `ChallengeStatusChanged` event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChallengeStatusChanged {
+    /// The L1 block number the challenged commitment was included in.
+    pub challenged_block_number: u64,
+    /// The challenged commitment.
+    pub challenged_commitment: Commitment,
+    /// The new status of the challenge.
+    pub status: ChallengeStatus,
+}
+
+impl ChallengeStatusChanged {
+    /// Decodes the event from a log, returning [None] if the log is not a well-formed
+    /// `ChallengeStatusChanged` event.
+    pub fn decode_log(log: &Log) -> Option<Self> {
+        let [topic, block_number] = log.topics() else {
+            return None;
+        };
+        if *topic != CHALLENGE_STATUS_CHANGED_TOPIC {
+            return None;
+        }
+
+        let data = &log.data.data;
+        let offset = decode_usize_word(data.get(..32)?)?;
+        let status = u8::try_from(decode_u64_word(data.get(32..64)?)?).ok()?;
+        Some(Self {
+            challenged_block_number: decode_u64_word(block_number.as_slice())?,
+            challenged_commitment: Commitment::decode(decode_bytes(data, offset)?).ok()?,
+            status: ChallengeStatus::from_u8(status)?,
+        })
+    }
+}
+
+/// The configuration of the L1 `DataAvailabilityChallenge` contract.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DAChallengeConfig {
+    /// The address of the challenge contract.
+    pub address: Address,
+    /// The number of L1 blocks after the inclusion of a commitment in which it can be challenged.
+    pub challenge_window: u64,
+    /// The number of L1 blocks after a challenge in which it can be resolved.
+    pub resolve_window: u64,
+}
+
+/// The state of a commitment tracked by the [DAChallengeWatcher].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct TrackedCommitment {
+    /// The status of the challenge of the commitment.
+    status: ChallengeStatus,
+    /// The L1 block number the commitment was challenged in, if it was challenged.
+    challenged_at: u64,
+    /// The input posted to L1 to resolve the challenge, if it could be decoded.
+    input: Option<Bytes>,
+    /// Whether the input of the commitment was derived from.
+    used: bool,
+}
+
+/// Follows the `ChallengeStatusChanged` events of the challenge contract, and tracks the
+/// challenge state machine of each challenged commitment.
+///
+/// The watcher processes every L1 block in order. When it is first advanced, the blocks of the
+/// preceding challenge and resolve windows are backfilled, so that challenges that are still
+/// pending at the first block are tracked. It is advanced to the L1 origin of the pipeline as
+/// commitments are read, and may look ahead of it while derivation waits for the input of a
+/// commitment that may be challenged. State is kept for a commitment until its challenge and
+/// resolve windows have passed.
+#[derive(Debug, Clone)]
+pub struct DAChallengeWatcher {
+    /// The configuration of the challenge contract.
+    pub config: DAChallengeConfig,
+    /// The number of the last processed L1 block.
+    origin: Option<u64>,
+    /// The tracked commitments, keyed by the L1 block number they were included in.
+    commitments: BTreeMap<(u64, Commitment), TrackedCommitment>,
+}
+
+impl DAChallengeWatcher {
+    /// Creates a new [DAChallengeWatcher] for the given challenge contract.
+    pub const fn new(config: DAChallengeConfig) -> Self {
+        Self { config, origin: None, commitments: BTreeMap::new() }
+    }
+
+    /// Returns the number of the last processed L1 block.
+    pub const fn origin(&self) -> Option<u64> {
+        self.origin
+    }
+
+    /// Returns the [ChallengeStatus] of the commitment included in the given L1 block.
+    pub fn status(&self, included_at: u64, commitment: &Commitment) -> ChallengeStatus {
+        self.commitments
+            .get(&(included_at, commitment.clone()))
+            .map_or(ChallengeStatus::Uninitialized, |tracked| tracked.status)
+    }
+
+    /// Returns the input posted to L1 to resolve the challenge of the commitment included in the
+    /// given L1 block, if it was resolved.
+    pub fn resolved_input(&self, included_at: u64, commitment: &Commitment) -> Option<&Bytes> {
+        self.commitments.get(&(included_at, commitment.clone()))?.input.as_ref()
+    }
+
+    /// Marks the input of the commitment included in the given L1 block as derived from, so that
+    /// its expiry resets the pipeline.
+    pub fn mark_used(&mut self, included_at: u64, commitment: Commitment) {
+        self.commitments.entry((included_at, commitment)).or_default().used = true;
+    }
+
+    /// Returns `true` if the challenge window of commitments included in the given L1 block has
+    /// passed at the origin of the watcher.
+    pub fn challenge_window_closed(&self, included_at: u64) -> bool {
+        self.origin
+            .is_some_and(|origin| origin > included_at.saturating_add(self.config.challenge_window))
+    }
+
+    /// Processes the L1 blocks after the origin of the watcher, up to and including the block
+    /// with the given number. If the watcher has no origin yet, the processing starts one
+    /// challenge and resolve window before the block.
+    ///
+    /// Returns [ResetError::AltDAChallengeExpired] if the challenge of a commitment that was
+    /// derived from expired.
+    pub async fn advance_to<C>(&mut self, provider: &mut C, number: u64) -> PipelineResult<()>
+    where
+        C: ChainProvider + Send,
+    {
+        let backfill = self.config.challenge_window.saturating_add(self.config.resolve_window);
+        let start = self.origin.map_or(number.saturating_sub(backfill), |origin| origin + 1);
+        for number in start..=number {
+            let block = provider.block_info_by_number(number).await.map_err(Into::into)?;
+            let receipts = provider
+                .filtered_receipts_by_hash(block.hash, self.config.address)
+                .await
+                .map_err(Into::into)?;
+
+            // The transactions are only needed to read the input of resolved challenges.
+            let resolved = receipts.iter().flat_map(|r| r.logs.iter()).any(|log| {
+                ChallengeStatusChanged::decode_log(log)
+                    .is_some_and(|event| event.status == ChallengeStatus::Resolved)
+            });
+            let txs = if resolved {
+                provider
+                    .block_info_and_transactions_by_hash(block.hash)
+                    .await
+                    .map_err(Into::into)?
+                    .1
+            } else {
+                Vec::new()
+            };
+
+            self.process_block(number, &receipts, &txs).map_err(ResetError::reset)?;
+        }
+        Ok(())
+    }
+
+    /// Applies the challenge events in the receipts of the L1 block with the given number, and
+    /// expires the challenges whose resolve window passed.
+    ///
+    /// The receipts hold one receipt per transaction, in block order, as returned by
+    /// [ChainProvider::filtered_receipts_by_hash], so the input resolving a challenge is read from
+    /// the transaction at the index of the receipt that emitted the event.
+    fn process_block(
+        &mut self,
+        number: u64,
+        receipts: &[Receipt],
+        txs: &[TxEnvelope],
+    ) -> Result<(), ResetError> {
+        let mut expired_used = None;
+
+        for (index, receipt) in receipts.iter().enumerate() {
+            for log in receipt.logs.iter().filter(|log| log.address == self.config.address) {
+                let Some(event) = ChallengeStatusChanged::decode_log(log) else {
+                    warn!(
+                        target: "alt-da-challenge",
+                        "Skipping malformed challenge event in block {number}"
+                    );
+                    continue;
+                };
+                let tracked = self
+                    .commitments
+                    .entry((event.challenged_block_number, event.challenged_commitment.clone()))
+                    .or_default();
+                match event.status {
+                    ChallengeStatus::Active => {
+                        info!(
+                            target: "alt-da-challenge",
+                            "Commitment included in block {} challenged in block {number}",
+                            event.challenged_block_number
+                        );
+                        tracked.status = ChallengeStatus::Active;
+                        tracked.challenged_at = number;
+                    }
+                    ChallengeStatus::Resolved => {
+                        tracked.status = ChallengeStatus::Resolved;
+                        tracked.input = txs.get(index).and_then(|tx| {
+                            resolve_input(tx, self.config.address, &event.challenged_commitment)
+                        });
+                        if tracked.input.is_none() {
+                            warn!(
+                                target: "alt-da-challenge",
+                                "Failed to read the input resolving the commitment of block {}",
+                                event.challenged_block_number
+                            );
+                        }
+                    }
+                    ChallengeStatus::Expired => {
+                        if tracked.status != ChallengeStatus::Expired && tracked.used {
+                            expired_used = Some(event.challenged_block_number);
+                        }
+                        tracked.status = ChallengeStatus::Expired;
+                    }
+                    ChallengeStatus::Uninitialized => {}
+                }
+            }
+        }
+
+        let resolve_window = self.config.resolve_window;
+        for ((included_at, _), tracked) in self.commitments.iter_mut() {
+            if tracked.status == ChallengeStatus::Active &&
+                number > tracked.challenged_at.saturating_add(resolve_window)
+            {
+                warn!(
+                    target: "alt-da-challenge",
+                    "Challenge of the commitment included in block {included_at} expired"
+                );
+                tracked.status = ChallengeStatus::Expired;
+                if tracked.used {
+                    expired_used = Some(*included_at);
+                }
+            }
+        }
+
+        // Once its challenge and resolve windows passed, a commitment can no longer change state.
+        let retention = self.config.challenge_window.saturating_add(resolve_window);
+        self.commitments
+            .retain(|(included_at, _), _| number <= included_at.saturating_add(retention));
+        self.origin = Some(number);
+
+        expired_used
+            .map_or(Ok(()), |included_at| Err(ResetError::AltDAChallengeExpired(included_at)))
+    }
+}
+
+/// Reads the input of the given commitment from a `resolve(uint256,bytes,bytes)` call to the
+/// challenge contract, verifying it against the commitment.
+fn resolve_input(tx: &TxEnvelope, address: Address, commitment: &Commitment) -> Option<Bytes> {
+    if tx.to() != Some(address) {
+        return None;
+    }
+    let args = tx.input().strip_prefix(&RESOLVE_SELECTOR)?;
+    let input = decode_bytes(args, decode_usize_word(args.get(64..96)?)?)?;
+    match commitment {
+        Commitment::Keccak256(hash) if keccak256(input) != *hash => None,
+        _ => Some(Bytes::copy_from_slice(input)),
+    }
+}
+
+/// Decodes the ABI `bytes` at the given offset of the data.
+fn decode_bytes(data: &[u8], offset: usize) -> Option<&[u8]> {
+    let start = offset.checked_add(32)?;
+    let len = decode_usize_word(data.get(offset..start)?)?;
+    data.get(start..start.checked_add(len)?)
+}
+
+/// Decodes an ABI `uint256` word that fits into a `u64`.
+fn decode_u64_word(word: &[u8]) -> Option<u64> {
+    if word.len() != 32 || word[..24].iter().any(|b| *b != 0) {
+        return None;
+    }
+    Some(u64::from_be_bytes(word[24..].try_into().ok()?))
+}
+
+/// Decodes an ABI `uint256` word that fits into a `usize`.
+fn decode_usize_word(word: &[u8]) -> Option<usize> {
+    usize::try_from(decode_u64_word(word)?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        errors::{AltDAProviderError, PipelineErrorKind},
+        sources::{AltDADataSource, AltDARegistry},
+        test_utils::{sign_as_batcher, TestChainProvider, SIMULATED_BATCHER},
+        traits::{AltDAProvider, DataAvailabilityProvider},
+    };
+    use alloc::{boxed::Box, string::ToString, vec};
+    use alloy_consensus::TxLegacy;
+    use alloy_primitives::{address, LogData, TxKind, U256};
+    use async_trait::async_trait;
+    use maili_protocol::BlockInfo;
+    use op_alloy_genesis::RollupConfig;
+
+    const CHALLENGE_CONTRACT: Address = address!("dac0000000000000000000000000000000000000");
+
+    const CONFIG: DAChallengeConfig =
+        DAChallengeConfig { address: CHALLENGE_CONTRACT, challenge_window: 4, resolve_window: 4 };
+
+    /// A DA server that never serves any input.
+    #[derive(Debug)]
+    struct UnavailableAltDAProvider;
+
+    #[async_trait]
+    impl AltDAProvider for UnavailableAltDAProvider {
+        async fn get_input(&mut self, _: &Commitment) -> Result<Bytes, AltDAProviderError> {
+            Err(AltDAProviderError::Unavailable("not found".to_string()))
+        }
+    }
+
+    /// ABI encodes `bytes` as a length word followed by the right padded data.
+    fn abi_bytes(data: &[u8]) -> Vec<u8> {
+        let mut encoded = U256::from(data.len()).to_be_bytes::<32>().to_vec();
+        encoded.extend_from_slice(data);
+        encoded.resize(32 + data.len().div_ceil(32) * 32, 0);
+        encoded
+    }
+
+    fn event_receipt(included_at: u64, commitment: &Commitment, status: u8) -> Receipt {
+        let data = [
+            U256::from(64).to_be_bytes::<32>().to_vec(),
+            U256::from(status).to_be_bytes::<32>().to_vec(),
+            abi_bytes(&commitment.encode()),
+        ]
+        .concat();
+        let log = Log {
+            address: CHALLENGE_CONTRACT,
+            data: LogData::new_unchecked(
+                vec![
+                    CHALLENGE_STATUS_CHANGED_TOPIC,
+                    B256::left_padding_from(&included_at.to_be_bytes()),
+                ],
+                data.into(),
+            ),
+        };
+        Receipt { status: true.into(), logs: vec![log], ..Default::default() }
+    }
+
+    fn resolve_tx(included_at: u64, commitment: &Commitment, input: &[u8]) -> TxEnvelope {
+        let encoded = commitment.encode();
+        let calldata = [
+            RESOLVE_SELECTOR.to_vec(),
+            U256::from(included_at).to_be_bytes::<32>().to_vec(),
+            U256::from(96).to_be_bytes::<32>().to_vec(),
+            U256::from(96 + abi_bytes(&encoded).len()).to_be_bytes::<32>().to_vec(),
+            abi_bytes(&encoded),
+            abi_bytes(input),
+        ]
+        .concat();
+        tx(CHALLENGE_CONTRACT, calldata)
+    }
+
+    fn tx(to: Address, input: Vec<u8>) -> TxEnvelope {
+        sign_as_batcher(TxLegacy {
+            to: TxKind::Call(to),
+            input: input.into(),
+            ..Default::default()
+        })
+    }
+
+    /// Returns a provider with empty L1 blocks 0 to 15, and the given receipts and transactions
+    /// in the given blocks.
+    fn provider(blocks: Vec<(u64, Vec<Receipt>, Vec<TxEnvelope>)>) -> TestChainProvider {
+        let mut provider = TestChainProvider::default();
+        for number in 0..16 {
+            let hash = B256::with_last_byte(number as u8 + 1);
+            let (receipts, txs) = blocks
+                .iter()
+                .find(|(n, _, _)| *n == number)
+                .map(|(_, receipts, txs)| (receipts.clone(), txs.clone()))
+                .unwrap_or_default();
+            provider.insert_block_with_transactions(
+                number,
+                BlockInfo { number, hash, ..Default::default() },
+                txs,
+            );
+            provider.insert_receipts(hash, receipts);
+        }
+        provider
+    }
+
+    #[test]
+    fn test_decode_challenge_status_changed() {
+        let commitment = Commitment::Keccak256(keccak256([0xAA]));
+        let receipt = event_receipt(7, &commitment, 1);
+        let event = ChallengeStatusChanged::decode_log(&receipt.logs[0]).unwrap();
+        assert_eq!(
+            event,
+            ChallengeStatusChanged {
+                challenged_block_number: 7,
+                challenged_commitment: commitment.clone(),
+                status: ChallengeStatus::Active,
+            }
+        );
+
+        // Unknown statuses and other events are not decoded.
+        let receipt = event_receipt(7, &commitment, 4);
+        assert_eq!(ChallengeStatusChanged::decode_log(&receipt.logs[0]), None);
+        let mut log = event_receipt(7, &commitment, 1).logs.remove(0);
+        log.data = LogData::new_unchecked(vec![B256::ZERO, B256::ZERO], log.data.data);
+        assert_eq!(ChallengeStatusChanged::decode_log(&log), None);
+    }
+
+    #[tokio::test]
+    async fn test_watcher_resolved_challenge() {
+        let input = [0xDD; 40];
+        let commitment = Commitment::Keccak256(keccak256(input));
+        // The resolving transaction follows an unrelated transaction, whose receipt is kept.
+        let mut provider = provider(vec![
+            (3, vec![event_receipt(1, &commitment, 1)], vec![]),
+            (
+                5,
+                vec![Receipt::default(), event_receipt(1, &commitment, 2)],
+                vec![tx(Address::ZERO, vec![]), resolve_tx(1, &commitment, &input)],
+            ),
+        ]);
+        let mut watcher = DAChallengeWatcher::new(CONFIG);
+
+        watcher.advance_to(&mut provider, 4).await.unwrap();
+        assert_eq!(watcher.origin(), Some(4));
+        assert_eq!(watcher.status(1, &commitment), ChallengeStatus::Active);
+        assert!(!watcher.challenge_window_closed(1));
+
+        watcher.advance_to(&mut provider, 6).await.unwrap();
+        assert_eq!(watcher.status(1, &commitment), ChallengeStatus::Resolved);
+        assert_eq!(watcher.resolved_input(1, &commitment).unwrap().as_ref(), input);
+        assert!(watcher.challenge_window_closed(1));
+
+        // The resolved commitment is pruned once its windows passed.
+        watcher.advance_to(&mut provider, 10).await.unwrap();
+        assert_eq!(watcher.status(1, &commitment), ChallengeStatus::Uninitialized);
+    }
+
+    #[tokio::test]
+    async fn test_watcher_expired_challenge() {
+        let commitment = Commitment::Keccak256(keccak256([0xAA]));
+        let mut provider = provider(vec![(2, vec![event_receipt(1, &commitment, 1)], vec![])]);
+        let mut watcher = DAChallengeWatcher::new(CONFIG);
+
+        watcher.advance_to(&mut provider, 6).await.unwrap();
+        assert_eq!(watcher.status(1, &commitment), ChallengeStatus::Active);
+        watcher.advance_to(&mut provider, 7).await.unwrap();
+        assert_eq!(watcher.status(1, &commitment), ChallengeStatus::Expired);
+    }
+
+    #[tokio::test]
+    async fn test_watcher_used_commitment_expiry_resets() {
+        let commitment = Commitment::Keccak256(keccak256([0xAA]));
+        let mut provider = provider(vec![(2, vec![event_receipt(1, &commitment, 1)], vec![])]);
+        let mut watcher = DAChallengeWatcher::new(CONFIG);
+
+        watcher.advance_to(&mut provider, 1).await.unwrap();
+        watcher.mark_used(1, commitment.clone());
+        assert_eq!(
+            watcher.advance_to(&mut provider, 8).await,
+            Err(PipelineErrorKind::Reset(ResetError::AltDAChallengeExpired(1)))
+        );
+
+        // The block that expired the challenge is not processed again.
+        assert_eq!(watcher.origin(), Some(7));
+        watcher.advance_to(&mut provider, 8).await.unwrap();
+        assert_eq!(watcher.status(1, &commitment), ChallengeStatus::Expired);
+    }
+
+    #[tokio::test]
+    async fn test_alt_da_data_source_challenges() {
+        let input = [0xDD; 40];
+        let expired = Commitment::Keccak256(keccak256([0xAA]));
+        let resolved = Commitment::Keccak256(keccak256(input));
+        let batcher_txs = vec![
+            tx(Address::ZERO, expired.to_batcher_data()),
+            tx(Address::ZERO, resolved.to_batcher_data()),
+        ];
+        let signer = SIMULATED_BATCHER;
+        let provider = provider(vec![
+            (0, vec![], batcher_txs),
+            (1, vec![event_receipt(0, &expired, 1)], vec![]),
+            (2, vec![event_receipt(0, &resolved, 1)], vec![]),
+            (3, vec![event_receipt(0, &resolved, 2)], vec![resolve_tx(0, &resolved, &input)]),
+        ]);
+        let registry = AltDARegistry::new().with_keccak256(UnavailableAltDAProvider);
+        let mut source = AltDADataSource::new(provider, registry, &RollupConfig::default())
+            .with_challenge_watcher(CONFIG);
+
+        // While the input is unavailable, the watcher looks ahead by one block per step, until the
+        // challenge of the first commitment expires.
+        let block_ref =
+            BlockInfo { number: 0, hash: B256::with_last_byte(1), ..Default::default() };
+        for origin in 1..=6 {
+            assert!(matches!(
+                source.next(&block_ref, signer).await,
+                Err(PipelineErrorKind::Temporary(_))
+            ));
+            assert_eq!(source.challenge_watcher.as_ref().unwrap().origin(), Some(origin));
+        }
+
+        // The expired commitment is skipped, and the resolved input is read from L1.
+        assert_eq!(source.next(&block_ref, signer).await.unwrap().as_ref(), input);
+    }
+}
@@ -0,0 +1,330 @@
+//! Contains the [ArchiveChainProvider], a [ChainProvider] backed by exported L1 block and
+//! receipt archives, for offline derivation of historical ranges.
+
+use crate::{
+    errors::ArchiveError,
+    traits::{ChainProvider, L1BlockTag},
+};
+use alloc::{boxed::Box, vec::Vec};
+use alloy_consensus::{Block, Header, Receipt, ReceiptEnvelope, TxEnvelope};
+use alloy_primitives::{map::HashMap, Bytes, B256};
+use alloy_rlp::Decodable;
+use async_trait::async_trait;
+use core::ops::Range;
+use maili_protocol::BlockInfo;
+
+/// An indexed block of an archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ArchiveEntry {
+    /// The block info, read from the header.
+    info: BlockInfo,
+    /// The byte range of the RLP encoded block in the block archive.
+    block: Range<usize>,
+    /// The byte range of the RLP encoded receipt list in the receipt archive.
+    receipts: Range<usize>,
+}
+
+/// An index of an L1 block archive and its receipt archive, for random access by block number or
+/// hash.
+///
+/// The block archive is a stream of RLP encoded blocks in ascending, consecutive order, as written
+/// by `geth export`. As the export format does not carry receipts, the receipt archive is a
+/// stream of RLP lists of consensus encoded receipts, one list per block in the same order.
+///
+/// Building the index only decodes the block headers, blocks and receipts are decoded on access.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ArchiveIndex {
+    /// The indexed blocks, in ascending order.
+    entries: Vec<ArchiveEntry>,
+    /// The position of each block in `entries`, by block hash.
+    by_hash: HashMap<B256, usize>,
+}
+
+impl ArchiveIndex {
+    /// Builds the index of the given block and receipt archives.
+    pub fn build(blocks: &[u8], receipts: &[u8]) -> Result<Self, ArchiveError> {
+        let mut index = Self::default();
+        let mut offset = 0;
+        while offset < blocks.len() {
+            let item = rlp_item(blocks, offset)?;
+            let mut payload = &blocks[item.clone()];
+            alloy_rlp::Header::decode(&mut payload)?;
+            let header = Header::decode(&mut payload)?;
+
+            if let Some(last) = index.entries.last() {
+                let expected = last.info.number + 1;
+                if header.number != expected {
+                    return Err(ArchiveError::NonConsecutive { expected, found: header.number });
+                }
+            }
+
+            let info = BlockInfo::new(
+                header.hash_slow(),
+                header.number,
+                header.parent_hash,
+                header.timestamp,
+            );
+            index.by_hash.insert(info.hash, index.entries.len());
+            index.entries.push(ArchiveEntry { info, block: item.clone(), receipts: 0..0 });
+            offset = item.end;
+        }
+
+        let mut offset = 0;
+        let mut count = 0;
+        while offset < receipts.len() {
+            let item = rlp_item(receipts, offset)?;
+            if let Some(entry) = index.entries.get_mut(count) {
+                entry.receipts = item.clone();
+            }
+            count += 1;
+            offset = item.end;
+        }
+        if count != index.entries.len() {
+            return Err(ArchiveError::ReceiptsMismatch {
+                blocks: index.entries.len(),
+                receipts: count,
+            });
+        }
+
+        Ok(index)
+    }
+
+    /// Returns the number of indexed blocks.
+    pub const fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the index is empty.
+    pub const fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the first indexed block, if any.
+    pub fn first(&self) -> Option<BlockInfo> {
+        self.entries.first().map(|e| e.info)
+    }
+
+    /// Returns the last indexed block, if any.
+    pub fn last(&self) -> Option<BlockInfo> {
+        self.entries.last().map(|e| e.info)
+    }
+
+    /// Returns the entry of the block with the given number.
+    fn by_number(&self, number: u64) -> Result<&ArchiveEntry, ArchiveError> {
+        let first = self.entries.first().map_or(0, |e| e.info.number);
+        number
+            .checked_sub(first)
+            .and_then(|i| self.entries.get(i as usize))
+            .ok_or(ArchiveError::BlockNotFound(number))
+    }
+
+    /// Returns the entry of the block with the given hash.
+    fn by_hash(&self, hash: B256) -> Result<&ArchiveEntry, ArchiveError> {
+        self.by_hash.get(&hash).map(|i| &self.entries[*i]).ok_or(ArchiveError::HashNotFound(hash))
+    }
+}
+
+/// Returns the byte range of the RLP item starting at `offset`, including its RLP header.
+fn rlp_item(data: &[u8], offset: usize) -> Result<Range<usize>, ArchiveError> {
+    let mut buf = &data[offset..];
+    let header = alloy_rlp::Header::decode(&mut buf)?;
+    if !header.list {
+        return Err(alloy_rlp::Error::UnexpectedString.into());
+    }
+    if header.payload_length > buf.len() {
+        return Err(alloy_rlp::Error::InputTooShort.into());
+    }
+    let end = data.len() - buf.len() + header.payload_length;
+    Ok(offset..end)
+}
+
+/// A [ChainProvider] that serves L1 blocks from exported block and receipt archives, see
+/// [ArchiveIndex] for the archive format.
+///
+/// The archived blocks are final, so all [L1BlockTag]s resolve to the last archived block.
+/// Requests for blocks past the end of the archive fail with a temporary error, as a live L1
+/// provider does for blocks that are not yet produced.
+#[derive(Debug, Clone)]
+pub struct ArchiveChainProvider {
+    /// The block archive.
+    blocks: Bytes,
+    /// The receipt archive.
+    receipts: Bytes,
+    /// The index of the archives.
+    index: ArchiveIndex,
+}
+
+impl ArchiveChainProvider {
+    /// Creates a new [ArchiveChainProvider], building the index of the archives.
+    pub fn new(blocks: Bytes, receipts: Bytes) -> Result<Self, ArchiveError> {
+        let index = ArchiveIndex::build(&blocks, &receipts)?;
+        Ok(Self::from_parts(blocks, receipts, index))
+    }
+
+    /// Creates a new [ArchiveChainProvider] from archives and their prebuilt index.
+    pub const fn from_parts(blocks: Bytes, receipts: Bytes, index: ArchiveIndex) -> Self {
+        Self { blocks, receipts, index }
+    }
+
+    /// Returns the index of the archives.
+    pub const fn index(&self) -> &ArchiveIndex {
+        &self.index
+    }
+
+    /// Decodes the block of the given entry.
+    fn block(&self, entry: &ArchiveEntry) -> Result<Block<TxEnvelope>, ArchiveError> {
+        Ok(Block::decode(&mut &self.blocks[entry.block.clone()])?)
+    }
+}
+
+#[async_trait]
+impl ChainProvider for ArchiveChainProvider {
+    type Error = ArchiveError;
+
+    async fn header_by_hash(&mut self, hash: B256) -> Result<Header, Self::Error> {
+        let entry = self.index.by_hash(hash)?;
+        let mut payload = &self.blocks[entry.block.clone()];
+        alloy_rlp::Header::decode(&mut payload)?;
+        Ok(Header::decode(&mut payload)?)
+    }
+
+    async fn block_info_by_number(&mut self, number: u64) -> Result<BlockInfo, Self::Error> {
+        Ok(self.index.by_number(number)?.info)
+    }
+
+    async fn block_info_by_tag(
+        &mut self,
+        _tag: L1BlockTag,
+    ) -> Result<Option<BlockInfo>, Self::Error> {
+        Ok(self.index.last())
+    }
+
+    async fn receipts_by_hash(&mut self, hash: B256) -> Result<Vec<Receipt>, Self::Error> {
+        let entry = self.index.by_hash(hash)?;
+        let receipts = Vec::<ReceiptEnvelope>::decode(&mut &self.receipts[entry.receipts.clone()])?;
+        receipts
+            .iter()
+            .map(|r| {
+                r.as_receipt().cloned().ok_or(alloy_rlp::Error::Custom("Unknown receipt type"))
+            })
+            .collect::<Result<_, _>>()
+            .map_err(Into::into)
+    }
+
+    async fn block_info_and_transactions_by_hash(
+        &mut self,
+        hash: B256,
+    ) -> Result<(BlockInfo, Vec<TxEnvelope>), Self::Error> {
+        let entry = self.index.by_hash(hash)?;
+        let block = self.block(entry)?;
+        Ok((entry.info, block.body.transactions))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use alloy_consensus::{BlockBody, ReceiptWithBloom, Signed, TxLegacy};
+    use alloy_primitives::{Address, Log, PrimitiveSignature};
+    use alloy_rlp::Encodable;
+
+    /// Builds block and receipt archives of consecutive blocks with one transaction each.
+    fn archives(numbers: &[u64]) -> (Bytes, Bytes, Vec<B256>) {
+        let mut blocks = Vec::new();
+        let mut receipts = Vec::new();
+        let mut hashes = Vec::new();
+        let mut parent_hash = B256::ZERO;
+        for &number in numbers {
+            let header =
+                Header { number, parent_hash, timestamp: number * 12, ..Default::default() };
+            parent_hash = header.hash_slow();
+            hashes.push(parent_hash);
+
+            let tx = TxLegacy { nonce: number, ..Default::default() };
+            let tx = TxEnvelope::Legacy(Signed::new_unchecked(
+                tx,
+                PrimitiveSignature::test_signature(),
+                B256::ZERO,
+            ));
+            let body = BlockBody { transactions: vec![tx], ommers: vec![], withdrawals: None };
+            Block { header, body }.encode(&mut blocks);
+
+            let receipt = Receipt {
+                status: true.into(),
+                cumulative_gas_used: number,
+                logs: vec![Log::new_unchecked(Address::ZERO, vec![], Bytes::new())],
+            };
+            vec![ReceiptEnvelope::Eip1559(ReceiptWithBloom::from(receipt))].encode(&mut receipts);
+        }
+        (blocks.into(), receipts.into(), hashes)
+    }
+
+    #[tokio::test]
+    async fn test_archive_chain_provider() {
+        let (blocks, receipts, hashes) = archives(&[100, 101, 102]);
+        let mut provider = ArchiveChainProvider::new(blocks, receipts).unwrap();
+        assert_eq!(provider.index().len(), 3);
+        assert_eq!(provider.index().first().unwrap().number, 100);
+
+        let info = provider.block_info_by_number(101).await.unwrap();
+        assert_eq!(info.hash, hashes[1]);
+        assert_eq!(info.parent_hash, hashes[0]);
+        assert_eq!(info.timestamp, 101 * 12);
+
+        let header = provider.header_by_hash(hashes[2]).await.unwrap();
+        assert_eq!(header.number, 102);
+
+        let (info, txs) = provider.block_info_and_transactions_by_hash(hashes[0]).await.unwrap();
+        assert_eq!(info.number, 100);
+        assert_eq!(txs.len(), 1);
+
+        let receipts = provider.receipts_by_hash(hashes[2]).await.unwrap();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].cumulative_gas_used, 102);
+        assert_eq!(receipts[0].logs.len(), 1);
+
+        let head = provider.block_info_by_tag(L1BlockTag::Finalized).await.unwrap();
+        assert_eq!(head.map(|b| b.number), Some(102));
+    }
+
+    #[tokio::test]
+    async fn test_archive_chain_provider_out_of_range() {
+        let (blocks, receipts, _) = archives(&[100, 101]);
+        let mut provider = ArchiveChainProvider::new(blocks, receipts).unwrap();
+        assert_eq!(
+            provider.block_info_by_number(99).await.unwrap_err(),
+            ArchiveError::BlockNotFound(99)
+        );
+        assert_eq!(
+            provider.block_info_by_number(102).await.unwrap_err(),
+            ArchiveError::BlockNotFound(102)
+        );
+        assert_eq!(
+            provider.header_by_hash(B256::ZERO).await.unwrap_err(),
+            ArchiveError::HashNotFound(B256::ZERO)
+        );
+    }
+
+    #[test]
+    fn test_archive_index_invalid() {
+        let (blocks, receipts, _) = archives(&[100, 102]);
+        assert_eq!(
+            ArchiveIndex::build(&blocks, &receipts).unwrap_err(),
+            ArchiveError::NonConsecutive { expected: 101, found: 102 }
+        );
+
+        let (blocks, receipts, _) = archives(&[100, 101]);
+        let (_, single, _) = archives(&[100]);
+        assert_eq!(
+            ArchiveIndex::build(&blocks, &single).unwrap_err(),
+            ArchiveError::ReceiptsMismatch { blocks: 2, receipts: 1 }
+        );
+
+        assert_eq!(
+            ArchiveIndex::build(&blocks[..blocks.len() - 1], &receipts).unwrap_err(),
+            ArchiveError::Rlp(alloy_rlp::Error::InputTooShort)
+        );
+        assert!(ArchiveIndex::build(&[], &[]).unwrap().is_empty());
+    }
+}
@@ -1,6 +1,6 @@
 //! Contains the `BlobData` struct.
 
-use crate::errors::BlobDecodingError;
+use crate::errors::{BlobDecodingError, BlobEncodingError};
 use alloc::{boxed::Box, vec};
 use alloy_eips::eip4844::{Blob, BYTES_PER_BLOB, VERSIONED_HASH_VERSION_KZG};
 use alloy_primitives::Bytes;
@@ -9,7 +9,7 @@ use alloy_primitives::Bytes;
 pub(crate) const BLOB_ENCODING_VERSION: u8 = 0;
 
 /// Maximum blob data size
-pub(crate) const BLOB_MAX_DATA_SIZE: usize = (4 * 31 + 3) * 1024 - 4; // 130044
+pub const BLOB_MAX_DATA_SIZE: usize = (4 * 31 + 3) * 1024 - 4; // 130044
 
 /// Blob Encoding/Decoding Rounds
 pub(crate) const BLOB_ENCODING_ROUNDS: usize = 1024;
@@ -28,141 +28,211 @@ impl BlobData {
     /// Returns a [BlobDecodingError] if the blob is invalid.
     pub(crate) fn decode(&self) -> Result<Bytes, BlobDecodingError> {
         let data = self.data.as_ref().ok_or(BlobDecodingError::MissingData)?;
+        decode_blob_data(data)
+    }
 
-        // Validate the blob encoding version
-        if data[VERSIONED_HASH_VERSION_KZG as usize] != BLOB_ENCODING_VERSION {
-            return Err(BlobDecodingError::InvalidEncodingVersion);
+    /// Fills in the pointers to the fetched blob bodies.
+    /// There should be exactly one placeholder blobOrCalldata
+    /// element for each blob, otherwise an error is returned.
+    pub(crate) fn fill(
+        &mut self,
+        blobs: &[Box<Blob>],
+        index: usize,
+    ) -> Result<bool, BlobDecodingError> {
+        // Do not fill if there is calldata here
+        if self.calldata.is_some() {
+            return Ok(false);
         }
 
-        // Decode the 3 byte big endian length value into a 4 byte integer
-        let length = u32::from_be_bytes([0, data[2], data[3], data[4]]) as usize;
-
-        // Validate the length
-        if length > BLOB_MAX_DATA_SIZE {
+        if index >= blobs.len() {
             return Err(BlobDecodingError::InvalidLength);
         }
 
-        // Round 0 copies the remaining 27 bytes of the first field element
-        let mut output = vec![0u8; BLOB_MAX_DATA_SIZE];
-        output[0..27].copy_from_slice(&data[5..32]);
+        if blobs[index].is_empty() || blobs[index].is_zero() {
+            return Err(BlobDecodingError::MissingData);
+        }
 
-        // Process the remaining 3 field elements to complete round 0
-        let mut output_pos = 28;
-        let mut input_pos = 32;
-        let mut encoded_byte = [0u8; 4];
-        encoded_byte[0] = data[0];
+        self.data = Some(Bytes::from(*blobs[index]));
+        Ok(true)
+    }
+}
 
-        for b in encoded_byte.iter_mut().skip(1) {
-            let (enc, opos, ipos) =
-                self.decode_field_element(output_pos, input_pos, &mut output)?;
-            *b = enc;
-            output_pos = opos;
-            input_pos = ipos;
-        }
+/// The blob encoding of channel data.
+///
+/// Each of the 4096 field elements of a blob carries 31 bytes of data in its lower order bytes,
+/// and 6 bits in its high order byte, whose two highest bits must be zero. Every 4 field elements
+/// carry 127 bytes, with the 4 by 6 high order bits packing 3 bytes. The first field element is
+/// prefixed by the encoding version and the 3 byte big endian data length, so a blob carries up
+/// to [BLOB_MAX_DATA_SIZE] bytes.
+pub trait BlobChannelData: Sized {
+    /// Decodes the channel data carried by the blob.
+    /// Returns a [BlobDecodingError] if the blob is not a valid encoding.
+    fn decode_channel_data(&self) -> Result<Bytes, BlobDecodingError>;
+
+    /// Encodes the channel data into a blob.
+    /// Returns a [BlobEncodingError] if the data is larger than [BLOB_MAX_DATA_SIZE].
+    fn encode_channel_data(data: &[u8]) -> Result<Self, BlobEncodingError>;
+}
 
-        // Reassemble the 4 by 6 bit encoded chunks into 3 bytes of output
-        output_pos = self.reassemble_bytes(output_pos, &encoded_byte, &mut output);
+impl BlobChannelData for Blob {
+    fn decode_channel_data(&self) -> Result<Bytes, BlobDecodingError> {
+        decode_blob_data(self.as_slice())
+    }
+
+    fn encode_channel_data(data: &[u8]) -> Result<Self, BlobEncodingError> {
+        if data.len() > BLOB_MAX_DATA_SIZE {
+            return Err(BlobEncodingError::DataTooLarge(data.len()));
+        }
 
-        // In each remaining round, decode 4 field elements (128 bytes) of the
-        // input into 127 bytes of output
-        for _ in 1..BLOB_ENCODING_ROUNDS {
-            // Break early if the output position is greater than the length
-            if output_pos >= length {
+        let mut blob = Self::ZERO;
+        let mut input_pos = 0;
+        let mut output_pos = 0;
+        for round in 0..BLOB_ENCODING_ROUNDS {
+            if input_pos >= data.len() {
                 break;
             }
 
-            for d in &mut encoded_byte {
-                let (enc, opos, ipos) =
-                    self.decode_field_element(output_pos, input_pos, &mut output)?;
-                *d = enc;
-                output_pos = opos;
-                input_pos = ipos;
+            // Read the lower order bytes of the 4 field elements, interleaved with the 3 bytes
+            // packed into their high order bytes. Round 0 starts with the version and the length.
+            let mut chunks = [[0u8; 31]; 4];
+            let mut packed = [0u8; 3];
+            if round == 0 {
+                chunks[0][0] = BLOB_ENCODING_VERSION;
+                chunks[0][1..4].copy_from_slice(&(data.len() as u32).to_be_bytes()[1..]);
+                read_padded(data, &mut input_pos, &mut chunks[0][4..]);
+            } else {
+                read_padded(data, &mut input_pos, &mut chunks[0]);
             }
-            output_pos = self.reassemble_bytes(output_pos, &encoded_byte, &mut output);
-        }
-
-        // Validate the remaining bytes
-        for o in output.iter().skip(length) {
-            if *o != 0u8 {
-                return Err(BlobDecodingError::InvalidFieldElement);
+            for (chunk, byte) in chunks[1..].iter_mut().zip(packed.chunks_mut(1)) {
+                read_padded(data, &mut input_pos, byte);
+                read_padded(data, &mut input_pos, chunk);
             }
-        }
 
-        // Validate the remaining bytes
-        output.truncate(length);
-        for i in input_pos..BYTES_PER_BLOB {
-            if data[i] != 0 {
-                return Err(BlobDecodingError::InvalidFieldElement);
+            // Split the 3 packed bytes into 4 by 6 bit high order bytes.
+            let [x, y, z] = packed;
+            let high_order_bytes = [
+                x & 0b0011_1111,
+                (y & 0b0000_1111) | ((x & 0b1100_0000) >> 2),
+                z & 0b0011_1111,
+                ((z & 0b1100_0000) >> 2) | ((y & 0b1111_0000) >> 4),
+            ];
+            for (high_order_byte, chunk) in high_order_bytes.into_iter().zip(chunks) {
+                blob[output_pos] = high_order_byte;
+                blob[output_pos + 1..output_pos + 32].copy_from_slice(&chunk);
+                output_pos += 32;
             }
         }
 
-        Ok(Bytes::from(output))
+        Ok(blob)
     }
+}
 
-    /// Decodes the next input field element by writing its lower 31 bytes into its
-    /// appropriate place in the output and checking the high order byte is valid.
-    /// Returns a [BlobDecodingError] if a field element is seen with either of its
-    /// two high order bits set.
-    pub(crate) fn decode_field_element(
-        &self,
-        output_pos: usize,
-        input_pos: usize,
-        output: &mut [u8],
-    ) -> Result<(u8, usize, usize), BlobDecodingError> {
-        let Some(data) = self.data.as_ref() else {
-            return Err(BlobDecodingError::MissingData);
-        };
+/// Decodes blob data into raw byte data.
+/// Returns a [BlobDecodingError] if the data is not a valid blob encoding.
+fn decode_blob_data(data: &[u8]) -> Result<Bytes, BlobDecodingError> {
+    // Validate the blob encoding version
+    if data[VERSIONED_HASH_VERSION_KZG as usize] != BLOB_ENCODING_VERSION {
+        return Err(BlobDecodingError::InvalidEncodingVersion);
+    }
 
-        // two highest order bits of the first byte of each field element should always be 0
-        if data[input_pos] & 0b1100_0000 != 0 {
-            return Err(BlobDecodingError::InvalidFieldElement);
-        }
-        output[output_pos..output_pos + 31].copy_from_slice(&data[input_pos + 1..input_pos + 32]);
-        Ok((data[input_pos], output_pos + 32, input_pos + 32))
+    // Decode the 3 byte big endian length value into a 4 byte integer
+    let length = u32::from_be_bytes([0, data[2], data[3], data[4]]) as usize;
+
+    // Validate the length
+    if length > BLOB_MAX_DATA_SIZE {
+        return Err(BlobDecodingError::InvalidLength);
     }
 
-    /// Reassemble 4 by 6 bit encoded chunks into 3 bytes of output and place them in their
-    /// appropriate output positions.
-    pub(crate) fn reassemble_bytes(
-        &self,
-        mut output_pos: usize,
-        encoded_byte: &[u8],
-        output: &mut [u8],
-    ) -> usize {
-        output_pos -= 1;
-        let x = (encoded_byte[0] & 0b0011_1111) | ((encoded_byte[1] & 0b0011_0000) << 2);
-        let y = (encoded_byte[1] & 0b0000_1111) | ((encoded_byte[3] & 0b0000_1111) << 4);
-        let z = (encoded_byte[2] & 0b0011_1111) | ((encoded_byte[3] & 0b0011_0000) << 2);
-        output[output_pos - 32] = z;
-        output[output_pos - (32 * 2)] = y;
-        output[output_pos - (32 * 3)] = x;
-        output_pos
+    // Round 0 copies the remaining 27 bytes of the first field element
+    let mut output = vec![0u8; BLOB_MAX_DATA_SIZE];
+    output[0..27].copy_from_slice(&data[5..32]);
+
+    // Process the remaining 3 field elements to complete round 0
+    let mut output_pos = 28;
+    let mut input_pos = 32;
+    let mut encoded_byte = [0u8; 4];
+    encoded_byte[0] = data[0];
+
+    for b in encoded_byte.iter_mut().skip(1) {
+        let (enc, opos, ipos) = decode_field_element(data, output_pos, input_pos, &mut output)?;
+        *b = enc;
+        output_pos = opos;
+        input_pos = ipos;
     }
 
-    /// Fills in the pointers to the fetched blob bodies.
-    /// There should be exactly one placeholder blobOrCalldata
-    /// element for each blob, otherwise an error is returned.
-    pub(crate) fn fill(
-        &mut self,
-        blobs: &[Box<Blob>],
-        index: usize,
-    ) -> Result<bool, BlobDecodingError> {
-        // Do not fill if there is calldata here
-        if self.calldata.is_some() {
-            return Ok(false);
+    // Reassemble the 4 by 6 bit encoded chunks into 3 bytes of output
+    output_pos = reassemble_bytes(output_pos, &encoded_byte, &mut output);
+
+    // In each remaining round, decode 4 field elements (128 bytes) of the
+    // input into 127 bytes of output
+    for _ in 1..BLOB_ENCODING_ROUNDS {
+        // Break early if the output position is greater than the length
+        if output_pos >= length {
+            break;
         }
 
-        if index >= blobs.len() {
-            return Err(BlobDecodingError::InvalidLength);
+        for d in &mut encoded_byte {
+            let (enc, opos, ipos) = decode_field_element(data, output_pos, input_pos, &mut output)?;
+            *d = enc;
+            output_pos = opos;
+            input_pos = ipos;
         }
+        output_pos = reassemble_bytes(output_pos, &encoded_byte, &mut output);
+    }
 
-        if blobs[index].is_empty() || blobs[index].is_zero() {
-            return Err(BlobDecodingError::MissingData);
+    // Validate the remaining bytes
+    for o in output.iter().skip(length) {
+        if *o != 0u8 {
+            return Err(BlobDecodingError::InvalidFieldElement);
         }
+    }
 
-        self.data = Some(Bytes::from(*blobs[index]));
-        Ok(true)
+    // Validate the remaining bytes
+    output.truncate(length);
+    if data[input_pos..BYTES_PER_BLOB].iter().any(|b| *b != 0) {
+        return Err(BlobDecodingError::InvalidFieldElement);
     }
+
+    Ok(Bytes::from(output))
+}
+
+/// Decodes the next input field element by writing its lower 31 bytes into its
+/// appropriate place in the output and checking the high order byte is valid.
+/// Returns a [BlobDecodingError] if a field element is seen with either of its
+/// two high order bits set.
+fn decode_field_element(
+    data: &[u8],
+    output_pos: usize,
+    input_pos: usize,
+    output: &mut [u8],
+) -> Result<(u8, usize, usize), BlobDecodingError> {
+    // two highest order bits of the first byte of each field element should always be 0
+    if data[input_pos] & 0b1100_0000 != 0 {
+        return Err(BlobDecodingError::InvalidFieldElement);
+    }
+    output[output_pos..output_pos + 31].copy_from_slice(&data[input_pos + 1..input_pos + 32]);
+    Ok((data[input_pos], output_pos + 32, input_pos + 32))
+}
+
+/// Reassemble 4 by 6 bit encoded chunks into 3 bytes of output and place them in their
+/// appropriate output positions.
+fn reassemble_bytes(mut output_pos: usize, encoded_byte: &[u8], output: &mut [u8]) -> usize {
+    output_pos -= 1;
+    let x = (encoded_byte[0] & 0b0011_1111) | ((encoded_byte[1] & 0b0011_0000) << 2);
+    let y = (encoded_byte[1] & 0b0000_1111) | ((encoded_byte[3] & 0b0000_1111) << 4);
+    let z = (encoded_byte[2] & 0b0011_1111) | ((encoded_byte[3] & 0b0011_0000) << 2);
+    output[output_pos - 32] = z;
+    output[output_pos - (32 * 2)] = y;
+    output[output_pos - (32 * 3)] = x;
+    output_pos
+}
+
+/// Fills the buffer with the next bytes of the data at `pos`, zero padded past its end.
+fn read_padded(data: &[u8], pos: &mut usize, buf: &mut [u8]) {
+    let n = buf.len().min(data.len().saturating_sub(*pos));
+    buf[..n].copy_from_slice(&data[*pos..*pos + n]);
+    buf[n..].fill(0);
+    *pos += n;
 }
 
 #[cfg(test)]
@@ -171,10 +241,9 @@ mod tests {
 
     #[test]
     fn test_reassemble_bytes() {
-        let blob_data = BlobData::default();
         let mut output = vec![0u8; 128];
         let encoded_byte = [0x00, 0x00, 0x00, 0x00];
-        let output_pos = blob_data.reassemble_bytes(127, &encoded_byte, &mut output);
+        let output_pos = reassemble_bytes(127, &encoded_byte, &mut output);
         assert_eq!(output_pos, 126);
         assert_eq!(output, vec![0u8; 128]);
     }
@@ -255,22 +324,12 @@ mod tests {
         assert_eq!(blob_data.decode(), Err(BlobDecodingError::InvalidFieldElement));
     }
 
-    #[test]
-    fn test_decode_field_element_missing_data() {
-        let blob_data = BlobData::default();
-        assert_eq!(
-            blob_data.decode_field_element(0, 0, &mut []),
-            Err(BlobDecodingError::MissingData)
-        );
-    }
-
     #[test]
     fn test_decode_field_element_invalid_field_element() {
         let mut data = vec![0u8; 32];
         data[0] = 0b1100_0000;
-        let blob_data = BlobData { data: Some(Bytes::from(data)), ..Default::default() };
         assert_eq!(
-            blob_data.decode_field_element(0, 0, &mut []),
+            decode_field_element(&data, 0, 0, &mut []),
             Err(BlobDecodingError::InvalidFieldElement)
         );
     }
@@ -279,9 +338,73 @@ mod tests {
     fn test_decode_field_element() {
         let mut data = vec![0u8; 32];
         data[1..32].copy_from_slice(&[1u8; 31]);
-        let blob_data = BlobData { data: Some(Bytes::from(data)), ..Default::default() };
         let mut output = vec![0u8; 31];
-        assert_eq!(blob_data.decode_field_element(0, 0, &mut output), Ok((0, 32, 32)));
+        assert_eq!(decode_field_element(&data, 0, 0, &mut output), Ok((0, 32, 32)));
         assert_eq!(output, vec![1u8; 31]);
     }
+
+    #[test]
+    fn test_encode_channel_data_layout() {
+        let blob = Blob::encode_channel_data(&[1, 2, 3]).unwrap();
+        assert_eq!(blob[..8], [0, BLOB_ENCODING_VERSION, 0, 0, 3, 1, 2, 3]);
+        assert!(blob[8..].iter().all(|b| *b == 0));
+        assert_eq!(Blob::encode_channel_data(&[]).unwrap(), Blob::ZERO);
+    }
+
+    #[test]
+    fn test_encode_channel_data_too_large() {
+        let data = vec![0xFF; BLOB_MAX_DATA_SIZE + 1];
+        assert_eq!(
+            Blob::encode_channel_data(&data),
+            Err(BlobEncodingError::DataTooLarge(BLOB_MAX_DATA_SIZE + 1))
+        );
+    }
+
+    #[test]
+    fn test_channel_data_roundtrip_lengths() {
+        // Every length of the first rounds, covering each position in the field elements.
+        for len in (0..=3 * 128).chain([BLOB_MAX_DATA_SIZE - 1, BLOB_MAX_DATA_SIZE]) {
+            let data = (0..len).map(|i| (i * 7 + 0xC5) as u8).collect::<Vec<_>>();
+            let blob = Blob::encode_channel_data(&data).unwrap();
+            assert_eq!(blob.decode_channel_data().unwrap(), data, "length {len}");
+        }
+    }
+
+    #[test]
+    fn test_decode_channel_data_trailing_data() {
+        let mut blob = Blob::encode_channel_data(&[0xAB; 200]).unwrap();
+        blob[BYTES_PER_BLOB - 1] = 1;
+        assert_eq!(blob.decode_channel_data(), Err(BlobDecodingError::InvalidFieldElement));
+
+        // Data past the length within the last decoded round is rejected as well.
+        let mut blob = Blob::encode_channel_data(&[0xAB; 200]).unwrap();
+        blob[250] = 1;
+        assert_eq!(blob.decode_channel_data(), Err(BlobDecodingError::InvalidFieldElement));
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn fuzz_channel_data_roundtrip(
+            data in proptest::collection::vec(proptest::prelude::any::<u8>(), 0..8192),
+        ) {
+            let blob = Blob::encode_channel_data(&data).unwrap();
+            proptest::prop_assert_eq!(blob.decode_channel_data().unwrap(), data);
+        }
+
+        /// Corrupted blobs must not panic on decoding, and are rejected if a high order bit of a
+        /// field element is set.
+        #[test]
+        fn fuzz_decode_channel_data_corrupted(
+            data in proptest::collection::vec(proptest::prelude::any::<u8>(), 0..1024),
+            pos in 0..BYTES_PER_BLOB,
+            byte in proptest::prelude::any::<u8>(),
+        ) {
+            let mut blob = Blob::encode_channel_data(&data).unwrap();
+            blob[pos] = byte;
+            let decoded = blob.decode_channel_data();
+            if pos % 32 == 0 && byte & 0b1100_0000 != 0 {
+                proptest::prop_assert_eq!(decoded, Err(BlobDecodingError::InvalidFieldElement));
+            }
+        }
+    }
 }
@@ -0,0 +1,133 @@
+//! Contains the [BlobEncoder], which packs batcher data into blobs.
+
+use crate::sources::{BlobChannelData, BLOB_MAX_DATA_SIZE};
+use alloc::vec::Vec;
+use alloy_eips::eip4844::{
+    builder::{PartialSidecar, SidecarCoder},
+    utils::WholeFe,
+    Blob, FIELD_ELEMENTS_PER_BLOB, FIELD_ELEMENT_BYTES_USIZE,
+};
+
+#[cfg(feature = "kzg")]
+use crate::errors::BlobEncodingError;
+#[cfg(feature = "kzg")]
+use alloc::string::ToString;
+#[cfg(feature = "kzg")]
+use alloy_eips::eip4844::{builder::SidecarBuilder, BlobTransactionSidecar};
+
+/// A [SidecarCoder] that packs data into blobs following the OP Stack blob encoding.
+///
+/// The data is split into chunks of up to [BLOB_MAX_DATA_SIZE] bytes, each encoded into its own
+/// blob with [BlobChannelData::encode_channel_data]. Empty data is encoded into a single empty
+/// blob, so that a batcher transaction always carries at least one blob.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BlobEncoder;
+
+impl BlobEncoder {
+    /// Encodes the data into as many blobs as needed.
+    pub fn encode(data: &[u8]) -> Vec<Blob> {
+        if data.is_empty() {
+            return Vec::from([Blob::ZERO]);
+        }
+        data.chunks(BLOB_MAX_DATA_SIZE)
+            .map(|chunk| Blob::encode_channel_data(chunk).expect("chunk fits in a blob"))
+            .collect()
+    }
+
+    /// Returns the number of blobs needed to encode the data.
+    pub const fn blob_count(data: &[u8]) -> usize {
+        if data.is_empty() {
+            1
+        } else {
+            data.len().div_ceil(BLOB_MAX_DATA_SIZE)
+        }
+    }
+
+    /// Encodes the data into a [BlobTransactionSidecar], computing the KZG commitment and proof
+    /// of each blob with the Ethereum mainnet trusted setup.
+    #[cfg(feature = "kzg")]
+    pub fn sidecar(data: &[u8]) -> Result<BlobTransactionSidecar, BlobEncodingError> {
+        // The builder starts with an empty blob, which is filled by the first chunk.
+        SidecarBuilder::from_coder_and_data(Self, data)
+            .build()
+            .map_err(|e| BlobEncodingError::Kzg(e.to_string()))
+    }
+}
+
+impl SidecarCoder for BlobEncoder {
+    fn required_fe(&self, data: &[u8]) -> usize {
+        Self::blob_count(data) * FIELD_ELEMENTS_PER_BLOB as usize
+    }
+
+    fn code(&mut self, builder: &mut PartialSidecar, data: &[u8]) {
+        for blob in Self::encode(data) {
+            // The two highest order bits of every encoded field element are zero, so it is always
+            // below the field modulus.
+            for fe in blob.chunks_exact(FIELD_ELEMENT_BYTES_USIZE) {
+                builder.ingest_valid_fe(WholeFe::new(fe).expect("valid field element"));
+            }
+        }
+    }
+
+    fn finish(self, _: &mut PartialSidecar) {}
+
+    fn decode_all(&mut self, blobs: &[Blob]) -> Option<Vec<Vec<u8>>> {
+        blobs.iter().map(|blob| blob.decode_channel_data().ok().map(Vec::from)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_eips::eip4844::builder::SidecarBuilder;
+
+    fn test_data(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[test]
+    fn test_blob_encoder_multiple_blobs() {
+        let data = test_data(2 * BLOB_MAX_DATA_SIZE + 5);
+        let blobs = BlobEncoder::encode(&data);
+        assert_eq!(blobs.len(), 3);
+        assert_eq!(BlobEncoder::blob_count(&data), 3);
+
+        let decoded = BlobEncoder.decode_all(&blobs).unwrap();
+        assert_eq!(
+            decoded.iter().map(Vec::len).collect::<Vec<_>>(),
+            [BLOB_MAX_DATA_SIZE, BLOB_MAX_DATA_SIZE, 5]
+        );
+        assert_eq!(decoded.concat(), data);
+    }
+
+    #[test]
+    fn test_blob_encoder_sidecar_builder() {
+        for len in [0, 1, BLOB_MAX_DATA_SIZE, BLOB_MAX_DATA_SIZE + 1] {
+            let data = test_data(len);
+            let blobs = SidecarBuilder::from_coder_and_data(BlobEncoder, &data).take();
+            assert_eq!(blobs, BlobEncoder::encode(&data), "length {len}");
+        }
+    }
+
+    #[test]
+    fn test_blob_encoder_decode_invalid_blob() {
+        let mut blobs = BlobEncoder::encode(&test_data(10));
+        blobs[0][0] = 0xFF;
+        assert_eq!(BlobEncoder.decode_all(&blobs), None);
+    }
+
+    #[cfg(feature = "kzg")]
+    #[test]
+    fn test_blob_encoder_sidecar() {
+        use alloy_eips::eip4844::env_settings::EnvKzgSettings;
+
+        let data = test_data(BLOB_MAX_DATA_SIZE + 1);
+        let sidecar = BlobEncoder::sidecar(&data).unwrap();
+        assert_eq!(sidecar.blobs, BlobEncoder::encode(&data));
+        assert_eq!(sidecar.commitments.len(), 2);
+        assert_eq!(sidecar.proofs.len(), 2);
+
+        let hashes = sidecar.versioned_hashes().collect::<Vec<_>>();
+        sidecar.validate(&hashes, EnvKzgSettings::Default.get()).unwrap();
+    }
+}
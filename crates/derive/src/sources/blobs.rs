@@ -3,15 +3,15 @@
 use crate::{
     errors::{BlobProviderError, PipelineError},
     sources::BlobData,
-    traits::{BlobProvider, ChainProvider, DataAvailabilityProvider},
-    types::PipelineResult,
+    traits::{BlobProvider, ChainProvider, DataAvailabilityProvider, DataIngestObserver},
+    types::{IngestDecision, IngestRejection, PipelineResult},
 };
-use alloc::{boxed::Box, string::ToString, vec::Vec};
+use alloc::{boxed::Box, string::ToString, sync::Arc, vec::Vec};
 use alloy_consensus::{Transaction, TxEip4844Variant, TxEnvelope, TxType};
 use alloy_eips::eip4844::IndexedBlobHash;
 use alloy_primitives::{Address, Bytes};
 use async_trait::async_trait;
-use maili_protocol::BlockInfo;
+use maili_protocol::{BlockInfo, DERIVATION_VERSION_0};
 
 /// A data iterator that reads from a blob.
 #[derive(Debug, Clone)]
@@ -24,14 +24,17 @@ where
     pub chain_provider: F,
     /// Fetches blobs.
     pub blob_fetcher: B,
-    /// The address of the batcher contract.
-    pub batcher_address: Address,
-    /// The L1 Signer.
-    pub signer: Address,
+    /// The batch inbox address.
+    pub batch_inbox_address: Address,
     /// Data.
     pub data: Vec<BlobData>,
     /// Whether the source is open.
     pub open: bool,
+    /// The observer of the decisions on candidate batcher transactions.
+    ///
+    /// The version byte of blob data is not checked, since the blobs are fetched after the
+    /// transactions are classified.
+    pub observer: Option<Arc<dyn DataIngestObserver>>,
 }
 
 impl<F, B> BlobSource<F, B>
@@ -40,23 +43,36 @@ where
     B: BlobProvider + Send,
 {
     /// Creates a new blob source.
-    pub const fn new(
-        chain_provider: F,
-        blob_fetcher: B,
-        batcher_address: Address,
-        signer: Address,
-    ) -> Self {
+    pub const fn new(chain_provider: F, blob_fetcher: B, batch_inbox_address: Address) -> Self {
         Self {
             chain_provider,
             blob_fetcher,
-            batcher_address,
-            signer,
+            batch_inbox_address,
             data: Vec::new(),
             open: false,
+            observer: None,
+        }
+    }
+
+    /// Sets the [DataIngestObserver] of the blob source.
+    pub fn with_observer(mut self, observer: Arc<dyn DataIngestObserver>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Reports the decision on a candidate batcher transaction to the observer, if any.
+    fn notify(&self, block_ref: &BlockInfo, tx: &TxEnvelope, decision: IngestDecision) {
+        if let Some(observer) = &self.observer {
+            observer.on_transaction(block_ref, *tx.tx_hash(), decision);
         }
     }
 
-    fn extract_blob_data(&self, txs: Vec<TxEnvelope>) -> (Vec<BlobData>, Vec<IndexedBlobHash>) {
+    fn extract_blob_data(
+        &self,
+        block_ref: &BlockInfo,
+        txs: Vec<TxEnvelope>,
+        batcher_address: Address,
+    ) -> (Vec<BlobData>, Vec<IndexedBlobHash>) {
         let mut index: u64 = 0;
         let mut data = Vec::new();
         let mut hashes = Vec::new();
@@ -74,23 +90,37 @@ where
                         (tx.to(), tx.input.clone(), Some(tx.blob_versioned_hashes.clone()))
                     }
                 },
-                _ => continue,
+                _ => {
+                    self.notify(block_ref, &tx, IngestRejection::UnsupportedTxType.into());
+                    continue;
+                }
+            };
+            let Some(to) = tx_kind else {
+                self.notify(block_ref, &tx, IngestRejection::WrongInbox.into());
+                continue;
             };
-            let Some(to) = tx_kind else { continue };
 
-            if to != self.batcher_address {
+            if to != self.batch_inbox_address {
                 index += blob_hashes.map_or(0, |h| h.len() as u64);
+                self.notify(block_ref, &tx, IngestRejection::WrongInbox.into());
                 continue;
             }
-            if tx.recover_signer().unwrap_or_default() != self.signer {
+            if tx.recover_signer().unwrap_or_default() != batcher_address {
                 index += blob_hashes.map_or(0, |h| h.len() as u64);
+                self.notify(block_ref, &tx, IngestRejection::WrongSender.into());
                 continue;
             }
             if tx.tx_type() != TxType::Eip4844 {
+                if calldata.first() != Some(&DERIVATION_VERSION_0) {
+                    self.notify(block_ref, &tx, IngestRejection::BadVersion.into());
+                    continue;
+                }
                 let blob_data = BlobData { data: None, calldata: Some(calldata.to_vec().into()) };
                 data.push(blob_data);
+                self.notify(block_ref, &tx, IngestDecision::Accepted);
                 continue;
             }
+            self.notify(block_ref, &tx, IngestDecision::Accepted);
             if !calldata.is_empty() {
                 let hash = match &tx {
                     TxEnvelope::Legacy(tx) => Some(tx.hash()),
@@ -116,8 +146,12 @@ where
         (data, hashes)
     }
 
-    /// Loads blob data into the source if it is not open.
-    async fn load_blobs(&mut self, block_ref: &BlockInfo) -> Result<(), BlobProviderError> {
+    /// Loads the blob data sent by the batcher into the source if it is not open.
+    async fn load_blobs(
+        &mut self,
+        block_ref: &BlockInfo,
+        batcher_address: Address,
+    ) -> Result<(), BlobProviderError> {
         if self.open {
             return Ok(());
         }
@@ -128,7 +162,7 @@ where
             .await
             .map_err(|e| BlobProviderError::Backend(e.to_string()))?;
 
-        let (mut data, blob_hashes) = self.extract_blob_data(info.1);
+        let (mut data, blob_hashes) = self.extract_blob_data(block_ref, info.1, batcher_address);
 
         // If there are no hashes, set the calldata and return.
         if blob_hashes.is_empty() {
@@ -180,8 +214,12 @@ where
 {
     type Item = Bytes;
 
-    async fn next(&mut self, block_ref: &BlockInfo) -> PipelineResult<Self::Item> {
-        self.load_blobs(block_ref).await?;
+    async fn next(
+        &mut self,
+        block_ref: &BlockInfo,
+        batcher_address: Address,
+    ) -> PipelineResult<Self::Item> {
+        self.load_blobs(block_ref, batcher_address).await?;
 
         let next_data = match self.next_data() {
             Ok(d) => d,
@@ -197,7 +235,7 @@ where
             Ok(d) => Ok(d),
             Err(_) => {
                 warn!(target: "blob-source", "Failed to decode blob data, skipping");
-                self.next(block_ref).await
+                self.next(block_ref, batcher_address).await
             }
         }
     }
@@ -213,16 +251,14 @@ pub(crate) mod tests {
     use super::*;
     use crate::{
         errors::PipelineErrorKind,
-        test_utils::{TestBlobProvider, TestChainProvider},
+        test_utils::{TestBlobProvider, TestChainProvider, TestIngestObserver},
     };
     use alloy_rlp::Decodable;
 
     pub(crate) fn default_test_blob_source() -> BlobSource<TestChainProvider, TestBlobProvider> {
         let chain_provider = TestChainProvider::default();
         let blob_fetcher = TestBlobProvider::default();
-        let batcher_address = Address::default();
-        let signer = Address::default();
-        BlobSource::new(chain_provider, blob_fetcher, batcher_address, signer)
+        BlobSource::new(chain_provider, blob_fetcher, Address::default())
     }
 
     pub(crate) fn valid_blob_txs() -> Vec<TxEnvelope> {
@@ -236,14 +272,14 @@ pub(crate) mod tests {
     async fn test_load_blobs_open() {
         let mut source = default_test_blob_source();
         source.open = true;
-        assert!(source.load_blobs(&BlockInfo::default()).await.is_ok());
+        assert!(source.load_blobs(&BlockInfo::default(), Address::ZERO).await.is_ok());
     }
 
     #[tokio::test]
     async fn test_load_blobs_chain_provider_err() {
         let mut source = default_test_blob_source();
         assert!(matches!(
-            source.load_blobs(&BlockInfo::default()).await,
+            source.load_blobs(&BlockInfo::default(), Address::ZERO).await,
             Err(BlobProviderError::Backend(_))
         ));
     }
@@ -254,7 +290,7 @@ pub(crate) mod tests {
         let block_info = BlockInfo::default();
         source.chain_provider.insert_block_with_transactions(0, block_info, Vec::new());
         assert!(!source.open); // Source is not open by default.
-        assert!(source.load_blobs(&BlockInfo::default()).await.is_ok());
+        assert!(source.load_blobs(&BlockInfo::default(), Address::ZERO).await.is_ok());
         assert!(source.data.is_empty());
         assert!(source.open);
     }
@@ -263,14 +299,14 @@ pub(crate) mod tests {
     async fn test_load_blobs_chain_provider_4844_txs_blob_fetch_error() {
         let mut source = default_test_blob_source();
         let block_info = BlockInfo::default();
-        source.signer = alloy_primitives::address!("A83C816D4f9b2783761a22BA6FADB0eB0606D7B2");
-        source.batcher_address =
+        let signer = alloy_primitives::address!("A83C816D4f9b2783761a22BA6FADB0eB0606D7B2");
+        source.batch_inbox_address =
             alloy_primitives::address!("11E9CA82A3a762b4B5bd264d4173a242e7a77064");
         let txs = valid_blob_txs();
         source.blob_fetcher.should_error = true;
         source.chain_provider.insert_block_with_transactions(1, block_info, txs);
         assert!(matches!(
-            source.load_blobs(&BlockInfo::default()).await,
+            source.load_blobs(&BlockInfo::default(), signer).await,
             Err(BlobProviderError::Backend(_))
         ));
     }
@@ -281,8 +317,8 @@ pub(crate) mod tests {
 
         let mut source = default_test_blob_source();
         let block_info = BlockInfo::default();
-        source.signer = alloy_primitives::address!("A83C816D4f9b2783761a22BA6FADB0eB0606D7B2");
-        source.batcher_address =
+        let signer = alloy_primitives::address!("A83C816D4f9b2783761a22BA6FADB0eB0606D7B2");
+        source.batch_inbox_address =
             alloy_primitives::address!("11E9CA82A3a762b4B5bd264d4173a242e7a77064");
         let txs = valid_blob_txs();
         source.chain_provider.insert_block_with_transactions(1, block_info, txs);
@@ -306,17 +342,48 @@ pub(crate) mod tests {
         for hash in hashes {
             source.blob_fetcher.insert_blob(hash, Blob::with_last_byte(1u8));
         }
-        source.load_blobs(&BlockInfo::default()).await.unwrap();
+        source.load_blobs(&BlockInfo::default(), signer).await.unwrap();
         assert!(source.open);
         assert!(!source.data.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_load_blobs_observer() {
+        let observer = Arc::new(TestIngestObserver::default());
+        let mut source = default_test_blob_source().with_observer(observer.clone());
+        let signer = alloy_primitives::address!("A83C816D4f9b2783761a22BA6FADB0eB0606D7B2");
+        let txs = valid_blob_txs();
+        source.chain_provider.insert_block_with_transactions(1, BlockInfo::default(), txs);
+
+        // The blob transaction is not sent to the batch inbox.
+        source.load_blobs(&BlockInfo::default(), signer).await.unwrap();
+        source.clear();
+
+        // The blob transaction is not sent by the batcher.
+        source.batch_inbox_address =
+            alloy_primitives::address!("11E9CA82A3a762b4B5bd264d4173a242e7a77064");
+        source.load_blobs(&BlockInfo::default(), Address::ZERO).await.unwrap();
+        source.clear();
+
+        // The blob transaction is accepted, before its blobs are fetched.
+        source.blob_fetcher.should_error = true;
+        assert!(source.load_blobs(&BlockInfo::default(), signer).await.is_err());
+        assert_eq!(
+            observer.decisions(),
+            [
+                IngestRejection::WrongInbox.into(),
+                IngestRejection::WrongSender.into(),
+                IngestDecision::Accepted
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn test_open_empty_data_eof() {
         let mut source = default_test_blob_source();
         source.open = true;
 
-        let err = source.next(&BlockInfo::default()).await.unwrap_err();
+        let err = source.next(&BlockInfo::default(), Address::ZERO).await.unwrap_err();
         assert!(matches!(err, PipelineErrorKind::Temporary(PipelineError::Eof)));
     }
 
@@ -326,7 +393,7 @@ pub(crate) mod tests {
         source.open = true;
         source.data.push(BlobData { data: None, calldata: Some(Bytes::default()) });
 
-        let data = source.next(&BlockInfo::default()).await.unwrap();
+        let data = source.next(&BlockInfo::default(), Address::ZERO).await.unwrap();
         assert_eq!(data, Bytes::default());
     }
 
@@ -336,14 +403,14 @@ pub(crate) mod tests {
         source.open = true;
         source.data.push(BlobData { data: Some(Bytes::from(&[1; 32])), calldata: None });
 
-        let err = source.next(&BlockInfo::default()).await.unwrap_err();
+        let err = source.next(&BlockInfo::default(), Address::ZERO).await.unwrap_err();
         assert!(matches!(err, PipelineErrorKind::Temporary(PipelineError::Eof)));
     }
 
     #[tokio::test]
     async fn test_blob_source_pipeline_error() {
         let mut source = default_test_blob_source();
-        let err = source.next(&BlockInfo::default()).await.unwrap_err();
+        let err = source.next(&BlockInfo::default(), Address::ZERO).await.unwrap_err();
         assert!(matches!(err, PipelineErrorKind::Temporary(PipelineError::Provider(_))));
     }
 }
@@ -2,16 +2,21 @@
 
 use crate::{
     errors::PipelineError,
-    traits::{ChainProvider, DataAvailabilityProvider},
-    types::PipelineResult,
+    traits::{ChainProvider, DataAvailabilityProvider, DataIngestObserver},
+    types::{IngestDecision, IngestRejection, PipelineResult},
 };
-use alloc::{boxed::Box, collections::VecDeque};
+use alloc::{boxed::Box, collections::VecDeque, sync::Arc, vec::Vec};
 use alloy_consensus::{Transaction, TxEnvelope};
 use alloy_primitives::{Address, Bytes};
 use async_trait::async_trait;
-use maili_protocol::BlockInfo;
+use maili_protocol::{BlockInfo, DERIVATION_VERSION_0};
+use op_alloy_genesis::RollupConfig;
 
 /// A data iterator that reads from calldata.
+///
+/// The [CalldataSource] only depends on the execution layer of the settlement chain, so it can be
+/// used as the [DataAvailabilityProvider] for chains that settle on an EVM chain without a beacon
+/// chain, such as an L3 settling on an OP Stack L2.
 #[derive(Debug, Clone)]
 pub struct CalldataSource<CP>
 where
@@ -21,22 +26,80 @@ where
     pub chain_provider: CP,
     /// The batch inbox address.
     pub batch_inbox_address: Address,
-    /// The L1 Signer.
-    pub signer: Address,
     /// Current calldata.
     pub calldata: VecDeque<Bytes>,
     /// Whether the calldata source is open.
     pub open: bool,
+    /// The observer of the decisions on candidate batcher transactions.
+    pub observer: Option<Arc<dyn DataIngestObserver>>,
+    /// The versions of batcher data accepted in addition to [DERIVATION_VERSION_0], e.g. the
+    /// version byte of an alt-DA commitment.
+    pub extra_versions: Vec<u8>,
 }
 
 impl<CP: ChainProvider + Send> CalldataSource<CP> {
     /// Creates a new calldata source.
-    pub const fn new(chain_provider: CP, batch_inbox_address: Address, signer: Address) -> Self {
-        Self { chain_provider, batch_inbox_address, signer, calldata: VecDeque::new(), open: false }
+    pub const fn new(chain_provider: CP, batch_inbox_address: Address) -> Self {
+        Self {
+            chain_provider,
+            batch_inbox_address,
+            calldata: VecDeque::new(),
+            open: false,
+            observer: None,
+            extra_versions: Vec::new(),
+        }
+    }
+
+    /// Creates a new calldata source for the batch inbox of the given [RollupConfig].
+    pub const fn new_from_config(chain_provider: CP, cfg: &RollupConfig) -> Self {
+        Self::new(chain_provider, cfg.batch_inbox_address)
+    }
+
+    /// Sets the [DataIngestObserver] of the calldata source.
+    pub fn with_observer(mut self, observer: Arc<dyn DataIngestObserver>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Also accepts batcher data with the given version byte, which is passed on with the version
+    /// byte, for a source that resolves it, e.g. an alt-DA commitment.
+    pub fn with_version(mut self, version: u8) -> Self {
+        self.extra_versions.push(version);
+        self
     }
 
-    /// Loads the calldata into the source if it is not open.
-    async fn load_calldata(&mut self, block_ref: &BlockInfo) -> Result<(), CP::Error> {
+    /// Returns the batcher data of the transaction, or the reason it is rejected.
+    fn batcher_data(
+        &self,
+        tx: &TxEnvelope,
+        batcher_address: Address,
+    ) -> Result<Bytes, IngestRejection> {
+        let (tx_kind, data) = match tx {
+            TxEnvelope::Legacy(tx) => (tx.tx().to(), tx.tx().input()),
+            TxEnvelope::Eip2930(tx) => (tx.tx().to(), tx.tx().input()),
+            TxEnvelope::Eip1559(tx) => (tx.tx().to(), tx.tx().input()),
+            _ => return Err(IngestRejection::UnsupportedTxType),
+        };
+        if tx_kind != Some(self.batch_inbox_address) {
+            return Err(IngestRejection::WrongInbox);
+        }
+        if tx.recover_signer().ok() != Some(batcher_address) {
+            return Err(IngestRejection::WrongSender);
+        }
+        match data.first() {
+            Some(&DERIVATION_VERSION_0) => {}
+            Some(version) if self.extra_versions.contains(version) => {}
+            _ => return Err(IngestRejection::BadVersion),
+        }
+        Ok(data.clone())
+    }
+
+    /// Loads the calldata sent by the batcher into the source if it is not open.
+    async fn load_calldata(
+        &mut self,
+        block_ref: &BlockInfo,
+        batcher_address: Address,
+    ) -> Result<(), CP::Error> {
         if self.open {
             return Ok(());
         }
@@ -47,21 +110,13 @@ impl<CP: ChainProvider + Send> CalldataSource<CP> {
         self.calldata = txs
             .iter()
             .filter_map(|tx| {
-                let (tx_kind, data) = match tx {
-                    TxEnvelope::Legacy(tx) => (tx.tx().to(), tx.tx().input()),
-                    TxEnvelope::Eip2930(tx) => (tx.tx().to(), tx.tx().input()),
-                    TxEnvelope::Eip1559(tx) => (tx.tx().to(), tx.tx().input()),
-                    _ => return None,
-                };
-                let to = tx_kind?;
-
-                if to != self.batch_inbox_address {
-                    return None;
+                let data = self.batcher_data(tx, batcher_address);
+                if let Some(observer) = &self.observer {
+                    let decision =
+                        data.as_ref().map_or_else(|r| (*r).into(), |_| IngestDecision::Accepted);
+                    observer.on_transaction(block_ref, *tx.tx_hash(), decision);
                 }
-                if tx.recover_signer().ok()? != self.signer {
-                    return None;
-                }
-                Some(data.to_vec().into())
+                data.ok()
             })
             .collect::<VecDeque<_>>();
 
@@ -75,8 +130,12 @@ impl<CP: ChainProvider + Send> CalldataSource<CP> {
 impl<CP: ChainProvider + Send> DataAvailabilityProvider for CalldataSource<CP> {
     type Item = Bytes;
 
-    async fn next(&mut self, block_ref: &BlockInfo) -> PipelineResult<Self::Item> {
-        self.load_calldata(block_ref).await.map_err(Into::into)?;
+    async fn next(
+        &mut self,
+        block_ref: &BlockInfo,
+        batcher_address: Address,
+    ) -> PipelineResult<Self::Item> {
+        self.load_calldata(block_ref, batcher_address).await.map_err(Into::into)?;
         self.calldata.pop_front().ok_or(PipelineError::Eof.temp())
     }
 
@@ -89,15 +148,22 @@ impl<CP: ChainProvider + Send> DataAvailabilityProvider for CalldataSource<CP> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{errors::PipelineErrorKind, test_utils::TestChainProvider};
+    use crate::{
+        errors::PipelineErrorKind,
+        test_utils::{TestChainProvider, TestIngestObserver},
+    };
     use alloc::{vec, vec::Vec};
     use alloy_consensus::{Signed, TxEip2930, TxEip4844, TxEip4844Variant, TxEip7702, TxLegacy};
-    use alloy_primitives::{address, Address, PrimitiveSignature as Signature, TxKind};
+    use alloy_primitives::{address, Address, PrimitiveSignature as Signature, TxKind, B256};
 
     pub(crate) fn test_legacy_tx(to: Address) -> TxEnvelope {
         let sig = Signature::test_signature();
         TxEnvelope::Legacy(Signed::new_unchecked(
-            TxLegacy { to: TxKind::Call(to), ..Default::default() },
+            TxLegacy {
+                to: TxKind::Call(to),
+                input: vec![DERIVATION_VERSION_0].into(),
+                ..Default::default()
+            },
             sig,
             Default::default(),
         ))
@@ -106,7 +172,11 @@ mod tests {
     pub(crate) fn test_eip2930_tx(to: Address) -> TxEnvelope {
         let sig = Signature::test_signature();
         TxEnvelope::Eip2930(Signed::new_unchecked(
-            TxEip2930 { to: TxKind::Call(to), ..Default::default() },
+            TxEip2930 {
+                to: TxKind::Call(to),
+                input: vec![DERIVATION_VERSION_0].into(),
+                ..Default::default()
+            },
             sig,
             Default::default(),
         ))
@@ -131,7 +201,7 @@ mod tests {
     }
 
     pub(crate) fn default_test_calldata_source() -> CalldataSource<TestChainProvider> {
-        CalldataSource::new(TestChainProvider::default(), Default::default(), Default::default())
+        CalldataSource::new(TestChainProvider::default(), Default::default())
     }
 
     #[tokio::test]
@@ -148,13 +218,13 @@ mod tests {
     async fn test_load_calldata_open() {
         let mut source = default_test_calldata_source();
         source.open = true;
-        assert!(source.load_calldata(&BlockInfo::default()).await.is_ok());
+        assert!(source.load_calldata(&BlockInfo::default(), Address::ZERO).await.is_ok());
     }
 
     #[tokio::test]
     async fn test_load_calldata_provider_err() {
         let mut source = default_test_calldata_source();
-        assert!(source.load_calldata(&BlockInfo::default()).await.is_err());
+        assert!(source.load_calldata(&BlockInfo::default(), Address::ZERO).await.is_err());
     }
 
     #[tokio::test]
@@ -163,11 +233,19 @@ mod tests {
         let block_info = BlockInfo::default();
         source.chain_provider.insert_block_with_transactions(0, block_info, Vec::new());
         assert!(!source.open); // Source is not open by default.
-        assert!(source.load_calldata(&BlockInfo::default()).await.is_ok());
+        assert!(source.load_calldata(&BlockInfo::default(), Address::ZERO).await.is_ok());
         assert!(source.calldata.is_empty());
         assert!(source.open);
     }
 
+    #[test]
+    fn test_calldata_source_new_from_config() {
+        let batch_inbox_address = address!("0123456789012345678901234567890123456789");
+        let cfg = RollupConfig { batch_inbox_address, ..Default::default() };
+        let source = CalldataSource::new_from_config(TestChainProvider::default(), &cfg);
+        assert_eq!(source.batch_inbox_address, batch_inbox_address);
+    }
+
     #[tokio::test]
     async fn test_load_calldata_wrong_batch_inbox_address() {
         let batch_inbox_address = address!("0123456789012345678901234567890123456789");
@@ -176,7 +254,7 @@ mod tests {
         let tx = test_legacy_tx(batch_inbox_address);
         source.chain_provider.insert_block_with_transactions(0, block_info, vec![tx]);
         assert!(!source.open); // Source is not open by default.
-        assert!(source.load_calldata(&BlockInfo::default()).await.is_ok());
+        assert!(source.load_calldata(&BlockInfo::default(), Address::ZERO).await.is_ok());
         assert!(source.calldata.is_empty());
         assert!(source.open);
     }
@@ -190,7 +268,7 @@ mod tests {
         let tx = test_legacy_tx(batch_inbox_address);
         source.chain_provider.insert_block_with_transactions(0, block_info, vec![tx]);
         assert!(!source.open); // Source is not open by default.
-        assert!(source.load_calldata(&BlockInfo::default()).await.is_ok());
+        assert!(source.load_calldata(&BlockInfo::default(), Address::ZERO).await.is_ok());
         assert!(source.calldata.is_empty());
         assert!(source.open);
     }
@@ -201,11 +279,11 @@ mod tests {
         let mut source = default_test_calldata_source();
         source.batch_inbox_address = batch_inbox_address;
         let tx = test_legacy_tx(batch_inbox_address);
-        source.signer = tx.recover_signer().unwrap();
+        let signer = tx.recover_signer().unwrap();
         let block_info = BlockInfo::default();
         source.chain_provider.insert_block_with_transactions(0, block_info, vec![tx]);
         assert!(!source.open); // Source is not open by default.
-        assert!(source.load_calldata(&BlockInfo::default()).await.is_ok());
+        assert!(source.load_calldata(&BlockInfo::default(), signer).await.is_ok());
         assert!(!source.calldata.is_empty()); // Calldata is NOT empty.
         assert!(source.open);
     }
@@ -216,11 +294,11 @@ mod tests {
         let mut source = default_test_calldata_source();
         source.batch_inbox_address = batch_inbox_address;
         let tx = test_eip2930_tx(batch_inbox_address);
-        source.signer = tx.recover_signer().unwrap();
+        let signer = tx.recover_signer().unwrap();
         let block_info = BlockInfo::default();
         source.chain_provider.insert_block_with_transactions(0, block_info, vec![tx]);
         assert!(!source.open); // Source is not open by default.
-        assert!(source.load_calldata(&BlockInfo::default()).await.is_ok());
+        assert!(source.load_calldata(&BlockInfo::default(), signer).await.is_ok());
         assert!(!source.calldata.is_empty()); // Calldata is NOT empty.
         assert!(source.open);
     }
@@ -231,11 +309,11 @@ mod tests {
         let mut source = default_test_calldata_source();
         source.batch_inbox_address = batch_inbox_address;
         let tx = test_blob_tx(batch_inbox_address);
-        source.signer = tx.recover_signer().unwrap();
+        let signer = tx.recover_signer().unwrap();
         let block_info = BlockInfo::default();
         source.chain_provider.insert_block_with_transactions(0, block_info, vec![tx]);
         assert!(!source.open); // Source is not open by default.
-        assert!(source.load_calldata(&BlockInfo::default()).await.is_ok());
+        assert!(source.load_calldata(&BlockInfo::default(), signer).await.is_ok());
         assert!(source.calldata.is_empty());
         assert!(source.open);
     }
@@ -246,20 +324,66 @@ mod tests {
         let mut source = default_test_calldata_source();
         source.batch_inbox_address = batch_inbox_address;
         let tx = test_eip7702_tx(batch_inbox_address);
-        source.signer = tx.recover_signer().unwrap();
+        let signer = tx.recover_signer().unwrap();
         let block_info = BlockInfo::default();
         source.chain_provider.insert_block_with_transactions(0, block_info, vec![tx]);
         assert!(!source.open); // Source is not open by default.
-        assert!(source.load_calldata(&BlockInfo::default()).await.is_ok());
+        assert!(source.load_calldata(&BlockInfo::default(), signer).await.is_ok());
         assert!(source.calldata.is_empty());
         assert!(source.open);
     }
 
+    #[tokio::test]
+    async fn test_load_calldata_observer() {
+        let batch_inbox_address = address!("0123456789012345678901234567890123456789");
+        let observer = Arc::new(TestIngestObserver::default());
+        let mut source = default_test_calldata_source().with_observer(observer.clone());
+        source.batch_inbox_address = batch_inbox_address;
+        let tx = test_legacy_tx(batch_inbox_address);
+        let signer = tx.recover_signer().unwrap();
+        let txs = vec![tx, test_legacy_tx(Address::ZERO), test_blob_tx(batch_inbox_address)];
+        source.chain_provider.insert_block_with_transactions(0, BlockInfo::default(), txs);
+        source.load_calldata(&BlockInfo::default(), signer).await.unwrap();
+        assert_eq!(source.calldata.len(), 1);
+        assert_eq!(
+            observer.decisions(),
+            [
+                IngestDecision::Accepted,
+                IngestRejection::WrongInbox.into(),
+                IngestRejection::UnsupportedTxType.into()
+            ]
+        );
+
+        // Data without the derivation version byte is rejected.
+        let tx = TxEnvelope::Legacy(Signed::new_unchecked(
+            TxLegacy {
+                to: TxKind::Call(batch_inbox_address),
+                input: vec![1].into(),
+                ..Default::default()
+            },
+            Signature::test_signature(),
+            Default::default(),
+        ));
+        let signer = tx.recover_signer().unwrap();
+        let block_info = BlockInfo { hash: B256::with_last_byte(1), ..Default::default() };
+        source.chain_provider.insert_block_with_transactions(1, block_info, vec![tx]);
+        source.clear();
+        source.load_calldata(&block_info, signer).await.unwrap();
+        assert!(source.calldata.is_empty());
+        assert_eq!(observer.decisions()[3], IngestRejection::BadVersion.into());
+
+        // Unless the version is accepted in addition.
+        let mut source = source.with_version(1);
+        source.clear();
+        source.load_calldata(&block_info, signer).await.unwrap();
+        assert_eq!(source.calldata, [Bytes::from(vec![1])]);
+    }
+
     #[tokio::test]
     async fn test_next_err_loading_calldata() {
         let mut source = default_test_calldata_source();
         assert!(matches!(
-            source.next(&BlockInfo::default()).await,
+            source.next(&BlockInfo::default(), Address::ZERO).await,
             Err(PipelineErrorKind::Temporary(_))
         ));
     }
@@ -0,0 +1,242 @@
+//! Contains the [CelestiaDataSource], which resolves the op-celestia blob references posted by the
+//! batcher into frame data fetched from a Celestia node.
+
+use crate::{
+    sources::CalldataSource,
+    traits::{CelestiaProvider, ChainProvider, DataAvailabilityProvider},
+    types::PipelineResult,
+};
+use alloc::{boxed::Box, vec::Vec};
+use alloy_primitives::{Address, Bytes, FixedBytes, B256};
+use async_trait::async_trait;
+use core::fmt::Debug;
+use maili_protocol::BlockInfo;
+use op_alloy_genesis::RollupConfig;
+
+/// The version byte of batcher data carrying a [CelestiaBlobRef], in place of the
+/// [DERIVATION_VERSION_0] frame data.
+///
+/// [DERIVATION_VERSION_0]: maili_protocol::DERIVATION_VERSION_0
+pub const CELESTIA_DERIVATION_VERSION: u8 = 0xce;
+
+/// A reference to a blob on Celestia, posted to the batch inbox by an op-celestia batcher.
+///
+/// The reference is encoded as the [CELESTIA_DERIVATION_VERSION] byte, followed by the Celestia
+/// block height as a little-endian `u64` and the 32 byte share commitment of the blob. The blob
+/// holds the frame data the batcher would otherwise have posted to L1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CelestiaBlobRef {
+    /// The Celestia block height the blob was included at.
+    pub height: u64,
+    /// The share commitment of the blob.
+    pub commitment: B256,
+}
+
+impl CelestiaBlobRef {
+    /// The length of an encoded reference, without the version byte.
+    pub const ENCODED_LEN: usize = 40;
+
+    /// Decodes a reference from batcher data without its version byte, returning [None] if the
+    /// data is not [Self::ENCODED_LEN] bytes long.
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        if data.len() != Self::ENCODED_LEN {
+            return None;
+        }
+        let height = u64::from_le_bytes(data[..8].try_into().ok()?);
+        Some(Self { height, commitment: B256::from_slice(&data[8..]) })
+    }
+
+    /// Encodes the reference as batcher data, including the version byte.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(1 + Self::ENCODED_LEN);
+        data.push(CELESTIA_DERIVATION_VERSION);
+        data.extend_from_slice(&self.height.to_le_bytes());
+        data.extend_from_slice(self.commitment.as_slice());
+        data
+    }
+}
+
+/// A [DataAvailabilityProvider] for chains using Celestia for data availability, as run by
+/// op-celestia.
+///
+/// Batcher transactions are read from calldata. Data with the [CELESTIA_DERIVATION_VERSION] is
+/// decoded as a [CelestiaBlobRef], and the referenced blob is fetched from the [CelestiaProvider]
+/// in the namespace of the chain. Plain [DERIVATION_VERSION_0] data is passed through, as an
+/// op-celestia batcher falls back to posting frames to L1 when Celestia is unavailable.
+///
+/// A malformed reference is skipped. If the blob cannot be fetched, the reference is kept, so that
+/// the fetch is retried on the next step.
+///
+/// [DERIVATION_VERSION_0]: maili_protocol::DERIVATION_VERSION_0
+#[derive(Debug, Clone)]
+pub struct CelestiaDataSource<C, P>
+where
+    C: ChainProvider + Send,
+    P: CelestiaProvider + Send,
+{
+    /// The calldata source the blob references are read from.
+    pub calldata_source: CalldataSource<C>,
+    /// The Celestia provider.
+    pub celestia_provider: P,
+    /// The namespace the batcher submits blobs to.
+    pub namespace: FixedBytes<29>,
+}
+
+impl<C, P> CelestiaDataSource<C, P>
+where
+    C: ChainProvider + Send,
+    P: CelestiaProvider + Send,
+{
+    /// Creates a new [CelestiaDataSource] for the batch inbox of the given [RollupConfig], which
+    /// fetches blobs in the given namespace.
+    pub fn new(
+        chain_provider: C,
+        celestia_provider: P,
+        namespace: FixedBytes<29>,
+        cfg: &RollupConfig,
+    ) -> Self {
+        Self {
+            calldata_source: CalldataSource::new_from_config(chain_provider, cfg)
+                .with_version(CELESTIA_DERIVATION_VERSION),
+            celestia_provider,
+            namespace,
+        }
+    }
+}
+
+#[async_trait]
+impl<C, P> DataAvailabilityProvider for CelestiaDataSource<C, P>
+where
+    C: ChainProvider + Send + Sync + Debug,
+    P: CelestiaProvider + Send + Sync + Debug,
+{
+    type Item = Bytes;
+
+    async fn next(
+        &mut self,
+        block_ref: &BlockInfo,
+        batcher_address: Address,
+    ) -> PipelineResult<Self::Item> {
+        loop {
+            let data = self.calldata_source.next(block_ref, batcher_address).await?;
+            if data.first() != Some(&CELESTIA_DERIVATION_VERSION) {
+                return Ok(data);
+            }
+
+            let Some(blob_ref) = CelestiaBlobRef::decode(&data[1..]) else {
+                warn!(target: "celestia-source", "Skipping malformed Celestia blob reference");
+                continue;
+            };
+            match self
+                .celestia_provider
+                .blob_get(blob_ref.height, self.namespace, blob_ref.commitment)
+                .await
+            {
+                Ok(blob) => return Ok(blob),
+                Err(e) => {
+                    warn!(
+                        target: "celestia-source",
+                        "Failed to fetch Celestia blob at height {}: {e}",
+                        blob_ref.height
+                    );
+                    self.calldata_source.calldata.push_front(data);
+                    return Err(e.into());
+                }
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.calldata_source.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        errors::PipelineErrorKind,
+        test_utils::{batcher_tx, TestChainProvider, TestProviderError, SIMULATED_BATCHER},
+    };
+    use alloc::{collections::BTreeMap, vec};
+    use alloy_consensus::TxEnvelope;
+    use maili_protocol::DERIVATION_VERSION_0;
+
+    #[derive(Debug, Clone, Default)]
+    struct TestCelestiaProvider {
+        blobs: BTreeMap<(u64, B256), Bytes>,
+    }
+
+    #[async_trait]
+    impl CelestiaProvider for TestCelestiaProvider {
+        type Error = TestProviderError;
+
+        async fn blob_get(
+            &mut self,
+            height: u64,
+            _: FixedBytes<29>,
+            commitment: B256,
+        ) -> Result<Bytes, Self::Error> {
+            self.blobs.get(&(height, commitment)).cloned().ok_or(TestProviderError::BlockNotFound)
+        }
+    }
+
+    fn source(txs: Vec<TxEnvelope>) -> CelestiaDataSource<TestChainProvider, TestCelestiaProvider> {
+        let mut chain_provider = TestChainProvider::default();
+        chain_provider.insert_block_with_transactions(0, BlockInfo::default(), txs);
+        CelestiaDataSource::new(
+            chain_provider,
+            TestCelestiaProvider::default(),
+            FixedBytes::ZERO,
+            &RollupConfig::default(),
+        )
+    }
+
+    #[test]
+    fn test_celestia_blob_ref_roundtrip() {
+        let blob_ref = CelestiaBlobRef { height: 0x0102, commitment: B256::repeat_byte(0xAA) };
+        let encoded = blob_ref.encode();
+        assert_eq!(encoded[..3], [CELESTIA_DERIVATION_VERSION, 0x02, 0x01]);
+        assert_eq!(CelestiaBlobRef::decode(&encoded[1..]), Some(blob_ref));
+        assert_eq!(CelestiaBlobRef::decode(&encoded[2..]), None);
+    }
+
+    #[tokio::test]
+    async fn test_celestia_data_source_next() {
+        let blob_ref = CelestiaBlobRef { height: 7, commitment: B256::repeat_byte(0xAA) };
+        let frames = Bytes::from(vec![DERIVATION_VERSION_0, 0xDD]);
+        let txs = vec![
+            batcher_tx(vec![CELESTIA_DERIVATION_VERSION, 0x01]),
+            batcher_tx(blob_ref.encode()),
+            batcher_tx(frames.to_vec()),
+        ];
+        let signer = SIMULATED_BATCHER;
+        let mut source = source(txs);
+        source.celestia_provider.blobs.insert((7, blob_ref.commitment), frames.clone());
+
+        // The malformed reference is skipped, and the blob and the L1 fallback data are returned.
+        let block_ref = BlockInfo::default();
+        assert_eq!(source.next(&block_ref, signer).await.unwrap(), frames);
+        assert_eq!(source.next(&block_ref, signer).await.unwrap(), frames);
+        assert!(source.next(&block_ref, signer).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_celestia_data_source_unavailable_blob() {
+        let blob_ref = CelestiaBlobRef { height: 7, commitment: B256::repeat_byte(0xAA) };
+        let tx = batcher_tx(blob_ref.encode());
+        let signer = SIMULATED_BATCHER;
+        let mut source = source(vec![tx]);
+
+        let block_ref = BlockInfo::default();
+        assert!(matches!(
+            source.next(&block_ref, signer).await,
+            Err(PipelineErrorKind::Temporary(_))
+        ));
+
+        // The reference is retried once the blob is available.
+        let blob = Bytes::from(vec![DERIVATION_VERSION_0, 0xDD]);
+        source.celestia_provider.blobs.insert((7, blob_ref.commitment), blob.clone());
+        assert_eq!(source.next(&block_ref, signer).await.unwrap(), blob);
+    }
+}
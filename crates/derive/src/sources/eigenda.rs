@@ -0,0 +1,286 @@
+//! Contains the [EigenDADataSource], which resolves the EigenDA certificate commitments posted by
+//! the batcher into frame data fetched from an EigenDA proxy.
+
+use crate::{
+    errors::{AltDAProviderError, EigenDAProviderError},
+    sources::{CalldataSource, Commitment, ALT_DA_DERIVATION_VERSION, GENERIC_COMMITMENT_TYPE},
+    traits::{AltDAProvider, ChainProvider, DataAvailabilityProvider, EigenDAProvider},
+    types::PipelineResult,
+};
+use alloc::{boxed::Box, string::ToString, vec::Vec};
+use alloy_primitives::{Address, Bytes};
+use async_trait::async_trait;
+use core::fmt::Debug;
+use maili_protocol::BlockInfo;
+use op_alloy_genesis::RollupConfig;
+
+/// The DA layer byte of generic commitments to EigenDA.
+pub const EIGENDA_DA_LAYER_BYTE: u8 = 0x00;
+
+/// An EigenDA certificate commitment, posted to the batch inbox by an EigenDA batcher.
+///
+/// The commitment is encoded as the [ALT_DA_DERIVATION_VERSION] byte, the
+/// [GENERIC_COMMITMENT_TYPE] byte and the [EIGENDA_DA_LAYER_BYTE], followed by the version of the
+/// certificate and the certificate itself. The EigenDA proxy serves the blob payload for the
+/// encoding without the derivation version byte.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EigenDACommitment {
+    /// The version of the certificate.
+    pub cert_version: u8,
+    /// The certificate of the blob.
+    pub cert: Bytes,
+}
+
+impl EigenDACommitment {
+    /// Decodes a commitment from batcher data, returning [None] if the data is not a generic
+    /// alt-DA commitment to EigenDA.
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        Self::from_commitment(&Commitment::decode_batcher_data(data).ok()?)
+    }
+
+    /// Returns the EigenDA commitment carried by the given generic alt-DA [Commitment], if it is
+    /// to EigenDA.
+    pub fn from_commitment(commitment: &Commitment) -> Option<Self> {
+        let Commitment::Generic(EIGENDA_DA_LAYER_BYTE, payload) = commitment else {
+            return None;
+        };
+        let (cert_version, cert) = payload.split_first()?;
+        Some(Self { cert_version: *cert_version, cert: Bytes::copy_from_slice(cert) })
+    }
+
+    /// Encodes the commitment as batcher data, including the derivation version byte.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(4 + self.cert.len());
+        data.extend_from_slice(&[
+            ALT_DA_DERIVATION_VERSION,
+            GENERIC_COMMITMENT_TYPE,
+            EIGENDA_DA_LAYER_BYTE,
+            self.cert_version,
+        ]);
+        data.extend_from_slice(&self.cert);
+        data
+    }
+}
+
+/// Adapts an [EigenDAProvider] to an [AltDAProvider], so that it can be registered for the
+/// [EIGENDA_DA_LAYER_BYTE] in an [AltDARegistry].
+///
+/// [AltDARegistry]: crate::sources::AltDARegistry
+#[derive(Debug, Clone)]
+pub struct EigenDAAltDAProvider<P: EigenDAProvider>(pub P);
+
+#[async_trait]
+impl<P: EigenDAProvider + Debug + Send + Sync> AltDAProvider for EigenDAAltDAProvider<P> {
+    async fn get_input(&mut self, commitment: &Commitment) -> Result<Bytes, AltDAProviderError> {
+        let commitment = EigenDACommitment::from_commitment(commitment).ok_or_else(|| {
+            AltDAProviderError::InvalidCommitment("Not an EigenDA commitment".to_string())
+        })?;
+        Ok(self.0.get_blob(&commitment).await?)
+    }
+}
+
+/// A [DataAvailabilityProvider] for chains using EigenDA for data availability.
+///
+/// Batcher transactions are read from calldata. Data with the [ALT_DA_DERIVATION_VERSION] is
+/// decoded as an [EigenDACommitment], and the certified blob is fetched from the
+/// [EigenDAProvider]. Plain [DERIVATION_VERSION_0] data is passed through, as the batcher falls
+/// back to posting frames to L1 when EigenDA is unavailable.
+///
+/// Following the alt-DA derivation rules, commitments that are not to EigenDA, and commitments
+/// whose certificate is invalid, are skipped. If the blob is not available, the commitment is
+/// kept and derivation waits for the blob, retrying the fetch on the next step.
+///
+/// [DERIVATION_VERSION_0]: maili_protocol::DERIVATION_VERSION_0
+#[derive(Debug, Clone)]
+pub struct EigenDADataSource<C, P>
+where
+    C: ChainProvider + Send,
+    P: EigenDAProvider + Send,
+{
+    /// The calldata source the commitments are read from.
+    pub calldata_source: CalldataSource<C>,
+    /// The EigenDA provider.
+    pub eigenda_provider: P,
+}
+
+impl<C, P> EigenDADataSource<C, P>
+where
+    C: ChainProvider + Send,
+    P: EigenDAProvider + Send,
+{
+    /// Creates a new [EigenDADataSource] for the batch inbox of the given [RollupConfig].
+    pub fn new(chain_provider: C, eigenda_provider: P, cfg: &RollupConfig) -> Self {
+        Self {
+            calldata_source: CalldataSource::new_from_config(chain_provider, cfg)
+                .with_version(ALT_DA_DERIVATION_VERSION),
+            eigenda_provider,
+        }
+    }
+}
+
+#[async_trait]
+impl<C, P> DataAvailabilityProvider for EigenDADataSource<C, P>
+where
+    C: ChainProvider + Send + Sync + Debug,
+    P: EigenDAProvider + Send + Sync + Debug,
+{
+    type Item = Bytes;
+
+    async fn next(
+        &mut self,
+        block_ref: &BlockInfo,
+        batcher_address: Address,
+    ) -> PipelineResult<Self::Item> {
+        loop {
+            let data = self.calldata_source.next(block_ref, batcher_address).await?;
+            if data.first() != Some(&ALT_DA_DERIVATION_VERSION) {
+                return Ok(data);
+            }
+
+            let Some(commitment) = EigenDACommitment::decode(&data) else {
+                warn!(target: "eigenda-source", "Skipping alt-DA commitment not to EigenDA");
+                continue;
+            };
+            match self.eigenda_provider.get_blob(&commitment).await {
+                Ok(blob) => return Ok(blob),
+                Err(e @ EigenDAProviderError::InvalidCert(_)) => {
+                    warn!(target: "eigenda-source", "Skipping EigenDA commitment: {e}");
+                }
+                Err(e) => {
+                    warn!(target: "eigenda-source", "Failed to fetch EigenDA blob: {e}");
+                    self.calldata_source.calldata.push_front(data);
+                    return Err(e.into());
+                }
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.calldata_source.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        errors::PipelineErrorKind,
+        sources::AltDARegistry,
+        test_utils::{batcher_tx, TestChainProvider, SIMULATED_BATCHER},
+    };
+    use alloc::vec;
+    use alloy_consensus::TxEnvelope;
+    use maili_protocol::DERIVATION_VERSION_0;
+
+    /// Serves a single blob, failing with the scripted error first if there is one.
+    #[derive(Debug, Clone, Default)]
+    struct TestEigenDAProvider {
+        blob: Option<(EigenDACommitment, Bytes)>,
+        error: Option<EigenDAProviderError>,
+    }
+
+    #[async_trait]
+    impl EigenDAProvider for TestEigenDAProvider {
+        async fn get_blob(
+            &mut self,
+            commitment: &EigenDACommitment,
+        ) -> Result<Bytes, EigenDAProviderError> {
+            if let Some(error) = self.error.take() {
+                return Err(error);
+            }
+            match &self.blob {
+                Some((c, blob)) if c == commitment => Ok(blob.clone()),
+                _ => Err(EigenDAProviderError::InvalidCert("unknown".to_string())),
+            }
+        }
+    }
+
+    fn source(txs: Vec<TxEnvelope>) -> EigenDADataSource<TestChainProvider, TestEigenDAProvider> {
+        let mut chain_provider = TestChainProvider::default();
+        chain_provider.insert_block_with_transactions(0, BlockInfo::default(), txs);
+        EigenDADataSource::new(
+            chain_provider,
+            TestEigenDAProvider::default(),
+            &RollupConfig::default(),
+        )
+    }
+
+    fn commitment(cert: u8) -> EigenDACommitment {
+        EigenDACommitment { cert_version: 0, cert: Bytes::from(vec![cert; 8]) }
+    }
+
+    #[test]
+    fn test_eigenda_commitment_roundtrip() {
+        let encoded = commitment(0xAA).encode();
+        assert_eq!(encoded[..4], [0x01, 0x01, 0x00, 0x00]);
+        assert_eq!(EigenDACommitment::decode(&encoded), Some(commitment(0xAA)));
+
+        // Keccak256 commitments and generic commitments to other DA layers are not decoded.
+        assert_eq!(EigenDACommitment::decode(&[0x01, 0x00, 0xAA]), None);
+        assert_eq!(EigenDACommitment::decode(&[0x01, 0x01, 0x0c, 0x00]), None);
+        assert_eq!(EigenDACommitment::decode(&[0x01, 0x01, 0x00]), None);
+    }
+
+    #[tokio::test]
+    async fn test_eigenda_data_source_next() {
+        let frames = Bytes::from(vec![DERIVATION_VERSION_0, 0xDD]);
+        let txs = vec![
+            batcher_tx(vec![0x01, 0x00, 0xAA]),
+            batcher_tx(commitment(0xBB).encode()),
+            batcher_tx(commitment(0xAA).encode()),
+            batcher_tx(frames.to_vec()),
+        ];
+        let signer = SIMULATED_BATCHER;
+        let mut source = source(txs);
+        source.eigenda_provider.blob = Some((commitment(0xAA), frames.clone()));
+
+        // The commitment not to EigenDA and the invalid certificate are skipped, and the blob and
+        // the L1 fallback data are returned.
+        let block_ref = BlockInfo::default();
+        assert_eq!(source.next(&block_ref, signer).await.unwrap(), frames);
+        assert_eq!(source.next(&block_ref, signer).await.unwrap(), frames);
+        assert!(source.next(&block_ref, signer).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_eigenda_data_source_unavailable_blob() {
+        let tx = batcher_tx(commitment(0xAA).encode());
+        let signer = SIMULATED_BATCHER;
+        let mut source = source(vec![tx]);
+        let blob = Bytes::from(vec![DERIVATION_VERSION_0, 0xDD]);
+        source.eigenda_provider.blob = Some((commitment(0xAA), blob.clone()));
+        source.eigenda_provider.error =
+            Some(EigenDAProviderError::Unavailable("not found".to_string()));
+
+        let block_ref = BlockInfo::default();
+        assert!(matches!(
+            source.next(&block_ref, signer).await,
+            Err(PipelineErrorKind::Temporary(_))
+        ));
+
+        // The commitment is retried once the blob is available.
+        assert_eq!(source.next(&block_ref, signer).await.unwrap(), blob);
+    }
+
+    #[tokio::test]
+    async fn test_eigenda_alt_da_provider() {
+        let blob = Bytes::from(vec![DERIVATION_VERSION_0, 0xDD]);
+        let provider = TestEigenDAProvider {
+            blob: Some((commitment(0xAA), blob.clone())),
+            ..Default::default()
+        };
+        let mut registry = AltDARegistry::new()
+            .with_generic(EIGENDA_DA_LAYER_BYTE, EigenDAAltDAProvider(provider));
+
+        let generic = Commitment::decode_batcher_data(&commitment(0xAA).encode()).unwrap();
+        let provider = registry.provider(&generic).unwrap();
+        assert_eq!(provider.get_input(&generic).await.unwrap(), blob);
+
+        // Invalid certificates are reported as invalid commitments.
+        let generic = Commitment::decode_batcher_data(&commitment(0xBB).encode()).unwrap();
+        assert!(matches!(
+            provider.get_input(&generic).await,
+            Err(AltDAProviderError::InvalidCommitment(_))
+        ));
+    }
+}
@@ -0,0 +1,387 @@
+//! Contains type-erased providers, which allow the providers of a pipeline to be chosen at
+//! runtime.
+//!
+//! The provider traits are object safe, but their associated error types must be named in a
+//! trait object, and the pipeline requires its providers to be [Clone]. The boxed providers in
+//! this module erase both: they wrap any provider whose errors convert into a
+//! [PipelineErrorKind], report errors as [PipelineErrorKind], and clone the wrapped provider.
+
+use crate::{
+    errors::PipelineErrorKind,
+    traits::{BlobProvider, ChainProvider, L1BlockTag, L2ChainProvider},
+};
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+use alloy_consensus::{Header, Receipt, TxEnvelope};
+use alloy_eips::eip4844::{Blob, IndexedBlobHash};
+use alloy_primitives::{Address, B256};
+use async_trait::async_trait;
+use core::fmt::Debug;
+use maili_protocol::{BatchValidationProvider, BlockInfo, L2BlockInfo};
+use op_alloy_consensus::OpBlock;
+use op_alloy_genesis::{RollupConfig, SystemConfig};
+
+/// The object safe form of a clonable [ChainProvider], with errors erased to
+/// [PipelineErrorKind].
+#[async_trait]
+trait ErasedChainProvider: Debug + Send + Sync {
+    fn clone_box(&self) -> Box<dyn ErasedChainProvider>;
+
+    async fn header_by_hash(&mut self, hash: B256) -> Result<Header, PipelineErrorKind>;
+
+    async fn block_info_by_number(&mut self, number: u64) -> Result<BlockInfo, PipelineErrorKind>;
+
+    async fn block_info_by_tag(
+        &mut self,
+        tag: L1BlockTag,
+    ) -> Result<Option<BlockInfo>, PipelineErrorKind>;
+
+    async fn receipts_by_hash(&mut self, hash: B256) -> Result<Vec<Receipt>, PipelineErrorKind>;
+
+    async fn filtered_receipts_by_hash(
+        &mut self,
+        hash: B256,
+        address: Address,
+    ) -> Result<Vec<Receipt>, PipelineErrorKind>;
+
+    async fn block_info_and_transactions_by_hash(
+        &mut self,
+        hash: B256,
+    ) -> Result<(BlockInfo, Vec<TxEnvelope>), PipelineErrorKind>;
+}
+
+#[async_trait]
+impl<P> ErasedChainProvider for P
+where
+    P: ChainProvider + Clone + Debug + Send + Sync + 'static,
+{
+    fn clone_box(&self) -> Box<dyn ErasedChainProvider> {
+        Box::new(self.clone())
+    }
+
+    async fn header_by_hash(&mut self, hash: B256) -> Result<Header, PipelineErrorKind> {
+        ChainProvider::header_by_hash(self, hash).await.map_err(Into::into)
+    }
+
+    async fn block_info_by_number(&mut self, number: u64) -> Result<BlockInfo, PipelineErrorKind> {
+        ChainProvider::block_info_by_number(self, number).await.map_err(Into::into)
+    }
+
+    async fn block_info_by_tag(
+        &mut self,
+        tag: L1BlockTag,
+    ) -> Result<Option<BlockInfo>, PipelineErrorKind> {
+        ChainProvider::block_info_by_tag(self, tag).await.map_err(Into::into)
+    }
+
+    async fn receipts_by_hash(&mut self, hash: B256) -> Result<Vec<Receipt>, PipelineErrorKind> {
+        ChainProvider::receipts_by_hash(self, hash).await.map_err(Into::into)
+    }
+
+    async fn filtered_receipts_by_hash(
+        &mut self,
+        hash: B256,
+        address: Address,
+    ) -> Result<Vec<Receipt>, PipelineErrorKind> {
+        ChainProvider::filtered_receipts_by_hash(self, hash, address).await.map_err(Into::into)
+    }
+
+    async fn block_info_and_transactions_by_hash(
+        &mut self,
+        hash: B256,
+    ) -> Result<(BlockInfo, Vec<TxEnvelope>), PipelineErrorKind> {
+        ChainProvider::block_info_and_transactions_by_hash(self, hash).await.map_err(Into::into)
+    }
+}
+
+/// A type-erased [ChainProvider].
+#[derive(Debug)]
+pub struct BoxedChainProvider(Box<dyn ErasedChainProvider>);
+
+impl BoxedChainProvider {
+    /// Erases the type of the given [ChainProvider].
+    pub fn new<P>(provider: P) -> Self
+    where
+        P: ChainProvider + Clone + Debug + Send + Sync + 'static,
+    {
+        Self(Box::new(provider))
+    }
+}
+
+impl Clone for BoxedChainProvider {
+    fn clone(&self) -> Self {
+        Self(self.0.clone_box())
+    }
+}
+
+#[async_trait]
+impl ChainProvider for BoxedChainProvider {
+    type Error = PipelineErrorKind;
+
+    async fn header_by_hash(&mut self, hash: B256) -> Result<Header, Self::Error> {
+        self.0.header_by_hash(hash).await
+    }
+
+    async fn block_info_by_number(&mut self, number: u64) -> Result<BlockInfo, Self::Error> {
+        self.0.block_info_by_number(number).await
+    }
+
+    async fn block_info_by_tag(
+        &mut self,
+        tag: L1BlockTag,
+    ) -> Result<Option<BlockInfo>, Self::Error> {
+        self.0.block_info_by_tag(tag).await
+    }
+
+    async fn receipts_by_hash(&mut self, hash: B256) -> Result<Vec<Receipt>, Self::Error> {
+        self.0.receipts_by_hash(hash).await
+    }
+
+    async fn filtered_receipts_by_hash(
+        &mut self,
+        hash: B256,
+        address: Address,
+    ) -> Result<Vec<Receipt>, Self::Error> {
+        self.0.filtered_receipts_by_hash(hash, address).await
+    }
+
+    async fn block_info_and_transactions_by_hash(
+        &mut self,
+        hash: B256,
+    ) -> Result<(BlockInfo, Vec<TxEnvelope>), Self::Error> {
+        self.0.block_info_and_transactions_by_hash(hash).await
+    }
+}
+
+/// The object safe form of a clonable [BlobProvider], with errors erased to
+/// [PipelineErrorKind].
+#[async_trait]
+trait ErasedBlobProvider: Debug + Send + Sync {
+    fn clone_box(&self) -> Box<dyn ErasedBlobProvider>;
+
+    async fn get_blobs(
+        &mut self,
+        block_ref: &BlockInfo,
+        blob_hashes: &[IndexedBlobHash],
+    ) -> Result<Vec<Box<Blob>>, PipelineErrorKind>;
+}
+
+#[async_trait]
+impl<B> ErasedBlobProvider for B
+where
+    B: BlobProvider + Clone + Debug + Send + Sync + 'static,
+{
+    fn clone_box(&self) -> Box<dyn ErasedBlobProvider> {
+        Box::new(self.clone())
+    }
+
+    async fn get_blobs(
+        &mut self,
+        block_ref: &BlockInfo,
+        blob_hashes: &[IndexedBlobHash],
+    ) -> Result<Vec<Box<Blob>>, PipelineErrorKind> {
+        BlobProvider::get_blobs(self, block_ref, blob_hashes).await.map_err(Into::into)
+    }
+}
+
+/// A type-erased [BlobProvider].
+#[derive(Debug)]
+pub struct BoxedBlobProvider(Box<dyn ErasedBlobProvider>);
+
+impl BoxedBlobProvider {
+    /// Erases the type of the given [BlobProvider].
+    pub fn new<B>(provider: B) -> Self
+    where
+        B: BlobProvider + Clone + Debug + Send + Sync + 'static,
+    {
+        Self(Box::new(provider))
+    }
+}
+
+impl Clone for BoxedBlobProvider {
+    fn clone(&self) -> Self {
+        Self(self.0.clone_box())
+    }
+}
+
+#[async_trait]
+impl BlobProvider for BoxedBlobProvider {
+    type Error = PipelineErrorKind;
+
+    async fn get_blobs(
+        &mut self,
+        block_ref: &BlockInfo,
+        blob_hashes: &[IndexedBlobHash],
+    ) -> Result<Vec<Box<Blob>>, Self::Error> {
+        self.0.get_blobs(block_ref, blob_hashes).await
+    }
+}
+
+/// The object safe form of a clonable [L2ChainProvider], with errors erased to
+/// [PipelineErrorKind].
+#[async_trait]
+trait ErasedL2ChainProvider: Debug + Send + Sync {
+    fn clone_box(&self) -> Box<dyn ErasedL2ChainProvider>;
+
+    async fn l2_block_info_by_number(
+        &mut self,
+        number: u64,
+    ) -> Result<L2BlockInfo, PipelineErrorKind>;
+
+    async fn block_by_number(&mut self, number: u64) -> Result<OpBlock, PipelineErrorKind>;
+
+    async fn system_config_by_number(
+        &mut self,
+        number: u64,
+        rollup_config: Arc<RollupConfig>,
+    ) -> Result<SystemConfig, PipelineErrorKind>;
+
+    async fn l2_block_infos_by_range(
+        &mut self,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<L2BlockInfo>, PipelineErrorKind>;
+}
+
+#[async_trait]
+impl<P> ErasedL2ChainProvider for P
+where
+    P: L2ChainProvider + Clone + Debug + Send + Sync + 'static,
+    <P as BatchValidationProvider>::Error: Into<PipelineErrorKind>,
+{
+    fn clone_box(&self) -> Box<dyn ErasedL2ChainProvider> {
+        Box::new(self.clone())
+    }
+
+    async fn l2_block_info_by_number(
+        &mut self,
+        number: u64,
+    ) -> Result<L2BlockInfo, PipelineErrorKind> {
+        BatchValidationProvider::l2_block_info_by_number(self, number).await.map_err(Into::into)
+    }
+
+    async fn block_by_number(&mut self, number: u64) -> Result<OpBlock, PipelineErrorKind> {
+        BatchValidationProvider::block_by_number(self, number).await.map_err(Into::into)
+    }
+
+    async fn system_config_by_number(
+        &mut self,
+        number: u64,
+        rollup_config: Arc<RollupConfig>,
+    ) -> Result<SystemConfig, PipelineErrorKind> {
+        L2ChainProvider::system_config_by_number(self, number, rollup_config)
+            .await
+            .map_err(Into::into)
+    }
+
+    async fn l2_block_infos_by_range(
+        &mut self,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<L2BlockInfo>, PipelineErrorKind> {
+        L2ChainProvider::l2_block_infos_by_range(self, start, end).await.map_err(Into::into)
+    }
+}
+
+/// A type-erased [L2ChainProvider].
+#[derive(Debug)]
+pub struct BoxedL2ChainProvider(Box<dyn ErasedL2ChainProvider>);
+
+impl BoxedL2ChainProvider {
+    /// Erases the type of the given [L2ChainProvider].
+    pub fn new<P>(provider: P) -> Self
+    where
+        P: L2ChainProvider + Clone + Debug + Send + Sync + 'static,
+        <P as BatchValidationProvider>::Error: Into<PipelineErrorKind>,
+    {
+        Self(Box::new(provider))
+    }
+}
+
+impl Clone for BoxedL2ChainProvider {
+    fn clone(&self) -> Self {
+        Self(self.0.clone_box())
+    }
+}
+
+#[async_trait]
+impl BatchValidationProvider for BoxedL2ChainProvider {
+    type Error = PipelineErrorKind;
+
+    async fn l2_block_info_by_number(&mut self, number: u64) -> Result<L2BlockInfo, Self::Error> {
+        self.0.l2_block_info_by_number(number).await
+    }
+
+    async fn block_by_number(&mut self, number: u64) -> Result<OpBlock, Self::Error> {
+        self.0.block_by_number(number).await
+    }
+}
+
+#[async_trait]
+impl L2ChainProvider for BoxedL2ChainProvider {
+    type Error = PipelineErrorKind;
+
+    async fn system_config_by_number(
+        &mut self,
+        number: u64,
+        rollup_config: Arc<RollupConfig>,
+    ) -> Result<SystemConfig, <Self as L2ChainProvider>::Error> {
+        self.0.system_config_by_number(number, rollup_config).await
+    }
+
+    async fn l2_block_infos_by_range(
+        &mut self,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<L2BlockInfo>, <Self as BatchValidationProvider>::Error> {
+        self.0.l2_block_infos_by_range(start, end).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // The erased traits are not imported, so that method calls resolve to the provider traits.
+    use super::{BoxedChainProvider, BoxedL2ChainProvider};
+    use crate::{
+        errors::PipelineError,
+        test_utils::{TestChainProvider, TestL2ChainProvider},
+        traits::{ChainProvider, L1BlockTag, L2ChainProvider},
+    };
+    use alloc::{sync::Arc, vec, vec::Vec};
+    use alloy_primitives::B256;
+    use maili_protocol::{BatchValidationProvider, BlockInfo, L2BlockInfo};
+
+    #[tokio::test]
+    async fn test_boxed_chain_provider() {
+        let block = BlockInfo { number: 1, hash: B256::with_last_byte(1), ..Default::default() };
+        let mut inner = TestChainProvider::default();
+        inner.insert_block(1, block);
+
+        let mut provider = BoxedChainProvider::new(inner);
+        let mut cloned = provider.clone();
+        assert_eq!(provider.block_info_by_number(1).await.unwrap(), block);
+        assert_eq!(cloned.block_info_by_number(1).await.unwrap(), block);
+        assert_eq!(provider.block_info_by_tag(L1BlockTag::Safe).await.unwrap(), None);
+        assert_eq!(
+            provider.block_info_by_number(2).await.unwrap_err(),
+            PipelineError::Provider("Block not found".into()).temp()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_boxed_l2_chain_provider() {
+        let blocks = (0..3)
+            .map(|number| L2BlockInfo {
+                block_info: BlockInfo { number, ..Default::default() },
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        let inner = TestL2ChainProvider { blocks: blocks.clone(), ..Default::default() };
+
+        let mut provider = BoxedL2ChainProvider::new(inner).clone();
+        assert_eq!(provider.l2_block_info_by_number(2).await.unwrap(), blocks[2]);
+        assert_eq!(provider.l2_block_infos_by_range(0, 1).await.unwrap(), blocks[..=1]);
+        assert!(provider.block_by_number(0).await.is_err());
+        assert!(provider.system_config_by_number(0, Arc::new(Default::default())).await.is_err());
+        assert_eq!(provider.l2_block_infos_by_range(2, 1).await.unwrap(), vec![]);
+    }
+}
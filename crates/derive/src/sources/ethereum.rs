@@ -3,11 +3,11 @@
 
 use crate::{
     sources::{BlobSource, CalldataSource},
-    traits::{BlobProvider, ChainProvider, DataAvailabilityProvider},
+    traits::{BlobProvider, ChainProvider, DataAvailabilityProvider, DataIngestObserver},
     types::PipelineResult,
 };
-use alloc::{boxed::Box, fmt::Debug};
-use alloy_primitives::Bytes;
+use alloc::{boxed::Box, fmt::Debug, sync::Arc};
+use alloy_primitives::{Address, Bytes};
 use async_trait::async_trait;
 use maili_protocol::BlockInfo;
 use op_alloy_genesis::RollupConfig;
@@ -43,14 +43,19 @@ where
 
     /// Instantiates a new [EthereumDataSource] from parts.
     pub fn new_from_parts(provider: C, blobs: B, cfg: &RollupConfig) -> Self {
-        let signer =
-            cfg.genesis.system_config.as_ref().map(|sc| sc.batcher_address).unwrap_or_default();
         Self {
             ecotone_timestamp: cfg.ecotone_time,
-            blob_source: BlobSource::new(provider.clone(), blobs, cfg.batch_inbox_address, signer),
-            calldata_source: CalldataSource::new(provider, cfg.batch_inbox_address, signer),
+            blob_source: BlobSource::new(provider.clone(), blobs, cfg.batch_inbox_address),
+            calldata_source: CalldataSource::new(provider, cfg.batch_inbox_address),
         }
     }
+
+    /// Sets the [DataIngestObserver] of both the blob and calldata sources.
+    pub fn with_observer(mut self, observer: Arc<dyn DataIngestObserver>) -> Self {
+        self.blob_source.observer = Some(observer.clone());
+        self.calldata_source.observer = Some(observer);
+        self
+    }
 }
 
 #[async_trait]
@@ -61,13 +66,17 @@ where
 {
     type Item = Bytes;
 
-    async fn next(&mut self, block_ref: &BlockInfo) -> PipelineResult<Self::Item> {
+    async fn next(
+        &mut self,
+        block_ref: &BlockInfo,
+        batcher_address: Address,
+    ) -> PipelineResult<Self::Item> {
         let ecotone_enabled =
             self.ecotone_timestamp.map(|e| block_ref.timestamp >= e).unwrap_or(false);
         if ecotone_enabled {
-            self.blob_source.next(block_ref).await
+            self.blob_source.next(block_ref, batcher_address).await
         } else {
-            self.calldata_source.next(block_ref).await
+            self.calldata_source.next(block_ref, batcher_address).await
         }
     }
 
@@ -93,9 +102,7 @@ mod tests {
     fn default_test_blob_source() -> BlobSource<TestChainProvider, TestBlobProvider> {
         let chain_provider = TestChainProvider::default();
         let blob_fetcher = TestBlobProvider::default();
-        let batcher_address = Address::default();
-        let signer = Address::default();
-        BlobSource::new(chain_provider, blob_fetcher, batcher_address, signer)
+        BlobSource::new(chain_provider, blob_fetcher, Address::default())
     }
 
     #[tokio::test]
@@ -103,10 +110,10 @@ mod tests {
         let chain = TestChainProvider::default();
         let blob = TestBlobProvider::default();
         let cfg = RollupConfig::default();
-        let mut calldata = CalldataSource::new(chain.clone(), Address::ZERO, Address::ZERO);
+        let mut calldata = CalldataSource::new(chain.clone(), Address::ZERO);
         calldata.calldata.insert(0, Default::default());
         calldata.open = true;
-        let mut blob = BlobSource::new(chain, blob, Address::ZERO, Address::ZERO);
+        let mut blob = BlobSource::new(chain, blob, Address::ZERO);
         blob.data = vec![Default::default()];
         blob.open = true;
         let mut data_source = EthereumDataSource::new(blob, calldata, &cfg);
@@ -124,12 +131,12 @@ mod tests {
         let mut blob = default_test_blob_source();
         blob.open = true;
         blob.data.push(BlobData { data: None, calldata: Some(Bytes::default()) });
-        let calldata = CalldataSource::new(chain.clone(), Address::ZERO, Address::ZERO);
+        let calldata = CalldataSource::new(chain.clone(), Address::ZERO);
         let cfg = RollupConfig { ecotone_time: Some(0), ..Default::default() };
 
         // Should successfully retrieve a blob batch from the block
         let mut data_source = EthereumDataSource::new(blob, calldata, &cfg);
-        let data = data_source.next(&BlockInfo::default()).await.unwrap();
+        let data = data_source.next(&BlockInfo::default(), Address::ZERO).await.unwrap();
         assert_eq!(data, Bytes::default());
     }
 
@@ -152,7 +159,7 @@ mod tests {
 
         // Should successfully retrieve a calldata batch from the block
         let mut data_source = EthereumDataSource::new_from_parts(chain, blob, &cfg);
-        let calldata_batch = data_source.next(&block_ref).await.unwrap();
+        let calldata_batch = data_source.next(&block_ref, batcher_address).await.unwrap();
         assert_eq!(calldata_batch.len(), 119823);
     }
 }
@@ -8,7 +8,10 @@
 //! [BlockInfo]: maili_protocol::BlockInfo
 
 mod blob_data;
-pub use blob_data::BlobData;
+pub use blob_data::{BlobChannelData, BlobData, BLOB_MAX_DATA_SIZE};
+
+mod blob_encoder;
+pub use blob_encoder::BlobEncoder;
 
 mod ethereum;
 pub use ethereum::EthereumDataSource;
@@ -18,3 +21,37 @@ pub use blobs::BlobSource;
 
 mod calldata;
 pub use calldata::CalldataSource;
+
+#[cfg(feature = "celestia")]
+mod celestia;
+#[cfg(feature = "celestia")]
+pub use celestia::{CelestiaBlobRef, CelestiaDataSource, CELESTIA_DERIVATION_VERSION};
+
+mod eigenda;
+pub use eigenda::{
+    EigenDAAltDAProvider, EigenDACommitment, EigenDADataSource, EIGENDA_DA_LAYER_BYTE,
+};
+
+mod alt_da;
+pub use alt_da::{
+    AltDADataSource, AltDARegistry, Commitment, ALT_DA_DERIVATION_VERSION, GENERIC_COMMITMENT_TYPE,
+    KECCAK256_COMMITMENT_TYPE,
+};
+
+mod alt_da_challenge;
+pub use alt_da_challenge::{
+    ChallengeStatus, ChallengeStatusChanged, DAChallengeConfig, DAChallengeWatcher,
+    CHALLENGE_STATUS_CHANGED_TOPIC,
+};
+
+mod archive;
+pub use archive::{ArchiveChainProvider, ArchiveIndex};
+
+mod shared;
+pub use shared::{SharedBlobProvider, SharedCacheStats, SharedChainProvider};
+
+mod erased;
+pub use erased::{BoxedBlobProvider, BoxedChainProvider, BoxedL2ChainProvider};
+
+mod system_config;
+pub use system_config::SystemConfigReplayer;
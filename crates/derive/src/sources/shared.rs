@@ -0,0 +1,273 @@
+//! Contains the [SharedChainProvider] and [SharedBlobProvider], which deduplicate L1 requests
+//! across pipelines that derive multiple L2 chains from the same L1 chain.
+
+use crate::traits::{BlobProvider, ChainProvider, L1BlockTag};
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+use alloy_consensus::{Header, Receipt, TxEnvelope};
+use alloy_eips::eip4844::{Blob, IndexedBlobHash};
+use alloy_primitives::B256;
+use async_trait::async_trait;
+use core::num::NonZeroUsize;
+use lru::LruCache;
+use maili_protocol::BlockInfo;
+use spin::Mutex;
+
+/// Hit and miss counters of a shared provider cache.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SharedCacheStats {
+    /// The number of requests served from the cache.
+    pub hits: u64,
+    /// The number of requests forwarded to the inner provider.
+    pub misses: u64,
+}
+
+/// The hash-keyed responses cached by a [SharedChainProvider].
+#[derive(Debug)]
+struct ChainCache {
+    headers: LruCache<B256, Header>,
+    receipts: LruCache<B256, Vec<Receipt>>,
+    blocks: LruCache<B256, (BlockInfo, Vec<TxEnvelope>)>,
+    stats: SharedCacheStats,
+}
+
+/// A [ChainProvider] wrapper whose clones share a cache of L1 responses.
+///
+/// Each pipeline in a [PipelineSet] is given a clone of the same [SharedChainProvider], so that
+/// headers, receipts and transactions requested by more than one pipeline are only fetched from
+/// the inner provider once. Only hash-keyed requests are cached, block lookups by number are
+/// always forwarded so that L1 reorgs are observed, as are L1 head tag lookups.
+///
+/// [PipelineSet]: crate::pipeline::PipelineSet
+#[derive(Debug, Clone)]
+pub struct SharedChainProvider<P: ChainProvider> {
+    /// The inner chain provider.
+    inner: P,
+    /// The spin-locked cache shared between clones.
+    cache: Arc<Mutex<ChainCache>>,
+}
+
+impl<P: ChainProvider> SharedChainProvider<P> {
+    /// Creates a new [SharedChainProvider] that caches up to `cache_size` responses of each kind.
+    pub fn new(inner: P, cache_size: usize) -> Self {
+        let size = NonZeroUsize::new(cache_size).expect("cache_size must be greater than 0");
+        Self {
+            inner,
+            cache: Arc::new(Mutex::new(ChainCache {
+                headers: LruCache::new(size),
+                receipts: LruCache::new(size),
+                blocks: LruCache::new(size),
+                stats: SharedCacheStats::default(),
+            })),
+        }
+    }
+
+    /// Returns the [SharedCacheStats] of the shared cache.
+    pub fn stats(&self) -> SharedCacheStats {
+        self.cache.lock().stats
+    }
+
+    /// Clears the shared cache.
+    pub fn clear(&self) {
+        let mut cache = self.cache.lock();
+        cache.headers.clear();
+        cache.receipts.clear();
+        cache.blocks.clear();
+    }
+}
+
+#[async_trait]
+impl<P: ChainProvider + Send> ChainProvider for SharedChainProvider<P> {
+    type Error = P::Error;
+
+    async fn header_by_hash(&mut self, hash: B256) -> Result<Header, Self::Error> {
+        {
+            let mut cache = self.cache.lock();
+            if let Some(header) = cache.headers.get(&hash).cloned() {
+                cache.stats.hits += 1;
+                return Ok(header);
+            }
+            cache.stats.misses += 1;
+        }
+        let header = self.inner.header_by_hash(hash).await?;
+        self.cache.lock().headers.put(hash, header.clone());
+        Ok(header)
+    }
+
+    async fn block_info_by_number(&mut self, number: u64) -> Result<BlockInfo, Self::Error> {
+        self.inner.block_info_by_number(number).await
+    }
+
+    async fn block_info_by_tag(
+        &mut self,
+        tag: L1BlockTag,
+    ) -> Result<Option<BlockInfo>, Self::Error> {
+        self.inner.block_info_by_tag(tag).await
+    }
+
+    async fn receipts_by_hash(&mut self, hash: B256) -> Result<Vec<Receipt>, Self::Error> {
+        {
+            let mut cache = self.cache.lock();
+            if let Some(receipts) = cache.receipts.get(&hash).cloned() {
+                cache.stats.hits += 1;
+                return Ok(receipts);
+            }
+            cache.stats.misses += 1;
+        }
+        let receipts = self.inner.receipts_by_hash(hash).await?;
+        self.cache.lock().receipts.put(hash, receipts.clone());
+        Ok(receipts)
+    }
+
+    async fn block_info_and_transactions_by_hash(
+        &mut self,
+        hash: B256,
+    ) -> Result<(BlockInfo, Vec<TxEnvelope>), Self::Error> {
+        {
+            let mut cache = self.cache.lock();
+            if let Some(block) = cache.blocks.get(&hash).cloned() {
+                cache.stats.hits += 1;
+                return Ok(block);
+            }
+            cache.stats.misses += 1;
+        }
+        let block = self.inner.block_info_and_transactions_by_hash(hash).await?;
+        self.cache.lock().blocks.put(hash, block.clone());
+        Ok(block)
+    }
+}
+
+/// The blobs cached by a [SharedBlobProvider].
+#[derive(Debug)]
+struct BlobCache {
+    blobs: LruCache<B256, Box<Blob>>,
+    stats: SharedCacheStats,
+}
+
+/// A [BlobProvider] wrapper whose clones share a cache of blobs, keyed by versioned hash.
+///
+/// A request is served from the cache only if all of the requested blobs are cached, otherwise
+/// the full request is forwarded to the inner provider.
+#[derive(Debug, Clone)]
+pub struct SharedBlobProvider<B: BlobProvider> {
+    /// The inner blob provider.
+    inner: B,
+    /// The spin-locked cache shared between clones.
+    cache: Arc<Mutex<BlobCache>>,
+}
+
+impl<B: BlobProvider> SharedBlobProvider<B> {
+    /// Creates a new [SharedBlobProvider] that caches up to `cache_size` blobs.
+    pub fn new(inner: B, cache_size: usize) -> Self {
+        let size = NonZeroUsize::new(cache_size).expect("cache_size must be greater than 0");
+        Self {
+            inner,
+            cache: Arc::new(Mutex::new(BlobCache {
+                blobs: LruCache::new(size),
+                stats: SharedCacheStats::default(),
+            })),
+        }
+    }
+
+    /// Returns the [SharedCacheStats] of the shared cache.
+    pub fn stats(&self) -> SharedCacheStats {
+        self.cache.lock().stats
+    }
+
+    /// Clears the shared cache.
+    pub fn clear(&self) {
+        self.cache.lock().blobs.clear();
+    }
+}
+
+#[async_trait]
+impl<B: BlobProvider + Send> BlobProvider for SharedBlobProvider<B> {
+    type Error = B::Error;
+
+    async fn get_blobs(
+        &mut self,
+        block_ref: &BlockInfo,
+        blob_hashes: &[IndexedBlobHash],
+    ) -> Result<Vec<Box<Blob>>, Self::Error> {
+        {
+            let mut cache = self.cache.lock();
+            let cached = blob_hashes
+                .iter()
+                .map(|h| cache.blobs.get(&h.hash).cloned())
+                .collect::<Option<Vec<_>>>();
+            if let Some(blobs) = cached {
+                cache.stats.hits += 1;
+                return Ok(blobs);
+            }
+            cache.stats.misses += 1;
+        }
+        let blobs = self.inner.get_blobs(block_ref, blob_hashes).await?;
+        if blobs.len() == blob_hashes.len() {
+            let mut cache = self.cache.lock();
+            for (hash, blob) in blob_hashes.iter().zip(blobs.iter()) {
+                cache.blobs.put(hash.hash, blob.clone());
+            }
+        }
+        Ok(blobs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{TestBlobProvider, TestChainProvider};
+
+    #[tokio::test]
+    async fn test_shared_chain_provider_dedup() {
+        let header = Header { number: 1, ..Default::default() };
+        let hash = header.hash_slow();
+        let mut inner = TestChainProvider::default();
+        inner.insert_header(hash, header.clone());
+        inner.insert_receipts(hash, Vec::new());
+
+        let mut first = SharedChainProvider::new(inner, 16);
+        let mut second = first.clone();
+        assert_eq!(first.header_by_hash(hash).await.unwrap(), header);
+        assert_eq!(first.receipts_by_hash(hash).await.unwrap(), Vec::new());
+
+        // The second provider is served from the shared cache, even if the inner provider fails.
+        second.inner.push_error(crate::test_utils::TestProviderError::HeaderNotFound);
+        assert_eq!(second.header_by_hash(hash).await.unwrap(), header);
+        assert_eq!(second.receipts_by_hash(hash).await.unwrap(), Vec::new());
+        assert_eq!(second.stats(), SharedCacheStats { hits: 2, misses: 2 });
+
+        // Block lookups by number are never cached.
+        assert!(second.block_info_by_number(0).await.is_err());
+
+        first.clear();
+        assert!(first.receipts_by_hash(B256::ZERO).await.is_err());
+        assert_eq!(first.stats().misses, 3);
+    }
+
+    #[tokio::test]
+    async fn test_shared_blob_provider_dedup() {
+        let hashes = [
+            IndexedBlobHash { index: 0, hash: B256::with_last_byte(1) },
+            IndexedBlobHash { index: 1, hash: B256::with_last_byte(2) },
+        ];
+        let mut inner = TestBlobProvider::default();
+        inner.insert_blob(hashes[0].hash, Blob::with_last_byte(1));
+        inner.insert_blob(hashes[1].hash, Blob::with_last_byte(2));
+
+        let mut first = SharedBlobProvider::new(inner, 16);
+        let mut second = first.clone();
+        let blobs = first.get_blobs(&BlockInfo::default(), &hashes).await.unwrap();
+        assert_eq!(blobs.len(), 2);
+
+        second.inner.should_error = true;
+        assert_eq!(
+            second.get_blobs(&BlockInfo::default(), &hashes[1..]).await.unwrap(),
+            blobs[1..]
+        );
+        assert_eq!(second.stats(), SharedCacheStats { hits: 1, misses: 1 });
+
+        // Partially cached requests are forwarded to the inner provider.
+        let missing =
+            [hashes[0].clone(), IndexedBlobHash { index: 2, hash: B256::with_last_byte(3) }];
+        assert!(second.get_blobs(&BlockInfo::default(), &missing).await.is_err());
+    }
+}
@@ -0,0 +1,197 @@
+//! Contains the [SystemConfigReplayer], which reconstructs the [SystemConfig] at any L1 block.
+
+use crate::{errors::SystemConfigReplayError, traits::ChainProvider, types::update_system_config};
+use alloc::{collections::BTreeMap, string::ToString, sync::Arc};
+use op_alloy_genesis::{RollupConfig, SystemConfig};
+
+/// Reconstructs the [SystemConfig] at arbitrary L1 block numbers by replaying the system config
+/// update logs emitted after the nearest known checkpoint.
+///
+/// The replayer is seeded with the genesis system config of the [RollupConfig] at the genesis L1
+/// block, and caches the config of every replayed block that emitted updates, along with the
+/// config of every queried block. Later queries only replay the blocks past the nearest cached
+/// checkpoint.
+///
+/// The config at an L1 block includes the updates emitted in that block, matching the config the
+/// [L1Traversal] stage holds once it has advanced to the block. Blocks are fetched by number, so
+/// the cache must be [invalidated](SystemConfigReplayer::invalidate) when the L1 chain reorgs.
+///
+/// [L1Traversal]: crate::stages::L1Traversal
+#[derive(Debug, Clone)]
+pub struct SystemConfigReplayer<P: ChainProvider> {
+    /// The chain provider.
+    provider: P,
+    /// The rollup config.
+    cfg: Arc<RollupConfig>,
+    /// The [SystemConfig] checkpoints, keyed by the L1 block number they are the config of.
+    checkpoints: BTreeMap<u64, SystemConfig>,
+}
+
+impl<P: ChainProvider + Send> SystemConfigReplayer<P> {
+    /// Creates a new [SystemConfigReplayer], seeded with the genesis system config of the given
+    /// [RollupConfig], if any.
+    pub fn new(provider: P, cfg: Arc<RollupConfig>) -> Self {
+        let mut checkpoints = BTreeMap::new();
+        if let Some(genesis) = cfg.genesis.system_config {
+            checkpoints.insert(cfg.genesis.l1.number, genesis);
+        }
+        Self { provider, cfg, checkpoints }
+    }
+
+    /// Inserts a trusted checkpoint, e.g. the system config of a finalized L2 block, from which
+    /// later blocks are replayed.
+    pub fn insert_checkpoint(&mut self, l1_block_number: u64, system_config: SystemConfig) {
+        self.checkpoints.insert(l1_block_number, system_config);
+    }
+
+    /// Removes all cached checkpoints at or above the given L1 block number, e.g. after an L1
+    /// reorg.
+    pub fn invalidate(&mut self, l1_block_number: u64) {
+        self.checkpoints.split_off(&l1_block_number);
+    }
+
+    /// Returns the number of cached checkpoints.
+    pub fn checkpoints(&self) -> usize {
+        self.checkpoints.len()
+    }
+
+    /// Returns the [SystemConfig] at the given L1 block number.
+    pub async fn system_config_at(
+        &mut self,
+        l1_block_number: u64,
+    ) -> Result<SystemConfig, SystemConfigReplayError> {
+        let (checkpoint, mut config) = self
+            .checkpoints
+            .range(..=l1_block_number)
+            .next_back()
+            .map(|(n, c)| (*n, *c))
+            .ok_or(SystemConfigReplayError::MissingCheckpoint(l1_block_number))?;
+
+        for number in checkpoint + 1..=l1_block_number {
+            let block = self
+                .provider
+                .block_info_by_number(number)
+                .await
+                .map_err(|e| SystemConfigReplayError::Provider(e.to_string()))?;
+            let receipts = self
+                .provider
+                .filtered_receipts_by_hash(block.hash, self.cfg.l1_system_config_address)
+                .await
+                .map_err(|e| SystemConfigReplayError::Provider(e.to_string()))?;
+            let updates = update_system_config(
+                &mut config,
+                &receipts,
+                self.cfg.l1_system_config_address,
+                self.cfg.is_ecotone_active(block.timestamp),
+            )?;
+            if !updates.is_empty() {
+                self.checkpoints.insert(number, config);
+            }
+        }
+
+        self.checkpoints.insert(l1_block_number, config);
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{TestChainProvider, TestProviderError};
+    use alloc::vec;
+    use alloy_consensus::Receipt;
+    use alloy_primitives::{address, Address, Log, LogData, B256, U256};
+    use maili_protocol::BlockInfo;
+    use op_alloy_genesis::{ChainGenesis, CONFIG_UPDATE_EVENT_VERSION_0, CONFIG_UPDATE_TOPIC};
+
+    const SYSTEM_CONFIG: Address = address!("1337000000000000000000000000000000000000");
+
+    fn batcher_update_receipt(batcher: Address) -> Receipt {
+        let data = [
+            U256::from(32).to_be_bytes::<32>(),
+            U256::from(32).to_be_bytes::<32>(),
+            batcher.into_word().0,
+        ]
+        .concat();
+        let log = Log {
+            address: SYSTEM_CONFIG,
+            data: LogData::new_unchecked(
+                vec![CONFIG_UPDATE_TOPIC, CONFIG_UPDATE_EVENT_VERSION_0, B256::ZERO],
+                data.into(),
+            ),
+        };
+        Receipt {
+            status: alloy_consensus::Eip658Value::Eip658(true),
+            logs: vec![log],
+            ..Default::default()
+        }
+    }
+
+    /// Returns a replayer over L1 blocks 0 to 4, with a batcher rotation in block 2.
+    fn test_replayer(
+        genesis_batcher: Address,
+        batcher: Address,
+    ) -> SystemConfigReplayer<TestChainProvider> {
+        let mut provider = TestChainProvider::default();
+        for number in 0..5 {
+            let hash = B256::with_last_byte(number as u8 + 1);
+            provider.insert_block(number, BlockInfo { number, hash, ..Default::default() });
+            let receipts = if number == 2 { vec![batcher_update_receipt(batcher)] } else { vec![] };
+            provider.insert_receipts(hash, receipts);
+        }
+        let cfg = RollupConfig {
+            l1_system_config_address: SYSTEM_CONFIG,
+            genesis: ChainGenesis {
+                system_config: Some(SystemConfig {
+                    batcher_address: genesis_batcher,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        SystemConfigReplayer::new(provider, Arc::new(cfg))
+    }
+
+    #[tokio::test]
+    async fn test_system_config_replayer() {
+        let (genesis, rotated) = (Address::with_last_byte(1), Address::with_last_byte(2));
+        let mut replayer = test_replayer(genesis, rotated);
+
+        assert_eq!(replayer.system_config_at(0).await.unwrap().batcher_address, genesis);
+        assert_eq!(replayer.system_config_at(1).await.unwrap().batcher_address, genesis);
+        assert_eq!(replayer.system_config_at(2).await.unwrap().batcher_address, rotated);
+        assert_eq!(replayer.system_config_at(4).await.unwrap().batcher_address, rotated);
+        assert_eq!(replayer.checkpoints(), 4);
+
+        // Cached blocks are served without fetching, and later blocks resume from the nearest
+        // checkpoint.
+        replayer.provider.push_error(TestProviderError::BlockNotFound);
+        assert_eq!(replayer.system_config_at(2).await.unwrap().batcher_address, rotated);
+        assert!(replayer.system_config_at(3).await.is_err());
+        assert_eq!(replayer.system_config_at(3).await.unwrap().batcher_address, rotated);
+    }
+
+    #[tokio::test]
+    async fn test_system_config_replayer_checkpoints() {
+        let (genesis, rotated) = (Address::with_last_byte(1), Address::with_last_byte(2));
+        let mut replayer = test_replayer(genesis, rotated);
+
+        // Replaying past a trusted checkpoint starts from the checkpoint.
+        let checkpoint =
+            SystemConfig { batcher_address: Address::with_last_byte(3), ..Default::default() };
+        replayer.insert_checkpoint(2, checkpoint);
+        assert_eq!(replayer.system_config_at(3).await.unwrap(), checkpoint);
+
+        // Invalidated blocks are replayed again.
+        replayer.invalidate(1);
+        assert_eq!(replayer.checkpoints(), 1);
+        assert_eq!(replayer.system_config_at(3).await.unwrap().batcher_address, rotated);
+
+        replayer.invalidate(0);
+        assert_eq!(
+            replayer.system_config_at(3).await.unwrap_err(),
+            SystemConfigReplayError::MissingCheckpoint(3)
+        );
+    }
+}
@@ -4,9 +4,9 @@ use crate::{
     errors::{PipelineError, ResetError},
     traits::{
         AttributesBuilder, AttributesProvider, NextAttributes, OriginAdvancer, OriginProvider,
-        SignalReceiver,
+        SignalReceiver, StageSnapshot,
     },
-    types::{PipelineResult, Signal},
+    types::{PipelineResult, PipelineSnapshot, Signal},
 };
 use alloc::{boxed::Box, sync::Arc};
 use async_trait::async_trait;
@@ -14,6 +14,7 @@ use core::fmt::Debug;
 use maili_protocol::{BlockInfo, L2BlockInfo, SingleBatch};
 use op_alloy_genesis::RollupConfig;
 use op_alloy_rpc_types_engine::{OpAttributesWithParent, OpPayloadAttributes};
+use tracing::Instrument;
 
 /// [AttributesQueue] accepts batches from the [BatchQueue] stage
 /// and transforms them into [OpPayloadAttributes].
@@ -59,7 +60,11 @@ where
     /// Loads a [SingleBatch] from the [AttributesProvider] if needed.
     pub async fn load_batch(&mut self, parent: L2BlockInfo) -> PipelineResult<SingleBatch> {
         if self.batch.is_none() {
-            let batch = self.prev.next_batch(parent).await?;
+            let batch = self
+                .prev
+                .next_batch(parent)
+                .instrument(debug_span!(target: "attributes-queue", "next_batch"))
+                .await?;
             self.batch = Some(batch);
             self.is_last_in_span = self.prev.is_last_in_span();
         }
@@ -114,7 +119,13 @@ where
 
         // Prepare the payload attributes
         let tx_count = batch.transactions.len();
-        let mut attributes = self.builder.prepare_payload_attributes(parent, batch.epoch()).await?;
+        let span = debug_span!(
+            target: "attributes-queue",
+            "prepare_payload_attributes",
+            epoch = batch.epoch().number,
+        );
+        let mut attributes =
+            self.builder.prepare_payload_attributes(parent, batch.epoch()).instrument(span).await?;
         attributes.no_tx_pool = Some(true);
         match attributes.transactions {
             Some(ref mut txs) => txs.extend(batch.transactions),
@@ -170,6 +181,22 @@ where
     }
 }
 
+impl<P, AB> StageSnapshot for AttributesQueue<P, AB>
+where
+    P: AttributesProvider
+        + OriginAdvancer
+        + OriginProvider
+        + SignalReceiver
+        + StageSnapshot
+        + Debug,
+    AB: AttributesBuilder + Debug,
+{
+    fn snapshot(&self, snapshot: &mut PipelineSnapshot) {
+        snapshot.batches += usize::from(self.batch.is_some());
+        self.prev.snapshot(snapshot);
+    }
+}
+
 #[async_trait]
 impl<P, AB> SignalReceiver for AttributesQueue<P, AB>
 where
@@ -4,8 +4,11 @@ use super::NextBatchProvider;
 use crate::{
     errors::PipelineError,
     stages::{BatchQueue, BatchValidator},
-    traits::{AttributesProvider, L2ChainProvider, OriginAdvancer, OriginProvider, SignalReceiver},
-    types::{PipelineResult, Signal},
+    traits::{
+        AttributesProvider, L2ChainProvider, OriginAdvancer, OriginProvider, SignalReceiver,
+        StageSnapshot,
+    },
+    types::{PipelineResult, PipelineSnapshot, Signal},
 };
 use alloc::{boxed::Box, sync::Arc};
 use async_trait::async_trait;
@@ -128,6 +131,22 @@ where
     }
 }
 
+impl<P, F> StageSnapshot for BatchProvider<P, F>
+where
+    P: NextBatchProvider + OriginAdvancer + OriginProvider + SignalReceiver + StageSnapshot + Debug,
+    F: L2ChainProvider + Clone + Debug,
+{
+    fn snapshot(&self, snapshot: &mut PipelineSnapshot) {
+        if let Some(batch_validator) = self.batch_validator.as_ref() {
+            batch_validator.snapshot(snapshot);
+        } else if let Some(batch_queue) = self.batch_queue.as_ref() {
+            batch_queue.snapshot(snapshot);
+        } else if let Some(prev) = self.prev.as_ref() {
+            prev.snapshot(snapshot);
+        }
+    }
+}
+
 #[async_trait]
 impl<P, F> SignalReceiver for BatchProvider<P, F>
 where
@@ -1,10 +1,13 @@
 //! This module contains the `BatchQueue` stage implementation.
 
-use super::NextBatchProvider;
+use super::{sequencing_window_action, NextBatchProvider, SequencingWindowAction};
 use crate::{
     errors::{PipelineEncodingError, PipelineError, PipelineErrorKind, ResetError},
-    traits::{AttributesProvider, L2ChainProvider, OriginAdvancer, OriginProvider, SignalReceiver},
-    types::{PipelineResult, ResetSignal, Signal},
+    traits::{
+        AttributesProvider, L2ChainProvider, OriginAdvancer, OriginProvider, SignalReceiver,
+        StageSnapshot,
+    },
+    types::{PipelineResult, PipelineSnapshot, ResetSignal, Signal},
 };
 use alloc::{boxed::Box, sync::Arc, vec::Vec};
 use async_trait::async_trait;
@@ -13,6 +16,7 @@ use maili_protocol::{
     Batch, BatchValidity, BatchWithInclusionBlock, BlockInfo, L2BlockInfo, SingleBatch,
 };
 use op_alloy_genesis::RollupConfig;
+use tracing::Instrument;
 
 /// [BatchQueue] is responsible for o rdering unordered batches
 /// and generating empty batches when the sequence window has passed.
@@ -125,21 +129,24 @@ where
         let origin = self.origin.ok_or(PipelineError::MissingOrigin.crit())?;
 
         // Go over all batches, in order of inclusion, and find the first batch we can accept.
-        // Filter in-place by only remembering the batches that may be processed in the future, or
-        // any undecided ones.
-        let mut remaining = Vec::new();
-        for i in 0..self.batches.len() {
-            let batch = &self.batches[i];
-            let validity =
-                batch.check_batch(&self.cfg, &self.l1_blocks, parent, &mut self.fetcher).await;
+        // Filter in-place by only removing the batches that are dropped or accepted, keeping the
+        // ones that may be processed in the future, or any undecided ones.
+        // The batches stay in the queue while they are checked, so that none are lost if this
+        // future is dropped, and are moved out rather than cloned once accepted.
+        let mut i = 0;
+        while i < self.batches.len() {
+            let validity = self.batches[i]
+                .check_batch(&self.cfg, &self.l1_blocks, parent, &mut self.fetcher)
+                .await;
             match validity {
                 BatchValidity::Future => {
                     // Drop Future batches post-holocene.
                     //
                     // See: <https://specs.optimism.io/protocol/holocene/derivation.html#batch-queue>
                     if !self.cfg.is_holocene_active(origin.timestamp) {
-                        remaining.push(batch.clone());
+                        i += 1;
                     } else {
+                        self.batches.remove(i);
                         self.prev.flush();
                         warn!(target: "batch-queue", "[HOLOCENE] Dropping future batch with parent: {}", parent.block_info.number);
                     }
@@ -147,20 +154,17 @@ where
                 BatchValidity::Drop => {
                     // If we drop a batch, flush previous batches buffered in the BatchStream
                     // stage.
+                    self.batches.remove(i);
                     self.prev.flush();
                     warn!(target: "batch-queue", "Dropping batch with parent: {}", parent.block_info);
-                    continue;
                 }
                 BatchValidity::Accept => {
-                    next_batch = Some(batch.clone());
-                    // Don't keep the current batch in the remaining items since we are processing
-                    // it now, but retain every batch we didn't get to yet.
-                    remaining.extend_from_slice(&self.batches[i + 1..]);
+                    // Don't keep the current batch in the queue since we are processing it now,
+                    // but retain every batch we didn't get to yet.
+                    next_batch = Some(self.batches.remove(i));
                     break;
                 }
                 BatchValidity::Undecided => {
-                    remaining.extend_from_slice(&self.batches[i..]);
-                    self.batches = remaining;
                     return Err(PipelineError::Eof.temp());
                 }
                 BatchValidity::Past => {
@@ -169,12 +173,11 @@ where
                         return Err(PipelineError::InvalidBatchValidity.crit());
                     }
 
+                    self.batches.remove(i);
                     warn!(target: "batch-queue", "[HOLOCENE] Dropping outdated batch with parent: {}", parent.block_info.number);
-                    continue;
                 }
             }
         }
-        self.batches = remaining;
 
         if let Some(nb) = next_batch {
             info!(target: "batch-queue", "Next batch found for timestamp {}", nb.batch.timestamp());
@@ -183,47 +186,34 @@ where
 
         // If the current epoch is too old compared to the L1 block we are at,
         // i.e. if the sequence window expired, we create empty batches for the current epoch
-        let expiry_epoch = epoch.number + self.cfg.seq_window_size;
-        let force_empty_batches =
-            (expiry_epoch == origin.number && empty) || expiry_epoch < origin.number;
-        let first_of_epoch = epoch.number == parent.l1_origin.number + 1;
-
-        // If the sequencer window did not expire,
-        // there is still room to receive batches for the current epoch.
-        // No need to force-create empty batch(es) towards the next epoch yet.
-        if !force_empty_batches {
-            return Err(PipelineError::Eof.temp());
-        }
-
-        info!(
-            target: "batch-queue",
-            "Generating empty batches for epoch: {} | parent: {}",
-            epoch.number, parent.l1_origin.number
-        );
-
-        // The next L1 block is needed to proceed towards the next epoch.
-        if self.l1_blocks.len() < 2 {
-            return Err(PipelineError::Eof.temp());
-        }
-
-        let next_epoch = self.l1_blocks[1];
-
-        // Fill with empty L2 blocks of the same epoch until we meet the time of the next L1 origin,
-        // to preserve that L2 time >= L1 time. If this is the first block of the epoch, always
-        // generate a batch to ensure that we at least have one batch per epoch.
-        if next_timestamp < next_epoch.timestamp || first_of_epoch {
-            info!(target: "batch-queue", "Generating empty batch for epoch: {}", epoch.number);
-            return Ok(Batch::Single(SingleBatch {
-                parent_hash: parent.block_info.hash,
-                epoch_num: epoch.number,
-                epoch_hash: epoch.hash,
-                timestamp: next_timestamp,
-                transactions: Vec::new(),
-            }));
+        match sequencing_window_action(&self.cfg, &self.l1_blocks, origin.number, empty, &parent) {
+            // There is still room to receive batches for the current epoch.
+            SequencingWindowAction::Wait => return Err(PipelineError::Eof.temp()),
+            // The next L1 block is needed to proceed towards the next epoch.
+            SequencingWindowAction::NeedNextOrigin => {
+                info!(
+                    target: "batch-queue",
+                    "Sequencing window expired for epoch {}, waiting for the next L1 origin",
+                    epoch.number
+                );
+                return Err(PipelineError::Eof.temp());
+            }
+            SequencingWindowAction::EmptyBatch => {
+                info!(target: "batch-queue", "Generating empty batch for epoch: {}", epoch.number);
+                return Ok(Batch::Single(SingleBatch {
+                    parent_hash: parent.block_info.hash,
+                    epoch_num: epoch.number,
+                    epoch_hash: epoch.hash,
+                    timestamp: next_timestamp,
+                    transactions: Vec::new(),
+                }));
+            }
+            SequencingWindowAction::AdvanceEpoch => {}
         }
 
         // At this point we have auto generated every batch for the current epoch
         // that we can, so we can advance to the next epoch.
+        let next_epoch = self.l1_blocks[1];
         info!(
             target: "batch-queue",
             "Advancing to next epoch: {}, timestamp: {}, epoch timestamp: {}",
@@ -344,7 +334,12 @@ where
 
         // Load more data into the batch queue.
         let mut out_of_data = false;
-        match self.prev.next_batch(parent, &self.l1_blocks).await {
+        match self
+            .prev
+            .next_batch(parent, &self.l1_blocks)
+            .instrument(debug_span!(target: "batch-queue", "next_batch"))
+            .await
+        {
             Ok(b) => {
                 if !origin_behind {
                     self.add_batch(b, parent).await.ok();
@@ -428,6 +423,17 @@ where
     }
 }
 
+impl<P, BF> StageSnapshot for BatchQueue<P, BF>
+where
+    P: NextBatchProvider + OriginAdvancer + OriginProvider + SignalReceiver + StageSnapshot + Debug,
+    BF: L2ChainProvider + Debug,
+{
+    fn snapshot(&self, snapshot: &mut PipelineSnapshot) {
+        snapshot.batches += self.batches.len() + self.next_spans.len();
+        self.prev.snapshot(snapshot);
+    }
+}
+
 #[async_trait]
 impl<P, BF> SignalReceiver for BatchQueue<P, BF>
 where
@@ -781,6 +787,41 @@ mod tests {
         assert_eq!(bq.l1_blocks.len(), 1);
     }
 
+    #[tokio::test]
+    async fn test_derive_next_batch_sequence_window_expired() {
+        let cfg =
+            Arc::new(RollupConfig { seq_window_size: 2, block_time: 2, ..Default::default() });
+        let mock = TestNextBatchProvider::new(vec![]);
+        let mut bq = BatchQueue::new(cfg, mock, TestL2ChainProvider::default());
+        let next_epoch = BlockInfo { number: 1, timestamp: 10, ..Default::default() };
+        bq.l1_blocks = vec![BlockInfo::default(), next_epoch];
+        let parent = L2BlockInfo::default();
+
+        // The origin is at the end of the sequencing window, but hasn't been fully read yet.
+        bq.origin = Some(BlockInfo { number: 2, ..Default::default() });
+        assert_eq!(
+            bq.derive_next_batch(false, parent).await.unwrap_err(),
+            PipelineError::Eof.temp()
+        );
+
+        // Without batcher data, deposit-only batches fill the epoch up to the next L1 origin.
+        let expected = Batch::Single(SingleBatch { timestamp: 2, ..Default::default() });
+        assert_eq!(bq.derive_next_batch(true, parent).await.unwrap(), expected);
+        bq.origin = Some(BlockInfo { number: 3, ..Default::default() });
+        assert_eq!(bq.derive_next_batch(false, parent).await.unwrap(), expected);
+
+        // Once the L2 time reaches the next L1 origin, the queue advances to the next epoch.
+        let parent = L2BlockInfo {
+            block_info: BlockInfo { timestamp: 8, ..Default::default() },
+            ..Default::default()
+        };
+        assert_eq!(
+            bq.derive_next_batch(false, parent).await.unwrap_err(),
+            PipelineError::Eof.temp()
+        );
+        assert_eq!(bq.l1_blocks, vec![next_epoch]);
+    }
+
     #[tokio::test]
     async fn test_derive_next_batch_future_batch() {
         // Construct a future single batch.
@@ -2,17 +2,18 @@
 
 use crate::{
     errors::{PipelineEncodingError, PipelineError},
-    stages::NextBatchProvider,
-    traits::{L2ChainProvider, OriginAdvancer, OriginProvider, SignalReceiver},
-    types::{PipelineResult, Signal},
+    stages::{check_tx_equivalence, NextBatchProvider, SpanBatchIter, SpanBatchLimits},
+    traits::{L2ChainProvider, OriginAdvancer, OriginProvider, SignalReceiver, StageSnapshot},
+    types::{PipelineResult, PipelineSnapshot, Signal},
 };
-use alloc::{boxed::Box, collections::VecDeque, sync::Arc};
+use alloc::{boxed::Box, sync::Arc};
 use async_trait::async_trait;
 use core::fmt::Debug;
 use maili_protocol::{
-    Batch, BatchValidity, BatchWithInclusionBlock, BlockInfo, L2BlockInfo, SingleBatch, SpanBatch,
+    Batch, BatchValidity, BatchWithInclusionBlock, BlockInfo, L2BlockInfo, SingleBatch,
 };
 use op_alloy_genesis::RollupConfig;
+use tracing::Instrument;
 
 /// Provides [Batch]es for the [BatchStream] stage.
 #[async_trait]
@@ -41,15 +42,18 @@ where
 {
     /// The previous stage in the derivation pipeline.
     prev: P,
-    /// There can only be a single staged span batch.
-    span: Option<SpanBatch>,
-    /// A buffer of single batches derived from the [SpanBatch].
-    buffer: VecDeque<SingleBatch>,
+    /// There can only be a single staged span batch, which is lazily converted into single
+    /// batches.
+    span: Option<SpanBatchIter>,
     /// A reference to the rollup config, used to check
     /// if the [BatchStream] stage should be activated.
     config: Arc<RollupConfig>,
     /// Used to validate the batches.
     fetcher: BF,
+    /// The limits enforced when converting span batches into single batches.
+    limits: SpanBatchLimits,
+    /// Whether to verify the transactions reconstructed from span batches.
+    tx_equivalence_checks: bool,
 }
 
 impl<P, BF> BatchStream<P, BF>
@@ -59,7 +63,30 @@ where
 {
     /// Create a new [BatchStream] stage.
     pub const fn new(prev: P, config: Arc<RollupConfig>, fetcher: BF) -> Self {
-        Self { prev, span: None, buffer: VecDeque::new(), config, fetcher }
+        Self {
+            prev,
+            span: None,
+            config,
+            fetcher,
+            limits: SpanBatchLimits::UNBOUNDED,
+            tx_equivalence_checks: false,
+        }
+    }
+
+    /// Sets the [SpanBatchLimits] enforced when converting span batches into single batches.
+    pub const fn with_span_batch_limits(mut self, limits: SpanBatchLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Enables the re-encoding equivalence checks of the transactions reconstructed from span
+    /// batches. When enabled, every transaction must re-encode to its reconstructed bytes, and
+    /// the transactions of each block are compared against the L2 block, if the fetcher has it.
+    ///
+    /// See [check_tx_equivalence].
+    pub const fn with_tx_equivalence_checks(mut self, enabled: bool) -> Self {
+        self.tx_equivalence_checks = enabled;
+        self
     }
 
     /// Returns if the [BatchStream] stage is active based on the
@@ -69,33 +96,38 @@ where
         Ok(self.config.is_holocene_active(origin.timestamp))
     }
 
-    /// Gets a [SingleBatch] from the in-memory buffer.
-    pub fn get_single_batch(
-        &mut self,
-        parent: L2BlockInfo,
-        l1_origins: &[BlockInfo],
-    ) -> PipelineResult<SingleBatch> {
-        trace!(target: "batch_span", "Attempting to get a SingleBatch from buffer len: {}", self.buffer.len());
-
-        self.try_hydrate_buffer(parent, l1_origins)?;
-        self.buffer.pop_front().ok_or_else(|| PipelineError::NotEnoughData.temp())
-    }
+    /// Gets the next [SingleBatch] out of the staged span batch.
+    ///
+    /// A span batch that exceeds the [SpanBatchLimits] is invalid, so it is dropped and the
+    /// stage is flushed.
+    pub fn get_single_batch(&mut self) -> PipelineResult<SingleBatch> {
+        let Some(span) = self.span.as_mut() else {
+            return Err(PipelineError::NotEnoughData.temp());
+        };
+        trace!(target: "batch_span", "Attempting to get a SingleBatch from span batch, remaining: {}", span.remaining());
 
-    /// Hydrates the buffer with single batches derived from the span batch, if there is one
-    /// queued up.
-    pub fn try_hydrate_buffer(
-        &mut self,
-        parent: L2BlockInfo,
-        l1_origins: &[BlockInfo],
-    ) -> PipelineResult<()> {
-        if let Some(span) = self.span.take() {
-            self.buffer.extend(
-                span.get_singular_batches(l1_origins, parent).map_err(|e| {
-                    PipelineError::BadEncoding(PipelineEncodingError::from(e)).crit()
-                })?,
-            );
+        match span.next() {
+            Some(Ok(batch)) => {
+                if span.remaining() == 0 {
+                    self.span = None;
+                }
+                Ok(batch)
+            }
+            Some(Err(PipelineEncodingError::SpanBatchLimitError(e))) => {
+                warn!(target: "batch-stream", "Dropping span batch: {e}");
+                self.prev.flush();
+                self.span = None;
+                Err(PipelineError::NotEnoughData.temp())
+            }
+            Some(Err(e)) => {
+                self.span = None;
+                Err(PipelineError::BadEncoding(e).crit())
+            }
+            None => {
+                self.span = None;
+                Err(PipelineError::NotEnoughData.temp())
+            }
         }
-        Ok(())
     }
 }
 
@@ -109,12 +141,11 @@ where
         if self.is_active().unwrap_or(false) {
             self.prev.flush();
             self.span = None;
-            self.buffer.clear();
         }
     }
 
     fn span_buffer_size(&self) -> usize {
-        self.buffer.len()
+        self.span.as_ref().map_or(0, SpanBatchIter::remaining)
     }
 
     async fn next_batch(
@@ -126,15 +157,22 @@ where
         // through this stage to the BatchQueue stage.
         if !self.is_active()? {
             trace!(target: "batch_span", "BatchStream stage is inactive, pass-through.");
-            return self.prev.next_batch().await;
+            return self
+                .prev
+                .next_batch()
+                .instrument(debug_span!(target: "batch-stream", "next_batch"))
+                .await;
         }
 
-        // If the buffer is empty, attempt to pull a batch from the previous stage.
-        if self.buffer.is_empty() {
+        // If there is no staged span batch, attempt to pull a batch from the previous stage.
+        if self.span.is_none() {
             // Safety: bubble up any errors from the batch reader.
             let batch_with_inclusion = BatchWithInclusionBlock::new(
                 self.origin().ok_or(PipelineError::MissingOrigin.crit())?,
-                self.prev.next_batch().await?,
+                self.prev
+                    .next_batch()
+                    .instrument(debug_span!(target: "batch-stream", "next_batch"))
+                    .await?,
             );
 
             // If the next batch is a singular batch, it is immediately
@@ -154,7 +192,9 @@ where
                         .await;
 
                     match validity {
-                        BatchValidity::Accept => self.span = Some(b),
+                        BatchValidity::Accept => {
+                            self.span = Some(SpanBatchIter::new(b, l1_origins, parent, self.limits))
+                        }
                         BatchValidity::Drop => {
                             // Flush the stage.
                             self.flush();
@@ -178,7 +218,14 @@ where
         }
 
         // Attempt to pull a SingleBatch out of the SpanBatch.
-        self.get_single_batch(parent, l1_origins).map(Batch::Single)
+        let batch = self.get_single_batch()?;
+        if self.tx_equivalence_checks {
+            let number = parent.block_info.number + 1;
+            check_tx_equivalence(&batch, number, &mut self.fetcher)
+                .await
+                .map_err(|e| PipelineError::BadEncoding(e.into()).crit())?;
+        }
+        Ok(Batch::Single(batch))
     }
 }
 
@@ -203,6 +250,22 @@ where
     }
 }
 
+impl<P, BF> StageSnapshot for BatchStream<P, BF>
+where
+    P: BatchStreamProvider
+        + OriginAdvancer
+        + OriginProvider
+        + SignalReceiver
+        + StageSnapshot
+        + Debug,
+    BF: L2ChainProvider + Debug,
+{
+    fn snapshot(&self, snapshot: &mut PipelineSnapshot) {
+        snapshot.batches += self.span.as_ref().map_or(0, SpanBatchIter::remaining);
+        self.prev.snapshot(snapshot);
+    }
+}
+
 #[async_trait]
 impl<P, BF> SignalReceiver for BatchStream<P, BF>
 where
@@ -211,7 +274,6 @@ where
 {
     async fn signal(&mut self, signal: Signal) -> PipelineResult<()> {
         self.prev.signal(signal).await?;
-        self.buffer.clear();
         self.span.take();
         Ok(())
     }
@@ -221,25 +283,30 @@ where
 mod test {
     use super::*;
     use crate::{
+        errors::{PipelineEncodingError, PipelineErrorKind, TxEquivalenceError},
         test_utils::{CollectingLayer, TestBatchStreamProvider, TestL2ChainProvider, TraceStorage},
         types::ResetSignal,
     };
     use alloc::vec;
     use alloy_eips::NumHash;
-    use maili_protocol::{SingleBatch, SpanBatchElement};
+    use alloy_primitives::Bytes;
+    use maili_protocol::{SingleBatch, SpanBatch, SpanBatchElement};
     use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+    fn staged_span() -> SpanBatchIter {
+        let span = SpanBatch { batches: vec![SpanBatchElement::default()], ..Default::default() };
+        SpanBatchIter::new(span, &[], Default::default(), SpanBatchLimits::default())
+    }
+
     #[tokio::test]
     async fn test_batch_stream_flush() {
         let config = Arc::new(RollupConfig { holocene_time: Some(0), ..RollupConfig::default() });
         let prev = TestBatchStreamProvider::new(vec![]);
         let mut stream = BatchStream::new(prev, config, TestL2ChainProvider::default());
-        stream.buffer.push_back(SingleBatch::default());
-        stream.span = Some(SpanBatch::default());
-        assert!(!stream.buffer.is_empty());
-        assert!(stream.span.is_some());
+        stream.span = Some(staged_span());
+        assert_eq!(stream.span_buffer_size(), 1);
         stream.flush();
-        assert!(stream.buffer.is_empty());
+        assert_eq!(stream.span_buffer_size(), 0);
         assert!(stream.span.is_none());
     }
 
@@ -248,12 +315,10 @@ mod test {
         let config = Arc::new(RollupConfig { holocene_time: Some(0), ..RollupConfig::default() });
         let prev = TestBatchStreamProvider::new(vec![]);
         let mut stream = BatchStream::new(prev, config.clone(), TestL2ChainProvider::default());
-        stream.buffer.push_back(SingleBatch::default());
-        stream.span = Some(SpanBatch::default());
+        stream.span = Some(staged_span());
         assert!(!stream.prev.reset);
         stream.signal(ResetSignal::default().signal()).await.unwrap();
         assert!(stream.prev.reset);
-        assert!(stream.buffer.is_empty());
         assert!(stream.span.is_none());
     }
 
@@ -262,12 +327,10 @@ mod test {
         let config = Arc::new(RollupConfig { holocene_time: Some(0), ..RollupConfig::default() });
         let prev = TestBatchStreamProvider::new(vec![]);
         let mut stream = BatchStream::new(prev, config.clone(), TestL2ChainProvider::default());
-        stream.buffer.push_back(SingleBatch::default());
-        stream.span = Some(SpanBatch::default());
+        stream.span = Some(staged_span());
         assert!(!stream.prev.flushed);
         stream.signal(Signal::FlushChannel).await.unwrap();
         assert!(stream.prev.flushed);
-        assert!(stream.buffer.is_empty());
         assert!(stream.span.is_none());
     }
 
@@ -367,6 +430,75 @@ mod test {
         assert!(stream.span.is_none());
     }
 
+    #[tokio::test]
+    async fn test_span_buffer_limits_exceeded() {
+        let mock_batch = SpanBatch {
+            batches: vec![
+                SpanBatchElement {
+                    epoch_num: 1,
+                    timestamp: 2,
+                    transactions: vec![Default::default(), Default::default()],
+                },
+                SpanBatchElement { epoch_num: 1, timestamp: 4, ..Default::default() },
+            ],
+            ..Default::default()
+        };
+        let mock_origins = [BlockInfo { number: 1, timestamp: 12, ..Default::default() }];
+
+        let data = vec![Ok(Batch::Span(mock_batch))];
+        let config = Arc::new(RollupConfig {
+            delta_time: Some(0),
+            holocene_time: Some(0),
+            block_time: 2,
+            ..RollupConfig::default()
+        });
+        let prev = TestBatchStreamProvider::new(data);
+        let limits = SpanBatchLimits { max_transactions: 1, ..Default::default() };
+        let mut stream = BatchStream::new(prev, config, TestL2ChainProvider::default())
+            .with_span_batch_limits(limits);
+
+        // The span batch is dropped and the channel flushed, rather than halting derivation.
+        let err = stream.next_batch(Default::default(), &mock_origins).await.unwrap_err();
+        assert_eq!(err, PipelineError::NotEnoughData.temp());
+        assert_eq!(stream.span_buffer_size(), 0);
+        assert!(stream.span.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_span_buffer_tx_equivalence_checks() {
+        let mock_batch = SpanBatch {
+            batches: vec![SpanBatchElement {
+                epoch_num: 1,
+                timestamp: 2,
+                transactions: vec![Bytes::from_static(&[0x7f, 0x00])],
+            }],
+            ..Default::default()
+        };
+        let mock_origins = [BlockInfo { number: 1, timestamp: 12, ..Default::default() }];
+
+        let data = vec![Ok(Batch::Span(mock_batch))];
+        let config = Arc::new(RollupConfig {
+            delta_time: Some(0),
+            holocene_time: Some(0),
+            block_time: 2,
+            ..RollupConfig::default()
+        });
+        let prev = TestBatchStreamProvider::new(data);
+        let mut stream = BatchStream::new(prev, config, TestL2ChainProvider::default())
+            .with_tx_equivalence_checks(true);
+
+        let err = stream.next_batch(Default::default(), &mock_origins).await.unwrap_err();
+        assert!(matches!(
+            err,
+            PipelineErrorKind::Critical(PipelineError::BadEncoding(
+                PipelineEncodingError::TxEquivalenceError(TxEquivalenceError::Decoding {
+                    index: 0,
+                    ..
+                })
+            ))
+        ));
+    }
+
     #[tokio::test]
     async fn test_single_batch_pass_through() {
         let data = vec![Ok(Batch::Single(SingleBatch::default()))];
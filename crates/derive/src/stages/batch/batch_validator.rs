@@ -1,18 +1,19 @@
 #![allow(clippy::unnecessary_map_or)]
 //! Contains the [BatchValidator] stage.
 
-use super::NextBatchProvider;
+use super::{sequencing_window_action, NextBatchProvider, SequencingWindowAction};
 use crate::{
     errors::ResetError,
     prelude::{OriginProvider, PipelineError, PipelineErrorKind},
-    traits::{AttributesProvider, OriginAdvancer, SignalReceiver},
-    types::{PipelineResult, ResetSignal, Signal},
+    traits::{AttributesProvider, OriginAdvancer, SignalReceiver, StageSnapshot},
+    types::{PipelineResult, PipelineSnapshot, ResetSignal, Signal},
 };
 use alloc::{boxed::Box, sync::Arc, vec::Vec};
 use async_trait::async_trait;
 use core::fmt::Debug;
 use maili_protocol::{Batch, BatchValidity, BlockInfo, L2BlockInfo, SingleBatch};
 use op_alloy_genesis::RollupConfig;
+use tracing::Instrument;
 
 /// The [BatchValidator] stage is responsible for validating the [SingleBatch]es from
 /// the [BatchStream] [AttributesQueue]'s consumption.
@@ -131,43 +132,36 @@ where
         let epoch = self.l1_blocks[0];
 
         // If the current epoch is too old compared to the L1 block we are at,
-        // i.e. if the sequence window expired, we create empty batches for the current epoch
+        // i.e. if the sequence window expired, we create empty batches for the current epoch.
+        // The Holocene batch stage considers the window expired at its last L1 block.
         let stage_origin = self.origin.ok_or(PipelineError::MissingOrigin.crit())?;
-        let expiry_epoch = epoch.number + self.cfg.seq_window_size;
-        let force_empty_batches = expiry_epoch <= stage_origin.number;
-        let first_of_epoch = epoch.number == parent.l1_origin.number + 1;
         let next_timestamp = parent.block_info.timestamp + self.cfg.block_time;
-
-        // If the sequencer window did not expire,
-        // there is still room to receive batches for the current epoch.
-        // No need to force-create empty batch(es) towards the next epoch yet.
-        if !force_empty_batches {
-            return Err(PipelineError::Eof.temp());
-        }
-
-        // The next L1 block is needed to proceed towards the next epoch.
-        if self.l1_blocks.len() < 2 {
-            return Err(PipelineError::Eof.temp());
-        }
-
-        let next_epoch = self.l1_blocks[1];
-
-        // Fill with empty L2 blocks of the same epoch until we meet the time of the next L1 origin,
-        // to preserve that L2 time >= L1 time. If this is the first block of the epoch, always
-        // generate a batch to ensure that we at least have one batch per epoch.
-        if next_timestamp < next_epoch.timestamp || first_of_epoch {
-            info!(target: "batch-validator", "Generating empty batch for epoch #{}", epoch.number);
-            return Ok(SingleBatch {
-                parent_hash: parent.block_info.hash,
-                epoch_num: epoch.number,
-                epoch_hash: epoch.hash,
-                timestamp: next_timestamp,
-                transactions: Vec::new(),
-            });
+        match sequencing_window_action(
+            &self.cfg,
+            &self.l1_blocks,
+            stage_origin.number,
+            true,
+            parent,
+        ) {
+            SequencingWindowAction::Wait | SequencingWindowAction::NeedNextOrigin => {
+                return Err(PipelineError::Eof.temp());
+            }
+            SequencingWindowAction::EmptyBatch => {
+                info!(target: "batch-validator", "Generating empty batch for epoch #{}", epoch.number);
+                return Ok(SingleBatch {
+                    parent_hash: parent.block_info.hash,
+                    epoch_num: epoch.number,
+                    epoch_hash: epoch.hash,
+                    timestamp: next_timestamp,
+                    transactions: Vec::new(),
+                });
+            }
+            SequencingWindowAction::AdvanceEpoch => {}
         }
 
         // At this point we have auto generated every batch for the current epoch
         // that we can, so we can advance to the next epoch.
+        let next_epoch = self.l1_blocks[1];
         debug!(
             target: "batch-validator",
             "Advancing batch validator epoch: {}, timestamp: {}, epoch timestamp: {}",
@@ -190,7 +184,10 @@ where
         // If the origin is behind, we must drain previous stages to catch up.
         let stage_origin = self.origin.ok_or(PipelineError::MissingOrigin.crit())?;
         if self.origin_behind(&parent) || parent.l1_origin.number == stage_origin.number {
-            self.prev.next_batch(parent, self.l1_blocks.as_ref()).await?;
+            self.prev
+                .next_batch(parent, self.l1_blocks.as_ref())
+                .instrument(debug_span!(target: "batch-validator", "next_batch"))
+                .await?;
             return Err(PipelineError::NotEnoughData.temp());
         }
 
@@ -212,7 +209,12 @@ where
         }
 
         // Pull the next batch from the previous stage.
-        let next_batch = match self.prev.next_batch(parent, self.l1_blocks.as_ref()).await {
+        let next_batch = match self
+            .prev
+            .next_batch(parent, self.l1_blocks.as_ref())
+            .instrument(debug_span!(target: "batch-validator", "next_batch"))
+            .await
+        {
             Ok(batch) => batch,
             Err(PipelineErrorKind::Temporary(PipelineError::Eof)) => {
                 return self.try_derive_empty_batch(&parent);
@@ -274,6 +276,15 @@ where
     }
 }
 
+impl<P> StageSnapshot for BatchValidator<P>
+where
+    P: NextBatchProvider + OriginAdvancer + OriginProvider + SignalReceiver + StageSnapshot + Debug,
+{
+    fn snapshot(&self, snapshot: &mut PipelineSnapshot) {
+        self.prev.snapshot(snapshot);
+    }
+}
+
 #[async_trait]
 impl<P> OriginAdvancer for BatchValidator<P>
 where
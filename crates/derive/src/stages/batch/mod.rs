@@ -16,6 +16,21 @@ use alloc::boxed::Box;
 use async_trait::async_trait;
 use maili_protocol::{Batch, BlockInfo, L2BlockInfo};
 
+mod span_batch_iter;
+pub use span_batch_iter::{SpanBatchIter, SpanBatchLimits};
+
+mod tx_equivalence;
+pub use tx_equivalence::{check_tx_equivalence, reencode_transaction};
+
+mod span_batch_stats;
+pub(crate) use span_batch_stats::decompress_channel;
+pub use span_batch_stats::{ChannelBatchStats, SpanBatchStats, TxTypeCounts};
+
+mod sequencing;
+pub use sequencing::{
+    is_sequencing_window_expired, sequencing_window_action, SequencingWindowAction,
+};
+
 mod batch_stream;
 pub use batch_stream::{BatchStream, BatchStreamProvider};
 
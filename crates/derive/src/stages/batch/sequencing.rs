@@ -0,0 +1,165 @@
+//! Sequencing window rules shared by the [BatchQueue] and [BatchValidator] stages.
+//!
+//! Batches for an epoch must be included within `seq_window_size` L1 blocks of the epoch's L1
+//! origin. Once the window has expired without a valid batch, the stages fill the epoch with
+//! deposit-only batches, and then force the L1 origin to advance to the next epoch.
+//!
+//! The max sequencer drift, which bounds how far the L2 time of a batch may run ahead of its L1
+//! origin, is checked per batch with [RollupConfig::max_sequencer_drift], which is fixed to
+//! [FJORD_MAX_SEQUENCER_DRIFT] from Fjord onwards.
+//!
+//! [BatchQueue]: crate::stages::BatchQueue
+//! [BatchValidator]: crate::stages::BatchValidator
+//! [FJORD_MAX_SEQUENCER_DRIFT]: op_alloy_genesis::FJORD_MAX_SEQUENCER_DRIFT
+
+use maili_protocol::{BlockInfo, L2BlockInfo};
+use op_alloy_genesis::RollupConfig;
+
+/// The action to take for the current epoch when no valid batch is available.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequencingWindowAction {
+    /// The sequencing window of the epoch is still open, so batches may still be submitted.
+    Wait,
+    /// The sequencing window expired, but the next L1 origin is needed to proceed.
+    NeedNextOrigin,
+    /// The sequencing window expired, and a deposit-only batch must be generated in the epoch.
+    EmptyBatch,
+    /// The sequencing window expired, and the L2 time reached the next L1 origin, so the epoch
+    /// must be advanced without generating a batch.
+    AdvanceEpoch,
+}
+
+/// Returns whether the sequencing window of the epoch with the given number has expired at the
+/// L1 `origin`.
+///
+/// The window expires once the pipeline moved past the last L1 block of the window, or at the
+/// last block of the window if `origin_exhausted` is set, i.e. all of its data has been read.
+/// The Holocene [BatchValidator] always treats its origin as exhausted.
+///
+/// [BatchValidator]: crate::stages::BatchValidator
+pub const fn is_sequencing_window_expired(
+    cfg: &RollupConfig,
+    epoch: u64,
+    origin: u64,
+    origin_exhausted: bool,
+) -> bool {
+    let expiry_epoch = epoch + cfg.seq_window_size;
+    expiry_epoch < origin || (expiry_epoch == origin && origin_exhausted)
+}
+
+/// Returns the [SequencingWindowAction] to take for the child of `parent`, given the L1 blocks
+/// buffered from the current epoch onwards and the L1 `origin` of the stage.
+///
+/// Deposit-only batches are generated until the L2 time meets the time of the next L1 origin, to
+/// preserve that L2 time >= L1 time. The first block of an epoch is always generated, so that
+/// every epoch has at least one L2 block.
+pub fn sequencing_window_action(
+    cfg: &RollupConfig,
+    l1_blocks: &[BlockInfo],
+    origin: u64,
+    origin_exhausted: bool,
+    parent: &L2BlockInfo,
+) -> SequencingWindowAction {
+    let Some(epoch) = l1_blocks.first() else {
+        return SequencingWindowAction::NeedNextOrigin;
+    };
+    if !is_sequencing_window_expired(cfg, epoch.number, origin, origin_exhausted) {
+        return SequencingWindowAction::Wait;
+    }
+    let Some(next_epoch) = l1_blocks.get(1) else {
+        return SequencingWindowAction::NeedNextOrigin;
+    };
+
+    let first_of_epoch = epoch.number == parent.l1_origin.number + 1;
+    let next_timestamp = parent.block_info.timestamp + cfg.block_time;
+    if next_timestamp < next_epoch.timestamp || first_of_epoch {
+        SequencingWindowAction::EmptyBatch
+    } else {
+        SequencingWindowAction::AdvanceEpoch
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{vec, vec::Vec};
+    use alloy_eips::BlockNumHash;
+    use alloy_primitives::Bytes;
+    use maili_protocol::{BatchValidity, SingleBatch};
+    use op_alloy_genesis::FJORD_MAX_SEQUENCER_DRIFT;
+
+    fn l1_blocks() -> Vec<BlockInfo> {
+        vec![
+            BlockInfo { number: 10, timestamp: 100, ..Default::default() },
+            BlockInfo { number: 11, timestamp: 112, ..Default::default() },
+        ]
+    }
+
+    #[test]
+    fn test_is_sequencing_window_expired() {
+        let cfg = RollupConfig { seq_window_size: 5, ..Default::default() };
+        assert!(!is_sequencing_window_expired(&cfg, 10, 14, true));
+        assert!(!is_sequencing_window_expired(&cfg, 10, 15, false));
+        assert!(is_sequencing_window_expired(&cfg, 10, 15, true));
+        assert!(is_sequencing_window_expired(&cfg, 10, 16, false));
+    }
+
+    #[test]
+    fn test_sequencing_window_action() {
+        let cfg = RollupConfig { seq_window_size: 5, block_time: 2, ..Default::default() };
+        let parent = |timestamp, origin| L2BlockInfo {
+            block_info: BlockInfo { timestamp, ..Default::default() },
+            l1_origin: BlockNumHash { number: origin, ..Default::default() },
+            seq_num: 0,
+        };
+        let blocks = l1_blocks();
+        let action = |blocks: &[BlockInfo], origin, parent| {
+            sequencing_window_action(&cfg, blocks, origin, false, &parent)
+        };
+
+        assert_eq!(action(&blocks, 15, parent(100, 10)), SequencingWindowAction::Wait);
+        assert_eq!(
+            action(&blocks[..1], 16, parent(100, 10)),
+            SequencingWindowAction::NeedNextOrigin
+        );
+        assert_eq!(action(&blocks, 16, parent(108, 10)), SequencingWindowAction::EmptyBatch);
+        assert_eq!(action(&blocks, 16, parent(110, 10)), SequencingWindowAction::AdvanceEpoch);
+        // The first block of the epoch is generated even if it is past the next L1 origin.
+        assert_eq!(action(&blocks, 16, parent(110, 9)), SequencingWindowAction::EmptyBatch);
+    }
+
+    #[test]
+    fn test_max_sequencer_drift_fjord() {
+        let cfg = RollupConfig {
+            seq_window_size: 5,
+            block_time: 2,
+            max_sequencer_drift: 600,
+            fjord_time: Some(200),
+            ..Default::default()
+        };
+        let blocks = vec![
+            BlockInfo { number: 10, timestamp: 1_000, ..Default::default() },
+            BlockInfo { number: 11, timestamp: 3_000, ..Default::default() },
+        ];
+        let parent = L2BlockInfo {
+            block_info: BlockInfo { timestamp: 2_000, ..Default::default() },
+            l1_origin: blocks[0].id(),
+            seq_num: 0,
+        };
+        let batch = SingleBatch {
+            epoch_num: 10,
+            timestamp: 2_002,
+            transactions: vec![Bytes::from_static(&[0x02])],
+            ..Default::default()
+        };
+        let inclusion = BlockInfo { number: 12, ..Default::default() };
+
+        // The batch is within the Fjord drift, but not within the configured drift.
+        assert!(2_002 <= blocks[0].timestamp + FJORD_MAX_SEQUENCER_DRIFT);
+        let validity = batch.check_batch(&cfg, &blocks, parent, &inclusion);
+        assert_eq!(validity, BatchValidity::Accept);
+        let pre_fjord = RollupConfig { fjord_time: None, ..cfg };
+        let validity = batch.check_batch(&pre_fjord, &blocks, parent, &inclusion);
+        assert_eq!(validity, BatchValidity::Drop);
+    }
+}
@@ -0,0 +1,374 @@
+//! Contains the [SpanBatchIter], a lazy [SpanBatch] to [SingleBatch] decoder.
+
+use crate::errors::{PipelineEncodingError, SpanBatchLimitError};
+use alloc::vec::{self, Vec};
+use maili_protocol::{
+    BlockInfo, L2BlockInfo, SingleBatch, SpanBatch, SpanBatchElement, SpanBatchError,
+};
+
+/// Caps enforced by the [SpanBatchIter] while yielding [SingleBatch]es.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpanBatchLimits {
+    /// The maximum number of transactions that may be yielded from a single [SpanBatch].
+    pub max_transactions: usize,
+    /// The maximum total size, in bytes, of the transaction payloads yielded from a single
+    /// [SpanBatch].
+    pub max_payload_bytes: usize,
+}
+
+impl SpanBatchLimits {
+    /// Limits that enforce no caps, matching the reference implementation.
+    pub const UNBOUNDED: Self =
+        Self { max_transactions: usize::MAX, max_payload_bytes: usize::MAX };
+}
+
+impl Default for SpanBatchLimits {
+    fn default() -> Self {
+        Self::UNBOUNDED
+    }
+}
+
+/// An iterator that lazily converts a [SpanBatch] into [SingleBatch]es.
+///
+/// Unlike [SpanBatch::get_singular_batches], the iterator takes ownership of the [SpanBatch] and
+/// moves the transaction data of each block out as it is yielded, rather than cloning all of it
+/// up front. The span batch's transaction caches are dropped upon construction. The iterator
+/// holds no borrows, so it can be kept around and consumed one block at a time.
+///
+/// Blocks with a timestamp at or before the L2 safe head are skipped. If any of the
+/// [SpanBatchLimits] are exceeded, an error is yielded and the iterator is fused.
+#[derive(Debug)]
+pub struct SpanBatchIter {
+    /// The remaining span batch elements.
+    elements: vec::IntoIter<SpanBatchElement>,
+    /// The L1 origins available to resolve epoch hashes.
+    l1_origins: Vec<BlockInfo>,
+    /// The L2 safe head that the span batch builds on.
+    l2_safe_head: L2BlockInfo,
+    /// The index of the last resolved L1 origin, epochs are monotonically increasing.
+    origin_index: usize,
+    /// The limits enforced while yielding batches.
+    limits: SpanBatchLimits,
+    /// The number of transactions yielded so far.
+    tx_count: usize,
+    /// The total size of the transactions yielded so far.
+    payload_bytes: usize,
+    /// Whether the iterator has yielded an error.
+    errored: bool,
+}
+
+impl SpanBatchIter {
+    /// Creates a new [SpanBatchIter], copying the L1 origins.
+    pub fn new(
+        span: SpanBatch,
+        l1_origins: &[BlockInfo],
+        l2_safe_head: L2BlockInfo,
+        limits: SpanBatchLimits,
+    ) -> Self {
+        Self {
+            elements: span.batches.into_iter(),
+            l1_origins: l1_origins.to_vec(),
+            l2_safe_head,
+            origin_index: 0,
+            limits,
+            tx_count: 0,
+            payload_bytes: 0,
+            errored: false,
+        }
+    }
+
+    /// Returns the number of span batch elements that have not been visited yet.
+    pub fn remaining(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Converts the next [SpanBatchElement] into a [SingleBatch].
+    fn convert(&mut self, element: SpanBatchElement) -> Result<SingleBatch, PipelineEncodingError> {
+        let (offset, origin) = self.l1_origins[self.origin_index..]
+            .iter()
+            .enumerate()
+            .find(|(_, origin)| origin.number == element.epoch_num)
+            .ok_or(SpanBatchError::MissingL1Origin)?;
+        self.origin_index += offset;
+
+        self.tx_count = self.tx_count.saturating_add(element.transactions.len());
+        if self.tx_count > self.limits.max_transactions {
+            return Err(SpanBatchLimitError::TooManyTransactions(
+                self.tx_count,
+                self.limits.max_transactions,
+            )
+            .into());
+        }
+        let size = element.transactions.iter().map(|tx| tx.len()).sum::<usize>();
+        self.payload_bytes = self.payload_bytes.saturating_add(size);
+        if self.payload_bytes > self.limits.max_payload_bytes {
+            return Err(SpanBatchLimitError::PayloadTooLarge(
+                self.payload_bytes,
+                self.limits.max_payload_bytes,
+            )
+            .into());
+        }
+
+        Ok(SingleBatch {
+            epoch_num: element.epoch_num,
+            epoch_hash: origin.hash,
+            timestamp: element.timestamp,
+            transactions: element.transactions,
+            ..Default::default()
+        })
+    }
+}
+
+impl Iterator for SpanBatchIter {
+    type Item = Result<SingleBatch, PipelineEncodingError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.errored {
+            return None;
+        }
+        let element =
+            self.elements.find(|e| e.timestamp > self.l2_safe_head.block_info.timestamp)?;
+        let result = self.convert(element);
+        self.errored = result.is_err();
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use alloy_consensus::{Signed, Transaction, TxEip1559, TxEnvelope, TxLegacy};
+    use alloy_eips::eip2718::{Decodable2718, Encodable2718};
+    use alloy_primitives::{b256, Address, Bytes, PrimitiveSignature as Signature, TxKind};
+    use maili_protocol::{RawSpanBatch, SpanDecodingError};
+
+    fn test_span() -> SpanBatch {
+        SpanBatch {
+            batches: vec![
+                SpanBatchElement {
+                    epoch_num: 1,
+                    timestamp: 2,
+                    transactions: vec![Bytes::from(vec![1; 10])],
+                },
+                SpanBatchElement {
+                    epoch_num: 1,
+                    timestamp: 4,
+                    transactions: vec![Bytes::from(vec![2; 10]), Bytes::from(vec![3; 10])],
+                },
+                SpanBatchElement { epoch_num: 2, timestamp: 6, transactions: vec![] },
+            ],
+            ..Default::default()
+        }
+    }
+
+    fn test_origins() -> [BlockInfo; 2] {
+        [
+            BlockInfo {
+                number: 1,
+                hash: b256!("1111111111111111111111111111111111111111111111111111111111111111"),
+                ..Default::default()
+            },
+            BlockInfo {
+                number: 2,
+                hash: b256!("2222222222222222222222222222222222222222222222222222222222222222"),
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn test_span_batch_iter_matches_get_singular_batches() {
+        let origins = test_origins();
+        let expected = test_span().get_singular_batches(&origins, L2BlockInfo::default()).unwrap();
+        let batches = SpanBatchIter::new(
+            test_span(),
+            &origins,
+            L2BlockInfo::default(),
+            SpanBatchLimits::default(),
+        )
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+        assert_eq!(batches, expected);
+        assert_eq!(batches[2].epoch_hash, origins[1].hash);
+    }
+
+    #[test]
+    fn test_span_batch_iter_skips_past_blocks() {
+        let origins = test_origins();
+        let mut safe_head = L2BlockInfo::default();
+        safe_head.block_info.timestamp = 4;
+        let mut iter =
+            SpanBatchIter::new(test_span(), &origins, safe_head, SpanBatchLimits::default());
+        assert_eq!(iter.remaining(), 3);
+        let batch = iter.next().unwrap().unwrap();
+        assert_eq!(batch.timestamp, 6);
+        assert_eq!(iter.remaining(), 0);
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn test_span_batch_iter_missing_origin() {
+        let origins = test_origins();
+        let mut iter = SpanBatchIter::new(
+            test_span(),
+            &origins[1..],
+            L2BlockInfo::default(),
+            SpanBatchLimits::default(),
+        );
+        assert_eq!(
+            iter.next().unwrap().unwrap_err(),
+            PipelineEncodingError::SpanBatchError(SpanBatchError::MissingL1Origin)
+        );
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn test_span_batch_iter_too_many_transactions() {
+        let origins = test_origins();
+        let limits = SpanBatchLimits { max_transactions: 2, ..Default::default() };
+        let mut iter = SpanBatchIter::new(test_span(), &origins, L2BlockInfo::default(), limits);
+        assert!(iter.next().unwrap().is_ok());
+        assert_eq!(
+            iter.next().unwrap().unwrap_err(),
+            SpanBatchLimitError::TooManyTransactions(3, 2).into()
+        );
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn test_span_batch_iter_payload_too_large() {
+        let origins = test_origins();
+        let limits = SpanBatchLimits { max_payload_bytes: 25, ..Default::default() };
+        let mut iter = SpanBatchIter::new(test_span(), &origins, L2BlockInfo::default(), limits);
+        assert!(iter.next().unwrap().is_ok());
+        assert_eq!(
+            iter.next().unwrap().unwrap_err(),
+            SpanBatchLimitError::PayloadTooLarge(30, 25).into()
+        );
+        assert!(iter.next().is_none());
+    }
+
+    fn legacy_tx(chain_id: Option<u64>, nonce: u64) -> TxEnvelope {
+        let tx = TxLegacy {
+            chain_id,
+            nonce,
+            gas_price: 1_000_000_000,
+            gas_limit: 21_000,
+            to: TxKind::Call(Address::with_last_byte(1)),
+            ..Default::default()
+        };
+        TxEnvelope::Legacy(Signed::new_unchecked(
+            tx,
+            Signature::test_signature(),
+            Default::default(),
+        ))
+    }
+
+    #[test]
+    fn test_span_batch_iter_legacy_tx_protection() {
+        let origins = test_origins();
+        let txs = [legacy_tx(Some(10), 0), legacy_tx(None, 1), legacy_tx(Some(10), 2)];
+        let mut span = SpanBatch { chain_id: 10, ..Default::default() };
+        let batch = SingleBatch {
+            epoch_num: 1,
+            epoch_hash: origins[0].hash,
+            timestamp: 2,
+            transactions: txs.iter().map(|tx| tx.encoded_2718().into()).collect(),
+            ..Default::default()
+        };
+        span.append_singular_batch(batch, 0).unwrap();
+
+        // Round-trip the span batch through its wire format.
+        let mut encoded = Vec::new();
+        span.to_raw_span_batch().unwrap().encode(&mut encoded).unwrap();
+        let derived =
+            RawSpanBatch::decode(&mut encoded.as_slice()).unwrap().derive(2, 0, 10).unwrap();
+
+        let batches = SpanBatchIter::new(
+            derived,
+            &origins,
+            L2BlockInfo::default(),
+            SpanBatchLimits::default(),
+        )
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+        assert_eq!(batches.len(), 1);
+        for (raw, expected) in batches[0].transactions.iter().zip(txs.iter()) {
+            let tx = TxEnvelope::decode_2718(&mut raw.as_ref()).unwrap();
+            assert_eq!(tx.is_replay_protected(), expected.is_replay_protected());
+            assert_eq!(tx.chain_id(), expected.chain_id());
+            assert_eq!(tx.recover_signer().unwrap(), expected.recover_signer().unwrap());
+            assert_eq!(raw.as_ref(), expected.encoded_2718());
+        }
+    }
+
+    #[test]
+    fn test_span_batch_legacy_tx_wrong_chain_id() {
+        let mut span = SpanBatch { chain_id: 10, ..Default::default() };
+        let batch = SingleBatch {
+            epoch_num: 1,
+            timestamp: 2,
+            transactions: vec![legacy_tx(Some(1), 0).encoded_2718().into()],
+            ..Default::default()
+        };
+        assert_eq!(
+            span.append_singular_batch(batch, 0).unwrap_err(),
+            SpanBatchError::Decoding(SpanDecodingError::InvalidTransactionData)
+        );
+    }
+
+    #[test]
+    fn test_span_batch_contract_creation() {
+        // Span batches encode contract creations with the contract creation bits, so they must
+        // round-trip rather than be rejected.
+        let origins = test_origins();
+        let create = TxEip1559 {
+            chain_id: 10,
+            gas_limit: 100_000,
+            to: TxKind::Create,
+            input: Bytes::from_static(&[0x60, 0x00]),
+            ..Default::default()
+        };
+        let create = TxEnvelope::Eip1559(Signed::new_unchecked(
+            create,
+            Signature::test_signature(),
+            Default::default(),
+        ));
+        let txs = [create, legacy_tx(Some(10), 1)];
+        let mut span = SpanBatch { chain_id: 10, ..Default::default() };
+        let batch = SingleBatch {
+            epoch_num: 1,
+            epoch_hash: origins[0].hash,
+            timestamp: 2,
+            transactions: txs.iter().map(|tx| tx.encoded_2718().into()).collect(),
+            ..Default::default()
+        };
+        span.append_singular_batch(batch, 0).unwrap();
+
+        let mut encoded = Vec::new();
+        span.to_raw_span_batch().unwrap().encode(&mut encoded).unwrap();
+        let derived =
+            RawSpanBatch::decode(&mut encoded.as_slice()).unwrap().derive(2, 0, 10).unwrap();
+
+        let batches = SpanBatchIter::new(
+            derived,
+            &origins,
+            L2BlockInfo::default(),
+            SpanBatchLimits::default(),
+        )
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+        let decoded = batches[0]
+            .transactions
+            .iter()
+            .map(|raw| TxEnvelope::decode_2718(&mut raw.as_ref()).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(decoded[0].kind(), TxKind::Create);
+        assert_eq!(decoded[0].input(), &Bytes::from_static(&[0x60, 0x00]));
+        assert_eq!(decoded[1].kind(), TxKind::Call(Address::with_last_byte(1)));
+        for (raw, expected) in batches[0].transactions.iter().zip(txs.iter()) {
+            assert_eq!(raw.as_ref(), expected.encoded_2718());
+        }
+    }
+}
@@ -0,0 +1,188 @@
+//! Contains the [SpanBatchStats] and [ChannelBatchStats] reporting API, for studying the
+//! efficiency of span batches and channel compression.
+
+use crate::stages::decode_batch;
+use alloc::vec::Vec;
+use alloy_primitives::Bytes;
+use alloy_rlp::Decodable;
+use maili_protocol::{decompress_brotli, Batch, SpanBatch};
+use miniz_oxide::inflate::decompress_to_vec_zlib_with_limit;
+use op_alloy_genesis::RollupConfig;
+
+/// The compression type of a zlib compressed channel, read from its first byte.
+const ZLIB_DEFLATE_COMPRESSION_METHOD: u8 = 8;
+
+/// The reserved compression type of a zlib compressed channel.
+const ZLIB_RESERVED_COMPRESSION_METHOD: u8 = 15;
+
+/// The version byte of a brotli compressed channel.
+const CHANNEL_VERSION_BROTLI: u8 = 1;
+
+/// Decompresses the channel data, returning the decompressed data and whether the channel was
+/// brotli compressed, or [None] if it fails to decompress within `max`.
+pub(crate) fn decompress_channel(data: &[u8], max: usize) -> Option<(Vec<u8>, bool)> {
+    let compression_type = *data.first()?;
+    if matches!(
+        compression_type & 0x0F,
+        ZLIB_DEFLATE_COMPRESSION_METHOD | ZLIB_RESERVED_COMPRESSION_METHOD
+    ) {
+        Some((decompress_to_vec_zlib_with_limit(data, max).ok()?, false))
+    } else if compression_type == CHANNEL_VERSION_BROTLI {
+        Some((decompress_brotli(&data[1..], max).ok()?, true))
+    } else {
+        None
+    }
+}
+
+/// The number of transactions of each type in a span batch.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TxTypeCounts {
+    /// Legacy transactions.
+    pub legacy: usize,
+    /// EIP-2930 transactions.
+    pub eip2930: usize,
+    /// EIP-1559 transactions.
+    pub eip1559: usize,
+    /// Transactions of any other type.
+    pub other: usize,
+}
+
+impl TxTypeCounts {
+    /// Returns the total number of transactions.
+    pub const fn total(&self) -> usize {
+        self.legacy + self.eip2930 + self.eip1559 + self.other
+    }
+}
+
+/// Statistics of a single [SpanBatch].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SpanBatchStats {
+    /// The number of L2 blocks in the span batch.
+    pub blocks: usize,
+    /// The number of transactions of each type.
+    pub tx_types: TxTypeCounts,
+    /// The number of L1 origin changes between consecutive blocks.
+    pub origin_changes: usize,
+    /// The size of the encoded span batch, including its type byte, in bytes.
+    pub encoded_size: usize,
+    /// The total size of the reconstructed EIP-2718 encoded transactions, in bytes.
+    pub tx_bytes: usize,
+}
+
+impl SpanBatchStats {
+    /// Computes the statistics of a decoded [SpanBatch] with the given encoded size.
+    pub fn new(batch: &SpanBatch, encoded_size: usize) -> Self {
+        let mut stats = Self { blocks: batch.batches.len(), encoded_size, ..Default::default() };
+        stats.origin_changes =
+            batch.batches.windows(2).filter(|w| w[0].epoch_num != w[1].epoch_num).count();
+        for tx in batch.batches.iter().flat_map(|b| b.transactions.iter()) {
+            stats.tx_bytes += tx.len();
+            match tx.first() {
+                Some(0xC0..=0xFF) => stats.tx_types.legacy += 1,
+                Some(0x01) => stats.tx_types.eip2930 += 1,
+                Some(0x02) => stats.tx_types.eip1559 += 1,
+                _ => stats.tx_types.other += 1,
+            }
+        }
+        stats
+    }
+}
+
+/// Statistics of the batches decoded from a single channel.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChannelBatchStats {
+    /// The size of the compressed channel data, in bytes.
+    pub compressed_size: usize,
+    /// The size of the decompressed channel data, in bytes.
+    pub decompressed_size: usize,
+    /// The number of singular batches in the channel.
+    pub single_batches: usize,
+    /// The statistics of each span batch in the channel, in decoding order.
+    pub span_batches: Vec<SpanBatchStats>,
+}
+
+impl ChannelBatchStats {
+    /// Decompresses the channel data and computes the statistics of its batches, the same way as
+    /// the `BatchReader` decodes them.
+    ///
+    /// Returns [None] if the channel cannot be decompressed within `max_rlp_bytes_per_channel`.
+    /// Decoding stops at the first batch that fails to decode.
+    pub fn from_channel(
+        cfg: &RollupConfig,
+        data: &[u8],
+        max_rlp_bytes_per_channel: usize,
+    ) -> Option<Self> {
+        let (decompressed, brotli) = decompress_channel(data, max_rlp_bytes_per_channel)?;
+
+        let mut stats = Self {
+            compressed_size: data.len(),
+            decompressed_size: decompressed.len(),
+            ..Default::default()
+        };
+        let mut buf = decompressed.as_slice();
+        while !buf.is_empty() {
+            let Ok(encoded) = Bytes::decode(&mut buf) else { break };
+            let Ok(batch) = decode_batch(&encoded, cfg) else { break };
+            // Brotli compressed channels are only valid after the Fjord hardfork.
+            if brotli && !cfg.is_fjord_active(batch.timestamp()) {
+                break;
+            }
+            match batch {
+                Batch::Single(_) => stats.single_batches += 1,
+                Batch::Span(batch) => {
+                    stats.span_batches.push(SpanBatchStats::new(&batch, encoded.len()))
+                }
+            }
+        }
+        Some(stats)
+    }
+
+    /// Returns the compression ratio of the channel, the decompressed size over the compressed
+    /// size.
+    pub fn compression_ratio(&self) -> f64 {
+        self.decompressed_size as f64 / self.compressed_size as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        constants::MAX_RLP_BYTES_PER_CHANNEL_FJORD, test_utils::new_compressed_batch_data,
+    };
+    use maili_protocol::BatchReader;
+
+    #[test]
+    fn test_channel_batch_stats() {
+        let cfg = RollupConfig::default();
+        let data = new_compressed_batch_data();
+        let max = MAX_RLP_BYTES_PER_CHANNEL_FJORD as usize;
+        let stats = ChannelBatchStats::from_channel(&cfg, &data, max).unwrap();
+
+        let mut reader = BatchReader::new(data.as_slice(), max);
+        let Some(Batch::Span(batch)) = reader.next_batch(&cfg) else {
+            panic!("expected a span batch");
+        };
+
+        assert_eq!(stats.compressed_size, data.len());
+        assert!(stats.compression_ratio() > 1.0);
+        assert_eq!(stats.single_batches, 0);
+        assert_eq!(stats.span_batches.len(), 1);
+
+        let span = stats.span_batches[0];
+        assert_eq!(span.blocks, batch.batches.len());
+        let txs = batch.batches.iter().map(|b| b.transactions.len()).sum::<usize>();
+        assert_eq!(span.tx_types.total(), txs);
+        assert!(span.encoded_size < stats.decompressed_size);
+        assert!(span.tx_bytes > span.encoded_size);
+    }
+
+    #[test]
+    fn test_channel_batch_stats_invalid() {
+        let cfg = RollupConfig::default();
+        assert_eq!(ChannelBatchStats::from_channel(&cfg, &[], 100), None);
+        assert_eq!(ChannelBatchStats::from_channel(&cfg, &[0xFF, 0x00], 100), None);
+        let data = new_compressed_batch_data();
+        assert_eq!(ChannelBatchStats::from_channel(&cfg, &data, 10), None);
+    }
+}
@@ -0,0 +1,190 @@
+//! Contains equivalence checks for the transactions reconstructed from a [SpanBatch].
+//!
+//! Span batches do not carry raw transactions, but the fields needed to rebuild them. A subtle
+//! divergence in the reconstruction (e.g. of the signature `v` value or the legacy replay
+//! protection) still produces a decodable transaction, but with a different hash than the one
+//! the sequencer included. These checks surface such a divergence where it happens, rather than
+//! as a mismatched output root after execution.
+//!
+//! [SpanBatch]: maili_protocol::SpanBatch
+
+use crate::errors::TxEquivalenceError;
+use alloc::{string::ToString, vec::Vec};
+use alloy_eips::eip2718::{Decodable2718, Encodable2718};
+use alloy_primitives::{keccak256, B256};
+use maili_protocol::{BatchValidationProvider, SingleBatch};
+use op_alloy_consensus::OpTxEnvelope;
+
+/// Decodes a reconstructed transaction and verifies that it re-encodes to the same bytes.
+///
+/// Returns the hash of the transaction. The `index` is the index of the transaction within its
+/// batch, used to report errors.
+pub fn reencode_transaction(index: usize, raw: &[u8]) -> Result<B256, TxEquivalenceError> {
+    let tx = OpTxEnvelope::decode_2718(&mut &raw[..])
+        .map_err(|e| TxEquivalenceError::Decoding { index, error: e.to_string() })?;
+    if tx.encoded_2718() != raw {
+        return Err(TxEquivalenceError::Reencoding(index));
+    }
+    Ok(keccak256(raw))
+}
+
+/// Verifies the transactions of a [SingleBatch] reconstructed from a span batch against the L2
+/// block with the given `number`.
+///
+/// Every transaction is first checked with [reencode_transaction]. If the provider has the L2
+/// block, and the block has the timestamp of the batch, the hashes of the transactions are then
+/// compared against the non-deposit transactions of the block.
+///
+/// Returns `true` if the transactions were compared against the L2 block, and `false` if the
+/// block is not available.
+pub async fn check_tx_equivalence<P: BatchValidationProvider>(
+    batch: &SingleBatch,
+    number: u64,
+    provider: &mut P,
+) -> Result<bool, TxEquivalenceError> {
+    let hashes = batch
+        .transactions
+        .iter()
+        .enumerate()
+        .map(|(i, raw)| reencode_transaction(i, raw))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let Ok(block) = provider.block_by_number(number).await else {
+        return Ok(false);
+    };
+    if block.header.timestamp != batch.timestamp {
+        return Ok(false);
+    }
+
+    let expected = block
+        .body
+        .transactions
+        .iter()
+        .filter(|tx| !matches!(tx, OpTxEnvelope::Deposit(_)))
+        .map(|tx| keccak256(tx.encoded_2718()))
+        .collect::<Vec<_>>();
+    if expected.len() != hashes.len() {
+        return Err(TxEquivalenceError::TransactionCount {
+            number,
+            expected: expected.len(),
+            actual: hashes.len(),
+        });
+    }
+    if let Some((index, (expected, actual))) =
+        expected.into_iter().zip(hashes).enumerate().find(|(_, (e, a))| e != a)
+    {
+        return Err(TxEquivalenceError::HashMismatch { number, index, expected, actual });
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestL2ChainProvider;
+    use alloc::vec;
+    use alloy_consensus::{BlockBody, Header, Signed, TxLegacy};
+    use alloy_primitives::{Address, Bytes, PrimitiveSignature as Signature, TxKind};
+    use alloy_rlp::Encodable;
+    use op_alloy_consensus::{OpBlock, OpTxType, TxDeposit};
+
+    fn legacy_tx(nonce: u64) -> OpTxEnvelope {
+        let tx = TxLegacy {
+            chain_id: Some(10),
+            nonce,
+            gas_price: 1_000_000_000,
+            gas_limit: 21_000,
+            to: TxKind::Call(Address::with_last_byte(1)),
+            ..Default::default()
+        };
+        OpTxEnvelope::Legacy(Signed::new_unchecked(
+            tx,
+            Signature::test_signature(),
+            Default::default(),
+        ))
+    }
+
+    fn batch(txs: &[OpTxEnvelope]) -> SingleBatch {
+        SingleBatch {
+            timestamp: 2,
+            transactions: txs.iter().map(|tx| Bytes::from(tx.encoded_2718())).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn provider(txs: Vec<OpTxEnvelope>) -> TestL2ChainProvider {
+        let block = OpBlock {
+            header: Header { number: 1, timestamp: 2, ..Default::default() },
+            body: BlockBody { transactions: txs, ..Default::default() },
+        };
+        TestL2ChainProvider { op_blocks: vec![block], ..Default::default() }
+    }
+
+    #[test]
+    fn test_reencode_transaction() {
+        let raw = legacy_tx(0).encoded_2718();
+        assert_eq!(reencode_transaction(0, &raw).unwrap(), keccak256(&raw));
+    }
+
+    #[test]
+    fn test_reencode_transaction_trailing_bytes() {
+        let mut raw = legacy_tx(0).encoded_2718();
+        raw.push(0);
+        assert_eq!(reencode_transaction(3, &raw).unwrap_err(), TxEquivalenceError::Reencoding(3));
+    }
+
+    #[test]
+    fn test_reencode_transaction_invalid() {
+        assert!(matches!(
+            reencode_transaction(1, &[0x7f, 0x00]),
+            Err(TxEquivalenceError::Decoding { index: 1, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_check_tx_equivalence() {
+        let mut deposit = vec![OpTxType::Deposit as u8];
+        TxDeposit::default().encode(&mut deposit);
+        let deposit = OpTxEnvelope::decode_2718(&mut deposit.as_slice()).unwrap();
+        let txs = vec![legacy_tx(0), legacy_tx(1)];
+        let mut provider = provider([vec![deposit], txs.clone()].concat());
+        assert!(check_tx_equivalence(&batch(&txs), 1, &mut provider).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_check_tx_equivalence_unavailable() {
+        let txs = vec![legacy_tx(0)];
+        let mut provider = provider(vec![legacy_tx(1)]);
+        assert!(!check_tx_equivalence(&batch(&txs), 2, &mut provider).await.unwrap());
+
+        // A block with a different timestamp is not the block the batch describes.
+        let mut other = batch(&txs);
+        other.timestamp = 4;
+        assert!(!check_tx_equivalence(&other, 1, &mut provider).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_check_tx_equivalence_count_mismatch() {
+        let mut provider = provider(vec![legacy_tx(0)]);
+        let err = check_tx_equivalence(&batch(&[legacy_tx(0), legacy_tx(1)]), 1, &mut provider)
+            .await
+            .unwrap_err();
+        assert_eq!(err, TxEquivalenceError::TransactionCount { number: 1, expected: 1, actual: 2 });
+    }
+
+    #[tokio::test]
+    async fn test_check_tx_equivalence_hash_mismatch() {
+        let mut provider = provider(vec![legacy_tx(0), legacy_tx(1)]);
+        let batch = batch(&[legacy_tx(0), legacy_tx(2)]);
+        let err = check_tx_equivalence(&batch, 1, &mut provider).await.unwrap_err();
+        assert_eq!(
+            err,
+            TxEquivalenceError::HashMismatch {
+                number: 1,
+                index: 1,
+                expected: keccak256(legacy_tx(1).encoded_2718()),
+                actual: keccak256(legacy_tx(2).encoded_2718()),
+            }
+        );
+    }
+}
@@ -0,0 +1,219 @@
+//! Contains the [BatchReader], which decodes [Batch]es from channel data.
+
+use crate::{stages::decompress_channel, types::BatchType};
+use alloc::vec::Vec;
+use alloy_primitives::Bytes;
+use alloy_rlp::{Buf, Decodable, Header};
+use maili_protocol::{
+    Batch, BatchDecodingError, SpanBatchBits, SpanBatchError, SpanBatchPayload, SpanBatchPrefix,
+    SpanBatchTransactions, SpanDecodingError, MAX_SPAN_BATCH_ELEMENTS,
+};
+use op_alloy_genesis::RollupConfig;
+
+/// The length of the parent check and L1 origin check of a span batch prefix.
+const SPAN_BATCH_CHECKS_LENGTH: usize = 40;
+
+/// The length of a span batch transaction signature, excluding the y parity bit.
+const SPAN_BATCH_SIGNATURE_LENGTH: usize = 64;
+
+/// The length of a span batch transaction `to` address.
+const SPAN_BATCH_TO_LENGTH: usize = 20;
+
+/// The [BatchReader] iteratively decodes [Batch]es from the raw data of a channel.
+///
+/// It follows the `BatchReader` of `maili-protocol`, but decodes each batch with [decode_batch],
+/// which rejects the batch data that the upstream decoder panics on.
+#[derive(Debug)]
+pub struct BatchReader {
+    /// The raw data to decode.
+    data: Option<Vec<u8>>,
+    /// The decompressed data.
+    decompressed: Vec<u8>,
+    /// Whether the channel was brotli compressed.
+    brotli: bool,
+    /// The current cursor in the `decompressed` data.
+    cursor: usize,
+    /// The maximum RLP bytes per channel.
+    max_rlp_bytes_per_channel: usize,
+}
+
+impl BatchReader {
+    /// Creates a new [BatchReader] from the given data and max decompressed RLP bytes per channel.
+    pub fn new<T>(data: T, max_rlp_bytes_per_channel: usize) -> Self
+    where
+        T: Into<Vec<u8>>,
+    {
+        Self {
+            data: Some(data.into()),
+            decompressed: Vec::new(),
+            brotli: false,
+            cursor: 0,
+            max_rlp_bytes_per_channel,
+        }
+    }
+
+    /// Pulls out the next batch from the reader.
+    pub fn next_batch(&mut self, cfg: &RollupConfig) -> Option<Batch> {
+        if let Some(data) = self.data.take() {
+            (self.decompressed, self.brotli) =
+                decompress_channel(&data, self.max_rlp_bytes_per_channel)?;
+        }
+
+        let reader = &mut &self.decompressed[self.cursor..];
+        let bytes = Bytes::decode(reader).ok()?;
+        let batch = decode_batch(&bytes, cfg).ok()?;
+
+        // Brotli compressed channels are only valid after the Fjord hardfork.
+        if self.brotli && !cfg.is_fjord_active(batch.timestamp()) {
+            return None;
+        }
+
+        self.cursor = self.decompressed.len() - reader.len();
+        Some(batch)
+    }
+}
+
+/// Decodes a [Batch] from its type byte and payload.
+///
+/// Unlike [Batch::decode], unknown batch types and span batches with truncated fixed-size fields
+/// are rejected with an error, rather than panicking. Batch data is untrusted batcher data, so a
+/// panic while decoding it would halt derivation.
+pub fn decode_batch(data: &[u8], cfg: &RollupConfig) -> Result<Batch, BatchDecodingError> {
+    let batch_type = data.first().copied().map(BatchType).ok_or(BatchDecodingError::EmptyBuffer)?;
+    match batch_type {
+        BatchType::SINGLE => {}
+        BatchType::SPAN => check_span_batch_bounds(&data[1..])?,
+        _ => {
+            return Err(BatchDecodingError::AlloyRlpError(alloy_rlp::Error::Custom(
+                "unknown batch type",
+            )))
+        }
+    }
+    Batch::decode(&mut &data[..], cfg)
+}
+
+/// Walks the wire format of a span batch, checking that its fixed-size fields are in bounds.
+///
+/// The variable-length fields are decoded with the `maili-protocol` decoders, which return an
+/// error on invalid data. The fixed-size fields are sliced by those decoders without a length
+/// check, so their lengths are checked here.
+fn check_span_batch_bounds(mut r: &[u8]) -> Result<(), SpanBatchError> {
+    let r = &mut r;
+
+    let mut prefix = SpanBatchPrefix::default();
+    prefix.decode_rel_timestamp(r)?;
+    prefix.decode_l1_origin_num(r)?;
+    if r.len() < SPAN_BATCH_CHECKS_LENGTH {
+        return Err(SpanDecodingError::ParentCheck.into());
+    }
+    r.advance(SPAN_BATCH_CHECKS_LENGTH);
+
+    let mut payload = SpanBatchPayload::default();
+    payload.decode_block_count(r)?;
+    payload.decode_origin_bits(r)?;
+    payload.decode_block_tx_counts(r)?;
+    let total = payload
+        .block_tx_counts
+        .iter()
+        .try_fold(0u64, |acc, count| acc.checked_add(*count))
+        .filter(|total| *total <= MAX_SPAN_BATCH_ELEMENTS)
+        .ok_or(SpanBatchError::TooBigSpanBatchSize)?;
+
+    let mut txs = SpanBatchTransactions { total_block_tx_count: total, ..Default::default() };
+    txs.decode_contract_creation_bits(r)?;
+    SpanBatchBits::decode(r, total as usize)?;
+    let signatures = total as usize * SPAN_BATCH_SIGNATURE_LENGTH;
+    if r.len() < signatures {
+        return Err(SpanDecodingError::InvalidTransactionSignature.into());
+    }
+    r.advance(signatures);
+    let tos = (total - txs.contract_creation_count()) as usize * SPAN_BATCH_TO_LENGTH;
+    if r.len() < tos {
+        return Err(SpanDecodingError::InvalidTransactionData.into());
+    }
+    r.advance(tos);
+
+    // The transaction data is sliced by the length in its RLP header.
+    for _ in 0..total {
+        match r.first() {
+            Some(tx_type) if *tx_type <= 0x7F => r.advance(1),
+            Some(_) => {}
+            None => return Err(SpanDecodingError::InvalidTransactionData.into()),
+        }
+        let header =
+            Header::decode(&mut &r[..]).map_err(|_| SpanDecodingError::InvalidTransactionData)?;
+        match header.payload_length.checked_add(header.length()) {
+            Some(length) if length <= r.len() => r.advance(length),
+            _ => return Err(SpanDecodingError::InvalidTransactionData.into()),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        constants::MAX_RLP_BYTES_PER_CHANNEL_FJORD, test_utils::new_compressed_batch_data,
+    };
+    use alloc::vec;
+    use maili_protocol::{SingleBatch, SpanBatch, SpanBatchElement};
+
+    #[test]
+    fn test_batch_reader_matches_upstream() {
+        let raw = new_compressed_batch_data();
+        let max = MAX_RLP_BYTES_PER_CHANNEL_FJORD as usize;
+        let cfg = RollupConfig::default();
+        let mut reader = BatchReader::new(raw.as_slice(), max);
+        let mut upstream = maili_protocol::BatchReader::new(raw.as_slice(), max);
+        let batch = reader.next_batch(&cfg).unwrap();
+        assert_eq!(Some(batch), upstream.next_batch(&cfg));
+        assert_eq!(reader.next_batch(&cfg), None);
+    }
+
+    #[test]
+    fn test_decode_batch_unknown_type() {
+        let cfg = RollupConfig::default();
+        assert_eq!(decode_batch(&[], &cfg), Err(BatchDecodingError::EmptyBuffer));
+        assert!(matches!(
+            decode_batch(&[0x02, 0xc0], &cfg),
+            Err(BatchDecodingError::AlloyRlpError(_))
+        ));
+    }
+
+    #[test]
+    fn test_decode_batch_single() {
+        let mut encoded = Vec::new();
+        let batch = Batch::Single(SingleBatch::default());
+        batch.encode(&mut encoded).unwrap();
+        assert_eq!(decode_batch(&encoded, &RollupConfig::default()), Ok(batch));
+    }
+
+    #[test]
+    fn test_decode_batch_truncated_span_batch() {
+        let span = SpanBatch {
+            batches: vec![SpanBatchElement::default()],
+            block_tx_counts: vec![0],
+            ..Default::default()
+        };
+        let mut encoded = Vec::new();
+        Batch::Span(span).encode(&mut encoded).unwrap();
+        let cfg = RollupConfig::default();
+        decode_batch(&encoded, &cfg).unwrap();
+
+        // Truncated within the parent and L1 origin checks.
+        assert_eq!(
+            decode_batch(&encoded[..10], &cfg),
+            Err(SpanBatchError::Decoding(SpanDecodingError::ParentCheck).into())
+        );
+
+        // A transaction is declared, but its signature is truncated.
+        let mut raw = vec![BatchType::SPAN.as_u8(), 0, 0];
+        raw.extend_from_slice(&[0; SPAN_BATCH_CHECKS_LENGTH]);
+        raw.extend_from_slice(&[1, 0, 1, 0, 0, 0xaa]);
+        assert_eq!(
+            decode_batch(&raw, &cfg),
+            Err(SpanBatchError::Decoding(SpanDecodingError::InvalidTransactionSignature).into())
+        );
+    }
+}
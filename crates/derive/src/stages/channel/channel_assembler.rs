@@ -1,19 +1,19 @@
 //! This module contains the [ChannelAssembler] stage.
 
-use super::{ChannelReaderProvider, NextFrameProvider};
+use super::{channel_snapshot, ChannelReaderProvider, NextFrameProvider};
 use crate::{
+    constants::ProtocolConstants,
     prelude::{OriginProvider, PipelineError},
-    traits::{OriginAdvancer, SignalReceiver},
-    types::{PipelineResult, Signal},
+    traits::{OriginAdvancer, SignalReceiver, StageSnapshot},
+    types::{ChannelId, PipelineResult, PipelineSnapshot, Signal},
 };
 use alloc::{boxed::Box, sync::Arc};
-use alloy_primitives::{hex, Bytes};
+use alloy_primitives::Bytes;
 use async_trait::async_trait;
 use core::fmt::Debug;
 use maili_protocol::{BlockInfo, Channel};
-use op_alloy_genesis::{
-    RollupConfig, MAX_RLP_BYTES_PER_CHANNEL_BEDROCK, MAX_RLP_BYTES_PER_CHANNEL_FJORD,
-};
+use op_alloy_genesis::RollupConfig;
+use tracing::Instrument;
 
 /// The [ChannelAssembler] stage is responsible for assembling the [Frame]s from the [FrameQueue]
 /// stage into a raw compressed [Channel].
@@ -46,12 +46,12 @@ where
     /// Returns whether or not the channel currently being assembled has timed out.
     pub fn is_timed_out(&self) -> PipelineResult<bool> {
         let origin = self.origin().ok_or(PipelineError::MissingOrigin.crit())?;
+        let channel_timeout =
+            ProtocolConstants::for_timestamp(&self.cfg, origin.timestamp).channel_timeout;
         let is_timed_out = self
             .channel
             .as_ref()
-            .map(|c| {
-                c.open_block_number() + self.cfg.channel_timeout(origin.timestamp) < origin.number
-            })
+            .map(|c| c.open_block_number() + channel_timeout < origin.number)
             .unwrap_or_default();
 
         Ok(is_timed_out)
@@ -72,7 +72,7 @@ where
                 warn!(
                     target: "channel-assembler",
                     "Channel (ID: {}) timed out at L1 origin #{}, open block #{}. Discarding channel.",
-                    hex::encode(channel.id()),
+                    ChannelId::from(channel.id()),
                     origin.number,
                     channel.open_block_number()
                 );
@@ -81,14 +81,18 @@ where
         }
 
         // Grab the next frame from the previous stage.
-        let next_frame = self.prev.next_frame().await?;
+        let next_frame = self
+            .prev
+            .next_frame()
+            .instrument(debug_span!(target: "channel-assembler", "next_frame"))
+            .await?;
 
         // Start a new channel if the frame number is 0.
         if next_frame.number == 0 {
             info!(
                 target: "channel-assembler",
                 "Starting new channel (ID: {}) at L1 origin #{}",
-                hex::encode(next_frame.id),
+                ChannelId::from(next_frame.id),
                 origin.number
             );
             self.channel = Some(Channel::new(next_frame.id, origin));
@@ -101,29 +105,25 @@ where
                 target: "channel-assembler",
                 "Adding frame #{} to channel (ID: {}) at L1 origin #{}",
                 next_frame.number,
-                hex::encode(channel.id()),
+                ChannelId::from(channel.id()),
                 origin.number
             );
             if channel.add_frame(next_frame, origin).is_err() {
                 error!(
                     target: "channel-assembler",
                     "Failed to add frame to channel (ID: {}) at L1 origin #{}",
-                    hex::encode(channel.id()),
+                    ChannelId::from(channel.id()),
                     origin.number
                 );
                 return Err(PipelineError::NotEnoughData.temp());
             }
 
-            let max_rlp_bytes_per_channel = if self.cfg.is_fjord_active(origin.timestamp) {
-                MAX_RLP_BYTES_PER_CHANNEL_FJORD
-            } else {
-                MAX_RLP_BYTES_PER_CHANNEL_BEDROCK
-            };
-            if channel.size() > max_rlp_bytes_per_channel as usize {
+            let constants = ProtocolConstants::for_timestamp(&self.cfg, origin.timestamp);
+            if channel.size() > constants.max_channel_size {
                 warn!(
                     target: "channel-assembler",
                     "Compressed channel size exceeded max RLP bytes per channel, dropping channel (ID: {}) with {} bytes",
-                    hex::encode(channel.id()),
+                    ChannelId::from(channel.id()),
                     channel.size()
                 );
                 self.channel = None;
@@ -138,7 +138,7 @@ where
                 info!(
                     target: "channel-assembler",
                     "Channel (ID: {}) ready for decompression.",
-                    hex::encode(channel.id()),
+                    ChannelId::from(channel.id()),
                 );
 
                 // Reset the channel and return the compressed bytes.
@@ -170,6 +170,17 @@ where
     }
 }
 
+impl<P> StageSnapshot for ChannelAssembler<P>
+where
+    P: NextFrameProvider + OriginAdvancer + OriginProvider + SignalReceiver + StageSnapshot + Debug,
+{
+    fn snapshot(&self, snapshot: &mut PipelineSnapshot) {
+        let origin = self.origin().map_or(0, |o| o.number);
+        snapshot.channels.extend(self.channel.as_ref().map(|c| channel_snapshot(c, origin)));
+        self.prev.snapshot(snapshot);
+    }
+}
+
 #[async_trait]
 impl<P> SignalReceiver for ChannelAssembler<P>
 where
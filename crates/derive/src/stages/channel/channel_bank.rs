@@ -1,24 +1,74 @@
 //! This module contains the `ChannelBank` struct.
 
-use super::NextFrameProvider;
+use super::{channel_snapshot, NextFrameProvider};
 use crate::{
+    constants::ProtocolConstants,
     errors::{PipelineError, PipelineErrorKind},
     stages::ChannelReaderProvider,
-    traits::{OriginAdvancer, OriginProvider, SignalReceiver},
-    types::{PipelineResult, Signal},
+    traits::{OriginAdvancer, OriginProvider, SignalReceiver, StageSnapshot},
+    types::{ChannelId, PipelineResult, PipelineSnapshot, Signal},
 };
 use alloc::{boxed::Box, collections::VecDeque, sync::Arc};
-use alloy_primitives::{hex, map::HashMap, Bytes};
+use alloy_primitives::{map::HashMap, Bytes};
 use async_trait::async_trait;
 use core::fmt::Debug;
-use maili_protocol::{BlockInfo, Channel, ChannelId, Frame};
+use maili_protocol::{BlockInfo, Channel, Frame, CHANNEL_ID_LENGTH};
 use op_alloy_genesis::RollupConfig;
+use tracing::Instrument;
+
+/// The policy used to pick which channel to evict when the [ChannelBank] is too large.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub enum ChannelBankEviction {
+    /// Evict the channel that was opened first. This is the protocol behavior.
+    #[default]
+    OldestFirst,
+    /// Evict the channel that has gone the longest without receiving a frame.
+    LeastRecentlyUpdated,
+}
 
-/// The maximum size of a channel bank.
-pub(crate) const MAX_CHANNEL_BANK_SIZE: usize = 100_000_000;
+/// Configuration for the [ChannelBank]'s memory limits.
+///
+/// The defaults match the protocol. Deviating from them can change the derived chain, and is only
+/// intended for memory-constrained environments that know their batcher's behavior.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct ChannelBankConfig {
+    /// Overrides the maximum total size of the channel bank. Defaults to the fork-dependent
+    /// protocol limit.
+    pub max_size: Option<usize>,
+    /// The maximum size of a single channel. Channels that grow beyond this size are dropped.
+    pub max_channel_size: Option<usize>,
+    /// Overrides the channel timeout before Granite, in L1 blocks. Defaults to the rollup
+    /// config's timeout.
+    pub channel_timeout: Option<u64>,
+    /// Overrides the channel timeout after Granite, in L1 blocks. Defaults to the rollup config's
+    /// Granite timeout.
+    pub granite_channel_timeout: Option<u64>,
+    /// The eviction policy used when pruning the channel bank.
+    pub eviction: ChannelBankEviction,
+}
 
-/// The maximum size of a channel bank after the Fjord Hardfork.
-pub(crate) const FJORD_MAX_CHANNEL_BANK_SIZE: usize = 1_000_000_000;
+/// A snapshot of the [ChannelBank]'s memory usage.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct ChannelBankMetrics {
+    /// The number of channels currently buffered.
+    pub channels: usize,
+    /// The total size of the buffered channels.
+    pub size: usize,
+    /// The maximum size of the channel bank at the current origin.
+    pub max_size: usize,
+    /// The number of channels evicted by pruning since the last reset.
+    pub evicted: u64,
+    /// The number of channels dropped for exceeding the maximum channel size since the last reset.
+    pub oversized: u64,
+    /// The number of channels dropped after timing out since the last reset.
+    pub timed_out: u64,
+}
 
 /// [ChannelBank] is a stateful stage that does the following:
 /// 1. Unmarshalls frames from L1 transaction data
@@ -39,9 +89,17 @@ where
     /// The rollup configuration.
     pub(crate) cfg: Arc<RollupConfig>,
     /// Map of channels by ID.
-    pub(crate) channels: HashMap<ChannelId, Channel>,
+    pub(crate) channels: HashMap<[u8; CHANNEL_ID_LENGTH], Channel>,
     /// Channels in FIFO order.
-    pub(crate) channel_queue: VecDeque<ChannelId>,
+    pub(crate) channel_queue: VecDeque<[u8; CHANNEL_ID_LENGTH]>,
+    /// The sequence number of the last frame ingested into each channel.
+    pub(crate) last_updated: HashMap<[u8; CHANNEL_ID_LENGTH], u64>,
+    /// The number of frames ingested since the last reset.
+    pub(crate) frame_seq: u64,
+    /// The memory limit configuration.
+    pub(crate) config: ChannelBankConfig,
+    /// The memory usage counters.
+    pub(crate) metrics: ChannelBankMetrics,
     /// The previous stage of the derivation pipeline.
     pub(crate) prev: P,
 }
@@ -52,7 +110,37 @@ where
 {
     /// Create a new [ChannelBank] stage.
     pub fn new(cfg: Arc<RollupConfig>, prev: P) -> Self {
-        Self { cfg, channels: HashMap::default(), channel_queue: VecDeque::new(), prev }
+        Self::with_config(cfg, prev, ChannelBankConfig::default())
+    }
+
+    /// Create a new [ChannelBank] stage with the given [ChannelBankConfig].
+    pub fn with_config(cfg: Arc<RollupConfig>, prev: P, config: ChannelBankConfig) -> Self {
+        Self {
+            cfg,
+            channels: HashMap::default(),
+            channel_queue: VecDeque::new(),
+            last_updated: HashMap::default(),
+            frame_seq: 0,
+            config,
+            metrics: ChannelBankMetrics::default(),
+            prev,
+        }
+    }
+
+    /// Returns the [ChannelBankConfig].
+    pub const fn config(&self) -> &ChannelBankConfig {
+        &self.config
+    }
+
+    /// Returns a snapshot of the channel bank's memory usage.
+    pub fn metrics(&self) -> ChannelBankMetrics {
+        let max_size = self.origin().map_or(0, |o| self.max_size(o.timestamp));
+        ChannelBankMetrics {
+            channels: self.channels.len(),
+            size: self.size(),
+            max_size,
+            ..self.metrics
+        }
     }
 
     /// Returns the size of the channel bank by accumulating over all channels.
@@ -60,28 +148,65 @@ where
         self.channels.iter().fold(0, |acc, (_, c)| acc + c.size())
     }
 
+    /// Returns the maximum size of the channel bank at the given L1 timestamp.
+    pub fn max_size(&self, timestamp: u64) -> usize {
+        self.config.max_size.unwrap_or_else(|| {
+            ProtocolConstants::for_timestamp(&self.cfg, timestamp).max_channel_bank_size
+        })
+    }
+
+    /// Returns the channel timeout, in L1 blocks, at the given L1 timestamp.
+    ///
+    /// The timeout is reduced at the Granite activation, so a channel opened before Granite may
+    /// time out earlier than it would have at its open block.
+    pub fn channel_timeout(&self, timestamp: u64) -> u64 {
+        let timeout = if self.cfg.is_granite_active(timestamp) {
+            self.config.granite_channel_timeout
+        } else {
+            self.config.channel_timeout
+        };
+        timeout.unwrap_or_else(|| {
+            ProtocolConstants::for_timestamp(&self.cfg, timestamp).channel_timeout
+        })
+    }
+
     /// Prunes the Channel bank, until it is below the max channel bank size.
-    /// Prunes from the high-priority channel since it failed to be read.
+    /// By default, prunes from the high-priority channel since it failed to be read.
     pub fn prune(&mut self) -> PipelineResult<()> {
         let mut total_size = self.size();
         let origin = self.origin().ok_or(PipelineError::MissingOrigin.crit())?;
-        let max_channel_bank_size = if self.cfg.is_fjord_active(origin.timestamp) {
-            FJORD_MAX_CHANNEL_BANK_SIZE
-        } else {
-            MAX_CHANNEL_BANK_SIZE
-        };
+        let max_channel_bank_size = self.max_size(origin.timestamp);
         while total_size > max_channel_bank_size {
-            let id =
-                self.channel_queue.pop_front().ok_or(PipelineError::ChannelProviderEmpty.crit())?;
-            let channel = self.channels.remove(&id).ok_or(PipelineError::ChannelNotFound.crit())?;
+            let index = match self.config.eviction {
+                ChannelBankEviction::OldestFirst => 0,
+                ChannelBankEviction::LeastRecentlyUpdated => self
+                    .channel_queue
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_, id)| self.last_updated.get(*id).copied().unwrap_or_default())
+                    .map_or(0, |(i, _)| i),
+            };
+            let id = self
+                .channel_queue
+                .remove(index)
+                .ok_or(PipelineError::ChannelProviderEmpty.crit())?;
+            let channel = self.remove_channel(&id).ok_or(PipelineError::ChannelNotFound.crit())?;
             total_size -= channel.size();
+            self.metrics.evicted += 1;
         }
         Ok(())
     }
 
+    /// Removes the channel with the given ID from the bank, but not from the channel queue.
+    fn remove_channel(&mut self, id: &[u8; CHANNEL_ID_LENGTH]) -> Option<Channel> {
+        self.last_updated.remove(id);
+        self.channels.remove(id)
+    }
+
     /// Adds new L1 data to the channel bank. Should only be called after all data has been read.
     pub fn ingest_frame(&mut self, frame: Frame) -> PipelineResult<()> {
         let origin = self.origin().ok_or(PipelineError::MissingOrigin.crit())?;
+        let channel_timeout = self.channel_timeout(origin.timestamp);
 
         // Get the channel for the frame, or create a new one if it doesn't exist.
         let current_channel = match self.channels.get_mut(&frame.id) {
@@ -95,12 +220,10 @@ where
         };
 
         // Check if the channel is not timed out. If it has, ignore the frame.
-        if current_channel.open_block_number() + self.cfg.channel_timeout(origin.timestamp) <
-            origin.number
-        {
+        if current_channel.open_block_number() + channel_timeout < origin.number {
             warn!(
                 target: "channel-bank",
-                "Channel (ID: {}) timed out", hex::encode(frame.id)
+                "Channel (ID: {}) timed out", ChannelId::from(frame.id)
             );
             return Ok(());
         }
@@ -112,6 +235,21 @@ where
             return Ok(());
         }
 
+        // Drop the channel if it has grown beyond the maximum channel size.
+        if self.config.max_channel_size.is_some_and(|max| current_channel.size() > max) {
+            warn!(
+                target: "channel-bank",
+                "Channel (ID: {}) exceeds the maximum channel size, dropping", ChannelId::from(frame_id)
+            );
+            self.remove_channel(&frame_id);
+            self.channel_queue.retain(|id| *id != frame_id);
+            self.metrics.oversized += 1;
+            return Ok(());
+        }
+
+        self.frame_seq += 1;
+        self.last_updated.insert(frame_id, self.frame_seq);
+
         self.prune()
     }
 
@@ -131,14 +269,14 @@ where
         let channel =
             self.channels.get(&first).ok_or(PipelineError::ChannelProviderEmpty.crit())?;
         let origin = self.origin().ok_or(PipelineError::ChannelProviderEmpty.crit())?;
-        if channel.open_block_number() + self.cfg.channel_timeout(origin.timestamp) < origin.number
-        {
+        if channel.open_block_number() + self.channel_timeout(origin.timestamp) < origin.number {
             warn!(
                 target: "channel-bank",
-                "Channel (ID: {}) timed out", hex::encode(first)
+                "Channel (ID: {}) timed out", ChannelId::from(first)
             );
-            self.channels.remove(&first);
+            self.remove_channel(&first);
             self.channel_queue.pop_front();
+            self.metrics.timed_out += 1;
             return Ok(None);
         }
 
@@ -166,14 +304,14 @@ where
             self.channels.get(&channel_id).ok_or(PipelineError::ChannelProviderEmpty.crit())?;
         let origin = self.origin().ok_or(PipelineError::MissingOrigin.crit())?;
 
-        let timed_out = channel.open_block_number() + self.cfg.channel_timeout(origin.timestamp) <
-            origin.number;
+        let timed_out =
+            channel.open_block_number() + self.channel_timeout(origin.timestamp) < origin.number;
         if timed_out || !channel.is_ready() {
             return Err(PipelineError::Eof.temp());
         }
 
         let frame_data = channel.frame_data();
-        self.channels.remove(&channel_id);
+        self.remove_channel(&channel_id);
         self.channel_queue.remove(index);
 
         frame_data.ok_or(PipelineError::ChannelProviderEmpty.crit())
@@ -206,7 +344,12 @@ where
         };
 
         // Load the data into the channel bank
-        let frame = match self.prev.next_frame().await {
+        let frame = match self
+            .prev
+            .next_frame()
+            .instrument(debug_span!(target: "channel-bank", "next_frame"))
+            .await
+        {
             Ok(f) => f,
             Err(e) => {
                 return Err(e);
@@ -227,6 +370,22 @@ where
     }
 }
 
+impl<P> StageSnapshot for ChannelBank<P>
+where
+    P: NextFrameProvider + OriginAdvancer + OriginProvider + SignalReceiver + StageSnapshot + Debug,
+{
+    fn snapshot(&self, snapshot: &mut PipelineSnapshot) {
+        let origin = self.origin().map_or(0, |o| o.number);
+        snapshot.channels.extend(
+            self.channel_queue
+                .iter()
+                .filter_map(|id| self.channels.get(id))
+                .map(|c| channel_snapshot(c, origin)),
+        );
+        self.prev.snapshot(snapshot);
+    }
+}
+
 #[async_trait]
 impl<P> SignalReceiver for ChannelBank<P>
 where
@@ -236,6 +395,9 @@ where
         self.prev.signal(signal).await?;
         self.channels.clear();
         self.channel_queue = VecDeque::with_capacity(10);
+        self.last_updated.clear();
+        self.frame_seq = 0;
+        self.metrics = ChannelBankMetrics::default();
         Ok(())
     }
 }
@@ -244,8 +406,9 @@ where
 mod tests {
     use super::*;
     use crate::{
+        constants::{FJORD_MAX_CHANNEL_BANK_SIZE, MAX_CHANNEL_BANK_SIZE},
         test_utils::{CollectingLayer, TestNextFrameProvider, TraceStorage},
-        types::ResetSignal,
+        types::{ChannelSnapshot, ResetSignal},
     };
     use alloc::{vec, vec::Vec};
     use tracing::Level;
@@ -290,7 +453,7 @@ mod tests {
         let mock = TestNextFrameProvider::new(vec![]);
         let cfg = Arc::new(RollupConfig::default());
         let mut channel_bank = ChannelBank::new(cfg, mock);
-        let id: ChannelId = [0xFF; 16];
+        let id = [0xFF; 16];
         channel_bank.channel_queue.push_back(id);
         let mut channel = Channel::new(id, BlockInfo::default());
         channel
@@ -325,7 +488,7 @@ mod tests {
         let mock = TestNextFrameProvider::new(vec![]);
         let cfg = Arc::new(RollupConfig::default());
         let mut channel_bank = ChannelBank::new(cfg, mock);
-        let id: ChannelId = [0xFF; 16];
+        let id = [0xFF; 16];
         channel_bank.channel_queue.push_back(id);
         let mut channel = Channel::new(id, BlockInfo::default());
         channel
@@ -360,7 +523,7 @@ mod tests {
         let mock = TestNextFrameProvider::new(vec![]);
         let cfg = Arc::new(RollupConfig { canyon_time: Some(0), ..Default::default() });
         let mut channel_bank = ChannelBank::new(cfg, mock);
-        let id: ChannelId = [0xFF; 16];
+        let id = [0xFF; 16];
         channel_bank.channel_queue.push_back(id);
         let mut channel = Channel::new(id, BlockInfo::default());
         channel
@@ -488,6 +651,166 @@ mod tests {
         assert_eq!(channel_bank.size(), current_size);
     }
 
+    fn ingest_eviction_frames(channel_bank: &mut ChannelBank<TestNextFrameProvider>) {
+        channel_bank.ingest_frame(crate::frame!(0xA1, 0, vec![0xDD; 50], false)).unwrap();
+        channel_bank.ingest_frame(crate::frame!(0xB1, 0, vec![0xDD; 50], false)).unwrap();
+        channel_bank.ingest_frame(crate::frame!(0xC1, 0, vec![0xDD; 50], false)).unwrap();
+        channel_bank.ingest_frame(crate::frame!(0xA1, 1, vec![0xDD; 50], false)).unwrap();
+        channel_bank.ingest_frame(crate::frame!(0xD1, 0, vec![0xDD; 50], false)).unwrap();
+    }
+
+    #[test]
+    fn test_prune_oldest_first() {
+        let mock = TestNextFrameProvider::new(vec![]);
+        let cfg = Arc::new(RollupConfig::default());
+        let config = ChannelBankConfig { max_size: Some(1000), ..Default::default() };
+        let mut channel_bank = ChannelBank::with_config(cfg, mock, config);
+        ingest_eviction_frames(&mut channel_bank);
+        assert_eq!(channel_bank.channel_queue, vec![[0xB1; 16], [0xC1; 16], [0xD1; 16]]);
+        assert_eq!(channel_bank.metrics().evicted, 1);
+    }
+
+    #[test]
+    fn test_prune_least_recently_updated() {
+        let mock = TestNextFrameProvider::new(vec![]);
+        let cfg = Arc::new(RollupConfig::default());
+        let config = ChannelBankConfig {
+            max_size: Some(1000),
+            eviction: ChannelBankEviction::LeastRecentlyUpdated,
+            ..Default::default()
+        };
+        let mut channel_bank = ChannelBank::with_config(cfg, mock, config);
+        ingest_eviction_frames(&mut channel_bank);
+        assert_eq!(channel_bank.channel_queue, vec![[0xA1; 16], [0xC1; 16], [0xD1; 16]]);
+        assert!(!channel_bank.last_updated.contains_key(&[0xB1; 16]));
+
+        let metrics = channel_bank.metrics();
+        assert_eq!(
+            metrics,
+            ChannelBankMetrics {
+                channels: 3,
+                size: 1000,
+                max_size: 1000,
+                evicted: 1,
+                oversized: 0,
+                timed_out: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_ingest_oversized_channel() {
+        let mock = TestNextFrameProvider::new(vec![]);
+        let cfg = Arc::new(RollupConfig::default());
+        let config = ChannelBankConfig { max_channel_size: Some(400), ..Default::default() };
+        let mut channel_bank = ChannelBank::with_config(cfg, mock, config);
+        channel_bank.ingest_frame(crate::frame!(0xA1, 0, vec![0xDD; 50], false)).unwrap();
+        assert_eq!(channel_bank.channels.len(), 1);
+        channel_bank.ingest_frame(crate::frame!(0xA1, 1, vec![0xDD; 50], false)).unwrap();
+        assert!(channel_bank.channels.is_empty());
+        assert!(channel_bank.channel_queue.is_empty());
+        assert_eq!(channel_bank.metrics().oversized, 1);
+    }
+
+    #[test]
+    fn test_channel_bank_snapshot() {
+        let mock = TestNextFrameProvider::new(vec![]);
+        let mut channel_bank = ChannelBank::new(Arc::new(RollupConfig::default()), mock);
+        channel_bank.prev.block_info = Some(BlockInfo { number: 2, ..Default::default() });
+        channel_bank.ingest_frame(crate::frame!(0xAA, 0, vec![0xDD; 50], false)).unwrap();
+        channel_bank.prev.block_info = Some(BlockInfo { number: 5, ..Default::default() });
+        channel_bank.ingest_frame(crate::frame!(0xBB, 0, vec![0xDD; 50], true)).unwrap();
+
+        let mut snapshot = PipelineSnapshot::default();
+        channel_bank.snapshot(&mut snapshot);
+        assert_eq!(
+            snapshot.channels,
+            [
+                ChannelSnapshot {
+                    id: ChannelId([0xAA; 16]),
+                    size: 250,
+                    open_block: 2,
+                    age: 3,
+                    ready: false
+                },
+                ChannelSnapshot {
+                    id: ChannelId([0xBB; 16]),
+                    size: 250,
+                    open_block: 5,
+                    age: 0,
+                    ready: true
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_channel_bank_config_overrides() {
+        let mock = TestNextFrameProvider::new(vec![]);
+        let cfg = Arc::new(RollupConfig { channel_timeout: 300, ..Default::default() });
+        let channel_bank = ChannelBank::new(cfg.clone(), TestNextFrameProvider::new(vec![]));
+        assert_eq!(channel_bank.channel_timeout(0), 300);
+        assert_eq!(channel_bank.max_size(0), MAX_CHANNEL_BANK_SIZE);
+
+        let config = ChannelBankConfig {
+            max_size: Some(10),
+            channel_timeout: Some(5),
+            ..Default::default()
+        };
+        let channel_bank = ChannelBank::with_config(cfg, mock, config);
+        assert_eq!(channel_bank.config(), &config);
+        assert_eq!(channel_bank.channel_timeout(0), 5);
+        assert_eq!(channel_bank.max_size(0), 10);
+    }
+
+    #[test]
+    fn test_channel_timeout_granite_boundary() {
+        let cfg = Arc::new(RollupConfig {
+            channel_timeout: 300,
+            granite_channel_timeout: 50,
+            granite_time: Some(100),
+            ..Default::default()
+        });
+        let channel_bank = ChannelBank::new(cfg.clone(), TestNextFrameProvider::new(vec![]));
+        assert_eq!(channel_bank.channel_timeout(99), 300);
+        assert_eq!(channel_bank.channel_timeout(100), 50);
+
+        // Overrides only apply to their fork.
+        let config = ChannelBankConfig { granite_channel_timeout: Some(5), ..Default::default() };
+        let channel_bank =
+            ChannelBank::with_config(cfg, TestNextFrameProvider::new(vec![]), config);
+        assert_eq!(channel_bank.channel_timeout(99), 300);
+        assert_eq!(channel_bank.channel_timeout(100), 5);
+    }
+
+    #[test]
+    fn test_channel_timeout_across_granite_activation() {
+        let cfg = Arc::new(RollupConfig {
+            channel_timeout: 300,
+            granite_channel_timeout: 50,
+            granite_time: Some(100),
+            ..Default::default()
+        });
+
+        // A channel opened before Granite times out with the Granite timeout once it is active.
+        for (timestamp, timed_out) in [(99, false), (100, true)] {
+            let mut channel_bank =
+                ChannelBank::new(cfg.clone(), TestNextFrameProvider::new(vec![]));
+            channel_bank.prev.block_info = Some(BlockInfo { number: 10, ..Default::default() });
+            channel_bank.ingest_frame(crate::frame!(0xFF, 0, vec![0xDD; 50], false)).unwrap();
+
+            channel_bank.prev.block_info =
+                Some(BlockInfo { number: 61, timestamp, ..Default::default() });
+            if timed_out {
+                assert_eq!(channel_bank.read(), Ok(None));
+                assert_eq!(channel_bank.metrics().timed_out, 1);
+            } else {
+                assert_eq!(channel_bank.read(), Err(PipelineError::Eof.temp()));
+                assert_eq!(channel_bank.channels.len(), 1);
+            }
+        }
+    }
+
     #[tokio::test]
     async fn test_read_empty_channel_bank() {
         let frames = [crate::frame!(0xFF, 0, vec![0xDD; 50], true)];
@@ -1,10 +1,13 @@
 //! This module contains the [ChannelProvider] stage.
 
-use super::{ChannelAssembler, ChannelBank, ChannelReaderProvider, NextFrameProvider};
+use super::{
+    ChannelAssembler, ChannelBank, ChannelBankConfig, ChannelBankEviction, ChannelReaderProvider,
+    NextFrameProvider,
+};
 use crate::{
     errors::PipelineError,
-    traits::{OriginAdvancer, OriginProvider, SignalReceiver},
-    types::{PipelineResult, Signal},
+    traits::{OriginAdvancer, OriginProvider, SignalReceiver, StageSnapshot},
+    types::{PipelineResult, PipelineSnapshot, Signal},
 };
 use alloc::{boxed::Box, sync::Arc};
 use alloy_primitives::Bytes;
@@ -40,6 +43,8 @@ where
     ///
     /// Must be [None] if `prev` or `channel_bank` is [Some].
     channel_assembler: Option<ChannelAssembler<P>>,
+    /// The configuration used when constructing the [ChannelBank].
+    bank_config: ChannelBankConfig,
 }
 
 impl<P> ChannelProvider<P>
@@ -48,7 +53,30 @@ where
 {
     /// Creates a new [ChannelProvider] with the given configuration and previous stage.
     pub const fn new(cfg: Arc<RollupConfig>, prev: P) -> Self {
-        Self { cfg, prev: Some(prev), channel_bank: None, channel_assembler: None }
+        Self {
+            cfg,
+            prev: Some(prev),
+            channel_bank: None,
+            channel_assembler: None,
+            bank_config: ChannelBankConfig {
+                max_size: None,
+                max_channel_size: None,
+                channel_timeout: None,
+                granite_channel_timeout: None,
+                eviction: ChannelBankEviction::OldestFirst,
+            },
+        }
+    }
+
+    /// Sets the [ChannelBankConfig] used when the [ChannelBank] is active.
+    pub const fn with_channel_bank_config(mut self, config: ChannelBankConfig) -> Self {
+        self.bank_config = config;
+        self
+    }
+
+    /// Returns the active [ChannelBank], if Holocene is not active.
+    pub const fn channel_bank(&self) -> Option<&ChannelBank<P>> {
+        self.channel_bank.as_ref()
     }
 
     /// Attempts to update the active stage of the mux.
@@ -60,7 +88,8 @@ where
             if self.cfg.is_holocene_active(origin.timestamp) {
                 self.channel_assembler = Some(ChannelAssembler::new(self.cfg.clone(), prev));
             } else {
-                self.channel_bank = Some(ChannelBank::new(self.cfg.clone(), prev));
+                self.channel_bank =
+                    Some(ChannelBank::with_config(self.cfg.clone(), prev, self.bank_config));
             }
         } else if self.channel_bank.is_some() && self.cfg.is_holocene_active(origin.timestamp) {
             // If the channel bank is active and Holocene is also active, transition to the channel
@@ -75,7 +104,11 @@ where
             // until Holocene re-activates.
             let channel_assembler =
                 self.channel_assembler.take().expect("Must have channel assembler");
-            self.channel_bank = Some(ChannelBank::new(self.cfg.clone(), channel_assembler.prev));
+            self.channel_bank = Some(ChannelBank::with_config(
+                self.cfg.clone(),
+                channel_assembler.prev,
+                self.bank_config,
+            ));
         }
         Ok(())
     }
@@ -116,6 +149,21 @@ where
     }
 }
 
+impl<P> StageSnapshot for ChannelProvider<P>
+where
+    P: NextFrameProvider + OriginAdvancer + OriginProvider + SignalReceiver + StageSnapshot + Debug,
+{
+    fn snapshot(&self, snapshot: &mut PipelineSnapshot) {
+        if let Some(channel_assembler) = self.channel_assembler.as_ref() {
+            channel_assembler.snapshot(snapshot);
+        } else if let Some(channel_bank) = self.channel_bank.as_ref() {
+            channel_bank.snapshot(snapshot);
+        } else if let Some(prev) = self.prev.as_ref() {
+            prev.snapshot(snapshot);
+        }
+    }
+}
+
 #[async_trait]
 impl<P> SignalReceiver for ChannelProvider<P>
 where
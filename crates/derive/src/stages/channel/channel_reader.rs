@@ -1,20 +1,26 @@
 //! This module contains the `ChannelReader` struct.
 
 use crate::{
+    constants::ProtocolConstants,
     errors::PipelineError,
-    stages::BatchStreamProvider,
-    traits::{OriginAdvancer, OriginProvider, SignalReceiver},
-    types::{PipelineResult, Signal},
+    stages::{BatchReader, BatchStreamProvider},
+    traits::{OriginAdvancer, OriginProvider, SignalReceiver, StageSnapshot},
+    types::{PipelineResult, PipelineSnapshot, Signal},
 };
-use alloc::{boxed::Box, sync::Arc};
+use alloc::{boxed::Box, sync::Arc, vec};
 use alloy_primitives::Bytes;
 use async_trait::async_trait;
 use core::fmt::Debug;
-use maili_protocol::{Batch, BatchReader, BlockInfo};
-use op_alloy_genesis::{
-    RollupConfig, MAX_RLP_BYTES_PER_CHANNEL_BEDROCK, MAX_RLP_BYTES_PER_CHANNEL_FJORD,
+use maili_protocol::{Batch, BlockInfo};
+use miniz_oxide::inflate::{
+    core::{
+        decompress, inflate_flags::TINFL_FLAG_PARSE_ZLIB_HEADER, DecompressorOxide,
+        TINFL_LZ_DICT_SIZE,
+    },
+    TINFLStatus,
 };
-use tracing::{debug, warn};
+use op_alloy_genesis::RollupConfig;
+use tracing::{debug, warn, Instrument};
 
 /// The [ChannelReader] provider trait.
 #[async_trait]
@@ -35,6 +41,10 @@ pub trait ChannelReaderProvider {
 ///
 /// Once the data is decompressed, it is decoded into a `Batch` and passed
 /// to the next stage in the pipeline.
+///
+/// Channels that decompress to more than the fork's maximum RLP bytes per channel are dropped
+/// with a [PipelineError::ChannelTooLarge] error. The size of zlib channels is checked before
+/// they are decompressed into memory, brotli channels are bounded by the decompressor itself.
 #[derive(Debug)]
 pub struct ChannelReader<P>
 where
@@ -60,18 +70,31 @@ where
     /// Creates the batch reader from available channel data.
     async fn set_batch_reader(&mut self) -> PipelineResult<()> {
         if self.next_batch.is_none() {
-            let channel =
-                self.prev.next_data().await?.ok_or(PipelineError::ChannelReaderEmpty.temp())?;
+            let channel = self
+                .prev
+                .next_data()
+                .instrument(debug_span!(target: "channel-reader", "next_data"))
+                .await?
+                .ok_or(PipelineError::ChannelReaderEmpty.temp())?;
 
             let origin = self.prev.origin().ok_or(PipelineError::MissingOrigin.crit())?;
-            let max_rlp_bytes_per_channel = if self.cfg.is_fjord_active(origin.timestamp) {
-                MAX_RLP_BYTES_PER_CHANNEL_FJORD
-            } else {
-                MAX_RLP_BYTES_PER_CHANNEL_BEDROCK
-            };
-
-            self.next_batch =
-                Some(BatchReader::new(&channel[..], max_rlp_bytes_per_channel as usize));
+            let max_rlp_bytes_per_channel =
+                ProtocolConstants::for_timestamp(&self.cfg, origin.timestamp).max_channel_size;
+
+            // Drop zlib channels that decompress beyond the limit before allocating them.
+            if is_zlib(&channel) &&
+                zlib_decompressed_size(&channel, max_rlp_bytes_per_channel).is_none()
+            {
+                warn!(
+                    target: "channel-reader",
+                    "Channel exceeds the maximum decompressed size of {} bytes, dropping",
+                    max_rlp_bytes_per_channel
+                );
+                return Err(PipelineError::ChannelTooLarge(max_rlp_bytes_per_channel).temp());
+            }
+
+            // The channel data is handed over without copying when it is not shared.
+            self.next_batch = Some(BatchReader::new(channel, max_rlp_bytes_per_channel));
         }
         Ok(())
     }
@@ -83,6 +106,47 @@ where
     }
 }
 
+/// The zlib deflate compression method.
+const ZLIB_DEFLATE_METHOD: u8 = 8;
+
+/// The reserved zlib compression method, which is treated as zlib by the [BatchReader].
+const ZLIB_RESERVED_METHOD: u8 = 15;
+
+/// Returns whether the channel data is zlib compressed, per the compression method of the zlib
+/// header.
+fn is_zlib(data: &[u8]) -> bool {
+    data.first().is_some_and(|b| matches!(b & 0x0F, ZLIB_DEFLATE_METHOD | ZLIB_RESERVED_METHOD))
+}
+
+/// Returns the decompressed size of zlib compressed data, or `None` if it exceeds `max`.
+///
+/// The data is inflated into a fixed-size window instead of a growing buffer, so memory usage is
+/// constant regardless of the decompressed size. Malformed data returns the size inflated so far,
+/// and is left to the [BatchReader] to reject.
+fn zlib_decompressed_size(data: &[u8], max: usize) -> Option<usize> {
+    let mut decompressor = Box::<DecompressorOxide>::default();
+    let mut window = vec![0u8; TINFL_LZ_DICT_SIZE];
+    let (mut read, mut window_pos, mut size) = (0, 0, 0usize);
+    loop {
+        let (status, consumed, written) = decompress(
+            &mut decompressor,
+            &data[read..],
+            &mut window,
+            window_pos,
+            TINFL_FLAG_PARSE_ZLIB_HEADER,
+        );
+        read += consumed;
+        window_pos = (window_pos + written) & (TINFL_LZ_DICT_SIZE - 1);
+        size += written;
+        if size > max {
+            return None;
+        }
+        if status != TINFLStatus::HasMoreOutput {
+            return Some(size);
+        }
+    }
+}
+
 #[async_trait]
 impl<P> OriginAdvancer for ChannelReader<P>
 where
@@ -140,6 +204,20 @@ where
     }
 }
 
+impl<P> StageSnapshot for ChannelReader<P>
+where
+    P: ChannelReaderProvider
+        + OriginAdvancer
+        + OriginProvider
+        + SignalReceiver
+        + StageSnapshot
+        + Debug,
+{
+    fn snapshot(&self, snapshot: &mut PipelineSnapshot) {
+        self.prev.snapshot(snapshot);
+    }
+}
+
 #[async_trait]
 impl<P> SignalReceiver for ChannelReader<P>
 where
@@ -165,21 +243,19 @@ where
 mod test {
     use super::*;
     use crate::{
-        errors::PipelineErrorKind, test_utils::TestChannelReaderProvider, types::ResetSignal,
+        constants::{MAX_RLP_BYTES_PER_CHANNEL_BEDROCK, MAX_RLP_BYTES_PER_CHANNEL_FJORD},
+        errors::PipelineErrorKind,
+        stages::decode_batch,
+        test_utils::{new_compressed_batch_data, TestChannelReaderProvider},
+        types::ResetSignal,
     };
     use alloc::vec;
-
-    fn new_compressed_batch_data() -> Bytes {
-        let file_contents =
-            alloc::string::String::from_utf8_lossy(include_bytes!("../../../testdata/batch.hex"));
-        let file_contents = &(&*file_contents)[..file_contents.len() - 1];
-        let data = alloy_primitives::hex::decode(file_contents).unwrap();
-        data.into()
-    }
+    use maili_protocol::{SINGLE_BATCH_TYPE, SPAN_BATCH_TYPE};
 
     #[tokio::test]
     async fn test_flush_channel_reader() {
-        let mock = TestChannelReaderProvider::new(vec![Ok(Some(new_compressed_batch_data()))]);
+        let mock =
+            TestChannelReaderProvider::new(vec![Ok(Some(new_compressed_batch_data().into()))]);
         let mut reader = ChannelReader::new(mock, Arc::new(RollupConfig::default()));
         reader.next_batch = Some(BatchReader::new(
             new_compressed_batch_data(),
@@ -224,7 +300,7 @@ mod test {
 
     #[tokio::test]
     async fn test_next_batch_batch_reader_not_enough_data() {
-        let mut first = new_compressed_batch_data();
+        let mut first = Bytes::from(new_compressed_batch_data());
         let second = first.split_to(first.len() / 2);
         let mock = TestChannelReaderProvider::new(vec![Ok(Some(first)), Ok(Some(second))]);
         let mut reader = ChannelReader::new(mock, Arc::new(RollupConfig::default()));
@@ -235,18 +311,45 @@ mod test {
     #[tokio::test]
     async fn test_next_batch_succeeds() {
         let raw = new_compressed_batch_data();
-        let mock = TestChannelReaderProvider::new(vec![Ok(Some(raw))]);
+        let mock = TestChannelReaderProvider::new(vec![Ok(Some(raw.into()))]);
         let mut reader = ChannelReader::new(mock, Arc::new(RollupConfig::default()));
         let res = reader.next_batch().await.unwrap();
         matches!(res, Batch::Span(_));
         assert!(reader.next_batch.is_some());
     }
 
+    #[test]
+    fn test_zlib_decompressed_size() {
+        let raw = new_compressed_batch_data();
+        let len = miniz_oxide::inflate::decompress_to_vec_zlib(&raw).unwrap().len();
+        assert!(is_zlib(&raw));
+        assert!(!is_zlib(&[0x01]));
+        assert_eq!(zlib_decompressed_size(&raw, len), Some(len));
+        assert_eq!(zlib_decompressed_size(&raw, len - 1), None);
+    }
+
+    #[tokio::test]
+    async fn test_next_batch_channel_too_large() {
+        let max = MAX_RLP_BYTES_PER_CHANNEL_BEDROCK as usize;
+        let data = Bytes::from(miniz_oxide::deflate::compress_to_vec_zlib(&vec![0; max + 1], 1));
+
+        let mock = TestChannelReaderProvider::new(vec![Ok(Some(data.clone()))]);
+        let mut reader = ChannelReader::new(mock, Arc::new(RollupConfig::default()));
+        assert_eq!(reader.next_batch().await, Err(PipelineError::ChannelTooLarge(max).temp()));
+        assert!(reader.next_batch.is_none());
+
+        // The limit is increased with Fjord, so the channel is decompressed and decoded.
+        let mock = TestChannelReaderProvider::new(vec![Ok(Some(data))]);
+        let cfg = RollupConfig { fjord_time: Some(0), ..Default::default() };
+        let mut reader = ChannelReader::new(mock, Arc::new(cfg));
+        assert_eq!(reader.next_batch().await, Err(PipelineError::NotEnoughData.temp()));
+    }
+
     #[tokio::test]
     async fn test_flush_post_holocene() {
         let raw = new_compressed_batch_data();
         let config = Arc::new(RollupConfig { holocene_time: Some(0), ..RollupConfig::default() });
-        let mock = TestChannelReaderProvider::new(vec![Ok(Some(raw))]);
+        let mock = TestChannelReaderProvider::new(vec![Ok(Some(raw.into()))]);
         let mut reader = ChannelReader::new(mock, config);
         let res = reader.next_batch().await.unwrap();
         matches!(res, Batch::Span(_));
@@ -254,4 +357,58 @@ mod test {
         reader.flush();
         assert!(reader.next_batch.is_none());
     }
+
+    proptest::proptest! {
+        /// Arbitrary channel data must decode into batches or be dropped, without panics.
+        #[test]
+        fn fuzz_channel_reader_arbitrary_data(
+            data in proptest::collection::vec(proptest::prelude::any::<u8>(), 0..2048),
+            holocene in proptest::prelude::any::<bool>(),
+        ) {
+            let mock = TestChannelReaderProvider::new(vec![Ok(Some(Bytes::from(data)))]);
+            let cfg = RollupConfig { holocene_time: holocene.then_some(0), ..Default::default() };
+            let mut reader = ChannelReader::new(mock, Arc::new(cfg));
+
+            let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+            let err = rt.block_on(async {
+                loop {
+                    if let Err(e) = reader.next_batch().await {
+                        break e;
+                    }
+                }
+            });
+            proptest::prop_assert!(matches!(err, PipelineErrorKind::Temporary(_)));
+        }
+
+        /// Arbitrary single batch payloads must decode or be rejected with an error, without
+        /// panics.
+        #[test]
+        fn fuzz_single_batch_decoding(
+            payload in proptest::collection::vec(proptest::prelude::any::<u8>(), 0..2048),
+        ) {
+            let raw = [&[SINGLE_BATCH_TYPE], payload.as_slice()].concat();
+            let _ = decode_batch(&raw, &RollupConfig::default());
+        }
+
+        /// Arbitrary span batch payloads, including the span batch bit lists, must decode or be
+        /// rejected with an error, without panics.
+        #[test]
+        fn fuzz_span_batch_decoding(
+            payload in proptest::collection::vec(proptest::prelude::any::<u8>(), 0..2048),
+        ) {
+            let raw = [&[SPAN_BATCH_TYPE], payload.as_slice()].concat();
+            let cfg = RollupConfig { delta_time: Some(0), ..Default::default() };
+            let _ = decode_batch(&raw, &cfg);
+        }
+
+        /// Batches of an arbitrary type must be rejected with an error, without panics.
+        #[test]
+        fn fuzz_batch_type_decoding(
+            batch_type in proptest::prelude::any::<u8>(),
+            payload in proptest::collection::vec(proptest::prelude::any::<u8>(), 0..64),
+        ) {
+            let raw = [&[batch_type], payload.as_slice()].concat();
+            let _ = decode_batch(&raw, &RollupConfig::default());
+        }
+    }
 }
@@ -11,20 +11,23 @@
 //! [FrameQueue]: crate::stages::FrameQueue
 //! [BatchQueue]: crate::stages::BatchQueue
 
-use crate::types::PipelineResult;
+use crate::types::{ChannelSnapshot, PipelineResult};
 use alloc::boxed::Box;
 use async_trait::async_trait;
-use maili_protocol::Frame;
+use maili_protocol::{Channel, Frame};
 
 pub(crate) mod channel_provider;
 pub use channel_provider::ChannelProvider;
 
 pub(crate) mod channel_bank;
-pub use channel_bank::ChannelBank;
+pub use channel_bank::{ChannelBank, ChannelBankConfig, ChannelBankEviction, ChannelBankMetrics};
 
 pub(crate) mod channel_assembler;
 pub use channel_assembler::ChannelAssembler;
 
+pub(crate) mod batch_reader;
+pub use batch_reader::{decode_batch, BatchReader};
+
 pub(crate) mod channel_reader;
 pub use channel_reader::{ChannelReader, ChannelReaderProvider};
 
@@ -36,3 +39,14 @@ pub trait NextFrameProvider {
     /// [FrameQueue]: crate::stages::FrameQueue
     async fn next_frame(&mut self) -> PipelineResult<Frame>;
 }
+
+/// Returns the [ChannelSnapshot] of a channel being assembled, at the given L1 origin number.
+pub(crate) fn channel_snapshot(channel: &Channel, origin: u64) -> ChannelSnapshot {
+    ChannelSnapshot {
+        id: channel.id().into(),
+        size: channel.size(),
+        open_block: channel.open_block_number(),
+        age: origin.saturating_sub(channel.open_block_number()),
+        ready: channel.is_ready(),
+    }
+}
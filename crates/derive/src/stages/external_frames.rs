@@ -0,0 +1,217 @@
+//! Contains the [ExternalFrames] stage, which feeds frames pushed through a [FrameSink] into the
+//! [FrameQueue] in place of the [L1Retrieval] stage.
+//!
+//! [FrameQueue]: crate::stages::FrameQueue
+//! [L1Retrieval]: crate::stages::L1Retrieval
+
+use crate::{
+    errors::PipelineError,
+    stages::FrameQueueProvider,
+    traits::{OriginAdvancer, OriginProvider, SignalReceiver, StageSnapshot},
+    types::{PipelineResult, PipelineSnapshot, Signal},
+};
+use alloc::{boxed::Box, collections::VecDeque, sync::Arc, vec::Vec};
+use alloy_primitives::Bytes;
+use async_trait::async_trait;
+use core::fmt::Debug;
+use maili_protocol::{BlockInfo, Frame, DERIVATION_VERSION_0};
+use spin::Mutex;
+
+/// A handle to push frame data into an [ExternalFrames] stage.
+///
+/// Clones of the sink share the same queue, so that an embedder can retrieve frames from an
+/// alternative data availability layer on its own task and push them into a running pipeline.
+#[derive(Debug, Clone, Default)]
+pub struct FrameSink {
+    /// The pending frame data, shared between clones.
+    queue: Arc<Mutex<VecDeque<Bytes>>>,
+}
+
+impl FrameSink {
+    /// Creates a new, empty [FrameSink].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pushes raw frame data, prefixed with the derivation version like the data of a batcher
+    /// transaction.
+    pub fn push_data(&self, data: Bytes) {
+        self.queue.lock().push_back(data);
+    }
+
+    /// Encodes the given [Frame]s as version 0 frame data, and pushes them.
+    pub fn push_frames(&self, frames: &[Frame]) {
+        let mut data = Vec::from([DERIVATION_VERSION_0]);
+        for frame in frames {
+            data.extend_from_slice(&frame.encode());
+        }
+        self.push_data(data.into());
+    }
+
+    /// Returns the number of pending frame data items.
+    pub fn len(&self) -> usize {
+        self.queue.lock().len()
+    }
+
+    /// Returns `true` if no frame data is pending.
+    pub fn is_empty(&self) -> bool {
+        self.queue.lock().is_empty()
+    }
+
+    /// Pops the oldest pending frame data.
+    fn pop(&self) -> Option<Bytes> {
+        self.queue.lock().pop_front()
+    }
+
+    /// Drops all pending frame data.
+    fn clear(&self) {
+        self.queue.lock().clear();
+    }
+}
+
+/// A [FrameQueueProvider] stage that serves the frame data pushed through a [FrameSink], bypassing
+/// L1 retrieval.
+///
+/// The previous stage, e.g. the [L1Traversal], still provides the L1 origin that channel timeouts
+/// and Holocene frame pruning are evaluated against. Once the sink is drained, the stage returns
+/// [PipelineError::Eof] to advance the origin, so frames should be pushed before the pipeline
+/// steps past the L1 block they are attributed to. On a reset, the pending frame data is dropped,
+/// and the embedder is expected to push the frames again from the reset origin.
+///
+/// [L1Traversal]: crate::stages::L1Traversal
+#[derive(Debug)]
+pub struct ExternalFrames<P>
+where
+    P: OriginAdvancer + OriginProvider + SignalReceiver + Debug,
+{
+    /// The previous stage in the pipeline.
+    pub prev: P,
+    /// The sink that frame data is pushed through.
+    sink: FrameSink,
+}
+
+impl<P> ExternalFrames<P>
+where
+    P: OriginAdvancer + OriginProvider + SignalReceiver + Debug,
+{
+    /// Creates a new [ExternalFrames] stage with the given previous stage, serving the frame
+    /// data pushed through the given [FrameSink].
+    pub const fn new(prev: P, sink: FrameSink) -> Self {
+        Self { prev, sink }
+    }
+
+    /// Returns the [FrameSink] of the stage.
+    pub const fn sink(&self) -> &FrameSink {
+        &self.sink
+    }
+}
+
+#[async_trait]
+impl<P> FrameQueueProvider for ExternalFrames<P>
+where
+    P: OriginAdvancer + OriginProvider + SignalReceiver + Send + Debug,
+{
+    type Item = Bytes;
+
+    async fn next_data(&mut self) -> PipelineResult<Bytes> {
+        self.sink.pop().ok_or(PipelineError::Eof.temp())
+    }
+}
+
+#[async_trait]
+impl<P> OriginAdvancer for ExternalFrames<P>
+where
+    P: OriginAdvancer + OriginProvider + SignalReceiver + Send + Debug,
+{
+    async fn advance_origin(&mut self) -> PipelineResult<()> {
+        self.prev.advance_origin().await
+    }
+}
+
+impl<P> OriginProvider for ExternalFrames<P>
+where
+    P: OriginAdvancer + OriginProvider + SignalReceiver + Debug,
+{
+    fn origin(&self) -> Option<BlockInfo> {
+        self.prev.origin()
+    }
+}
+
+impl<P> StageSnapshot for ExternalFrames<P>
+where
+    P: OriginAdvancer + OriginProvider + SignalReceiver + StageSnapshot + Debug,
+{
+    fn snapshot(&self, snapshot: &mut PipelineSnapshot) {
+        self.prev.snapshot(snapshot);
+    }
+}
+
+#[async_trait]
+impl<P> SignalReceiver for ExternalFrames<P>
+where
+    P: OriginAdvancer + OriginProvider + SignalReceiver + Send + Debug,
+{
+    async fn signal(&mut self, signal: Signal) -> PipelineResult<()> {
+        self.prev.signal(signal).await?;
+        if matches!(signal, Signal::Reset(_) | Signal::Activation(_)) {
+            self.sink.clear();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        stages::{FrameQueue, NextFrameProvider},
+        test_utils::{encode_frames, TestFrameQueueProvider},
+        types::ResetSignal,
+    };
+    use alloc::vec;
+
+    fn stage(sink: &FrameSink) -> ExternalFrames<TestFrameQueueProvider> {
+        let mut prev = TestFrameQueueProvider::new(vec![]);
+        prev.set_origin(BlockInfo::default());
+        ExternalFrames::new(prev, sink.clone())
+    }
+
+    #[test]
+    fn test_frame_sink_push_frames() {
+        let frames = [crate::frame!(0xAA, 0, vec![0xDD; 50], true)];
+        let sink = FrameSink::new();
+        assert!(sink.is_empty());
+        sink.push_frames(&frames);
+        assert_eq!(sink.len(), 1);
+        assert_eq!(sink.pop(), Some(encode_frames(&frames)));
+    }
+
+    #[tokio::test]
+    async fn test_external_frames_into_frame_queue() {
+        let frames = [
+            crate::frame!(0xAA, 0, vec![0xDD; 50], false),
+            crate::frame!(0xAA, 1, vec![0xDD; 50], true),
+        ];
+        let sink = FrameSink::new();
+        let mut frame_queue = FrameQueue::new(stage(&sink), Default::default());
+        assert_eq!(frame_queue.next_frame().await.unwrap_err(), PipelineError::Eof.temp());
+
+        // Frames pushed through a clone of the sink reach the running frame queue.
+        sink.push_frames(&frames[..1]);
+        sink.push_data(encode_frames(&frames[1..]));
+        assert_eq!(frame_queue.next_frame().await.unwrap(), frames[0]);
+        assert_eq!(frame_queue.next_frame().await.unwrap(), frames[1]);
+        assert!(frame_queue.prev.sink().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_external_frames_reset() {
+        let sink = FrameSink::new();
+        let mut stage = stage(&sink);
+        sink.push_frames(&[crate::frame!(0xAA, 0, vec![0xDD; 50], true)]);
+
+        stage.signal(ResetSignal::default().signal()).await.unwrap();
+        assert!(stage.prev.reset);
+        assert!(sink.is_empty());
+    }
+}
@@ -3,15 +3,20 @@
 use crate::{
     errors::PipelineError,
     stages::NextFrameProvider,
-    traits::{OriginAdvancer, OriginProvider, SignalReceiver},
-    types::{PipelineResult, Signal},
+    traits::{OriginAdvancer, OriginProvider, SignalReceiver, StageSnapshot},
+    types::{ChannelId, PipelineResult, PipelineSnapshot, Signal},
+};
+use alloc::{
+    boxed::Box,
+    collections::{BTreeMap, VecDeque},
+    sync::Arc,
 };
-use alloc::{boxed::Box, collections::VecDeque, sync::Arc};
 use alloy_primitives::Bytes;
 use async_trait::async_trait;
 use core::fmt::Debug;
 use maili_protocol::{BlockInfo, Frame};
 use op_alloy_genesis::RollupConfig;
+use tracing::Instrument;
 
 /// Provides data frames for the [FrameQueue] stage.
 #[async_trait]
@@ -25,9 +30,55 @@ pub trait FrameQueueProvider {
     async fn next_data(&mut self) -> PipelineResult<Self::Item>;
 }
 
+/// The maximum number of [DuplicateFrame] events buffered by the [FrameQueue] in strict mode.
+/// The oldest events are dropped once the limit is reached.
+pub(crate) const MAX_DUPLICATE_EVENTS: usize = 1024;
+
+/// Configuration for the [FrameQueue]'s duplicate frame detection.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct FrameQueueConfig {
+    /// Whether duplicate frames are recorded as [DuplicateFrame] events, drained with
+    /// [FrameQueue::take_duplicates]. Duplicate frames are counted either way.
+    pub strict_duplicates: bool,
+}
+
+/// The frame counters of the [FrameQueue].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct FrameQueueMetrics {
+    /// The number of frames parsed since the last reset.
+    pub frames: u64,
+    /// The number of frames parsed since the last reset with the same channel ID and frame number
+    /// as a previously parsed frame.
+    pub duplicates: u64,
+}
+
+/// A frame with the same channel ID and frame number as a previously parsed frame, e.g. resubmitted
+/// by a misbehaving batcher.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct DuplicateFrame {
+    /// The channel ID.
+    pub channel_id: ChannelId,
+    /// The frame number.
+    pub number: u16,
+    /// The L1 origin the frame was first seen in.
+    pub first_seen: BlockInfo,
+    /// The L1 origin the duplicate was seen in.
+    pub origin: BlockInfo,
+}
+
 /// The [FrameQueue] stage of the derivation pipeline.
 /// This stage takes the output of the [L1Retrieval] stage and parses it into frames.
 ///
+/// Frames are keyed by channel ID and frame number to detect duplicates, which are counted in
+/// the [FrameQueueMetrics]. Duplicates are still passed on to the next stage, where the channel
+/// drops them per the protocol, and frames are forgotten once their channel timed out.
+///
 /// [L1Retrieval]: crate::stages::L1Retrieval
 #[derive(Debug)]
 pub struct FrameQueue<P>
@@ -40,6 +91,14 @@ where
     queue: VecDeque<Frame>,
     /// The rollup config.
     rollup_config: Arc<RollupConfig>,
+    /// The L1 origin each frame was first seen in, keyed by channel ID and frame number.
+    seen: BTreeMap<(ChannelId, u16), BlockInfo>,
+    /// The duplicate detection configuration.
+    config: FrameQueueConfig,
+    /// The frame counters.
+    metrics: FrameQueueMetrics,
+    /// The duplicate frames recorded in strict mode.
+    duplicates: VecDeque<DuplicateFrame>,
 }
 
 impl<P> FrameQueue<P>
@@ -50,7 +109,70 @@ where
     ///
     /// [L1Retrieval]: crate::stages::L1Retrieval
     pub const fn new(prev: P, cfg: Arc<RollupConfig>) -> Self {
-        Self { prev, queue: VecDeque::new(), rollup_config: cfg }
+        Self::with_config(prev, cfg, FrameQueueConfig { strict_duplicates: false })
+    }
+
+    /// Create a new [FrameQueue] stage with the given [FrameQueueConfig].
+    pub const fn with_config(prev: P, cfg: Arc<RollupConfig>, config: FrameQueueConfig) -> Self {
+        Self {
+            prev,
+            queue: VecDeque::new(),
+            rollup_config: cfg,
+            seen: BTreeMap::new(),
+            config,
+            metrics: FrameQueueMetrics { frames: 0, duplicates: 0 },
+            duplicates: VecDeque::new(),
+        }
+    }
+
+    /// Returns the [FrameQueueConfig].
+    pub const fn config(&self) -> &FrameQueueConfig {
+        &self.config
+    }
+
+    /// Returns the frame counters.
+    pub const fn metrics(&self) -> FrameQueueMetrics {
+        self.metrics
+    }
+
+    /// Drains the [DuplicateFrame]s recorded in strict mode, oldest first.
+    pub fn take_duplicates(&mut self) -> VecDeque<DuplicateFrame> {
+        core::mem::take(&mut self.duplicates)
+    }
+
+    /// Records the frames parsed in the given L1 origin, detecting duplicates.
+    fn track_frames(&mut self, frames: &[Frame], origin: BlockInfo) {
+        let channel_timeout = self.rollup_config.channel_timeout(origin.timestamp);
+        self.seen.retain(|_, first_seen| first_seen.number + channel_timeout >= origin.number);
+
+        for frame in frames {
+            self.metrics.frames += 1;
+            let key = (ChannelId::from(frame.id), frame.number);
+            let Some(first_seen) = self.seen.get(&key).copied() else {
+                self.seen.insert(key, origin);
+                continue;
+            };
+
+            self.metrics.duplicates += 1;
+            if self.config.strict_duplicates {
+                warn!(
+                    target: "frame-queue",
+                    "Duplicate frame {} of channel {}, first seen in L1 block #{}",
+                    frame.number,
+                    key.0,
+                    first_seen.number
+                );
+                if self.duplicates.len() == MAX_DUPLICATE_EVENTS {
+                    self.duplicates.pop_front();
+                }
+                self.duplicates.push_back(DuplicateFrame {
+                    channel_id: key.0,
+                    number: frame.number,
+                    first_seen,
+                    origin,
+                });
+            }
+        }
     }
 
     /// Returns if holocene is active.
@@ -114,7 +236,12 @@ where
             return Ok(());
         }
 
-        let data = match self.prev.next_data().await {
+        let data = match self
+            .prev
+            .next_data()
+            .instrument(debug_span!(target: "frame-queue", "next_data"))
+            .await
+        {
             Ok(data) => data,
             Err(e) => {
                 debug!(target: "frame-queue", "Failed to retrieve data: {:?}", e);
@@ -130,11 +257,13 @@ where
             return Ok(());
         };
 
+        let origin = self.origin().ok_or(PipelineError::MissingOrigin.crit())?;
+        self.track_frames(&frames, origin);
+
         // Optimistically extend the queue with the new frames.
         self.queue.extend(frames);
 
         // Prune frames if Holocene is active.
-        let origin = self.origin().ok_or(PipelineError::MissingOrigin.crit())?;
         self.prune(origin);
 
         Ok(())
@@ -178,6 +307,21 @@ where
     }
 }
 
+impl<P> StageSnapshot for FrameQueue<P>
+where
+    P: FrameQueueProvider
+        + OriginAdvancer
+        + OriginProvider
+        + SignalReceiver
+        + StageSnapshot
+        + Debug,
+{
+    fn snapshot(&self, snapshot: &mut PipelineSnapshot) {
+        snapshot.frames += self.queue.len();
+        self.prev.snapshot(snapshot);
+    }
+}
+
 #[async_trait]
 impl<P> SignalReceiver for FrameQueue<P>
 where
@@ -186,6 +330,8 @@ where
     async fn signal(&mut self, signal: Signal) -> PipelineResult<()> {
         self.prev.signal(signal).await?;
         self.queue = VecDeque::default();
+        self.seen.clear();
+        self.metrics = FrameQueueMetrics::default();
         Ok(())
     }
 }
@@ -193,7 +339,10 @@ where
 #[cfg(test)]
 pub(crate) mod tests {
     use super::*;
-    use crate::{test_utils::TestFrameQueueProvider, types::ResetSignal};
+    use crate::{
+        test_utils::{encode_frames, TestFrameQueueProvider},
+        types::ResetSignal,
+    };
     use alloc::vec;
 
     #[tokio::test]
@@ -206,6 +355,137 @@ pub(crate) mod tests {
         assert!(frame_queue.prev.reset);
     }
 
+    #[tokio::test]
+    async fn test_frame_queue_duplicate_frames() {
+        let frames = [
+            crate::frame!(0xAA, 0, vec![0xDD; 50], false),
+            crate::frame!(0xAA, 1, vec![0xDD; 50], true),
+        ];
+        let origin = BlockInfo { number: 10, ..Default::default() };
+        let data = || vec![Ok(encode_frames(&frames[..1])), Ok(encode_frames(&frames))];
+
+        for strict_duplicates in [false, true] {
+            let mut mock = TestFrameQueueProvider::new(data());
+            mock.set_origin(origin);
+            let config = FrameQueueConfig { strict_duplicates };
+            let mut frame_queue = FrameQueue::with_config(mock, Default::default(), config);
+
+            // Duplicates are passed on to the next stage.
+            for frame in [&frames[0], &frames[1], &frames[0]] {
+                assert_eq!(frame_queue.next_frame().await.unwrap(), *frame);
+            }
+            assert_eq!(frame_queue.metrics(), FrameQueueMetrics { frames: 3, duplicates: 1 });
+
+            let duplicates = frame_queue.take_duplicates();
+            if strict_duplicates {
+                assert_eq!(
+                    duplicates,
+                    [DuplicateFrame {
+                        channel_id: ChannelId([0xAA; 16]),
+                        number: 0,
+                        first_seen: origin,
+                        origin
+                    }]
+                );
+            } else {
+                assert!(duplicates.is_empty());
+            }
+            assert!(frame_queue.take_duplicates().is_empty());
+
+            frame_queue.signal(ResetSignal::default().signal()).await.unwrap();
+            assert_eq!(frame_queue.metrics(), FrameQueueMetrics::default());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_frame_queue_duplicate_frames_after_timeout() {
+        let frame = crate::frame!(0xAA, 0, vec![0xDD; 50], false);
+        let data =
+            vec![Ok(encode_frames(core::slice::from_ref(&frame))), Ok(encode_frames(&[frame]))];
+        let cfg = Arc::new(RollupConfig { channel_timeout: 5, ..Default::default() });
+        let mut mock = TestFrameQueueProvider::new(data);
+        mock.set_origin(BlockInfo { number: 10, ..Default::default() });
+        let config = FrameQueueConfig { strict_duplicates: true };
+        let mut frame_queue = FrameQueue::with_config(mock, cfg, config);
+        frame_queue.next_frame().await.unwrap();
+
+        // The frame is forgotten once its channel timed out.
+        frame_queue.prev.set_origin(BlockInfo { number: 16, ..Default::default() });
+        frame_queue.next_frame().await.unwrap();
+        assert_eq!(frame_queue.metrics(), FrameQueueMetrics { frames: 2, duplicates: 0 });
+        assert!(frame_queue.take_duplicates().is_empty());
+    }
+
+    /// A custom stage that multiplexes two data sources into the [FrameQueue].
+    #[derive(Debug)]
+    struct Multiplexer {
+        sources: [TestFrameQueueProvider; 2],
+        next: usize,
+    }
+
+    #[async_trait]
+    impl FrameQueueProvider for Multiplexer {
+        type Item = Bytes;
+
+        async fn next_data(&mut self) -> PipelineResult<Bytes> {
+            let source = self.next;
+            self.next ^= 1;
+            self.sources[source].next_data().await
+        }
+    }
+
+    impl OriginProvider for Multiplexer {
+        fn origin(&self) -> Option<BlockInfo> {
+            self.sources[0].origin()
+        }
+    }
+
+    #[async_trait]
+    impl OriginAdvancer for Multiplexer {
+        async fn advance_origin(&mut self) -> PipelineResult<()> {
+            for source in self.sources.iter_mut() {
+                source.advance_origin().await?;
+            }
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl SignalReceiver for Multiplexer {
+        async fn signal(&mut self, signal: Signal) -> PipelineResult<()> {
+            for source in self.sources.iter_mut() {
+                source.signal(signal).await?;
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_frame_queue_custom_stage() {
+        fn assert_stage<S: crate::traits::PipelineStage>(_: &S) {}
+
+        let frames = [
+            crate::frame!(0xAA, 0, vec![0xDD; 50], false),
+            crate::frame!(0xBB, 0, vec![0xDD; 50], true),
+        ];
+        let mut multiplexer = Multiplexer {
+            sources: [
+                TestFrameQueueProvider::new(vec![Ok(encode_frames(&frames[..1]))]),
+                TestFrameQueueProvider::new(vec![Ok(encode_frames(&frames[1..]))]),
+            ],
+            next: 0,
+        };
+        multiplexer.sources[0].set_origin(BlockInfo::default());
+        assert_stage(&multiplexer);
+
+        let mut frame_queue = FrameQueue::new(multiplexer, Default::default());
+        assert_eq!(frame_queue.next_frame().await.unwrap(), frames[0]);
+        assert_eq!(frame_queue.next_frame().await.unwrap(), frames[1]);
+
+        frame_queue.signal(ResetSignal::default().signal()).await.unwrap();
+        assert!(frame_queue.prev.sources.iter().all(|s| s.reset));
+    }
+
     #[tokio::test]
     async fn test_frame_queue_empty_bytes() {
         let data = vec![Ok(Bytes::from(vec![0x00]))];
@@ -497,4 +777,31 @@ pub(crate) mod tests {
         assert.holocene_active(true);
         assert.next_frames().await;
     }
+
+    proptest::proptest! {
+        /// Arbitrary batcher data must be classified as valid frames or dropped, without panics.
+        #[test]
+        fn fuzz_frame_queue_arbitrary_data(
+            data in proptest::collection::vec(proptest::prelude::any::<u8>(), 0..2048),
+            versioned in proptest::prelude::any::<bool>(),
+            holocene in proptest::prelude::any::<bool>(),
+        ) {
+            // Prefix the derivation version so that frame parsing is exercised.
+            let data = if versioned { [&[0x00], data.as_slice()].concat() } else { data };
+            let mut mock = TestFrameQueueProvider::new(vec![Ok(Bytes::from(data))]);
+            mock.set_origin(BlockInfo::default());
+            let cfg = RollupConfig { holocene_time: holocene.then_some(0), ..Default::default() };
+            let mut frame_queue = FrameQueue::new(mock, Arc::new(cfg));
+
+            let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+            let err = rt.block_on(async {
+                loop {
+                    if let Err(e) = frame_queue.next_frame().await {
+                        break e;
+                    }
+                }
+            });
+            proptest::prop_assert!(matches!(err, crate::errors::PipelineErrorKind::Temporary(_)));
+        }
+    }
 }
@@ -3,13 +3,16 @@
 use crate::{
     errors::{PipelineError, PipelineErrorKind},
     stages::FrameQueueProvider,
-    traits::{DataAvailabilityProvider, OriginAdvancer, OriginProvider, SignalReceiver},
-    types::{ActivationSignal, PipelineResult, ResetSignal, Signal},
+    traits::{
+        DataAvailabilityProvider, OriginAdvancer, OriginProvider, SignalReceiver, StageSnapshot,
+    },
+    types::{ActivationSignal, PipelineResult, PipelineSnapshot, ResetSignal, Signal},
 };
 use alloc::boxed::Box;
 use alloy_primitives::Address;
 use async_trait::async_trait;
 use maili_protocol::BlockInfo;
+use tracing::Instrument;
 
 /// Provides L1 blocks for the [L1Retrieval] stage.
 /// This is the previous stage in the pipeline.
@@ -92,7 +95,8 @@ where
         // SAFETY: The above check ensures that `next` is not None.
         let next = self.next.as_ref().expect("infallible");
 
-        match self.provider.next(next).await {
+        let span = debug_span!(target: "l1-retrieval", "provider_next", block = next.number);
+        match self.provider.next(next, self.prev.batcher_addr()).instrument(span).await {
             Ok(data) => Ok(data),
             Err(e) => {
                 if let PipelineErrorKind::Temporary(PipelineError::Eof) = e {
@@ -115,6 +119,16 @@ where
     }
 }
 
+impl<DAP, P> StageSnapshot for L1Retrieval<DAP, P>
+where
+    DAP: DataAvailabilityProvider,
+    P: L1RetrievalProvider + OriginAdvancer + OriginProvider + SignalReceiver + StageSnapshot,
+{
+    fn snapshot(&self, snapshot: &mut PipelineSnapshot) {
+        self.prev.snapshot(snapshot);
+    }
+}
+
 #[async_trait]
 impl<DAP, P> SignalReceiver for L1Retrieval<DAP, P>
 where
@@ -137,9 +151,140 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{stages::l1_traversal::tests::*, test_utils::TestDAP};
-    use alloc::vec;
-    use alloy_primitives::Bytes;
+    use crate::{
+        sources::{BlobSource, CalldataSource},
+        stages::{l1_traversal::tests::*, L1Traversal},
+        test_utils::{TestBlobProvider, TestChainProvider, TestDAP},
+    };
+    use alloc::{sync::Arc, vec, vec::Vec};
+    use alloy_consensus::{Receipt, Signed, TxEnvelope, TxLegacy};
+    use alloy_primitives::{
+        address, Bytes, Log, LogData, PrimitiveSignature as Signature, TxKind, B256, U256,
+    };
+    use maili_protocol::DERIVATION_VERSION_0;
+    use op_alloy_genesis::{
+        RollupConfig, SystemConfig, CONFIG_UPDATE_EVENT_VERSION_0, CONFIG_UPDATE_TOPIC,
+    };
+
+    const BATCH_INBOX: Address = address!("ff00000000000000000000000000000000000010");
+    const SYSTEM_CONFIG: Address = address!("1337000000000000000000000000000000000000");
+
+    /// Returns a calldata batcher transaction carrying the nonce as its frame data. Since all test
+    /// transactions share a signature, the recovered sender differs per nonce.
+    fn batcher_tx(nonce: u64) -> TxEnvelope {
+        TxEnvelope::Legacy(Signed::new_unchecked(
+            TxLegacy {
+                nonce,
+                to: TxKind::Call(BATCH_INBOX),
+                input: Bytes::from(vec![DERIVATION_VERSION_0, nonce as u8]),
+                ..Default::default()
+            },
+            Signature::test_signature(),
+            Default::default(),
+        ))
+    }
+
+    /// Returns a receipt with a system config log rotating the batcher to the given address.
+    fn batcher_update_receipt(batcher: Address) -> Receipt {
+        let data = [
+            U256::from(32).to_be_bytes::<32>(),
+            U256::from(32).to_be_bytes::<32>(),
+            batcher.into_word().0,
+        ]
+        .concat();
+        let log = Log {
+            address: SYSTEM_CONFIG,
+            data: LogData::new_unchecked(
+                vec![CONFIG_UPDATE_TOPIC, CONFIG_UPDATE_EVENT_VERSION_0, B256::ZERO],
+                data.into(),
+            ),
+        };
+        Receipt {
+            status: alloy_consensus::Eip658Value::Eip658(true),
+            logs: vec![log],
+            ..Default::default()
+        }
+    }
+
+    /// Drains the data of the current L1 block of the [L1Retrieval] stage.
+    async fn drain<DAP>(
+        retrieval: &mut L1Retrieval<DAP, L1Traversal<TestChainProvider>>,
+    ) -> Vec<Bytes>
+    where
+        DAP: DataAvailabilityProvider<Item = Bytes> + Send,
+    {
+        let mut data = Vec::new();
+        loop {
+            match retrieval.next_data().await {
+                Ok(d) => data.push(d),
+                Err(e) => {
+                    assert_eq!(e, PipelineError::Eof.temp());
+                    return data;
+                }
+            }
+        }
+    }
+
+    /// Asserts that a batcher rotation applies to the data of the L1 block that emits it, so
+    /// that data sent by the previous batcher is rejected exactly at the activation origin.
+    async fn assert_batcher_rotation<DAP>(dap: impl FnOnce(TestChainProvider) -> DAP)
+    where
+        DAP: DataAvailabilityProvider<Item = Bytes> + Send,
+    {
+        let (old, new) = (batcher_tx(0), batcher_tx(1));
+        let old_batcher = old.recover_signer().unwrap();
+        let new_batcher = new.recover_signer().unwrap();
+        assert_ne!(old_batcher, new_batcher);
+
+        let mut chain = TestChainProvider::default();
+        let block_0 = BlockInfo { number: 0, hash: B256::with_last_byte(1), ..Default::default() };
+        let block_1 = BlockInfo {
+            number: 1,
+            hash: B256::with_last_byte(2),
+            parent_hash: block_0.hash,
+            ..Default::default()
+        };
+        for block in [block_0, block_1] {
+            chain.insert_block_with_transactions(
+                block.number,
+                block,
+                vec![old.clone(), new.clone()],
+            );
+        }
+        chain.insert_receipts(block_1.hash, vec![batcher_update_receipt(new_batcher)]);
+
+        let cfg = RollupConfig { l1_system_config_address: SYSTEM_CONFIG, ..Default::default() };
+        let traversal = L1Traversal::new(chain.clone(), Arc::new(cfg));
+        let mut retrieval = L1Retrieval::new(traversal, dap(chain));
+        let system_config = SystemConfig { batcher_address: old_batcher, ..Default::default() };
+        let reset = ResetSignal {
+            l1_origin: block_0,
+            system_config: Some(system_config),
+            ..Default::default()
+        };
+        retrieval.signal(reset.signal()).await.unwrap();
+
+        // Before the rotation, only the data of the previous batcher is accepted.
+        assert_eq!(drain(&mut retrieval).await, [Bytes::from(vec![DERIVATION_VERSION_0, 0])]);
+
+        // The rotation takes effect at the L1 block that emits it.
+        retrieval.advance_origin().await.unwrap();
+        assert_eq!(retrieval.prev.batcher_addr(), new_batcher);
+        assert_eq!(drain(&mut retrieval).await, [Bytes::from(vec![DERIVATION_VERSION_0, 1])]);
+    }
+
+    #[tokio::test]
+    async fn test_l1_retrieval_calldata_batcher_rotation() {
+        assert_batcher_rotation(|chain| CalldataSource::new(chain, BATCH_INBOX)).await;
+    }
+
+    #[tokio::test]
+    async fn test_l1_retrieval_blob_batcher_rotation() {
+        assert_batcher_rotation(|chain| {
+            BlobSource::new(chain, TestBlobProvider::default(), BATCH_INBOX)
+        })
+        .await;
+    }
 
     #[tokio::test]
     async fn test_l1_retrieval_flush_channel() {
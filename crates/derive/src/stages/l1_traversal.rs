@@ -3,14 +3,20 @@
 use crate::{
     errors::{PipelineError, ResetError},
     stages::L1RetrievalProvider,
-    traits::{ChainProvider, OriginAdvancer, OriginProvider, SignalReceiver},
-    types::{ActivationSignal, PipelineResult, ResetSignal, Signal},
+    traits::{
+        ChainProvider, L1BlockTag, OriginAdvancer, OriginProvider, SignalReceiver, StageSnapshot,
+    },
+    types::{
+        update_system_config, ActivationSignal, GasPayingToken, PipelineResult, PipelineSnapshot,
+        ResetSignal, Signal,
+    },
 };
-use alloc::{boxed::Box, sync::Arc};
+use alloc::{boxed::Box, collections::BTreeMap, sync::Arc};
 use alloy_primitives::Address;
 use async_trait::async_trait;
 use maili_protocol::BlockInfo;
 use op_alloy_genesis::{RollupConfig, SystemConfig};
+use tracing::Instrument;
 
 /// The [L1Traversal] stage of the derivation pipeline.
 ///
@@ -18,7 +24,7 @@ use op_alloy_genesis::{RollupConfig, SystemConfig};
 /// (a [ChainProvider] implementation) and the current L1 [BlockInfo] in the pipeline,
 /// which are used to traverse the L1 chain. When the [L1Traversal] stage is advanced,
 /// it fetches the next L1 [BlockInfo] from the data source and updates the [SystemConfig]
-/// with the receipts from the block.
+/// and the [GasPayingToken] with the receipts from the block.
 #[derive(Debug, Clone)]
 pub struct L1Traversal<Provider: ChainProvider> {
     /// The current block in the traversal stage.
@@ -29,8 +35,22 @@ pub struct L1Traversal<Provider: ChainProvider> {
     pub done: bool,
     /// The system config.
     pub system_config: SystemConfig,
+    /// The gas paying token, as last set by a system deposit of the L1 `SystemConfig` contract.
+    ///
+    /// The token is not part of the [SystemConfig] restored on a reset, and is kept across
+    /// resets. Custom gas token chains set the token at genesis, so the traversal should be seeded
+    /// with it via [L1Traversal::with_gas_paying_token].
+    pub gas_paying_token: GasPayingToken,
     /// A reference to the rollup config.
     pub rollup_config: Arc<RollupConfig>,
+    /// The number of L1 blocks that must be built on top of a block before the traversal
+    /// advances to it. Zero advances to the next block as soon as it is available.
+    pub confirmations: u64,
+    /// The L1 head tag to follow. If set, the traversal never advances past the block with the
+    /// tag, rather than advancing to any available block.
+    pub follow_tag: Option<L1BlockTag>,
+    /// The [SystemConfig] checkpoints, keyed by the L1 block number at which they took effect.
+    system_config_history: BTreeMap<u64, SystemConfig>,
 }
 
 #[async_trait]
@@ -57,16 +77,54 @@ impl<F: ChainProvider> L1Traversal<F> {
             data_source,
             done: false,
             system_config: SystemConfig::default(),
+            gas_paying_token: GasPayingToken::ETHER,
             rollup_config: cfg,
+            confirmations: 0,
+            follow_tag: None,
+            system_config_history: BTreeMap::new(),
         }
     }
+
+    /// Sets the number of confirmations the next L1 block needs before the traversal advances
+    /// to it, trading derivation latency for resistance to shallow L1 reorgs.
+    pub const fn with_confirmations(mut self, confirmations: u64) -> Self {
+        self.confirmations = confirmations;
+        self
+    }
+
+    /// Sets the L1 head tag to follow, so that the traversal only advances to blocks that are
+    /// labelled safe or finalized by the L1 node.
+    pub const fn with_follow_tag(mut self, tag: L1BlockTag) -> Self {
+        self.follow_tag = Some(tag);
+        self
+    }
+
+    /// Sets the gas paying token of the chain, e.g. read from the `SystemConfig` contract of a
+    /// custom gas token chain at startup.
+    pub const fn with_gas_paying_token(mut self, token: GasPayingToken) -> Self {
+        self.gas_paying_token = token;
+        self
+    }
+
+    /// Returns the [SystemConfig] that was in effect at the given L1 block number, if the
+    /// traversal has observed it since the last reset.
+    pub fn system_config_at(&self, l1_block_number: u64) -> Option<SystemConfig> {
+        self.system_config_history.range(..=l1_block_number).next_back().map(|(_, c)| *c)
+    }
+
+    /// Resets the [SystemConfig] history, seeding it with the current config at the given
+    /// L1 block number.
+    fn reset_system_config_history(&mut self, l1_block_number: u64) {
+        self.system_config_history.clear();
+        self.system_config_history.insert(l1_block_number, self.system_config);
+    }
 }
 
 #[async_trait]
 impl<F: ChainProvider + Send> OriginAdvancer for L1Traversal<F> {
     /// Advances the internal state of the [L1Traversal] stage to the next L1 block.
     /// This function fetches the next L1 [BlockInfo] from the data source and updates the
-    /// [SystemConfig] with the receipts from the block.
+    /// [SystemConfig] and the [GasPayingToken] with the receipts from the block.
     async fn advance_origin(&mut self) -> PipelineResult<()> {
         // Pull the next block or return EOF.
         // PipelineError::EOF has special handling further up the pipeline.
@@ -77,24 +135,96 @@ impl<F: ChainProvider + Send> OriginAdvancer for L1Traversal<F> {
                 return Err(PipelineError::Eof.temp());
             }
         };
-        let next_l1_origin =
-            self.data_source.block_info_by_number(block.number + 1).await.map_err(Into::into)?;
+        // Only advance once the next block is buried under enough confirmations, counted from
+        // the followed L1 head tag if one is set.
+        let target = block.number + 1 + self.confirmations;
+        let ready = match self.follow_tag {
+            Some(tag) => self
+                .data_source
+                .block_info_by_tag(tag)
+                .instrument(debug_span!(target: "l1-traversal", "block_info_by_tag"))
+                .await
+                .map_err(Into::into)?
+                .is_some_and(|head| head.number >= target),
+            None if self.confirmations > 0 => {
+                let span = debug_span!(target: "l1-traversal", "block_info_by_number", target);
+                self.data_source.block_info_by_number(target).instrument(span).await.is_ok()
+            }
+            None => true,
+        };
+        if !ready {
+            trace!(
+                target: "l1-traversal",
+                "Waiting for L1 block #{} to be confirmed by block #{}",
+                block.number + 1,
+                target
+            );
+            return Err(PipelineError::Eof.temp());
+        }
+        let span =
+            debug_span!(target: "l1-traversal", "block_info_by_number", target = block.number + 1);
+        let next_l1_origin = self
+            .data_source
+            .block_info_by_number(block.number + 1)
+            .instrument(span)
+            .await
+            .map_err(Into::into)?;
 
         // Check block hashes for reorgs.
         if block.hash != next_l1_origin.parent_hash {
             return Err(ResetError::ReorgDetected(block.hash, next_l1_origin.parent_hash).into());
         }
 
-        // Fetch receipts for the next l1 block and update the system config.
-        let receipts =
-            self.data_source.receipts_by_hash(next_l1_origin.hash).await.map_err(Into::into)?;
+        // Fetch the receipts of the next l1 block and update the system config. The receipts
+        // are not filtered, since the gas paying token is set through a deposit.
+        let receipts = self
+            .data_source
+            .receipts_by_hash(next_l1_origin.hash)
+            .instrument(debug_span!(target: "l1-traversal", "receipts_by_hash"))
+            .await
+            .map_err(Into::into)?;
 
-        if let Err(e) = self.system_config.update_with_receipts(
+        let updates = update_system_config(
+            &mut self.system_config,
             receipts.as_slice(),
             self.rollup_config.l1_system_config_address,
             self.rollup_config.is_ecotone_active(next_l1_origin.timestamp),
+        )
+        .map_err(|e| PipelineError::SystemConfigUpdate(e).crit())?;
+        if !updates.is_empty() {
+            debug!(
+                target: "l1-traversal",
+                "Applied {} system config updates at L1 block #{}",
+                updates.len(),
+                next_l1_origin.number
+            );
+            self.system_config_history.insert(next_l1_origin.number, self.system_config);
+        }
+
+        // Malformed deposits are rejected by the attributes builder, so decoding failures are
+        // not fatal here.
+        match GasPayingToken::from_receipts(
+            next_l1_origin.hash,
+            receipts.as_slice(),
+            self.rollup_config.deposit_contract_address,
         ) {
-            return Err(PipelineError::SystemConfigUpdate(e).crit());
+            Ok(Some(token)) => {
+                info!(
+                    target: "l1-traversal",
+                    "Gas paying token set to {} at L1 block #{}",
+                    token.address,
+                    next_l1_origin.number
+                );
+                self.gas_paying_token = token;
+            }
+            Ok(None) => {}
+            Err(e) => {
+                warn!(
+                    target: "l1-traversal",
+                    "Failed to decode the deposits of L1 block #{}: {e}",
+                    next_l1_origin.number
+                );
+            }
         }
 
         let prev_block_holocene = self.rollup_config.is_holocene_active(block.timestamp);
@@ -120,6 +250,13 @@ impl<F: ChainProvider> OriginProvider for L1Traversal<F> {
     }
 }
 
+impl<F: ChainProvider> StageSnapshot for L1Traversal<F> {
+    fn snapshot(&self, snapshot: &mut PipelineSnapshot) {
+        snapshot.gas_paying_token =
+            self.gas_paying_token.is_custom().then_some(self.gas_paying_token);
+    }
+}
+
 #[async_trait]
 impl<F: ChainProvider + Send> SignalReceiver for L1Traversal<F> {
     async fn signal(&mut self, signal: Signal) -> PipelineResult<()> {
@@ -129,6 +266,7 @@ impl<F: ChainProvider + Send> SignalReceiver for L1Traversal<F> {
                 self.block = Some(l1_origin);
                 self.done = false;
                 self.system_config = system_config.expect("System config must be provided.");
+                self.reset_system_config_history(l1_origin.number);
             }
             _ => {}
         }
@@ -140,10 +278,15 @@ impl<F: ChainProvider + Send> SignalReceiver for L1Traversal<F> {
 #[cfg(test)]
 pub(crate) mod tests {
     use super::*;
-    use crate::{errors::PipelineErrorKind, test_utils::TestChainProvider};
-    use alloc::vec;
+    use crate::{
+        errors::PipelineErrorKind,
+        test_utils::{DerivationFixture, L1BlockFixture, TestChainProvider, TestProviderError},
+        types::{DEPOSITOR_ACCOUNT, L1_BLOCK_ADDRESS},
+    };
+    use alloc::{string::ToString, vec};
     use alloy_consensus::Receipt;
     use alloy_primitives::{address, b256, hex, Bytes, Log, LogData, B256};
+    use maili_protocol::DEPOSIT_EVENT_ABI_HASH;
     use op_alloy_genesis::{CONFIG_UPDATE_EVENT_VERSION_0, CONFIG_UPDATE_TOPIC};
 
     const L1_SYS_CONFIG_ADDR: Address = address!("1337000000000000000000000000000000000000");
@@ -263,6 +406,41 @@ pub(crate) mod tests {
         assert!(traversal.advance_origin().await.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_l1_traversal_confirmations() {
+        let blocks = vec![BlockInfo::default(), BlockInfo::default(), BlockInfo::default()];
+        let receipts = new_receipts();
+        let mut traversal = new_test_traversal(blocks, receipts).with_confirmations(2);
+        traversal.block = Some(BlockInfo::default());
+
+        // Block #1 is only confirmed by one block.
+        assert_eq!(traversal.advance_origin().await.unwrap_err(), PipelineError::Eof.temp());
+        assert_eq!(traversal.origin(), Some(BlockInfo::default()));
+
+        traversal.data_source.insert_block(3, BlockInfo::default());
+        assert!(traversal.advance_origin().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_l1_traversal_follow_tag() {
+        let blocks = (0..3).map(|number| BlockInfo { number, ..Default::default() }).collect();
+        let receipts = new_receipts();
+        let mut traversal =
+            new_test_traversal(blocks, receipts).with_follow_tag(L1BlockTag::Finalized);
+        traversal.block = Some(BlockInfo::default());
+
+        // No block is finalized yet, so the traversal waits even though block #1 is available.
+        assert_eq!(traversal.advance_origin().await.unwrap_err(), PipelineError::Eof.temp());
+
+        let finalized = BlockInfo { number: 1, ..Default::default() };
+        let safe = BlockInfo { number: 2, ..Default::default() };
+        traversal.data_source.insert_tagged_block(L1BlockTag::Safe, safe);
+        traversal.data_source.insert_tagged_block(L1BlockTag::Finalized, finalized);
+        assert!(traversal.advance_origin().await.is_ok());
+        assert_eq!(traversal.origin(), Some(finalized));
+        assert_eq!(traversal.advance_origin().await.unwrap_err(), PipelineError::Eof.temp());
+    }
+
     #[tokio::test]
     async fn test_l1_traversal_missing_receipts() {
         let blocks = vec![BlockInfo::default(), BlockInfo::default()];
@@ -298,6 +476,38 @@ pub(crate) mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_l1_traversal_scripted_provider_error() {
+        let blocks = vec![BlockInfo::default(), BlockInfo::default()];
+        let receipts = new_receipts();
+        let mut traversal = new_test_traversal(blocks, receipts);
+        traversal.data_source.push_error(TestProviderError::BlockNotFound);
+        assert_eq!(
+            traversal.advance_origin().await.unwrap_err(),
+            PipelineError::Provider(TestProviderError::BlockNotFound.to_string()).temp()
+        );
+        assert!(traversal.advance_origin().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_l1_traversal_from_fixture() {
+        let fixture = DerivationFixture {
+            l1_blocks: vec![
+                L1BlockFixture { info: BlockInfo::default(), ..Default::default() },
+                L1BlockFixture {
+                    info: BlockInfo { number: 1, ..Default::default() },
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let mut traversal =
+            L1Traversal::new(fixture.chain_provider(), Arc::new(fixture.rollup_config));
+        traversal.block = Some(BlockInfo::default());
+        assert!(traversal.advance_origin().await.is_ok());
+        assert_eq!(traversal.origin().unwrap().number, 1);
+    }
+
     #[tokio::test]
     async fn test_l1_traversal_system_config_update_fails() {
         let first = b256!("3333333333333333333333333333333333333333333333333333333333333333");
@@ -325,4 +535,83 @@ pub(crate) mod tests {
         let expected = address!("000000000000000000000000000000000000bEEF");
         assert_eq!(traversal.system_config.batcher_address, expected);
     }
+
+    #[tokio::test]
+    async fn test_l1_traversal_filtered_receipts() {
+        let mut traversal = new_populated_test_traversal();
+        let receipts =
+            traversal.data_source.filtered_receipts_by_hash(B256::ZERO, L1_SYS_CONFIG_ADDR).await;
+        let receipts = receipts.unwrap();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].status, alloy_consensus::Eip658Value::Eip658(true));
+        assert_eq!(receipts[0].logs, [new_update_batcher_log(), new_update_batcher_log()]);
+    }
+
+    #[tokio::test]
+    async fn test_l1_traversal_system_config_at() {
+        let block1 = BlockInfo { number: 1, ..BlockInfo::default() };
+        let block2 = BlockInfo { number: 2, ..BlockInfo::default() };
+        let mut provider = TestChainProvider::default();
+        provider.insert_block(2, block2);
+        provider.insert_receipts(block2.hash, new_receipts());
+        let rollup_config = RollupConfig {
+            l1_system_config_address: L1_SYS_CONFIG_ADDR,
+            ..RollupConfig::default()
+        };
+        let mut traversal = L1Traversal::new(provider, Arc::new(rollup_config));
+        let cfg = SystemConfig::default();
+        assert!(traversal
+            .signal(
+                ResetSignal { l1_origin: block1, system_config: Some(cfg), ..Default::default() }
+                    .signal()
+            )
+            .await
+            .is_ok());
+        assert!(traversal.advance_origin().await.is_ok());
+
+        let expected = address!("000000000000000000000000000000000000bEEF");
+        assert_eq!(traversal.system_config_at(0), None);
+        assert_eq!(traversal.system_config_at(1), Some(cfg));
+        assert_eq!(traversal.system_config_at(2).unwrap().batcher_address, expected);
+        assert_eq!(traversal.system_config_at(100).unwrap().batcher_address, expected);
+    }
+
+    #[tokio::test]
+    async fn test_l1_traversal_gas_paying_token() {
+        let token = GasPayingToken {
+            address: address!("2222222222222222222222222222222222222222"),
+            decimals: 18,
+            name: B256::with_last_byte(1),
+            symbol: B256::with_last_byte(2),
+        };
+        let mut opaque = [0u8; 73].to_vec();
+        opaque[64..72].copy_from_slice(&200_000u64.to_be_bytes());
+        opaque.extend_from_slice(&token.set_gas_paying_token_calldata());
+        let len = B256::with_last_byte(opaque.len() as u8);
+        let mut data = [B256::with_last_byte(32).as_slice(), len.as_slice(), &opaque].concat();
+        data.resize(data.len().div_ceil(32) * 32, 0);
+        let log = Log {
+            address: Address::ZERO,
+            data: LogData::new_unchecked(
+                vec![
+                    DEPOSIT_EVENT_ABI_HASH,
+                    DEPOSITOR_ACCOUNT.into_word(),
+                    L1_BLOCK_ADDRESS.into_word(),
+                    B256::ZERO,
+                ],
+                data.into(),
+            ),
+        };
+        let receipt = Receipt { status: true.into(), logs: vec![log], ..Default::default() };
+
+        let blocks = vec![BlockInfo::default(), BlockInfo::default()];
+        let mut traversal = new_test_traversal(blocks, vec![receipt]);
+        assert_eq!(traversal.gas_paying_token, GasPayingToken::ETHER);
+        assert!(traversal.advance_origin().await.is_ok());
+        assert_eq!(traversal.gas_paying_token, token);
+
+        let mut snapshot = PipelineSnapshot::default();
+        traversal.snapshot(&mut snapshot);
+        assert_eq!(snapshot.gas_paying_token, Some(token));
+    }
 }
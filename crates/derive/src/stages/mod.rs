@@ -22,17 +22,27 @@ mod l1_retrieval;
 pub use l1_retrieval::{L1Retrieval, L1RetrievalProvider};
 
 mod frame_queue;
-pub use frame_queue::{FrameQueue, FrameQueueProvider};
+pub use frame_queue::{
+    DuplicateFrame, FrameQueue, FrameQueueConfig, FrameQueueMetrics, FrameQueueProvider,
+};
+
+mod external_frames;
+pub use external_frames::{ExternalFrames, FrameSink};
 
 mod channel;
 pub use channel::{
-    ChannelAssembler, ChannelBank, ChannelProvider, ChannelReader, ChannelReaderProvider,
+    decode_batch, BatchReader, ChannelAssembler, ChannelBank, ChannelBankConfig,
+    ChannelBankEviction, ChannelBankMetrics, ChannelProvider, ChannelReader, ChannelReaderProvider,
     NextFrameProvider,
 };
 
 mod batch;
+pub(crate) use batch::decompress_channel;
 pub use batch::{
-    BatchProvider, BatchQueue, BatchStream, BatchStreamProvider, BatchValidator, NextBatchProvider,
+    check_tx_equivalence, is_sequencing_window_expired, reencode_transaction,
+    sequencing_window_action, BatchProvider, BatchQueue, BatchStream, BatchStreamProvider,
+    BatchValidator, ChannelBatchStats, NextBatchProvider, SequencingWindowAction, SpanBatchIter,
+    SpanBatchLimits, SpanBatchStats, TxTypeCounts,
 };
 
 mod attributes_queue;
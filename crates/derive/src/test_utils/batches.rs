@@ -0,0 +1,23 @@
+//! Batch data fixtures.
+
+use crate::provenance::L1TxRef;
+use alloc::vec::Vec;
+use alloy_primitives::{hex, B256};
+use maili_protocol::BlockInfo;
+
+/// Returns the compressed channel data of the `testdata/batch.hex` fixture, which holds a single
+/// span batch.
+pub fn new_compressed_batch_data() -> Vec<u8> {
+    let raw = include_str!("../../testdata/batch.hex");
+    hex::decode(raw.trim()).unwrap()
+}
+
+/// Returns a reference to the transaction at the given index of the L1 block, with the index as
+/// the last byte of its hash.
+pub fn tx_ref(block: u64, index: u64) -> L1TxRef {
+    L1TxRef {
+        block: BlockInfo { number: block, ..Default::default() },
+        hash: B256::with_last_byte(index as u8),
+        index,
+    }
+}
@@ -1,7 +1,7 @@
 //! An implementation of the [BlobProvider] trait for tests.
 
 use crate::{errors::BlobProviderError, traits::BlobProvider};
-use alloc::{boxed::Box, vec::Vec};
+use alloc::{boxed::Box, collections::VecDeque, vec::Vec};
 use alloy_eips::eip4844::{Blob, IndexedBlobHash};
 use alloy_primitives::{map::HashMap, B256};
 use async_trait::async_trait;
@@ -14,6 +14,8 @@ pub struct TestBlobProvider {
     pub blobs: HashMap<B256, Blob>,
     /// whether the blob provider should return an error.
     pub should_error: bool,
+    /// Errors returned, in order, by the next calls to the provider.
+    pub scripted_errors: VecDeque<BlobProviderError>,
 }
 
 impl TestBlobProvider {
@@ -22,6 +24,12 @@ impl TestBlobProvider {
         self.blobs.insert(hash, blob);
     }
 
+    /// Scripts the next call to the mock blob provider that is not already scripted to fail with
+    /// the given error.
+    pub fn push_error(&mut self, err: BlobProviderError) {
+        self.scripted_errors.push_back(err);
+    }
+
     /// Clears blobs from the mock blob provider.
     pub fn clear(&mut self) {
         self.blobs.clear();
@@ -40,6 +48,9 @@ impl BlobProvider for TestBlobProvider {
         if self.should_error {
             return Err(BlobProviderError::SlotDerivation);
         }
+        if let Some(err) = self.scripted_errors.pop_front() {
+            return Err(err);
+        }
         let mut blobs = Vec::new();
         for blob_hash in blob_hashes {
             if let Some(data) = self.blobs.get(&blob_hash.hash) {
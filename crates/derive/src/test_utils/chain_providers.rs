@@ -2,9 +2,9 @@
 
 use crate::{
     errors::{PipelineError, PipelineErrorKind},
-    traits::{ChainProvider, L2ChainProvider},
+    traits::{ChainProvider, L1BlockTag, L2ChainProvider},
 };
-use alloc::{boxed::Box, string::ToString, sync::Arc, vec::Vec};
+use alloc::{boxed::Box, collections::VecDeque, string::ToString, sync::Arc, vec::Vec};
 use alloy_consensus::{Header, Receipt, TxEnvelope};
 use alloy_primitives::{map::HashMap, B256};
 use async_trait::async_trait;
@@ -24,6 +24,10 @@ pub struct TestChainProvider {
     pub receipts: Vec<(B256, Vec<Receipt>)>,
     /// Maps block hashes to transactions using a tuple list.
     pub transactions: Vec<(B256, Vec<TxEnvelope>)>,
+    /// Errors returned, in order, by the next calls to the provider.
+    pub scripted_errors: VecDeque<TestProviderError>,
+    /// The blocks labelled with L1 head tags.
+    pub tagged_blocks: Vec<(L1BlockTag, BlockInfo)>,
 }
 
 impl TestChainProvider {
@@ -32,6 +36,12 @@ impl TestChainProvider {
         self.blocks.push((number, block));
     }
 
+    /// Labels a block with the given L1 head tag, replacing the previously labelled block.
+    pub fn insert_tagged_block(&mut self, tag: L1BlockTag, block: BlockInfo) {
+        self.tagged_blocks.retain(|(t, _)| *t != tag);
+        self.tagged_blocks.push((tag, block));
+    }
+
     /// Insert a block with transactions into the mock chain provider.
     pub fn insert_block_with_transactions(
         &mut self,
@@ -68,6 +78,17 @@ impl TestChainProvider {
         self.receipts.clear();
     }
 
+    /// Scripts the next call to the mock chain provider that is not already scripted to fail
+    /// with the given error.
+    pub fn push_error(&mut self, err: TestProviderError) {
+        self.scripted_errors.push_back(err);
+    }
+
+    /// Returns the next scripted error, if any.
+    fn scripted_error(&mut self) -> Result<(), TestProviderError> {
+        self.scripted_errors.pop_front().map_or(Ok(()), Err)
+    }
+
     /// Clears all blocks and receipts from the mock chain provider.
     pub fn clear(&mut self) {
         self.clear_blocks();
@@ -77,7 +98,7 @@ impl TestChainProvider {
 }
 
 /// An error for the [TestChainProvider] and [TestL2ChainProvider].
-#[derive(Error, Debug)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
 pub enum TestProviderError {
     /// The block was not found.
     #[error("Block not found")]
@@ -107,6 +128,7 @@ impl ChainProvider for TestChainProvider {
     type Error = TestProviderError;
 
     async fn header_by_hash(&mut self, hash: B256) -> Result<Header, Self::Error> {
+        self.scripted_error()?;
         if let Some((_, header)) = self.headers.iter().find(|(_, b)| b.hash_slow() == hash) {
             Ok(header.clone())
         } else {
@@ -115,6 +137,7 @@ impl ChainProvider for TestChainProvider {
     }
 
     async fn block_info_by_number(&mut self, _number: u64) -> Result<BlockInfo, Self::Error> {
+        self.scripted_error()?;
         if let Some((_, block)) = self.blocks.iter().find(|(n, _)| *n == _number) {
             Ok(*block)
         } else {
@@ -122,7 +145,16 @@ impl ChainProvider for TestChainProvider {
         }
     }
 
+    async fn block_info_by_tag(
+        &mut self,
+        tag: L1BlockTag,
+    ) -> Result<Option<BlockInfo>, Self::Error> {
+        self.scripted_error()?;
+        Ok(self.tagged_blocks.iter().find(|(t, _)| *t == tag).map(|(_, b)| *b))
+    }
+
     async fn receipts_by_hash(&mut self, _hash: B256) -> Result<Vec<Receipt>, Self::Error> {
+        self.scripted_error()?;
         if let Some((_, receipts)) = self.receipts.iter().find(|(h, _)| *h == _hash) {
             Ok(receipts.clone())
         } else {
@@ -134,6 +166,7 @@ impl ChainProvider for TestChainProvider {
         &mut self,
         hash: B256,
     ) -> Result<(BlockInfo, Vec<TxEnvelope>), Self::Error> {
+        self.scripted_error()?;
         let block = self
             .blocks
             .iter()
@@ -5,8 +5,8 @@
 use crate::{
     errors::PipelineError,
     stages::NextFrameProvider,
-    traits::{OriginAdvancer, OriginProvider, SignalReceiver},
-    types::{PipelineResult, Signal},
+    traits::{OriginAdvancer, OriginProvider, SignalReceiver, StageSnapshot},
+    types::{PipelineResult, PipelineSnapshot, Signal},
 };
 use alloc::{boxed::Box, vec::Vec};
 use async_trait::async_trait;
@@ -63,3 +63,7 @@ impl SignalReceiver for TestNextFrameProvider {
         Ok(())
     }
 }
+
+impl StageSnapshot for TestNextFrameProvider {
+    fn snapshot(&self, _: &mut PipelineSnapshot) {}
+}
@@ -0,0 +1,322 @@
+//! Derivation test fixtures that populate the mock providers.
+
+use crate::{
+    attributes::StatefulAttributesBuilder,
+    errors::{PipelineError, PipelineErrorKind},
+    pipeline::PipelineBuilder,
+    sources::EthereumDataSource,
+    test_utils::{TestBlobProvider, TestChainProvider, TestL2ChainProvider},
+    traits::Pipeline,
+    types::StepResult,
+};
+use alloc::{sync::Arc, vec::Vec};
+use alloy_consensus::{Header, Receipt, TxEnvelope};
+use alloy_eips::eip4844::Blob;
+use alloy_primitives::B256;
+use maili_protocol::{BlockInfo, L2BlockInfo};
+use op_alloy_genesis::{RollupConfig, SystemConfig};
+use op_alloy_rpc_types_engine::OpAttributesWithParent;
+use thiserror::Error;
+
+/// An error when running a [DerivationFixture].
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum FixtureError {
+    /// The L1 origin of the safe head is not in the fixture.
+    #[error("L1 origin {0} of the safe head not found")]
+    MissingOrigin(u64),
+    /// The pipeline failed with a non-recoverable error.
+    #[error("Pipeline error: {0}")]
+    Pipeline(PipelineErrorKind),
+    /// A different number of attributes than expected was derived.
+    #[error("Expected {expected} attributes, derived {derived}")]
+    AttributesCount {
+        /// The number of expected attributes.
+        expected: usize,
+        /// The number of derived attributes.
+        derived: usize,
+    },
+    /// The derived attributes differ from the expected attributes at the given index.
+    #[error("Attributes mismatch at index {0}")]
+    AttributesMismatch(usize),
+}
+
+impl From<PipelineErrorKind> for FixtureError {
+    fn from(err: PipelineErrorKind) -> Self {
+        Self::Pipeline(err)
+    }
+}
+
+/// The L1 chain data of a single block in a [DerivationFixture].
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct L1BlockFixture {
+    /// The block info.
+    pub info: BlockInfo,
+    /// The block header.
+    pub header: Header,
+    /// The receipts of the block.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub receipts: Vec<Receipt>,
+    /// The transactions of the block.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub transactions: Vec<TxEnvelope>,
+    /// The blobs referenced by the block, keyed by versioned hash.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub blobs: Vec<(B256, Blob)>,
+}
+
+/// A derivation test vector, describing the L1 and L2 chain state visible to the pipeline, and the
+/// attributes expected to be derived from it.
+///
+/// With the `serde` feature enabled, fixtures can be deserialized from JSON test vectors and loaded
+/// into the [TestChainProvider], [TestBlobProvider], and [TestL2ChainProvider] mocks. Hashes,
+/// quantities, and transaction data use the hex encoding of the Ethereum JSON-RPC API, so vectors
+/// exported from op-node's derivation tests can be converted field by field.
+///
+/// [DerivationFixture::run] derives the attributes with the [EthereumDataSource] and the
+/// [StatefulAttributesBuilder], and compares them with the expected attributes.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct DerivationFixture {
+    /// The rollup config of the chain.
+    pub rollup_config: RollupConfig,
+    /// The L1 blocks, in ascending order.
+    pub l1_blocks: Vec<L1BlockFixture>,
+    /// The L2 blocks, in ascending order.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub l2_blocks: Vec<L2BlockInfo>,
+    /// The system configs, keyed by L2 block number.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub system_configs: Vec<(u64, SystemConfig)>,
+    /// The L2 safe head that derivation starts from.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub safe_head: L2BlockInfo,
+    /// The attributes expected to be derived, in order.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub expected_attributes: Vec<OpAttributesWithParent>,
+}
+
+impl DerivationFixture {
+    /// Returns a [TestChainProvider] serving the fixture's L1 blocks.
+    pub fn chain_provider(&self) -> TestChainProvider {
+        let mut provider = TestChainProvider::default();
+        for block in &self.l1_blocks {
+            provider.insert_block_with_transactions(
+                block.info.number,
+                block.info,
+                block.transactions.clone(),
+            );
+            provider.insert_header(block.info.hash, block.header.clone());
+            provider.insert_receipts(block.info.hash, block.receipts.clone());
+        }
+        provider
+    }
+
+    /// Returns a [TestBlobProvider] serving the fixture's blobs.
+    pub fn blob_provider(&self) -> TestBlobProvider {
+        let mut provider = TestBlobProvider::default();
+        for (hash, blob) in self.l1_blocks.iter().flat_map(|b| b.blobs.iter()) {
+            provider.insert_blob(*hash, *blob);
+        }
+        provider
+    }
+
+    /// Returns a [TestL2ChainProvider] serving the fixture's L2 blocks and system configs.
+    pub fn l2_chain_provider(&self) -> TestL2ChainProvider {
+        TestL2ChainProvider::new(
+            self.l2_blocks.clone(),
+            Vec::new(),
+            self.system_configs.iter().cloned().collect(),
+        )
+    }
+
+    /// Derives attributes from the fixture, starting at its safe head.
+    ///
+    /// The cursor advances through the fixture's L2 blocks as attributes are derived. Derivation
+    /// stops once the L1 chain is exhausted, or once attributes for the last L2 block have been
+    /// derived.
+    pub async fn derive(&self) -> Result<Vec<OpAttributesWithParent>, FixtureError> {
+        let cfg = Arc::new(self.rollup_config.clone());
+        let origin = self
+            .l1_blocks
+            .iter()
+            .find(|b| b.info.number == self.safe_head.l1_origin.number)
+            .map(|b| b.info)
+            .ok_or(FixtureError::MissingOrigin(self.safe_head.l1_origin.number))?;
+
+        let chain_provider = self.chain_provider();
+        let l2_chain_provider = self.l2_chain_provider();
+        let dap =
+            EthereumDataSource::new_from_parts(chain_provider.clone(), self.blob_provider(), &cfg);
+        let builder = StatefulAttributesBuilder::new(
+            cfg.clone(),
+            l2_chain_provider.clone(),
+            chain_provider.clone(),
+        );
+        let mut pipeline = PipelineBuilder::new()
+            .rollup_config(cfg)
+            .origin(origin)
+            .dap_source(dap)
+            .builder(builder)
+            .chain_provider(chain_provider)
+            .l2_chain_provider(l2_chain_provider)
+            .build();
+        pipeline.reset(self.safe_head, origin).await?;
+
+        let mut cursor = self.safe_head;
+        let mut derived = Vec::new();
+        let next_block = |cursor: &L2BlockInfo| {
+            self.l2_blocks.iter().find(|b| b.block_info.number == cursor.block_info.number + 1)
+        };
+        while let Some(next) = next_block(&cursor) {
+            match pipeline.step(cursor).await {
+                StepResult::PreparedAttributes => {
+                    derived.extend(pipeline.next());
+                    cursor = *next;
+                }
+                StepResult::AdvancedOrigin => {}
+                StepResult::OriginAdvanceErr(PipelineErrorKind::Temporary(PipelineError::Eof)) => {
+                    break
+                }
+                StepResult::StepFailed(PipelineErrorKind::Temporary(
+                    PipelineError::NotEnoughData,
+                )) => {}
+                StepResult::OriginAdvanceErr(e) | StepResult::StepFailed(e) => return Err(e.into()),
+            }
+        }
+        Ok(derived)
+    }
+
+    /// Derives attributes from the fixture with [DerivationFixture::derive], and checks that they
+    /// match the expected attributes.
+    pub async fn run(&self) -> Result<(), FixtureError> {
+        let derived = self.derive().await?;
+        if derived.len() != self.expected_attributes.len() {
+            return Err(FixtureError::AttributesCount {
+                expected: self.expected_attributes.len(),
+                derived: derived.len(),
+            });
+        }
+        if let Some(index) = derived.iter().zip(&self.expected_attributes).position(|(d, e)| d != e)
+        {
+            return Err(FixtureError::AttributesMismatch(index));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use op_alloy_genesis::ChainGenesis;
+
+    /// A fixture with an empty batcher inbox, so that every L2 block is derived as deposit-only
+    /// once the sequencing windows expire.
+    fn deposit_only_fixture() -> DerivationFixture {
+        let mut l1_blocks: Vec<L1BlockFixture> = Vec::new();
+        for number in 0..5 {
+            let header = Header {
+                number,
+                timestamp: number * 12,
+                parent_hash: l1_blocks.last().map(|b| b.info.hash).unwrap_or_default(),
+                base_fee_per_gas: Some(7),
+                ..Default::default()
+            };
+            let info = BlockInfo {
+                hash: header.hash_slow(),
+                number,
+                parent_hash: header.parent_hash,
+                timestamp: header.timestamp,
+            };
+            l1_blocks.push(L1BlockFixture { info, header, ..Default::default() });
+        }
+
+        let l2_blocks: Vec<L2BlockInfo> = (0..13)
+            .map(|number: u64| {
+                let epoch = &l1_blocks[(number / 6) as usize].info;
+                L2BlockInfo {
+                    block_info: BlockInfo {
+                        hash: B256::with_last_byte(number as u8 + 1),
+                        number,
+                        parent_hash: B256::with_last_byte(number as u8),
+                        timestamp: number * 2,
+                    },
+                    l1_origin: epoch.id(),
+                    seq_num: number % 6,
+                }
+            })
+            .collect();
+
+        let rollup_config = RollupConfig {
+            genesis: ChainGenesis {
+                l1: l1_blocks[0].info.id(),
+                l2: l2_blocks[0].block_info.id(),
+                system_config: Some(SystemConfig::default()),
+                ..Default::default()
+            },
+            block_time: 2,
+            seq_window_size: 2,
+            max_sequencer_drift: 600,
+            channel_timeout: 100,
+            ..Default::default()
+        };
+
+        DerivationFixture {
+            rollup_config,
+            system_configs: (0..13).map(|n| (n, SystemConfig::default())).collect(),
+            safe_head: l2_blocks[0],
+            l2_blocks,
+            l1_blocks,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fixture_derive_deposit_only() {
+        let fixture = deposit_only_fixture();
+        let derived = fixture.derive().await.unwrap();
+        assert_eq!(derived.len(), 12);
+        for (attributes, parent) in derived.iter().zip(&fixture.l2_blocks) {
+            assert_eq!(attributes.parent, *parent);
+            assert_eq!(
+                attributes.attributes.payload_attributes.timestamp,
+                parent.block_info.timestamp + 2
+            );
+            assert_eq!(attributes.attributes.transactions.as_ref().map(Vec::len), Some(1));
+            assert_eq!(attributes.attributes.no_tx_pool, Some(true));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fixture_run_mismatch() {
+        let mut fixture = deposit_only_fixture();
+        assert_eq!(
+            fixture.run().await,
+            Err(FixtureError::AttributesCount { expected: 0, derived: 12 })
+        );
+
+        fixture.expected_attributes = fixture.derive().await.unwrap();
+        assert_eq!(fixture.run().await, Ok(()));
+
+        fixture.expected_attributes[3].attributes.payload_attributes.timestamp += 1;
+        assert_eq!(fixture.run().await, Err(FixtureError::AttributesMismatch(3)));
+    }
+
+    #[tokio::test]
+    async fn test_fixture_missing_origin() {
+        let mut fixture = deposit_only_fixture();
+        fixture.safe_head.l1_origin.number = 10;
+        assert_eq!(fixture.run().await, Err(FixtureError::MissingOrigin(10)));
+    }
+
+    #[cfg(feature = "serde")]
+    #[tokio::test]
+    async fn test_fixture_json_vector() {
+        let raw = include_str!("../../testdata/deposit_only_fixture.json");
+        let fixture: DerivationFixture = serde_json::from_str(raw).unwrap();
+        assert_eq!(fixture.expected_attributes.len(), 12);
+        fixture.run().await.unwrap();
+    }
+}
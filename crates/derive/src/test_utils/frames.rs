@@ -21,7 +21,8 @@ pub struct FrameQueueBuilder {
     expected_err: Option<PipelineErrorKind>,
 }
 
-fn encode_frames(frames: &[Frame]) -> Bytes {
+/// Encodes the frames into batcher transaction data.
+pub(crate) fn encode_frames(frames: &[Frame]) -> Bytes {
     let mut bytes = Vec::new();
     bytes.extend_from_slice(&[DERIVATION_VERSION_0]);
     for frame in frames.iter() {
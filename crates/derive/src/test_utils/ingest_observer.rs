@@ -0,0 +1,27 @@
+//! An implementation of the [DataIngestObserver] trait for tests.
+
+use crate::{traits::DataIngestObserver, types::IngestDecision};
+use alloc::vec::Vec;
+use alloy_primitives::B256;
+use maili_protocol::BlockInfo;
+use spin::Mutex;
+
+/// A [DataIngestObserver] that records every decision.
+#[derive(Debug, Default)]
+pub struct TestIngestObserver {
+    /// The recorded transaction hashes and decisions, in call order.
+    pub decisions: Mutex<Vec<(B256, IngestDecision)>>,
+}
+
+impl TestIngestObserver {
+    /// Returns the recorded decisions, without the transaction hashes.
+    pub fn decisions(&self) -> Vec<IngestDecision> {
+        self.decisions.lock().iter().map(|(_, d)| *d).collect()
+    }
+}
+
+impl DataIngestObserver for TestIngestObserver {
+    fn on_transaction(&self, _: &BlockInfo, tx_hash: B256, decision: IngestDecision) {
+        self.decisions.lock().push((tx_hash, decision));
+    }
+}
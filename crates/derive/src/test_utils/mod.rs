@@ -16,6 +16,9 @@ pub use chain_providers::{TestChainProvider, TestL2ChainProvider, TestProviderEr
 mod data_availability_provider;
 pub use data_availability_provider::TestDAP;
 
+mod ingest_observer;
+pub use ingest_observer::TestIngestObserver;
+
 mod batch_provider;
 pub use batch_provider::TestNextBatchProvider;
 
@@ -43,7 +46,27 @@ pub use tracing::{CollectingLayer, TraceStorage};
 mod sys_config_fetcher;
 pub use sys_config_fetcher::{TestSystemConfigL2Fetcher, TestSystemConfigL2FetcherError};
 
+mod fixtures;
+pub use fixtures::{DerivationFixture, FixtureError, L1BlockFixture};
+
+mod batches;
+pub use batches::{new_compressed_batch_data, tx_ref};
+
 mod frames;
+#[cfg(test)]
+pub(crate) use frames::encode_frames;
 pub use frames::{FrameQueueAsserter, FrameQueueBuilder};
 
+mod simulation;
+#[cfg(test)]
+pub(crate) use simulation::{batcher_tx, sign_as_batcher};
+pub use simulation::{
+    channel_frames, encode_channel, l2_block_hash, PipelineSimulation, SimulatedL1, SimulatedL2,
+    SimulationBatchProvider, SimulationDataSource, SimulationPipeline, VirtualClock,
+    SIMULATED_BATCHER,
+};
+
+mod scenario;
+pub use scenario::{Scenario, ScenarioBuilder, ScenarioError, ScenarioOutcome, ScenarioStep};
+
 mod macros;
@@ -20,8 +20,8 @@ use crate::{
         L1Traversal,
     },
     test_utils::{TestAttributesBuilder, TestDAP},
-    traits::{NextAttributes, OriginAdvancer, OriginProvider, SignalReceiver},
-    types::Signal,
+    traits::{NextAttributes, OriginAdvancer, OriginProvider, SignalReceiver, StageSnapshot},
+    types::{PipelineSnapshot, Signal},
 };
 
 /// A fully custom [NextAttributes].
@@ -63,6 +63,10 @@ impl NextAttributes for TestNextAttributes {
     }
 }
 
+impl StageSnapshot for TestNextAttributes {
+    fn snapshot(&self, _: &mut PipelineSnapshot) {}
+}
+
 /// An [L1Traversal] using test providers and sources.
 pub type TestL1Traversal = L1Traversal<TestChainProvider>;
 
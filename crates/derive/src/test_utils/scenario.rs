@@ -0,0 +1,611 @@
+//! Scripted L1 reorg scenarios, run on a [PipelineSimulation].
+//!
+//! A [Scenario] scripts the L1 chain, the batcher submitting to it, and the reorgs it goes through
+//! as a list of [ScenarioStep]s, built with the [ScenarioBuilder]. Running it replays the steps on
+//! a [PipelineSimulation], and then checks that the resets left the pipeline where a node syncing
+//! the final L1 chain from scratch would be, so that reorg incidents can be encoded as regression
+//! tests without asserting on the internals of the pipeline.
+
+use crate::{
+    errors::PipelineErrorKind,
+    test_utils::{
+        channel_frames, encode_channel, l2_block_hash, PipelineSimulation, SimulatedL1,
+        SIMULATED_BATCHER,
+    },
+    traits::Pipeline,
+};
+use alloc::{collections::VecDeque, vec, vec::Vec};
+use alloy_consensus::{SignableTransaction, TxEnvelope, TxLegacy};
+use alloy_eips::eip2718::Encodable2718;
+use alloy_primitives::{Address, PrimitiveSignature as Signature, TxKind};
+use maili_protocol::{Batch, BlockInfo, Frame, L2BlockInfo, SingleBatch, SpanBatch};
+use op_alloy_genesis::RollupConfig;
+use thiserror::Error;
+
+/// An error when running a [Scenario].
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ScenarioError {
+    /// The pipeline failed with a non-recoverable error.
+    #[error("Pipeline error: {0}")]
+    Pipeline(PipelineErrorKind),
+    /// The L2 block with the given number has an L1 origin that is not canonical.
+    #[error("L2 block {0} has a non-canonical L1 origin")]
+    NonCanonicalOrigin(u64),
+    /// The L2 chain differs from the one derived from scratch from the given L2 block number on.
+    #[error("L2 chain diverged from a fresh derivation at block {0}")]
+    Diverged(u64),
+}
+
+impl From<PipelineErrorKind> for ScenarioError {
+    fn from(err: PipelineErrorKind) -> Self {
+        Self::Pipeline(err)
+    }
+}
+
+/// A step of a [Scenario].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScenarioStep {
+    /// Sequences the given number of L2 blocks, and queues a channel of the single batches of the
+    /// L2 blocks that are not queued yet, split into the given number of frames.
+    Batches {
+        /// The number of L2 blocks.
+        blocks: u64,
+        /// The number of frames.
+        frames: usize,
+    },
+    /// Sequences the given number of L2 blocks, and queues a channel of a span batch of the L2
+    /// blocks that are not queued yet, split into the given number of frames.
+    SpanBatch {
+        /// The number of L2 blocks.
+        blocks: u64,
+        /// The number of frames.
+        frames: usize,
+    },
+    /// Scripts an L1 block with a batcher transaction carrying up to the given number of queued
+    /// frames. The block is empty if no frames are queued.
+    SubmitFrames(usize),
+    /// Scripts the given number of empty L1 blocks.
+    EmptyBlocks(u64),
+    /// Reveals the scripted L1 blocks, and runs the pipeline until it is idle.
+    Sync,
+    /// Drops the given number of L1 blocks at the tip of the chain. The pipeline only notices the
+    /// reorg once the new fork is scripted past the old tip.
+    Reorg(u64),
+}
+
+/// The result of a [Scenario] that passed its checks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScenarioOutcome {
+    /// The L2 chain, from the genesis to the safe head.
+    pub l2_blocks: Vec<L2BlockInfo>,
+    /// The L2 safe head.
+    pub safe_head: L2BlockInfo,
+    /// The head of the L2 chain sequenced by the batcher, which is the safe head once every
+    /// sequenced block is derived from its batch.
+    pub unsafe_head: L2BlockInfo,
+    /// The tip of the L1 chain.
+    pub l1_tip: BlockInfo,
+    /// The number of resets handled by the pipeline.
+    pub resets: usize,
+    /// The number of attributes derived, including those of L2 blocks dropped by resets.
+    pub derived: usize,
+}
+
+/// A scripted L1 reorg scenario, built with the [ScenarioBuilder].
+///
+/// The L1 chain starts at a genesis block at timestamp `0`, and the batcher sequences L2 blocks on
+/// the L2 genesis. When sequencing, the batcher adopts the next L1 block as the epoch once it is
+/// scripted and the L2 block is not older than it. As op-batcher does, it drops the L2 blocks of
+/// the epochs dropped by a reorg, and queues the batches of the channels lost to it again with the
+/// next [ScenarioStep::Batches] or [ScenarioStep::SpanBatch].
+///
+/// [Scenario::run] replays the steps, reveals the whole L1 chain and runs the pipeline until it is
+/// idle. The scenario passes if every L2 block has a canonical L1 origin, and the L2 chain is the
+/// one derived by a new [PipelineSimulation] over the final L1 chain.
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    /// The rollup config, with the [SIMULATED_BATCHER] as the batcher.
+    pub cfg: RollupConfig,
+    /// The L1 block time, in seconds.
+    pub l1_block_time: u64,
+    /// The steps.
+    pub steps: Vec<ScenarioStep>,
+}
+
+impl Scenario {
+    /// Runs the scenario, and checks the L2 chain it derived.
+    ///
+    /// ## Panics
+    /// Panics if a [ScenarioStep::Reorg] would drop the L1 genesis block.
+    pub async fn run(&self) -> Result<ScenarioOutcome, ScenarioError> {
+        let l1 = SimulatedL1::new(0, self.l1_block_time);
+        let mut sim = PipelineSimulation::new(self.cfg.clone(), l1.clone()).await?;
+        let mut batcher = ScenarioBatcher::new(sim.pipeline.rollup_config(), sim.safe_head());
+        for step in &self.steps {
+            match *step {
+                ScenarioStep::Batches { blocks, frames } => {
+                    batcher.queue_channel(&l1, blocks, frames, false)
+                }
+                ScenarioStep::SpanBatch { blocks, frames } => {
+                    batcher.queue_channel(&l1, blocks, frames, true)
+                }
+                ScenarioStep::SubmitFrames(count) => batcher.submit(&l1, count),
+                ScenarioStep::EmptyBlocks(count) => {
+                    l1.push_empty_blocks(count);
+                }
+                ScenarioStep::Sync => {
+                    l1.advance_to_block(l1.tip().number);
+                    sim.run_until_idle().await?;
+                }
+                ScenarioStep::Reorg(depth) => {
+                    let number = (l1.tip().number + 1).saturating_sub(depth);
+                    l1.reorg(number);
+                    batcher.reorg(number);
+                }
+            }
+        }
+        l1.advance_to_block(l1.tip().number);
+        sim.run_until_idle().await?;
+
+        let l2_blocks = sim.l2.blocks();
+        if let Some(block) = l2_blocks
+            .iter()
+            .find(|b| l1.block(b.l1_origin.number).is_none_or(|o| o.hash != b.l1_origin.hash))
+        {
+            return Err(ScenarioError::NonCanonicalOrigin(block.block_info.number));
+        }
+
+        let mut fresh = PipelineSimulation::new(self.cfg.clone(), l1.snapshot()).await?;
+        fresh.run_until_idle().await?;
+        let expected = fresh.l2.blocks();
+        if let Some(i) =
+            (0..l2_blocks.len().max(expected.len())).find(|&i| l2_blocks.get(i) != expected.get(i))
+        {
+            return Err(ScenarioError::Diverged(l2_blocks[0].block_info.number + i as u64));
+        }
+
+        Ok(ScenarioOutcome {
+            safe_head: sim.safe_head(),
+            unsafe_head: batcher.head(),
+            l2_blocks,
+            l1_tip: l1.tip(),
+            resets: sim.resets,
+            derived: sim.derived.len(),
+        })
+    }
+}
+
+/// A builder for a [Scenario].
+#[derive(Debug, Clone)]
+pub struct ScenarioBuilder {
+    /// The scenario being built.
+    scenario: Scenario,
+}
+
+impl ScenarioBuilder {
+    /// Creates a new [ScenarioBuilder] for the given rollup config, with an L1 block time of 12
+    /// seconds. The batcher address of the genesis system config is set to the
+    /// [SIMULATED_BATCHER].
+    pub fn new(mut cfg: RollupConfig) -> Self {
+        cfg.genesis.system_config.get_or_insert_with(Default::default).batcher_address =
+            SIMULATED_BATCHER;
+        Self { scenario: Scenario { cfg, l1_block_time: 12, steps: Vec::new() } }
+    }
+
+    /// Sets the L1 block time, in seconds.
+    pub const fn with_l1_block_time(mut self, l1_block_time: u64) -> Self {
+        self.scenario.l1_block_time = l1_block_time;
+        self
+    }
+
+    /// Appends the step.
+    pub fn step(mut self, step: ScenarioStep) -> Self {
+        self.scenario.steps.push(step);
+        self
+    }
+
+    /// Appends a [ScenarioStep::Batches].
+    pub fn batches(self, blocks: u64, frames: usize) -> Self {
+        self.step(ScenarioStep::Batches { blocks, frames })
+    }
+
+    /// Appends a [ScenarioStep::SpanBatch].
+    pub fn span_batch(self, blocks: u64, frames: usize) -> Self {
+        self.step(ScenarioStep::SpanBatch { blocks, frames })
+    }
+
+    /// Appends a [ScenarioStep::SubmitFrames].
+    pub fn submit_frames(self, count: usize) -> Self {
+        self.step(ScenarioStep::SubmitFrames(count))
+    }
+
+    /// Appends a [ScenarioStep::EmptyBlocks].
+    pub fn empty_blocks(self, count: u64) -> Self {
+        self.step(ScenarioStep::EmptyBlocks(count))
+    }
+
+    /// Appends a [ScenarioStep::Sync].
+    pub fn sync(self) -> Self {
+        self.step(ScenarioStep::Sync)
+    }
+
+    /// Appends a [ScenarioStep::Reorg].
+    pub fn reorg(self, depth: u64) -> Self {
+        self.step(ScenarioStep::Reorg(depth))
+    }
+
+    /// Builds the [Scenario].
+    pub fn build(self) -> Scenario {
+        self.scenario
+    }
+}
+
+/// A channel opened by the [ScenarioBatcher].
+#[derive(Debug)]
+struct ScenarioChannel {
+    /// The index of the first batch of the channel.
+    first: usize,
+    /// The index after the last batch of the channel.
+    end: usize,
+    /// The numbers of the L1 blocks that the submitted frames of the channel were included in.
+    submitted: Vec<u64>,
+}
+
+/// The batcher of a [Scenario], sequencing L2 blocks and submitting their batches to the
+/// [SimulatedL1].
+#[derive(Debug)]
+struct ScenarioBatcher {
+    /// The L2 chain id.
+    chain_id: u64,
+    /// The L2 block time, in seconds.
+    block_time: u64,
+    /// The L2 genesis timestamp.
+    genesis_timestamp: u64,
+    /// The batch inbox address.
+    batch_inbox: Address,
+    /// The sequenced L2 blocks, from the genesis.
+    blocks: Vec<L2BlockInfo>,
+    /// The batches and sequence numbers of the sequenced L2 blocks after the genesis.
+    batches: Vec<(SingleBatch, u64)>,
+    /// The number of batches queued in a channel.
+    batched: usize,
+    /// The channels that are not lost to a reorg, in the order they were opened.
+    channels: Vec<ScenarioChannel>,
+    /// The frames waiting to be submitted, with the index of their channel.
+    pending: VecDeque<(usize, Frame)>,
+    /// The number of channels opened so far.
+    opened: u64,
+}
+
+impl ScenarioBatcher {
+    /// Creates a new [ScenarioBatcher] sequencing on the given L2 genesis block.
+    fn new(cfg: &RollupConfig, genesis: L2BlockInfo) -> Self {
+        Self {
+            chain_id: cfg.l2_chain_id,
+            block_time: cfg.block_time,
+            genesis_timestamp: cfg.genesis.l2_time,
+            batch_inbox: cfg.batch_inbox_address,
+            blocks: vec![genesis],
+            batches: Vec::new(),
+            batched: 0,
+            channels: Vec::new(),
+            pending: VecDeque::new(),
+            opened: 0,
+        }
+    }
+
+    /// Returns the latest sequenced L2 block.
+    fn head(&self) -> L2BlockInfo {
+        *self.blocks.last().expect("genesis is never dropped")
+    }
+
+    /// Sequences the given number of L2 blocks with a user transaction each.
+    fn sequence(&mut self, l1: &SimulatedL1, count: u64) {
+        for _ in 0..count {
+            let parent = self.head();
+            let timestamp = parent.block_info.timestamp + self.block_time;
+            let next = l1.block(parent.l1_origin.number + 1).filter(|b| b.timestamp <= timestamp);
+            let (epoch, seq_num) = next.map_or_else(
+                || {
+                    let epoch = l1.block(parent.l1_origin.number).expect("epoch is canonical");
+                    (epoch, parent.seq_num + 1)
+                },
+                |epoch| (epoch, 0),
+            );
+            let number = parent.block_info.number + 1;
+            let batch = SingleBatch {
+                parent_hash: parent.block_info.hash,
+                epoch_num: epoch.number,
+                epoch_hash: epoch.hash,
+                timestamp,
+                transactions: vec![self.user_transaction(number).encoded_2718().into()],
+            };
+            self.blocks.push(L2BlockInfo {
+                block_info: BlockInfo {
+                    hash: l2_block_hash(parent.block_info.hash, timestamp, &batch.transactions),
+                    number,
+                    parent_hash: parent.block_info.hash,
+                    timestamp,
+                },
+                l1_origin: epoch.id(),
+                seq_num,
+            });
+            self.batches.push((batch, seq_num));
+        }
+    }
+
+    /// Returns the user transaction of the L2 block with the given number.
+    fn user_transaction(&self, number: u64) -> TxEnvelope {
+        let tx = TxLegacy {
+            chain_id: Some(self.chain_id),
+            nonce: number,
+            gas_price: 1_000_000_000,
+            gas_limit: 21_000,
+            to: TxKind::Call(Address::with_last_byte(1)),
+            ..Default::default()
+        };
+        TxEnvelope::Legacy(tx.into_signed(Signature::test_signature()))
+    }
+
+    /// Sequences the given number of L2 blocks, and queues a channel of the batches that are not
+    /// queued yet, split into the given number of frames.
+    fn queue_channel(&mut self, l1: &SimulatedL1, blocks: u64, frames: usize, span: bool) {
+        self.sequence(l1, blocks);
+        let (first, end) = (self.batched, self.batches.len());
+        if first == end {
+            return;
+        }
+        let batches = self.batches[first..end].iter().cloned();
+        let batches = if span {
+            let mut span = SpanBatch {
+                chain_id: self.chain_id,
+                genesis_timestamp: self.genesis_timestamp,
+                ..Default::default()
+            };
+            for (batch, seq_num) in batches {
+                span.append_singular_batch(batch, seq_num).expect("batches are contiguous");
+            }
+            vec![Batch::Span(span)]
+        } else {
+            batches.map(|(batch, _)| Batch::Single(batch)).collect()
+        };
+
+        self.opened += 1;
+        let mut id = [0; 16];
+        id[8..].copy_from_slice(&self.opened.to_be_bytes());
+        let channel = self.channels.len();
+        self.channels.push(ScenarioChannel { first, end, submitted: Vec::new() });
+        self.batched = end;
+        let data = encode_channel(&batches);
+        self.pending.extend(channel_frames(id, &data, frames).into_iter().map(|f| (channel, f)));
+    }
+
+    /// Scripts an L1 block with up to the given number of pending frames.
+    fn submit(&mut self, l1: &SimulatedL1, count: usize) {
+        let frames = self.pending.drain(..count.min(self.pending.len())).collect::<Vec<_>>();
+        if frames.is_empty() {
+            l1.push_block(Vec::new());
+            return;
+        }
+        let data = frames.iter().map(|(_, frame)| frame.clone()).collect::<Vec<_>>();
+        let block = l1.push_block(vec![SimulatedL1::batcher_transaction(self.batch_inbox, &data)]);
+        for (channel, _) in frames {
+            self.channels[channel].submitted.push(block.number);
+        }
+    }
+
+    /// Handles a reorg of the L1 blocks from the given number on.
+    ///
+    /// The L2 blocks of the dropped epochs are dropped, to be sequenced again. A channel is lost
+    /// if it has a frame in a dropped L1 block or a batch of a dropped L2 block, and the batches
+    /// of the lost channels and of the channels opened after them are queued again.
+    fn reorg(&mut self, number: u64) {
+        while self.blocks.len() > 1 && self.head().l1_origin.number >= number {
+            self.blocks.pop();
+            self.batches.pop();
+        }
+        let batches = self.batches.len();
+        if let Some(lost) = self
+            .channels
+            .iter()
+            .position(|c| c.end > batches || c.submitted.iter().any(|n| *n >= number))
+        {
+            self.batched = self.channels[lost].first;
+            self.channels.truncate(lost);
+            self.pending.retain(|(channel, _)| *channel < lost);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    /// The reorg depths that every scenario is run with.
+    const DEPTHS: [u64; 5] = [1, 2, 8, 32, 64];
+
+    fn rollup_config() -> RollupConfig {
+        RollupConfig {
+            block_time: 2,
+            max_sequencer_drift: 600,
+            seq_window_size: 200,
+            channel_timeout: 100,
+            l2_chain_id: 10,
+            batch_inbox_address: address!("ff00000000000000000000000000000000000010"),
+            regolith_time: Some(0),
+            canyon_time: Some(0),
+            delta_time: Some(0),
+            ..Default::default()
+        }
+    }
+
+    /// Runs the scenario, and asserts that the pipeline reset and then derived every sequenced L2
+    /// block from its batch.
+    async fn assert_recovers(scenario: ScenarioBuilder) -> ScenarioOutcome {
+        let outcome = scenario.build().run().await.unwrap();
+        assert!(outcome.resets > 0);
+        assert_eq!(outcome.safe_head, outcome.unsafe_head);
+        outcome
+    }
+
+    #[tokio::test]
+    async fn test_scenario_without_reorg() {
+        let outcome = ScenarioBuilder::new(rollup_config())
+            .empty_blocks(1)
+            .batches(6, 2)
+            .span_batch(6, 2)
+            .submit_frames(4)
+            .sync()
+            .build()
+            .run()
+            .await
+            .unwrap();
+        assert_eq!(outcome.resets, 0);
+        assert_eq!(outcome.safe_head, outcome.unsafe_head);
+        assert_eq!(outcome.safe_head.block_info.number, 12);
+        assert_eq!(outcome.safe_head.l1_origin.number, 1);
+        assert_eq!(outcome.derived, 12);
+    }
+
+    #[tokio::test]
+    async fn test_reorg_mid_channel_first_frame() {
+        for depth in DEPTHS {
+            // The reorg drops the first frame of the channel, so the batches are resubmitted in a
+            // new channel.
+            let outcome = assert_recovers(
+                ScenarioBuilder::new(rollup_config())
+                    .empty_blocks(2)
+                    .batches(6, 2)
+                    .submit_frames(1)
+                    .empty_blocks(depth - 1)
+                    .sync()
+                    .reorg(depth)
+                    .batches(0, 2)
+                    .submit_frames(2)
+                    .empty_blocks(depth)
+                    .sync(),
+            )
+            .await;
+            assert_eq!(outcome.safe_head.block_info.number, 6);
+            assert_eq!(outcome.derived, 6);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reorg_mid_channel_last_frame() {
+        for depth in DEPTHS {
+            // The channel is completed on the old fork, so its batches are derived, and derived
+            // again from a new channel once the reorg of the last frame is detected.
+            let outcome = assert_recovers(
+                ScenarioBuilder::new(rollup_config())
+                    .empty_blocks(2)
+                    .batches(6, 2)
+                    .submit_frames(1)
+                    .empty_blocks(1)
+                    .submit_frames(1)
+                    .empty_blocks(depth - 1)
+                    .sync()
+                    .reorg(depth)
+                    .batches(0, 1)
+                    .submit_frames(1)
+                    .empty_blocks(depth)
+                    .sync(),
+            )
+            .await;
+            assert_eq!(outcome.safe_head.block_info.number, 6);
+            assert_eq!(outcome.derived, 12);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reorg_mid_span_batch() {
+        for depth in DEPTHS {
+            // The span batch is split into three frames, and the reorg drops the second one while
+            // the third one is still queued.
+            let outcome = assert_recovers(
+                ScenarioBuilder::new(rollup_config())
+                    .empty_blocks(1)
+                    .span_batch(12, 3)
+                    .submit_frames(1)
+                    .submit_frames(1)
+                    .empty_blocks(depth - 1)
+                    .sync()
+                    .reorg(depth)
+                    .span_batch(0, 2)
+                    .submit_frames(1)
+                    .submit_frames(1)
+                    .empty_blocks(depth)
+                    .sync(),
+            )
+            .await;
+            assert_eq!(outcome.safe_head.block_info.number, 12);
+            assert_eq!(outcome.derived, 12);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reorg_of_span_batch_epochs() {
+        for depth in DEPTHS {
+            // The span batch spans an epoch per L1 block, and the reorg drops all but its first
+            // two epochs, so its later L2 blocks are sequenced again on the new fork.
+            let outcome = assert_recovers(
+                ScenarioBuilder::new(rollup_config())
+                    .empty_blocks(depth)
+                    .span_batch(6 * depth, 2)
+                    .submit_frames(2)
+                    .sync()
+                    .reorg(depth)
+                    .empty_blocks(depth)
+                    .span_batch(6, 2)
+                    .submit_frames(2)
+                    .sync(),
+            )
+            .await;
+            // The L2 blocks of the first two epochs are kept by the batcher.
+            assert_eq!(outcome.safe_head.block_info.number, (6 * depth).min(11) + 6);
+            assert_eq!(outcome.derived, (6 * depth + outcome.safe_head.block_info.number) as usize);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reorg_after_derivation() {
+        for depth in DEPTHS {
+            // The batches are derived from a block that is reorged out, and resubmitted on the
+            // new fork with the batches of the next L2 blocks.
+            let outcome = assert_recovers(
+                ScenarioBuilder::new(rollup_config())
+                    .empty_blocks(1)
+                    .batches(6, 1)
+                    .submit_frames(1)
+                    .empty_blocks(depth - 1)
+                    .sync()
+                    .reorg(depth)
+                    .batches(6, 1)
+                    .submit_frames(1)
+                    .empty_blocks(depth)
+                    .sync(),
+            )
+            .await;
+            assert_eq!(outcome.safe_head.block_info.number, 12);
+            assert_eq!(outcome.derived, 18);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scenario_detects_divergence() {
+        // The reorg drops the block that the batches were derived from, but the new fork never
+        // grows past the old tip, so the pipeline keeps the stale L2 blocks.
+        let err = ScenarioBuilder::new(rollup_config())
+            .empty_blocks(1)
+            .batches(6, 1)
+            .submit_frames(1)
+            .sync()
+            .reorg(1)
+            .build()
+            .run()
+            .await
+            .unwrap_err();
+        assert_eq!(err, ScenarioError::Diverged(1));
+    }
+}
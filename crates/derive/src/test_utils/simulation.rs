@@ -0,0 +1,796 @@
+//! A deterministic simulation of the L1 chain driving a derivation pipeline.
+//!
+//! The [SimulatedL1] serves a scripted L1 chain, revealing each block once the [VirtualClock]
+//! reaches its timestamp. Tests advance the clock, script new blocks and reorgs, and step the
+//! [PipelineSimulation] until it waits for L1 blocks that are not visible yet, so that scenarios
+//! such as sequencing window expiry, channel timeouts and reorgs mid-channel play out without
+//! wall-clock sleeps.
+
+use crate::{
+    attributes::{l1_block_info_from_encoded_tx, StatefulAttributesBuilder},
+    errors::{BlobProviderError, PipelineError, PipelineErrorKind},
+    pipeline::{DerivationPipeline, PipelineBuilder},
+    sources::EthereumDataSource,
+    stages::{
+        AttributesQueue, BatchProvider, BatchStream, ChannelProvider, ChannelReader, FrameQueue,
+        L1Retrieval, L1Traversal,
+    },
+    test_utils::{frames::encode_frames, L1BlockFixture, TestProviderError},
+    traits::{BlobProvider, ChainProvider, L2ChainProvider, OriginProvider, Pipeline},
+    types::StepResult,
+};
+use alloc::{boxed::Box, sync::Arc, vec, vec::Vec};
+use alloy_consensus::{Header, Receipt, SignableTransaction, TxEnvelope, TxLegacy};
+use alloy_eips::eip4844::{Blob, IndexedBlobHash};
+use alloy_primitives::{
+    address, keccak256, uint, Address, Bytes, PrimitiveSignature as Signature, TxKind, B256, U256,
+};
+use alloy_rlp::Encodable;
+use async_trait::async_trait;
+use maili_protocol::{Batch, BatchValidationProvider, BlockInfo, ChannelId, Frame, L2BlockInfo};
+use op_alloy_consensus::{OpBlock, DEPOSIT_TX_TYPE_ID};
+use op_alloy_genesis::{RollupConfig, SystemConfig};
+use op_alloy_rpc_types_engine::OpAttributesWithParent;
+use spin::Mutex;
+
+/// The address of the secret key `1`, which signs the [SimulatedL1::batcher_transaction]s.
+pub const SIMULATED_BATCHER: Address = address!("7E5F4552091A69125d5DfCb7b8C2659029395Bdf");
+
+/// The order of the secp256k1 curve.
+const SECP256K1_ORDER: U256 =
+    uint!(0xFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141_U256);
+
+/// The x coordinate of the secp256k1 generator point, whose y coordinate is even.
+const SECP256K1_GENERATOR_X: U256 =
+    uint!(0x79BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798_U256);
+
+/// Signs the hash with the secret key `1` and the nonce `1`.
+///
+/// With both set to `1`, the signature point is the generator, and `s = hash + r` needs no curve
+/// arithmetic. The nonce is fixed, so this must never sign anything but test transactions.
+fn sign_with_generator(hash: B256) -> Signature {
+    let r = SECP256K1_GENERATOR_X;
+    let s = U256::from_be_bytes(hash.0).add_mod(r, SECP256K1_ORDER);
+    // Normalize to the lower half of the order, negating the signature point.
+    if s > SECP256K1_ORDER >> 1 {
+        Signature::new(r, SECP256K1_ORDER - s, true)
+    } else {
+        Signature::new(r, s, false)
+    }
+}
+
+/// Signs the legacy transaction as the [SIMULATED_BATCHER].
+pub(crate) fn sign_as_batcher(tx: TxLegacy) -> TxEnvelope {
+    let signature = sign_with_generator(tx.signature_hash());
+    TxEnvelope::Legacy(tx.into_signed(signature))
+}
+
+/// Returns a transaction from the [SIMULATED_BATCHER] to the zero address, carrying the input.
+#[cfg(test)]
+pub(crate) fn batcher_tx(input: Vec<u8>) -> TxEnvelope {
+    sign_as_batcher(TxLegacy {
+        to: TxKind::Call(Address::ZERO),
+        input: input.into(),
+        ..Default::default()
+    })
+}
+
+/// A virtual clock, shared by the [SimulatedL1] and the tests driving it.
+///
+/// The clock only moves when the test advances it, and never moves backwards.
+#[derive(Debug, Clone, Default)]
+pub struct VirtualClock(Arc<Mutex<u64>>);
+
+impl VirtualClock {
+    /// Creates a new [VirtualClock] at the given timestamp.
+    pub fn new(now: u64) -> Self {
+        Self(Arc::new(Mutex::new(now)))
+    }
+
+    /// Returns the current timestamp.
+    pub fn now(&self) -> u64 {
+        *self.0.lock()
+    }
+
+    /// Advances the clock by the given number of seconds.
+    pub fn advance(&self, secs: u64) {
+        *self.0.lock() += secs;
+    }
+
+    /// Advances the clock to the given timestamp, if it is in the future.
+    pub fn advance_to(&self, timestamp: u64) {
+        let mut now = self.0.lock();
+        *now = (*now).max(timestamp);
+    }
+}
+
+/// The scripted L1 chain of a [SimulatedL1].
+#[derive(Debug, Default)]
+struct SimulatedChain {
+    /// The L1 block time, in seconds.
+    block_time: u64,
+    /// The canonical blocks, indexed by number.
+    blocks: Vec<L1BlockFixture>,
+    /// The number of reorgs scripted so far, which salts the hashes of the blocks of each fork.
+    reorgs: u64,
+}
+
+/// A scripted L1 chain, served to the pipeline as a [ChainProvider] and [BlobProvider].
+///
+/// Blocks are scripted ahead of time with [SimulatedL1::push_block], one L1 block time apart, and
+/// become visible once the [VirtualClock] reaches their timestamp. Until then, the providers
+/// report them as not found, as an L1 node would. [SimulatedL1::reorg] replaces the tip of the
+/// chain with a new fork.
+///
+/// Clones share the same chain and clock, so the pipeline observes the changes made by the test.
+#[derive(Debug, Clone)]
+pub struct SimulatedL1 {
+    /// The clock that reveals the scripted blocks.
+    pub clock: VirtualClock,
+    /// The scripted chain.
+    chain: Arc<Mutex<SimulatedChain>>,
+}
+
+impl SimulatedL1 {
+    /// Creates a new [SimulatedL1] with the given L1 block time, whose genesis block is at the
+    /// given timestamp. The clock starts at the genesis block.
+    pub fn new(genesis_timestamp: u64, block_time: u64) -> Self {
+        let l1 = Self {
+            clock: VirtualClock::new(genesis_timestamp),
+            chain: Arc::new(Mutex::new(SimulatedChain { block_time, ..Default::default() })),
+        };
+        l1.push_block_at(genesis_timestamp, Vec::new(), Vec::new(), Vec::new());
+        l1
+    }
+
+    /// Returns the genesis block.
+    pub fn genesis(&self) -> BlockInfo {
+        self.chain.lock().blocks[0].info
+    }
+
+    /// Returns the canonical block with the given number, whether it is visible or not.
+    pub fn block(&self, number: u64) -> Option<BlockInfo> {
+        self.chain.lock().blocks.get(number as usize).map(|b| b.info)
+    }
+
+    /// Returns the latest scripted block.
+    pub fn tip(&self) -> BlockInfo {
+        self.chain.lock().blocks.last().map(|b| b.info).expect("genesis is always scripted")
+    }
+
+    /// Returns the latest block that is visible at the current time.
+    pub fn head(&self) -> BlockInfo {
+        let now = self.clock.now();
+        let chain = self.chain.lock();
+        chain.blocks.iter().rev().find(|b| b.info.timestamp <= now).map(|b| b.info).unwrap()
+    }
+
+    /// Scripts the next block with the given transactions, and returns it.
+    pub fn push_block(&self, transactions: Vec<TxEnvelope>) -> BlockInfo {
+        self.push_block_with_data(transactions, Vec::new(), Vec::new())
+    }
+
+    /// Scripts the next block with the given transactions, receipts and blobs, and returns it.
+    pub fn push_block_with_data(
+        &self,
+        transactions: Vec<TxEnvelope>,
+        receipts: Vec<Receipt>,
+        blobs: Vec<(B256, Blob)>,
+    ) -> BlockInfo {
+        let timestamp = {
+            let chain = self.chain.lock();
+            chain.blocks.last().map(|b| b.info.timestamp).unwrap_or_default() + chain.block_time
+        };
+        self.push_block_at(timestamp, transactions, receipts, blobs)
+    }
+
+    /// Scripts the given number of empty blocks, and returns the last one.
+    pub fn push_empty_blocks(&self, count: u64) -> BlockInfo {
+        (0..count).fold(self.tip(), |_, _| self.push_block(Vec::new()))
+    }
+
+    /// Scripts a block at the given timestamp on top of the current tip.
+    fn push_block_at(
+        &self,
+        timestamp: u64,
+        transactions: Vec<TxEnvelope>,
+        receipts: Vec<Receipt>,
+        blobs: Vec<(B256, Blob)>,
+    ) -> BlockInfo {
+        let mut chain = self.chain.lock();
+        let parent = chain.blocks.last().map(|b| b.info);
+        let header = Header {
+            number: parent.map_or(0, |p| p.number + 1),
+            timestamp,
+            parent_hash: parent.map(|p| p.hash).unwrap_or_default(),
+            base_fee_per_gas: Some(7),
+            mix_hash: B256::left_padding_from(&chain.reorgs.to_be_bytes()),
+            ..Default::default()
+        };
+        let info = BlockInfo {
+            hash: header.hash_slow(),
+            number: header.number,
+            parent_hash: header.parent_hash,
+            timestamp,
+        };
+        chain.blocks.push(L1BlockFixture { info, header, receipts, transactions, blobs });
+        info
+    }
+
+    /// Drops the canonical blocks from the given number onwards, so that the blocks scripted next
+    /// form a new fork with different hashes.
+    ///
+    /// ## Panics
+    /// Panics if the genesis block would be dropped.
+    pub fn reorg(&self, number: u64) {
+        assert!(number > 0, "Cannot reorg the genesis block");
+        let mut chain = self.chain.lock();
+        chain.blocks.truncate(number as usize);
+        chain.reorgs += 1;
+    }
+
+    /// Advances the clock to the timestamp of the canonical block with the given number, making
+    /// it and its ancestors visible.
+    ///
+    /// ## Panics
+    /// Panics if the block is not scripted.
+    pub fn advance_to_block(&self, number: u64) {
+        let block = self.block(number).expect("Block is not scripted");
+        self.clock.advance_to(block.timestamp);
+    }
+
+    /// Returns a batcher transaction to the given batch inbox, carrying the given frames.
+    ///
+    /// The transaction is signed by the [SIMULATED_BATCHER], which must be the batcher address of
+    /// the chain.
+    pub fn batcher_transaction(batch_inbox: Address, frames: &[Frame]) -> TxEnvelope {
+        let tx = TxLegacy {
+            to: TxKind::Call(batch_inbox),
+            input: encode_frames(frames),
+            ..Default::default()
+        };
+        sign_as_batcher(tx)
+    }
+
+    /// Returns a copy of the canonical chain that no longer shares state with this one, with all
+    /// of its blocks visible.
+    pub fn snapshot(&self) -> Self {
+        let chain = self.chain.lock();
+        let tip = chain.blocks.last().map(|b| b.info.timestamp).unwrap_or_default();
+        Self {
+            clock: VirtualClock::new(tip),
+            chain: Arc::new(Mutex::new(SimulatedChain {
+                block_time: chain.block_time,
+                blocks: chain.blocks.clone(),
+                reorgs: chain.reorgs,
+            })),
+        }
+    }
+
+    /// Returns the value of the given visible block, looked up by hash.
+    fn visible<T>(&self, hash: B256, f: impl FnOnce(&L1BlockFixture) -> T) -> Option<T> {
+        let now = self.clock.now();
+        let chain = self.chain.lock();
+        chain.blocks.iter().find(|b| b.info.hash == hash && b.info.timestamp <= now).map(f)
+    }
+}
+
+#[async_trait]
+impl ChainProvider for SimulatedL1 {
+    type Error = TestProviderError;
+
+    async fn header_by_hash(&mut self, hash: B256) -> Result<Header, Self::Error> {
+        self.visible(hash, |b| b.header.clone()).ok_or(TestProviderError::HeaderNotFound)
+    }
+
+    async fn block_info_by_number(&mut self, number: u64) -> Result<BlockInfo, Self::Error> {
+        self.block(number)
+            .filter(|b| b.timestamp <= self.clock.now())
+            .ok_or(TestProviderError::BlockNotFound)
+    }
+
+    async fn receipts_by_hash(&mut self, hash: B256) -> Result<Vec<Receipt>, Self::Error> {
+        self.visible(hash, |b| b.receipts.clone()).ok_or(TestProviderError::ReceiptsNotFound)
+    }
+
+    async fn block_info_and_transactions_by_hash(
+        &mut self,
+        hash: B256,
+    ) -> Result<(BlockInfo, Vec<TxEnvelope>), Self::Error> {
+        self.visible(hash, |b| (b.info, b.transactions.clone()))
+            .ok_or(TestProviderError::BlockNotFound)
+    }
+}
+
+#[async_trait]
+impl BlobProvider for SimulatedL1 {
+    type Error = BlobProviderError;
+
+    async fn get_blobs(
+        &mut self,
+        block_ref: &BlockInfo,
+        blob_hashes: &[IndexedBlobHash],
+    ) -> Result<Vec<Box<Blob>>, Self::Error> {
+        let blobs = self.visible(block_ref.hash, |b| b.blobs.clone()).unwrap_or_default();
+        Ok(blob_hashes
+            .iter()
+            .filter_map(|h| blobs.iter().find(|(hash, _)| *hash == h.hash))
+            .map(|(_, blob)| Box::new(*blob))
+            .collect())
+    }
+}
+
+/// Returns the synthetic hash of a simulated L2 block, committing to its parent, its timestamp,
+/// and its non-deposit transactions.
+///
+/// Deposits are left out, so that a batcher can compute the hashes of the blocks it sequences.
+pub fn l2_block_hash(parent_hash: B256, timestamp: u64, transactions: &[Bytes]) -> B256 {
+    let mut preimage = [parent_hash.as_slice(), &timestamp.to_be_bytes()].concat();
+    for tx in transactions.iter().filter(|tx| tx.first() != Some(&DEPOSIT_TX_TYPE_ID)) {
+        preimage.extend_from_slice(keccak256(tx).as_slice());
+    }
+    keccak256(preimage)
+}
+
+/// The L2 chain built by a [PipelineSimulation], served to the pipeline as an [L2ChainProvider].
+///
+/// Every L2 block has the [SystemConfig] of the genesis, and is stored with the L1 block it was
+/// derived from. Clones share the same chain.
+#[derive(Debug, Clone)]
+pub struct SimulatedL2 {
+    /// The L2 blocks and the L1 blocks they were derived from, indexed by number from the genesis.
+    blocks: Arc<Mutex<Vec<(L2BlockInfo, BlockInfo)>>>,
+    /// The system config of every L2 block.
+    system_config: SystemConfig,
+}
+
+impl SimulatedL2 {
+    /// Creates a new [SimulatedL2] from the given genesis block and system config. The genesis is
+    /// derived from its L1 origin.
+    pub fn new(genesis: L2BlockInfo, system_config: SystemConfig) -> Self {
+        let l1_genesis = BlockInfo {
+            hash: genesis.l1_origin.hash,
+            number: genesis.l1_origin.number,
+            ..Default::default()
+        };
+        Self { blocks: Arc::new(Mutex::new(vec![(genesis, l1_genesis)])), system_config }
+    }
+
+    /// Returns the L2 blocks, in ascending order.
+    pub fn blocks(&self) -> Vec<L2BlockInfo> {
+        self.blocks.lock().iter().map(|(b, _)| *b).collect()
+    }
+
+    /// Returns the safe head, i.e. the latest L2 block.
+    pub fn safe_head(&self) -> L2BlockInfo {
+        self.blocks.lock().last().expect("genesis is never dropped").0
+    }
+
+    /// Returns the L1 block that the L2 block with the given number was derived from.
+    pub fn derived_from(&self, number: u64) -> Option<BlockInfo> {
+        self.blocks.lock().iter().find(|(b, _)| b.block_info.number == number).map(|(_, o)| *o)
+    }
+
+    /// Drops the latest L2 blocks whose L1 derivation source is rejected by `keep`, and returns
+    /// the new safe head. The genesis block is always kept.
+    fn rewind(&self, keep: impl Fn(&BlockInfo) -> bool) -> L2BlockInfo {
+        let mut blocks = self.blocks.lock();
+        while blocks.len() > 1 && !blocks.last().is_some_and(|(_, source)| keep(source)) {
+            blocks.pop();
+        }
+        blocks[blocks.len() - 1].0
+    }
+
+    /// Returns the block with the given number.
+    fn block(&self, number: u64) -> Option<L2BlockInfo> {
+        self.blocks.lock().iter().find(|(b, _)| b.block_info.number == number).map(|(b, _)| *b)
+    }
+}
+
+#[async_trait]
+impl BatchValidationProvider for SimulatedL2 {
+    type Error = TestProviderError;
+
+    async fn l2_block_info_by_number(&mut self, number: u64) -> Result<L2BlockInfo, Self::Error> {
+        self.block(number).ok_or(TestProviderError::BlockNotFound)
+    }
+
+    async fn block_by_number(&mut self, _: u64) -> Result<OpBlock, Self::Error> {
+        Err(TestProviderError::L2BlockNotFound)
+    }
+}
+
+#[async_trait]
+impl L2ChainProvider for SimulatedL2 {
+    type Error = TestProviderError;
+
+    async fn system_config_by_number(
+        &mut self,
+        number: u64,
+        _: Arc<RollupConfig>,
+    ) -> Result<SystemConfig, <Self as L2ChainProvider>::Error> {
+        self.block(number)
+            .map(|_| self.system_config)
+            .ok_or(TestProviderError::SystemConfigNotFound(number))
+    }
+}
+
+/// The data source of a [SimulationPipeline].
+pub type SimulationDataSource = EthereumDataSource<SimulatedL1, SimulatedL1>;
+
+/// The [BatchProvider] stage of a [SimulationPipeline].
+pub type SimulationBatchProvider = BatchProvider<
+    BatchStream<
+        ChannelReader<
+            ChannelProvider<
+                FrameQueue<L1Retrieval<SimulationDataSource, L1Traversal<SimulatedL1>>>,
+            >,
+        >,
+        SimulatedL2,
+    >,
+    SimulatedL2,
+>;
+
+/// A [DerivationPipeline] deriving from a [SimulatedL1] onto a [SimulatedL2].
+pub type SimulationPipeline = DerivationPipeline<
+    AttributesQueue<SimulationBatchProvider, StatefulAttributesBuilder<SimulatedL1, SimulatedL2>>,
+    SimulatedL2,
+>;
+
+/// Drives a [SimulationPipeline] over a [SimulatedL1], building the [SimulatedL2] chain from the
+/// derived attributes.
+///
+/// The derived attributes are "executed" by appending an L2 block with the [l2_block_hash], whose
+/// L1 origin and sequence number are read from the L1 info deposit. Resets requested by the
+/// pipeline, e.g. after an L1 reorg, are handled as a rollup node does: the L2 blocks derived from
+/// L1 blocks that are no longer canonical are dropped, and the pipeline is reset to the remaining
+/// safe head, re-reading the L1 chain from a channel timeout before its L1 origin.
+#[derive(Debug)]
+pub struct PipelineSimulation {
+    /// The simulated L1 chain.
+    pub l1: SimulatedL1,
+    /// The simulated L2 chain.
+    pub l2: SimulatedL2,
+    /// The pipeline.
+    pub pipeline: SimulationPipeline,
+    /// The attributes derived so far, in order, including those of L2 blocks dropped by resets.
+    pub derived: Vec<OpAttributesWithParent>,
+    /// The number of resets handled so far.
+    pub resets: usize,
+}
+
+impl PipelineSimulation {
+    /// Creates a new [PipelineSimulation] deriving from the given [SimulatedL1].
+    ///
+    /// The genesis of the rollup config is anchored to the genesis block of the L1 chain, and the
+    /// L2 genesis block is at its timestamp. The pipeline is reset to the L2 genesis block.
+    pub async fn new(mut cfg: RollupConfig, l1: SimulatedL1) -> Result<Self, PipelineErrorKind> {
+        let l1_genesis = l1.genesis();
+        cfg.genesis.l1 = l1_genesis.id();
+        cfg.genesis.l2_time = l1_genesis.timestamp;
+        let l2_genesis = L2BlockInfo {
+            block_info: BlockInfo {
+                hash: cfg.genesis.l2.hash,
+                number: cfg.genesis.l2.number,
+                parent_hash: B256::ZERO,
+                timestamp: l1_genesis.timestamp,
+            },
+            l1_origin: l1_genesis.id(),
+            seq_num: 0,
+        };
+        let l2 = SimulatedL2::new(l2_genesis, cfg.genesis.system_config.unwrap_or_default());
+
+        let cfg = Arc::new(cfg);
+        let dap = EthereumDataSource::new_from_parts(l1.clone(), l1.clone(), &cfg);
+        let builder = StatefulAttributesBuilder::new(cfg.clone(), l2.clone(), l1.clone());
+        let mut pipeline = PipelineBuilder::new()
+            .rollup_config(cfg)
+            .origin(l1_genesis)
+            .dap_source(dap)
+            .builder(builder)
+            .chain_provider(l1.clone())
+            .l2_chain_provider(l2.clone())
+            .build();
+        pipeline.reset(l2_genesis, l1_genesis).await?;
+
+        Ok(Self { l1, l2, pipeline, derived: Vec::new(), resets: 0 })
+    }
+
+    /// Returns the L2 safe head.
+    pub fn safe_head(&self) -> L2BlockInfo {
+        self.l2.safe_head()
+    }
+
+    /// Steps the pipeline once on the safe head, and executes the attributes it prepared, if any.
+    pub async fn step(&mut self) -> Result<StepResult, PipelineErrorKind> {
+        let result = self.pipeline.step(self.safe_head()).await;
+        if matches!(result, StepResult::PreparedAttributes) {
+            if let Some(attributes) = self.pipeline.next() {
+                self.execute(attributes)?;
+            }
+        }
+        Ok(result)
+    }
+
+    /// Steps the pipeline until it is idle, i.e. waiting for L1 blocks that are not visible at
+    /// the current time, and returns the attributes derived in the meantime.
+    ///
+    /// Resets are handled as they are requested. Any other non-temporary error is returned.
+    pub async fn run_until_idle(
+        &mut self,
+    ) -> Result<Vec<OpAttributesWithParent>, PipelineErrorKind> {
+        let start = self.derived.len();
+        loop {
+            match self.step().await? {
+                StepResult::PreparedAttributes | StepResult::AdvancedOrigin => {}
+                StepResult::StepFailed(PipelineErrorKind::Temporary(
+                    PipelineError::NotEnoughData,
+                )) => {}
+                StepResult::OriginAdvanceErr(PipelineErrorKind::Temporary(_)) |
+                StepResult::StepFailed(PipelineErrorKind::Temporary(_)) => break,
+                StepResult::OriginAdvanceErr(PipelineErrorKind::Reset(e)) |
+                StepResult::StepFailed(PipelineErrorKind::Reset(e)) => {
+                    debug!(target: "simulation", "Resetting the pipeline: {e}");
+                    self.reset().await?;
+                }
+                StepResult::OriginAdvanceErr(e) | StepResult::StepFailed(e) => return Err(e),
+            }
+        }
+        Ok(self.derived[start..].to_vec())
+    }
+
+    /// Drops the L2 blocks derived from L1 blocks that are no longer canonical, and resets the
+    /// pipeline to the remaining safe head.
+    ///
+    /// The L1 chain is re-read from a channel timeout before the L1 origin of the safe head, so
+    /// that the channels holding the batches of the next L2 blocks are read from their first frame.
+    async fn reset(&mut self) -> Result<(), PipelineErrorKind> {
+        self.resets += 1;
+        let l1 = &self.l1;
+        let safe_head = self.l2.rewind(|s| l1.block(s.number).is_some_and(|b| b.hash == s.hash));
+        let origin =
+            l1.block(safe_head.l1_origin.number).ok_or(PipelineError::MissingOrigin.crit())?;
+        let timeout = self.pipeline.rollup_config().channel_timeout(origin.timestamp);
+        let l1_origin = l1
+            .block(origin.number.saturating_sub(timeout))
+            .ok_or(PipelineError::MissingOrigin.crit())?;
+        self.pipeline.reset(safe_head, l1_origin).await
+    }
+
+    /// Executes the attributes, appending the L2 block built from them to the [SimulatedL2].
+    fn execute(&mut self, attributes: OpAttributesWithParent) -> Result<(), PipelineErrorKind> {
+        let parent = attributes.parent;
+        let timestamp = attributes.attributes.payload_attributes.timestamp;
+        let txs = attributes.attributes.transactions.as_deref().unwrap_or_default();
+        let l1_info = l1_block_info_from_encoded_tx(txs.first().map_or(&[][..], |tx| &tx[..]))
+            .map_err(|e| PipelineError::BadEncoding(e).crit())?;
+
+        let source = self.pipeline.origin().ok_or(PipelineError::MissingOrigin.crit())?;
+        let block = L2BlockInfo {
+            block_info: BlockInfo {
+                hash: l2_block_hash(parent.block_info.hash, timestamp, txs),
+                number: parent.block_info.number + 1,
+                parent_hash: parent.block_info.hash,
+                timestamp,
+            },
+            l1_origin: l1_info.id(),
+            seq_num: l1_info.sequence_number(),
+        };
+        self.l2.blocks.lock().push((block, source));
+        self.derived.push(attributes);
+        Ok(())
+    }
+}
+
+/// Encodes the batches into zlib compressed channel data.
+///
+/// The data is stored uncompressed in the zlib stream, which is valid for any zlib decoder.
+pub fn encode_channel(batches: &[Batch]) -> Vec<u8> {
+    let mut rlp = Vec::new();
+    for batch in batches {
+        let mut encoded = Vec::new();
+        batch.encode(&mut encoded).expect("batch encoding failed");
+        encoded.as_slice().encode(&mut rlp);
+    }
+
+    let mut data = vec![0x78, 0x01];
+    if rlp.is_empty() {
+        data.extend([0x01, 0x00, 0x00, 0xFF, 0xFF]);
+    }
+    let mut blocks = rlp.chunks(u16::MAX as usize).peekable();
+    while let Some(block) = blocks.next() {
+        let len = block.len() as u16;
+        data.push(blocks.peek().is_none() as u8);
+        data.extend(len.to_le_bytes());
+        data.extend((!len).to_le_bytes());
+        data.extend(block);
+    }
+    let (a, b) = rlp.iter().fold((1u32, 0u32), |(a, b), byte| {
+        let a = (a + *byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+    data.extend(((b << 16) | a).to_be_bytes());
+    data
+}
+
+/// Splits the channel data into the given number of frames of the channel with the given id.
+pub fn channel_frames(id: ChannelId, data: &[u8], count: usize) -> Vec<Frame> {
+    let size = data.len().div_ceil(count).max(1);
+    let mut frames = data
+        .chunks(size)
+        .enumerate()
+        .map(|(i, chunk)| Frame { id, number: i as u16, data: chunk.to_vec(), is_last: false })
+        .collect::<Vec<_>>();
+    if let Some(last) = frames.last_mut() {
+        last.is_last = true;
+    }
+    frames
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use maili_protocol::SingleBatch;
+    use op_alloy_genesis::ChainGenesis;
+
+    const BATCH_INBOX: Address = address!("ff00000000000000000000000000000000000010");
+
+    fn rollup_config(seq_window_size: u64, channel_timeout: u64) -> RollupConfig {
+        RollupConfig {
+            genesis: ChainGenesis {
+                system_config: Some(SystemConfig {
+                    batcher_address: SIMULATED_BATCHER,
+                    gas_limit: 30_000_000,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            block_time: 2,
+            max_sequencer_drift: 600,
+            seq_window_size,
+            channel_timeout,
+            batch_inbox_address: BATCH_INBOX,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the frames of a channel holding a batch with a user transaction for the first L2
+    /// block after the genesis.
+    fn first_batch_frames(sim: &PipelineSimulation) -> Vec<Frame> {
+        let batch = SingleBatch {
+            parent_hash: sim.safe_head().block_info.hash,
+            epoch_num: 0,
+            epoch_hash: sim.l1.genesis().hash,
+            timestamp: sim.safe_head().block_info.timestamp + 2,
+            transactions: vec![Bytes::from(vec![0x02, 0xAA])],
+        };
+        channel_frames([0xCC; 16], &encode_channel(&[Batch::Single(batch)]), 2)
+    }
+
+    fn timestamps(attributes: &[OpAttributesWithParent]) -> Vec<u64> {
+        attributes.iter().map(|a| a.attributes.payload_attributes.timestamp).collect()
+    }
+
+    fn tx_count(attributes: &OpAttributesWithParent) -> usize {
+        attributes.attributes.transactions.as_ref().map_or(0, Vec::len)
+    }
+
+    #[test]
+    fn test_virtual_clock() {
+        let clock = VirtualClock::new(10);
+        clock.advance(5);
+        assert_eq!(clock.now(), 15);
+        clock.advance_to(12);
+        assert_eq!(clock.now(), 15);
+        clock.advance_to(20);
+        assert_eq!(clock.now(), 20);
+    }
+
+    #[test]
+    fn test_batcher_transaction_signer() {
+        let frames = channel_frames([0xCC; 16], &encode_channel(&[]), 1);
+        for frames in [&[][..], &frames[..]] {
+            let tx = SimulatedL1::batcher_transaction(BATCH_INBOX, frames);
+            assert_eq!(tx.recover_signer().unwrap(), SIMULATED_BATCHER);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_simulated_l1_visibility_and_reorg() {
+        let mut l1 = SimulatedL1::new(100, 12);
+        let old = l1.push_empty_blocks(2);
+        assert_eq!((old.number, old.timestamp), (2, 124));
+        assert_eq!(l1.head(), l1.genesis());
+        assert!(l1.block_info_by_number(1).await.is_err());
+
+        l1.clock.advance(12);
+        assert_eq!(l1.block_info_by_number(1).await.unwrap(), l1.head());
+        assert!(l1.block_info_by_number(2).await.is_err());
+
+        l1.reorg(2);
+        let new = l1.push_empty_blocks(1);
+        assert_eq!((new.number, new.parent_hash), (2, old.parent_hash));
+        assert_ne!(new.hash, old.hash);
+        l1.advance_to_block(2);
+        assert_eq!(l1.block_info_by_number(2).await.unwrap(), new);
+        assert!(l1.header_by_hash(old.hash).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_simulation_sequencing_window_expiry() {
+        let l1 = SimulatedL1::new(0, 12);
+        l1.push_empty_blocks(4);
+        let mut sim = PipelineSimulation::new(rollup_config(2, 10), l1.clone()).await.unwrap();
+        assert!(sim.run_until_idle().await.unwrap().is_empty());
+
+        // The sequencing window of the first epoch is still open.
+        l1.advance_to_block(1);
+        assert!(sim.run_until_idle().await.unwrap().is_empty());
+
+        // Once the window expired, the epoch is filled with deposit-only blocks, up to the time of
+        // the next L1 origin.
+        l1.advance_to_block(2);
+        let derived = sim.run_until_idle().await.unwrap();
+        assert_eq!(timestamps(&derived), vec![2, 4, 6, 8, 10]);
+        assert!(derived.iter().all(|a| tx_count(a) == 1));
+        assert_eq!(sim.safe_head().l1_origin, l1.genesis().id());
+    }
+
+    #[tokio::test]
+    async fn test_simulation_channel_timeout() {
+        for (last_frame_block, timed_out) in [(2, false), (3, true)] {
+            let l1 = SimulatedL1::new(0, 12);
+            let mut sim = PipelineSimulation::new(rollup_config(4, 1), l1.clone()).await.unwrap();
+            let frames = first_batch_frames(&sim);
+            for number in 1..=4 {
+                let txs = match number {
+                    1 => vec![SimulatedL1::batcher_transaction(BATCH_INBOX, &frames[..1])],
+                    n if n == last_frame_block => {
+                        vec![SimulatedL1::batcher_transaction(BATCH_INBOX, &frames[1..])]
+                    }
+                    _ => Vec::new(),
+                };
+                l1.push_block(txs);
+            }
+
+            l1.advance_to_block(last_frame_block);
+            let derived = sim.run_until_idle().await.unwrap();
+            if timed_out {
+                // The channel timed out before its last frame, so the first epoch is only filled
+                // with deposit-only blocks once its sequencing window expired.
+                assert!(derived.is_empty());
+                l1.advance_to_block(4);
+                let derived = sim.run_until_idle().await.unwrap();
+                assert_eq!(timestamps(&derived)[0], 2);
+                assert!(derived.iter().all(|a| tx_count(a) == 1));
+            } else {
+                assert_eq!(timestamps(&derived), vec![2]);
+                assert_eq!(tx_count(&derived[0]), 2);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_simulation_reorg_mid_channel() {
+        let l1 = SimulatedL1::new(0, 12);
+        let mut sim = PipelineSimulation::new(rollup_config(4, 10), l1.clone()).await.unwrap();
+        let frames = first_batch_frames(&sim);
+
+        // The first frame of the channel is included in a block that is reorged out.
+        l1.push_block(vec![SimulatedL1::batcher_transaction(BATCH_INBOX, &frames[..1])]);
+        l1.advance_to_block(1);
+        assert!(sim.run_until_idle().await.unwrap().is_empty());
+
+        // The batcher resubmits the whole channel on the new fork.
+        l1.reorg(1);
+        l1.push_block(Vec::new());
+        let tip = l1.push_block(vec![SimulatedL1::batcher_transaction(BATCH_INBOX, &frames)]);
+        l1.advance_to_block(2);
+
+        let derived = sim.run_until_idle().await.unwrap();
+        assert_eq!(sim.resets, 1);
+        assert_eq!(timestamps(&derived), vec![2]);
+        assert_eq!(tx_count(&derived[0]), 2);
+        assert_eq!(sim.pipeline.origin(), Some(tip));
+    }
+}
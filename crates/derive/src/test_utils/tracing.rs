@@ -1,9 +1,9 @@
 //! This module contains a subscriber layer for `tracing-subscriber` that collects traces and their
-//! log levels.
+//! log levels, and optionally the spans that are opened.
 
 use alloc::{format, string::String, sync::Arc, vec::Vec};
 use spin::Mutex;
-use tracing::{Event, Level, Subscriber};
+use tracing::{span::Attributes, Event, Id, Level, Subscriber};
 use tracing_subscriber::{layer::Context, Layer};
 
 /// The storage for the collected traces.
@@ -36,12 +36,20 @@ impl TraceStorage {
 pub struct CollectingLayer {
     /// The storage for the collected traces.
     pub storage: TraceStorage,
+    /// The storage for the opened spans, by name and fields.
+    pub spans: Option<TraceStorage>,
 }
 
 impl CollectingLayer {
     /// Creates a new collecting layer with the specified storage.
     pub const fn new(storage: TraceStorage) -> Self {
-        Self { storage }
+        Self { storage, spans: None }
+    }
+
+    /// Also collects the opened spans into the specified storage.
+    pub fn with_spans(mut self, spans: TraceStorage) -> Self {
+        self.spans = Some(spans);
+        self
     }
 }
 
@@ -54,4 +62,12 @@ impl<S: Subscriber> Layer<S> for CollectingLayer {
         let mut storage = self.storage.0.lock();
         storage.push((level, message));
     }
+
+    fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+        if let Some(spans) = &self.spans {
+            let metadata = attrs.metadata();
+            let span = format!("{} {:?}", metadata.name(), attrs.values());
+            spans.0.lock().push((*metadata.level(), span));
+        }
+    }
 }
@@ -1,10 +1,17 @@
 //! Contains traits that describe the functionality of various data sources used in the derivation
 //! pipeline's stages.
 
-use crate::{errors::PipelineErrorKind, types::PipelineResult};
+use crate::{
+    errors::{AltDAProviderError, BlobProviderError, EigenDAProviderError, PipelineErrorKind},
+    sources::{Commitment, EigenDACommitment},
+    types::{IngestDecision, PipelineResult},
+};
 use alloc::{boxed::Box, fmt::Debug, string::ToString, vec::Vec};
+use alloy_consensus::Header;
 use alloy_eips::eip4844::{Blob, IndexedBlobHash};
-use alloy_primitives::Bytes;
+#[cfg(feature = "celestia")]
+use alloy_primitives::FixedBytes;
+use alloy_primitives::{Address, Bytes, B256};
 use async_trait::async_trait;
 use core::fmt::Display;
 use maili_protocol::BlockInfo;
@@ -23,16 +30,161 @@ pub trait BlobProvider {
     ) -> Result<Vec<Box<Blob>>, Self::Error>;
 }
 
+#[async_trait]
+impl<T: BlobProvider + Send + ?Sized> BlobProvider for &mut T {
+    type Error = T::Error;
+
+    async fn get_blobs(
+        &mut self,
+        block_ref: &BlockInfo,
+        blob_hashes: &[IndexedBlobHash],
+    ) -> Result<Vec<Box<Blob>>, Self::Error> {
+        (**self).get_blobs(block_ref, blob_hashes).await
+    }
+}
+
+#[async_trait]
+impl<T: BlobProvider + Send + ?Sized> BlobProvider for Box<T> {
+    type Error = T::Error;
+
+    async fn get_blobs(
+        &mut self,
+        block_ref: &BlockInfo,
+        blob_hashes: &[IndexedBlobHash],
+    ) -> Result<Vec<Box<Blob>>, Self::Error> {
+        (**self).get_blobs(block_ref, blob_hashes).await
+    }
+}
+
+/// Fetches the parent beacon block root of an L1 block from the beacon chain.
+///
+/// Used as a fallback by the [StatefulAttributesBuilder] when the L1 [ChainProvider] returns
+/// post-Cancun headers without the `parentBeaconBlockRoot` field.
+///
+/// [StatefulAttributesBuilder]: crate::attributes::StatefulAttributesBuilder
+/// [ChainProvider]: crate::traits::ChainProvider
+#[async_trait]
+pub trait BeaconRootProvider: Debug + Send + Sync {
+    /// Returns the parent root of the beacon block that contains the given L1 execution block.
+    async fn parent_beacon_block_root(&self, l1_header: &Header)
+        -> Result<B256, BlobProviderError>;
+}
+
+/// Fetches the input committed to by an alt-DA [Commitment], for the [AltDADataSource].
+///
+/// [AltDADataSource]: crate::sources::AltDADataSource
+#[async_trait]
+pub trait AltDAProvider: Debug + Send + Sync {
+    /// Fetches the input committed to by the given commitment.
+    ///
+    /// Returns [AltDAProviderError::InvalidCommitment] if the commitment does not verify, in
+    /// which case it is dropped, and [AltDAProviderError::Unavailable] if the input cannot be
+    /// retrieved (yet), in which case derivation waits for it.
+    async fn get_input(&mut self, commitment: &Commitment) -> Result<Bytes, AltDAProviderError>;
+}
+
+/// Fetches blobs from a Celestia node, for the [CelestiaDataSource].
+///
+/// [CelestiaDataSource]: crate::sources::CelestiaDataSource
+#[cfg(feature = "celestia")]
+#[async_trait]
+pub trait CelestiaProvider {
+    /// The error type for the [CelestiaProvider].
+    type Error: Display + ToString + Into<PipelineErrorKind>;
+
+    /// Fetches the data of the blob with the given share commitment, included in the namespace
+    /// at the given Celestia block height.
+    async fn blob_get(
+        &mut self,
+        height: u64,
+        namespace: FixedBytes<29>,
+        commitment: B256,
+    ) -> Result<Bytes, Self::Error>;
+}
+
+/// Fetches blobs from an EigenDA proxy or disperser, for the [EigenDADataSource].
+///
+/// [EigenDADataSource]: crate::sources::EigenDADataSource
+#[async_trait]
+pub trait EigenDAProvider {
+    /// Fetches the payload of the blob certified by the given commitment.
+    ///
+    /// Returns [EigenDAProviderError::InvalidCert] if the certificate does not verify, in which
+    /// case the commitment is dropped, and [EigenDAProviderError::Unavailable] if the blob cannot
+    /// be retrieved (yet), in which case derivation waits for it.
+    ///
+    /// [EigenDAProviderError::InvalidCert]: crate::errors::EigenDAProviderError::InvalidCert
+    /// [EigenDAProviderError::Unavailable]: crate::errors::EigenDAProviderError::Unavailable
+    async fn get_blob(
+        &mut self,
+        commitment: &EigenDACommitment,
+    ) -> Result<Bytes, EigenDAProviderError>;
+}
+
 /// Describes the functionality of a data source that can provide data availability information.
 #[async_trait]
 pub trait DataAvailabilityProvider {
     /// The item type of the data iterator.
     type Item: Send + Sync + Debug + Into<Bytes>;
 
-    /// Returns the next data for the given [BlockInfo].
+    /// Returns the next data for the given [BlockInfo], sent by the given batcher address.
     /// Returns a `PipelineError::Eof` if there is no more data for the given block ref.
-    async fn next(&mut self, block_ref: &BlockInfo) -> PipelineResult<Self::Item>;
+    ///
+    /// The batcher address is the one of the system config at `block_ref`, so that a batcher
+    /// rotation takes effect in the L1 block that emits the config update, and data sent by the
+    /// previous batcher is rejected from that block on.
+    async fn next(
+        &mut self,
+        block_ref: &BlockInfo,
+        batcher_address: Address,
+    ) -> PipelineResult<Self::Item>;
 
     /// Clears the data source for the next block ref.
     fn clear(&mut self);
 }
+
+#[async_trait]
+impl<T: DataAvailabilityProvider + Send + ?Sized> DataAvailabilityProvider for &mut T {
+    type Item = T::Item;
+
+    async fn next(
+        &mut self,
+        block_ref: &BlockInfo,
+        batcher_address: Address,
+    ) -> PipelineResult<Self::Item> {
+        (**self).next(block_ref, batcher_address).await
+    }
+
+    fn clear(&mut self) {
+        (**self).clear()
+    }
+}
+
+#[async_trait]
+impl<T: DataAvailabilityProvider + Send + ?Sized> DataAvailabilityProvider for Box<T> {
+    type Item = T::Item;
+
+    async fn next(
+        &mut self,
+        block_ref: &BlockInfo,
+        batcher_address: Address,
+    ) -> PipelineResult<Self::Item> {
+        (**self).next(block_ref, batcher_address).await
+    }
+
+    fn clear(&mut self) {
+        (**self).clear()
+    }
+}
+
+/// An observer of the decisions a data source makes on candidate batcher transactions.
+///
+/// The [CalldataSource] and [BlobSource] invoke the observer for every transaction of the L1
+/// blocks they load, so that operators can verify their batcher data is picked up.
+///
+/// [CalldataSource]: crate::sources::CalldataSource
+/// [BlobSource]: crate::sources::BlobSource
+pub trait DataIngestObserver: Debug + Send + Sync {
+    /// Called with the decision on the transaction with the given hash in the given L1 block.
+    fn on_transaction(&self, block_ref: &BlockInfo, tx_hash: B256, decision: IngestDecision);
+}
@@ -2,19 +2,24 @@
 //! pipeline.
 
 mod pipeline;
-pub use pipeline::Pipeline;
+pub use pipeline::{Pipeline, PRODUCE_YIELD_BUDGET};
 
 mod providers;
-pub use providers::{BatchValidationProviderDerive, ChainProvider, L2ChainProvider};
+pub use providers::{BatchValidationProviderDerive, ChainProvider, L1BlockTag, L2ChainProvider};
 
 mod attributes;
 pub use attributes::{AttributesBuilder, AttributesProvider, NextAttributes};
 
 mod data_sources;
-pub use data_sources::{BlobProvider, DataAvailabilityProvider};
+#[cfg(feature = "celestia")]
+pub use data_sources::CelestiaProvider;
+pub use data_sources::{
+    AltDAProvider, BeaconRootProvider, BlobProvider, DataAvailabilityProvider, DataIngestObserver,
+    EigenDAProvider,
+};
 
 mod reset;
 pub use reset::ResetProvider;
 
 mod stages;
-pub use stages::{OriginAdvancer, OriginProvider, SignalReceiver};
+pub use stages::{OriginAdvancer, OriginProvider, PipelineStage, SignalReceiver, StageSnapshot};
@@ -2,12 +2,24 @@
 
 use alloc::boxed::Box;
 use async_trait::async_trait;
-use core::iter::Iterator;
-use maili_protocol::L2BlockInfo;
+use core::{
+    future::Future,
+    iter::Iterator,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use maili_protocol::{BlockInfo, L2BlockInfo};
 use op_alloy_genesis::{RollupConfig, SystemConfig};
 use op_alloy_rpc_types_engine::OpAttributesWithParent;
 
-use crate::{errors::PipelineErrorKind, traits::OriginProvider, types::StepResult};
+use crate::{
+    errors::{PipelineError, PipelineErrorKind},
+    traits::OriginProvider,
+    types::{PipelineResult, ProduceResult, StepResult},
+};
+
+/// The number of steps [Pipeline::produce_next] takes before yielding to the executor.
+pub const PRODUCE_YIELD_BUDGET: usize = 64;
 
 /// This trait defines the interface for interacting with the derivation pipeline.
 #[async_trait]
@@ -18,6 +30,70 @@ pub trait Pipeline: OriginProvider + Iterator<Item = OpAttributesWithParent> {
     /// Attempts to progress the pipeline.
     async fn step(&mut self, cursor: L2BlockInfo) -> StepResult;
 
+    /// Pauses the pipeline.
+    ///
+    /// A paused pipeline does not step its stages: [Pipeline::step] fails with a temporary
+    /// [PipelineError::Paused] until [Pipeline::resume] is called. The stages keep their state and
+    /// provider connections, and can still be reset, so that a consumer can halt derivation e.g.
+    /// during maintenance or while handling a reorg.
+    fn pause(&mut self);
+
+    /// Resumes a paused pipeline.
+    fn resume(&mut self);
+
+    /// Returns `true` if the pipeline is paused.
+    fn is_paused(&self) -> bool;
+
+    /// Steps the pipeline until the next [OpAttributesWithParent] are produced, the origin can
+    /// no longer be advanced, the pipeline is paused, or a reset or critical error occurs.
+    ///
+    /// Temporary step errors, such as a stage waiting on more frames, are retried. The pipeline
+    /// cooperatively yields to the executor every [PRODUCE_YIELD_BUDGET] steps, so that
+    /// consumers can drive it without busy-looping other tasks.
+    async fn produce_next(&mut self, cursor: L2BlockInfo) -> ProduceResult
+    where
+        Self: Send,
+    {
+        let mut budget = PRODUCE_YIELD_BUDGET;
+        loop {
+            if let Some(attributes) = self.next() {
+                return ProduceResult::Attributes(attributes);
+            }
+
+            match self.step(cursor).await {
+                StepResult::PreparedAttributes | StepResult::AdvancedOrigin => {}
+                StepResult::OriginAdvanceErr(e @ PipelineErrorKind::Temporary(_)) => {
+                    return ProduceResult::Exhausted(e);
+                }
+                StepResult::StepFailed(PipelineErrorKind::Temporary(PipelineError::Paused)) => {
+                    return ProduceResult::Paused;
+                }
+                StepResult::StepFailed(PipelineErrorKind::Temporary(_)) => {}
+                StepResult::OriginAdvanceErr(e) | StepResult::StepFailed(e) => {
+                    return ProduceResult::Failed(e);
+                }
+            }
+
+            budget -= 1;
+            if budget == 0 {
+                YieldNow(false).await;
+                budget = PRODUCE_YIELD_BUDGET;
+            }
+        }
+    }
+
+    /// Resets the pipeline to the given L2 safe head and L1 origin.
+    ///
+    /// The reset walks the reset handlers of all stages, clearing their buffered channels and
+    /// batches, and drops any prepared [OpAttributesWithParent]. The stages are re-seeded with the
+    /// [SystemConfig] of the L2 safe head, so that consumers can handle L1 reorgs and restarts
+    /// without rebuilding the pipeline.
+    async fn reset(
+        &mut self,
+        l2_safe_head: L2BlockInfo,
+        l1_origin: BlockInfo,
+    ) -> PipelineResult<()>;
+
     /// Returns the rollup config.
     fn rollup_config(&self) -> &RollupConfig;
 
@@ -27,3 +103,20 @@ pub trait Pipeline: OriginProvider + Iterator<Item = OpAttributesWithParent> {
         number: u64,
     ) -> Result<SystemConfig, PipelineErrorKind>;
 }
+
+/// A future that yields to the executor once before completing.
+#[derive(Debug)]
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            return Poll::Ready(());
+        }
+        self.0 = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
@@ -3,12 +3,21 @@
 use crate::errors::PipelineErrorKind;
 use alloc::{boxed::Box, string::ToString, sync::Arc, vec::Vec};
 use alloy_consensus::{Header, Receipt, TxEnvelope};
-use alloy_primitives::B256;
+use alloy_primitives::{Address, B256};
 use async_trait::async_trait;
 use core::fmt::Display;
-use maili_protocol::{BatchValidationProvider, BlockInfo};
+use maili_protocol::{BatchValidationProvider, BlockInfo, L2BlockInfo};
 use op_alloy_genesis::{RollupConfig, SystemConfig};
 
+/// An L1 block label that a [ChainProvider] may resolve to a block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum L1BlockTag {
+    /// The latest block that is considered safe from reorgs, i.e. justified.
+    Safe,
+    /// The latest finalized block.
+    Finalized,
+}
+
 /// Describes the functionality of a data source that can provide information from the blockchain.
 #[async_trait]
 pub trait ChainProvider {
@@ -22,10 +31,42 @@ pub trait ChainProvider {
     /// source.
     async fn block_info_by_number(&mut self, number: u64) -> Result<BlockInfo, Self::Error>;
 
+    /// Returns the block currently labelled with the given [L1BlockTag].
+    ///
+    /// The default implementation returns [None], for data sources that do not track L1 head
+    /// labels, such as those backed by a preimage oracle.
+    async fn block_info_by_tag(
+        &mut self,
+        _tag: L1BlockTag,
+    ) -> Result<Option<BlockInfo>, Self::Error> {
+        Ok(None)
+    }
+
     /// Returns all receipts in the block with the given hash, or an error if the block does not
     /// exist in the data source.
     async fn receipts_by_hash(&mut self, hash: B256) -> Result<Vec<Receipt>, Self::Error>;
 
+    /// Returns all receipts in the block with the given hash, retaining only the logs emitted by
+    /// `address`.
+    ///
+    /// Implementations must return one receipt per transaction, in block order, including the
+    /// receipts left without logs, so that the receipt at an index belongs to the transaction at
+    /// the same index of the block.
+    ///
+    /// The default implementation filters the result of [ChainProvider::receipts_by_hash].
+    /// Providers backed by an RPC can override it to push the filter down to the node, e.g. with
+    /// `eth_getLogs`, rather than fetching every receipt of busy L1 blocks. Log indices are not
+    /// preserved, so the filtered receipts must not be used to derive user deposits.
+    async fn filtered_receipts_by_hash(
+        &mut self,
+        hash: B256,
+        address: Address,
+    ) -> Result<Vec<Receipt>, Self::Error> {
+        let mut receipts = self.receipts_by_hash(hash).await?;
+        receipts.iter_mut().for_each(|r| r.logs.retain(|log| log.address == address));
+        Ok(receipts)
+    }
+
     /// Returns the [BlockInfo] and list of [TxEnvelope]s from the given block hash.
     async fn block_info_and_transactions_by_hash(
         &mut self,
@@ -33,6 +74,61 @@ pub trait ChainProvider {
     ) -> Result<(BlockInfo, Vec<TxEnvelope>), Self::Error>;
 }
 
+/// Implements [ChainProvider] for a pointer type by forwarding every method, including the
+/// provided ones, to the provider that it dereferences to.
+macro_rules! forward_chain_provider {
+    ($ty:ty) => {
+        #[async_trait]
+        impl<T: ChainProvider + Send + ?Sized> ChainProvider for $ty {
+            type Error = T::Error;
+
+            async fn header_by_hash(&mut self, hash: B256) -> Result<Header, Self::Error> {
+                (**self).header_by_hash(hash).await
+            }
+
+            async fn block_info_by_number(
+                &mut self,
+                number: u64,
+            ) -> Result<BlockInfo, Self::Error> {
+                (**self).block_info_by_number(number).await
+            }
+
+            async fn block_info_by_tag(
+                &mut self,
+                tag: L1BlockTag,
+            ) -> Result<Option<BlockInfo>, Self::Error> {
+                (**self).block_info_by_tag(tag).await
+            }
+
+            async fn receipts_by_hash(&mut self, hash: B256) -> Result<Vec<Receipt>, Self::Error> {
+                (**self).receipts_by_hash(hash).await
+            }
+
+            async fn filtered_receipts_by_hash(
+                &mut self,
+                hash: B256,
+                address: Address,
+            ) -> Result<Vec<Receipt>, Self::Error> {
+                (**self).filtered_receipts_by_hash(hash, address).await
+            }
+
+            async fn block_info_and_transactions_by_hash(
+                &mut self,
+                hash: B256,
+            ) -> Result<(BlockInfo, Vec<TxEnvelope>), Self::Error> {
+                (**self).block_info_and_transactions_by_hash(hash).await
+            }
+        }
+    };
+}
+
+// Mutable references and boxes, including `Box<dyn ChainProvider<Error = E> + Send>`, are chain
+// providers themselves, so that stages can borrow a provider or be handed a type-erased one.
+// Shared ownership is not supported, since the methods take `&mut self`: providers are shared by
+// cloning them, or by wrapping them in a `SharedChainProvider`.
+forward_chain_provider!(&mut T);
+forward_chain_provider!(Box<T>);
+
 /// Describes the functionality of a data source that fetches safe blocks.
 #[async_trait]
 pub trait L2ChainProvider: BatchValidationProviderDerive {
@@ -45,6 +141,25 @@ pub trait L2ChainProvider: BatchValidationProviderDerive {
         number: u64,
         rollup_config: Arc<RollupConfig>,
     ) -> Result<SystemConfig, <Self as L2ChainProvider>::Error>;
+
+    /// Returns the [L2BlockInfo]s of the L2 blocks `start..=end`, in ascending order.
+    ///
+    /// The default implementation fetches each block with
+    /// [BatchValidationProvider::l2_block_info_by_number]. Providers that can fetch a range more
+    /// efficiently, e.g. with a batched RPC request or a single walk back from a known head,
+    /// should override it, since validating a span batch that overlaps the safe head touches every
+    /// block of the overlap.
+    async fn l2_block_infos_by_range(
+        &mut self,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<L2BlockInfo>, <Self as BatchValidationProvider>::Error> {
+        let mut infos = Vec::with_capacity(end.saturating_sub(start).saturating_add(1) as usize);
+        for number in start..=end {
+            infos.push(self.l2_block_info_by_number(number).await?);
+        }
+        Ok(infos)
+    }
 }
 
 /// A super-trait for [BatchValidationProvider] that binds `Self::Error` to have a conversion into
@@ -59,3 +174,84 @@ where
     <T as BatchValidationProvider>::Error: Into<PipelineErrorKind>,
 {
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{TestChainProvider, TestL2ChainProvider, TestProviderError};
+    use alloc::vec;
+    use alloy_primitives::Log;
+
+    /// Fetches a block by number and the safe block through the given [ChainProvider].
+    async fn fetch_blocks<P: ChainProvider + Send>(
+        mut provider: P,
+    ) -> (Result<BlockInfo, P::Error>, Result<Option<BlockInfo>, P::Error>) {
+        let block = provider.block_info_by_number(1).await;
+        (block, provider.block_info_by_tag(L1BlockTag::Safe).await)
+    }
+
+    #[tokio::test]
+    async fn test_chain_provider_forwarding() {
+        let block = BlockInfo { number: 1, hash: B256::with_last_byte(1), ..Default::default() };
+        let tagged = BlockInfo { number: 2, ..Default::default() };
+        let mut provider = TestChainProvider::default();
+        provider.insert_block(1, block);
+        provider.insert_tagged_block(L1BlockTag::Safe, tagged);
+
+        let (by_number, by_tag) = fetch_blocks(&mut provider).await;
+        assert_eq!(by_number.unwrap(), block);
+        assert_eq!(by_tag.unwrap(), Some(tagged));
+
+        let mut boxed: Box<dyn ChainProvider<Error = TestProviderError> + Send> =
+            Box::new(provider);
+        assert_eq!(boxed.block_info_by_number(1).await.unwrap(), block);
+        assert_eq!(boxed.block_info_by_tag(L1BlockTag::Safe).await.unwrap(), Some(tagged));
+        assert_eq!(
+            boxed.block_info_by_number(2).await.unwrap_err(),
+            TestProviderError::BlockNotFound
+        );
+    }
+
+    #[tokio::test]
+    async fn test_filtered_receipts_by_hash() {
+        let address = Address::repeat_byte(0xAA);
+        let log = |address| Log::new_unchecked(address, vec![], Default::default());
+        let receipt =
+            |cumulative_gas_used, logs| Receipt { cumulative_gas_used, logs, ..Default::default() };
+        let mut provider = TestChainProvider::default();
+        provider.insert_receipts(
+            B256::ZERO,
+            vec![
+                receipt(1, vec![log(Address::ZERO)]),
+                receipt(2, vec![log(Address::ZERO), log(address)]),
+                receipt(3, vec![]),
+            ],
+        );
+
+        // One receipt per transaction is returned in block order, without the other logs.
+        let receipts = provider.filtered_receipts_by_hash(B256::ZERO, address).await.unwrap();
+        assert_eq!(
+            receipts,
+            [receipt(1, vec![]), receipt(2, vec![log(address)]), receipt(3, vec![])]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_l2_block_infos_by_range() {
+        let blocks = (0..5)
+            .map(|number| L2BlockInfo {
+                block_info: BlockInfo { number, ..Default::default() },
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        let mut provider = TestL2ChainProvider { blocks: blocks.clone(), ..Default::default() };
+
+        assert_eq!(provider.l2_block_infos_by_range(1, 3).await.unwrap(), blocks[1..=3]);
+        assert_eq!(provider.l2_block_infos_by_range(2, 2).await.unwrap(), vec![blocks[2]]);
+        assert!(provider.l2_block_infos_by_range(3, 2).await.unwrap().is_empty());
+        assert_eq!(
+            provider.l2_block_infos_by_range(3, 5).await,
+            Err(TestProviderError::BlockNotFound)
+        );
+    }
+}
@@ -1,12 +1,26 @@
 //! This module contains common traits for stages within the derivation pipeline.
+//!
+//! Every stage of the pipeline is generic over its previous stage, bounded by the stage's input
+//! trait (e.g. [FrameQueueProvider] for the [FrameQueue]) and the [PipelineStage] traits below.
+//! A custom stage, such as a data source multiplexer, drops into the stack by implementing the
+//! input trait of the stage that follows it and the [PipelineStage] traits.
+//!
+//! [FrameQueueProvider]: crate::stages::FrameQueueProvider
+//! [FrameQueue]: crate::stages::FrameQueue
 
 use alloc::boxed::Box;
 use async_trait::async_trait;
+use core::fmt::Debug;
 use maili_protocol::BlockInfo;
 
-use crate::types::{PipelineResult, Signal};
+use crate::types::{PipelineResult, PipelineSnapshot, Signal};
 
 /// Providers a way for the pipeline to accept a signal from the driver.
+///
+/// This is the reset handler of a stage. On a [Signal::Reset] or [Signal::Activation], a stage
+/// must forward the signal to its previous stage first, then clear any buffered data, so that
+/// the signal walks the stack from the lowest stage up. A [Signal::FlushChannel] is forwarded down
+/// to the stage that buffers the active channel, which drops it.
 #[async_trait]
 pub trait SignalReceiver {
     /// Receives a signal from the driver.
@@ -14,15 +28,43 @@ pub trait SignalReceiver {
 }
 
 /// Provides a method for accessing the pipeline's current L1 origin.
+///
+/// Stages that do not track an origin themselves return the origin of their previous stage, so
+/// that the origin of the top-level stage is the origin of the [L1Traversal] stage.
+///
+/// [L1Traversal]: crate::stages::L1Traversal
 pub trait OriginProvider {
-    /// Returns the optional L1 [BlockInfo] origin.
+    /// Returns the optional L1 [BlockInfo] origin, or [None] if the pipeline has not been reset
+    /// to an origin yet.
     fn origin(&self) -> Option<BlockInfo>;
 }
 
 /// Defines a trait for advancing the L1 origin of the pipeline.
+///
+/// Stages forward the call to their previous stage, and the lowest stage moves to the next L1
+/// block. Advancing must not drop data that was buffered from earlier origins.
 #[async_trait]
 pub trait OriginAdvancer {
     /// Advances the internal state of the lowest stage to the next l1 origin.
     /// This method is the equivalent of the reference implementation `advance_l1_block`.
+    ///
+    /// Returns a [PipelineError::Eof] error if the next L1 block is not available yet.
+    ///
+    /// [PipelineError::Eof]: crate::errors::PipelineError::Eof
     async fn advance_origin(&mut self) -> PipelineResult<()>;
 }
+
+/// The traits shared by every stage of the pipeline, implemented for all types implementing
+/// [OriginProvider], [OriginAdvancer] and [SignalReceiver].
+pub trait PipelineStage: OriginProvider + OriginAdvancer + SignalReceiver + Debug + Send {}
+
+impl<T> PipelineStage for T where T: OriginProvider + OriginAdvancer + SignalReceiver + Debug + Send {}
+
+/// Records the buffered state of a stage into a [PipelineSnapshot].
+///
+/// Stages record their own state and then forward the call to their previous stage, so that
+/// the snapshot of the top-level stage covers the whole stack.
+pub trait StageSnapshot {
+    /// Records the stage's state into the given [PipelineSnapshot].
+    fn snapshot(&self, snapshot: &mut PipelineSnapshot);
+}
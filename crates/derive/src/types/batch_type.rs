@@ -0,0 +1,121 @@
+//! Contains the [BatchType], a typed batch type byte.
+
+use alloy_primitives::hex::{self, FromHex};
+use core::{fmt, str::FromStr};
+use maili_protocol::{SINGLE_BATCH_TYPE, SPAN_BATCH_TYPE};
+
+/// The type byte that prefixes an encoded batch, displayed and parsed by name.
+///
+/// Known types are rendered as `single` and `span`, and unknown types as a `0x`-prefixed hex
+/// byte, so that the type of a rejected batch is readable in logs and debug output.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct BatchType(pub u8);
+
+impl BatchType {
+    /// The type of a [maili_protocol::SingleBatch].
+    pub const SINGLE: Self = Self(SINGLE_BATCH_TYPE);
+
+    /// The type of a [maili_protocol::SpanBatch].
+    pub const SPAN: Self = Self(SPAN_BATCH_TYPE);
+
+    /// Returns the raw type byte.
+    pub const fn as_u8(&self) -> u8 {
+        self.0
+    }
+
+    /// Returns true if the type is a single or span batch.
+    pub const fn is_known(&self) -> bool {
+        matches!(*self, Self::SINGLE | Self::SPAN)
+    }
+}
+
+impl From<u8> for BatchType {
+    fn from(batch_type: u8) -> Self {
+        Self(batch_type)
+    }
+}
+
+impl From<BatchType> for u8 {
+    fn from(batch_type: BatchType) -> Self {
+        batch_type.0
+    }
+}
+
+impl PartialEq<u8> for BatchType {
+    fn eq(&self, other: &u8) -> bool {
+        &self.0 == other
+    }
+}
+
+impl fmt::Display for BatchType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::SINGLE => f.write_str("single"),
+            Self::SPAN => f.write_str("span"),
+            Self(batch_type) => write!(f, "{}", hex::encode_prefixed([batch_type])),
+        }
+    }
+}
+
+impl fmt::Debug for BatchType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl FromStr for BatchType {
+    type Err = hex::FromHexError;
+
+    /// Parses `single`, `span`, or a single hex byte, with or without a `0x` prefix.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "single" => Ok(Self::SINGLE),
+            "span" => Ok(Self::SPAN),
+            _ => <[u8; 1]>::from_hex(s).map(|[batch_type]| Self(batch_type)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_batch_type_display_parse() {
+        for (batch_type, s) in
+            [(BatchType::SINGLE, "single"), (BatchType::SPAN, "span"), (BatchType(0x7f), "0x7f")]
+        {
+            assert_eq!(batch_type.to_string(), s);
+            assert_eq!(alloc::format!("{batch_type:?}"), s);
+            assert_eq!(s.parse::<BatchType>().unwrap(), batch_type);
+        }
+        assert_eq!("01".parse::<BatchType>().unwrap(), BatchType::SPAN);
+    }
+
+    #[test]
+    fn test_batch_type_parse_invalid() {
+        assert_eq!("0x0102".parse::<BatchType>(), Err(hex::FromHexError::InvalidStringLength));
+        assert_eq!(
+            "zz".parse::<BatchType>(),
+            Err(hex::FromHexError::InvalidHexCharacter { c: 'z', index: 0 })
+        );
+    }
+
+    #[test]
+    fn test_batch_type_is_known() {
+        assert!(BatchType::SINGLE.is_known());
+        assert!(BatchType::SPAN.is_known());
+        assert!(!BatchType(0x02).is_known());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_batch_type_serde() {
+        let json = serde_json::to_string(&BatchType::SPAN).unwrap();
+        assert_eq!(json, "1");
+        assert_eq!(serde_json::from_str::<BatchType>(&json).unwrap(), BatchType::SPAN);
+    }
+}
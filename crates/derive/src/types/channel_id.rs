@@ -0,0 +1,104 @@
+//! Contains the [ChannelId], a typed channel ID.
+
+use alloy_primitives::hex::{self, FromHex};
+use core::{fmt, str::FromStr};
+use maili_protocol::CHANNEL_ID_LENGTH;
+
+/// The ID of a channel, displayed and parsed as a `0x`-prefixed hex string.
+///
+/// This wraps the raw ID of [maili_protocol::Frame] and [maili_protocol::Channel], so that IDs
+/// are rendered as hex in logs and debug output, and cannot be confused with other 16 byte values.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct ChannelId(
+    #[cfg_attr(feature = "serde", serde(with = "alloy_primitives::hex"))]
+    pub  [u8; CHANNEL_ID_LENGTH],
+);
+
+impl ChannelId {
+    /// Returns the raw bytes of the channel ID.
+    pub const fn as_bytes(&self) -> &[u8; CHANNEL_ID_LENGTH] {
+        &self.0
+    }
+}
+
+impl From<[u8; CHANNEL_ID_LENGTH]> for ChannelId {
+    fn from(id: [u8; CHANNEL_ID_LENGTH]) -> Self {
+        Self(id)
+    }
+}
+
+impl From<ChannelId> for [u8; CHANNEL_ID_LENGTH] {
+    fn from(id: ChannelId) -> Self {
+        id.0
+    }
+}
+
+impl PartialEq<[u8; CHANNEL_ID_LENGTH]> for ChannelId {
+    fn eq(&self, other: &[u8; CHANNEL_ID_LENGTH]) -> bool {
+        &self.0 == other
+    }
+}
+
+impl AsRef<[u8]> for ChannelId {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Display for ChannelId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode_prefixed(self.0))
+    }
+}
+
+impl fmt::Debug for ChannelId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl FromStr for ChannelId {
+    type Err = hex::FromHexError;
+
+    /// Parses a hex channel ID of exactly 16 bytes, with or without a `0x` prefix.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        <[u8; CHANNEL_ID_LENGTH]>::from_hex(s).map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_channel_id_display_parse() {
+        let id = ChannelId([0xAB; 16]);
+        let s = id.to_string();
+        assert_eq!(s, "0xabababababababababababababababab");
+        assert_eq!(alloc::format!("{id:?}"), s);
+        assert_eq!(s.parse::<ChannelId>().unwrap(), id);
+        assert_eq!("abababababababababababababababab".parse::<ChannelId>().unwrap(), id);
+    }
+
+    #[test]
+    fn test_channel_id_parse_invalid() {
+        assert_eq!("0xabab".parse::<ChannelId>(), Err(hex::FromHexError::InvalidStringLength));
+        assert_eq!(
+            "0xzzababababababababababababababab".parse::<ChannelId>(),
+            Err(hex::FromHexError::InvalidHexCharacter { c: 'z', index: 0 })
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_channel_id_serde() {
+        let id = ChannelId([0x01; 16]);
+        let json = serde_json::to_string(&id).unwrap();
+        assert_eq!(json, "\"0x01010101010101010101010101010101\"");
+        assert_eq!(serde_json::from_str::<ChannelId>(&json).unwrap(), id);
+        assert!(serde_json::from_str::<ChannelId>("\"0x0101\"").is_err());
+    }
+}
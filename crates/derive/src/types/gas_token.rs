@@ -0,0 +1,182 @@
+//! Contains the [GasPayingToken] of custom gas token chains.
+//!
+//! The L1 `SystemConfig` contract of a custom gas token chain sets the token through the
+//! `OptimismPortal`, which emits a system deposit from the [DEPOSITOR_ACCOUNT] calling
+//! `setGasPayingToken(address,uint8,bytes32,bytes32)` on the [L1_BLOCK_ADDRESS] predeploy. The
+//! deposit is derived like any other user deposit, and [GasPayingToken::from_receipts] decodes the
+//! token it sets.
+
+use crate::{attributes::decode_deposits, errors::PipelineEncodingError};
+use alloc::vec::Vec;
+use alloy_consensus::Receipt;
+use alloy_primitives::{address, b256, Address, TxKind, B256};
+use op_alloy_consensus::TxDeposit;
+
+/// The sentinel address of ether as the gas paying token.
+pub const ETHER_TOKEN_ADDRESS: Address = address!("EeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE");
+
+/// The account that system deposits are sent from.
+pub const DEPOSITOR_ACCOUNT: Address = address!("DeaDDEaDDeAdDeAdDEAdDEaddeAddEAdDEAd0001");
+
+/// The address of the `L1Block` predeploy.
+pub const L1_BLOCK_ADDRESS: Address = address!("4200000000000000000000000000000000000015");
+
+/// The selector of the `setGasPayingToken(address,uint8,bytes32,bytes32)` function of the
+/// `L1Block` predeploy.
+const SET_GAS_PAYING_TOKEN_SELECTOR: [u8; 4] = [0x71, 0xcf, 0xaa, 0x3f];
+
+/// The token that pays for gas on L2, as set by the L1 `SystemConfig` contract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct GasPayingToken {
+    /// The L1 address of the token, or the [ETHER_TOKEN_ADDRESS].
+    pub address: Address,
+    /// The decimals of the token.
+    pub decimals: u8,
+    /// The name of the token, as a left-aligned `bytes32` string.
+    pub name: B256,
+    /// The symbol of the token, as a left-aligned `bytes32` string.
+    pub symbol: B256,
+}
+
+impl Default for GasPayingToken {
+    fn default() -> Self {
+        Self::ETHER
+    }
+}
+
+impl GasPayingToken {
+    /// Ether, the gas paying token of chains without a custom gas token.
+    pub const ETHER: Self = Self {
+        address: ETHER_TOKEN_ADDRESS,
+        decimals: 18,
+        name: b256!("4574686572000000000000000000000000000000000000000000000000000000"),
+        symbol: b256!("4554480000000000000000000000000000000000000000000000000000000000"),
+    };
+
+    /// Returns `true` if the token is a custom gas token rather than ether.
+    pub fn is_custom(&self) -> bool {
+        self.address != ETHER_TOKEN_ADDRESS
+    }
+
+    /// Decodes the token set by a `setGasPayingToken` system deposit, returning [None] if the
+    /// deposit is not one.
+    pub fn from_deposit(tx: &TxDeposit) -> Option<Self> {
+        if tx.from != DEPOSITOR_ACCOUNT || tx.to != TxKind::Call(L1_BLOCK_ADDRESS) {
+            return None;
+        }
+        let args = tx.input.strip_prefix(&SET_GAS_PAYING_TOKEN_SELECTOR)?;
+        if args.len() != 128 ||
+            args[..12].iter().any(|b| *b != 0) ||
+            args[32..63].iter().any(|b| *b != 0)
+        {
+            return None;
+        }
+        Some(Self {
+            address: Address::from_slice(&args[12..32]),
+            decimals: args[63],
+            name: B256::from_slice(&args[64..96]),
+            symbol: B256::from_slice(&args[96..128]),
+        })
+    }
+
+    /// Decodes the token set by the deposits of the L1 block with the given receipts, returning
+    /// [None] if the block does not set the token. If the block sets the token more than once, the
+    /// last token is returned.
+    pub fn from_receipts(
+        block_hash: B256,
+        receipts: &[Receipt],
+        deposit_contract: Address,
+    ) -> Result<Option<Self>, PipelineEncodingError> {
+        let deposits = decode_deposits(block_hash, receipts, deposit_contract)?;
+        Ok(deposits.iter().rev().find_map(Self::from_deposit))
+    }
+
+    /// Encodes the `setGasPayingToken` call that sets the token.
+    pub fn set_gas_paying_token_calldata(&self) -> Vec<u8> {
+        let mut calldata = Vec::with_capacity(4 + 128);
+        calldata.extend_from_slice(&SET_GAS_PAYING_TOKEN_SELECTOR);
+        calldata.extend_from_slice(self.address.into_word().as_slice());
+        calldata.extend_from_slice(B256::with_last_byte(self.decimals).as_slice());
+        calldata.extend_from_slice(self.name.as_slice());
+        calldata.extend_from_slice(self.symbol.as_slice());
+        calldata
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use alloy_consensus::Eip658Value;
+    use alloy_primitives::{Bytes, Log, LogData, U256};
+    use maili_protocol::DEPOSIT_EVENT_ABI_HASH;
+
+    const DEPOSIT_CONTRACT: Address = address!("1111111111111111111111111111111111111111");
+
+    const TOKEN: GasPayingToken = GasPayingToken {
+        address: address!("2222222222222222222222222222222222222222"),
+        decimals: 18,
+        name: b256!("546f6b656e000000000000000000000000000000000000000000000000000000"),
+        symbol: b256!("544b4e0000000000000000000000000000000000000000000000000000000000"),
+    };
+
+    /// Returns a `TransactionDeposited` log of a deposit without value from `from` to `to`.
+    fn deposit_log(from: Address, to: Address, calldata: &[u8]) -> Log {
+        let mut opaque = vec![0u8; 73];
+        opaque[64..72].copy_from_slice(&200_000u64.to_be_bytes());
+        opaque.extend_from_slice(calldata);
+        let mut data = [
+            U256::from(32).to_be_bytes::<32>().to_vec(),
+            U256::from(opaque.len()).to_be_bytes::<32>().to_vec(),
+            opaque,
+        ]
+        .concat();
+        data.resize(data.len().div_ceil(32) * 32, 0);
+        Log {
+            address: DEPOSIT_CONTRACT,
+            data: LogData::new_unchecked(
+                vec![DEPOSIT_EVENT_ABI_HASH, from.into_word(), to.into_word(), B256::ZERO],
+                Bytes::from(data),
+            ),
+        }
+    }
+
+    fn receipt(logs: Vec<Log>) -> Receipt {
+        Receipt { status: Eip658Value::Eip658(true), logs, ..Default::default() }
+    }
+
+    #[test]
+    fn test_gas_paying_token_ether() {
+        assert_eq!(GasPayingToken::default(), GasPayingToken::ETHER);
+        assert!(!GasPayingToken::ETHER.is_custom());
+        assert!(TOKEN.is_custom());
+    }
+
+    #[test]
+    fn test_gas_paying_token_from_receipts() {
+        let calldata = TOKEN.set_gas_paying_token_calldata();
+        let receipts = vec![receipt(vec![
+            deposit_log(DEPOSITOR_ACCOUNT, L1_BLOCK_ADDRESS, &calldata),
+            // A user deposit with the same calldata does not set the token.
+            deposit_log(TOKEN.address, L1_BLOCK_ADDRESS, &calldata),
+        ])];
+        assert_eq!(
+            GasPayingToken::from_receipts(B256::ZERO, &receipts, DEPOSIT_CONTRACT),
+            Ok(Some(TOKEN))
+        );
+        assert_eq!(GasPayingToken::from_receipts(B256::ZERO, &receipts, Address::ZERO), Ok(None));
+    }
+
+    #[test]
+    fn test_gas_paying_token_from_deposit_rejects_malformed_calldata() {
+        // The decimals word has dirty high-order bytes.
+        let mut calldata = TOKEN.set_gas_paying_token_calldata();
+        calldata[36] = 0x01;
+        let receipts =
+            vec![receipt(vec![deposit_log(DEPOSITOR_ACCOUNT, L1_BLOCK_ADDRESS, &calldata)])];
+        let deposits = decode_deposits(B256::ZERO, &receipts, DEPOSIT_CONTRACT).unwrap();
+        assert_eq!(GasPayingToken::from_deposit(&deposits[0]), None);
+    }
+}
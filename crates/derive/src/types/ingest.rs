@@ -0,0 +1,35 @@
+//! Contains the decisions reported to a [DataIngestObserver].
+//!
+//! [DataIngestObserver]: crate::traits::DataIngestObserver
+
+/// The decision of a data source on a candidate batcher transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub enum IngestDecision {
+    /// The transaction data was accepted, and is passed on to the frame queue.
+    Accepted,
+    /// The transaction was rejected.
+    Rejected(IngestRejection),
+}
+
+/// The reason a candidate batcher transaction was rejected by a data source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub enum IngestRejection {
+    /// The transaction type cannot carry batcher data.
+    UnsupportedTxType,
+    /// The transaction is not sent to the batch inbox address.
+    WrongInbox,
+    /// The transaction is not signed by the batcher address of the current system config.
+    WrongSender,
+    /// The transaction data does not start with the derivation version byte.
+    BadVersion,
+}
+
+impl From<IngestRejection> for IngestDecision {
+    fn from(reason: IngestRejection) -> Self {
+        Self::Rejected(reason)
+    }
+}
@@ -1,7 +1,30 @@
 //! Primitive types for `kona-derive`.
 
+mod batch_type;
+pub use batch_type::BatchType;
+
+mod channel_id;
+pub use channel_id::ChannelId;
+
+mod ingest;
+pub use ingest::{IngestDecision, IngestRejection};
+
 mod results;
-pub use results::{PipelineResult, StepResult};
+pub use results::{PipelineResult, ProduceResult, StepResult};
+
+#[cfg(feature = "serde")]
+mod remote;
+#[cfg(feature = "serde")]
+pub use remote::{FrameDef, SingleBatchDef, SpanBatchElementDef};
 
 mod signals;
 pub use signals::{ActivationSignal, ResetSignal, Signal};
+
+mod snapshot;
+pub use snapshot::{ChannelSnapshot, PipelineSnapshot};
+
+mod gas_token;
+pub use gas_token::{GasPayingToken, DEPOSITOR_ACCOUNT, ETHER_TOKEN_ADDRESS, L1_BLOCK_ADDRESS};
+
+mod system_config;
+pub use system_config::{unpack_ecotone_scalar, update_system_config, SystemConfigUpdate};
@@ -0,0 +1,89 @@
+//! Serde definitions for the [maili_protocol] wire types, which do not implement serde
+//! themselves.
+//!
+//! The definitions are used with the `with` attribute, e.g.
+//! `#[serde(with = "kona_derive::types::FrameDef")]` on a [Frame] field.
+
+use alloc::vec::Vec;
+use alloy_primitives::{BlockHash, Bytes};
+use maili_protocol::{ChannelId, Frame, SingleBatch, SpanBatchElement};
+
+/// The serde definition of a [Frame]. Channel IDs and frame data are hex encoded.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[serde(remote = "Frame", rename_all = "camelCase")]
+pub struct FrameDef {
+    /// The channel ID.
+    #[serde(with = "alloy_primitives::hex")]
+    pub id: ChannelId,
+    /// The frame number.
+    pub number: u16,
+    /// The frame data.
+    #[serde(with = "alloy_primitives::hex")]
+    pub data: Vec<u8>,
+    /// Whether or not the frame is the last in the channel.
+    pub is_last: bool,
+}
+
+/// The serde definition of a [SingleBatch].
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[serde(remote = "SingleBatch", rename_all = "camelCase")]
+pub struct SingleBatchDef {
+    /// Block hash of the previous L2 block.
+    pub parent_hash: BlockHash,
+    /// The batch epoch number.
+    pub epoch_num: u64,
+    /// The block hash of the first L1 block in the epoch.
+    pub epoch_hash: BlockHash,
+    /// The L2 block timestamp of this batch.
+    pub timestamp: u64,
+    /// The L2 block transactions in this batch.
+    pub transactions: Vec<Bytes>,
+}
+
+/// The serde definition of a [SpanBatchElement], an L2 block of a span batch.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[serde(remote = "SpanBatchElement", rename_all = "camelCase")]
+pub struct SpanBatchElementDef {
+    /// The epoch number of the L1 block.
+    pub epoch_num: u64,
+    /// The timestamp of the L2 block.
+    pub timestamp: u64,
+    /// The transactions in the L2 block.
+    pub transactions: Vec<Bytes>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use alloy_primitives::B256;
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Artifacts {
+        #[serde(with = "FrameDef")]
+        frame: Frame,
+        #[serde(with = "SingleBatchDef")]
+        batch: SingleBatch,
+    }
+
+    #[test]
+    fn test_remote_roundtrip() {
+        let artifacts = Artifacts {
+            frame: Frame::new([0xAB; 16], 1, vec![0x01, 0x02], true),
+            batch: SingleBatch {
+                parent_hash: B256::with_last_byte(1),
+                epoch_num: 2,
+                timestamp: 3,
+                transactions: vec![Bytes::from_static(&[0x02, 0x03])],
+                ..Default::default()
+            },
+        };
+        let json = serde_json::to_value(&artifacts).unwrap();
+        assert_eq!(json["frame"]["id"], "0xabababababababababababababababab");
+        assert_eq!(json["frame"]["data"], "0x0102");
+        assert_eq!(json["frame"]["isLast"], true);
+        assert_eq!(json["batch"]["epochNum"], 2);
+        assert_eq!(json["batch"]["transactions"][0], "0x0203");
+        assert_eq!(serde_json::from_value::<Artifacts>(json).unwrap(), artifacts);
+    }
+}
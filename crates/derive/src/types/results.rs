@@ -1,6 +1,7 @@
 //! Result types for the `kona-derive` pipeline.
 
 use crate::errors::PipelineErrorKind;
+use op_alloy_rpc_types_engine::OpAttributesWithParent;
 
 /// A result type for the derivation pipeline stages.
 pub type PipelineResult<T> = Result<T, PipelineErrorKind>;
@@ -17,3 +18,20 @@ pub enum StepResult {
     /// Step failed.
     StepFailed(PipelineErrorKind),
 }
+
+/// The outcome of [Pipeline::produce_next].
+///
+/// [Pipeline::produce_next]: crate::traits::Pipeline::produce_next
+#[derive(Debug, PartialEq)]
+pub enum ProduceResult {
+    /// The next attributes were produced.
+    Attributes(OpAttributesWithParent),
+    /// The pipeline could not advance its origin, and needs more L1 data to make progress.
+    Exhausted(PipelineErrorKind),
+    /// The pipeline failed with a reset or critical error.
+    Failed(PipelineErrorKind),
+    /// The pipeline is paused, see [Pipeline::pause].
+    ///
+    /// [Pipeline::pause]: crate::traits::Pipeline::pause
+    Paused,
+}
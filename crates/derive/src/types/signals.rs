@@ -9,6 +9,8 @@ use op_alloy_genesis::SystemConfig;
 
 /// A signal to send to the pipeline.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 #[allow(clippy::large_enum_variant)]
 pub enum Signal {
     /// Reset the pipeline.
@@ -32,6 +34,8 @@ impl Signal {
 
 /// A pipeline reset signal.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct ResetSignal {
     /// The L2 safe head to reset to.
     pub l2_safe_head: L2BlockInfo,
@@ -55,6 +59,8 @@ impl ResetSignal {
 
 /// A pipeline hardfork activation signal.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct ActivationSignal {
     /// The L2 safe head to reset to.
     pub l2_safe_head: L2BlockInfo,
@@ -0,0 +1,56 @@
+//! Contains the [PipelineSnapshot], a debug view of the derivation pipeline's state.
+
+use super::{ChannelId, GasPayingToken};
+use alloc::vec::Vec;
+use alloy_primitives::B256;
+use maili_protocol::BlockInfo;
+
+/// A debug view of the state of a derivation pipeline, e.g. to dump on a critical error or to
+/// expose through a status endpoint.
+///
+/// The snapshot is taken with [DerivationPipeline::snapshot], and filled in by each stage's
+/// [StageSnapshot] implementation.
+///
+/// [DerivationPipeline::snapshot]: crate::pipeline::DerivationPipeline::snapshot
+/// [StageSnapshot]: crate::traits::StageSnapshot
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct PipelineSnapshot {
+    /// The current L1 origin of the pipeline.
+    pub origin: Option<BlockInfo>,
+    /// The number of parsed frames buffered in the frame queue.
+    pub frames: usize,
+    /// The channels being assembled, in the order they were opened.
+    pub channels: Vec<ChannelSnapshot>,
+    /// The number of decoded batches buffered by the batch stages.
+    pub batches: usize,
+    /// The number of prepared attributes not yet consumed.
+    pub prepared: usize,
+    /// The hash of the last derived attributes, see [DerivationPipeline::snapshot].
+    ///
+    /// [DerivationPipeline::snapshot]: crate::pipeline::DerivationPipeline::snapshot
+    pub last_attributes_hash: Option<B256>,
+    /// The custom gas paying token tracked by the [L1Traversal] stage, or `None` if gas is paid in
+    /// ether.
+    ///
+    /// [L1Traversal]: crate::stages::L1Traversal
+    pub gas_paying_token: Option<GasPayingToken>,
+}
+
+/// A channel being assembled, as seen in a [PipelineSnapshot].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct ChannelSnapshot {
+    /// The channel ID.
+    pub id: ChannelId,
+    /// The size of the channel's frame data.
+    pub size: usize,
+    /// The number of the L1 block the channel was opened in.
+    pub open_block: u64,
+    /// The number of L1 blocks since the channel was opened.
+    pub age: u64,
+    /// Whether all frames of the channel have been received.
+    pub ready: bool,
+}
@@ -0,0 +1,542 @@
+//! Typed [SystemConfig] updates decoded from L1 `ConfigUpdate` event logs.
+//!
+//! The L1 `SystemConfig` contract emits a `ConfigUpdate` event every time one of the
+//! derivation-relevant parameters changes:
+//!
+//! ```text
+//! event ConfigUpdate(
+//!    uint256 indexed version,
+//!    UpdateType indexed updateType,
+//!    bytes data
+//! );
+//! ```
+//!
+//! [SystemConfigUpdate] decodes every known `UpdateType` into a typed value that can be inspected
+//! and then applied to a [SystemConfig].
+
+use alloc::vec::Vec;
+use alloy_consensus::{Eip658Value, Receipt};
+use alloy_primitives::{Address, Log, U256};
+use op_alloy_genesis::{
+    BatcherUpdateError, EIP1559UpdateError, GasConfigUpdateError, GasLimitUpdateError,
+    LogProcessingError, RollupConfig, SystemConfig, SystemConfigUpdateError,
+    SystemConfigUpdateType, CONFIG_UPDATE_EVENT_VERSION_0, CONFIG_UPDATE_TOPIC,
+};
+
+/// The Ecotone scalar version byte for the packed `(blob_base_fee_scalar, base_fee_scalar)` format.
+const ECOTONE_SCALAR_VERSION_1: u8 = 1;
+
+/// A decoded `ConfigUpdate` event emitted by the L1 `SystemConfig` contract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub enum SystemConfigUpdate {
+    /// The batcher address was updated.
+    Batcher(Address),
+    /// The fee overhead and scalar were updated.
+    ///
+    /// Post-Ecotone, the `scalar` is a versioned, packed encoding of the base fee scalar and the
+    /// blob base fee scalar, and the `overhead` is ignored.
+    GasConfig {
+        /// The fee overhead.
+        overhead: U256,
+        /// The (possibly versioned) fee scalar.
+        scalar: U256,
+    },
+    /// The L2 block gas limit was updated.
+    GasLimit(u64),
+    /// The unsafe block signer was updated.
+    ///
+    /// This update does not affect derivation, and is only surfaced for consumers that track
+    /// the P2P configuration. [None] if the payload could not be decoded.
+    UnsafeBlockSigner(Option<Address>),
+    /// The Holocene EIP-1559 parameters were updated.
+    Eip1559 {
+        /// The EIP-1559 base fee max change denominator.
+        denominator: u32,
+        /// The EIP-1559 elasticity multiplier.
+        elasticity: u32,
+    },
+}
+
+impl SystemConfigUpdate {
+    /// Decodes a [SystemConfigUpdate] from a `ConfigUpdate` [Log].
+    ///
+    /// The caller is responsible for checking that the log was emitted by the L1 system config
+    /// contract.
+    pub fn try_from_log(log: &Log) -> Result<Self, SystemConfigUpdateError> {
+        let topics = log.topics();
+        if topics.len() < 3 {
+            return Err(SystemConfigUpdateError::LogProcessing(
+                LogProcessingError::InvalidTopicLen(topics.len()),
+            ));
+        }
+        if topics[0] != CONFIG_UPDATE_TOPIC {
+            return Err(SystemConfigUpdateError::LogProcessing(LogProcessingError::InvalidTopic));
+        }
+
+        let version = topics[1];
+        if version != CONFIG_UPDATE_EVENT_VERSION_0 {
+            return Err(SystemConfigUpdateError::LogProcessing(
+                LogProcessingError::UnsupportedVersion(version),
+            ));
+        }
+
+        let Some(update_type) = decode_u64_word(topics[2].as_slice()) else {
+            return Err(SystemConfigUpdateError::LogProcessing(
+                LogProcessingError::UpdateTypeDecodingError,
+            ));
+        };
+        let data = log.data.data.as_ref();
+
+        match SystemConfigUpdateType::try_from(update_type)? {
+            SystemConfigUpdateType::Batcher => Self::decode_address(data)
+                .map(Self::Batcher)
+                .map_err(SystemConfigUpdateError::Batcher),
+            SystemConfigUpdateType::GasConfig => {
+                Self::decode_gas_config(data).map_err(SystemConfigUpdateError::GasConfig)
+            }
+            SystemConfigUpdateType::GasLimit => {
+                Self::decode_gas_limit(data).map_err(SystemConfigUpdateError::GasLimit)
+            }
+            SystemConfigUpdateType::UnsafeBlockSigner => {
+                Ok(Self::UnsafeBlockSigner(Self::decode_address(data).ok()))
+            }
+            SystemConfigUpdateType::Eip1559 => {
+                Self::decode_eip1559(data).map_err(SystemConfigUpdateError::Eip1559)
+            }
+        }
+    }
+
+    /// Returns the [SystemConfigUpdateType] of the update.
+    pub const fn update_type(&self) -> SystemConfigUpdateType {
+        match self {
+            Self::Batcher(_) => SystemConfigUpdateType::Batcher,
+            Self::GasConfig { .. } => SystemConfigUpdateType::GasConfig,
+            Self::GasLimit(_) => SystemConfigUpdateType::GasLimit,
+            Self::UnsafeBlockSigner(_) => SystemConfigUpdateType::UnsafeBlockSigner,
+            Self::Eip1559 { .. } => SystemConfigUpdateType::Eip1559,
+        }
+    }
+
+    /// Applies the update to the given [SystemConfig].
+    ///
+    /// Post-Ecotone, gas config updates with an unrecognized scalar encoding are ignored and
+    /// the previous scalar is retained. Valid Ecotone scalars are additionally unpacked into
+    /// [SystemConfig::base_fee_scalar] and [SystemConfig::blob_base_fee_scalar].
+    pub fn apply(&self, config: &mut SystemConfig, ecotone_active: bool) {
+        match *self {
+            Self::Batcher(address) => config.batcher_address = address,
+            Self::GasConfig { overhead, scalar } => {
+                if !ecotone_active {
+                    config.overhead = overhead;
+                    config.scalar = scalar;
+                    return;
+                }
+
+                let scalar_bytes = scalar.to_be_bytes::<32>();
+                if RollupConfig::check_ecotone_l1_system_config_scalar(scalar_bytes).is_err() {
+                    warn!(target: "system-config", "Ignoring invalid ecotone scalar: {scalar}");
+                    return;
+                }
+                let (blob_base_fee_scalar, base_fee_scalar) = unpack_ecotone_scalar(scalar_bytes);
+                config.overhead = U256::ZERO;
+                config.scalar = scalar;
+                config.base_fee_scalar = Some(base_fee_scalar as u64);
+                config.blob_base_fee_scalar = Some(blob_base_fee_scalar as u64);
+            }
+            Self::GasLimit(gas_limit) => config.gas_limit = gas_limit,
+            Self::Eip1559 { denominator, elasticity } => {
+                config.eip1559_denominator = Some(denominator);
+                config.eip1559_elasticity = Some(elasticity);
+            }
+            // Ignored in derivation.
+            Self::UnsafeBlockSigner(_) => {}
+        }
+    }
+
+    /// Decodes all [SystemConfigUpdate]s emitted by the `l1_system_config_address` in the
+    /// given receipts, in log order. Logs within failed receipts are skipped.
+    pub fn from_receipts(
+        receipts: &[Receipt],
+        l1_system_config_address: Address,
+    ) -> Result<Vec<Self>, SystemConfigUpdateError> {
+        receipts
+            .iter()
+            .filter(|r| r.status != Eip658Value::Eip658(false))
+            .flat_map(|r| r.logs.iter())
+            .filter(|log| {
+                log.address == l1_system_config_address &&
+                    log.topics().first() == Some(&CONFIG_UPDATE_TOPIC)
+            })
+            .map(Self::try_from_log)
+            .collect()
+    }
+
+    /// Decodes an address payload, as used by the batcher and unsafe block signer updates.
+    fn decode_address(data: &[u8]) -> Result<Address, BatcherUpdateError> {
+        if data.len() != 96 {
+            return Err(BatcherUpdateError::InvalidDataLen(data.len()));
+        }
+        let pointer =
+            decode_u64_word(&data[0..32]).ok_or(BatcherUpdateError::PointerDecodingError)?;
+        if pointer != 32 {
+            return Err(BatcherUpdateError::InvalidDataPointer(pointer));
+        }
+        let length =
+            decode_u64_word(&data[32..64]).ok_or(BatcherUpdateError::LengthDecodingError)?;
+        if length != 32 {
+            return Err(BatcherUpdateError::InvalidDataLength(length));
+        }
+        decode_address_word(&data[64..96]).ok_or(BatcherUpdateError::BatcherAddressDecodingError)
+    }
+
+    /// Decodes a gas config (overhead and scalar) update.
+    fn decode_gas_config(data: &[u8]) -> Result<Self, GasConfigUpdateError> {
+        if data.len() != 128 {
+            return Err(GasConfigUpdateError::InvalidDataLen(data.len()));
+        }
+        let pointer =
+            decode_u64_word(&data[0..32]).ok_or(GasConfigUpdateError::PointerDecodingError)?;
+        if pointer != 32 {
+            return Err(GasConfigUpdateError::InvalidDataPointer(pointer));
+        }
+        let length =
+            decode_u64_word(&data[32..64]).ok_or(GasConfigUpdateError::LengthDecodingError)?;
+        if length != 64 {
+            return Err(GasConfigUpdateError::InvalidDataLength(length));
+        }
+        let overhead = U256::from_be_slice(&data[64..96]);
+        let scalar = U256::from_be_slice(&data[96..128]);
+        Ok(Self::GasConfig { overhead, scalar })
+    }
+
+    /// Decodes a gas limit update.
+    fn decode_gas_limit(data: &[u8]) -> Result<Self, GasLimitUpdateError> {
+        if data.len() != 96 {
+            return Err(GasLimitUpdateError::InvalidDataLen(data.len()));
+        }
+        let pointer =
+            decode_u64_word(&data[0..32]).ok_or(GasLimitUpdateError::PointerDecodingError)?;
+        if pointer != 32 {
+            return Err(GasLimitUpdateError::InvalidDataPointer(pointer));
+        }
+        let length =
+            decode_u64_word(&data[32..64]).ok_or(GasLimitUpdateError::LengthDecodingError)?;
+        if length != 32 {
+            return Err(GasLimitUpdateError::InvalidDataLength(length));
+        }
+        // The gas limit is a `uint256` on L1, and is saturated into a `u64`.
+        let gas_limit = U256::from_be_slice(&data[64..96]).saturating_to::<u64>();
+        Ok(Self::GasLimit(gas_limit))
+    }
+
+    /// Decodes an EIP-1559 parameters update.
+    fn decode_eip1559(data: &[u8]) -> Result<Self, EIP1559UpdateError> {
+        if data.len() != 96 {
+            return Err(EIP1559UpdateError::InvalidDataLen(data.len()));
+        }
+        let pointer =
+            decode_u64_word(&data[0..32]).ok_or(EIP1559UpdateError::PointerDecodingError)?;
+        if pointer != 32 {
+            return Err(EIP1559UpdateError::InvalidDataPointer(pointer));
+        }
+        let length =
+            decode_u64_word(&data[32..64]).ok_or(EIP1559UpdateError::LengthDecodingError)?;
+        if length != 32 {
+            return Err(EIP1559UpdateError::InvalidDataLength(length));
+        }
+        let params =
+            decode_u64_word(&data[64..96]).ok_or(EIP1559UpdateError::EIP1559DecodingError)?;
+        Ok(Self::Eip1559 { denominator: (params >> 32) as u32, elasticity: params as u32 })
+    }
+}
+
+/// Applies all [SystemConfigUpdate]s found in the receipts to the [SystemConfig], returning the
+/// applied updates in order.
+///
+/// All updates are decoded before any is applied, so the [SystemConfig] is left untouched if
+/// any of the logs fail to decode.
+pub fn update_system_config(
+    config: &mut SystemConfig,
+    receipts: &[Receipt],
+    l1_system_config_address: Address,
+    ecotone_active: bool,
+) -> Result<Vec<SystemConfigUpdate>, SystemConfigUpdateError> {
+    let updates = SystemConfigUpdate::from_receipts(receipts, l1_system_config_address)?;
+    updates.iter().for_each(|update| update.apply(config, ecotone_active));
+    Ok(updates)
+}
+
+/// Unpacks an Ecotone scalar into its `(blob_base_fee_scalar, base_fee_scalar)` components.
+///
+/// Version 0 scalars only carry the base fee scalar, in which case the blob base fee scalar is
+/// zero.
+pub const fn unpack_ecotone_scalar(scalar: [u8; 32]) -> (u32, u32) {
+    let base_fee_scalar = u32::from_be_bytes([scalar[28], scalar[29], scalar[30], scalar[31]]);
+    let blob_base_fee_scalar = if scalar[0] == ECOTONE_SCALAR_VERSION_1 {
+        u32::from_be_bytes([scalar[24], scalar[25], scalar[26], scalar[27]])
+    } else {
+        0
+    };
+    (blob_base_fee_scalar, base_fee_scalar)
+}
+
+/// Decodes an ABI `uint64` word, rejecting words with dirty high-order bytes.
+fn decode_u64_word(word: &[u8]) -> Option<u64> {
+    if word.len() != 32 || word[..24].iter().any(|b| *b != 0) {
+        return None;
+    }
+    Some(u64::from_be_bytes(word[24..].try_into().ok()?))
+}
+
+/// Decodes an ABI `address` word, rejecting words with dirty high-order bytes.
+fn decode_address_word(word: &[u8]) -> Option<Address> {
+    if word.len() != 32 || word[..12].iter().any(|b| *b != 0) {
+        return None;
+    }
+    Some(Address::from_slice(&word[12..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use alloy_primitives::{address, hex, Bytes, LogData, B256};
+
+    const L1_SYS_CONFIG_ADDR: Address = address!("1337000000000000000000000000000000000000");
+
+    fn update_log(update_type: u64, data: Bytes) -> Log {
+        Log {
+            address: L1_SYS_CONFIG_ADDR,
+            data: LogData::new_unchecked(
+                vec![
+                    CONFIG_UPDATE_TOPIC,
+                    CONFIG_UPDATE_EVENT_VERSION_0,
+                    B256::left_padding_from(&update_type.to_be_bytes()),
+                ],
+                data,
+            ),
+        }
+    }
+
+    #[test]
+    fn test_decode_batcher_update() {
+        let log = update_log(0, hex!("00000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000020000000000000000000000000000000000000000000000000000000000000beef").into());
+        let update = SystemConfigUpdate::try_from_log(&log).unwrap();
+        assert_eq!(
+            update,
+            SystemConfigUpdate::Batcher(address!("000000000000000000000000000000000000bEEF"))
+        );
+        assert_eq!(update.update_type(), SystemConfigUpdateType::Batcher);
+    }
+
+    #[test]
+    fn test_decode_batcher_update_dirty_address() {
+        let log = update_log(0, hex!("00000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000020ff0000000000000000000000000000000000000000000000000000000000beef").into());
+        let err = SystemConfigUpdate::try_from_log(&log).unwrap_err();
+        assert_eq!(
+            err,
+            SystemConfigUpdateError::Batcher(BatcherUpdateError::BatcherAddressDecodingError)
+        );
+    }
+
+    #[test]
+    fn test_decode_gas_config_update() {
+        let log = update_log(1, hex!("00000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000040000000000000000000000000000000000000000000000000000000000000babe000000000000000000000000000000000000000000000000000000000000beef").into());
+        let update = SystemConfigUpdate::try_from_log(&log).unwrap();
+        assert_eq!(
+            update,
+            SystemConfigUpdate::GasConfig {
+                overhead: U256::from(0xbabe),
+                scalar: U256::from(0xbeef)
+            }
+        );
+
+        let mut config = SystemConfig::default();
+        update.apply(&mut config, false);
+        assert_eq!(config.overhead, U256::from(0xbabe));
+        assert_eq!(config.scalar, U256::from(0xbeef));
+        assert_eq!(config.base_fee_scalar, None);
+    }
+
+    #[test]
+    fn test_decode_gas_config_invalid_length() {
+        let log = update_log(1, Bytes::from(vec![0u8; 96]));
+        let err = SystemConfigUpdate::try_from_log(&log).unwrap_err();
+        assert_eq!(
+            err,
+            SystemConfigUpdateError::GasConfig(GasConfigUpdateError::InvalidDataLen(96))
+        );
+    }
+
+    #[test]
+    fn test_apply_gas_config_ecotone_v1_scalar() {
+        let mut scalar = [0u8; 32];
+        scalar[0] = ECOTONE_SCALAR_VERSION_1;
+        scalar[24..28].copy_from_slice(&810949u32.to_be_bytes());
+        scalar[28..32].copy_from_slice(&1368u32.to_be_bytes());
+        let update = SystemConfigUpdate::GasConfig {
+            overhead: U256::from(0xbabe),
+            scalar: U256::from_be_bytes(scalar),
+        };
+
+        let mut config = SystemConfig { overhead: U256::from(1), ..Default::default() };
+        update.apply(&mut config, true);
+        assert_eq!(config.overhead, U256::ZERO);
+        assert_eq!(config.scalar, U256::from_be_bytes(scalar));
+        assert_eq!(config.base_fee_scalar, Some(1368));
+        assert_eq!(config.blob_base_fee_scalar, Some(810949));
+    }
+
+    #[test]
+    fn test_apply_gas_config_ecotone_v0_scalar() {
+        let update = SystemConfigUpdate::GasConfig {
+            overhead: U256::from(0xbabe),
+            scalar: U256::from(0xbeef),
+        };
+        let mut config = SystemConfig::default();
+        update.apply(&mut config, true);
+        assert_eq!(config.overhead, U256::ZERO);
+        assert_eq!(config.base_fee_scalar, Some(0xbeef));
+        assert_eq!(config.blob_base_fee_scalar, Some(0));
+    }
+
+    #[test]
+    fn test_apply_gas_config_ecotone_invalid_scalar_ignored() {
+        let mut scalar = [0u8; 32];
+        scalar[0] = 2;
+        let update = SystemConfigUpdate::GasConfig {
+            overhead: U256::from(0xbabe),
+            scalar: U256::from_be_bytes(scalar),
+        };
+        let mut config = SystemConfig { scalar: U256::from(7), ..Default::default() };
+        update.apply(&mut config, true);
+        assert_eq!(config.scalar, U256::from(7));
+        assert_eq!(config.base_fee_scalar, None);
+    }
+
+    #[test]
+    fn test_decode_gas_limit_update() {
+        let log = update_log(2, hex!("00000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000020000000000000000000000000000000000000000000000000000000000000beef").into());
+        let update = SystemConfigUpdate::try_from_log(&log).unwrap();
+        assert_eq!(update, SystemConfigUpdate::GasLimit(0xbeef));
+        let mut config = SystemConfig::default();
+        update.apply(&mut config, false);
+        assert_eq!(config.gas_limit, 0xbeef);
+    }
+
+    #[test]
+    fn test_decode_unsafe_block_signer_update() {
+        let log = update_log(3, hex!("00000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000020000000000000000000000000000000000000000000000000000000000000beef").into());
+        let update = SystemConfigUpdate::try_from_log(&log).unwrap();
+        assert_eq!(
+            update,
+            SystemConfigUpdate::UnsafeBlockSigner(Some(address!(
+                "000000000000000000000000000000000000bEEF"
+            )))
+        );
+
+        // The update is a no-op for derivation.
+        let mut config = SystemConfig::default();
+        update.apply(&mut config, true);
+        assert_eq!(config, SystemConfig::default());
+
+        // Malformed unsafe block signer payloads are not an error.
+        let log = update_log(3, Bytes::default());
+        let update = SystemConfigUpdate::try_from_log(&log).unwrap();
+        assert_eq!(update, SystemConfigUpdate::UnsafeBlockSigner(None));
+    }
+
+    #[test]
+    fn test_decode_eip1559_update() {
+        let log = update_log(4, hex!("000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000babe0000beef").into());
+        let update = SystemConfigUpdate::try_from_log(&log).unwrap();
+        assert_eq!(update, SystemConfigUpdate::Eip1559 { denominator: 0xbabe, elasticity: 0xbeef });
+        let mut config = SystemConfig::default();
+        update.apply(&mut config, true);
+        assert_eq!(config.eip1559_denominator, Some(0xbabe));
+        assert_eq!(config.eip1559_elasticity, Some(0xbeef));
+    }
+
+    #[test]
+    fn test_decode_invalid_update_type() {
+        let log = update_log(5, Bytes::default());
+        let err = SystemConfigUpdate::try_from_log(&log).unwrap_err();
+        assert_eq!(
+            err,
+            SystemConfigUpdateError::LogProcessing(
+                LogProcessingError::InvalidSystemConfigUpdateType(5)
+            )
+        );
+    }
+
+    #[test]
+    fn test_decode_invalid_version() {
+        let mut log = update_log(0, Bytes::default());
+        log.data.topics_mut()[1] = B256::with_last_byte(1);
+        let err = SystemConfigUpdate::try_from_log(&log).unwrap_err();
+        assert_eq!(
+            err,
+            SystemConfigUpdateError::LogProcessing(LogProcessingError::UnsupportedVersion(
+                B256::with_last_byte(1)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_decode_invalid_topic_len() {
+        let mut log = update_log(0, Bytes::default());
+        log.data = LogData::new_unchecked(vec![CONFIG_UPDATE_TOPIC], Bytes::default());
+        let err = SystemConfigUpdate::try_from_log(&log).unwrap_err();
+        assert_eq!(
+            err,
+            SystemConfigUpdateError::LogProcessing(LogProcessingError::InvalidTopicLen(1))
+        );
+    }
+
+    #[test]
+    fn test_update_system_config_from_receipts() {
+        let batcher = update_log(0, hex!("00000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000020000000000000000000000000000000000000000000000000000000000000beef").into());
+        let gas_limit = update_log(2, hex!("00000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000020000000000000000000000000000000000000000000000000000000000000babe").into());
+        let mut foreign = batcher.clone();
+        foreign.address = Address::ZERO;
+        let receipts = vec![
+            Receipt {
+                status: Eip658Value::Eip658(true),
+                logs: vec![batcher, foreign],
+                ..Default::default()
+            },
+            Receipt {
+                status: Eip658Value::Eip658(false),
+                logs: vec![gas_limit.clone()],
+                ..Default::default()
+            },
+            Receipt {
+                status: Eip658Value::Eip658(true),
+                logs: vec![gas_limit],
+                ..Default::default()
+            },
+        ];
+
+        let decoded = SystemConfigUpdate::from_receipts(&receipts, L1_SYS_CONFIG_ADDR).unwrap();
+        assert_eq!(decoded.len(), 2);
+
+        let mut config = SystemConfig::default();
+        let applied =
+            update_system_config(&mut config, &receipts, L1_SYS_CONFIG_ADDR, false).unwrap();
+        assert_eq!(applied, decoded);
+        assert_eq!(config.batcher_address, address!("000000000000000000000000000000000000bEEF"));
+        assert_eq!(config.gas_limit, 0xbabe);
+    }
+
+    #[test]
+    fn test_unpack_ecotone_scalar() {
+        let mut scalar = [0u8; 32];
+        scalar[24..28].copy_from_slice(&5u32.to_be_bytes());
+        scalar[28..32].copy_from_slice(&6u32.to_be_bytes());
+        assert_eq!(unpack_ecotone_scalar(scalar), (0, 6));
+        scalar[0] = ECOTONE_SCALAR_VERSION_1;
+        assert_eq!(unpack_ecotone_scalar(scalar), (5, 6));
+    }
+}
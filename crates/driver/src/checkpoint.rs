@@ -0,0 +1,17 @@
+//! Contains the checkpoints recorded by the driver while backfilling.
+
+use alloy_primitives::B256;
+
+/// An L2 block derived by [Driver::backfill], recorded so that a historical range can be diffed
+/// against a canonical node afterwards.
+///
+/// [Driver::backfill]: crate::Driver::backfill
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DriverCheckpoint {
+    /// The number of the derived L2 block.
+    pub number: u64,
+    /// The hash of the derived L2 block.
+    pub hash: B256,
+    /// The output root after the derived L2 block.
+    pub output_root: B256,
+}
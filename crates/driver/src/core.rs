@@ -15,7 +15,10 @@ use op_alloy_consensus::{OpBlock, OpTxEnvelope, OpTxType};
 use op_alloy_genesis::RollupConfig;
 use op_alloy_rpc_types_engine::OpAttributesWithParent;
 
-use crate::{DriverError, DriverPipeline, DriverResult, Executor, PipelineCursor, TipCursor};
+use crate::{
+    DriverCheckpoint, DriverError, DriverPipeline, DriverResult, Executor, PipelineCursor,
+    StallWatchdog, TipCursor,
+};
 
 /// The Rollup Driver entrypoint.
 #[derive(Debug)]
@@ -35,6 +38,8 @@ where
     pub cursor: PipelineCursor,
     /// The Executor.
     pub executor: E,
+    /// The optional watchdog detecting derivation stalls.
+    pub watchdog: Option<StallWatchdog>,
 }
 
 impl<E, DP, P> Driver<E, DP, P>
@@ -51,14 +56,54 @@ where
             pipeline,
             cursor,
             executor,
+            watchdog: None,
         }
     }
 
+    /// Sets the [StallWatchdog] that detects derivation stalls while producing payloads.
+    pub fn with_watchdog(mut self, watchdog: StallWatchdog) -> Self {
+        self.watchdog = Some(watchdog);
+        self
+    }
+
     /// Waits until the executor is ready.
     pub async fn wait_for_executor(&mut self) {
         self.executor.wait_until_ready().await;
     }
 
+    /// Derives and executes the historical range up to the `target` block number, calling
+    /// `on_checkpoint` with the derived block every `interval` blocks, and with the last one.
+    ///
+    /// This is [Driver::advance_to_target] in steps of `interval` blocks, so that a long backfill
+    /// can report and persist its progress, and the derived block hashes can be compared with a
+    /// canonical node afterwards. If the data source is exhausted before the target, the backfill
+    /// stops at the last derived block.
+    ///
+    /// ## Returns
+    /// - `Ok((number, block_hash, output_root))` - The last derived block.
+    /// - `Err(e)` - An error if a block could not be produced.
+    pub async fn backfill(
+        &mut self,
+        cfg: &RollupConfig,
+        target: u64,
+        interval: u64,
+        mut on_checkpoint: impl FnMut(DriverCheckpoint) + Send,
+    ) -> DriverResult<(u64, B256, B256), E::Error> {
+        let interval = interval.max(1);
+        loop {
+            let start = self.cursor.l2_safe_head().block_info.number;
+            let next = start.saturating_add(interval).min(target);
+            let (number, hash, output_root) = self.advance_to_target(cfg, Some(next)).await?;
+            if number > start {
+                on_checkpoint(DriverCheckpoint { number, hash, output_root });
+            }
+            if number >= target || number < next {
+                info!(target: "client", "Backfill complete at L2 block #{number}");
+                return Ok((number, hash, output_root));
+            }
+        }
+    }
+
     /// Advances the derivation pipeline to the target block number.
     ///
     /// ## Takes
@@ -89,7 +134,7 @@ where
 
             let OpAttributesWithParent { mut attributes, .. } = match self
                 .pipeline
-                .produce_payload(*self.cursor.l2_safe_head())
+                .produce_payload_watched(*self.cursor.l2_safe_head(), self.watchdog.as_mut())
                 .await
             {
                 Ok(attrs) => attrs,
@@ -181,3 +226,97 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_utils::{genesis_cursor, TestExecutor, TestPipeline, BLOCK_TIME},
+        WatchdogConfig,
+    };
+    use alloc::{sync::Arc, vec};
+    use core::{
+        sync::atomic::{AtomicU64, Ordering},
+        time::Duration,
+    };
+    use kona_derive::types::StepResult;
+
+    fn driver(
+        steps: impl IntoIterator<Item = StepResult>,
+    ) -> Driver<TestExecutor, TestPipeline, TestPipeline> {
+        Driver::new(genesis_cursor(), TestExecutor::default(), TestPipeline::new(steps))
+    }
+
+    fn prepared(count: usize) -> impl Iterator<Item = StepResult> {
+        (0..count).map(|_| StepResult::PreparedAttributes)
+    }
+
+    #[tokio::test]
+    async fn test_backfill_checkpoints() {
+        let mut driver = driver(prepared(5));
+        let mut checkpoints = Vec::new();
+
+        let (number, hash, output_root) = driver
+            .backfill(&RollupConfig::default(), 5, 2, |checkpoint| checkpoints.push(checkpoint))
+            .await
+            .unwrap();
+        assert_eq!(number, 5);
+        assert_eq!(hash, driver.cursor.l2_safe_head().block_info.hash);
+        assert_eq!(output_root, B256::with_last_byte(5));
+        assert_eq!(checkpoints.iter().map(|c| c.number).collect::<Vec<_>>(), vec![2, 4, 5]);
+        assert_eq!(checkpoints[2], DriverCheckpoint { number, hash, output_root });
+
+        // Each derived block builds on the previous one.
+        let head = driver.cursor.l2_safe_head();
+        assert_eq!(head.block_info.timestamp, 5 * BLOCK_TIME);
+        assert_eq!(head.seq_num, 5);
+    }
+
+    #[tokio::test]
+    async fn test_backfill_stops_at_end_of_source() {
+        let mut driver = driver(prepared(3));
+        let mut checkpoints = Vec::new();
+
+        let (number, ..) = driver
+            .backfill(&RollupConfig::default(), 10, 2, |checkpoint| checkpoints.push(checkpoint))
+            .await
+            .unwrap();
+        assert_eq!(number, 3);
+        assert_eq!(checkpoints.iter().map(|c| c.number).collect::<Vec<_>>(), vec![2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_backfill_without_progress() {
+        let mut driver = driver([]);
+        let mut checkpoints = Vec::new();
+
+        let (number, ..) = driver
+            .backfill(&RollupConfig::default(), 10, 0, |checkpoint| checkpoints.push(checkpoint))
+            .await
+            .unwrap();
+        assert_eq!(number, 0);
+        assert!(checkpoints.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_advance_reports_stalls() {
+        let config =
+            WatchdogConfig { stall_timeout: Duration::from_secs(1), reset_on_stall: false };
+        let secs = Arc::new(AtomicU64::new(0));
+        let watchdog = StallWatchdog::new(config, move || {
+            Duration::from_secs(secs.fetch_add(1, Ordering::Relaxed))
+        });
+        let steps = [
+            StepResult::StepFailed(PipelineError::NotEnoughData.temp()),
+            StepResult::PreparedAttributes,
+        ];
+        let mut driver = driver(steps).with_watchdog(watchdog);
+
+        // The stall is reported, but derivation carries on without a reset.
+        let (number, ..) =
+            driver.advance_to_target(&RollupConfig::default(), Some(1)).await.unwrap();
+        assert_eq!(number, 1);
+        assert_eq!(driver.watchdog.as_ref().map(StallWatchdog::stalls), Some(1));
+        assert!(driver.pipeline.signals.is_empty());
+    }
+}
@@ -0,0 +1,210 @@
+//! Contains the [LagMonitor], which tracks how far derivation is behind the L1 and L2 heads.
+
+use crate::PipelineCursor;
+use alloc::boxed::Box;
+use core::fmt;
+
+/// How far derivation is behind the chain heads, in blocks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DerivationLag {
+    /// The number of L1 blocks between the L1 head and the pipeline origin.
+    pub l1_origin_lag: u64,
+    /// The number of L2 blocks between the L2 unsafe head and the derived safe head, if the
+    /// unsafe head is known.
+    pub safe_head_lag: Option<u64>,
+}
+
+/// The lag thresholds above which the [LagMonitor] raises a [LagAlert].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LagThresholds {
+    /// The maximum L1 origin lag, in L1 blocks.
+    pub l1_origin_lag: Option<u64>,
+    /// The maximum safe head lag, in L2 blocks.
+    pub safe_head_lag: Option<u64>,
+}
+
+/// A lag that crossed its threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LagAlert {
+    /// The L1 origin lag exceeds its threshold.
+    L1OriginLag {
+        /// The current lag, in L1 blocks.
+        lag: u64,
+        /// The exceeded threshold.
+        threshold: u64,
+    },
+    /// The safe head lag exceeds its threshold.
+    SafeHeadLag {
+        /// The current lag, in L2 blocks.
+        lag: u64,
+        /// The exceeded threshold.
+        threshold: u64,
+    },
+}
+
+/// Tracks the L1 origin lag and the safe head lag of derivation as gauges.
+///
+/// The driver has no view of the chain heads, so the caller observes the lag with the L1 head
+/// and the L2 unsafe head, e.g. as reported by the RPC nodes. When a lag first exceeds its
+/// threshold, a warning is logged and the alert callback is called, once until the lag recovers.
+#[derive(Default)]
+pub struct LagMonitor {
+    /// The alert thresholds.
+    thresholds: LagThresholds,
+    /// Called when a lag exceeds its threshold.
+    on_alert: Option<Box<dyn FnMut(LagAlert) + Send + Sync>>,
+    /// The last observed lag.
+    lag: DerivationLag,
+    /// Whether the L1 origin lag exceeds its threshold.
+    l1_origin_alerting: bool,
+    /// Whether the safe head lag exceeds its threshold.
+    safe_head_alerting: bool,
+}
+
+impl LagMonitor {
+    /// Creates a new [LagMonitor] with the given thresholds.
+    pub fn new(thresholds: LagThresholds) -> Self {
+        Self { thresholds, ..Default::default() }
+    }
+
+    /// Sets the callback called when a lag exceeds its threshold.
+    pub fn with_alert(mut self, on_alert: impl FnMut(LagAlert) + Send + Sync + 'static) -> Self {
+        self.on_alert = Some(Box::new(on_alert));
+        self
+    }
+
+    /// Returns the last observed lag.
+    pub const fn lag(&self) -> DerivationLag {
+        self.lag
+    }
+
+    /// Observes the lag of the given cursor behind the L1 head and the L2 unsafe head, if known.
+    pub fn observe(
+        &mut self,
+        cursor: &PipelineCursor,
+        l1_head: u64,
+        l2_unsafe_head: Option<u64>,
+    ) -> DerivationLag {
+        self.lag = DerivationLag {
+            l1_origin_lag: l1_head.saturating_sub(cursor.origin().number),
+            safe_head_lag: l2_unsafe_head
+                .map(|head| head.saturating_sub(cursor.l2_safe_head().block_info.number)),
+        };
+
+        let l1_origin_alert = self.thresholds.l1_origin_lag.and_then(|threshold| {
+            let lag = self.lag.l1_origin_lag;
+            (lag > threshold).then_some(LagAlert::L1OriginLag { lag, threshold })
+        });
+        let safe_head_alert = self.thresholds.safe_head_lag.and_then(|threshold| {
+            let lag = self.lag.safe_head_lag?;
+            (lag > threshold).then_some(LagAlert::SafeHeadLag { lag, threshold })
+        });
+
+        self.l1_origin_alerting = self.alert(l1_origin_alert, self.l1_origin_alerting);
+        self.safe_head_alerting = self.alert(safe_head_alert, self.safe_head_alerting);
+        self.lag
+    }
+
+    /// Raises the alert if it is not already raised, returning whether it is raised.
+    fn alert(&mut self, alert: Option<LagAlert>, alerting: bool) -> bool {
+        let Some(alert) = alert else {
+            return false;
+        };
+        if !alerting {
+            warn!(target: "client", "Derivation is falling behind: {:?}", alert);
+            if let Some(on_alert) = self.on_alert.as_mut() {
+                on_alert(alert);
+            }
+        }
+        true
+    }
+}
+
+impl fmt::Debug for LagMonitor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LagMonitor")
+            .field("thresholds", &self.thresholds)
+            .field("lag", &self.lag)
+            .field("l1_origin_alerting", &self.l1_origin_alerting)
+            .field("safe_head_alerting", &self.safe_head_alerting)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::genesis_cursor;
+    use alloc::{sync::Arc, vec, vec::Vec};
+    use maili_protocol::BlockInfo;
+    use std::sync::Mutex;
+
+    const THRESHOLDS: LagThresholds =
+        LagThresholds { l1_origin_lag: Some(10), safe_head_lag: Some(100) };
+
+    fn monitor() -> (LagMonitor, Arc<Mutex<Vec<LagAlert>>>) {
+        let alerts = Arc::new(Mutex::new(Vec::new()));
+        let sink = alerts.clone();
+        let monitor =
+            LagMonitor::new(THRESHOLDS).with_alert(move |alert| sink.lock().unwrap().push(alert));
+        (monitor, alerts)
+    }
+
+    #[test]
+    fn test_lag_gauges() {
+        let cursor = genesis_cursor();
+        let mut monitor = LagMonitor::new(LagThresholds::default());
+
+        let lag = monitor.observe(&cursor, 5, Some(20));
+        assert_eq!(lag, DerivationLag { l1_origin_lag: 5, safe_head_lag: Some(20) });
+        assert_eq!(monitor.lag(), lag);
+
+        // The safe head lag is unknown without the unsafe head.
+        let lag = monitor.observe(&cursor, 0, None);
+        assert_eq!(lag, DerivationLag { l1_origin_lag: 0, safe_head_lag: None });
+    }
+
+    #[test]
+    fn test_lag_alerts_once_until_recovered() {
+        let cursor = genesis_cursor();
+        let (mut monitor, alerts) = monitor();
+
+        monitor.observe(&cursor, 10, Some(100));
+        assert!(alerts.lock().unwrap().is_empty());
+
+        monitor.observe(&cursor, 11, Some(101));
+        monitor.observe(&cursor, 12, Some(102));
+        assert_eq!(
+            *alerts.lock().unwrap(),
+            vec![
+                LagAlert::L1OriginLag { lag: 11, threshold: 10 },
+                LagAlert::SafeHeadLag { lag: 101, threshold: 100 },
+            ]
+        );
+
+        // Once the lag recovers, the alert is raised again when it is next exceeded.
+        monitor.observe(&cursor, 10, None);
+        monitor.observe(&cursor, 13, None);
+        assert_eq!(
+            alerts.lock().unwrap().last(),
+            Some(&LagAlert::L1OriginLag { lag: 13, threshold: 10 })
+        );
+        assert_eq!(alerts.lock().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_lag_follows_the_cursor() {
+        let mut cursor = genesis_cursor();
+        let (mut monitor, alerts) = monitor();
+
+        monitor.observe(&cursor, 20, None);
+        assert_eq!(alerts.lock().unwrap().len(), 1);
+
+        // The origin catches up with the L1 head.
+        let origin = BlockInfo { number: 15, ..Default::default() };
+        cursor.advance(origin, cursor.tip().clone());
+        assert_eq!(monitor.observe(&cursor, 20, None).l1_origin_lag, 5);
+        monitor.observe(&cursor, 30, None);
+        assert_eq!(alerts.lock().unwrap().len(), 2);
+    }
+}
@@ -29,3 +29,15 @@ pub use cursor::PipelineCursor;
 
 mod tip;
 pub use tip::TipCursor;
+
+mod checkpoint;
+pub use checkpoint::DriverCheckpoint;
+
+mod watchdog;
+pub use watchdog::{DerivationStalled, StallWatchdog, StalledStage, WatchdogConfig};
+
+mod lag;
+pub use lag::{DerivationLag, LagAlert, LagMonitor, LagThresholds};
+
+#[cfg(test)]
+mod test_utils;
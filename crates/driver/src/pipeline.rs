@@ -5,6 +5,7 @@ use async_trait::async_trait;
 use maili_protocol::L2BlockInfo;
 use op_alloy_rpc_types_engine::OpAttributesWithParent;
 
+use crate::StallWatchdog;
 use kona_derive::{
     errors::{PipelineError, PipelineErrorKind, ResetError},
     traits::{Pipeline, SignalReceiver},
@@ -27,12 +28,44 @@ where
     async fn produce_payload(
         &mut self,
         l2_safe_head: L2BlockInfo,
+    ) -> Result<OpAttributesWithParent, PipelineErrorKind> {
+        self.produce_payload_watched(l2_safe_head, None).await
+    }
+
+    /// Produces the next [OpAttributesWithParent] payload like [DriverPipeline::produce_payload],
+    /// reporting every step to the given [StallWatchdog].
+    ///
+    /// When the watchdog detects a stall, a [DerivationStalled] event is logged and, if configured
+    /// and the stalled step failed temporarily, the pipeline is reset to the L2 safe head. Other
+    /// step results are handled as usual, so critical errors and resets are never masked.
+    ///
+    /// [DerivationStalled]: crate::DerivationStalled
+    async fn produce_payload_watched(
+        &mut self,
+        l2_safe_head: L2BlockInfo,
+        mut watchdog: Option<&mut StallWatchdog>,
     ) -> Result<OpAttributesWithParent, PipelineErrorKind> {
         // As we start the safe head at the disputed block's parent, we step the pipeline until the
         // first attributes are produced. All batches at and before the safe head will be
         // dropped, so the first payload will always be the disputed one.
         loop {
-            match self.step(l2_safe_head).await {
+            let result = self.step(l2_safe_head).await;
+            let mut reset_on_stall = false;
+            if let Some(watchdog) = watchdog.as_deref_mut() {
+                if let Some(stall) = watchdog.observe(&result, self.origin()) {
+                    warn!(
+                        target: "client_derivation_driver",
+                        stage = ?stall.stage,
+                        stalled_for_ms = stall.stalled_for.as_millis() as u64,
+                        origin = ?stall.origin.map(|o| o.number),
+                        stalls = watchdog.stalls(),
+                        "DerivationStalled"
+                    );
+                    reset_on_stall = watchdog.config().reset_on_stall;
+                }
+            }
+
+            match result {
                 StepResult::PreparedAttributes => {
                     info!(target: "client_derivation_driver", "Stepped derivation pipeline")
                 }
@@ -44,17 +77,29 @@ where
                     // complete the current step. In this case, we retry the step to see if other
                     // stages can make progress.
                     match e {
+                        PipelineErrorKind::Temporary(PipelineError::Paused) => {
+                            // Hand control back to the consumer, which resumes the pipeline.
+                            info!(target: "client_derivation_driver", "Derivation pipeline is paused");
+                            return Err(e);
+                        }
                         PipelineErrorKind::Temporary(_) => {
                             trace!(target: "client_derivation_driver", "Failed to step derivation pipeline temporarily: {:?}", e);
-                            continue
+                            if reset_on_stall {
+                                // The pipeline has not made progress within the stall timeout, so
+                                // reset it to the L2 safe head and restart the timer.
+                                self.reset_to_safe_head(l2_safe_head).await?;
+                                if let Some(watchdog) = watchdog.as_deref_mut() {
+                                    watchdog.rearm();
+                                }
+                            }
+                            continue;
                         }
                         PipelineErrorKind::Reset(e) => {
                             warn!(target: "client_derivation_driver", "Failed to step derivation pipeline due to reset: {:?}", e);
-                            let system_config = self
-                                .system_config_by_number(l2_safe_head.block_info.number)
-                                .await?;
-
                             if matches!(e, ResetError::HoloceneActivation) {
+                                let system_config = self
+                                    .system_config_by_number(l2_safe_head.block_info.number)
+                                    .await?;
                                 let l1_origin =
                                     self.origin().ok_or(PipelineError::MissingOrigin.crit())?;
                                 self.signal(
@@ -74,22 +119,12 @@ where
 
                                 // Reset the pipeline to the initial L2 safe head and L1 origin,
                                 // and try again.
-                                let l1_origin =
-                                    self.origin().ok_or(PipelineError::MissingOrigin.crit())?;
-                                self.signal(
-                                    ResetSignal {
-                                        l2_safe_head,
-                                        l1_origin,
-                                        system_config: Some(system_config),
-                                    }
-                                    .signal(),
-                                )
-                                .await?;
+                                self.reset_to_safe_head(l2_safe_head).await?;
                             }
                         }
                         PipelineErrorKind::Critical(_) => {
                             warn!(target: "client_derivation_driver", "Failed to step derivation pipeline: {:?}", e);
-                            return Err(e)
+                            return Err(e);
                         }
                     }
                 }
@@ -100,4 +135,107 @@ where
             }
         }
     }
+
+    /// Resets the pipeline to the given L2 safe head and the current L1 origin.
+    async fn reset_to_safe_head(
+        &mut self,
+        l2_safe_head: L2BlockInfo,
+    ) -> Result<(), PipelineErrorKind> {
+        let system_config = self.system_config_by_number(l2_safe_head.block_info.number).await?;
+        let l1_origin = self.origin().ok_or(PipelineError::MissingOrigin.crit())?;
+        self.signal(
+            ResetSignal { l2_safe_head, l1_origin, system_config: Some(system_config) }.signal(),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_utils::TestPipeline, WatchdogConfig};
+    use alloc::sync::Arc;
+    use core::{
+        sync::atomic::{AtomicU64, Ordering},
+        time::Duration,
+    };
+    use kona_derive::types::Signal;
+
+    /// Returns a [StallWatchdog] whose clock advances by one second on every read.
+    fn ticking_watchdog(reset_on_stall: bool) -> StallWatchdog {
+        let config = WatchdogConfig { stall_timeout: Duration::from_secs(3), reset_on_stall };
+        let secs = Arc::new(AtomicU64::new(0));
+        StallWatchdog::new(config, move || {
+            Duration::from_secs(secs.fetch_add(1, Ordering::Relaxed))
+        })
+    }
+
+    #[tokio::test]
+    async fn test_produce_payload_resets_on_stall() {
+        let not_enough_data = || StepResult::StepFailed(PipelineError::NotEnoughData.temp());
+        let steps = (0..5).map(|_| not_enough_data()).chain([StepResult::PreparedAttributes]);
+        let mut pipeline = TestPipeline::new(steps);
+        let mut watchdog = ticking_watchdog(true);
+
+        let attributes = pipeline
+            .produce_payload_watched(L2BlockInfo::default(), Some(&mut watchdog))
+            .await
+            .unwrap();
+        assert_eq!(attributes.parent, L2BlockInfo::default());
+        assert_eq!(watchdog.stalls(), 1);
+        assert!(matches!(pipeline.signals[..], [Signal::Reset(_)]));
+    }
+
+    #[tokio::test]
+    async fn test_produce_payload_critical_error_on_stall() {
+        let not_enough_data = || StepResult::StepFailed(PipelineError::NotEnoughData.temp());
+        let steps = (0..2)
+            .map(|_| not_enough_data())
+            .chain([StepResult::StepFailed(PipelineError::EndOfSource.crit())]);
+        let mut pipeline = TestPipeline::new(steps);
+        let mut watchdog = ticking_watchdog(true);
+
+        // The stall timeout elapses on the critical error, which is returned without a reset.
+        let err = pipeline
+            .produce_payload_watched(L2BlockInfo::default(), Some(&mut watchdog))
+            .await
+            .unwrap_err();
+        assert_eq!(err, PipelineError::EndOfSource.crit());
+        assert_eq!(watchdog.stalls(), 1);
+        assert!(pipeline.signals.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_produce_payload_activation_on_stall() {
+        let not_enough_data = || StepResult::StepFailed(PipelineError::NotEnoughData.temp());
+        let steps = (0..2).map(|_| not_enough_data()).chain([
+            StepResult::StepFailed(ResetError::HoloceneActivation.reset()),
+            StepResult::PreparedAttributes,
+        ]);
+        let mut pipeline = TestPipeline::new(steps);
+        let mut watchdog = ticking_watchdog(true);
+
+        // The activation coincides with the stall, and is signalled instead of a reset.
+        pipeline
+            .produce_payload_watched(L2BlockInfo::default(), Some(&mut watchdog))
+            .await
+            .unwrap();
+        assert_eq!(watchdog.stalls(), 1);
+        assert!(matches!(pipeline.signals[..], [Signal::Activation(_)]));
+    }
+
+    #[tokio::test]
+    async fn test_produce_payload_waits_at_l1_tip() {
+        let at_tip = || StepResult::OriginAdvanceErr(PipelineError::Eof.temp());
+        let steps = (0..10).map(|_| at_tip()).chain([StepResult::PreparedAttributes]);
+        let mut pipeline = TestPipeline::new(steps);
+        let mut watchdog = ticking_watchdog(true);
+
+        pipeline
+            .produce_payload_watched(L2BlockInfo::default(), Some(&mut watchdog))
+            .await
+            .unwrap();
+        assert_eq!(watchdog.stalls(), 0);
+        assert!(pipeline.signals.is_empty());
+    }
 }
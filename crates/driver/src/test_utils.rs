@@ -0,0 +1,189 @@
+//! Test utilities for the driver.
+
+use crate::{DriverPipeline, Executor, PipelineCursor, TipCursor};
+use alloc::{boxed::Box, collections::VecDeque, vec, vec::Vec};
+use alloy_consensus::{Header, Sealable, Sealed};
+use alloy_eips::eip2718::Encodable2718;
+use alloy_primitives::B256;
+use async_trait::async_trait;
+use kona_derive::{
+    errors::{PipelineError, PipelineErrorKind},
+    traits::{OriginProvider, Pipeline, SignalReceiver},
+    types::{PipelineResult, Signal, StepResult},
+};
+use maili_protocol::{BlockInfo, L1BlockInfoTx, L2BlockInfo};
+use op_alloy_genesis::{RollupConfig, SystemConfig};
+use op_alloy_rpc_types_engine::{OpAttributesWithParent, OpPayloadAttributes};
+use thiserror::Error;
+
+/// The L2 block time of the test chain.
+pub(crate) const BLOCK_TIME: u64 = 2;
+
+/// Returns a [PipelineCursor] at the L2 genesis block, with the L1 genesis block as origin.
+pub(crate) fn genesis_cursor() -> PipelineCursor {
+    let header = Header::default().seal_slow();
+    let l2_genesis = L2BlockInfo {
+        block_info: BlockInfo { hash: header.hash(), ..Default::default() },
+        ..Default::default()
+    };
+    let mut cursor = PipelineCursor::new(10, BlockInfo::default());
+    cursor.advance(BlockInfo::default(), TipCursor::new(l2_genesis, header, B256::ZERO));
+    cursor
+}
+
+/// A [DriverPipeline] that replays the scripted step results.
+///
+/// [StepResult::PreparedAttributes] prepares the attributes of the block after the L2 safe head,
+/// and [StepResult::AdvancedOrigin] advances the origin by one block. Once the script is
+/// exhausted, the data source is exhausted as well.
+#[derive(Debug, Default)]
+pub(crate) struct TestPipeline {
+    /// The step results, in order.
+    pub(crate) steps: VecDeque<StepResult>,
+    /// The L1 origin.
+    pub(crate) origin: BlockInfo,
+    /// The prepared attributes.
+    pub(crate) prepared: Option<OpAttributesWithParent>,
+    /// The received signals.
+    pub(crate) signals: Vec<Signal>,
+    /// The rollup config.
+    pub(crate) cfg: RollupConfig,
+}
+
+impl TestPipeline {
+    /// Creates a new [TestPipeline] with the given step results.
+    pub(crate) fn new(steps: impl IntoIterator<Item = StepResult>) -> Self {
+        Self { steps: steps.into_iter().collect(), ..Default::default() }
+    }
+
+    /// Returns the attributes of the block after the given L2 safe head, starting with an L1
+    /// info deposit for the current origin.
+    fn attributes(&self, l2_safe_head: L2BlockInfo) -> OpAttributesWithParent {
+        let timestamp = l2_safe_head.block_info.timestamp + BLOCK_TIME;
+        let l1_header = Header { number: self.origin.number, ..Default::default() };
+        let (_, l1_info) = L1BlockInfoTx::try_new_with_deposit_tx(
+            &self.cfg,
+            &SystemConfig::default(),
+            l2_safe_head.seq_num + 1,
+            &l1_header,
+            timestamp,
+        )
+        .unwrap();
+        let mut attributes = OpPayloadAttributes {
+            transactions: Some(vec![l1_info.encoded_2718().into()]),
+            ..Default::default()
+        };
+        attributes.payload_attributes.timestamp = timestamp;
+        OpAttributesWithParent::new(attributes, l2_safe_head, false)
+    }
+}
+
+impl Iterator for TestPipeline {
+    type Item = OpAttributesWithParent;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.prepared.take()
+    }
+}
+
+impl OriginProvider for TestPipeline {
+    fn origin(&self) -> Option<BlockInfo> {
+        Some(self.origin)
+    }
+}
+
+#[async_trait]
+impl SignalReceiver for TestPipeline {
+    async fn signal(&mut self, signal: Signal) -> PipelineResult<()> {
+        self.signals.push(signal);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Pipeline for TestPipeline {
+    fn peek(&self) -> Option<&OpAttributesWithParent> {
+        self.prepared.as_ref()
+    }
+
+    async fn step(&mut self, cursor: L2BlockInfo) -> StepResult {
+        let result = self
+            .steps
+            .pop_front()
+            .unwrap_or(StepResult::StepFailed(PipelineError::EndOfSource.crit()));
+        match result {
+            StepResult::PreparedAttributes => self.prepared = Some(self.attributes(cursor)),
+            StepResult::AdvancedOrigin => self.origin.number += 1,
+            _ => {}
+        }
+        result
+    }
+
+    fn pause(&mut self) {}
+
+    fn resume(&mut self) {}
+
+    fn is_paused(&self) -> bool {
+        false
+    }
+
+    async fn reset(&mut self, _: L2BlockInfo, l1_origin: BlockInfo) -> PipelineResult<()> {
+        self.origin = l1_origin;
+        Ok(())
+    }
+
+    fn rollup_config(&self) -> &RollupConfig {
+        &self.cfg
+    }
+
+    async fn system_config_by_number(&mut self, _: u64) -> Result<SystemConfig, PipelineErrorKind> {
+        Ok(SystemConfig::default())
+    }
+}
+
+impl DriverPipeline<Self> for TestPipeline {
+    fn flush(&mut self) {}
+}
+
+/// An error returned by the [TestExecutor].
+#[derive(Error, Debug)]
+#[error("test executor error")]
+pub(crate) struct TestExecutorError;
+
+/// An [Executor] that builds the block on top of the L2 safe head without executing it.
+#[derive(Debug, Default)]
+pub(crate) struct TestExecutor {
+    /// The header of the L2 safe head.
+    safe_head: Option<Sealed<Header>>,
+    /// The number of the last built block.
+    number: u64,
+}
+
+#[async_trait]
+impl Executor for TestExecutor {
+    type Error = TestExecutorError;
+
+    async fn wait_until_ready(&mut self) {}
+
+    fn update_safe_head(&mut self, header: Sealed<Header>) {
+        self.safe_head = Some(header);
+    }
+
+    async fn execute_payload(
+        &mut self,
+        attributes: OpPayloadAttributes,
+    ) -> Result<Header, Self::Error> {
+        let parent = self.safe_head.as_ref().ok_or(TestExecutorError)?;
+        self.number = parent.number + 1;
+        Ok(Header {
+            number: self.number,
+            parent_hash: parent.hash(),
+            timestamp: attributes.payload_attributes.timestamp,
+            ..Default::default()
+        })
+    }
+
+    fn compute_output_root(&mut self) -> Result<B256, Self::Error> {
+        Ok(B256::left_padding_from(&self.number.to_be_bytes()))
+    }
+}
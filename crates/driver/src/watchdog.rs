@@ -0,0 +1,271 @@
+//! Contains the [StallWatchdog], which detects when the derivation pipeline stops making progress.
+
+use alloc::boxed::Box;
+use core::{fmt, time::Duration};
+use kona_derive::{
+    errors::{PipelineError, PipelineErrorKind},
+    types::StepResult,
+};
+use maili_protocol::BlockInfo;
+
+/// The pipeline stage suspected of stalling derivation, inferred from the last step result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StalledStage {
+    /// The next L1 block could not be fetched.
+    L1Traversal,
+    /// The data of the current L1 block could not be fetched.
+    L1Retrieval,
+    /// No channel could be assembled or read from the retrieved frames.
+    Channel,
+    /// No batch could be derived from the read channels.
+    Batch,
+    /// No attributes could be built from the derived batches.
+    Attributes,
+    /// The stage could not be inferred from the step result.
+    Unknown,
+}
+
+impl StalledStage {
+    /// Infers the stalled stage from a failed step.
+    pub const fn from_step(result: &StepResult) -> Self {
+        match result {
+            StepResult::PreparedAttributes | StepResult::AdvancedOrigin => Self::Unknown,
+            StepResult::OriginAdvanceErr(_) => Self::L1Traversal,
+            StepResult::StepFailed(PipelineErrorKind::Temporary(e)) => match e {
+                PipelineError::Provider(_) | PipelineError::Eof => Self::L1Retrieval,
+                PipelineError::NotEnoughData |
+                PipelineError::ChannelProviderEmpty |
+                PipelineError::ChannelNotFound |
+                PipelineError::ChannelReaderEmpty => Self::Channel,
+                PipelineError::BatchQueueEmpty => Self::Batch,
+                PipelineError::AttributesBuilder(_) => Self::Attributes,
+                _ => Self::Unknown,
+            },
+            StepResult::StepFailed(_) => Self::Unknown,
+        }
+    }
+}
+
+/// Emitted by the [StallWatchdog] when no attributes were prepared and the origin did not advance
+/// for the stall timeout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DerivationStalled {
+    /// The time since the last progress of the pipeline.
+    pub stalled_for: Duration,
+    /// The stage suspected of stalling derivation.
+    pub stage: StalledStage,
+    /// The L1 origin of the pipeline, if any.
+    pub origin: Option<BlockInfo>,
+}
+
+/// The configuration of a [StallWatchdog].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatchdogConfig {
+    /// The duration without progress after which derivation is considered stalled.
+    pub stall_timeout: Duration,
+    /// Whether to reset the pipeline to the L2 safe head when derivation stalls.
+    pub reset_on_stall: bool,
+}
+
+/// Detects when the derivation pipeline stops making progress.
+///
+/// The driver reports every pipeline step to the watchdog. When no attributes were prepared and
+/// the origin did not advance for [WatchdogConfig::stall_timeout], a [DerivationStalled] event is
+/// emitted, and the timer re-arms so that a persisting stall is reported once per timeout.
+///
+/// A pipeline whose origin cannot advance with a temporary [PipelineError::Eof] has reached the
+/// L1 tip, and is waiting for the next L1 block rather than stalled. Such steps restart the timer.
+///
+/// The watchdog reads time from the given clock, as the driver has no clock of its own, e.g. in
+/// the fault proof program.
+pub struct StallWatchdog {
+    /// The watchdog configuration.
+    config: WatchdogConfig,
+    /// Returns the current monotonic time.
+    clock: Box<dyn Fn() -> Duration + Send + Sync>,
+    /// The time of the last progress, or of the last emitted stall.
+    last_progress: Duration,
+    /// The number of emitted stalls.
+    stalls: u64,
+}
+
+impl StallWatchdog {
+    /// Creates a new [StallWatchdog] with the given configuration and monotonic clock.
+    pub fn new(
+        config: WatchdogConfig,
+        clock: impl Fn() -> Duration + Send + Sync + 'static,
+    ) -> Self {
+        let last_progress = clock();
+        Self { config, clock: Box::new(clock), last_progress, stalls: 0 }
+    }
+
+    /// Returns the watchdog configuration.
+    pub const fn config(&self) -> &WatchdogConfig {
+        &self.config
+    }
+
+    /// Returns the number of stalls emitted so far.
+    pub const fn stalls(&self) -> u64 {
+        self.stalls
+    }
+
+    /// Restarts the timer, e.g. after the pipeline was reset.
+    pub fn rearm(&mut self) {
+        self.last_progress = (self.clock)();
+    }
+
+    /// Observes the result of a pipeline step, returning a [DerivationStalled] event if the
+    /// pipeline has not made progress for the stall timeout.
+    pub fn observe(
+        &mut self,
+        result: &StepResult,
+        origin: Option<BlockInfo>,
+    ) -> Option<DerivationStalled> {
+        let now = (self.clock)();
+        if matches!(
+            result,
+            StepResult::PreparedAttributes |
+                StepResult::AdvancedOrigin |
+                StepResult::OriginAdvanceErr(PipelineErrorKind::Temporary(PipelineError::Eof))
+        ) {
+            self.last_progress = now;
+            return None;
+        }
+
+        let stalled_for = now.saturating_sub(self.last_progress);
+        if stalled_for < self.config.stall_timeout {
+            return None;
+        }
+
+        self.last_progress = now;
+        self.stalls += 1;
+        Some(DerivationStalled { stalled_for, stage: StalledStage::from_step(result), origin })
+    }
+}
+
+impl fmt::Debug for StallWatchdog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StallWatchdog")
+            .field("config", &self.config)
+            .field("last_progress", &self.last_progress)
+            .field("stalls", &self.stalls)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::sync::Arc;
+    use core::sync::atomic::{AtomicU64, Ordering};
+
+    const CONFIG: WatchdogConfig =
+        WatchdogConfig { stall_timeout: Duration::from_secs(10), reset_on_stall: false };
+
+    /// A fake monotonic clock, in seconds.
+    #[derive(Debug, Default, Clone)]
+    struct FakeClock(Arc<AtomicU64>);
+
+    impl FakeClock {
+        fn advance(&self, secs: u64) {
+            self.0.fetch_add(secs, Ordering::Relaxed);
+        }
+
+        fn watchdog(&self) -> StallWatchdog {
+            let clock = self.clone();
+            StallWatchdog::new(CONFIG, move || Duration::from_secs(clock.0.load(Ordering::Relaxed)))
+        }
+    }
+
+    fn not_enough_data() -> StepResult {
+        StepResult::StepFailed(PipelineError::NotEnoughData.temp())
+    }
+
+    #[test]
+    fn test_stalled_stage_from_step() {
+        let stage = |result| StalledStage::from_step(&result);
+        assert_eq!(stage(StepResult::AdvancedOrigin), StalledStage::Unknown);
+        assert_eq!(
+            stage(StepResult::OriginAdvanceErr(PipelineError::Eof.temp())),
+            StalledStage::L1Traversal
+        );
+        assert_eq!(
+            stage(StepResult::StepFailed(PipelineError::Eof.temp())),
+            StalledStage::L1Retrieval
+        );
+        assert_eq!(stage(not_enough_data()), StalledStage::Channel);
+        assert_eq!(
+            stage(StepResult::StepFailed(PipelineError::BatchQueueEmpty.temp())),
+            StalledStage::Batch
+        );
+        assert_eq!(
+            stage(StepResult::StepFailed(PipelineError::EndOfSource.crit())),
+            StalledStage::Unknown
+        );
+    }
+
+    #[test]
+    fn test_watchdog_emits_stall_once_per_timeout() {
+        let clock = FakeClock::default();
+        let mut watchdog = clock.watchdog();
+        let origin = Some(BlockInfo { number: 7, ..Default::default() });
+
+        clock.advance(9);
+        assert_eq!(watchdog.observe(&not_enough_data(), origin), None);
+        clock.advance(1);
+        assert_eq!(
+            watchdog.observe(&not_enough_data(), origin),
+            Some(DerivationStalled {
+                stalled_for: Duration::from_secs(10),
+                stage: StalledStage::Channel,
+                origin,
+            })
+        );
+        assert_eq!(watchdog.stalls(), 1);
+
+        // The timer re-armed, so the persisting stall is reported again after another timeout.
+        clock.advance(5);
+        assert_eq!(watchdog.observe(&not_enough_data(), origin), None);
+        clock.advance(5);
+        assert!(watchdog.observe(&not_enough_data(), origin).is_some());
+        assert_eq!(watchdog.stalls(), 2);
+    }
+
+    #[test]
+    fn test_watchdog_progress_restarts_timer() {
+        let clock = FakeClock::default();
+        let mut watchdog = clock.watchdog();
+
+        for progress in [StepResult::PreparedAttributes, StepResult::AdvancedOrigin] {
+            clock.advance(9);
+            assert_eq!(watchdog.observe(&progress, None), None);
+            clock.advance(9);
+            assert_eq!(watchdog.observe(&not_enough_data(), None), None);
+        }
+
+        clock.advance(30);
+        watchdog.rearm();
+        assert_eq!(watchdog.observe(&not_enough_data(), None), None);
+        assert_eq!(watchdog.stalls(), 0);
+    }
+
+    #[test]
+    fn test_watchdog_ignores_eof_at_l1_tip() {
+        let clock = FakeClock::default();
+        let mut watchdog = clock.watchdog();
+        let at_tip = StepResult::OriginAdvanceErr(PipelineError::Eof.temp());
+
+        // Waiting for the next L1 block is not a stall, however long it takes.
+        clock.advance(60);
+        assert_eq!(watchdog.observe(&at_tip, None), None);
+        clock.advance(60);
+        assert_eq!(watchdog.observe(&at_tip, None), None);
+        assert_eq!(watchdog.stalls(), 0);
+
+        // Other origin advance errors are still stalls.
+        clock.advance(10);
+        let stall = watchdog
+            .observe(&StepResult::OriginAdvanceErr(PipelineError::MissingOrigin.crit()), None);
+        assert_eq!(stall.map(|s| s.stage), Some(StalledStage::L1Traversal));
+    }
+}
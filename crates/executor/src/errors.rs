@@ -39,7 +39,7 @@ pub enum ExecutorError {
     ExecutionError(EVMError<TrieDBError>),
     /// Signature error.
     #[error("Signature error: {0}")]
-    SignatureError(alloy_primitives::SignatureError),
+    SignatureError(#[from] SignerRecoveryError),
     /// RLP error.
     #[error("RLP error: {0}")]
     RLPError(alloy_eips::eip2718::Eip2718Error),
@@ -48,6 +48,19 @@ pub enum ExecutorError {
     MissingExecutor,
 }
 
+/// An error returned by a [SignerRecovery] backend.
+///
+/// [SignerRecovery]: crate::SignerRecovery
+#[derive(Error, Debug)]
+pub enum SignerRecoveryError {
+    /// The signature is invalid.
+    #[error("Invalid signature: {0}")]
+    Signature(#[from] alloy_primitives::SignatureError),
+    /// The backend failed to recover the signer.
+    #[error("Failed to recover the signer: {0}")]
+    Recovery(String),
+}
+
 /// A [Result] type for the [ExecutorError] enum.
 pub type ExecutorResult<T> = Result<T, ExecutorError>;
 
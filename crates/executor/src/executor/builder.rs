@@ -1,7 +1,11 @@
 //! Contains the builder pattern for the [StatelessL2BlockExecutor].
 
 use super::StatelessL2BlockExecutor;
-use crate::db::{TrieDB, TrieDBProvider};
+use crate::{
+    db::{TrieDB, TrieDBProvider},
+    K256SignerRecovery, SignerRecovery,
+};
+use alloc::sync::Arc;
 use alloy_consensus::{Header, Sealable, Sealed};
 use kona_mpt::TrieHinter;
 use op_alloy_genesis::RollupConfig;
@@ -28,6 +32,8 @@ where
     parent_header: Option<Sealed<Header>>,
     /// The [KonaHandleRegister] to use during execution.
     handler_register: Option<KonaHandleRegister<F, H>>,
+    /// The [SignerRecovery] backend to recover transaction senders with.
+    signer_recovery: Option<Arc<dyn SignerRecovery>>,
 }
 
 impl<'a, F, H> StatelessL2BlockExecutorBuilder<'a, F, H>
@@ -37,7 +43,14 @@ where
 {
     /// Instantiate a new builder with the given [RollupConfig].
     pub fn new(config: &'a RollupConfig, provider: F, hinter: H) -> Self {
-        Self { config, provider, hinter, parent_header: None, handler_register: None }
+        Self {
+            config,
+            provider,
+            hinter,
+            parent_header: None,
+            handler_register: None,
+            signer_recovery: None,
+        }
     }
 
     /// Set the [Header] to begin execution from.
@@ -52,6 +65,12 @@ where
         self
     }
 
+    /// Set the [SignerRecovery] backend for execution. Defaults to [K256SignerRecovery].
+    pub fn with_signer_recovery(mut self, signer_recovery: Arc<dyn SignerRecovery>) -> Self {
+        self.signer_recovery = Some(signer_recovery);
+        self
+    }
+
     /// Build the [StatelessL2BlockExecutor] from the builder configuration.
     pub fn build(self) -> StatelessL2BlockExecutor<'a, F, H> {
         let parent_header = self.parent_header.unwrap_or_else(|| {
@@ -65,6 +84,7 @@ where
             config: self.config,
             trie_db,
             handler_register: self.handler_register,
+            signer_recovery: self.signer_recovery.unwrap_or_else(|| Arc::new(K256SignerRecovery)),
         }
     }
 }
@@ -1,7 +1,9 @@
 //! Environment preparation for the executor.
 
 use super::{util::decode_holocene_eip_1559_params, StatelessL2BlockExecutor};
-use crate::{constants::FEE_RECIPIENT, ExecutorError, ExecutorResult, TrieDBProvider};
+use crate::{
+    constants::FEE_RECIPIENT, ExecutorError, ExecutorResult, SignerRecovery, TrieDBProvider,
+};
 use alloy_consensus::Header;
 use alloy_eips::{eip1559::BaseFeeParams, eip7840::BlobParams};
 use alloy_primitives::{TxKind, U256};
@@ -127,7 +129,8 @@ where
     ///
     /// ## Takes
     /// - `transaction`: The transaction to prepare the environment for.
-    /// - `env`: The transaction environment to prepare.
+    /// - `encoded_transaction`: The EIP-2718 encoding of the transaction.
+    /// - `signer_recovery`: The [SignerRecovery] backend to recover the sender with.
     ///
     /// ## Returns
     /// - `Ok(())` if the environment was successfully prepared.
@@ -135,12 +138,14 @@ where
     pub(crate) fn prepare_tx_env(
         transaction: &OpTxEnvelope,
         encoded_transaction: &[u8],
+        signer_recovery: &dyn SignerRecovery,
     ) -> ExecutorResult<TxEnv> {
         let mut env = TxEnv::default();
         match transaction {
             OpTxEnvelope::Legacy(signed_tx) => {
                 let tx = signed_tx.tx();
-                env.caller = signed_tx.recover_signer().map_err(ExecutorError::SignatureError)?;
+                env.caller = signer_recovery
+                    .recover_signer(signed_tx.signature(), signed_tx.signature_hash())?;
                 env.gas_limit = tx.gas_limit;
                 env.gas_price = U256::from(tx.gas_price);
                 env.gas_priority_fee = None;
@@ -165,7 +170,8 @@ where
             }
             OpTxEnvelope::Eip2930(signed_tx) => {
                 let tx = signed_tx.tx();
-                env.caller = signed_tx.recover_signer().map_err(ExecutorError::SignatureError)?;
+                env.caller = signer_recovery
+                    .recover_signer(signed_tx.signature(), signed_tx.signature_hash())?;
                 env.gas_limit = tx.gas_limit;
                 env.gas_price = U256::from(tx.gas_price);
                 env.gas_priority_fee = None;
@@ -190,7 +196,8 @@ where
             }
             OpTxEnvelope::Eip1559(signed_tx) => {
                 let tx = signed_tx.tx();
-                env.caller = signed_tx.recover_signer().map_err(ExecutorError::SignatureError)?;
+                env.caller = signer_recovery
+                    .recover_signer(signed_tx.signature(), signed_tx.signature_hash())?;
                 env.gas_limit = tx.gas_limit;
                 env.gas_price = U256::from(tx.max_fee_per_gas);
                 env.gas_priority_fee = Some(U256::from(tx.max_priority_fee_per_gas));
@@ -5,10 +5,10 @@ use crate::{
     db::TrieDB,
     errors::TrieDBError,
     syscalls::{ensure_create2_deployer_canyon, pre_block_beacon_root_contract_call},
-    ExecutorError, ExecutorResult, TrieDBProvider,
+    ExecutorError, ExecutorResult, SignerRecovery, TrieDBProvider,
 };
-use alloc::vec::Vec;
-use alloy_consensus::{Header, Sealable, Transaction, EMPTY_OMMER_ROOT_HASH, EMPTY_ROOT_HASH};
+use alloc::{sync::Arc, vec::Vec};
+use alloy_consensus::{Header, Sealable, Transaction, EMPTY_OMMER_ROOT_HASH};
 use alloy_eips::eip2718::{Decodable2718, Encodable2718};
 use alloy_primitives::{keccak256, logs_bloom, Bytes, Log, B256, U256};
 use kona_mpt::{ordered_trie_with_encoder, TrieHinter};
@@ -27,7 +27,7 @@ pub use builder::{KonaHandleRegister, StatelessL2BlockExecutorBuilder};
 mod env;
 
 mod util;
-use util::encode_holocene_eip_1559_params;
+use util::{compute_withdrawals_root, encode_holocene_eip_1559_params};
 
 /// The block executor for the L2 client program. Operates off of a [TrieDB] backed [State],
 /// allowing for stateless block execution of OP Stack blocks.
@@ -43,6 +43,8 @@ where
     trie_db: TrieDB<F, H>,
     /// The [KonaHandleRegister] to use during execution.
     handler_register: Option<KonaHandleRegister<F, H>>,
+    /// The [SignerRecovery] backend to recover transaction senders with.
+    signer_recovery: Arc<dyn SignerRecovery>,
 }
 
 impl<'a, F, H> StatelessL2BlockExecutor<'a, F, H>
@@ -181,7 +183,11 @@ where
             // Modify the transaction environment with the current transaction.
             evm = evm
                 .modify()
-                .with_tx_env(Self::prepare_tx_env(&transaction, raw_transaction)?)
+                .with_tx_env(Self::prepare_tx_env(
+                    &transaction,
+                    raw_transaction,
+                    self.signer_recovery.as_ref(),
+                )?)
                 .build();
 
             // If the transaction is a deposit, cache the depositor account.
@@ -272,17 +278,12 @@ where
         );
 
         // The withdrawals root on OP Stack chains, after Canyon activation, is always the empty
-        // root hash.
-        let mut withdrawals_root = self
-            .config
-            .is_canyon_active(payload.payload_attributes.timestamp)
-            .then_some(EMPTY_ROOT_HASH);
-
-        // If the Isthmus hardfork is active, the withdrawals root is the L2 to L1 message passer
-        // account.
-        if self.config.is_isthmus_active(payload.payload_attributes.timestamp) {
-            withdrawals_root = Some(Self::message_passer_account(state.database)?);
-        }
+        // root hash. If the Isthmus hardfork is active, the withdrawals root is the storage root
+        // of the L2 to L1 message passer account.
+        let withdrawals_root =
+            compute_withdrawals_root(self.config, payload.payload_attributes.timestamp, || {
+                Self::message_passer_account(state.database)
+            })?;
 
         // Compute logs bloom filter for the block.
         let logs_bloom = logs_bloom(receipts.iter().flat_map(|receipt| receipt.logs()));
@@ -361,6 +362,12 @@ where
         Ok(state.database.parent_block_header())
     }
 
+    /// Returns the withdrawal storage root committed to by the output root, i.e. the storage root
+    /// of the L2 to L1 message passer account in the state of the parent block.
+    pub fn withdrawals_storage_root(&mut self) -> ExecutorResult<B256> {
+        Ok(Self::message_passer_account(&mut self.trie_db)?)
+    }
+
     /// Computes the current output root of the executor, based on the parent header and the
     /// state's underlying trie.
     ///
@@ -374,7 +381,7 @@ where
     /// - `Ok(output_root)`: The computed output root.
     /// - `Err(_)`: If an error occurred while computing the output root.
     pub fn compute_output_root(&mut self) -> ExecutorResult<B256> {
-        let storage_root = Self::message_passer_account(&mut self.trie_db)?;
+        let storage_root = self.withdrawals_storage_root()?;
         let parent_header = self.trie_db.parent_block_header();
 
         info!(
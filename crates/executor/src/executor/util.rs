@@ -2,9 +2,9 @@
 
 use crate::{constants::HOLOCENE_EXTRA_DATA_VERSION, ExecutorError, ExecutorResult};
 use alloc::vec::Vec;
-use alloy_consensus::Header;
+use alloy_consensus::{Header, EMPTY_ROOT_HASH};
 use alloy_eips::eip1559::BaseFeeParams;
-use alloy_primitives::{Bytes, B64};
+use alloy_primitives::{Bytes, B256, B64};
 use op_alloy_genesis::RollupConfig;
 use op_alloy_rpc_types_engine::OpPayloadAttributes;
 
@@ -70,6 +70,30 @@ pub(crate) fn encode_holocene_eip_1559_params(
     Ok(data.into())
 }
 
+/// Computes the withdrawals root of the [Header] of an L2 block.
+///
+/// ## Takes
+/// - `config`: The [RollupConfig] for the chain.
+/// - `timestamp`: The timestamp of the block.
+/// - `storage_root`: Fetches the storage root of the L2 to L1 message passer, only called once
+///   Isthmus is active.
+///
+/// ## Returns
+/// - `Ok(None)`: Before Canyon.
+/// - `Ok(Some(EMPTY_ROOT_HASH))`: After Canyon, since OP Stack blocks carry no withdrawals.
+/// - `Ok(Some(storage_root))`: After Isthmus.
+/// - `Err(_)`: If the storage root could not be fetched.
+pub(crate) fn compute_withdrawals_root<E>(
+    config: &RollupConfig,
+    timestamp: u64,
+    storage_root: impl FnOnce() -> Result<B256, E>,
+) -> Result<Option<B256>, E> {
+    if config.is_isthmus_active(timestamp) {
+        return storage_root().map(Some);
+    }
+    Ok(config.is_canyon_active(timestamp).then_some(EMPTY_ROOT_HASH))
+}
+
 /// Encodes the canyon base fee parameters, per Holocene spec.
 ///
 /// <https://specs.optimism.io/protocol/holocene/exec-engine.html#eip1559params-encoding>
@@ -85,10 +109,16 @@ pub(crate) fn encode_canyon_base_fee_params(config: &RollupConfig) -> B64 {
 #[cfg(test)]
 mod test {
     use super::decode_holocene_eip_1559_params;
-    use crate::executor::util::{encode_canyon_base_fee_params, encode_holocene_eip_1559_params};
-    use alloy_consensus::Header;
+    use crate::{
+        executor::util::{
+            compute_withdrawals_root, encode_canyon_base_fee_params,
+            encode_holocene_eip_1559_params,
+        },
+        ExecutorError,
+    };
+    use alloy_consensus::{Header, EMPTY_ROOT_HASH};
     use alloy_eips::eip1559::BaseFeeParams;
-    use alloy_primitives::{b64, hex, B64};
+    use alloy_primitives::{b64, hex, B256, B64};
     use alloy_rpc_types_engine::PayloadAttributes;
     use op_alloy_genesis::RollupConfig;
     use op_alloy_rpc_types_engine::OpPayloadAttributes;
@@ -199,4 +229,35 @@ mod test {
         };
         assert_eq!(encode_canyon_base_fee_params(&cfg), b64!("0000002000000040"));
     }
+
+    #[test]
+    fn test_compute_withdrawals_root_fork_boundaries() {
+        let cfg =
+            RollupConfig { canyon_time: Some(10), isthmus_time: Some(20), ..Default::default() };
+        let storage_root = B256::with_last_byte(1);
+        let root = |timestamp| {
+            compute_withdrawals_root(&cfg, timestamp, || Ok::<_, ExecutorError>(storage_root))
+                .unwrap()
+        };
+
+        assert_eq!(root(9), None);
+        assert_eq!(root(10), Some(EMPTY_ROOT_HASH));
+        assert_eq!(root(19), Some(EMPTY_ROOT_HASH));
+        assert_eq!(root(20), Some(storage_root));
+    }
+
+    #[test]
+    fn test_compute_withdrawals_root_storage_root_error() {
+        let cfg = RollupConfig { isthmus_time: Some(0), ..Default::default() };
+        assert!(matches!(
+            compute_withdrawals_root(&cfg, 0, || Err(ExecutorError::MissingEIP1559Params)),
+            Err(ExecutorError::MissingEIP1559Params)
+        ));
+        // The storage root is not fetched before Isthmus.
+        let cfg = RollupConfig { canyon_time: Some(0), ..Default::default() };
+        assert!(matches!(
+            compute_withdrawals_root(&cfg, 0, || Err(ExecutorError::MissingEIP1559Params)),
+            Ok(Some(EMPTY_ROOT_HASH))
+        ));
+    }
 }
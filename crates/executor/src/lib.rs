@@ -13,7 +13,7 @@ extern crate alloc;
 extern crate tracing;
 
 mod errors;
-pub use errors::{ExecutorError, ExecutorResult, TrieDBError, TrieDBResult};
+pub use errors::{ExecutorError, ExecutorResult, SignerRecoveryError, TrieDBError, TrieDBResult};
 
 mod executor;
 pub use executor::{KonaHandleRegister, StatelessL2BlockExecutor, StatelessL2BlockExecutorBuilder};
@@ -21,5 +21,10 @@ pub use executor::{KonaHandleRegister, StatelessL2BlockExecutor, StatelessL2Bloc
 mod db;
 pub use db::{NoopTrieDBProvider, TrieAccount, TrieDB, TrieDBProvider};
 
+mod recovery;
+#[cfg(feature = "secp256k1")]
+pub use recovery::Secp256k1SignerRecovery;
+pub use recovery::{K256SignerRecovery, SignerRecovery};
+
 mod constants;
 mod syscalls;
@@ -0,0 +1,147 @@
+//! Contains the [SignerRecovery] trait and its backends, which recover the senders of the
+//! transactions executed by the [StatelessL2BlockExecutor].
+//!
+//! [StatelessL2BlockExecutor]: crate::StatelessL2BlockExecutor
+
+use crate::errors::SignerRecoveryError;
+use alloy_primitives::{Address, PrimitiveSignature, B256};
+use core::fmt::Debug;
+
+/// Recovers the address that signed a transaction.
+///
+/// The backend is chosen by the environment the executor runs in, e.g. a native backend on the
+/// host, or a backend that avoids ECDSA entirely inside of a fault proof VM.
+pub trait SignerRecovery: Debug + Send + Sync {
+    /// Recovers the address that produced the `signature` over the `signature_hash` of a
+    /// transaction.
+    fn recover_signer(
+        &self,
+        signature: &PrimitiveSignature,
+        signature_hash: B256,
+    ) -> Result<Address, SignerRecoveryError>;
+}
+
+/// The default [SignerRecovery] backend, which recovers signers with the pure Rust `k256` crate.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct K256SignerRecovery;
+
+impl SignerRecovery for K256SignerRecovery {
+    fn recover_signer(
+        &self,
+        signature: &PrimitiveSignature,
+        signature_hash: B256,
+    ) -> Result<Address, SignerRecoveryError> {
+        Ok(signature.recover_address_from_prehash(&signature_hash)?)
+    }
+}
+
+/// A [SignerRecovery] backend that recovers signers with the native `libsecp256k1` library,
+/// which is faster than [K256SignerRecovery] on native targets.
+#[cfg(feature = "secp256k1")]
+#[derive(Debug, Clone)]
+pub struct Secp256k1SignerRecovery {
+    /// The verification context.
+    secp: secp256k1::Secp256k1<secp256k1::VerifyOnly>,
+}
+
+#[cfg(feature = "secp256k1")]
+impl Default for Secp256k1SignerRecovery {
+    fn default() -> Self {
+        Self { secp: secp256k1::Secp256k1::verification_only() }
+    }
+}
+
+#[cfg(feature = "secp256k1")]
+impl SignerRecovery for Secp256k1SignerRecovery {
+    fn recover_signer(
+        &self,
+        signature: &PrimitiveSignature,
+        signature_hash: B256,
+    ) -> Result<Address, SignerRecoveryError> {
+        use alloc::string::ToString;
+        use secp256k1::{
+            ecdsa::{RecoverableSignature, RecoveryId},
+            Message,
+        };
+
+        let recovery = |e: secp256k1::Error| SignerRecoveryError::Recovery(e.to_string());
+        let mut compact = [0u8; 64];
+        compact[..32].copy_from_slice(&signature.r().to_be_bytes::<32>());
+        compact[32..].copy_from_slice(&signature.s().to_be_bytes::<32>());
+        let recovery_id = RecoveryId::from_i32(signature.v() as i32).map_err(recovery)?;
+        let signature =
+            RecoverableSignature::from_compact(&compact, recovery_id).map_err(recovery)?;
+
+        let public_key = self
+            .secp
+            .recover_ecdsa(&Message::from_digest(signature_hash.0), &signature)
+            .map_err(recovery)?;
+        Ok(Address::from_raw_public_key(&public_key.serialize_uncompressed()[1..]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_consensus::{SignableTransaction, TxLegacy};
+    use alloy_primitives::{address, b256, Bytes, TxKind, U256};
+    use core::str::FromStr;
+
+    /// Returns the signature and signature hash of the example transaction of EIP-155.
+    fn eip155_example() -> (PrimitiveSignature, B256) {
+        let tx = TxLegacy {
+            chain_id: Some(1),
+            nonce: 9,
+            gas_price: 20_000_000_000,
+            gas_limit: 21_000,
+            to: TxKind::Call(address!("3535353535353535353535353535353535353535")),
+            value: U256::from(10u128.pow(18)),
+            input: Bytes::new(),
+        };
+        let signature = PrimitiveSignature::new(
+            U256::from_str(
+                "18515461264373351373200002665853028612451056578545711640558177340181847433846",
+            )
+            .unwrap(),
+            U256::from_str(
+                "46948507304638947509940763649030358759909902576025900602547168820602576006531",
+            )
+            .unwrap(),
+            false,
+        );
+        let signature_hash = tx.signature_hash();
+        assert_eq!(
+            signature_hash,
+            b256!("daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53")
+        );
+        (signature, signature_hash)
+    }
+
+    #[test]
+    fn test_k256_recover_signer() {
+        let (signature, signature_hash) = eip155_example();
+        assert_eq!(
+            K256SignerRecovery.recover_signer(&signature, signature_hash).unwrap(),
+            address!("9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F")
+        );
+    }
+
+    #[test]
+    fn test_k256_recover_signer_invalid() {
+        let signature = PrimitiveSignature::new(U256::ZERO, U256::ZERO, false);
+        assert!(matches!(
+            K256SignerRecovery.recover_signer(&signature, B256::ZERO),
+            Err(SignerRecoveryError::Signature(_))
+        ));
+    }
+
+    #[cfg(feature = "secp256k1")]
+    #[test]
+    fn test_secp256k1_recover_signer() {
+        let (signature, signature_hash) = eip155_example();
+        assert_eq!(
+            Secp256k1SignerRecovery::default().recover_signer(&signature, signature_hash).unwrap(),
+            address!("9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F")
+        );
+    }
+}
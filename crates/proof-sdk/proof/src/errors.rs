@@ -13,6 +13,9 @@ pub enum OracleProviderError {
     /// Requested block number is past the chain head.
     #[error("Block number ({0}) past chain head ({_1})")]
     BlockNumberPastHead(u64, u64),
+    /// Requested L2 block number predates the Bedrock genesis block of the rollup config.
+    #[error("L2 block number ({0}) predates the Bedrock genesis block ({_1})")]
+    PreBedrock(u64, u64),
     /// Preimage oracle error.
     #[error("Preimage oracle error: {0}")]
     Preimage(PreimageOracleError),
@@ -5,7 +5,7 @@ use alloy_consensus::{Header, Sealed};
 use alloy_primitives::B256;
 use async_trait::async_trait;
 use kona_driver::Executor;
-use kona_executor::{KonaHandleRegister, StatelessL2BlockExecutor, TrieDBProvider};
+use kona_executor::{KonaHandleRegister, SignerRecovery, StatelessL2BlockExecutor, TrieDBProvider};
 use kona_mpt::TrieHinter;
 use op_alloy_genesis::RollupConfig;
 use op_alloy_rpc_types_engine::OpPayloadAttributes;
@@ -25,6 +25,8 @@ where
     trie_hinter: H,
     /// The handle register for the executor.
     handle_register: Option<KonaHandleRegister<P, H>>,
+    /// The signer recovery backend for the executor.
+    signer_recovery: Option<Arc<dyn SignerRecovery>>,
     /// The executor.
     inner: Option<StatelessL2BlockExecutor<'a, P, H>>,
 }
@@ -42,7 +44,20 @@ where
         handle_register: Option<KonaHandleRegister<P, H>>,
         inner: Option<StatelessL2BlockExecutor<'a, P, H>>,
     ) -> Self {
-        Self { rollup_config, trie_provider, trie_hinter, handle_register, inner }
+        Self {
+            rollup_config,
+            trie_provider,
+            trie_hinter,
+            handle_register,
+            signer_recovery: None,
+            inner,
+        }
+    }
+
+    /// Sets the [SignerRecovery] backend that transaction senders are recovered with.
+    pub fn with_signer_recovery(mut self, signer_recovery: Arc<dyn SignerRecovery>) -> Self {
+        self.signer_recovery = Some(signer_recovery);
+        self
     }
 }
 
@@ -75,6 +90,9 @@ where
         if let Some(register) = self.handle_register {
             builder = builder.with_handle_register(register);
         }
+        if let Some(signer_recovery) = &self.signer_recovery {
+            builder = builder.with_signer_recovery(signer_recovery.clone());
+        }
         self.inner = Some(builder.build());
     }
 
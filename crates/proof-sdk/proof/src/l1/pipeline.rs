@@ -160,6 +160,30 @@ where
         self.pipeline.step(cursor).await
     }
 
+    /// Pauses the pipeline.
+    fn pause(&mut self) {
+        self.pipeline.pause()
+    }
+
+    /// Resumes the pipeline.
+    fn resume(&mut self) {
+        self.pipeline.resume()
+    }
+
+    /// Returns `true` if the pipeline is paused.
+    fn is_paused(&self) -> bool {
+        self.pipeline.is_paused()
+    }
+
+    /// Resets the pipeline to the given L2 safe head and L1 origin.
+    async fn reset(
+        &mut self,
+        l2_safe_head: L2BlockInfo,
+        l1_origin: BlockInfo,
+    ) -> PipelineResult<()> {
+        self.pipeline.reset(l2_safe_head, l1_origin).await
+    }
+
     /// Returns the rollup config.
     fn rollup_config(&self) -> &RollupConfig {
         self.pipeline.rollup_config()
@@ -44,6 +44,13 @@ impl<T: CommsClient> OracleL2ChainProvider<T> {
             return Err(OracleProviderError::BlockNumberPastHead(block_number, header.number));
         }
 
+        // Legacy blocks before the Bedrock genesis carry no L1 info deposit, and cannot be
+        // converted into L2 block infos or system configs.
+        let genesis = self.rollup_config.genesis.l2.number;
+        if block_number < genesis {
+            return Err(OracleProviderError::PreBedrock(block_number, genesis));
+        }
+
         // Walk back the block headers to the desired block number.
         while header.number > block_number {
             header = self.header_by_hash(header.parent_hash)?;
@@ -51,25 +58,10 @@ impl<T: CommsClient> OracleL2ChainProvider<T> {
 
         Ok(header)
     }
-}
-
-#[async_trait]
-impl<T: CommsClient + Send + Sync> BatchValidationProvider for OracleL2ChainProvider<T> {
-    type Error = OracleProviderError;
-
-    async fn l2_block_info_by_number(&mut self, number: u64) -> Result<L2BlockInfo, Self::Error> {
-        // Get the block at the given number.
-        let block = self.block_by_number(number).await?;
-
-        // Construct the system config from the payload.
-        L2BlockInfo::from_block_and_genesis(&block, &self.rollup_config.genesis)
-            .map_err(OracleProviderError::BlockInfo)
-    }
 
-    async fn block_by_number(&mut self, number: u64) -> Result<OpBlock, Self::Error> {
-        // Fetch the header for the given block number.
-        let header @ Header { transactions_root, timestamp, .. } =
-            self.header_by_number(number).await?;
+    /// Returns the [OpBlock] with the given [Header], fetching its transactions from the oracle.
+    async fn block_by_header(&mut self, header: Header) -> Result<OpBlock, OracleProviderError> {
+        let Header { transactions_root, timestamp, .. } = header;
         let header_hash = header.hash_slow();
 
         // Fetch the transactions in the block.
@@ -105,6 +97,26 @@ impl<T: CommsClient + Send + Sync> BatchValidationProvider for OracleL2ChainProv
     }
 }
 
+#[async_trait]
+impl<T: CommsClient + Send + Sync> BatchValidationProvider for OracleL2ChainProvider<T> {
+    type Error = OracleProviderError;
+
+    async fn l2_block_info_by_number(&mut self, number: u64) -> Result<L2BlockInfo, Self::Error> {
+        // Get the block at the given number.
+        let block = self.block_by_number(number).await?;
+
+        // Construct the system config from the payload.
+        L2BlockInfo::from_block_and_genesis(&block, &self.rollup_config.genesis)
+            .map_err(OracleProviderError::BlockInfo)
+    }
+
+    async fn block_by_number(&mut self, number: u64) -> Result<OpBlock, Self::Error> {
+        // Fetch the header for the given block number.
+        let header = self.header_by_number(number).await?;
+        self.block_by_header(header).await
+    }
+}
+
 #[async_trait]
 impl<T: CommsClient + Send + Sync> L2ChainProvider for OracleL2ChainProvider<T> {
     type Error = OracleProviderError;
@@ -121,6 +133,36 @@ impl<T: CommsClient + Send + Sync> L2ChainProvider for OracleL2ChainProvider<T>
         to_system_config(&block, rollup_config.as_ref())
             .map_err(OracleProviderError::OpBlockConversion)
     }
+
+    async fn l2_block_infos_by_range(
+        &mut self,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<L2BlockInfo>, OracleProviderError> {
+        if start > end {
+            return Ok(Vec::new());
+        }
+
+        // Walk back from the L2 safe head once, rather than once per block.
+        let mut header = self.header_by_number(end).await?;
+        let mut headers = Vec::with_capacity((end - start + 1) as usize);
+        while header.number > start {
+            let parent_hash = header.parent_hash;
+            headers.push(header);
+            header = self.header_by_hash(parent_hash)?;
+        }
+        headers.push(header);
+
+        let mut infos = Vec::with_capacity(headers.len());
+        for header in headers.into_iter().rev() {
+            let block = self.block_by_header(header).await?;
+            infos.push(
+                L2BlockInfo::from_block_and_genesis(&block, &self.rollup_config.genesis)
+                    .map_err(OracleProviderError::BlockInfo)?,
+            );
+        }
+        Ok(infos)
+    }
 }
 
 impl<T: CommsClient> TrieProvider for OracleL2ChainProvider<T> {
@@ -20,6 +20,9 @@ pub mod errors;
 
 pub mod executor;
 
+mod recovery;
+pub use recovery::OracleSignerRecovery;
+
 mod hint;
 pub use hint::{Hint, HintType};
 
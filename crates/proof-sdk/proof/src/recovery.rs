@@ -0,0 +1,79 @@
+//! Contains the [OracleSignerRecovery], a [SignerRecovery] backend for the fault proof program.
+
+use crate::{block_on, errors::OracleProviderError, HintType};
+use alloc::{string::ToString, sync::Arc};
+use alloy_primitives::{keccak256, Address, PrimitiveSignature, B256, U256};
+use kona_executor::{SignerRecovery, SignerRecoveryError};
+use kona_preimage::{errors::PreimageOracleError, CommsClient, PreimageKey, PreimageKeyType};
+
+/// The address of the `ecrecover` precompile.
+const ECRECOVER_ADDRESS: Address = Address::with_last_byte(1);
+
+/// A [SignerRecovery] backend that has the host recover transaction senders, avoiding the cost of
+/// ECDSA within the fault proof VM.
+///
+/// The senders are fetched as `ecrecover` precompile results, which the host computes when
+/// hinted with [HintType::L1Precompile]. Like all precompile preimages, they are verified by the
+/// on-chain preimage oracle, so a dishonest host cannot substitute the sender of a transaction.
+#[derive(Debug, Clone)]
+pub struct OracleSignerRecovery<T: CommsClient> {
+    /// The preimage oracle client.
+    oracle: Arc<T>,
+}
+
+impl<T: CommsClient> OracleSignerRecovery<T> {
+    /// Creates a new [OracleSignerRecovery] with the given oracle client.
+    pub const fn new(oracle: Arc<T>) -> Self {
+        Self { oracle }
+    }
+
+    /// Fetches the result of the `ecrecover` precompile for the given input from the host.
+    async fn ecrecover(&self, input: &[u8]) -> Result<B256, OracleProviderError> {
+        let hint_data = &[ECRECOVER_ADDRESS.as_ref(), input];
+        self.oracle
+            .write(&HintType::L1Precompile.encode_with(hint_data))
+            .await
+            .map_err(OracleProviderError::Preimage)?;
+
+        let key_hash = keccak256([ECRECOVER_ADDRESS.as_slice(), input].concat());
+        let result = self
+            .oracle
+            .get(PreimageKey::new(*key_hash, PreimageKeyType::Precompile))
+            .await
+            .map_err(OracleProviderError::Preimage)?;
+
+        // The first byte reports whether the precompile succeeded, and `ecrecover` returns the
+        // left-padded address, or no data if the signature is invalid.
+        match result.split_first() {
+            Some((1, output)) if output.len() == 32 => Ok(B256::from_slice(output)),
+            Some((1, _)) => Err(OracleProviderError::Preimage(PreimageOracleError::Other(
+                "Invalid signature".to_string(),
+            ))),
+            _ => Err(OracleProviderError::Preimage(PreimageOracleError::Other(
+                "Error executing ecrecover precompile in host".to_string(),
+            ))),
+        }
+    }
+}
+
+impl<T: CommsClient + Send + Sync + core::fmt::Debug> SignerRecovery for OracleSignerRecovery<T> {
+    fn recover_signer(
+        &self,
+        signature: &PrimitiveSignature,
+        signature_hash: B256,
+    ) -> Result<Address, SignerRecoveryError> {
+        // The `ecrecover` input is the hash, followed by `v`, `r` and `s` as 32 byte words.
+        let v = U256::from(27 + signature.v() as u8);
+        let input = [
+            signature_hash.as_slice(),
+            &v.to_be_bytes::<32>(),
+            &signature.r().to_be_bytes::<32>(),
+            &signature.s().to_be_bytes::<32>(),
+        ]
+        .concat();
+
+        let word = block_on(self.ecrecover(&input))
+            .map_err(|e| SignerRecoveryError::Recovery(e.to_string()))?;
+        Ok(Address::from_word(word))
+    }
+}
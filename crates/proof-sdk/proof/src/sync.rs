@@ -13,7 +13,20 @@ use kona_preimage::CommsClient;
 use maili_protocol::BatchValidationProvider;
 use maili_registry::RollupConfig;
 
+/// Maps an L2 block number that predates the Bedrock genesis block of the rollup config to the
+/// genesis (migration) block, the earliest block that derivation can start from.
+pub const fn bedrock_start_block(rollup_config: &RollupConfig, number: u64) -> u64 {
+    if number < rollup_config.genesis.l2.number {
+        rollup_config.genesis.l2.number
+    } else {
+        number
+    }
+}
+
 /// Constructs a [`PipelineCursor`] from the caching oracle, boot info, and providers.
+///
+/// Returns an [OracleProviderError::PreBedrock] error if the safe head predates the Bedrock
+/// genesis block, see [bedrock_start_block].
 pub async fn new_pipeline_cursor<O>(
     rollup_config: &RollupConfig,
     safe_header: Sealed<Header>,
@@ -23,6 +36,11 @@ pub async fn new_pipeline_cursor<O>(
 where
     O: CommsClient + FlushableCache + FlushableCache + Send + Sync + Debug,
 {
+    let genesis = rollup_config.genesis.l2.number;
+    if safe_header.number < genesis {
+        return Err(OracleProviderError::PreBedrock(safe_header.number, genesis));
+    }
+
     let safe_head_info = l2_chain_provider.l2_block_info_by_number(safe_header.number).await?;
     let l1_origin = chain_provider.block_info_by_number(safe_head_info.l1_origin.number).await?;
 